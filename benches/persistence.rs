@@ -0,0 +1,114 @@
+// benches/persistence.rs
+// Criterion benchmarks of the persistence hot paths on synthetic databases of 10k, 100k and 1M raw
+// intervals: inserting an interval (every app switch), `query_stats` at every level (`stats`) and
+// `aggregate_and_cleanup` (startup and the hourly job). Run with `cargo bench --features bench`;
+// criterion compares each run with the previous one, so schema or SQL changes show up as regressions.
+
+use clap::ValueEnum;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use mouse_tracking::clock::ManualClock;
+use mouse_tracking::persistence::fixtures::DatabaseBuilder;
+use mouse_tracking::persistence::{aggregate_and_cleanup, insert_new_interval, query_stats, DEFAULT_HOURLY_SUMMARY_DAYS};
+use mouse_tracking::timezone::ReportTimeZone;
+use mouse_tracking::types::{AggregationLevel, NewInterval, StatsFilter, TimePeriod};
+use rusqlite::Connection;
+use std::hint::black_box;
+use std::path::Path;
+
+/// Raw intervals in each benchmarked database
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+/// Fixed "now" (2025-10-09 08:53 UTC), so every run sees the same periods
+const NOW: i64 = 1_760_000_000;
+/// The intervals fill the 12 weeks before the current hour (`--period last-12-weeks`)
+const SPAN_SECS: i64 = 84 * 86_400;
+
+fn timezone() -> ReportTimeZone {
+    ReportTimeZone::parse("UTC").expect("UTC is a valid time zone")
+}
+
+fn build_database(rows: usize) -> Connection {
+    let end = NOW - NOW % 3600;
+    DatabaseBuilder::new().synthetic_intervals(rows, end - SPAN_SECS, end).build().expect("synthetic database")
+}
+
+fn insert_interval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert_new_interval");
+    for rows in SIZES {
+        let conn = build_database(rows);
+        let mut start_time = NOW;
+        group.bench_function(BenchmarkId::from_parameter(rows), |b| {
+            b.iter(|| {
+                start_time += 1;
+                let interval = NewInterval {
+                    app_name: "bench.exe",
+                    main_title: "Benchmark",
+                    detailed_title: "Benchmark - bench.exe",
+                    virtual_desktop: None,
+                    repo: None,
+                    executable_path: None,
+                    command_line: None,
+                    ticket: None,
+                    session_type: None,
+                    remote_host: None,
+                    document: None,
+                    passive: false,
+                    start_time,
+                    device_id: "bench",
+                    user_name: "bench",
+                };
+                black_box(insert_new_interval(&conn, &interval).expect("insert"))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn stats(c: &mut Criterion) {
+    let (timezone, clock, filter) = (timezone(), ManualClock::new(NOW), StatsFilter::default());
+    let mut group = c.benchmark_group("query_stats");
+    for rows in SIZES {
+        let conn = build_database(rows);
+        for level in AggregationLevel::value_variants() {
+            let name = level.to_possible_value().expect("levels have CLI names").get_name().to_string();
+            group.bench_function(BenchmarkId::new(name, rows), |b| {
+                b.iter(|| black_box(query_stats(&conn, TimePeriod::Last12Weeks, *level, &filter, &timezone, &clock).expect("stats")))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Each iteration aggregates a fresh copy of a database file holding `rows` raw intervals.
+fn aggregate(c: &mut Criterion) {
+    let (timezone, clock) = (timezone(), ManualClock::new(NOW));
+    let dir = std::env::temp_dir().join(format!("mouse_tracking-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("bench directory");
+    let mut group = c.benchmark_group("aggregate_and_cleanup");
+    for rows in SIZES {
+        let template = dir.join(format!("{}.sqlite", rows));
+        let work = dir.join("work.sqlite");
+        build_database(rows).execute("VACUUM INTO ?1", [template.to_string_lossy()]).expect("template database");
+        group.bench_function(BenchmarkId::from_parameter(rows), |b| {
+            b.iter_batched(
+                || copy_database(&template, &work),
+                |mut conn| black_box(aggregate_and_cleanup(&mut conn, None, &timezone, &clock, false, DEFAULT_HOURLY_SUMMARY_DAYS).expect("aggregate")),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+fn copy_database(template: &Path, work: &Path) -> Connection {
+    std::fs::copy(template, work).expect("copy template database");
+    Connection::open(work).expect("open database copy")
+}
+
+criterion_group! {
+    name = benches;
+    // The 1M row cases take seconds per iteration
+    config = Criterion::default().sample_size(10);
+    targets = insert_interval, stats, aggregate
+}
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+// build.rs
+// Generates the gRPC service code from proto/tracker.proto when the `grpc` feature is on.
+
+fn main() {
+    println!("cargo:rerun-if-changed=proto/tracker.proto");
+    #[cfg(feature = "grpc")]
+    {
+        let mut config = prost_build::Config::new();
+        // Vendored protoc, so building does not need protobuf installed
+        config.protoc_executable(protoc_bin_vendored::protoc_bin_path().expect("vendored protoc unavailable"));
+        tonic_build::configure()
+            .compile_protos_with_config(config, &["proto/tracker.proto"], &["proto"])
+            .expect("failed to compile proto/tracker.proto");
+    }
+}
@@ -0,0 +1,7 @@
+-- Copies the raw intervals delete_aggregated.sql is about to remove (archive_raw_intervals).
+-- ?1 = aggregate_until, ?2 = now
+INSERT OR IGNORE INTO app_intervals_archive
+    (id, app_name, main_window_title, detailed_window_title, start_time, end_time, virtual_desktop, passive, device_id, repo, executable_path, command_line, ticket, user_name, session_type, remote_host, document, archived_at)
+SELECT id, app_name, main_window_title, detailed_window_title, start_time, end_time, virtual_desktop, passive, device_id, repo, executable_path, command_line, ticket, user_name, session_type, remote_host, document, ?2
+FROM app_intervals
+WHERE end_time IS NOT NULL AND end_time <= ?1;
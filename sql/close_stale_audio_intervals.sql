@@ -0,0 +1,5 @@
+-- Ends open audio intervals not seen since ?3 (the tracker crashed, slept or stopped polling)
+-- at the last poll that saw them playing.
+UPDATE audio_intervals
+SET end_time = heartbeat_time
+WHERE end_time IS NULL AND device_id = ?1 AND user_name = ?2 AND heartbeat_time < ?3;
@@ -0,0 +1 @@
+SELECT COUNT(*) FROM weeks_summary_by_app WHERE app_name = ?1;
@@ -0,0 +1,2 @@
+-- Audio intervals already rolled into daily_audio_summary (`AUDIO_SUMMARY` in src/persistence/dimensions.rs)
+DELETE FROM audio_intervals WHERE end_time IS NOT NULL AND end_time <= ?1;
@@ -0,0 +1,2 @@
+-- Events starting in [?1, ?2), replaced by what the calendar holds for that window now.
+DELETE FROM calendar_events WHERE start_time >= ?1 AND start_time < ?2;
@@ -1,3 +1,4 @@
 UPDATE app_intervals
-SET end_time = start_time
-WHERE end_time IS NULL AND start_time < ?1
\ No newline at end of file
+SET end_time = COALESCE(heartbeat_time, start_time)
+WHERE end_time IS NULL AND start_time < ?1
+  AND COALESCE(user_name, ?2) = ?2 -- Only the intervals of this user's tracker
@@ -0,0 +1,3 @@
+UPDATE app_intervals
+SET heartbeat_time = MAX(?1, start_time)
+WHERE id = ?2 AND end_time IS NULL
@@ -0,0 +1 @@
+UPDATE tracker_runs SET last_seen = MAX(last_seen, ?2) WHERE id = ?1;
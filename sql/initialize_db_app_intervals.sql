@@ -4,5 +4,17 @@ CREATE TABLE IF NOT EXISTS app_intervals (
     main_window_title TEXT,
     detailed_window_title TEXT,
     start_time INTEGER NOT NULL,
-    end_time INTEGER
+    end_time INTEGER,
+    heartbeat_time INTEGER, -- Last time the tracker confirmed this interval was still active
+    virtual_desktop TEXT, -- Virtual desktop of the window (NULL if unknown/unsupported)
+    passive INTEGER NOT NULL DEFAULT 0, -- 1 = full-screen media/meeting time
+    device_id TEXT, -- Computer the interval was recorded on (NULL for data from older versions)
+    repo TEXT, -- Git repository the activity was in (NULL if unknown or repo_detection is off)
+    executable_path TEXT, -- Full path of the app's executable (NULL unless record_executable_path is on)
+    command_line TEXT, -- Command line of the app's process (NULL unless record_command_line is on)
+    ticket TEXT, -- Issue key found in the window title, e.g. PROJ-123 (NULL unless ticket_detection is on)
+    user_name TEXT, -- OS user the tracker ran as (`user_name` in config.json; NULL for data from older versions)
+    session_type TEXT, -- Remote desktop involved: "rdp"/"citrix" session, or a client such as "rdp-client" (NULL = local)
+    remote_host TEXT, -- Machine a remote desktop client window was connected to, from its title (NULL if none)
+    document TEXT -- File open in an editor or office app, from its title (NULL unless document_detection is on)
 );
\ No newline at end of file
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS app_intervals_archive (
+    id INTEGER PRIMARY KEY, -- Id the interval had in app_intervals
+    app_name TEXT NOT NULL,
+    main_window_title TEXT,
+    detailed_window_title TEXT,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    virtual_desktop TEXT,
+    passive INTEGER NOT NULL DEFAULT 0,
+    device_id TEXT,
+    repo TEXT,
+    executable_path TEXT,
+    command_line TEXT,
+    ticket TEXT,
+    user_name TEXT,
+    session_type TEXT,
+    remote_host TEXT,
+    document TEXT,
+    archived_at INTEGER NOT NULL -- When aggregation moved the interval here
+);
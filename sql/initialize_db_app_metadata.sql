@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS app_metadata (
+    app_name TEXT PRIMARY KEY, -- Executable name as recorded in app_intervals (e.g. msedge.exe)
+    display_name TEXT, -- Friendly name shown in stats and exports
+    icon_path TEXT, -- File holding the app's icon (the executable itself when read from Windows)
+    source TEXT NOT NULL, -- 'user' (app alias set) or 'version_info' (read from the executable)
+    updated_at INTEGER NOT NULL
+);
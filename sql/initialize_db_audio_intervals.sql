@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS audio_intervals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    app_name TEXT NOT NULL, -- App whose audio session was playing
+    start_time INTEGER NOT NULL,
+    end_time INTEGER, -- NULL while it is still playing
+    heartbeat_time INTEGER NOT NULL, -- Last poll that saw it playing; a crashed tracker's rows end here
+    device_id TEXT NOT NULL,
+    user_name TEXT NOT NULL
+);
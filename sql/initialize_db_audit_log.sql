@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    changed_at INTEGER NOT NULL, -- When the edit was made
+    action TEXT NOT NULL, -- 'delete', 'split', 'add', 'clock_adjustment'
+    interval_id INTEGER NOT NULL, -- app_intervals.id the edit applied to (0 for clock adjustments)
+    details TEXT -- JSON: the interval before the edit plus the action's parameters
+);
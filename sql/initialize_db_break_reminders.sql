@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS break_reminders (
+    reminded_at INTEGER PRIMARY KEY, -- When the break reminder was shown
+    active_secs INTEGER NOT NULL, -- Continuous activity at that moment
+    break_start INTEGER -- Start of the break that followed within grace_minutes (NULL = not taken)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS calendar_events (
+    start_time INTEGER NOT NULL, -- Start of the event (one row per occurrence of a recurring event)
+    uid TEXT NOT NULL, -- UID of the event in the calendar
+    end_time INTEGER NOT NULL,
+    title TEXT NOT NULL, -- SUMMARY of the event; time overlapping it is counted under this meeting
+    PRIMARY KEY (start_time, uid)
+);
@@ -2,5 +2,6 @@ CREATE TABLE IF NOT EXISTS days_summary_by_app (
     app_name TEXT NOT NULL,
     day_timestamp INTEGER NOT NULL, -- Start of the day timestamp
     total_duration_secs INTEGER NOT NULL DEFAULT 0,
+    passive_duration_secs INTEGER NOT NULL DEFAULT 0, -- Part of total_duration_secs tagged passive
     PRIMARY KEY (app_name, day_timestamp)
 );
\ No newline at end of file
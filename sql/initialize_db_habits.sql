@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS habits (
+    name TEXT PRIMARY KEY, -- Chosen with `habit add`, e.g. "coding"
+    app_glob TEXT NOT NULL, -- Case-insensitive glob matched against app_name
+    daily_minutes INTEGER NOT NULL, -- A day counts toward the streak once matching apps reach this
+    created_at INTEGER NOT NULL
+);
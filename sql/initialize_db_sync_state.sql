@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS sync_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1), -- Single row
+    last_interval_id INTEGER NOT NULL DEFAULT 0, -- Watermark: highest app_intervals.id already pushed
+    last_sync_time INTEGER, -- When the last successful push finished
+    last_sync_count INTEGER NOT NULL DEFAULT 0 -- Intervals uploaded by that push
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS tracker_daily_stats (
+    day_timestamp INTEGER PRIMARY KEY, -- Start of the local day
+    interval_count INTEGER NOT NULL DEFAULT 0, -- Raw intervals aggregated for the day
+    longest_session_secs INTEGER NOT NULL DEFAULT 0, -- Longest run of intervals without a break (starting that day)
+    detection_errors INTEGER NOT NULL DEFAULT 0, -- Failed detector polls
+    last_error TEXT -- Message of the most recent detection error
+);
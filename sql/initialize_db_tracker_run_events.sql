@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS tracker_run_events (
+    run_id INTEGER NOT NULL, -- tracker_runs.id
+    event_time INTEGER NOT NULL,
+    kind TEXT NOT NULL -- 'suspend', 'clock_jump', 'pause', 'resume', 'off_hours_start', 'off_hours_end'
+);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS tracker_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_time INTEGER NOT NULL, -- When `track` started recording
+    last_seen INTEGER NOT NULL, -- Last heartbeat; a run that crashed ends here
+    end_time INTEGER, -- NULL while running, and after a crash or power loss
+    device_id TEXT NOT NULL,
+    user_name TEXT NOT NULL
+);
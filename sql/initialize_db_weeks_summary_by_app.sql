@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS weeks_summary_by_app (
+    app_name TEXT NOT NULL,
+    week_timestamp INTEGER NOT NULL, -- Start of the week (local midnight of its first day, see `week_start`)
+    total_duration_secs INTEGER NOT NULL DEFAULT 0,
+    passive_duration_secs INTEGER NOT NULL DEFAULT 0, -- Part of total_duration_secs tagged passive
+    PRIMARY KEY (app_name, week_timestamp)
+);
\ No newline at end of file
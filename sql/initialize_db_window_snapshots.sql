@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS window_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    snapshot_time INTEGER NOT NULL, -- All rows of one snapshot share this timestamp
+    app_name TEXT NOT NULL,
+    window_title TEXT
+);
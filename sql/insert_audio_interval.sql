@@ -0,0 +1,2 @@
+INSERT INTO audio_intervals (app_name, start_time, end_time, heartbeat_time, device_id, user_name)
+VALUES (?1, ?2, NULL, ?2, ?3, ?4);
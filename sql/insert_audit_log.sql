@@ -0,0 +1 @@
+INSERT INTO audit_log (changed_at, action, interval_id, details) VALUES (?1, ?2, ?3, ?4);
@@ -0,0 +1 @@
+INSERT OR IGNORE INTO break_reminders (reminded_at, active_secs) VALUES (?1, ?2);
@@ -0,0 +1,3 @@
+-- ?1 start, ?2 uid, ?3 end, ?4 title
+INSERT OR REPLACE INTO calendar_events (start_time, uid, end_time, title)
+VALUES (?1, ?2, ?3, ?4);
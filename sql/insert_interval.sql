@@ -3,5 +3,16 @@ INSERT INTO app_intervals (
     main_window_title,
     detailed_window_title,
     start_time,
-    end_time
-) VALUES (?1, ?2, ?3, ?4, NULL);
\ No newline at end of file
+    end_time,
+    virtual_desktop,
+    passive,
+    device_id,
+    repo,
+    executable_path,
+    command_line,
+    ticket,
+    user_name,
+    session_type,
+    remote_host,
+    document
+) VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15);
@@ -0,0 +1,2 @@
+INSERT INTO tracker_runs (start_time, last_seen, end_time, device_id, user_name)
+VALUES (?1, ?1, NULL, ?2, ?3);
@@ -0,0 +1,4 @@
+-- Compaction: the first interval of a merged run (?1) now ends at the end of the run (?2)
+UPDATE app_intervals
+SET end_time = ?2, heartbeat_time = NULL
+WHERE id = ?1 AND end_time IS NOT NULL;
@@ -0,0 +1,6 @@
+-- PostgreSQL port of sql/add_detection_errors.sql.
+INSERT INTO tracker_daily_stats (day_timestamp, detection_errors, last_error)
+VALUES ($1, $2, $3)
+ON CONFLICT (day_timestamp) DO UPDATE SET
+    detection_errors = tracker_daily_stats.detection_errors + excluded.detection_errors,
+    last_error = excluded.last_error;
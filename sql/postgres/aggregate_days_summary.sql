@@ -0,0 +1,12 @@
+INSERT INTO days_summary_by_app AS s (app_name, day_timestamp, total_duration_secs, passive_duration_secs)
+SELECT
+    app_name,
+    day_timestamp,
+    SUM(total_duration_secs)::BIGINT AS total_for_day,
+    SUM(passive_duration_secs)::BIGINT AS passive_for_day
+FROM daily_summary -- Aggregate FROM the detailed daily summary
+WHERE day_timestamp < $1 -- aggregate_cutoff_day_ts (e.g., start of yesterday)
+GROUP BY app_name, day_timestamp
+ON CONFLICT (app_name, day_timestamp) DO UPDATE SET
+    total_duration_secs = s.total_duration_secs + excluded.total_duration_secs,
+    passive_duration_secs = s.passive_duration_secs + excluded.passive_duration_secs;
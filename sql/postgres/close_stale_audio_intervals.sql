@@ -0,0 +1,4 @@
+-- PostgreSQL port of sql/close_stale_audio_intervals.sql
+UPDATE audio_intervals
+SET end_time = heartbeat_time
+WHERE end_time IS NULL AND device_id = $1 AND user_name = $2 AND heartbeat_time < $3;
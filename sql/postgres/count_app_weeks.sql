@@ -0,0 +1,2 @@
+-- PostgreSQL port of sql/count_app_weeks.sql.
+SELECT COUNT(*) FROM weeks_summary_by_app WHERE app_name = $1;
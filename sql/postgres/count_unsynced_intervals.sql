@@ -0,0 +1,6 @@
+-- Number of completed intervals above the sync watermark ($1); same rule as query_unsynced_intervals.sql
+SELECT COUNT(*)
+FROM app_intervals
+WHERE id > $1
+  AND end_time IS NOT NULL
+  AND id < COALESCE((SELECT MIN(id) FROM app_intervals WHERE id > $1 AND end_time IS NULL), 9223372036854775807);
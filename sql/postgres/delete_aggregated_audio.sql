@@ -0,0 +1,2 @@
+DELETE FROM audio_intervals
+WHERE end_time IS NOT NULL AND end_time <= $1; -- Start of the current local day, as in the audio aggregation
@@ -0,0 +1,2 @@
+DELETE FROM hourly_summary
+WHERE hour_timestamp < $1; -- Use aggregate_cutoff_day_ts
\ No newline at end of file
@@ -0,0 +1 @@
+DELETE FROM app_metadata WHERE app_name = $1;
@@ -0,0 +1,2 @@
+-- PostgreSQL port of sql/delete_calendar_events.sql
+DELETE FROM calendar_events WHERE start_time >= $1 AND start_time < $2;
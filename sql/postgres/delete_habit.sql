@@ -0,0 +1 @@
+DELETE FROM habits WHERE name = $1;
@@ -0,0 +1 @@
+DELETE FROM app_intervals WHERE id = $1 AND end_time IS NOT NULL;
@@ -0,0 +1 @@
+DELETE FROM daily_summary WHERE app_name = $1 AND detailed_window_title = $2;
@@ -0,0 +1,3 @@
+UPDATE audio_intervals
+SET end_time = GREATEST($1, start_time), heartbeat_time = GREATEST($1, start_time) -- never before the start if the clock was set back
+WHERE id = $2 AND end_time IS NULL;
@@ -0,0 +1,4 @@
+UPDATE app_intervals
+SET end_time = COALESCE(heartbeat_time, $1)
+WHERE end_time IS NULL AND start_time >= $2
+  AND COALESCE(user_name, $3) = $3
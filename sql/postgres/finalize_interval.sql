@@ -0,0 +1,3 @@
+UPDATE app_intervals
+SET end_time = GREATEST($1, start_time) -- never before the start if the clock was set back
+WHERE id = $2 AND end_time IS NULL
\ No newline at end of file
@@ -0,0 +1 @@
+UPDATE tracker_runs SET last_seen = GREATEST(last_seen, $2) WHERE id = $1;
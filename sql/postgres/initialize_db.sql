@@ -0,0 +1,174 @@
+-- PostgreSQL schema; mirrors the SQLite tables created by sql/initialize_db_*.sql. The hourly and daily
+-- summaries of the raw and audio intervals are created from `SUMMARIES` and `AUDIO_SUMMARY` in
+-- src/persistence/dimensions.rs.
+-- Timestamps are Unix seconds (BIGINT) on both backends.
+CREATE TABLE IF NOT EXISTS app_intervals (
+    id BIGSERIAL PRIMARY KEY,
+    app_name TEXT NOT NULL,
+    main_window_title TEXT,
+    detailed_window_title TEXT,
+    start_time BIGINT NOT NULL,
+    end_time BIGINT,
+    heartbeat_time BIGINT, -- Last time the tracker confirmed this interval was still active
+    virtual_desktop TEXT, -- Virtual desktop of the window (NULL if unknown/unsupported)
+    passive BOOLEAN NOT NULL DEFAULT FALSE, -- Full-screen media/meeting time
+    device_id TEXT, -- Computer the interval was recorded on
+    repo TEXT, -- Git repository the activity was in (NULL if unknown)
+    executable_path TEXT, -- Full path of the app's executable (NULL unless record_executable_path is on)
+    command_line TEXT, -- Command line of the app's process (NULL unless record_command_line is on)
+    ticket TEXT, -- Issue key found in the window title (NULL unless ticket_detection is on)
+    user_name TEXT, -- OS user the tracker ran as
+    session_type TEXT, -- Remote desktop involved ("rdp", "citrix", "rdp-client", ...; NULL = local)
+    remote_host TEXT, -- Machine a remote desktop client window was connected to (NULL if none)
+    document TEXT -- File open in an editor or office app (NULL unless document_detection is on)
+);
+
+CREATE TABLE IF NOT EXISTS days_summary_by_app (
+    app_name TEXT NOT NULL,
+    day_timestamp BIGINT NOT NULL,
+    total_duration_secs BIGINT NOT NULL DEFAULT 0,
+    passive_duration_secs BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (app_name, day_timestamp)
+);
+
+CREATE TABLE IF NOT EXISTS window_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    snapshot_time BIGINT NOT NULL, -- All rows of one snapshot share this timestamp
+    app_name TEXT NOT NULL,
+    window_title TEXT
+);
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    id SMALLINT PRIMARY KEY CHECK (id = 1), -- Single row
+    last_interval_id BIGINT NOT NULL DEFAULT 0, -- Watermark: highest app_intervals.id already pushed
+    last_sync_time BIGINT, -- When the last successful push finished
+    last_sync_count BIGINT NOT NULL DEFAULT 0 -- Intervals uploaded by that push
+);
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    changed_at BIGINT NOT NULL, -- When the edit was made
+    action TEXT NOT NULL, -- 'delete', 'split', 'add', 'clock_adjustment'
+    interval_id BIGINT NOT NULL, -- app_intervals.id the edit applied to (0 for clock adjustments)
+    details TEXT -- JSON: the interval before the edit plus the action's parameters
+);
+
+CREATE TABLE IF NOT EXISTS app_metadata (
+    app_name TEXT PRIMARY KEY, -- Executable name as recorded in app_intervals (e.g. msedge.exe)
+    display_name TEXT, -- Friendly name shown in stats and exports
+    icon_path TEXT, -- File holding the app's icon (the executable itself when read from Windows)
+    source TEXT NOT NULL, -- 'user' (app alias set) or 'version_info' (read from the executable)
+    updated_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS habits (
+    name TEXT PRIMARY KEY, -- Chosen with `habit add`, e.g. "coding"
+    app_glob TEXT NOT NULL, -- Case-insensitive glob matched against app_name
+    daily_minutes BIGINT NOT NULL, -- A day counts toward the streak once matching apps reach this
+    created_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS audio_intervals (
+    id BIGSERIAL PRIMARY KEY,
+    app_name TEXT NOT NULL, -- App whose audio session was playing
+    start_time BIGINT NOT NULL,
+    end_time BIGINT, -- NULL while it is still playing
+    heartbeat_time BIGINT NOT NULL, -- Last poll that saw it playing
+    device_id TEXT NOT NULL,
+    user_name TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tracker_runs (
+    id BIGSERIAL PRIMARY KEY,
+    start_time BIGINT NOT NULL, -- When `track` started recording
+    last_seen BIGINT NOT NULL, -- Last heartbeat; a run that crashed ends here
+    end_time BIGINT, -- NULL while running, and after a crash or power loss
+    device_id TEXT NOT NULL,
+    user_name TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tracker_run_events (
+    id BIGSERIAL PRIMARY KEY,
+    run_id BIGINT NOT NULL, -- tracker_runs.id
+    event_time BIGINT NOT NULL,
+    kind TEXT NOT NULL -- 'suspend', 'clock_jump', 'pause', 'resume', 'off_hours_start', 'off_hours_end'
+);
+
+CREATE TABLE IF NOT EXISTS break_reminders (
+    reminded_at BIGINT PRIMARY KEY,
+    active_secs BIGINT NOT NULL,
+    break_start BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS calendar_events (
+    start_time BIGINT NOT NULL,
+    uid TEXT NOT NULL,
+    end_time BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    PRIMARY KEY (start_time, uid)
+);
+
+CREATE TABLE IF NOT EXISTS weeks_summary_by_app (
+    app_name TEXT NOT NULL,
+    week_timestamp BIGINT NOT NULL, -- Start of the week (local midnight of its first day, see `week_start`)
+    total_duration_secs BIGINT NOT NULL DEFAULT 0,
+    passive_duration_secs BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (app_name, week_timestamp)
+);
+
+CREATE TABLE IF NOT EXISTS tracker_daily_stats (
+    day_timestamp BIGINT PRIMARY KEY, -- Start of the local day
+    interval_count BIGINT NOT NULL DEFAULT 0,
+    longest_session_secs BIGINT NOT NULL DEFAULT 0,
+    detection_errors BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+CREATE TABLE IF NOT EXISTS app_intervals_archive (
+    id BIGINT PRIMARY KEY, -- Id the interval had in app_intervals
+    app_name TEXT NOT NULL,
+    main_window_title TEXT,
+    detailed_window_title TEXT,
+    start_time BIGINT NOT NULL,
+    end_time BIGINT NOT NULL,
+    virtual_desktop TEXT,
+    passive BOOLEAN NOT NULL DEFAULT FALSE,
+    device_id TEXT,
+    repo TEXT,
+    executable_path TEXT,
+    command_line TEXT,
+    ticket TEXT,
+    user_name TEXT,
+    session_type TEXT,
+    remote_host TEXT,
+    document TEXT,
+    archived_at BIGINT NOT NULL -- When aggregation moved the interval here
+);
+
+-- Columns added after the first release of this schema
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS device_id TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS repo TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS repo TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS executable_path TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS command_line TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS executable_path TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS command_line TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS ticket TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS ticket TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS user_name TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS user_name TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS session_type TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS remote_host TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS session_type TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS remote_host TEXT;
+ALTER TABLE app_intervals ADD COLUMN IF NOT EXISTS document TEXT;
+ALTER TABLE app_intervals_archive ADD COLUMN IF NOT EXISTS document TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_app_intervals_app_name ON app_intervals (app_name);
+CREATE INDEX IF NOT EXISTS idx_app_intervals_main_title ON app_intervals (main_window_title);
+CREATE INDEX IF NOT EXISTS idx_app_intervals_detailed_title ON app_intervals (detailed_window_title);
+CREATE INDEX IF NOT EXISTS idx_app_intervals_start_time ON app_intervals (start_time);
+CREATE INDEX IF NOT EXISTS idx_app_intervals_end_time ON app_intervals (end_time);
+CREATE INDEX IF NOT EXISTS idx_window_snapshots_time ON window_snapshots (snapshot_time);
+CREATE INDEX IF NOT EXISTS idx_app_intervals_archive_start_time ON app_intervals_archive (start_time);
+CREATE INDEX IF NOT EXISTS idx_tracker_run_events_run_id ON tracker_run_events (run_id);
@@ -0,0 +1 @@
+INSERT INTO break_reminders (reminded_at, active_secs) VALUES ($1, $2) ON CONFLICT (reminded_at) DO NOTHING;
@@ -0,0 +1,6 @@
+-- PostgreSQL port of sql/insert_calendar_event.sql ($1 start, $2 uid, $3 end, $4 title)
+INSERT INTO calendar_events (start_time, uid, end_time, title)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (start_time, uid) DO UPDATE SET
+    end_time = excluded.end_time,
+    title = excluded.title;
@@ -0,0 +1,19 @@
+INSERT INTO app_intervals (
+    app_name,
+    main_window_title,
+    detailed_window_title,
+    start_time,
+    end_time,
+    virtual_desktop,
+    passive,
+    device_id,
+    repo,
+    executable_path,
+    command_line,
+    ticket,
+    user_name,
+    session_type,
+    remote_host,
+    document
+) VALUES ($1, $2, $3, $4, NULL, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+RETURNING id;
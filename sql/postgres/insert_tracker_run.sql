@@ -0,0 +1,3 @@
+INSERT INTO tracker_runs (start_time, last_seen, end_time, device_id, user_name)
+VALUES ($1, $1, NULL, $2, $3)
+RETURNING id;
@@ -0,0 +1 @@
+INSERT INTO tracker_run_events (run_id, event_time, kind) VALUES ($1, $2, $3);
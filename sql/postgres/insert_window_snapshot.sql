@@ -0,0 +1,2 @@
+INSERT INTO window_snapshots (snapshot_time, app_name, window_title)
+VALUES ($1, $2, $3);
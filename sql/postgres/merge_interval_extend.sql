@@ -0,0 +1,4 @@
+-- PostgreSQL port of sql/merge_interval_extend.sql
+UPDATE app_intervals
+SET end_time = $2, heartbeat_time = NULL
+WHERE id = $1 AND end_time IS NOT NULL;
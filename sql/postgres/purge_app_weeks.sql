@@ -0,0 +1,2 @@
+-- PostgreSQL port of sql/purge_app_weeks.sql.
+DELETE FROM weeks_summary_by_app WHERE app_name = $1 AND week_timestamp >= $2 AND week_timestamp < $3;
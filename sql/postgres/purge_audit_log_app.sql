@@ -0,0 +1,5 @@
+-- PostgreSQL port of sql/purge_audit_log_app.sql.
+DELETE FROM audit_log
+WHERE details::jsonb #>> '{before,app_name}' = $1
+   OR details::jsonb #>> '{added,app_name}' = $1
+   OR details::jsonb ->> 'app_name' = $1;
@@ -0,0 +1,4 @@
+-- PostgreSQL port of sql/query_app_metadata.sql
+SELECT app_name, display_name, icon_path, source
+FROM app_metadata
+ORDER BY app_name;
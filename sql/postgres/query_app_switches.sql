@@ -0,0 +1,15 @@
+-- How often the app changed between consecutive raw intervals (live and archived, oldest first)
+-- starting in [$1, $2). Locked time ($3) is left out, so unlocking back into the same app is no switch.
+WITH period_intervals AS (
+    SELECT app_name, start_time, id FROM app_intervals
+    WHERE start_time >= $1 AND start_time < $2 AND app_name <> $3
+    UNION ALL
+    SELECT app_name, start_time, id FROM app_intervals_archive
+    WHERE start_time >= $1 AND start_time < $2 AND app_name <> $3
+),
+ordered AS (
+    SELECT app_name, LAG(app_name) OVER (ORDER BY start_time, id) AS previous_app
+    FROM period_intervals
+)
+SELECT COUNT(*) FROM ordered
+WHERE previous_app IS NOT NULL AND previous_app <> app_name;
@@ -0,0 +1,9 @@
+-- PostgreSQL port of sql/query_archived_intervals.sql ($3 = NULL or ILIKE pattern).
+SELECT id, app_name, main_window_title, detailed_window_title, start_time,
+       end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals_archive
+WHERE start_time < $2
+  AND end_time > $1
+  AND ($3::TEXT IS NULL OR app_name ILIKE $3)
+ORDER BY start_time, id;
@@ -0,0 +1,4 @@
+-- PostgreSQL port of sql/query_break_compliance.sql
+SELECT COUNT(*), COUNT(break_start)
+FROM break_reminders
+WHERE reminded_at >= $1 AND reminded_at < $2;
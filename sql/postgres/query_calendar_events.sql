@@ -0,0 +1,5 @@
+-- PostgreSQL port of sql/query_calendar_events.sql
+SELECT uid, title, start_time, end_time
+FROM calendar_events
+WHERE start_time < $2 AND end_time > $1
+ORDER BY start_time, title;
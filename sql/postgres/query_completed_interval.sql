@@ -0,0 +1,7 @@
+-- PostgreSQL port of sql/query_completed_interval.sql
+SELECT id, app_name, main_window_title, detailed_window_title, start_time,
+       end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals
+WHERE id = $1
+  AND end_time IS NOT NULL;
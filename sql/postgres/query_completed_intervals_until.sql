@@ -0,0 +1,5 @@
+-- PostgreSQL port of sql/query_completed_intervals_until.sql.
+SELECT app_name, start_time, end_time
+FROM app_intervals
+WHERE end_time IS NOT NULL AND end_time <= $1
+ORDER BY start_time;
@@ -0,0 +1,16 @@
+-- Seconds per local day and app from $1 on, for habit streaks: days_summary_by_app, plus
+-- daily_summary for the days not rolled into it yet. Raw intervals are added by the caller.
+SELECT day_timestamp, app_name, SUM(total_duration_secs)::BIGINT AS total_duration
+FROM (
+    SELECT day_timestamp, app_name, total_duration_secs
+    FROM days_summary_by_app
+    WHERE day_timestamp >= $1
+
+    UNION ALL
+
+    SELECT day_timestamp, app_name, total_duration_secs
+    FROM daily_summary
+    WHERE day_timestamp >= $1
+) AS combined
+GROUP BY day_timestamp, app_name
+ORDER BY day_timestamp;
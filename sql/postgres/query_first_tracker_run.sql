@@ -0,0 +1 @@
+SELECT MIN(start_time) FROM tracker_runs WHERE user_name = $1;
@@ -0,0 +1,4 @@
+-- PostgreSQL port of sql/query_habits.sql
+SELECT name, app_glob, daily_minutes, created_at
+FROM habits
+ORDER BY name;
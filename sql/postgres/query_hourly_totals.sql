@@ -0,0 +1,16 @@
+-- Tracked seconds per hour from hourly_summary for `stats --level profile`, oldest first.
+-- Params: $1 / $2 = period boundaries, $3 = NULL or ILIKE pattern for app_name,
+-- $4 = NULL or case-insensitive substring of the detailed title, $5 = TRUE to leave out passive time,
+-- $6 = NULL or OS user (hourly rows carry no user, so a user filter leaves only raw intervals).
+-- Raw intervals not aggregated yet are added by the caller.
+SELECT
+    hour_timestamp,
+    SUM(total_duration_secs - CASE WHEN $5 THEN passive_duration_secs ELSE 0 END)::BIGINT AS total_duration
+FROM hourly_summary
+WHERE hour_timestamp >= $1 AND hour_timestamp < $2
+  AND ($3::TEXT IS NULL OR app_name ILIKE $3)
+  AND ($4::TEXT IS NULL
+       OR strpos(lower(detailed_window_title), lower($4)) > 0)
+  AND $6::TEXT IS NULL
+GROUP BY hour_timestamp
+ORDER BY hour_timestamp;
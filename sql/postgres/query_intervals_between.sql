@@ -0,0 +1,8 @@
+-- Raw intervals overlapping [$1, $2), oldest first; $3 = end time used for the still-open interval
+SELECT id, app_name, main_window_title, detailed_window_title, start_time,
+       COALESCE(end_time, $3) AS end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals
+WHERE start_time < $2
+  AND COALESCE(end_time, $3) > $1
+ORDER BY start_time, id;
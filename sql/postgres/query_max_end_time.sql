@@ -0,0 +1,3 @@
+SELECT MAX(end_time)
+FROM app_intervals
+WHERE end_time < $1
\ No newline at end of file
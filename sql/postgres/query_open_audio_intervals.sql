@@ -0,0 +1,4 @@
+-- PostgreSQL port of sql/query_open_audio_intervals.sql
+SELECT id, app_name
+FROM audio_intervals
+WHERE end_time IS NULL AND device_id = $1 AND user_name = $2;
@@ -0,0 +1,26 @@
+-- PostgreSQL port of sql/query_stats_audio.sql. Params: $1 start, $2 end, $3 effective end, $4 app
+-- pattern, $5 min duration, $6 limit, $7 whole days, $8 user
+SELECT
+    app_name,
+    SUM(duration)::BIGINT AS total_duration
+FROM (
+    SELECT app_name, total_duration_secs AS duration
+    FROM daily_audio_summary
+    WHERE $7 AND day_timestamp >= $1 AND day_timestamp < $2
+      AND ($8::TEXT IS NULL OR user_name = $8)
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        GREATEST(0, LEAST(COALESCE(end_time, heartbeat_time), $3) - GREATEST(start_time, $1)) AS duration
+    FROM audio_intervals
+    WHERE start_time < $3
+      AND COALESCE(end_time, heartbeat_time) > $1
+      AND ($8::TEXT IS NULL OR user_name = $8)
+) AS combined
+WHERE $4::TEXT IS NULL OR app_name ILIKE $4
+GROUP BY app_name
+HAVING SUM(duration) >= $5::BIGINT -- SUM of BIGINT is NUMERIC
+ORDER BY total_duration DESC
+LIMIT $6;
@@ -0,0 +1,84 @@
+-- PostgreSQL port of sql/query_stats_by_app.sql.
+-- Params:
+--   $1 / $2  = period start / end (summary tables)
+--   $3       = MIN(now, end), used to clip raw intervals
+--   $4       = end time used for currently active intervals
+--   $5       = NULL or ILIKE pattern for app_name (converted from the user's glob)
+--   $6       = NULL or case-insensitive substring of the detailed title
+--   $7       = minimum total seconds per row
+--   $8       = maximum number of rows (NULL = no limit)
+--   $9       = TRUE to leave out time tagged passive
+--   $10      = TRUE when the period is made of whole local days; FALSE for hour periods (hourly_summary)
+--   $11      = NULL or OS user whose time is counted (daily_user_summary + raw intervals)
+--   $12      = whole weeks before this are read from weeks_summary_by_app (= $1 when none)
+SELECT
+    app_name,
+    SUM(duration)::BIGINT AS total_duration
+FROM (
+    SELECT app_name, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM weeks_summary_by_app
+    WHERE $10 AND week_timestamp >= $1 AND week_timestamp < $12
+      -- Summary rows carry no titles or users, so they cannot satisfy a title or user filter
+      AND $6::TEXT IS NULL
+      AND $11::TEXT IS NULL
+
+    UNION ALL
+
+    SELECT app_name, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM days_summary_by_app
+    WHERE $10 AND day_timestamp >= GREATEST($1, $12) AND day_timestamp < $2
+      AND $6::TEXT IS NULL
+      AND $11::TEXT IS NULL
+
+    UNION ALL
+
+    -- Days not rolled into days_summary_by_app yet (yesterday and today)
+    SELECT app_name, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_summary
+    WHERE $10 AND day_timestamp >= $1 AND day_timestamp < $2
+      AND ($6::TEXT IS NULL
+           OR strpos(lower(detailed_window_title), lower($6)) > 0)
+      AND $11::TEXT IS NULL
+
+    UNION ALL
+
+    -- Hour periods: completed hours already rolled into hourly_summary
+    SELECT app_name, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM hourly_summary
+    WHERE NOT $10 AND hour_timestamp >= $1 AND hour_timestamp < $2
+      AND ($6::TEXT IS NULL
+           OR strpos(lower(detailed_window_title), lower($6)) > 0)
+      AND $11::TEXT IS NULL
+
+    UNION ALL
+
+    -- With a user filter: whole days per user (app-level summaries above mix all users)
+    SELECT app_name, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_user_summary
+    WHERE $10 AND day_timestamp >= $1 AND day_timestamp < $2
+      AND user_name = $11
+      AND $6::TEXT IS NULL
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        GREATEST(0, LEAST(COALESCE(end_time, $4), $3) - GREATEST(start_time, $1)) AS duration
+    FROM app_intervals
+    WHERE start_time < $3
+      AND COALESCE(end_time, $4) > $1
+      AND NOT ($9 AND passive)
+      AND ($11::TEXT IS NULL OR COALESCE(user_name, '[Unknown User]') = $11)
+      AND ($6::TEXT IS NULL
+           OR strpos(lower(detailed_window_title), lower($6)) > 0)
+) AS combined
+WHERE $5::TEXT IS NULL OR app_name ILIKE $5
+GROUP BY app_name
+HAVING SUM(duration) >= $7::BIGINT -- SUM of BIGINT is NUMERIC
+ORDER BY total_duration DESC
+LIMIT $8;
@@ -0,0 +1,39 @@
+-- PostgreSQL port of sql/query_stats_detailed.sql. Params: see query_stats_by_app.sql
+SELECT
+    app_name,
+    detailed_window_title,
+    SUM(duration)::BIGINT AS total_duration
+FROM (
+    SELECT app_name, detailed_window_title, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_summary
+    WHERE $10 AND day_timestamp >= $1 AND day_timestamp < $2
+      AND $11::TEXT IS NULL
+
+    UNION ALL
+
+    SELECT app_name, detailed_window_title, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM hourly_summary
+    WHERE NOT $10 AND hour_timestamp >= $1 AND hour_timestamp < $2
+      AND $11::TEXT IS NULL
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        detailed_window_title,
+        GREATEST(0, LEAST(COALESCE(end_time, $4), $3) - GREATEST(start_time, $1)) AS duration
+    FROM app_intervals
+    WHERE start_time < $3
+      AND COALESCE(end_time, $4) > $1
+      AND NOT ($9 AND passive)
+      AND ($11::TEXT IS NULL OR COALESCE(user_name, '[Unknown User]') = $11)
+) AS combined
+WHERE ($5::TEXT IS NULL OR app_name ILIKE $5)
+  AND ($6::TEXT IS NULL
+       OR strpos(lower(detailed_window_title), lower($6)) > 0)
+GROUP BY app_name, detailed_window_title
+HAVING SUM(duration) >= $7::BIGINT -- SUM of BIGINT is NUMERIC
+ORDER BY total_duration DESC
+LIMIT $8;
@@ -0,0 +1,3 @@
+SELECT last_interval_id, last_sync_time, last_sync_count
+FROM sync_state
+WHERE id = 1;
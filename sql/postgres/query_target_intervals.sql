@@ -0,0 +1,10 @@
+-- PostgreSQL port of sql/query_target_intervals.sql
+SELECT id, app_name, main_window_title, detailed_window_title, start_time,
+       COALESCE(end_time, $4) AS end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals
+WHERE app_name = $1
+  AND ($5::TEXT IS NULL OR COALESCE(detailed_window_title, '') = $5)
+  AND start_time < $3
+  AND COALESCE(end_time, $4) > $2
+ORDER BY start_time, id;
@@ -0,0 +1,8 @@
+-- Every event of the runs of user $3 overlapping [$1, $2), oldest first
+SELECT e.run_id, e.event_time, e.kind
+FROM tracker_run_events e
+JOIN tracker_runs r ON r.id = e.run_id
+WHERE r.user_name = $3
+  AND r.start_time < $2
+  AND COALESCE(r.end_time, r.last_seen) > $1
+ORDER BY e.event_time, e.id;
@@ -0,0 +1,7 @@
+-- Runs of user $3 overlapping [$1, $2), oldest first
+SELECT id, start_time, last_seen, end_time
+FROM tracker_runs
+WHERE user_name = $3
+  AND start_time < $2
+  AND COALESCE(end_time, last_seen) > $1
+ORDER BY start_time, id;
@@ -0,0 +1,10 @@
+-- Completed intervals after the sync watermark ($1), oldest first, at most $2 rows.
+-- Stops before the oldest still-open interval so the watermark never skips a row that completes later.
+SELECT id, app_name, main_window_title, detailed_window_title, start_time, end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals
+WHERE id > $1
+  AND end_time IS NOT NULL
+  AND id < COALESCE((SELECT MIN(id) FROM app_intervals WHERE id > $1 AND end_time IS NULL), 9223372036854775807)
+ORDER BY id
+LIMIT $2;
@@ -0,0 +1,7 @@
+-- PostgreSQL port of sql/query_weeks_rebuild_from.sql.
+SELECT MIN(day_timestamp) FROM (
+    SELECT day_timestamp FROM daily_summary WHERE day_timestamp < $1 -- aggregate_cutoff_day_ts
+    UNION ALL
+    SELECT day_timestamp FROM days_summary_by_app
+    WHERE NOT EXISTS (SELECT 1 FROM weeks_summary_by_app)
+) AS days;
@@ -0,0 +1,7 @@
+-- Returns the windows of the most recent snapshot taken at or before $1
+SELECT snapshot_time, app_name, window_title
+FROM window_snapshots
+WHERE snapshot_time = (
+    SELECT MAX(snapshot_time) FROM window_snapshots WHERE snapshot_time <= $1
+)
+ORDER BY id;
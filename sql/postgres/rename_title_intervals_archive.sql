@@ -0,0 +1,5 @@
+-- Renames title $2 of app $1 to $3 in both title columns of app_intervals_archive (`normalize --apply-history`).
+UPDATE app_intervals_archive SET
+    main_window_title = CASE WHEN main_window_title = $2 THEN $3 ELSE main_window_title END,
+    detailed_window_title = CASE WHEN detailed_window_title = $2 THEN $3 ELSE detailed_window_title END
+WHERE app_name = $1 AND (main_window_title = $2 OR detailed_window_title = $2);
@@ -0,0 +1,14 @@
+-- PostgreSQL port of sql/split_interval_insert.sql
+INSERT INTO app_intervals (
+    app_name, main_window_title, detailed_window_title, start_time, end_time,
+    heartbeat_time, virtual_desktop, passive, device_id, repo,
+    executable_path, command_line, ticket, user_name, session_type, remote_host, document
+)
+SELECT app_name, main_window_title, detailed_window_title, $2, end_time,
+       heartbeat_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals
+WHERE id = $1
+  AND end_time IS NOT NULL
+  AND start_time < $2 AND end_time > $2
+RETURNING id;
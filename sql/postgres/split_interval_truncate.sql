@@ -0,0 +1,6 @@
+-- PostgreSQL port of sql/split_interval_truncate.sql
+UPDATE app_intervals
+SET end_time = $2, heartbeat_time = NULL
+WHERE id = $1
+  AND end_time IS NOT NULL
+  AND start_time < $2 AND end_time > $2;
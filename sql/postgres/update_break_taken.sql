@@ -0,0 +1 @@
+UPDATE break_reminders SET break_start = $2 WHERE reminded_at = $1;
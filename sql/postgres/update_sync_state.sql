@@ -0,0 +1,6 @@
+INSERT INTO sync_state (id, last_interval_id, last_sync_time, last_sync_count)
+VALUES (1, $1, $2, $3)
+ON CONFLICT(id) DO UPDATE SET
+    last_interval_id = excluded.last_interval_id,
+    last_sync_time = excluded.last_sync_time,
+    last_sync_count = excluded.last_sync_count;
@@ -0,0 +1,9 @@
+-- PostgreSQL port of sql/upsert_app_metadata.sql
+INSERT INTO app_metadata (app_name, display_name, icon_path, source, updated_at)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (app_name) DO UPDATE SET
+    display_name = excluded.display_name,
+    icon_path = COALESCE(excluded.icon_path, app_metadata.icon_path),
+    source = excluded.source,
+    updated_at = excluded.updated_at
+WHERE excluded.source = 'user' OR app_metadata.source <> 'user';
@@ -0,0 +1,6 @@
+-- PostgreSQL port of sql/upsert_tracker_daily_intervals.sql.
+INSERT INTO tracker_daily_stats (day_timestamp, interval_count, longest_session_secs)
+VALUES ($1, $2, $3)
+ON CONFLICT (day_timestamp) DO UPDATE SET
+    interval_count = tracker_daily_stats.interval_count + excluded.interval_count,
+    longest_session_secs = GREATEST(tracker_daily_stats.longest_session_secs, excluded.longest_session_secs);
@@ -0,0 +1,6 @@
+-- PostgreSQL port of sql/upsert_weeks_summary.sql.
+INSERT INTO weeks_summary_by_app (app_name, week_timestamp, total_duration_secs, passive_duration_secs)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (app_name, week_timestamp) DO UPDATE SET
+    total_duration_secs = excluded.total_duration_secs,
+    passive_duration_secs = excluded.passive_duration_secs;
@@ -0,0 +1,3 @@
+-- Weeks of app ?1 from ?2 (start of the week holding the range start) until ?3; weeks only partly
+-- in the range are rebuilt from days_summary_by_app afterwards.
+DELETE FROM weeks_summary_by_app WHERE app_name = ?1 AND week_timestamp >= ?2 AND week_timestamp < ?3;
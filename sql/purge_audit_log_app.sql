@@ -0,0 +1,6 @@
+-- Removes the audit_log entries about app ?1 (`purge --app` without a range): deleted or split
+-- intervals, manual entries and title renames keep the app and its titles in their details.
+DELETE FROM audit_log
+WHERE json_extract(details, '$.before.app_name') = ?1
+   OR json_extract(details, '$.added.app_name') = ?1
+   OR json_extract(details, '$.app_name') = ?1;
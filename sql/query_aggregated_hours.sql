@@ -0,0 +1,4 @@
+-- Hours in [?1, ?2) already rolled into hourly_summary (their raw intervals may be gone)
+SELECT DISTINCT hour_timestamp FROM hourly_summary
+WHERE hour_timestamp >= ?1 AND hour_timestamp < ?2
+ORDER BY hour_timestamp;
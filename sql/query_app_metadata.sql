@@ -0,0 +1,3 @@
+SELECT app_name, display_name, icon_path, source
+FROM app_metadata
+ORDER BY app_name;
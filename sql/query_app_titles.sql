@@ -0,0 +1,45 @@
+-- Drill-down: usage of one application per detailed window title for a given time period
+-- (summary rows plus raw, not yet aggregated intervals, like query_stats_detailed.sql).
+-- Params: :app_name (exact), :start, :end, :whole_days, :effective_end, :now, :title_contains,
+--         :min_duration, :limit, :exclude_passive, :user (as in query_stats_by_app.sql)
+SELECT
+    app_name,
+    COALESCE(detailed_window_title, '') AS title,
+    SUM(duration) AS total_duration
+FROM (
+    SELECT app_name, detailed_window_title, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_summary
+    WHERE app_name = :app_name
+      AND :whole_days AND day_timestamp >= :start AND day_timestamp < :end
+      -- Summary rows carry no users, so they cannot satisfy a user filter
+      AND :user IS NULL
+
+    UNION ALL
+
+    SELECT app_name, detailed_window_title, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM hourly_summary
+    WHERE app_name = :app_name
+      AND NOT :whole_days AND hour_timestamp >= :start AND hour_timestamp < :end
+      AND :user IS NULL
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        detailed_window_title,
+        MAX(0, MIN(COALESCE(end_time, :now), :effective_end) - MAX(start_time, :start)) AS duration
+    FROM app_intervals
+    WHERE app_name = :app_name
+      AND start_time < :effective_end
+      AND COALESCE(end_time, :now) > :start
+      AND NOT (:exclude_passive AND passive = 1)
+      AND (:user IS NULL OR COALESCE(user_name, '[Unknown User]') = :user)
+)
+WHERE (:title_contains IS NULL
+       OR instr(lower(detailed_window_title), lower(:title_contains)) > 0)
+GROUP BY app_name, title
+HAVING total_duration >= :min_duration
+ORDER BY total_duration DESC
+LIMIT :limit;
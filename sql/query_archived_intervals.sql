@@ -0,0 +1,10 @@
+-- Archived intervals overlapping [?1, ?2), oldest first; ?3 = NULL or glob matched
+-- case-insensitively against app_name
+SELECT id, app_name, main_window_title, detailed_window_title, start_time,
+       end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals_archive
+WHERE start_time < ?2
+  AND end_time > ?1
+  AND (?3 IS NULL OR lower(app_name) GLOB lower(?3))
+ORDER BY start_time, id;
@@ -0,0 +1,4 @@
+-- Break reminders shown in [?1, ?2) and how many of them were followed by a break.
+SELECT COUNT(*), COUNT(break_start)
+FROM break_reminders
+WHERE reminded_at >= ?1 AND reminded_at < ?2;
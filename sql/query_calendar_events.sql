@@ -0,0 +1,5 @@
+-- Events overlapping [?1, ?2), in start order.
+SELECT uid, title, start_time, end_time
+FROM calendar_events
+WHERE start_time < ?2 AND end_time > ?1
+ORDER BY start_time, title;
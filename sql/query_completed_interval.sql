@@ -0,0 +1,7 @@
+-- One completed raw interval by id (open intervals belong to the running tracker and are not editable)
+SELECT id, app_name, main_window_title, detailed_window_title, start_time,
+       end_time, virtual_desktop, passive, device_id, repo,
+       executable_path, command_line, ticket, user_name, session_type, remote_host, document
+FROM app_intervals
+WHERE id = ?1
+  AND end_time IS NOT NULL;
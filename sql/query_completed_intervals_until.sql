@@ -0,0 +1,5 @@
+-- App and times of completed raw intervals ending by ?1, for the tracker's own statistics
+SELECT app_name, start_time, end_time
+FROM app_intervals
+WHERE end_time IS NOT NULL AND end_time <= ?1
+ORDER BY start_time;
@@ -0,0 +1,4 @@
+SELECT app_name, day_timestamp, total_duration_secs, passive_duration_secs
+FROM days_summary_by_app
+WHERE day_timestamp >= ?1 -- Start of the first week to rebuild
+ORDER BY day_timestamp;
\ No newline at end of file
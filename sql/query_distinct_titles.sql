@@ -0,0 +1,8 @@
+-- Every (app, title) stored in raw intervals, the archive and the per-title summaries (`normalize`).
+SELECT app_name, main_window_title FROM app_intervals WHERE main_window_title IS NOT NULL
+UNION SELECT app_name, detailed_window_title FROM app_intervals WHERE detailed_window_title IS NOT NULL
+UNION SELECT app_name, main_window_title FROM app_intervals_archive WHERE main_window_title IS NOT NULL
+UNION SELECT app_name, detailed_window_title FROM app_intervals_archive WHERE detailed_window_title IS NOT NULL
+UNION SELECT app_name, detailed_window_title FROM hourly_summary
+UNION SELECT app_name, detailed_window_title FROM daily_summary
+ORDER BY 1, 2;
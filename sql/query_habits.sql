@@ -0,0 +1,3 @@
+SELECT name, app_glob, daily_minutes, created_at
+FROM habits
+ORDER BY name;
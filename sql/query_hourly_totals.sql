@@ -0,0 +1,15 @@
+-- Tracked seconds per hour from hourly_summary for `stats --level profile`, oldest first.
+-- Params: :start / :end = period boundaries; :app_glob, :title_contains, :exclude_passive and
+-- :user as in query_stats_by_app.sql. Raw intervals not aggregated yet are added by the caller.
+SELECT
+    hour_timestamp,
+    SUM(total_duration_secs - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END) AS total_duration
+FROM hourly_summary
+WHERE hour_timestamp >= :start AND hour_timestamp < :end
+  AND (:app_glob IS NULL OR lower(app_name) GLOB lower(:app_glob))
+  AND (:title_contains IS NULL
+       OR instr(lower(detailed_window_title), lower(:title_contains)) > 0)
+  -- Hourly rows carry no user; with a user filter only raw intervals count
+  AND :user IS NULL
+GROUP BY hour_timestamp
+ORDER BY hour_timestamp;
@@ -0,0 +1,29 @@
+-- Longest stretch on one app within [?1, ?2): consecutive raw intervals (live and archived) of the
+-- same app at most ?5 seconds apart, clipped to the period. ?3 = end time used for the still-open
+-- interval, ?4 = locked app name (left out). One row (app_name, start, end), none if nothing was tracked.
+WITH period_intervals AS (
+    SELECT app_name, MAX(start_time, ?1) AS start_time, MIN(COALESCE(end_time, ?3), ?2) AS end_time, id
+    FROM app_intervals
+    WHERE start_time < ?2 AND COALESCE(end_time, ?3) > ?1 AND app_name <> ?4
+    UNION ALL
+    SELECT app_name, MAX(start_time, ?1), MIN(end_time, ?2), id
+    FROM app_intervals_archive
+    WHERE start_time < ?2 AND end_time > ?1 AND app_name <> ?4
+),
+marked AS (
+    SELECT app_name, start_time, end_time, id,
+           CASE WHEN LAG(app_name) OVER w = app_name AND start_time - LAG(end_time) OVER w <= ?5
+                THEN 0 ELSE 1 END AS new_run
+    FROM period_intervals
+    WINDOW w AS (ORDER BY start_time, id)
+),
+runs AS (
+    SELECT app_name, start_time, end_time,
+           SUM(new_run) OVER (ORDER BY start_time, id ROWS UNBOUNDED PRECEDING) AS run_id
+    FROM marked
+)
+SELECT app_name, MIN(start_time) AS run_start, MAX(end_time) AS run_end
+FROM runs
+GROUP BY run_id, app_name
+ORDER BY run_end - run_start DESC, run_start
+LIMIT 1;
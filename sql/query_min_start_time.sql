@@ -0,0 +1,8 @@
+-- Earliest start of the completed raw intervals (app and audio) ending by ?1: where aggregation's buckets start
+SELECT MIN(start_time) FROM (
+    SELECT start_time FROM app_intervals
+    WHERE end_time IS NOT NULL AND end_time <= ?1 AND end_time > start_time
+    UNION ALL
+    SELECT start_time FROM audio_intervals
+    WHERE end_time IS NOT NULL AND end_time <= ?1 AND end_time > start_time
+);
@@ -0,0 +1,4 @@
+-- Audio intervals of this device and user still open after close_stale_audio_intervals.sql: (id, app_name)
+SELECT id, app_name
+FROM audio_intervals
+WHERE end_time IS NULL AND device_id = ?1 AND user_name = ?2;
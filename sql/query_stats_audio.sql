@@ -0,0 +1,31 @@
+-- Fetches time per app of the audio stream (`audio_tracking`, `stats --stream audio`) for a given period.
+-- Combines the daily_audio_summary table with raw, not yet aggregated audio intervals; open ones count
+-- up to the last poll that saw them playing.
+-- Params: :start, :end, :whole_days, :effective_end, :app_glob, :min_duration, :limit and :user as in
+-- query_stats_by_app.sql. Audio has no window titles or passive time.
+-- There is no hourly audio summary; audio is aggregated a day at a time, so the raw intervals of today
+-- are all still there for hour periods.
+SELECT
+    app_name,
+    SUM(duration) AS total_duration
+FROM (
+    SELECT app_name, total_duration_secs AS duration
+    FROM daily_audio_summary
+    WHERE :whole_days AND day_timestamp >= :start AND day_timestamp < :end
+      AND (:user IS NULL OR user_name = :user)
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        MAX(0, MIN(COALESCE(end_time, heartbeat_time), :effective_end) - MAX(start_time, :start)) AS duration
+    FROM audio_intervals
+    WHERE start_time < :effective_end
+      AND COALESCE(end_time, heartbeat_time) > :start
+      AND (:user IS NULL OR user_name = :user)
+)
+WHERE :app_glob IS NULL OR lower(app_name) GLOB lower(:app_glob)
+GROUP BY app_name
+HAVING total_duration >= :min_duration
+ORDER BY total_duration DESC
+LIMIT :limit;
@@ -0,0 +1,93 @@
+-- Fetches usage aggregated by application name for a given time period.
+-- Combines the long-term weeks_summary_by_app and days_summary_by_app tables, the recent
+-- daily_summary and raw, not yet aggregated intervals, then applies the optional stats
+-- filters so only the requested rows leave SQLite.
+-- Params:
+--   :start / :end      = period boundaries (summary tables)
+--   :whole_days        = 1 when the period is made of whole local days (day/week summaries
+--                        apply); 0 for hour periods, which read hourly_summary instead
+--   :weeks_until       = whole weeks before this are read from weeks_summary_by_app
+--                        instead of days_summary_by_app (= :start when none)
+--   :effective_end     = MIN(now, :end), used to clip raw intervals
+--   :now               = end time used for currently active intervals
+--   :app_glob          = NULL or glob matched case-insensitively against app_name
+--   :title_contains    = NULL or case-insensitive substring of the detailed title
+--   :min_duration      = minimum total seconds per row
+--   :limit             = maximum number of rows (-1 = no limit)
+--   :exclude_passive   = 1 to leave out time tagged passive (full-screen media/meetings)
+--   :user              = NULL or OS user whose time is counted (shared databases); whole days then
+--                        come from daily_user_summary (`check_user_filter` refuses it for completed
+--                        hours and with :title_contains, which those rows cannot answer)
+SELECT
+    app_name,
+    SUM(duration) AS total_duration
+FROM (
+    SELECT app_name, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM weeks_summary_by_app
+    WHERE :whole_days AND week_timestamp >= :start AND week_timestamp < :weeks_until
+      -- Summary rows carry no titles or users, so they cannot satisfy a title or user filter
+      AND :title_contains IS NULL
+      AND :user IS NULL
+
+    UNION ALL
+
+    SELECT app_name, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM days_summary_by_app
+    WHERE :whole_days AND day_timestamp >= MAX(:start, :weeks_until) AND day_timestamp < :end
+      AND :title_contains IS NULL
+      AND :user IS NULL
+
+    UNION ALL
+
+    -- Days not rolled into days_summary_by_app yet (yesterday and today)
+    SELECT app_name, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_summary
+    WHERE :whole_days AND day_timestamp >= :start AND day_timestamp < :end
+      AND (:title_contains IS NULL
+           OR instr(lower(detailed_window_title), lower(:title_contains)) > 0)
+      AND :user IS NULL
+
+    UNION ALL
+
+    -- Hour periods: completed hours already rolled into hourly_summary (a day row would
+    -- count the whole day)
+    SELECT app_name, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM hourly_summary
+    WHERE NOT :whole_days AND hour_timestamp >= :start AND hour_timestamp < :end
+      AND (:title_contains IS NULL
+           OR instr(lower(detailed_window_title), lower(:title_contains)) > 0)
+      AND :user IS NULL
+
+    UNION ALL
+
+    -- With a user filter: whole days per user (app-level summaries above mix all users)
+    SELECT app_name, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_user_summary
+    WHERE :whole_days AND day_timestamp >= :start AND day_timestamp < :end
+      AND user_name = :user
+      AND :title_contains IS NULL
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        -- Both ends clipped to the period; COALESCE(end_time, :now) handles active intervals
+        MAX(0, MIN(COALESCE(end_time, :now), :effective_end) - MAX(start_time, :start)) AS duration
+    FROM app_intervals
+    WHERE start_time < :effective_end
+      AND COALESCE(end_time, :now) > :start
+      AND NOT (:exclude_passive AND passive = 1)
+      AND (:user IS NULL OR COALESCE(user_name, '[Unknown User]') = :user)
+      AND (:title_contains IS NULL
+           OR instr(lower(detailed_window_title), lower(:title_contains)) > 0)
+)
+WHERE :app_glob IS NULL OR lower(app_name) GLOB lower(:app_glob)
+GROUP BY app_name
+HAVING total_duration >= :min_duration
+ORDER BY total_duration DESC
+LIMIT :limit;
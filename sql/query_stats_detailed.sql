@@ -0,0 +1,43 @@
+-- Fetches usage aggregated by application name and detailed window title for a given time period.
+-- Combines the daily_summary (or, for hour periods, hourly_summary) table with raw, not yet aggregated intervals,
+-- then applies the optional stats filters so only the requested rows leave SQLite.
+-- Params: see query_stats_by_app.sql
+SELECT
+    app_name,
+    detailed_window_title,
+    SUM(duration) AS total_duration
+FROM (
+    SELECT app_name, detailed_window_title, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM daily_summary
+    WHERE :whole_days AND day_timestamp >= :start AND day_timestamp < :end
+      AND :user IS NULL
+
+    UNION ALL
+
+    SELECT app_name, detailed_window_title, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM hourly_summary
+    WHERE NOT :whole_days AND hour_timestamp >= :start AND hour_timestamp < :end
+      AND :user IS NULL
+
+    UNION ALL
+
+    SELECT
+        app_name,
+        detailed_window_title,
+        -- Both ends clipped to the period; COALESCE(end_time, :now) handles active intervals
+        MAX(0, MIN(COALESCE(end_time, :now), :effective_end) - MAX(start_time, :start)) AS duration
+    FROM app_intervals
+    WHERE start_time < :effective_end
+      AND COALESCE(end_time, :now) > :start
+      AND NOT (:exclude_passive AND passive = 1)
+      AND (:user IS NULL OR COALESCE(user_name, '[Unknown User]') = :user)
+)
+WHERE (:app_glob IS NULL OR lower(app_name) GLOB lower(:app_glob))
+  AND (:title_contains IS NULL
+       OR instr(lower(detailed_window_title), lower(:title_contains)) > 0)
+GROUP BY app_name, detailed_window_title
+HAVING total_duration >= :min_duration
+ORDER BY total_duration DESC
+LIMIT :limit;
@@ -0,0 +1,9 @@
+-- App names with the most tracked time (summaries plus raw intervals), most used first; ?1 = how many
+SELECT app_name FROM (
+    SELECT app_name, total_duration_secs FROM days_summary_by_app
+    UNION ALL SELECT app_name, total_duration_secs FROM daily_summary
+    UNION ALL SELECT app_name, COALESCE(end_time, start_time) - start_time FROM app_intervals
+)
+GROUP BY app_name
+ORDER BY SUM(total_duration_secs) DESC
+LIMIT ?1;
@@ -0,0 +1,7 @@
+-- Local days with any recorded time; ?1 is the UTC offset in seconds, less day_start_hour, for raw intervals
+SELECT day_timestamp FROM days_summary_by_app
+UNION
+SELECT day_timestamp FROM daily_summary
+UNION
+SELECT start_time - (start_time + ?1) % 86400 FROM app_intervals
+ORDER BY 1;
@@ -0,0 +1,3 @@
+SELECT day_timestamp, interval_count, longest_session_secs, detection_errors, last_error
+FROM tracker_daily_stats
+ORDER BY day_timestamp;
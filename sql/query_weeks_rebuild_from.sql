@@ -0,0 +1,9 @@
+-- First day whose week needs rebuilding in weeks_summary_by_app (NULL = none)
+SELECT MIN(day_timestamp) FROM (
+    -- Days about to be rolled into days_summary_by_app
+    SELECT day_timestamp FROM daily_summary WHERE day_timestamp < ?1 -- aggregate_cutoff_day_ts
+    UNION ALL
+    -- Everything while weeks_summary_by_app is empty (databases from before it existed)
+    SELECT day_timestamp FROM days_summary_by_app
+    WHERE NOT EXISTS (SELECT 1 FROM weeks_summary_by_app)
+);
\ No newline at end of file
@@ -0,0 +1,9 @@
+-- Adds the daily_summary rows of title ?2 of app ?1 to title ?3 (`normalize --apply-history`);
+-- the old rows are deleted afterwards with delete_title_daily.sql.
+INSERT INTO daily_summary (app_name, detailed_window_title, day_timestamp, total_duration_secs, passive_duration_secs)
+SELECT app_name, ?3, day_timestamp, total_duration_secs, passive_duration_secs
+FROM daily_summary
+WHERE app_name = ?1 AND detailed_window_title = ?2
+ON CONFLICT(app_name, detailed_window_title, day_timestamp) DO UPDATE SET
+    total_duration_secs = total_duration_secs + excluded.total_duration_secs,
+    passive_duration_secs = passive_duration_secs + excluded.passive_duration_secs;
@@ -0,0 +1,6 @@
+-- First half of a split: interval ?1 now ends at ?2
+UPDATE app_intervals
+SET end_time = ?2, heartbeat_time = NULL
+WHERE id = ?1
+  AND end_time IS NOT NULL
+  AND start_time < ?2 AND end_time > ?2;
@@ -0,0 +1,6 @@
+-- ?1 name, ?2 app glob, ?3 daily minutes, ?4 now. Redefining a habit keeps its creation time.
+INSERT INTO habits (name, app_glob, daily_minutes, created_at)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT(name) DO UPDATE SET
+    app_glob = excluded.app_glob,
+    daily_minutes = excluded.daily_minutes;
@@ -0,0 +1,6 @@
+-- Adds an aggregation batch's interval count and keeps the longest session of the day
+INSERT INTO tracker_daily_stats (day_timestamp, interval_count, longest_session_secs)
+VALUES (?1, ?2, ?3)
+ON CONFLICT(day_timestamp) DO UPDATE SET
+    interval_count = interval_count + excluded.interval_count,
+    longest_session_secs = MAX(longest_session_secs, excluded.longest_session_secs);
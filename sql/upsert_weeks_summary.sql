@@ -0,0 +1,6 @@
+-- Replaces (not adds to) a week total: weeks are rebuilt from days_summary_by_app
+INSERT INTO weeks_summary_by_app (app_name, week_timestamp, total_duration_secs, passive_duration_secs)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT(app_name, week_timestamp) DO UPDATE SET
+    total_duration_secs = excluded.total_duration_secs,
+    passive_duration_secs = excluded.passive_duration_secs;
\ No newline at end of file
@@ -0,0 +1,105 @@
+// src/app_metadata.rs
+// Friendly names for executables (`app_metadata` table): set with `app alias set` or, on Windows,
+// read from the executable's version resource the first time the tracker sees an app.
+
+use crate::errors::AppResult;
+use crate::persistence::Storage;
+use crate::types::{AppMetadata, AppMetadataSource};
+use std::collections::{HashMap, HashSet};
+
+/// Display names by executable, loaded once per command and applied when printing or exporting.
+#[derive(Debug, Clone, Default)]
+pub struct AppAliases {
+    /// Lower-cased app name -> display name
+    names: HashMap<String, String>,
+}
+
+impl AppAliases {
+    pub fn load(storage: &mut dyn Storage) -> AppResult<Self> {
+        Ok(Self::from_metadata(&storage.query_app_metadata()?))
+    }
+
+    /// Names set by the user win over ones read from executables (app names are case-insensitive).
+    pub fn from_metadata(entries: &[AppMetadata]) -> Self {
+        let mut names = HashMap::new();
+        for source in [AppMetadataSource::VersionInfo, AppMetadataSource::User] {
+            for entry in entries.iter().filter(|entry| entry.source == source) {
+                if let Some(display_name) = entry.display_name.as_deref().filter(|name| !name.trim().is_empty()) {
+                    names.insert(entry.app_name.to_lowercase(), display_name.to_string());
+                }
+            }
+        }
+        AppAliases { names }
+    }
+
+    /// The friendly name of `app_name`, or `app_name` itself when it has none.
+    pub fn display<'a>(&'a self, app_name: &'a str) -> &'a str {
+        self.names.get(&app_name.to_lowercase()).map_or(app_name, String::as_str)
+    }
+
+    /// Renames the apps of per-app totals, adding up apps that share a friendly name.
+    pub fn apply_to_totals(&self, totals: Vec<(String, i64)>) -> Vec<(String, i64)> {
+        let mut merged: Vec<(String, i64)> = Vec::with_capacity(totals.len());
+        for (app_name, secs) in totals {
+            let name = self.display(&app_name).to_string();
+            match merged.iter_mut().find(|(existing, _)| *existing == name) {
+                Some((_, total)) => *total += secs,
+                None => merged.push((name, secs)),
+            }
+        }
+        merged
+    }
+}
+
+/// Fills `app_metadata` from executables' version resources as new apps show up (Windows only).
+#[derive(Debug)]
+pub struct MetadataCollector {
+    /// Apps already described (stored or looked up this session)
+    known: HashSet<String>,
+}
+
+impl MetadataCollector {
+    /// None on platforms without version resources.
+    pub fn start(storage: &mut dyn Storage) -> Option<Self> {
+        if !cfg!(target_os = "windows") {
+            return None;
+        }
+        let known = match storage.query_app_metadata() {
+            Ok(entries) => entries.into_iter().map(|entry| entry.app_name.to_lowercase()).collect(),
+            Err(e) => {
+                log::warn!("Reading app metadata failed: {}", e);
+                HashSet::new()
+            }
+        };
+        Some(MetadataCollector { known })
+    }
+
+    /// Looks up `app_name` the first time it is seen and stores its friendly name, if the executable has one.
+    pub fn observe(&mut self, storage: &mut dyn Storage, app_name: &str, now: i64) {
+        if !self.known.insert(app_name.to_lowercase()) {
+            return;
+        }
+        let Some(metadata) = read_version_info(app_name) else { return };
+        match storage.upsert_app_metadata(&metadata, now) {
+            Ok(()) => log::debug!("Recorded friendly name {:?} for {}.", metadata.display_name, app_name),
+            Err(e) => log::warn!("Storing app metadata for {} failed: {}", app_name, e),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_version_info(app_name: &str) -> Option<AppMetadata> {
+    let path = crate::windows_api::executable_path(app_name)?;
+    let description = crate::windows_api::executable_description(&path)?;
+    Some(AppMetadata {
+        app_name: app_name.to_string(),
+        display_name: Some(description),
+        icon_path: Some(path.to_string_lossy().into_owned()),
+        source: AppMetadataSource::VersionInfo,
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_version_info(_app_name: &str) -> Option<AppMetadata> {
+    None
+}
@@ -0,0 +1,106 @@
+// src/break_reminder.rs
+// Break reminders: the detection thread reports on every poll whether the user is active (an app is
+// detected, not idle, locked or paused). After `break_minutes` of inactivity the count restarts;
+// after `after_minutes` of activity without such a break a desktop notification suggests one.
+// Reminders, and whether a break started within `grace_minutes` of them, go to `break_reminders`.
+
+use crate::config::BreakReminderConfig;
+use crate::recap::{self, RecapNotification};
+use std::thread;
+
+/// What the persistence thread records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakEvent {
+    /// A reminder was shown at `at`, after `active_secs` of continuous activity
+    Reminded { at: i64, active_secs: i64 },
+    /// The reminder shown at `reminded_at` was followed by a break starting at `break_start`
+    Taken { reminded_at: i64, break_start: i64 },
+}
+
+/// Tracks the current stretch of activity from the samples of the detection thread.
+#[derive(Debug)]
+pub struct BreakReminder {
+    after_secs: i64,
+    break_secs: i64,
+    grace_secs: i64,
+    /// Start of the current stretch of activity (None = on a break)
+    active_since: Option<i64>,
+    /// Last poll that saw activity
+    last_active: Option<i64>,
+    /// Last reminder of the current stretch; the next one is due `after_secs` later
+    reminded_at: Option<i64>,
+}
+
+impl BreakReminder {
+    /// None when `break_reminder` is not configured.
+    pub fn from_config(config: Option<&BreakReminderConfig>) -> Option<Self> {
+        let config = config?;
+        Some(BreakReminder {
+            after_secs: config.after_minutes as i64 * 60,
+            break_secs: config.break_minutes as i64 * 60,
+            grace_secs: config.grace_minutes as i64 * 60,
+            active_since: None,
+            last_active: None,
+            reminded_at: None,
+        })
+    }
+
+    /// Feeds one poll; shows the reminder when it is due. A sleep or a stopped detection loop
+    /// longer than `break_minutes` between two active polls counts as a break.
+    pub fn observe(&mut self, active: bool, now: i64) -> Option<BreakEvent> {
+        let inactive_since = self.last_active.unwrap_or(now);
+        if now - inactive_since >= self.break_secs && self.active_since.is_some() {
+            let taken = self.end_stretch(inactive_since);
+            if active {
+                self.start_stretch(now);
+            }
+            return taken;
+        }
+        if !active {
+            return None;
+        }
+        if self.active_since.is_none() {
+            self.start_stretch(now);
+        }
+        self.last_active = Some(now);
+        let since = self.reminded_at.or(self.active_since).unwrap_or(now);
+        if now - since < self.after_secs {
+            return None;
+        }
+        let active_secs = now - self.active_since.unwrap_or(now);
+        self.reminded_at = Some(now);
+        notify(active_secs);
+        Some(BreakEvent::Reminded { at: now, active_secs })
+    }
+
+    fn start_stretch(&mut self, now: i64) {
+        self.active_since = Some(now);
+        self.last_active = Some(now);
+        self.reminded_at = None;
+    }
+
+    /// The break that began at `break_start`; taken if it followed the last reminder closely enough.
+    fn end_stretch(&mut self, break_start: i64) -> Option<BreakEvent> {
+        self.active_since = None;
+        self.last_active = None;
+        let reminded_at = self.reminded_at.take()?;
+        (break_start - reminded_at <= self.grace_secs).then_some(BreakEvent::Taken { reminded_at, break_start })
+    }
+}
+
+/// Shows the reminder on its own thread so a slow notifier never delays detection.
+fn notify(active_secs: i64) {
+    let notification = RecapNotification {
+        title: "Time for a break".to_string(),
+        body: format!("You have been active for {} min without a break. Stand up, stretch and rest your eyes.", active_secs / 60),
+        report: None,
+    };
+    let spawned = thread::Builder::new().name("break-reminder".to_string()).spawn(move || {
+        if let Err(e) = recap::show_notification(&notification) {
+            log::warn!("Break reminder notification failed: {}", e);
+        }
+    });
+    if let Err(e) = spawned {
+        log::warn!("Could not show the break reminder: {}", e);
+    }
+}
@@ -0,0 +1,430 @@
+// src/calendar.rs
+// Meetings from a calendar (`calendar` in config.json): events are read from a local .ics file or a
+// CalDAV collection and stored in `calendar_events`. Aggregation (`daily_meeting_summary`) and
+// `stats --level meetings` count tracked time overlapping an event under the event's title.
+// All-day, cancelled and free ("transparent") events are not meetings and are skipped.
+
+use crate::commands::set_key::load_api_key;
+use crate::config::{AppConfig, CalendarConfig};
+use crate::errors::{AppError, AppResult};
+use crate::persistence::Storage;
+use crate::timezone::ReportTimeZone;
+use crate::types::{ApiKeyType, CalendarEvent};
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use jiff::{Span, Timestamp};
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+/// Default of `calendar.refresh_minutes`
+pub const DEFAULT_REFRESH_MINUTES: u64 = 15;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Occurrences generated per recurring event at most (rules without COUNT or UNTIL run forever)
+const MAX_OCCURRENCES: usize = 5000;
+
+/// Reads the events of the local days from `days_back` days ago through tomorrow and replaces the
+/// stored ones starting in that window; returns the number of events stored.
+pub fn refresh(app_config: &AppConfig, storage: &mut dyn Storage, now: i64, days_back: u32) -> AppResult<usize> {
+    let calendar = app_config
+        .calendar
+        .as_ref()
+        .ok_or_else(|| AppError::Config("No calendar configured; set \"calendar\" in config.json.".to_string()))?;
+    let timezone = &app_config.timezone;
+    let mut from = timezone.day_start(now);
+    for _ in 0..days_back {
+        from = timezone.previous_day_start(from);
+    }
+    let to = timezone.next_day_start(timezone.next_day_start(now));
+    let mut events = fetch(app_config, calendar, from, to)?;
+    // Events that started earlier were stored by an earlier refresh
+    events.retain(|event| event.start_time >= from && event.start_time < to);
+    storage.replace_calendar_events(from, to, &events)?;
+    Ok(events.len())
+}
+
+/// Occurrences of the calendar's events overlapping [from, to).
+pub fn fetch(app_config: &AppConfig, calendar: &CalendarConfig, from: i64, to: i64) -> AppResult<Vec<CalendarEvent>> {
+    let timezone = &app_config.timezone;
+    if let Some(path) = &calendar.ics_file {
+        let text = std::fs::read_to_string(path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+        return Ok(parse_ics(&text, from, to, timezone));
+    }
+    let url = calendar.caldav_url.as_deref().unwrap_or_default();
+    let mut events = Vec::new();
+    for text in fetch_caldav(app_config, calendar, url, from, to)? {
+        events.extend(parse_ics(&text, from, to, timezone));
+    }
+    events.sort_by_key(|event| event.start_time);
+    Ok(events)
+}
+
+/// Reads today's events once with the stored password (`config test-key caldav`).
+pub fn test_credentials(app_config: &AppConfig) -> AppResult<()> {
+    let calendar = app_config
+        .calendar
+        .as_ref()
+        .filter(|calendar| calendar.caldav_url.is_some())
+        .ok_or_else(|| AppError::Config("No CalDAV calendar configured; set \"calendar.caldav_url\" in config.json.".to_string()))?;
+    let now = chrono::Utc::now().timestamp();
+    let from = app_config.timezone.day_start(now);
+    let events = fetch(app_config, calendar, from, app_config.timezone.next_day_start(now))?;
+    println!("CalDAV server answered with {} event(s) today.", events.len());
+    Ok(())
+}
+
+/// The iCalendar objects of the collection's events overlapping [from, to), through a CalDAV
+/// `calendar-query` REPORT. Servers are asked to expand recurring events; `parse_ics` expands
+/// the ones they return unexpanded.
+fn fetch_caldav(app_config: &AppConfig, calendar: &CalendarConfig, url: &str, from: i64, to: i64) -> AppResult<Vec<String>> {
+    let (start, end) = (caldav_time(from), caldav_time(to));
+    let body = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">"#,
+            r#"<D:prop><C:calendar-data><C:expand start="{0}" end="{1}"/></C:calendar-data></D:prop>"#,
+            r#"<C:filter><C:comp-filter name="VCALENDAR"><C:comp-filter name="VEVENT">"#,
+            r#"<C:time-range start="{0}" end="{1}"/>"#,
+            r#"</C:comp-filter></C:comp-filter></C:filter></C:calendar-query>"#
+        ),
+        start, end
+    );
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Integration(e.to_string()))?;
+    let method = reqwest::Method::from_bytes(b"REPORT").map_err(|e| AppError::Integration(e.to_string()))?;
+    let mut request = client
+        .request(method, url)
+        .header("Depth", "1")
+        .header(reqwest::header::CONTENT_TYPE, "application/xml; charset=utf-8")
+        .body(body);
+    if let Some(username) = &calendar.username {
+        request = request.basic_auth(username, Some(load_api_key(app_config, ApiKeyType::Caldav)?));
+    }
+    let response = request.send().map_err(|e| AppError::Integration(format!("CalDAV request to {} failed: {}", url, e)))?;
+    let status = response.status();
+    let text = response.text().map_err(|e| AppError::Integration(format!("CalDAV server sent an unreadable response: {}", e)))?;
+    if !status.is_success() {
+        return Err(AppError::Integration(format!("CalDAV server returned HTTP {} for {}", status, url)));
+    }
+    let calendar_data = Regex::new(r"(?s)<(?:[\w-]+:)?calendar-data[^>]*>(.*?)</(?:[\w-]+:)?calendar-data>")
+        .map_err(|e| AppError::Unexpected(e.to_string()))?;
+    Ok(calendar_data.captures_iter(&text).map(|captures| unescape_xml(&captures[1])).collect())
+}
+
+/// UTC time as CalDAV expects it ("20261016T000000Z").
+fn caldav_time(timestamp: i64) -> String {
+    Timestamp::from_second(timestamp).map_or_else(|_| "19700101T000000Z".to_string(), |time| time.strftime("%Y%m%dT%H%M%SZ").to_string())
+}
+
+fn unescape_xml(text: &str) -> String {
+    let text = text.trim();
+    if let Some(cdata) = text.strip_prefix("<![CDATA[").and_then(|rest| rest.strip_suffix("]]>")) {
+        return cdata.to_string();
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#13;", "\r")
+        .replace("&#xD;", "\r")
+        .replace("&amp;", "&")
+}
+
+/// A DTSTART/DTEND/RECURRENCE-ID/EXDATE value: a local date and time in the zone it was given in.
+#[derive(Debug, Clone)]
+struct IcsTime {
+    datetime: DateTime,
+    zone: TimeZone,
+    all_day: bool,
+}
+
+impl IcsTime {
+    fn timestamp(&self) -> Option<i64> {
+        self.datetime.to_zoned(self.zone.clone()).ok().map(|zoned| zoned.timestamp().as_second())
+    }
+}
+
+/// The properties of one VEVENT that matter here.
+#[derive(Debug, Default)]
+struct RawEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    start: Option<IcsTime>,
+    end: Option<IcsTime>,
+    duration: Option<i64>,
+    rrule: Option<String>,
+    exdates: Vec<i64>,
+    recurrence_id: Option<i64>,
+    /// STATUS:CANCELLED or TRANSP:TRANSPARENT
+    skipped: bool,
+}
+
+/// Occurrences of the VEVENTs in `text` overlapping [from, to), recurring events expanded. Times
+/// without a zone, or with a TZID that is not an IANA name, are read in `timezone`.
+pub fn parse_ics(text: &str, from: i64, to: i64, timezone: &ReportTimeZone) -> Vec<CalendarEvent> {
+    let mut raw_events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // Components nested in a VEVENT (VALARM) have properties of their own
+    let mut nested = 0;
+    for line in unfold(text) {
+        let Some((name, params, value)) = split_property(&line) else { continue };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(RawEvent::default()),
+            ("END", "VEVENT") => raw_events.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {}
+        }
+        let Some(event) = current.as_mut().filter(|_| nested == 0) else { continue };
+        match name.as_str() {
+            "UID" => event.uid = Some(value.to_string()),
+            "SUMMARY" => event.summary = Some(unescape_text(value)),
+            "DTSTART" => event.start = parse_time(value, &params, timezone),
+            "DTEND" => event.end = parse_time(value, &params, timezone),
+            "DURATION" => event.duration = parse_duration(value),
+            "RRULE" => event.rrule = Some(value.to_string()),
+            "EXDATE" => event
+                .exdates
+                .extend(value.split(',').filter_map(|date| parse_time(date, &params, timezone)?.timestamp())),
+            "RECURRENCE-ID" => event.recurrence_id = parse_time(value, &params, timezone).and_then(|time| time.timestamp()),
+            "STATUS" if value.eq_ignore_ascii_case("CANCELLED") => event.skipped = true,
+            "TRANSP" if value.eq_ignore_ascii_case("TRANSPARENT") => event.skipped = true,
+            _ => {}
+        }
+    }
+
+    // Occurrences moved or cancelled by an override (an event with the same UID and a RECURRENCE-ID)
+    let overridden: HashSet<(String, i64)> = raw_events
+        .iter()
+        .filter_map(|event| Some((event.uid.clone()?, event.recurrence_id?)))
+        .collect();
+    let mut events = Vec::new();
+    for raw in raw_events {
+        let Some(start) = raw.start.as_ref().filter(|start| !start.all_day) else { continue };
+        let Some(start_time) = start.timestamp() else { continue };
+        let duration = match (&raw.end, raw.duration) {
+            (Some(end), _) => end.timestamp().map_or(0, |end_time| end_time - start_time),
+            (None, Some(duration)) => duration,
+            (None, None) => 0,
+        };
+        if raw.skipped || duration <= 0 {
+            continue;
+        }
+        let title = raw.summary.clone().filter(|summary| !summary.trim().is_empty()).unwrap_or_else(|| "(no title)".to_string());
+        let uid = raw.uid.clone().unwrap_or_else(|| format!("{}@{}", title, start_time));
+        let starts = match (&raw.rrule, raw.recurrence_id) {
+            (Some(rrule), None) => occurrences(start, rrule, to),
+            _ => vec![start_time],
+        };
+        for occurrence in starts {
+            let is_override_target = raw.recurrence_id.is_none() && overridden.contains(&(uid.clone(), occurrence));
+            if is_override_target || raw.exdates.contains(&occurrence) {
+                continue;
+            }
+            if occurrence < to && occurrence + duration > from {
+                events.push(CalendarEvent { uid: uid.clone(), title: title.clone(), start_time: occurrence, end_time: occurrence + duration });
+            }
+        }
+    }
+    events.sort_by_key(|event| event.start_time);
+    events
+}
+
+/// Content lines with folded continuation lines (starting with a space or tab) joined back.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// "DTSTART;TZID=Europe/Paris:20261016T090000" -> ("DTSTART", {"TZID": "Europe/Paris"}, "20261016T090000")
+fn split_property(line: &str) -> Option<(String, BTreeMap<String, String>, &str)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(index, c)| {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => return Some(index),
+            _ => {}
+        }
+        None
+    })?;
+    let (head, value) = (&line[..colon], line[colon + 1..].trim_end());
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value))
+}
+
+/// SUMMARY text with its escapes resolved (line breaks become spaces).
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => text.push(' '),
+                Some(escaped) => text.push(escaped),
+                None => {}
+            },
+            (c, false) => text.push(c),
+        }
+    }
+    text.trim().to_string()
+}
+
+/// "20261016T090000Z" (UTC), "20261016T090000" with or without TZID, or "20261016" (all day).
+fn parse_time(value: &str, params: &BTreeMap<String, String>, timezone: &ReportTimeZone) -> Option<IcsTime> {
+    let value = value.trim();
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i16>().ok();
+    let date = Date::new(number(0..4)?, number(4..6)? as i8, number(6..8)? as i8).ok()?;
+    if value.len() == 8 || params.get("VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE")) {
+        return Some(IcsTime { datetime: date.at(0, 0, 0, 0), zone: timezone.zone().clone(), all_day: true });
+    }
+    if value.get(8..9) != Some("T") {
+        return None;
+    }
+    let datetime = date.at(number(9..11)? as i8, number(11..13)? as i8, number(13..15)? as i8, 0);
+    let zone = if value.ends_with('Z') {
+        TimeZone::UTC
+    } else {
+        match params.get("TZID") {
+            // Windows zone names (Outlook exports) are not IANA names; read those in `timezone`
+            Some(tzid) => TimeZone::get(tzid).unwrap_or_else(|_| {
+                log::debug!("Unknown calendar time zone {:?}; using {}", tzid, timezone.name());
+                timezone.zone().clone()
+            }),
+            None => timezone.zone().clone(),
+        }
+    };
+    Some(IcsTime { datetime, zone, all_day: false })
+}
+
+/// "PT1H30M", "P1D", "P1W" in seconds.
+fn parse_duration(value: &str) -> Option<i64> {
+    let (sign, rest) = match value.trim().strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim().trim_start_matches('+')),
+    };
+    let mut secs = 0;
+    let mut digits = String::new();
+    for c in rest.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            'T' => {}
+            unit => {
+                let count: i64 = digits.parse().ok()?;
+                digits.clear();
+                secs += count
+                    * match unit {
+                        'W' => 7 * 86_400,
+                        'D' => 86_400,
+                        'H' => 3600,
+                        'M' => 60,
+                        'S' => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    Some(sign * secs)
+}
+
+/// Start times of a recurring event's occurrences before `to` (RRULE with FREQ DAILY, WEEKLY with
+/// BYDAY, MONTHLY or YEARLY on the start's day, INTERVAL, COUNT and UNTIL). Occurrences keep the
+/// local time of the start in its zone across DST changes. Other rules yield only the first occurrence.
+fn occurrences(start: &IcsTime, rrule: &str, to: i64) -> Vec<i64> {
+    let rule: BTreeMap<String, String> = rrule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim().to_ascii_uppercase()))
+        .collect();
+    let interval = rule.get("INTERVAL").and_then(|interval| interval.parse::<i64>().ok()).filter(|interval| *interval > 0).unwrap_or(1);
+    let count = rule.get("COUNT").and_then(|count| count.parse::<usize>().ok());
+    let until = rule.get("UNTIL").and_then(|until| {
+        let time = parse_time(until, &BTreeMap::new(), &ReportTimeZone::default())?;
+        let time = if until.ends_with('Z') { time } else { IcsTime { zone: start.zone.clone(), ..time } };
+        // A date-only UNTIL includes that whole day
+        Some(time.timestamp()? + if time.all_day { 86_399 } else { 0 })
+    });
+    let date = start.datetime.date();
+    let steps = 0..MAX_OCCURRENCES as i64;
+    let unsupported = ["BYSETPOS", "BYHOUR", "BYMINUTE", "BYYEARDAY", "BYWEEKNO", "BYMONTH"].iter().any(|key| rule.contains_key(*key));
+    let by_day = rule.get("BYDAY");
+
+    // Each step yields the dates of one period; an empty period (the 31st of a shorter month) yields none
+    let dates: Vec<Date> = match rule.get("FREQ").map(String::as_str) {
+        _ if unsupported => Vec::new(),
+        Some("DAILY") if by_day.is_none() => {
+            steps.map_while(|step| date.checked_add(Span::new().days(step * interval)).ok()).collect()
+        }
+        Some("WEEKLY") => {
+            let mut weekdays: Vec<i64> = match by_day {
+                Some(days) => days.split(',').filter_map(|day| weekday_offset(day.trim())).collect(),
+                None => vec![i64::from(date.weekday().to_monday_zero_offset())],
+            };
+            weekdays.sort_unstable();
+            weekdays.dedup();
+            let monday = date.checked_sub(Span::new().days(i64::from(date.weekday().to_monday_zero_offset()))).ok();
+            steps
+                .map_while(|step| monday?.checked_add(Span::new().weeks(step * interval)).ok())
+                .flat_map(|week| weekdays.iter().filter_map(move |offset| week.checked_add(Span::new().days(*offset)).ok()).collect::<Vec<_>>())
+                .filter(|candidate| *candidate >= date)
+                .collect()
+        }
+        Some("MONTHLY") if by_day.is_none() => {
+            let day = rule.get("BYMONTHDAY").and_then(|day| day.parse::<i8>().ok()).unwrap_or(date.day());
+            let first_month = i64::from(date.year()) * 12 + i64::from(date.month() - 1);
+            steps
+                .map_while(|step| {
+                    let month = first_month + step * interval;
+                    Some(Date::new(i16::try_from(month / 12).ok()?, (month % 12 + 1) as i8, day).ok())
+                })
+                .flatten()
+                .collect()
+        }
+        Some("YEARLY") if by_day.is_none() => steps
+            .map_while(|step| Some(Date::new(i16::try_from(i64::from(date.year()) + step * interval).ok()?, date.month(), date.day()).ok()))
+            .flatten()
+            .collect(),
+        _ => Vec::new(),
+    };
+    if dates.is_empty() {
+        log::debug!("Unsupported recurrence rule {:?}; only its first occurrence is counted", rrule);
+        return start.timestamp().into_iter().collect();
+    }
+    expand(start, dates, count, until, to)
+}
+
+/// Start times for the occurrence dates, in order, until COUNT, UNTIL or `to` is reached.
+fn expand(start: &IcsTime, dates: Vec<Date>, count: Option<usize>, until: Option<i64>, to: i64) -> Vec<i64> {
+    let time = start.datetime.time();
+    let mut times = Vec::new();
+    for (index, date) in dates.into_iter().enumerate() {
+        if count.is_some_and(|count| index >= count) {
+            break;
+        }
+        let Some(timestamp) = date.to_datetime(time).to_zoned(start.zone.clone()).ok().map(|zoned| zoned.timestamp().as_second()) else {
+            continue;
+        };
+        if timestamp >= to || until.is_some_and(|until| timestamp > until) {
+            break;
+        }
+        times.push(timestamp);
+    }
+    times
+}
+
+/// "MO" -> 0 ... "SU" -> 6
+fn weekday_offset(day: &str) -> Option<i64> {
+    ["MO", "TU", "WE", "TH", "FR", "SA", "SU"].iter().position(|name| *name == day).map(|index| index as i64)
+}
@@ -0,0 +1,400 @@
+// src/cli.rs
+// Command-line definitions, kept in the library so completions and man pages can be generated from them.
+
+use crate::types::{AggregationLevel, DetectorKind, DurationFormat, IntervalStream, TimePeriod};
+use clap::Parser;
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Tracks application and window usage time.", long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+
+    /// Increase logging verbosity (-v, -vv, -vvv)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Plain output without colors or bars (also set by the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// How durations are printed (default: `duration_format` in config.json, else clock)
+    #[arg(long, global = true, value_enum)]
+    pub duration_format: Option<DurationFormat>,
+
+    /// Use this profile's config.json, database and keyring keys (see `profile list`)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+}
+
+
+#[derive(clap::Subcommand, Debug)]
+pub enum Commands {
+    /// Start tracking application usage
+    Track {
+        /// Show a system tray icon with Pause/Resume/Open Stats/Quit (Windows only)
+        #[arg(long)]
+        tray: bool,
+        /// Run in the background without console output (used by autostart)
+        #[arg(long)]
+        daemon: bool,
+        /// Where activity comes from; `mock` replays --script (development and CI)
+        #[arg(long, value_enum, default_value_t = DetectorKind::Os)]
+        detector: DetectorKind,
+        /// Replay script for `--detector mock`: JSON steps or CSV rows (see README)
+        #[arg(long, value_name = "FILE", required_if_eq("detector", "mock"))]
+        script: Option<PathBuf>,
+        /// Stream interval start/end events as JSON lines to FILE (appended), or to stdout with `-`
+        #[arg(long, value_name = "FILE|-")]
+        emit_events: Option<PathBuf>,
+        /// Run the tracker as a child process and restart it when it crashes or its detection hangs (used by autostart)
+        #[arg(long)]
+        supervise: bool,
+        /// Heartbeat file written for the supervising watchdog (set by --supervise)
+        #[arg(long, value_name = "FILE", hide = true, conflicts_with = "supervise")]
+        watchdog_heartbeat: Option<PathBuf>,
+    },
+    /// Show usage statistics
+    Stats {
+        /// How to group usage time (`--group-by` is an alias)
+        #[arg(short, long, visible_alias = "group-by", value_enum, default_value_t = AggregationLevel::ByApplication)]
+        level: AggregationLevel,
+        /// Only show the N entries with the most time
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+        /// Only include applications matching this glob (case-insensitive, e.g. "chrome*")
+        #[arg(long = "app", value_name = "GLOB")]
+        app_glob: Option<String>,
+        /// Only include windows whose title contains this text (case-insensitive)
+        #[arg(long, value_name = "TEXT")]
+        title_contains: Option<String>,
+        /// Hide entries with less total time than this many seconds
+        #[arg(long = "min-duration", value_name = "SECS")]
+        min_duration_secs: Option<i64>,
+        /// Leave out passive time (full-screen media/meeting apps)
+        #[arg(long)]
+        exclude_passive: bool,
+        /// Only count time recorded for this OS user (default with `shared_database_path`: yourself)
+        #[arg(long, value_name = "NAME")]
+        user: Option<String>,
+        /// With `shared_database_path`: count the time of every user
+        #[arg(long, conflicts_with = "user")]
+        all_users: bool,
+        /// Explore interactively: switch periods, drill from apps to titles to raw intervals
+        #[arg(short, long)]
+        interactive: bool,
+        /// Cut longer window titles to this many terminal columns (ending with "…")
+        #[arg(long, value_name = "COLUMNS")]
+        max_title_width: Option<usize>,
+        /// Only show this period (default: today, the last completed hour and the current hour)
+        #[arg(long, value_enum)]
+        period: Option<TimePeriod>,
+        /// With `--level profile`: weekdays and weekends side by side
+        #[arg(long)]
+        split_weekend: bool,
+        /// With `pseudonymize`: show the names listed in `pseudonymize.reveal` instead of their hashes
+        #[arg(long)]
+        reveal: bool,
+        /// Which intervals to count: the foreground window, or apps playing audio (`audio_tracking`)
+        #[arg(long, value_enum, default_value_t)]
+        stream: IntervalStream,
+    },
+    /// Open a window with live activity, usage charts and settings (needs the `gui` feature)
+    Gui,
+    /// Aggregate old data and cleanup database (usually run automatically)
+    Aggregate {
+        /// Report how many rows would be summarized and deleted, then roll everything back
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show stored titles the `title_rules` in config.json would rewrite
+    Normalize {
+        /// Rewrite them in the raw intervals, the archive and the summaries
+        #[arg(long)]
+        apply_history: bool,
+    },
+    /// Initialize or update the database schema
+    InitDb,
+    /// Check the database, detector, platform APIs and keyring, and suggest fixes
+    Doctor,
+    /// Show figures about the tracker itself (days tracked, database size, errors) for bug reports
+    SelfStats,
+    /// Show whether a tracker is running, its state and its detection retry counters
+    Status {
+        /// Also show the health of the tracking loop: detection and database write latency, missed polls, errors
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Register or remove the tracker as a login item
+    Autostart {
+        #[command(subcommand)]
+        autostart_command: crate::types::AutostartCommand,
+    },
+    /// Inspect context snapshots of open windows
+    Snapshots {
+        #[command(subcommand)]
+        snapshots_command: crate::types::SnapshotsCommand,
+    },
+    /// Record time the tracker could not see (offline work, meetings away from the computer)
+    AddEntry {
+        /// Application/activity name, e.g. "Meeting"
+        #[arg(long)]
+        app: String,
+        /// Start, local time: "HH:MM", "YYYY-MM-DD HH:MM" or RFC 3339
+        #[arg(long)]
+        from: String,
+        /// End, same formats as --from
+        #[arg(long)]
+        to: String,
+        /// Window title to record (shown in detailed stats)
+        #[arg(long)]
+        title: Option<String>,
+        /// Add the entry even if it overlaps tracked activity
+        #[arg(long)]
+        force: bool,
+    },
+    /// Review a day's untracked gaps and unidentified intervals one by one, assigning each to an app
+    /// or a category, or marking it as a break
+    Audit {
+        /// Local day to review: "yesterday", "today" or YYYY-MM-DD
+        #[arg(long, default_value = "yesterday")]
+        date: String,
+        /// Skip gaps and intervals shorter than this many minutes
+        #[arg(long, value_name = "N", default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..=1440))]
+        min_minutes: u64,
+    },
+    /// List the periods of a day where nothing was recorded and why: tracker not running or crashed,
+    /// system asleep, paused, outside tracking_hours, or running with nothing detected
+    Gaps {
+        /// Local day: "today", "yesterday" or YYYY-MM-DD
+        #[arg(long, default_value = "today")]
+        date: String,
+        /// Skip gaps shorter than this many minutes
+        #[arg(long, value_name = "N", default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..=1440))]
+        min_minutes: u64,
+    },
+    /// Permanently delete tracked data from every table: everything, or one app's (optionally within a range)
+    Purge {
+        /// Delete all data, in every table
+        #[arg(long, conflicts_with_all = ["app", "from", "to"], required_unless_present = "app")]
+        all: bool,
+        /// Delete the data of this app (exact name as recorded, e.g. "chrome.exe")
+        #[arg(long, value_name = "NAME")]
+        app: Option<String>,
+        /// Only data starting at or after this local time: "HH:MM", "YYYY-MM-DD [HH:MM]" or RFC 3339
+        #[arg(long, requires = "app")]
+        from: Option<String>,
+        /// Only data starting before this time, same formats as --from
+        #[arg(long, requires = "app")]
+        to: Option<String>,
+        /// Do not ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Inspect and correct raw tracked intervals
+    Intervals {
+        #[command(subcommand)]
+        intervals_command: crate::types::IntervalsCommand,
+    },
+    /// Query raw intervals kept after aggregation (`archive_raw_intervals`)
+    Archive {
+        #[command(subcommand)]
+        archive_command: crate::types::ArchiveCommand,
+    },
+    /// Separate configs and databases, e.g. "work" and "personal" (select with --profile)
+    Profile {
+        #[command(subcommand)]
+        profile_command: crate::types::ProfileCommand,
+    },
+    /// Per-application settings such as friendly names
+    App {
+        #[command(subcommand)]
+        app_command: crate::types::AppCommand,
+    },
+    /// Daily goals on app time and their streaks
+    ///
+    /// Example: `mouse_tracking habit add coding --app "code*" --min-minutes 60`
+    Habit {
+        #[command(subcommand)]
+        habit_command: crate::types::HabitCommand,
+    },
+    /// Calendar events whose overlapping time counts as meetings (`stats --level meetings`)
+    Calendar {
+        #[command(subcommand)]
+        calendar_command: crate::types::CalendarCommand,
+    },
+    /// Database file maintenance (SQLCipher encryption, integrity check, VACUUM and backups)
+    Db {
+        #[command(subcommand)]
+        db_command: crate::types::DbCommand,
+    },
+    /// Jobs the running tracker starts on a schedule (aggregate, backup, sync, saved reports)
+    ///
+    /// Example: `mouse_tracking schedule next`
+    Schedule {
+        #[command(subcommand)]
+        schedule_command: crate::types::ScheduleCommand,
+    },
+    /// Upload tracked intervals to the configured sync server
+    Sync {
+        #[command(subcommand)]
+        sync_command: crate::types::SyncCommand,
+    },
+    /// Export a day's activity timeline (e.g. as a calendar file or a worklog)
+    Export {
+        #[arg(long, value_enum, default_value_t = crate::types::ExportFormat::Ics)]
+        format: crate::types::ExportFormat,
+        /// Local day to export as YYYY-MM-DD (default: today)
+        #[arg(long)]
+        date: Option<String>,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the summary of a period (total, top apps, categories), or post it to Slack/Discord;
+    /// `report run <NAME>` runs a saved report from the "reports" section of config.json
+    ///
+    /// Example: `mouse_tracking report --post slack`
+    #[command(args_conflicts_with_subcommands = true)]
+    Report {
+        #[command(subcommand)]
+        report_command: Option<crate::types::ReportCommand>,
+        #[arg(long, value_enum, default_value_t = crate::types::TimePeriod::Today)]
+        period: crate::types::TimePeriod,
+        /// Post to the webhook set in the "notify" section of config.json instead of printing (repeatable)
+        #[arg(long, value_enum)]
+        post: Vec<crate::types::PostTarget>,
+    },
+    /// Today at a glance: total and category time with bars and limits, pickups (app switches) and the longest streak
+    ///
+    /// Categories and limits come from the "screentime" section of config.json.
+    Screentime,
+    /// Run one read-only SQL statement against the SQLite database (custom reports)
+    ///
+    /// Example: `mouse_tracking query "SELECT app_name, SUM(total_duration_secs) FROM daily_summary GROUP BY 1" --format csv`
+    Query {
+        /// A single SELECT (or other read-only) statement
+        #[arg(required_unless_present = "schema")]
+        sql: Option<String>,
+        #[arg(long, value_enum, default_value_t = crate::types::QueryFormat::Table)]
+        format: crate::types::QueryFormat,
+        /// Describe the tables and their columns instead of running a query
+        #[arg(long, conflicts_with = "sql")]
+        schema: bool,
+    },
+    /// Answer a question about tracked time, e.g. "how much time did I spend in VS Code last Tuesday?"
+    ///
+    /// A language model turns the question into a stats query; only that query's numbers are printed.
+    Ask {
+        question: String,
+        /// Service that interprets the question (its key: `config set-key openai|anthropic`; ollama, a
+        /// local server at `ollama_url`, needs none). Default: `llm_provider` in config.json, else openai
+        #[arg(long, value_enum)]
+        provider: Option<crate::types::LlmProvider>,
+        /// Model name (default: gpt-4o-mini for OpenAI, claude-3-5-haiku-latest for Anthropic, llama3.2 for Ollama)
+        #[arg(long)]
+        model: Option<String>,
+    },
+    /// Push tracked time to Toggl Track
+    Toggl {
+        #[command(subcommand)]
+        toggl_command: crate::types::TogglCommand,
+    },
+    /// Print a shell completion script
+    ///
+    /// Example: `mouse_tracking completions bash > ~/.local/share/bash-completion/completions/mouse_tracking`
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print a snippet that makes the shell report its command and directory to the tracker
+    ///
+    /// Example: `eval "$(mouse_tracking shell-hook bash)"` in ~/.bashrc (needs "shell_hook" in config.json)
+    ShellHook {
+        #[arg(value_enum)]
+        shell: crate::types::HookShell,
+    },
+    /// Sent by the shell-hook snippet: the shell's PID, directory and current command
+    #[command(hide = true)]
+    ShellReport {
+        #[arg(long)]
+        pid: u32,
+        #[arg(long, default_value = "")]
+        cwd: String,
+        /// Command being run (absent = back at the prompt)
+        #[arg(long, allow_hyphen_values = true)]
+        command: Option<String>,
+        /// The shell is exiting
+        #[arg(long)]
+        exit: bool,
+    },
+    /// Generate the man page (stdout), or one page per subcommand into a directory
+    Manpage {
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+    /// Install the latest release from GitHub Releases, or a release archive downloaded by hand
+    ///
+    /// Example: `mouse_tracking update --from-file mouse_tracking-v0.3.0-x86_64-pc-windows-msvc.zip`
+    Update {
+        /// Install this release archive (keep its original file name) instead of downloading one
+        #[arg(long, value_name = "FILE")]
+        from_file: Option<PathBuf>,
+    },
+    /// Manage configuration (like API keys)
+    Config {
+        #[command(subcommand)]
+        config_command: crate::types::ConfigCommand,
+    },
+}
+
+impl Commands {
+    /// What the command does, for the administrator password prompt when the configuration is locked
+    /// (`config lock`); None for what the tracked user may still run: tracking, per-app stats, screen
+    /// time and status. `config lock`/`unlock` check the password themselves, and `profile` commands and
+    /// `--profile` are checked against the default profile's lock before a profile is loaded.
+    pub fn admin_action(&self) -> Option<&'static str> {
+        use crate::types::ConfigCommand;
+        match self {
+            Commands::Track { .. }
+            | Commands::Screentime
+            | Commands::Status { .. }
+            | Commands::SelfStats
+            | Commands::Gaps { .. }
+            | Commands::Doctor
+            | Commands::InitDb
+            | Commands::ShellHook { .. }
+            | Commands::ShellReport { .. }
+            | Commands::Completions { .. }
+            | Commands::Manpage { .. }
+            | Commands::Profile { .. } => None,
+            Commands::Stats { level: AggregationLevel::ByApplication, interactive: false, user: None, all_users: false, reveal: false, .. } => None,
+            Commands::Stats { .. } => Some("see detailed stats"),
+            Commands::Config { config_command: ConfigCommand::Lock | ConfigCommand::Unlock | ConfigCommand::LockStatus | ConfigCommand::ListKeys } => None,
+            Commands::Config { .. } => Some("manage keys"),
+            Commands::Db { db_command: crate::types::DbCommand::Path } => None,
+            Commands::Snapshots { .. } | Commands::Export { .. } | Commands::Query { .. } | Commands::Ask { .. } | Commands::Report { .. } => {
+                Some("see detailed activity")
+            }
+            Commands::Gui
+            | Commands::Aggregate { .. }
+            | Commands::Normalize { .. }
+            | Commands::Autostart { .. }
+            | Commands::AddEntry { .. }
+            | Commands::Audit { .. }
+            | Commands::Purge { .. }
+            | Commands::Intervals { .. }
+            | Commands::Archive { .. }
+            | Commands::App { .. }
+            | Commands::Habit { .. }
+            | Commands::Calendar { .. }
+            | Commands::Db { .. }
+            | Commands::Schedule { .. }
+            | Commands::Sync { .. }
+            | Commands::Toggl { .. }
+            | Commands::Update { .. } => Some("change settings or data"),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+// src/clock.rs
+// Wall-clock sanity checks for the tracker: interval times are wall-clock (Unix) seconds, the poll
+// loop runs on `Instant`. Comparing the two between polls exposes NTP steps, manual clock changes
+// and (where `Instant` stops while suspended) sleep.
+// Code that needs "now" takes a `Clock`, so hour/day rollovers, DST changes and clock jumps can be
+// simulated with a `ManualClock` instead of waiting for them.
+
+use chrono::Utc;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Source of the current wall-clock and monotonic time.
+pub trait Clock: Debug + Send + Sync {
+    /// Wall-clock time in Unix seconds
+    fn now(&self) -> i64;
+    /// Monotonic time (durations between polls, min_interval, heartbeats)
+    fn instant(&self) -> Instant;
+}
+
+/// The system clocks (`Utc::now`, `Instant::now`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The clock used when none is injected.
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a test can keep one and
+/// advance it while the code under test holds another.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    origin: Instant,
+    /// Monotonic seconds since `origin`
+    elapsed: Arc<AtomicI64>,
+    /// Wall-clock time at `origin`; changed by `set_wall_clock` without moving the monotonic clock
+    wall_origin: Arc<AtomicI64>,
+}
+
+impl ManualClock {
+    pub fn new(now: i64) -> Self {
+        ManualClock { origin: Instant::now(), elapsed: Arc::new(AtomicI64::new(0)), wall_origin: Arc::new(AtomicI64::new(now)) }
+    }
+
+    /// Lets `secs` pass on both clocks.
+    pub fn advance(&self, secs: i64) {
+        self.elapsed.fetch_add(secs.max(0), Ordering::SeqCst);
+    }
+
+    /// Sets the wall clock to `now` without any monotonic time passing (an NTP step or a manual
+    /// change, seen as a `ClockJump` by `ClockGuard`).
+    pub fn set_wall_clock(&self, now: i64) {
+        self.wall_origin.store(now - self.elapsed.load(Ordering::SeqCst), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> i64 {
+        self.wall_origin.load(Ordering::SeqCst) + self.elapsed.load(Ordering::SeqCst)
+    }
+
+    fn instant(&self) -> Instant {
+        self.origin + Duration::from_secs(self.elapsed.load(Ordering::SeqCst) as u64)
+    }
+}
+
+/// Wall-clock seconds beyond (or short of) the monotonic clock between two polls that count as a jump
+pub const CLOCK_JUMP_SECS: i64 = 10;
+
+/// A wall-clock discontinuity between two polls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ClockJump {
+    /// Wall-clock time of the last poll before the jump
+    pub before: i64,
+    /// Wall-clock time of the first poll after it
+    pub after: i64,
+    /// How far the wall clock moved beyond the monotonic clock (negative = set back)
+    pub offset_secs: i64,
+}
+
+impl ClockJump {
+    pub fn is_backward(&self) -> bool {
+        self.offset_secs < 0
+    }
+}
+
+/// Remembers the last poll's `Instant` and wall-clock time to spot jumps in between.
+#[derive(Debug, Default)]
+pub struct ClockGuard {
+    previous: Option<(Instant, i64)>,
+}
+
+impl ClockGuard {
+    /// Compares `now` with the last recorded poll. `Instant` does not advance while the system is
+    /// suspended on Linux and macOS, so sleep there shows up as a forward jump.
+    pub fn check(&self, now_instant: Instant, now_timestamp: i64) -> Option<ClockJump> {
+        let (previous_instant, previous_timestamp) = self.previous?;
+        let monotonic_secs = now_instant.saturating_duration_since(previous_instant).as_secs() as i64;
+        let offset_secs = now_timestamp - previous_timestamp - monotonic_secs;
+        (offset_secs.abs() > CLOCK_JUMP_SECS).then_some(ClockJump { before: previous_timestamp, after: now_timestamp, offset_secs })
+    }
+
+    pub fn record(&mut self, instant: Instant, timestamp: i64) {
+        self.previous = Some((instant, timestamp));
+    }
+
+    /// Wall-clock time of the last recorded poll.
+    pub fn previous_timestamp(&self) -> Option<i64> {
+        self.previous.map(|(_, timestamp)| timestamp)
+    }
+}
@@ -0,0 +1,108 @@
+// src/commands/add_entry.rs
+
+use crate::config::AppConfig;
+use crate::detection::LOCKED_APP_NAME;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::pseudonym::Pseudonymizer;
+use crate::types::{IntervalRecord, NewInterval};
+use crate::utils::{format_duration, format_local_timestamp, parse_user_time};
+use chrono::Utc;
+
+/// Tracked time overlapping a manual entry by up to this much is ignored (edges of a switch away)
+const OVERLAP_TOLERANCE_SECS: i64 = 60;
+
+/// Arguments of the `add-entry` command
+#[derive(Debug)]
+pub struct ManualEntry {
+    pub app: String,
+    pub title: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub force: bool,
+}
+
+/// Inserts a finished interval for time the tracker could not see (offline work, meetings...).
+pub fn execute(app_config: &AppConfig, entry: ManualEntry) -> AppResult<()> {
+    let app = entry.app.trim();
+    if app.is_empty() {
+        return Err(AppError::Config("--app must not be empty".to_string()));
+    }
+    let start = parse_user_time(&entry.from).map_err(AppError::Config)?.timestamp();
+    let end = parse_user_time(&entry.to).map_err(AppError::Config)?.timestamp();
+    let now = Utc::now().timestamp();
+    if start >= end {
+        return Err(AppError::Config("--from must be before --to".to_string()));
+    }
+    if end > now {
+        return Err(AppError::Config(format!("--to ({}) is in the future", format_local_timestamp(end))));
+    }
+
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+
+    // Locked-screen time is expected while away from the computer; anything else was really tracked
+    let overlapping: Vec<IntervalRecord> = storage
+        .query_intervals_between(start, end, now)?
+        .into_iter()
+        .filter(|interval| interval.app_name != LOCKED_APP_NAME)
+        .collect();
+    let overlap_secs: i64 = overlapping
+        .iter()
+        .map(|interval| interval.end_time.min(end) - interval.start_time.max(start))
+        .sum();
+    if overlap_secs > OVERLAP_TOLERANCE_SECS && !entry.force {
+        println!("{} of tracked activity already falls in this range:", format_duration(overlap_secs, app_config.duration_format));
+        for interval in &overlapping {
+            println!(
+                "  {:>8}  {} - {}  {} '{}'",
+                interval.id,
+                format_local_timestamp(interval.start_time),
+                format_local_timestamp(interval.end_time),
+                interval.app_name,
+                interval.detailed_title.as_deref().unwrap_or_default()
+            );
+        }
+        return Err(AppError::Config(
+            "Entry overlaps tracked data; fix it with `intervals delete/split` or pass --force to add it anyway".to_string(),
+        ));
+    }
+
+    let title = entry.title.as_deref().unwrap_or_default();
+    // With `pseudonymize` the entry is stored hashed, like tracked activity
+    let (stored_app, stored_title) = match app_config.pseudonymize {
+        Some(_) => {
+            let pseudonyms = Pseudonymizer::load(app_config)?;
+            (pseudonyms.hash(app), pseudonyms.hash(title))
+        }
+        None => (app.to_string(), title.to_string()),
+    };
+    let interval = NewInterval {
+        app_name: &stored_app,
+        main_title: &stored_title,
+        detailed_title: &stored_title,
+        virtual_desktop: None,
+        repo: None,
+        executable_path: None,
+        command_line: None,
+        ticket: None,
+        session_type: None,
+        remote_host: None,
+        document: None,
+        passive: false,
+        start_time: start,
+        device_id: &app_config.device_id,
+        user_name: &app_config.user_name,
+    };
+    let id = storage.add_manual_interval(&interval, end, now)?;
+    println!(
+        "Added interval {}: {} '{}' {} - {} ({})",
+        id,
+        app,
+        title,
+        format_local_timestamp(start),
+        format_local_timestamp(end),
+        format_duration(end - start, app_config.duration_format)
+    );
+    Ok(())
+}
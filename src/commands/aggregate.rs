@@ -0,0 +1,65 @@
+// src/commands/aggregate.rs
+// `aggregate`: rolls raw intervals into the summary tables; `--dry-run` only reports what would change.
+// While `track` runs, its gRPC API does the work when it is served; otherwise the run waits out the tracker's writes.
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use crate::grpc;
+use crate::instance;
+use crate::persistence;
+use crate::types::AggregationReport;
+
+pub fn execute(app_config: &AppConfig, dry_run: bool) -> AppResult<()> {
+    if let Some(tracker) = instance::running_tracker(app_config) {
+        if !dry_run && let Some(addr) = tracker.grpc_listen {
+            match grpc::aggregate_in_tracker(addr) {
+                Ok(true) => {
+                    println!("Aggregated by the running tracker ({}).", tracker.describe());
+                    return Ok(());
+                }
+                Ok(false) => {}
+                Err(e) => eprintln!("Warning: {}; aggregating here instead.", e),
+            }
+        }
+        eprintln!("Note: a tracker is running ({}); waiting for its writes when the database is busy.", tracker.describe());
+    }
+    let mut storage = persistence::open_storage(app_config)?;
+    if dry_run {
+        let report = storage.preview_aggregation(app_config.merge_gap_secs)?;
+        println!("Dry run for {} (nothing was changed):", persistence::describe_location(app_config));
+        print_report(&report);
+    } else {
+        let report = storage.aggregate_and_cleanup(app_config.merge_gap_secs)?;
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn print_report(report: &AggregationReport) {
+    let rows = [
+        ("Raw intervals merged", report.merged_intervals),
+        ("hourly_summary rows", report.hourly_rows),
+        ("daily_summary rows", report.daily_rows),
+        ("daily_desktop_summary rows", report.desktop_rows),
+        ("daily_device_summary rows", report.device_rows),
+        ("daily_repo_summary rows", report.repo_rows),
+        ("daily_path_summary rows", report.path_rows),
+        ("daily_meeting_summary rows", report.meeting_rows),
+        ("daily_ticket_summary rows", report.ticket_rows),
+        ("daily_user_summary rows", report.user_rows),
+        ("daily_remote_summary rows", report.remote_rows),
+        ("daily_document_summary rows", report.document_rows),
+        ("daily_audio_summary rows", report.audio_rows),
+        ("Audio intervals deleted", report.deleted_audio),
+        ("tracker_daily_stats rows", report.tracker_day_rows),
+        ("Raw intervals archived", report.archived_raw),
+        ("Raw intervals deleted", report.deleted_raw),
+        ("days_summary_by_app rows", report.days_summary_rows),
+        ("weeks_summary_by_app rows", report.weeks_summary_rows),
+        ("Old daily_summary rows deleted", report.deleted_daily),
+        ("Old hourly_summary rows deleted", report.deleted_hourly),
+    ];
+    for (label, count) in rows {
+        println!("  {:<32} {:>8}", label, count);
+    }
+}
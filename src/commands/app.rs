@@ -0,0 +1,73 @@
+// src/commands/app.rs
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::table::{Column, Table};
+use crate::types::{AliasCommand, AppCommand, AppMetadata, AppMetadataSource};
+use chrono::Utc;
+
+/// Execute per-application settings commands
+pub fn execute(app_config: &AppConfig, command: AppCommand) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    match command {
+        AppCommand::Alias { alias_command: AliasCommand::Set { app, name, icon } } => {
+            let (app, name) = (app.trim(), name.trim());
+            if app.is_empty() || name.is_empty() {
+                return Err(AppError::Config("Both the app and its friendly name must be given".to_string()));
+            }
+            if let Some(icon) = &icon
+                && !icon.is_file()
+            {
+                eprintln!("Warning: icon file {:?} does not exist.", icon);
+            }
+            // Reuse the stored spelling so one app never ends up with two rows
+            let app = storage
+                .query_app_metadata()?
+                .into_iter()
+                .find(|entry| entry.app_name.eq_ignore_ascii_case(app))
+                .map_or_else(|| app.to_string(), |entry| entry.app_name);
+            let metadata = AppMetadata {
+                app_name: app.clone(),
+                display_name: Some(name.to_string()),
+                icon_path: icon.map(|path| path.to_string_lossy().into_owned()),
+                source: AppMetadataSource::User,
+            };
+            storage.upsert_app_metadata(&metadata, Utc::now().timestamp())?;
+            println!("'{}' is now shown as '{}'.", app, name);
+        }
+        AppCommand::Alias { alias_command: AliasCommand::Remove { app } } => {
+            let matching: Vec<String> = storage
+                .query_app_metadata()?
+                .into_iter()
+                .filter(|entry| entry.app_name.eq_ignore_ascii_case(app.trim()))
+                .map(|entry| entry.app_name)
+                .collect();
+            if matching.is_empty() {
+                return Err(AppError::Config(format!("No friendly name is set for '{}'", app)));
+            }
+            for app_name in &matching {
+                storage.delete_app_metadata(app_name)?;
+            }
+            println!("'{}' is shown under its executable name again.", app);
+        }
+        AppCommand::Alias { alias_command: AliasCommand::List } => {
+            let entries = storage.query_app_metadata()?;
+            if entries.is_empty() {
+                println!("No friendly names set. Add one with: app alias set msedge.exe \"Microsoft Edge\"");
+                return Ok(());
+            }
+            let mut table = Table::new(vec![Column::new("Application").min_width(20), Column::new("Friendly Name")]);
+            for entry in entries {
+                let origin = match entry.source {
+                    AppMetadataSource::User => "",
+                    AppMetadataSource::VersionInfo => "  (from executable)",
+                };
+                table.add_row(vec![entry.app_name, format!("{}{}", entry.display_name.as_deref().unwrap_or("-"), origin)]);
+            }
+            table.print();
+        }
+    }
+    Ok(())
+}
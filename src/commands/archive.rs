@@ -0,0 +1,42 @@
+// src/commands/archive.rs
+// `archive query`: raw intervals moved to `app_intervals_archive` by aggregation.
+
+use crate::commands::intervals::print_interval_table;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::types::ArchiveCommand;
+use crate::utils::{format_local_timestamp, parse_user_time};
+use chrono::Utc;
+
+pub fn execute(app_config: &AppConfig, command: ArchiveCommand) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    match command {
+        ArchiveCommand::Query { from, to, app_glob } => {
+            let from = match from {
+                Some(from) => parse_user_time(&from).map_err(AppError::Config)?.timestamp(),
+                None => 0,
+            };
+            let to = match to {
+                Some(to) => parse_user_time(&to).map_err(AppError::Config)?.timestamp(),
+                None => Utc::now().timestamp(),
+            };
+            if from >= to {
+                return Err(AppError::Config("--from must be before --to".to_string()));
+            }
+            let intervals = storage.query_archived_intervals(from, to, app_glob.as_deref())?;
+            let Some(first) = intervals.first() else {
+                if app_config.archive_raw_intervals {
+                    println!("No archived intervals in this range.");
+                } else {
+                    println!("No archived intervals in this range (archive_raw_intervals is off in config.json).");
+                }
+                return Ok(());
+            };
+            println!("Archived intervals between {} and {}:", format_local_timestamp(from.max(first.start_time)), format_local_timestamp(to));
+            print_interval_table(&intervals, app_config.duration_format);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+// src/commands/ask.rs
+// `ask "<question>"`: a language model turns the question into an `AskPlan` (a date range and the
+// `stats` filters), never into SQL. The plan is checked, then run through the same by-app query as
+// `stats`; the printed answer is computed locally from those rows. Sent to the model: the question,
+// today's date and the names of the most used apps (no titles, no durations); with `--provider
+// ollama` it goes to a local server only.
+
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::llm;
+use crate::persistence;
+use crate::table::{Align, Column, Table};
+use crate::types::{IntervalStream, LlmProvider, StatsFilter};
+use crate::utils::format_duration;
+use serde::Deserialize;
+
+/// App names listed in the prompt so "VS Code" can be matched to "Code.exe"
+const KNOWN_APPS: usize = 100;
+/// Longest range a plan may cover
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// What the model must answer with; every field maps onto a `stats` parameter.
+#[derive(Debug, Deserialize)]
+struct AskPlan {
+    /// First and last local day of the range, inclusive ("YYYY-MM-DD")
+    from: String,
+    to: String,
+    #[serde(default)]
+    app_glob: Option<String>,
+    #[serde(default)]
+    title_contains: Option<String>,
+    #[serde(default)]
+    top: Option<usize>,
+    #[serde(default)]
+    exclude_passive: bool,
+}
+
+const SYSTEM_PROMPT: &str = r#"You translate questions about computer usage time into a JSON query plan.
+Answer with one JSON object and nothing else:
+{"from": "YYYY-MM-DD", "to": "YYYY-MM-DD", "app_glob": string or null, "title_contains": string or null, "top": number or null, "exclude_passive": boolean}
+- "from" and "to" are the first and last local day asked about (inclusive). Resolve relative dates ("last Tuesday", "this month") from today's date.
+- "app_glob" is a case-insensitive glob on the executable name (e.g. "Code.exe", "chrome*"). Pick it from the known apps when one matches the app in the question; null for all apps.
+- "title_contains" is a word the window title must contain (e.g. a website or document name); null otherwise.
+- "top" limits the answer to the N apps with the most time ("top 5 apps"); null otherwise.
+- "exclude_passive" is true only when the question excludes watching videos or meetings."#;
+
+/// Answers `question`; "today" and the end of the range come from `clock`.
+pub fn execute(app_config: &AppConfig, question: &str, provider: LlmProvider, model: Option<&str>, clock: &dyn Clock) -> AppResult<()> {
+    if app_config.database_url.is_some() {
+        return Err(AppError::Config("ask reads the local SQLite database (database_url is set)".to_string()));
+    }
+    let db_path = &app_config.database_path;
+    if !db_path.exists() {
+        return Err(AppError::Config(format!("No database at {:?} yet", db_path)));
+    }
+    let passphrase = persistence::database_passphrase(app_config)?;
+    let conn = persistence::open_connection_read_only(db_path, passphrase.as_deref())?;
+    let timezone = &app_config.timezone;
+    let now = clock.now();
+
+    let known_apps = persistence::query_top_app_names(&conn, KNOWN_APPS)?;
+    let prompt = format!(
+        "Today is {} {}.\nKnown apps: {}\nQuestion: {}",
+        timezone.weekday_name(now),
+        timezone.date_string(now),
+        known_apps.join(", "),
+        question
+    );
+    let reply = llm::complete(app_config, provider, model, SYSTEM_PROMPT, &prompt)?;
+    log::debug!("Model reply: {}", reply);
+    let plan = parse_plan(&reply)?;
+
+    let start = timezone.date_start(&plan.from).map_err(|e| invalid_plan(&reply, &e))?;
+    let last_day = timezone.date_start(&plan.to).map_err(|e| invalid_plan(&reply, &e))?;
+    let end = timezone.next_day_start(last_day);
+    if end <= start || end - start > MAX_RANGE_DAYS * 86_400 + 3600 {
+        return Err(invalid_plan(&reply, &format!("range {} to {} is empty or longer than {} days", plan.from, plan.to, MAX_RANGE_DAYS)));
+    }
+    let filter = StatsFilter {
+        top: plan.top.filter(|top| *top > 0),
+        app_glob: plan.app_glob.filter(|glob| !glob.trim().is_empty() && glob.trim() != "*"),
+        title_contains: plan.title_contains.filter(|text| !text.trim().is_empty()),
+        min_duration_secs: None,
+        exclude_passive: plan.exclude_passive,
+        user: None,
+        stream: IntervalStream::Foreground,
+    };
+    let rows = persistence::query_app_totals_between(&conn, start, end, &filter, now)?;
+
+    println!("Question: {}", question);
+    println!("Answered from: {}", describe_plan(&plan.from, &plan.to, &filter));
+    if rows.is_empty() {
+        println!("No tracked time matches.");
+        return Ok(());
+    }
+    let format = app_config.duration_format;
+    let mut table = Table::new(vec![Column::new("Application").max_width(Some(60)), Column::new("Time").align(Align::Right)]);
+    for (app, secs) in &rows {
+        table.add_row([app.clone(), format_duration(*secs, format)]);
+    }
+    print!("{}", table.render());
+    let total: i64 = rows.iter().map(|(_, secs)| secs).sum();
+    println!("Answer: {} in total", format_duration(total, format));
+    if filter.title_contains.is_some() {
+        println!("(Window titles are only kept for recent days; older days are not searched by title.)");
+    }
+    Ok(())
+}
+
+/// The plan in the reply; models sometimes wrap the JSON in a code fence or a sentence.
+fn parse_plan(reply: &str) -> AppResult<AskPlan> {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(open), Some(close)) if open < close => &reply[open..=close],
+        _ => return Err(invalid_plan(reply, "no JSON object")),
+    };
+    serde_json::from_str(json).map_err(|e| invalid_plan(reply, &e.to_string()))
+}
+
+fn invalid_plan(reply: &str, reason: &str) -> AppError {
+    AppError::Integration(format!("Could not use the model's answer ({}): {}", reason, reply.trim()))
+}
+
+/// "apps matching "Code.exe", 2026-10-13 to 2026-10-13"
+fn describe_plan(from: &str, to: &str, filter: &StatsFilter) -> String {
+    let mut parts = vec![if from == to { from.to_string() } else { format!("{} to {}", from, to) }];
+    match &filter.app_glob {
+        Some(glob) => parts.push(format!("apps matching \"{}\"", glob)),
+        None => parts.push("all apps".to_string()),
+    }
+    if let Some(text) = &filter.title_contains {
+        parts.push(format!("titles containing \"{}\"", text));
+    }
+    if let Some(top) = filter.top {
+        parts.push(format!("top {}", top));
+    }
+    if filter.exclude_passive {
+        parts.push("passive time excluded".to_string());
+    }
+    parts.join(", ")
+}
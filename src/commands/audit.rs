@@ -0,0 +1,224 @@
+// src/commands/audit.rs
+// `audit`: walks through a day's untracked gaps and the intervals recorded under no real app
+// ("[Access Denied]", ...) one by one, and assigns each to an app or a category, or marks it as a
+// break. Corrections go through `add_manual_interval` and `delete_interval` like `add-entry` and
+// `intervals delete`, so every one is in `audit_log`.
+
+use crate::config::AppConfig;
+use crate::detection::BREAK_APP_NAME;
+use crate::errors::{AppError, AppResult};
+use crate::persistence::{self, Storage};
+use crate::pseudonym::Pseudonymizer;
+use crate::types::{IntervalRecord, NewInterval};
+use crate::utils::format_duration;
+use chrono::Utc;
+use std::io::{self, BufRead, Write};
+
+/// App names detectors record when they cannot tell which app had the focus
+const UNIDENTIFIED_APPS: &[&str] = &["[Access Denied]", "Unknown"];
+/// Prefix of the Windows detector's placeholder for windows without a process id
+const SYSTEM_PROCESS_PREFIX: &str = "[System Process";
+
+/// Something to review: untracked time between two intervals, or an interval without a real app.
+enum AuditItem {
+    Gap { start: i64, end: i64, before: String, after: String },
+    Unidentified(Box<IntervalRecord>),
+}
+
+impl AuditItem {
+    fn span(&self) -> (i64, i64) {
+        match self {
+            AuditItem::Gap { start, end, .. } => (*start, *end),
+            AuditItem::Unidentified(interval) => (interval.start_time, interval.end_time),
+        }
+    }
+}
+
+/// What the user chose for an item
+enum Answer {
+    App(String),
+    Category(String),
+    Break,
+    Skip,
+    Quit,
+}
+
+/// Reviews the local day `date` ("yesterday", "today" or YYYY-MM-DD); gaps and intervals shorter
+/// than `min_minutes` are not asked about.
+pub fn execute(app_config: &AppConfig, date: &str, min_minutes: u64) -> AppResult<()> {
+    let timezone = &app_config.timezone;
+    let now = Utc::now().timestamp();
+    let day_start = match date.trim() {
+        "today" => timezone.day_start(now),
+        "yesterday" => timezone.previous_day_start(timezone.day_start(now)),
+        date => timezone.date_start(date).map_err(AppError::Config)?,
+    };
+    let day_end = timezone.next_day_start(day_start).min(now);
+    if day_end <= day_start {
+        return Err(AppError::Config(format!("{} has not started yet", timezone.date_string(day_start))));
+    }
+
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let intervals = storage.query_intervals_between(day_start, day_end, now)?;
+    let items = find_items(intervals, day_start, day_end, min_minutes as i64 * 60);
+    let heading = timezone.date_string(day_start);
+    if items.is_empty() {
+        println!("Nothing to review for {}: no gaps or unidentified intervals of {} min or more.", heading, min_minutes);
+        return Ok(());
+    }
+    let categories: Vec<&str> = app_config.screentime.categories.iter().map(|category| category.name.as_str()).collect();
+    println!("Reviewing {} item(s) for {}.", items.len(), heading);
+    print_help(&categories);
+
+    // With `pseudonymize` corrections are stored hashed, like tracked activity
+    let pseudonyms = app_config.pseudonymize.as_ref().map(|_| Pseudonymizer::load(app_config)).transpose()?;
+    let format = app_config.duration_format;
+    let stdin = io::stdin();
+    let mut corrected = 0;
+    for (index, item) in items.iter().enumerate() {
+        let (start, end) = item.span();
+        let what = match item {
+            AuditItem::Gap { before, after, .. } => format!("untracked, between {} and {}", before, after),
+            AuditItem::Unidentified(interval) => format!("{} '{}'", interval.app_name, interval.detailed_title.as_deref().unwrap_or_default()),
+        };
+        println!();
+        println!(
+            "[{}/{}] {}–{} ({}): {}",
+            index + 1,
+            items.len(),
+            timezone.time_string(start),
+            timezone.time_string(end),
+            format_duration(end - start, format),
+            what
+        );
+        let answer = loop {
+            print!("audit> ");
+            io::stdout().flush().ok();
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).map_err(|e| AppError::Unexpected(format!("Failed to read input: {}", e)))? == 0 {
+                println!();
+                break Answer::Quit; // End of input (Ctrl+D / Ctrl+Z)
+            }
+            match parse_answer(line.trim(), &categories) {
+                Ok(Some(answer)) => break answer,
+                Ok(None) => print_help(&categories),
+                Err(message) => println!("{}", message),
+            }
+        };
+        let app = match answer {
+            Answer::App(app) => app,
+            Answer::Category(category) => category,
+            Answer::Break => BREAK_APP_NAME.to_string(),
+            Answer::Skip => continue,
+            Answer::Quit => break,
+        };
+        let stored_app = pseudonyms.as_ref().map_or_else(|| app.clone(), |pseudonyms| pseudonyms.hash(&app));
+        // A failed correction should not end the review
+        match apply(storage.as_mut(), item, &stored_app, app_config, now) {
+            Ok(true) => {
+                corrected += 1;
+                println!("Recorded as {}.", app);
+            }
+            Ok(false) => println!("The interval is still open, or was changed or aggregated meanwhile; nothing was modified."),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    println!();
+    println!("Corrected {} of {} item(s).", corrected, items.len());
+    Ok(())
+}
+
+/// Gaps of at least `min_secs` between the first and last interval of the day (locked time counts
+/// as tracked), and intervals of at least `min_secs` under an unidentified app, in time order.
+fn find_items(mut intervals: Vec<IntervalRecord>, day_start: i64, day_end: i64, min_secs: i64) -> Vec<AuditItem> {
+    intervals.sort_by_key(|interval| (interval.start_time, interval.id));
+    let mut items = Vec::new();
+    // End of the tracked time so far and the app it ended on
+    let mut covered: Option<(i64, String)> = None;
+    for interval in intervals {
+        let (start, end) = (interval.start_time.max(day_start), interval.end_time.min(day_end));
+        if end <= start {
+            continue;
+        }
+        if let Some((covered_end, before)) = &covered
+            && start - covered_end >= min_secs
+        {
+            items.push(AuditItem::Gap { start: *covered_end, end: start, before: before.clone(), after: interval.app_name.clone() });
+        }
+        if covered.as_ref().is_none_or(|(covered_end, _)| end >= *covered_end) {
+            covered = Some((end, interval.app_name.clone()));
+        }
+        if is_unidentified(&interval.app_name) && end - start >= min_secs {
+            items.push(AuditItem::Unidentified(Box::new(interval)));
+        }
+    }
+    items.sort_by_key(|item| item.span());
+    items
+}
+
+fn is_unidentified(app_name: &str) -> bool {
+    UNIDENTIFIED_APPS.contains(&app_name) || app_name.starts_with(SYSTEM_PROCESS_PREFIX)
+}
+
+/// None for "help" (or anything unknown), to show the commands again.
+fn parse_answer(input: &str, categories: &[&str]) -> Result<Option<Answer>, String> {
+    let (command, argument) = input.split_once(char::is_whitespace).map_or((input, ""), |(command, rest)| (command, rest.trim()));
+    Ok(Some(match command.to_lowercase().as_str() {
+        "" | "s" | "skip" => Answer::Skip,
+        "b" | "break" => Answer::Break,
+        "q" | "quit" => Answer::Quit,
+        "a" | "app" if argument.is_empty() => return Err("Usage: app <name>".to_string()),
+        "a" | "app" => Answer::App(argument.to_string()),
+        "c" | "category" => match categories.iter().find(|category| category.eq_ignore_ascii_case(argument)) {
+            Some(category) => Answer::Category(category.to_string()),
+            None if categories.is_empty() => return Err("No categories are configured (screentime.categories or notify.categories)".to_string()),
+            None => return Err(format!("Unknown category {:?}; choose one of: {}", argument, categories.join(", "))),
+        },
+        _ => return Ok(None),
+    }))
+}
+
+fn print_help(categories: &[&str]) {
+    println!("For each item: 'app <name>', 'category <name>', 'break', 'skip' (or Enter) or 'quit'.");
+    if !categories.is_empty() {
+        println!("Categories: {}", categories.join(", "));
+    }
+}
+
+/// Records the item's time under `app`; an unidentified interval is deleted and re-added under it.
+/// False if the interval is gone (aggregated or edited since it was listed) or still open.
+fn apply(storage: &mut dyn Storage, item: &AuditItem, app: &str, app_config: &AppConfig, now: i64) -> AppResult<bool> {
+    let (start, end) = item.span();
+    let (device_id, user_name) = match item {
+        AuditItem::Unidentified(interval) => {
+            if storage.query_completed_interval(interval.id)?.is_none() || !storage.delete_interval(interval.id, now)? {
+                return Ok(false);
+            }
+            (
+                interval.device_id.as_deref().unwrap_or(&app_config.device_id),
+                interval.user_name.as_deref().unwrap_or(&app_config.user_name),
+            )
+        }
+        AuditItem::Gap { .. } => (app_config.device_id.as_str(), app_config.user_name.as_str()),
+    };
+    let interval = NewInterval {
+        app_name: app,
+        main_title: "",
+        detailed_title: "",
+        virtual_desktop: None,
+        repo: None,
+        executable_path: None,
+        command_line: None,
+        ticket: None,
+        session_type: None,
+        remote_host: None,
+        document: None,
+        passive: false,
+        start_time: start,
+        device_id,
+        user_name,
+    };
+    storage.add_manual_interval(&interval, end, now)?;
+    Ok(true)
+}
@@ -0,0 +1,132 @@
+// src/commands/autostart.rs
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::types::AutostartCommand;
+use std::path::PathBuf;
+
+/// Arguments the login entry passes to the executable.
+const AUTOSTART_ARGS: &str = "track --daemon --supervise";
+
+/// Execute autostart-related commands (enable, disable, status)
+pub fn execute(app_config: &AppConfig, command: AutostartCommand) -> AppResult<()> {
+    match command {
+        AutostartCommand::Enable => {
+            let exe = current_exe()?;
+            log::info!("Registering autostart entry for {:?}", exe);
+            let location = platform::enable(app_config, &exe)?;
+            println!("✅ Autostart enabled: '{}' will run at login.", launch_command(&exe));
+            println!("   Entry: {}", location);
+        }
+        AutostartCommand::Disable => {
+            if platform::disable(app_config)? {
+                println!("✅ Autostart disabled.");
+            } else {
+                println!("Autostart was not enabled.");
+            }
+        }
+        AutostartCommand::Status => match platform::status(app_config)? {
+            Some(entry) => println!("Autostart is enabled: {}", entry),
+            None => println!("Autostart is disabled."),
+        },
+    }
+    Ok(())
+}
+
+fn current_exe() -> AppResult<PathBuf> {
+    std::env::current_exe()
+        .map_err(|e| AppError::Unexpected(format!("Could not determine current executable: {}", e)))
+}
+
+fn launch_command(exe: &std::path::Path) -> String {
+    format!("\"{}\" {}", exe.display(), AUTOSTART_ARGS)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    //! Windows: a value under HKCU\Software\Microsoft\Windows\CurrentVersion\Run
+    use super::launch_command;
+    use crate::{config::AppConfig, errors::AppResult, windows_api};
+    use std::path::Path;
+
+    pub fn enable(app_config: &AppConfig, exe: &Path) -> AppResult<String> {
+        windows_api::set_run_entry(&app_config.app_name, &launch_command(exe))?;
+        Ok(format!(r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run\{}", app_config.app_name))
+    }
+
+    pub fn disable(app_config: &AppConfig) -> AppResult<bool> {
+        windows_api::delete_run_entry(&app_config.app_name)
+    }
+
+    pub fn status(app_config: &AppConfig) -> AppResult<Option<String>> {
+        windows_api::get_run_entry(&app_config.app_name)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    //! Linux/BSD: XDG autostart `.desktop` file. macOS: a LaunchAgent plist.
+    //! Both are plain files, so enable/disable/status only differ in path and contents.
+    use super::{launch_command, AUTOSTART_ARGS};
+    use crate::{config::AppConfig, errors::{AppError, AppResult}};
+    use std::path::{Path, PathBuf};
+
+    fn entry_path(app_config: &AppConfig) -> AppResult<PathBuf> {
+        if cfg!(target_os = "macos") {
+            let home = dirs::home_dir()
+                .ok_or_else(|| AppError::DataDir("Could not find home directory.".to_string()))?;
+            Ok(home.join("Library/LaunchAgents").join(format!("com.netajam.{}.plist", app_config.app_name)))
+        } else {
+            // dirs::config_dir honors $XDG_CONFIG_HOME
+            let config_dir = dirs::config_dir()
+                .ok_or_else(|| AppError::DataDir("Could not find user config directory.".to_string()))?;
+            Ok(config_dir.join("autostart").join(format!("{}.desktop", app_config.app_name)))
+        }
+    }
+
+    fn entry_contents(app_config: &AppConfig, exe: &Path) -> String {
+        if cfg!(target_os = "macos") {
+            let args: String = std::iter::once(exe.display().to_string())
+                .chain(AUTOSTART_ARGS.split_whitespace().map(str::to_string))
+                .map(|arg| format!("        <string>{}</string>\n", arg))
+                .collect();
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+                 <plist version=\"1.0\">\n<dict>\n    <key>Label</key>\n    <string>com.netajam.{}</string>\n    \
+                 <key>ProgramArguments</key>\n    <array>\n{}    </array>\n    <key>RunAtLoad</key>\n    <true/>\n</dict>\n</plist>\n",
+                app_config.app_name, args
+            )
+        } else {
+            format!(
+                "[Desktop Entry]\nType=Application\nName={}\nComment=Tracks application usage time\nExec={}\nX-GNOME-Autostart-enabled=true\nNoDisplay=true\n",
+                app_config.app_name,
+                launch_command(exe)
+            )
+        }
+    }
+
+    pub fn enable(app_config: &AppConfig, exe: &Path) -> AppResult<String> {
+        let path = entry_path(app_config)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::Io { path: parent.to_path_buf(), source: e })?;
+        }
+        std::fs::write(&path, entry_contents(app_config, exe))
+            .map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+        Ok(path.display().to_string())
+    }
+
+    pub fn disable(app_config: &AppConfig) -> AppResult<bool> {
+        let path = entry_path(app_config)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_file(&path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+        Ok(true)
+    }
+
+    pub fn status(app_config: &AppConfig) -> AppResult<Option<String>> {
+        let path = entry_path(app_config)?;
+        Ok(path.exists().then(|| path.display().to_string()))
+    }
+}
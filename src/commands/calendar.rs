@@ -0,0 +1,49 @@
+// src/commands/calendar.rs
+
+use crate::calendar;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::table::{Align, Column, Table};
+use crate::types::CalendarCommand;
+use crate::utils::format_duration;
+use chrono::Utc;
+
+/// Execute calendar commands
+pub fn execute(app_config: &AppConfig, command: CalendarCommand) -> AppResult<()> {
+    if app_config.calendar.is_none() {
+        return Err(AppError::Config("No calendar configured; set \"calendar\" in config.json (ics_file or caldav_url).".to_string()));
+    }
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let now = Utc::now().timestamp();
+    match command {
+        CalendarCommand::Sync { days } => {
+            let count = calendar::refresh(app_config, storage.as_mut(), now, days)?;
+            println!("Stored {} event(s) from the last {} day(s) through tomorrow.", count, days);
+            println!("(Time aggregated before this sync is not counted under these events.)");
+        }
+        CalendarCommand::List => {
+            let timezone = &app_config.timezone;
+            let events = storage.query_calendar_events(timezone.day_start(now), timezone.next_day_start(now))?;
+            if events.is_empty() {
+                println!("No events stored for today. Read the calendar with: calendar sync");
+                return Ok(());
+            }
+            let mut table = Table::new(vec![
+                Column::new("Time"),
+                Column::new("Length").align(Align::Right),
+                Column::new("Meeting").max_width(Some(60)),
+            ]);
+            for event in events {
+                table.add_row([
+                    format!("{}-{}", timezone.time_string(event.start_time), timezone.time_string(event.end_time)),
+                    format_duration(event.end_time - event.start_time, app_config.duration_format),
+                    event.title,
+                ]);
+            }
+            print!("{}", table.render());
+        }
+    }
+    Ok(())
+}
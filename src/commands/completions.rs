@@ -0,0 +1,48 @@
+// src/commands/completions.rs
+// Shell completions and man pages generated from the clap definitions in `cli.rs`.
+
+use crate::cli::Cli;
+use crate::errors::{AppError, AppResult};
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Prints the completion script for `shell` to stdout.
+pub fn execute_completions(shell: Shell) -> AppResult<()> {
+    let mut command = Cli::command();
+    let bin_name = command.get_name().to_string();
+    // Rendered first so a closed pipe (e.g. `| head`) is an error instead of a panic
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, bin_name, &mut script);
+    write_stdout(&script)
+}
+
+fn write_stdout(bytes: &[u8]) -> AppResult<()> {
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(bytes).and_then(|_| stdout.flush()) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(AppError::Unexpected(format!("Failed to write to stdout: {}", e))),
+        _ => Ok(()),
+    }
+}
+
+/// Prints the main man page, or writes it plus one page per subcommand into `out_dir`.
+pub fn execute_manpage(out_dir: Option<&Path>) -> AppResult<()> {
+    let command = Cli::command();
+    match out_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir).map_err(|e| AppError::Io { path: dir.to_path_buf(), source: e })?;
+            clap_mangen::generate_to(command, dir).map_err(|e| AppError::Io { path: dir.to_path_buf(), source: e })?;
+            println!("Man pages written to {:?}", dir);
+        }
+        None => {
+            let mut page = Vec::new();
+            clap_mangen::Man::new(command)
+                .render(&mut page)
+                .map_err(|e| AppError::Unexpected(format!("Failed to render man page: {}", e)))?;
+            write_stdout(&page)?;
+        }
+    }
+    Ok(())
+}
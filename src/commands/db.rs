@@ -0,0 +1,196 @@
+// src/commands/db.rs
+
+use crate::commands::set_key::load_api_key;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence::{self, Storage};
+use crate::types::{ApiKeyType, DbCommand};
+use crate::utils::format_bytes;
+use rusqlite::params;
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Execute database maintenance commands
+pub fn execute(app_config: &AppConfig, command: DbCommand) -> AppResult<()> {
+    match command {
+        DbCommand::Path => {
+            print_path(app_config);
+            Ok(())
+        }
+        _ if app_config.database_url.is_some() => {
+            Err(AppError::Config("db commands only apply to the local SQLite database (database_url is set)".to_string()))
+        }
+        DbCommand::Encrypt => encrypt(app_config),
+        DbCommand::Decrypt => decrypt(app_config),
+        DbCommand::Maintain => maintain(app_config),
+        DbCommand::Backup { keep } => {
+            let mut storage = persistence::open_storage(app_config)?;
+            let path = backup(app_config, storage.as_mut(), keep)?;
+            println!("✅ Backed up to {:?} ({}).", path, format_bytes(persistence::database_file_size(&path)));
+            Ok(())
+        }
+    }
+}
+
+/// Written next to the database, one file per backup.
+const BACKUP_DIR_NAME: &str = "backups";
+
+/// `db path`: the resolved locations, so scripts and support requests need not guess them.
+fn print_path(app_config: &AppConfig) {
+    let data_dir = app_config.config_file_path.parent().unwrap_or(Path::new("."));
+    println!("Data directory: {}", data_dir.display());
+    println!("Config file:    {}", app_config.config_file_path.display());
+    if app_config.database_url.is_some() {
+        println!("Database:       {} (database_url in config.json)", persistence::describe_location(app_config));
+        return;
+    }
+    println!("Database:       {} ({})", app_config.database_path.display(), app_config.database_path_origin);
+    let mode = match app_config.database_write_mode {
+        persistence::WriteMode::Wal => "WAL",
+        persistence::WriteMode::Safe => "safe-write (no WAL, full sync)",
+    };
+    println!("Journal mode:   {}", mode);
+}
+
+/// Writes `backups/app_usage-<local time>.sqlite` and deletes the oldest backups beyond `keep`.
+/// Also run by the scheduled `backup` job with the tracker's own storage.
+pub fn backup(app_config: &AppConfig, storage: &mut dyn Storage, keep: Option<usize>) -> AppResult<PathBuf> {
+    let dir = app_config.database_path.parent().unwrap_or(Path::new(".")).join(BACKUP_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| AppError::Io { path: dir.clone(), source: e })?;
+    let stem = app_config.database_path.file_stem().map_or("app_usage".into(), |stem| stem.to_string_lossy());
+    let path = dir.join(format!("{}-{}.sqlite", stem, Local::now().format("%Y-%m-%d-%H%M%S")));
+    if path.exists() {
+        return Err(AppError::Config(format!("Backup {:?} already exists", path)));
+    }
+    storage.backup_to(&path)?;
+
+    if let Some(keep) = keep {
+        let prefix = format!("{}-", stem);
+        let mut backups: Vec<PathBuf> = fs::read_dir(&dir)
+            .map_err(|e| AppError::Io { path: dir.clone(), source: e })?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "sqlite"))
+            .filter(|file| file.file_name().is_some_and(|name| name.to_string_lossy().starts_with(&prefix)))
+            .collect();
+        // Names sort by time
+        backups.sort();
+        let excess = backups.len().saturating_sub(keep.max(1));
+        for old in &backups[..excess] {
+            fs::remove_file(old).map_err(|e| AppError::Io { path: old.clone(), source: e })?;
+            log::info!("Deleted old backup {:?}.", old);
+        }
+    }
+    Ok(path)
+}
+
+/// Runs `persistence::maintain_db` and reports how much space VACUUM gave back.
+fn maintain(app_config: &AppConfig) -> AppResult<()> {
+    let db_path = &app_config.database_path;
+    if !db_path.exists() {
+        return Err(AppError::Config(format!("No database at {:?} yet", db_path)));
+    }
+    let passphrase = persistence::database_passphrase(app_config)?;
+    let size_before = persistence::database_file_size(db_path);
+    println!("Maintaining {:?} (stop the tracker first)...", db_path);
+    let conn = persistence::open_database(app_config, passphrase.as_deref())?;
+    let free_percent = persistence::free_page_percent(&conn)?;
+    let problems = persistence::maintain_db(&conn)?;
+    if !problems.is_empty() {
+        for problem in problems.iter().take(10) {
+            eprintln!("  {}", problem);
+        }
+        return Err(AppError::Config(format!(
+            "Integrity check found {} problem(s); ANALYZE and VACUUM were skipped. Back up the file and recover it with `sqlite3 <file> .recover`.",
+            problems.len()
+        )));
+    }
+    drop(conn);
+    let size_after = persistence::database_file_size(db_path);
+    println!("Integrity check: ok");
+    println!("Free pages before VACUUM: {:.1}%", free_percent);
+    println!(
+        "Size: {} -> {} ({} reclaimed)",
+        format_bytes(size_before),
+        format_bytes(size_after),
+        format_bytes(size_before.saturating_sub(size_after))
+    );
+    println!("✅ Database analyzed and vacuumed.");
+    Ok(())
+}
+
+fn require_sqlcipher() -> AppResult<()> {
+    if cfg!(feature = "sqlcipher") {
+        Ok(())
+    } else {
+        Err(AppError::Config("This build has no SQLCipher support (rebuild with --features sqlcipher)".to_string()))
+    }
+}
+
+/// Re-writes the plaintext database into an encrypted copy with `sqlcipher_export`, then swaps the files.
+fn encrypt(app_config: &AppConfig) -> AppResult<()> {
+    require_sqlcipher()?;
+    if app_config.database_encryption {
+        return Err(AppError::Config("database_encryption is already enabled; the database is expected to be encrypted".to_string()));
+    }
+    let passphrase = load_api_key(app_config, ApiKeyType::Database)?;
+    let db_path = &app_config.database_path;
+    let tmp_path = sibling_path(db_path, "encrypting");
+    println!("Encrypting {:?} (stop the tracker first)...", db_path);
+    {
+        let conn = persistence::open_database(app_config, None)?;
+        persistence::checkpoint_wal(&conn)?;
+        export_copy(&conn, &tmp_path, &passphrase)?;
+    }
+    replace_database(db_path, &tmp_path)?;
+    println!("✅ Database encrypted. Set \"database_encryption\": true in {:?} to use it.", app_config.config_file_path);
+    Ok(())
+}
+
+fn decrypt(app_config: &AppConfig) -> AppResult<()> {
+    require_sqlcipher()?;
+    let passphrase = load_api_key(app_config, ApiKeyType::Database)?;
+    let db_path = &app_config.database_path;
+    let tmp_path = sibling_path(db_path, "decrypting");
+    println!("Decrypting {:?} (stop the tracker first)...", db_path);
+    {
+        let conn = persistence::open_database(app_config, Some(&passphrase))?;
+        persistence::checkpoint_wal(&conn)?;
+        export_copy(&conn, &tmp_path, "")?;
+    }
+    replace_database(db_path, &tmp_path)?;
+    println!("✅ Database decrypted. Remove \"database_encryption\" from {:?}.", app_config.config_file_path);
+    Ok(())
+}
+
+/// Copies every table of `conn` into a new database at `target` keyed with `passphrase` ("" = plaintext).
+fn export_copy(conn: &rusqlite::Connection, target: &Path, passphrase: &str) -> AppResult<()> {
+    if target.exists() {
+        fs::remove_file(target).map_err(|e| AppError::Io { path: target.to_path_buf(), source: e })?;
+    }
+    conn.execute("ATTACH DATABASE ?1 AS converted KEY ?2", params![target.to_string_lossy(), passphrase])?;
+    conn.query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(()))?;
+    conn.execute("DETACH DATABASE converted", [])?;
+    Ok(())
+}
+
+/// Moves the converted copy over the original, dropping the original's WAL/SHM files.
+fn replace_database(db_path: &Path, converted: &Path) -> AppResult<()> {
+    for suffix in ["-wal", "-shm"] {
+        let side_file = sibling_path(db_path, suffix);
+        if side_file.exists() {
+            fs::remove_file(&side_file).map_err(|e| AppError::Io { path: side_file.clone(), source: e })?;
+        }
+    }
+    fs::rename(converted, db_path).map_err(|e| AppError::Io { path: db_path.to_path_buf(), source: e })
+}
+
+/// `app_usage.sqlite` + `-wal` -> `app_usage.sqlite-wal`
+fn sibling_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    if !suffix.starts_with('-') {
+        name.push(".");
+    }
+    name.push(suffix);
+    PathBuf::from(name)
+}
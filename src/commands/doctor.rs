@@ -0,0 +1,289 @@
+// src/commands/doctor.rs
+// `doctor`: checks the database, detector, platform APIs and keyring, and prints a fix for each problem.
+
+use crate::config::AppConfig;
+use crate::detection::{self, ActivityDetector};
+use crate::errors::{AppResult, PersistenceError};
+use crate::persistence;
+use crate::types::ApiKeyType;
+use clap::ValueEnum;
+use keyring::Entry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Problem,
+}
+
+/// The outcome of one check.
+#[derive(Debug)]
+struct Finding {
+    status: Status,
+    area: &'static str,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(area: &'static str, message: impl Into<String>) -> Self {
+        Finding { status: Status::Ok, area, message: message.into(), fix: None }
+    }
+
+    fn warning(area: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Finding { status: Status::Warning, area, message: message.into(), fix: Some(fix.into()) }
+    }
+
+    fn problem(area: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Finding { status: Status::Problem, area, message: message.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// Runs every check and prints the findings. Only reads: nothing is created or repaired.
+pub fn execute(app_config: &AppConfig) -> AppResult<()> {
+    let mut findings = vec![check_config(app_config)];
+    findings.extend(check_database(app_config));
+    findings.extend(check_detection(app_config));
+    findings.extend(check_keyring(app_config));
+
+    for finding in &findings {
+        let icon = match finding.status {
+            Status::Ok => "✅",
+            Status::Warning => "⚠️ ",
+            Status::Problem => "❌",
+        };
+        println!("{} {:<10} {}", icon, finding.area, finding.message);
+        if let Some(fix) = &finding.fix {
+            println!("   {:<10} Fix: {}", "", fix);
+        }
+    }
+    let problems = findings.iter().filter(|f| f.status == Status::Problem).count();
+    let warnings = findings.iter().filter(|f| f.status == Status::Warning).count();
+    println!();
+    if problems == 0 && warnings == 0 {
+        println!("Everything looks fine.");
+    } else {
+        println!("{} problem(s), {} warning(s).", problems, warnings);
+    }
+    Ok(())
+}
+
+fn check_config(app_config: &AppConfig) -> Finding {
+    // An unreadable config file already failed before any command ran
+    if app_config.config_file_path.exists() {
+        Finding::ok("Config", format!("Loaded {:?}", app_config.config_file_path))
+    } else {
+        Finding::ok("Config", format!("No {:?}, using defaults", app_config.config_file_path))
+    }
+}
+
+fn check_database(app_config: &AppConfig) -> Vec<Finding> {
+    if app_config.database_url.is_some() {
+        let location = persistence::describe_location(app_config);
+        return vec![match persistence::open_storage(app_config) {
+            Ok(_) => Finding::ok("Database", format!("Connected to {}", location)),
+            Err(e) => Finding::problem(
+                "Database",
+                format!("Cannot connect to {}: {}", location, e),
+                "Check database_url in config.json and that the server accepts connections from this machine.",
+            ),
+        }];
+    }
+
+    let path = &app_config.database_path;
+    if !path.exists() {
+        return vec![Finding::warning("Database", format!("No database at {:?} yet", path), "Run `init-db` or start tracking with `track`.")];
+    }
+    let passphrase = match persistence::database_passphrase(app_config) {
+        Ok(passphrase) => passphrase,
+        Err(e) => {
+            return vec![Finding::problem(
+                "Database",
+                format!("Cannot get the database passphrase: {}", e),
+                "Set it with `config set-key database`, or remove database_encryption from config.json for a plaintext database.",
+            )];
+        }
+    };
+    let conn = match persistence::open_database(app_config, passphrase.as_deref()) {
+        Ok(conn) => conn,
+        Err(e) => return vec![unreadable_database(app_config, &e)],
+    };
+
+    let mut findings = Vec::new();
+    if let Some(location) = persistence::location::risky_location(path) {
+        let fix = match app_config.database_write_mode {
+            persistence::WriteMode::Safe => "Move it to a local folder with database_path; until then safe-write mode (no WAL, full sync) is used.",
+            persistence::WriteMode::Wal => "Move it to a local folder with database_path, or remove \"database_safe_write\": false from config.json.",
+        };
+        findings.push(Finding::warning("Location", format!("{:?} is in {}; it can be locked or corrupted while written", path, location), fix));
+    }
+    match persistence::integrity_check(&conn) {
+        Ok(rows) if rows.iter().all(|row| row == "ok") => findings.push(Finding::ok("Database", format!("{:?} passed the integrity check", path))),
+        Ok(rows) => findings.push(Finding::problem(
+            "Database",
+            format!("Integrity check reported {} problem(s), first: {}", rows.len(), rows.first().map_or("", String::as_str)),
+            format!("Stop the tracker, back up {:?} and rebuild it with `sqlite3 <file> .recover | sqlite3 <new file>`.", path),
+        )),
+        Err(source) => {
+            findings.push(unreadable_database(app_config, &PersistenceError::Pragma { path: path.clone(), pragma: "integrity_check", source }));
+            return findings;
+        }
+    }
+    findings.push(match persistence::schema_version(&conn) {
+        Ok(version) if version == persistence::SCHEMA_VERSION => Finding::ok("Schema", format!("Version {}", version)),
+        Ok(version) if version < persistence::SCHEMA_VERSION => Finding::warning(
+            "Schema",
+            format!("Version {}, this build uses {}", version, persistence::SCHEMA_VERSION),
+            "Run `init-db` (starting `track` also upgrades the schema).",
+        ),
+        Ok(version) => Finding::problem(
+            "Schema",
+            format!("Version {} was written by a newer mouse_tracking (this build uses {})", version, persistence::SCHEMA_VERSION),
+            "Install the latest release before tracking into this database.",
+        ),
+        Err(e) => Finding::problem("Schema", format!("Cannot read the schema version: {}", e), "See the database finding above."),
+    });
+    findings
+}
+
+fn unreadable_database(app_config: &AppConfig, error: &PersistenceError) -> Finding {
+    let fix = if let PersistenceError::CreateDataDir { .. } = error {
+        "Check that the data directory's parent exists and is writable by this user."
+    } else if app_config.database_encryption {
+        "Check that the keyring `database` passphrase is the one the file was encrypted with."
+    } else {
+        "If the file was encrypted with `db encrypt`, set \"database_encryption\": true in config.json; otherwise restore it from a backup."
+    };
+    Finding::problem("Database", error.to_string(), fix)
+}
+
+fn check_detection(app_config: &AppConfig) -> Vec<Finding> {
+    let detector = match detection::create_detector(app_config) {
+        Ok(detector) => detector,
+        Err(e) => return vec![Finding::problem("Detector", format!("Cannot create the detector: {}", e), detector_fix())],
+    };
+    let mut findings = vec![match detector.get_current_activity() {
+        Ok(Some(info)) if info.detailed_title.is_empty() => Finding::ok("Detector", format!("Sees '{}'", info.app_name)),
+        Ok(Some(info)) => Finding::ok("Detector", format!("Sees '{}' ({})", info.app_name, info.detailed_title)),
+        Ok(None) => Finding::warning(
+            "Detector",
+            "Works, but no foreground window was detected",
+            "Run `doctor` from a terminal inside your desktop session, not over SSH or from a service.",
+        ),
+        Err(e) => Finding::problem("Detector", format!("Detection failed: {}", e), detector_fix()),
+    }];
+    findings.extend(check_platform_apis(detector.as_ref()));
+    findings
+}
+
+/// The optional APIs the tracker uses besides the foreground window.
+fn check_platform_apis(detector: &dyn ActivityDetector) -> Vec<Finding> {
+    let mut findings = vec![match detector.idle_time() {
+        Ok(Some(idle)) => Finding::ok("Idle time", format!("Available (idle for {}s)", idle.as_secs())),
+        Ok(None) => Finding::warning(
+            "Idle time",
+            "Not reported by this detector",
+            "Adaptive polling stays at check_interval; nothing to fix unless you rely on it.",
+        ),
+        Err(e) => Finding::problem("Idle time", format!("Failed: {}", e), detector_fix()),
+    }];
+    findings.push(match detector.is_session_locked() {
+        Ok(locked) => Finding::ok("Lock state", if locked { "Available (session is locked)" } else { "Available" }),
+        Err(e) => Finding::warning("Lock state", format!("Unavailable: {}", e), lock_state_fix()),
+    });
+    #[cfg(target_os = "linux")]
+    findings.push(match std::process::Command::new("gdbus").arg("help").output() {
+        Ok(_) => Finding::ok("Sleep", "gdbus found for logind suspend notifications"),
+        Err(e) => Finding::warning(
+            "Sleep",
+            format!("Cannot run gdbus: {}", e),
+            "Install gdbus (libglib2.0-bin / glib2) so suspends are noticed right away; clock checks still catch them later.",
+        ),
+    });
+    findings
+}
+
+fn detector_fix() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "Run the tracker in the interactive desktop session of the user being tracked (not as a service)."
+    } else if cfg!(target_os = "macos") {
+        "There is no built-in macOS detector yet; declare one in detector_plugins. A plugin reading window titles needs the Accessibility permission (System Settings > Privacy & Security)."
+    } else {
+        "There is no built-in Linux detector; declare one in detector_plugins (see the README), and check that its command runs in your session."
+    }
+}
+
+fn lock_state_fix() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "Locked time needs logind: run inside a logind session (XDG_SESSION_ID set) with `loginctl` installed."
+    } else {
+        "Locked time is then attributed to the last window; check the detector's platform access."
+    }
+}
+
+/// Keys the current configuration cannot work without.
+fn required_keys(app_config: &AppConfig) -> Vec<(ApiKeyType, &'static str)> {
+    let mut required = Vec::new();
+    if app_config.database_encryption {
+        required.push((ApiKeyType::Database, "database_encryption"));
+    }
+    if app_config.sync_url.is_some() {
+        required.push((ApiKeyType::Sync, "sync_url"));
+    }
+    if app_config.end_to_end_encryption {
+        required.push((ApiKeyType::Encryption, "end_to_end_encryption"));
+    }
+    if app_config.toggl.is_some() {
+        required.push((ApiKeyType::Toggl, "toggl"));
+    }
+    if app_config.mqtt.as_ref().is_some_and(|mqtt| mqtt.username.is_some()) {
+        required.push((ApiKeyType::Mqtt, "mqtt.username"));
+    }
+    if app_config.calendar.as_ref().is_some_and(|calendar| calendar.caldav_url.is_some() && calendar.username.is_some()) {
+        required.push((ApiKeyType::Caldav, "calendar.username"));
+    }
+    required
+}
+
+fn check_keyring(app_config: &AppConfig) -> Vec<Finding> {
+    let mut set_keys = Vec::new();
+    let mut missing = Vec::new();
+    for key_type in ApiKeyType::value_variants() {
+        let lookup = Entry::new(&app_config.keyring_service_name, key_type.keyring_username()).and_then(|entry| entry.get_password());
+        match lookup {
+            Ok(_) => set_keys.push(*key_type),
+            Err(keyring::Error::NoEntry) => missing.push(*key_type),
+            Err(e) => {
+                return vec![Finding::problem(
+                    "Keyring",
+                    format!("Unavailable: {}", e),
+                    if cfg!(target_os = "linux") {
+                        "Start and unlock a Secret Service provider (GNOME Keyring or KWallet) in your session."
+                    } else {
+                        "Unlock the system credential store for this user."
+                    },
+                )];
+            }
+        }
+    }
+
+    let names = |keys: &[ApiKeyType]| keys.iter().map(|key| cli_name(*key)).collect::<Vec<_>>().join(", ");
+    let mut findings = vec![Finding::ok(
+        "Keyring",
+        if set_keys.is_empty() { "Reachable, no keys set".to_string() } else { format!("Reachable, keys set: {}", names(&set_keys)) },
+    )];
+    for (key_type, setting) in required_keys(app_config) {
+        if missing.contains(&key_type) {
+            findings.push(Finding::problem(
+                "Keyring",
+                format!("{} is configured but the {} key is missing", setting, key_type),
+                format!("Run `config set-key {}`.", cli_name(key_type)),
+            ));
+        }
+    }
+    findings
+}
+
+fn cli_name(key_type: ApiKeyType) -> String {
+    key_type.to_possible_value().map_or_else(|| key_type.to_string(), |value| value.get_name().to_string())
+}
@@ -0,0 +1,267 @@
+// src/commands/explore.rs
+// `stats --interactive`: a small line-based REPL to drill from apps to window titles to raw intervals.
+
+use crate::app_metadata::AppAliases;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence::{self, Storage};
+use crate::types::{AggregatedResult, AggregationLevel, DurationFormat, StatsFilter, TimePeriod};
+use crate::utils::{format_duration, format_local_timestamp};
+use clap::ValueEnum;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+Commands:
+  <n>                      drill into row n (app -> titles -> intervals)
+  back | b                 go up one level
+  home                     back to the application list
+  period <p>               today, last-hour, current-hour, this-week,
+                           last-week or last-12-weeks
+  sort time|name [asc|desc]
+  filter [text]            only rows containing text (no text = clear)
+  passive                  toggle excluding passive time
+  refresh | r              re-run the current query
+  help | ?                 this help
+  quit | q                 leave";
+
+/// A level of the drill-down.
+#[derive(Debug, Clone)]
+enum View {
+    Apps,
+    Titles { app: String },
+    Intervals { app: String, title: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Time,
+    Name,
+}
+
+#[derive(Debug)]
+struct Row {
+    label: String,
+    secs: i64,
+    /// View opened by selecting the row (None for raw intervals)
+    next: Option<View>,
+}
+
+struct Explorer {
+    storage: Box<dyn Storage>,
+    aliases: AppAliases,
+    duration_format: DurationFormat,
+    period: TimePeriod,
+    filter: StatsFilter,
+    /// Drill-down path; the last entry is the current view
+    views: Vec<View>,
+    sort: SortKey,
+    descending: bool,
+    text_filter: Option<String>,
+    /// Rows as last displayed, so row numbers stay valid until the next listing
+    rows: Vec<Row>,
+}
+
+impl Explorer {
+    fn current_view(&self) -> &View {
+        self.views.last().unwrap_or(&View::Apps)
+    }
+
+    fn load_rows(&mut self) -> AppResult<Vec<Row>> {
+        let rows = match self.current_view().clone() {
+            View::Apps => match self.storage.query_stats(self.period, AggregationLevel::ByApplication, &self.filter)? {
+                AggregatedResult::ByApp(apps) => apps
+                    .into_iter()
+                    .map(|(app, secs)| Row { label: self.aliases.display(&app).to_string(), next: Some(View::Titles { app }), secs })
+                    .collect(),
+                _ => Vec::new(),
+            },
+            View::Titles { app } => self
+                .storage
+                .query_app_titles(self.period, &app, &self.filter)?
+                .into_iter()
+                .map(|record| Row {
+                    label: if record.detailed_title.is_empty() { "(no title)".to_string() } else { record.detailed_title.clone() },
+                    secs: record.total_duration_secs,
+                    next: Some(View::Intervals { app: app.clone(), title: Some(record.detailed_title) }),
+                })
+                .collect(),
+            View::Intervals { app, title } => self
+                .storage
+                .query_target_intervals(self.period, &app, title.as_deref())?
+                .into_iter()
+                .filter(|interval| !(self.filter.exclude_passive && interval.passive))
+                .filter(|interval| {
+                    self.filter.user.as_ref().is_none_or(|user| interval.user_name.as_deref().unwrap_or("[Unknown User]") == user)
+                })
+                .map(|interval| Row {
+                    label: format!(
+                        "{} - {}{}  {}",
+                        format_local_timestamp(interval.start_time),
+                        format_local_timestamp(interval.end_time),
+                        if interval.passive { " (passive)" } else { "" },
+                        interval.detailed_title.unwrap_or_default()
+                    ),
+                    secs: interval.end_time - interval.start_time,
+                    next: None,
+                })
+                .collect(),
+        };
+        Ok(rows)
+    }
+
+    /// Re-queries the current view and prints it with row numbers.
+    fn show(&mut self) -> AppResult<()> {
+        let mut rows = self.load_rows()?;
+        if let Some(text) = &self.text_filter {
+            let text = text.to_lowercase();
+            rows.retain(|row| row.label.to_lowercase().contains(&text));
+        }
+        match self.sort {
+            SortKey::Time => rows.sort_by_key(|row| row.secs),
+            SortKey::Name => rows.sort_by_key(|row| row.label.to_lowercase()),
+        }
+        if self.descending {
+            rows.reverse();
+        }
+
+        println!("\n--- {} | {} ---", self.period, self.breadcrumb());
+        let mut status = vec![format!("sort {} {}", if self.sort == SortKey::Time { "time" } else { "name" }, if self.descending { "desc" } else { "asc" })];
+        if let Some(text) = &self.text_filter {
+            status.push(format!("filter '{}'", text));
+        }
+        if self.filter.exclude_passive {
+            status.push("excluding passive time".to_string());
+        }
+        println!("({})", status.join(", "));
+        if rows.is_empty() {
+            println!("  No activity recorded for this selection.");
+        }
+        let total: i64 = rows.iter().map(|row| row.secs).sum();
+        for (index, row) in rows.iter().enumerate() {
+            println!("  {:>3}. {:>8}  {}", index + 1, format_duration(row.secs, self.duration_format), row.label);
+        }
+        if !rows.is_empty() {
+            println!("       {:>8}  total", format_duration(total, self.duration_format));
+        }
+        self.rows = rows;
+        Ok(())
+    }
+
+    fn breadcrumb(&self) -> String {
+        match self.current_view() {
+            View::Apps => "Applications".to_string(),
+            View::Titles { app } => format!("Applications > {}", self.aliases.display(app)),
+            View::Intervals { app, title } => {
+                format!("Applications > {} > {}", self.aliases.display(app), title.as_deref().map_or("(all titles)", |t| if t.is_empty() { "(no title)" } else { t }))
+            }
+        }
+    }
+
+    /// Applies one command line; returns false when the user wants to leave.
+    fn handle(&mut self, line: &str) -> AppResult<bool> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else { return Ok(true) };
+        let rest: Vec<&str> = words.collect();
+        match command {
+            "quit" | "q" | "exit" => return Ok(false),
+            "help" | "?" => println!("{}", HELP),
+            "back" | "b" | ".." => {
+                if self.views.len() > 1 {
+                    self.views.pop();
+                    self.text_filter = None;
+                }
+                self.show()?;
+            }
+            "home" => {
+                self.views.truncate(1);
+                self.text_filter = None;
+                self.show()?;
+            }
+            "refresh" | "r" => self.show()?,
+            "passive" => {
+                self.filter.exclude_passive = !self.filter.exclude_passive;
+                self.show()?;
+            }
+            "period" => match rest.first().map(|name| TimePeriod::from_str(name, true)) {
+                Some(Ok(period)) => {
+                    self.period = period;
+                    self.show()?;
+                }
+                _ => println!("Usage: period today|last-hour|current-hour|this-week|last-week|last-12-weeks"),
+            },
+            "sort" => {
+                match rest.first().copied() {
+                    Some("time") => self.sort = SortKey::Time,
+                    Some("name") => self.sort = SortKey::Name,
+                    _ => {
+                        println!("Usage: sort time|name [asc|desc]");
+                        return Ok(true);
+                    }
+                }
+                // Longest first for time, alphabetical for names unless told otherwise
+                self.descending = match rest.get(1).copied() {
+                    Some("asc") => false,
+                    Some("desc") => true,
+                    _ => self.sort == SortKey::Time,
+                };
+                self.show()?;
+            }
+            "filter" => {
+                self.text_filter = if rest.is_empty() { None } else { Some(rest.join(" ")) };
+                self.show()?;
+            }
+            other => match other.parse::<usize>() {
+                Ok(number) if number >= 1 && number <= self.rows.len() => match self.rows[number - 1].next.clone() {
+                    Some(view) => {
+                        self.views.push(view);
+                        self.text_filter = None;
+                        self.show()?;
+                    }
+                    None => println!("Raw intervals are the most detailed level."),
+                },
+                Ok(_) => println!("No row {} (1-{}).", other, self.rows.len()),
+                Err(_) => println!("Unknown command '{}'. Type 'help' for the list.", other),
+            },
+        }
+        Ok(true)
+    }
+}
+
+/// Runs the explorer until `quit` or end of input, starting at the application list for `period`.
+pub fn execute(app_config: &AppConfig, filter: &StatsFilter, period: TimePeriod) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let aliases = AppAliases::load(storage.as_mut())?;
+    let mut explorer = Explorer {
+        storage,
+        aliases,
+        duration_format: app_config.duration_format,
+        period,
+        filter: filter.clone(),
+        views: vec![View::Apps],
+        sort: SortKey::Time,
+        descending: true,
+        text_filter: None,
+        rows: Vec::new(),
+    };
+    println!("Interactive stats for {}. Type 'help' for commands, 'quit' to leave.", persistence::describe_location(app_config));
+    explorer.show()?;
+
+    let stdin = io::stdin();
+    loop {
+        print!("stats> ");
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).map_err(|e| AppError::Unexpected(format!("Failed to read input: {}", e)))? == 0 {
+            println!();
+            break; // End of input (Ctrl+D / Ctrl+Z)
+        }
+        match explorer.handle(line.trim()) {
+            Ok(true) => {}
+            Ok(false) => break,
+            // A failed query should not end the session
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    Ok(())
+}
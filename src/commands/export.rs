@@ -0,0 +1,50 @@
+// src/commands/export.rs
+
+use crate::config::AppConfig;
+use crate::crypto;
+use crate::errors::{AppError, AppResult};
+use crate::export;
+use crate::persistence;
+use crate::types::ExportFormat;
+use std::path::PathBuf;
+
+/// Writes the activity of one local day in `format` to `output` (stdout when None).
+/// With `end_to_end_encryption` the file holds an encrypted payload instead.
+pub fn execute(app_config: &AppConfig, format: ExportFormat, date: Option<String>, output: Option<PathBuf>) -> AppResult<()> {
+    let timezone = &app_config.timezone;
+    let now = chrono::Utc::now().timestamp();
+    // Days start at `day_start_hour`, like the stats periods
+    let date = date.unwrap_or_else(|| timezone.date_string(now));
+    let day_start = timezone.date_start(&date).map_err(AppError::Config)?;
+    let day_end = timezone.next_day_start(day_start);
+
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let (blocks, interval_count) = export::load_blocks(storage.as_mut(), day_start, day_end, now)?;
+    log::info!("Exporting {} block(s) from {} interval(s) for {}.", blocks.len(), interval_count, date);
+
+    let contents = match format {
+        ExportFormat::Ics => export::render_ics(&blocks, &app_config.app_name, &app_config.device_id, app_config.duration_format),
+        ExportFormat::Html => {
+            let focus = export::load_focus(storage.as_mut(), day_start, day_end, now, &app_config.timezone)?;
+            export::render_html(&blocks, &focus, &date, &app_config.app_name, app_config.duration_format)
+        }
+        ExportFormat::Worklog => export::render_worklog(&export::load_worklog(storage.as_mut(), day_start, day_end, now)?),
+    };
+    let contents = if app_config.end_to_end_encryption {
+        let key = crypto::load_key(app_config)?;
+        let payload = crypto::encrypt(&key, contents.as_bytes())?;
+        serde_json::to_string_pretty(&payload).map_err(|e| AppError::Crypto(e.to_string()))?
+    } else {
+        contents
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, contents).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+            eprintln!("Wrote {} block(s) to {:?}.", blocks.len(), path);
+        }
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
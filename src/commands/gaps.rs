@@ -0,0 +1,75 @@
+// src/commands/gaps.rs
+// `gaps`: the periods of a day where nothing was recorded at all and why (see `gaps::find`), so
+// missing time can be told apart from time the tracker simply did not run.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::gaps::{self, GapReason};
+use crate::persistence;
+use crate::utils::format_duration;
+use chrono::Utc;
+use std::collections::BTreeMap;
+
+/// Lists the gaps of at least `min_minutes` on the local day `date` ("yesterday", "today" or YYYY-MM-DD).
+pub fn execute(app_config: &AppConfig, date: &str, min_minutes: u64) -> AppResult<()> {
+    let timezone = &app_config.timezone;
+    let now = Utc::now().timestamp();
+    let day_start = match date.trim() {
+        "today" => timezone.day_start(now),
+        "yesterday" => timezone.previous_day_start(timezone.day_start(now)),
+        date => timezone.date_start(date).map_err(AppError::Config)?,
+    };
+    let day_end = timezone.next_day_start(day_start).min(now);
+    if day_end <= day_start {
+        return Err(AppError::Config(format!("{} has not started yet", timezone.date_string(day_start))));
+    }
+
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let mut intervals = storage.query_intervals_between(day_start, day_end, now)?;
+    intervals.extend(storage.query_archived_intervals(day_start, day_end, None)?);
+    // Other users' intervals in a shared database are not this user's tracked time
+    if app_config.shared_database || app_config.database_url.is_some() {
+        intervals.retain(|interval| interval.user_name.as_deref().is_none_or(|user| user == app_config.user_name));
+    }
+    let history = storage.query_tracker_history(day_start, day_end, &app_config.user_name)?;
+    // Archived raw intervals still show where the gaps of aggregated hours were
+    let aggregated_hours = if app_config.archive_raw_intervals { Vec::new() } else { storage.query_aggregated_hours(day_start, day_end)? };
+    let report = gaps::find(&intervals, &history, &aggregated_hours, (day_start, day_end), now, min_minutes as i64 * 60);
+
+    let format = app_config.duration_format;
+    let heading = timezone.date_string(day_start);
+    if report.gaps.is_empty() {
+        println!("No gaps of {} min or more on {}.", min_minutes, heading);
+    } else {
+        println!("Gaps of {} min or more on {}:", min_minutes, heading);
+        for gap in &report.gaps {
+            println!(
+                "  {}–{}  {:>10}  {}",
+                timezone.time_string(gap.start),
+                timezone.time_string(gap.end),
+                format_duration(gap.end - gap.start, format),
+                gap.reason.describe()
+            );
+        }
+        let mut by_reason: BTreeMap<GapReason, i64> = BTreeMap::new();
+        for gap in &report.gaps {
+            *by_reason.entry(gap.reason).or_default() += gap.end - gap.start;
+        }
+        println!();
+        for (reason, secs) in by_reason {
+            println!("  {:<48} {}", reason.describe(), format_duration(secs, format));
+        }
+    }
+    println!();
+    println!(
+        "Tracked {} (locked {}), in gaps {}.",
+        format_duration(report.tracked_secs, format),
+        format_duration(report.locked_secs, format),
+        format_duration(report.untracked_secs, format)
+    );
+    if report.gaps.iter().any(|gap| gap.reason == GapReason::Aggregated) {
+        println!("Set \"archive_raw_intervals\" to keep the raw intervals of aggregated hours for this report.");
+    }
+    Ok(())
+}
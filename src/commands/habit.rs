@@ -0,0 +1,47 @@
+// src/commands/habit.rs
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::habits;
+use crate::persistence;
+use crate::types::{Habit, HabitCommand};
+use chrono::Utc;
+
+/// Execute habit commands
+pub fn execute(app_config: &AppConfig, command: HabitCommand) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    match command {
+        HabitCommand::Add { name, app_glob, min_minutes } => {
+            let (name, app_glob) = (name.trim(), app_glob.trim());
+            if name.is_empty() || app_glob.is_empty() {
+                return Err(AppError::Config("Both the habit name and --app must be given".to_string()));
+            }
+            let habit = Habit {
+                name: name.to_string(),
+                app_glob: app_glob.to_string(),
+                daily_minutes: min_minutes,
+                created_at: Utc::now().timestamp(),
+            };
+            storage.upsert_habit(&habit)?;
+            println!("Habit '{}': at least {} min/day in apps matching \"{}\".", name, min_minutes, app_glob);
+        }
+        HabitCommand::Remove { name } => {
+            if !storage.delete_habit(name.trim())? {
+                return Err(AppError::Config(format!("No habit named '{}'", name)));
+            }
+            println!("Habit '{}' removed.", name.trim());
+        }
+        HabitCommand::List => {
+            let statuses = habits::load_status(storage.as_mut(), &app_config.timezone, Utc::now().timestamp())?;
+            if statuses.is_empty() {
+                println!("No habits defined. Add one with: habit add coding --app \"code*\" --min-minutes 60");
+                return Ok(());
+            }
+            for status in &statuses {
+                println!("{}", habits::describe(status, app_config.duration_format));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+// src/commands/intervals.rs
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::types::{DurationFormat, IntervalRecord, IntervalsCommand};
+use crate::utils::{format_duration, format_local_timestamp, parse_user_time};
+use chrono::Utc;
+
+/// Execute raw interval inspection/editing commands
+pub fn execute(app_config: &AppConfig, command: IntervalsCommand) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let now = Utc::now().timestamp();
+    match command {
+        IntervalsCommand::List { from, to } => {
+            let from = match from {
+                Some(from) => parse_user_time(&from).map_err(AppError::Config)?.timestamp(),
+                None => app_config.timezone.day_start(now),
+            };
+            let to = match to {
+                Some(to) => parse_user_time(&to).map_err(AppError::Config)?.timestamp(),
+                None => now,
+            };
+            if from >= to {
+                return Err(AppError::Config("--from must be before --to".to_string()));
+            }
+            let intervals = storage.query_intervals_between(from, to, now)?;
+            print_intervals(&intervals, from, to, app_config.duration_format);
+        }
+        IntervalsCommand::Delete { id } => {
+            let interval = require_completed(storage.query_completed_interval(id)?, id)?;
+            if storage.delete_interval(id, now)? {
+                println!("Deleted interval {}: {}", id, describe(&interval));
+            }
+        }
+        IntervalsCommand::Split { id, at } => {
+            let at = parse_user_time(&at).map_err(AppError::Config)?.timestamp();
+            let interval = require_completed(storage.query_completed_interval(id)?, id)?;
+            if at <= interval.start_time || at >= interval.end_time {
+                return Err(AppError::Config(format!(
+                    "{} is not inside interval {} ({} - {})",
+                    format_local_timestamp(at),
+                    id,
+                    format_local_timestamp(interval.start_time),
+                    format_local_timestamp(interval.end_time)
+                )));
+            }
+            match storage.split_interval(id, at, now)? {
+                Some(new_id) => println!(
+                    "Split interval {} at {}: {} keeps the first {}, {} holds the remaining {}.",
+                    id,
+                    format_local_timestamp(at),
+                    id,
+                    format_duration(at - interval.start_time, app_config.duration_format),
+                    new_id,
+                    format_duration(interval.end_time - at, app_config.duration_format)
+                ),
+                None => println!("Interval {} changed while splitting; nothing was modified.", id),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn require_completed(interval: Option<IntervalRecord>, id: i64) -> AppResult<IntervalRecord> {
+    interval.ok_or_else(|| {
+        AppError::Config(format!(
+            "No completed raw interval with id {} (it may still be open, or already rolled into summaries by aggregation)",
+            id
+        ))
+    })
+}
+
+fn describe(interval: &IntervalRecord) -> String {
+    format!(
+        "{} '{}' {} - {}",
+        interval.app_name,
+        interval.detailed_title.as_deref().unwrap_or_default(),
+        format_local_timestamp(interval.start_time),
+        format_local_timestamp(interval.end_time)
+    )
+}
+
+fn print_intervals(intervals: &[IntervalRecord], from: i64, to: i64, duration_format: DurationFormat) {
+    println!("Raw intervals between {} and {}:", format_local_timestamp(from), format_local_timestamp(to));
+    if intervals.is_empty() {
+        println!("  None (older time may already be aggregated into summaries).");
+        return;
+    }
+    print_interval_table(intervals, duration_format);
+}
+
+/// One row per interval: id, start, end, duration, application and window title.
+pub(crate) fn print_interval_table(intervals: &[IntervalRecord], duration_format: DurationFormat) {
+    let max_app_len = intervals.iter().map(|i| i.app_name.len()).max().unwrap_or(20).max(11);
+    println!("  {:>8} | {:<19} | {:<19} | {:>8} | {:<app_width$} | Window Title", "Id", "Start", "End", "Duration", "Application", app_width = max_app_len);
+    println!("  {:->8}-+-{:-<19}-+-{:-<19}-+-{:->8}-+-{:-<app_width$}-+-------------", "", "", "", "", "", app_width = max_app_len);
+    for interval in intervals {
+        println!(
+            "  {:>8} | {:<19} | {:<19} | {:>8} | {:<app_width$} | {}",
+            interval.id,
+            format_local_timestamp(interval.start_time),
+            format_local_timestamp(interval.end_time),
+            format_duration(interval.end_time - interval.start_time, duration_format),
+            interval.app_name,
+            interval.detailed_title.as_deref().unwrap_or_default(),
+            app_width = max_app_len
+        );
+    }
+}
@@ -2,4 +2,33 @@ pub mod track; // <-- ADD THIS LINE
 pub mod stats;
 pub mod update;
 pub mod set_key;
+pub mod autostart;
+pub mod snapshots;
+pub mod sync;
+pub mod db;
+pub mod toggl;
+pub mod export;
+pub mod report;
+pub mod query;
+pub mod schedule;
+pub mod shell_hook;
+pub mod completions;
+pub mod explore;
+pub mod intervals;
+pub mod add_entry;
+pub mod app;
+pub mod doctor;
+pub mod self_stats;
+pub mod status;
+pub mod aggregate;
+pub mod normalize;
+pub mod archive;
+pub mod ask;
+pub mod screentime;
+pub mod habit;
+pub mod calendar;
+pub mod audit;
+pub mod gaps;
+pub mod purge;
 
+pub mod profile;
\ No newline at end of file
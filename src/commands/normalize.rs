@@ -0,0 +1,38 @@
+// src/commands/normalize.rs
+// `normalize`: lists the stored titles `title_rules` would rewrite; `--apply-history` rewrites them in the
+// raw intervals, the archive and the per-title summaries (new titles are rewritten while tracking).
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use crate::persistence;
+use crate::types::TitleRename;
+
+pub fn execute(app_config: &AppConfig, apply_history: bool) -> AppResult<()> {
+    if app_config.title_rules.is_empty() {
+        println!("No title rules. Add them under \"title_rules\" in {:?}.", app_config.config_file_path);
+        return Ok(());
+    }
+    let mut storage = persistence::open_storage(app_config)?;
+    let renames: Vec<TitleRename> = storage
+        .query_distinct_titles()?
+        .into_iter()
+        .filter_map(|(app_name, title)| {
+            let normalized = app_config.title_rules.normalize(&app_name, &title);
+            (normalized != title).then_some(TitleRename { app_name, from: title, to: normalized })
+        })
+        .collect();
+    if renames.is_empty() {
+        println!("All stored titles already match the title rules.");
+        return Ok(());
+    }
+    for rename in &renames {
+        println!("{:<24} {:?} -> {:?}", rename.app_name, rename.from, rename.to);
+    }
+    if apply_history {
+        let changed = storage.rename_titles(&renames, chrono::Utc::now().timestamp())?;
+        println!("Renamed {} title(s) in {} row(s) of {}.", renames.len(), changed, persistence::describe_location(app_config));
+    } else {
+        println!("{} title(s) would be renamed. Run `normalize --apply-history` to rewrite them.", renames.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// src/commands/profile.rs
+// `profile list/create/delete`: independent config.json + database pairs selected with `--profile`.
+// The default profile is the data directory itself; named ones live in `profiles/<name>/`.
+
+use crate::config::{self, CONFIG_FILE_NAME, DATABASE_FILE_NAME, DEFAULT_PROFILE};
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::types::{ApiKeyType, ProfileCommand};
+use crate::utils::format_bytes;
+use clap::ValueEnum;
+use keyring::Entry;
+
+/// `selected` is the `--profile` value of this invocation.
+pub fn execute(selected: Option<&str>, command: ProfileCommand) -> AppResult<()> {
+    let selected = selected.unwrap_or(DEFAULT_PROFILE);
+    match command {
+        ProfileCommand::List => {
+            let names = std::iter::once(DEFAULT_PROFILE.to_string()).chain(config::list_profiles()?);
+            for name in names {
+                let db_path = config::profile_dir_path(Some(&name))?.join(DATABASE_FILE_NAME);
+                let size = if db_path.exists() { format_bytes(persistence::database_file_size(&db_path)) } else { "no database".to_string() };
+                let marker = if name == selected { "*" } else { " " };
+                println!("{} {:<20} {}", marker, name, size);
+            }
+        }
+        ProfileCommand::Create { name, copy_config } => {
+            if name == DEFAULT_PROFILE {
+                return Err(AppError::Config(format!("'{}' always exists", DEFAULT_PROFILE)));
+            }
+            let dir = config::profile_dir_path(Some(&name))?;
+            if dir.exists() {
+                return Err(AppError::Config(format!("Profile '{}' already exists at {:?}", name, dir)));
+            }
+            std::fs::create_dir_all(&dir).map_err(|e| AppError::Io { path: dir.clone(), source: e })?;
+            if copy_config {
+                let source = config::profile_dir_path(Some(selected))?.join(CONFIG_FILE_NAME);
+                if source.exists() {
+                    let target = dir.join(CONFIG_FILE_NAME);
+                    std::fs::copy(&source, &target).map_err(|e| AppError::Io { path: target, source: e })?;
+                    println!("Copied {:?}.", source);
+                } else {
+                    println!("Profile '{}' has no config.json; starting from the defaults.", selected);
+                }
+            }
+            println!("Created profile '{}' in {:?}. Use it with `--profile {}`.", name, dir, name);
+            println!("It has its own keyring keys: set the ones it needs with `--profile {} config set-key ...`.", name);
+        }
+        ProfileCommand::Delete { name, yes } => {
+            if name == DEFAULT_PROFILE {
+                return Err(AppError::Config(format!("The '{}' profile cannot be deleted", DEFAULT_PROFILE)));
+            }
+            if name == selected {
+                return Err(AppError::Config(format!("Profile '{}' is selected with --profile; run delete from another profile", name)));
+            }
+            let dir = config::profile_dir_path(Some(&name))?;
+            if !dir.is_dir() {
+                return Err(AppError::Config(format!("Profile '{}' does not exist", name)));
+            }
+            if crate::config_lock::lock_path(&dir.join(config::CONFIG_FILE_NAME)).exists() {
+                return Err(AppError::Locked(format!("profile '{}' is locked; run `--profile {} config unlock` first", name, name)));
+            }
+            if !yes {
+                println!("This would delete {:?} (config and database) and the keyring keys of profile '{}'.", dir, name);
+                println!("Stop any tracker running with `--profile {}`, then rerun with --yes.", name);
+                return Ok(());
+            }
+            std::fs::remove_dir_all(&dir).map_err(|e| AppError::Io { path: dir.clone(), source: e })?;
+            let removed_keys = delete_keyring_keys(&config::keyring_service_name(Some(&name)));
+            println!("Deleted profile '{}' ({} keyring key(s) removed).", name, removed_keys);
+        }
+    }
+    Ok(())
+}
+
+/// Removes every key of the service; a keyring that cannot be reached only leaves keys behind.
+fn delete_keyring_keys(service_name: &str) -> usize {
+    let mut removed = 0;
+    for key_type in ApiKeyType::value_variants() {
+        match Entry::new(service_name, key_type.keyring_username()).and_then(|entry| entry.delete_credential()) {
+            Ok(()) => removed += 1,
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => log::warn!("Could not delete the {} key of {}: {}", key_type, service_name, e),
+        }
+    }
+    removed
+}
@@ -0,0 +1,79 @@
+// src/commands/purge.rs
+// `purge`: deletes tracked data for good, from every table in one transaction: everything (`--all`)
+// or one app's rows, optionally limited to a time range. A local SQLite file is vacuumed afterwards
+// so the deleted rows do not linger in free pages; backups in `backups/` are not touched.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::instance;
+use crate::persistence;
+use crate::types::PurgeScope;
+use crate::utils::{format_local_timestamp, parse_user_time};
+use std::io::{self, BufRead, Write};
+
+pub fn execute(app_config: &AppConfig, all: bool, app: Option<String>, from: Option<String>, to: Option<String>, yes: bool) -> AppResult<()> {
+    let parse = |time: Option<String>| time.map(|time| parse_user_time(&time).map(|time| time.timestamp())).transpose().map_err(AppError::Config);
+    let scope = match app {
+        Some(app_name) if !all => PurgeScope::App { app_name, from: parse(from)?, to: parse(to)? },
+        _ => PurgeScope::All,
+    };
+    if let PurgeScope::App { from: Some(from), to: Some(to), .. } = scope
+        && from >= to
+    {
+        return Err(AppError::Config("--from must be before --to".to_string()));
+    }
+    // A running tracker would keep writing into the open interval it holds
+    if let Some(tracker) = instance::running_tracker(app_config) {
+        return Err(AppError::Config(format!("A tracker is running on this database ({}); stop it before purging", tracker.describe())));
+    }
+
+    let location = persistence::describe_location(app_config);
+    let what = match &scope {
+        PurgeScope::All => "ALL tracked data (intervals, summaries, archive, snapshots, audit log, habits and app aliases)".to_string(),
+        PurgeScope::App { app_name, from, to } => {
+            let range = match (from, to) {
+                (None, None) => "all time, including its alias and audit log entries".to_string(),
+                (from, to) => format!(
+                    "starting from {} until {}",
+                    from.map_or("the beginning".to_string(), format_local_timestamp),
+                    to.map_or("now".to_string(), format_local_timestamp)
+                ),
+            };
+            format!("all data of '{}' ({})", app_name, range)
+        }
+    };
+    println!("This permanently deletes {} from {}.", what, location);
+    if !yes && !confirm()? {
+        println!("Nothing was deleted.");
+        return Ok(());
+    }
+
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let removed = storage.purge(&scope)?;
+    drop(storage);
+    let total: usize = removed.iter().map(|(_, rows)| rows).sum();
+    for (table, rows) in removed.iter().filter(|(_, rows)| *rows > 0) {
+        println!("  {:<24} {:>8}", table, rows);
+    }
+    println!("Removed {} row(s) from {}.", total, location);
+
+    if app_config.database_url.is_none() && total > 0 {
+        let passphrase = persistence::database_passphrase(app_config)?;
+        let conn = persistence::open_database(app_config, passphrase.as_deref())?;
+        let problems = persistence::maintain_db(&conn)?;
+        if !problems.is_empty() {
+            eprintln!("Integrity check found {} problem(s); the file was not vacuumed. Run `doctor`.", problems.len());
+        }
+    }
+    Ok(())
+}
+
+/// Asks for "yes"; anything else (or end of input) cancels.
+fn confirm() -> AppResult<bool> {
+    print!("Type \"yes\" to continue: ");
+    io::stdout().flush().ok();
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).map_err(|e| AppError::Unexpected(format!("Failed to read input: {}", e)))?;
+    Ok(line.trim().eq_ignore_ascii_case("yes"))
+}
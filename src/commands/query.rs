@@ -0,0 +1,168 @@
+// src/commands/query.rs
+// `query "<SQL>"`: read-only SQL against the local SQLite database for custom reports. The connection
+// is opened read-only with `query_only` on, and only a single statement SQLite reports as read-only runs.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::table::{Align, Column, Table};
+use crate::types::QueryFormat;
+use rusqlite::types::ValueRef;
+use rusqlite::fallible_iterator::FallibleIterator;
+use rusqlite::{Batch, Connection};
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+
+/// What each table created by `initialize_db` holds, shown by `query --schema`.
+const TABLE_DOCS: &[(&str, &str)] = &[
+    ("app_intervals", "Raw intervals, one per stretch of time on one window; deleted (or archived) after aggregation"),
+    ("app_intervals_archive", "Raw intervals kept after aggregation when archive_raw_intervals is on"),
+    ("hourly_summary", "Total seconds per app for each completed hour"),
+    ("daily_summary", "Total seconds per app for each completed day"),
+    ("days_summary_by_app", "Total seconds per app and local day, the source of the weekly totals"),
+    ("weeks_summary_by_app", "Total seconds per app for each week"),
+    ("daily_desktop_summary", "Total seconds per virtual desktop and app for each day"),
+    ("daily_device_summary", "Total seconds per device and app for each day"),
+    ("daily_repo_summary", "Total seconds per Git repository and app for each day (repo_detection)"),
+    ("daily_path_summary", "Total seconds per executable path and app for each day (record_executable_path)"),
+    ("window_snapshots", "Periodic snapshots of all open windows (snapshot_interval_mins)"),
+    ("audit_log", "Manual edits (intervals, add-entry, normalize) and system clock jumps"),
+    ("tracker_daily_stats", "Per day: intervals aggregated, longest session and failed detector polls"),
+    ("app_metadata", "Friendly names and icon paths of executables (app alias)"),
+    ("calendar_events", "Occurrences of calendar events read from the calendar (calendar sync)"),
+    ("daily_meeting_summary", "Total seconds per calendar event title and app for each day (calendar)"),
+    ("break_reminders", "Break reminders shown (break_reminder) and the start of the break that followed"),
+    ("daily_ticket_summary", "Total seconds per issue key and app for each day (ticket_detection)"),
+    ("daily_user_summary", "Total seconds per OS user and app for each day"),
+    ("daily_document_summary", "Total seconds per document (file open in an editor or office app) and app for each day"),
+    ("daily_remote_summary", "Total seconds per remote desktop session type, remote machine and app for each day"),
+    ("audio_intervals", "Apps playing audio, recorded in parallel with the foreground intervals (audio_tracking)"),
+    ("daily_audio_summary", "Total seconds per app playing audio and OS user for each completed day (audio_tracking)"),
+    ("tracker_runs", "Runs of the tracker: start, last heartbeat and clean end (NULL after a crash), per device and OS user"),
+    ("tracker_run_events", "System sleeps, clock jumps, pauses and tracking_hours boundaries within a tracker run (gaps)"),
+    ("habits", "Daily goals on app time and their streaks (habit add)"),
+    ("sync_state", "Watermark of the last interval pushed by sync"),
+];
+
+pub fn execute(app_config: &AppConfig, sql: Option<&str>, format: QueryFormat, schema: bool) -> AppResult<()> {
+    if app_config.database_url.is_some() {
+        return Err(AppError::Config("query reads the local SQLite database (database_url is set)".to_string()));
+    }
+    let db_path = &app_config.database_path;
+    if !db_path.exists() {
+        return Err(AppError::Config(format!("No database at {:?} yet", db_path)));
+    }
+    let passphrase = persistence::database_passphrase(app_config)?;
+    let conn = persistence::open_connection_read_only(db_path, passphrase.as_deref())?;
+    if schema {
+        return print_schema(&conn);
+    }
+    let sql = sql.ok_or_else(|| AppError::Config("Pass an SQL statement or --schema".to_string()))?;
+
+    let mut batch = Batch::new(&conn, sql);
+    let Some(mut stmt) = batch.next()? else {
+        return Err(AppError::Config("The SQL contains no statement".to_string()));
+    };
+    if batch.next()?.is_some() {
+        return Err(AppError::Config("Run one statement at a time".to_string()));
+    }
+    if !stmt.readonly() {
+        return Err(AppError::Config("Only read-only statements can be run (e.g. SELECT, WITH ... SELECT, read-only PRAGMAs)".to_string()));
+    }
+    let headers: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let mut rows: Vec<Vec<Value>> = Vec::new();
+    let mut result = stmt.query([])?;
+    while let Some(row) = result.next()? {
+        let values = (0..headers.len()).map(|index| row.get_ref(index).map(to_json)).collect::<Result<Vec<_>, _>>()?;
+        rows.push(values);
+    }
+
+    print_rows(&headers, rows, format)
+}
+
+/// Prints result rows (JSON values, NULL = `Value::Null`) in `format`; also used by saved reports.
+pub fn print_rows(headers: &[String], rows: Vec<Vec<Value>>, format: QueryFormat) -> AppResult<()> {
+    write_rows(&mut io::stdout().lock(), headers, rows, format).map_err(|e| AppError::Unexpected(format!("Cannot write the rows: {}", e)))
+}
+
+/// `print_rows` into any writer (report files written by scheduled jobs).
+pub fn write_rows(out: &mut dyn Write, headers: &[String], rows: Vec<Vec<Value>>, format: QueryFormat) -> io::Result<()> {
+    match format {
+        QueryFormat::Table => write!(out, "{}", render_table(headers, &rows))?,
+        QueryFormat::Csv => {
+            writeln!(out, "{}", headers.iter().map(|header| csv_field(header)).collect::<Vec<_>>().join(","))?;
+            for row in &rows {
+                writeln!(out, "{}", row.iter().map(|value| csv_field(&plain_text(value))).collect::<Vec<_>>().join(","))?;
+            }
+        }
+        QueryFormat::Json => {
+            let objects: Vec<Value> = rows
+                .into_iter()
+                .map(|row| Value::Object(headers.iter().cloned().zip(row).collect::<Map<String, Value>>()))
+                .collect();
+            writeln!(out, "{}", serde_json::to_string_pretty(&objects).map_err(io::Error::other)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Blobs become lowercase hex strings.
+fn to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(number) => Value::from(number),
+        ValueRef::Real(number) => Value::from(number),
+        ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::from(bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+    }
+}
+
+/// NULL is an empty field in CSV and the table.
+fn plain_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Columns holding only numbers (and NULLs) are right-aligned.
+fn render_table(headers: &[String], rows: &[Vec<Value>]) -> String {
+    let columns = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            let numeric = rows.iter().all(|row| matches!(row[index], Value::Number(_) | Value::Null));
+            Column::new(header.clone()).align(if numeric && !rows.is_empty() { Align::Right } else { Align::Left }).max_width(Some(60))
+        })
+        .collect();
+    let mut table = Table::new(columns);
+    for row in rows {
+        table.add_row(row.iter().map(plain_text));
+    }
+    format!("{}({} row{})\n", table.render(), rows.len(), if rows.len() == 1 { "" } else { "s" })
+}
+
+/// Every table with its columns (name, type, constraints) as created, plus what it holds.
+fn print_schema(conn: &Connection) -> AppResult<()> {
+    println!("Timestamps are UTC Unix seconds; durations are seconds.");
+    let mut stmt = conn.prepare("SELECT name, sql FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+    let tables = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))?;
+    for table in tables {
+        let (name, sql) = table?;
+        println!();
+        if let Some((_, doc)) = TABLE_DOCS.iter().find(|(table, _)| *table == name) {
+            println!("-- {}", doc);
+        }
+        println!("{};", sql.unwrap_or_else(|| format!("-- {} (no definition)", name)).trim());
+    }
+    Ok(())
+}
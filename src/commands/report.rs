@@ -0,0 +1,118 @@
+// src/commands/report.rs
+
+use crate::app_metadata::AppAliases;
+use crate::commands;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::notify;
+use crate::persistence::{self, Storage};
+use crate::saved_reports::SavedReport;
+use crate::types::{AggregatedResult, AggregationLevel, DisplayOptions, PostTarget, QueryFormat, ReportCommand, TimePeriod};
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// Prints the summary of `period`, or posts it to each of `post` (Slack/Discord webhooks of the `notify` section).
+pub fn execute(app_config: &AppConfig, period: TimePeriod, post: &[PostTarget]) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let summary = notify::summarize(app_config, storage.as_mut(), period, chrono::Utc::now().timestamp())?;
+    if post.is_empty() {
+        println!("{}", notify::render(&summary, None, app_config));
+        return Ok(());
+    }
+    for target in post {
+        notify::post(app_config, &summary, *target)?;
+        println!("✅ Posted the {} summary to {}.", summary.heading, target);
+    }
+    Ok(())
+}
+
+/// `report run <NAME>` / `report list`: saved reports from the `reports` section of config.json.
+pub fn execute_saved(app_config: &AppConfig, command: ReportCommand, color: bool) -> AppResult<()> {
+    match command {
+        ReportCommand::List => {
+            if app_config.reports.is_empty() {
+                println!("No saved reports. Add them under \"reports\" in {:?}.", app_config.config_file_path);
+                return Ok(());
+            }
+            for (name, report) in &app_config.reports {
+                let period = report.period.map_or("default periods".to_string(), |period| period.to_string());
+                let description = report.description.as_deref().map(|text| format!(": {}", text)).unwrap_or_default();
+                println!("{:<24} {}, {}{}", name, period, report.level, description);
+            }
+            Ok(())
+        }
+        ReportCommand::Run { name } => {
+            let report = app_config.reports.get(&name).ok_or_else(|| {
+                let known: Vec<&str> = app_config.reports.keys().map(String::as_str).collect();
+                AppError::Config(format!("No saved report '{}' (known: {})", name, if known.is_empty() { "none".to_string() } else { known.join(", ") }))
+            })?;
+            run_saved(app_config, report, color)
+        }
+    }
+}
+
+/// The table format is the `stats` output; CSV and JSON hold one row per period and entry, in seconds.
+fn run_saved(app_config: &AppConfig, report: &SavedReport, color: bool) -> AppResult<()> {
+    if report.format == QueryFormat::Table {
+        let display = DisplayOptions { max_title_width: None, color, duration_format: app_config.duration_format, split_weekend: false, reveal: false };
+        return commands::stats::execute(app_config, report.level, report.period, &report.filter, &display);
+    }
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let (headers, rows) = saved_report_rows(storage.as_mut(), report)?;
+    commands::query::print_rows(&headers, rows, report.format)
+}
+
+/// Column names and rows of a saved report: one row per period and entry, with `total_secs`.
+pub fn saved_report_rows(storage: &mut dyn Storage, report: &SavedReport) -> AppResult<(Vec<String>, Vec<Vec<Value>>)> {
+    let aliases = AppAliases::load(storage)?;
+    let periods = report.period.map_or_else(|| vec![TimePeriod::Today, TimePeriod::LastCompletedHour, TimePeriod::CurrentHour], |period| vec![period]);
+    let key_columns: &[&str] = match report.level {
+        AggregationLevel::ByApplication => &["app"],
+        AggregationLevel::Detailed => &["app", "title"],
+        AggregationLevel::ByDesktop => &["desktop"],
+        AggregationLevel::ByDevice => &["device"],
+        AggregationLevel::ByRepo => &["repo"],
+        AggregationLevel::ByMeeting => &["meeting"],
+        AggregationLevel::ByTicket => &["ticket"],
+        AggregationLevel::ByUser => &["user"],
+        AggregationLevel::ByDocument => &["document"],
+        AggregationLevel::ByRemote => &["remote"],
+        AggregationLevel::ByPath => &["executable_path"],
+        AggregationLevel::Focus => &["metric"],
+        AggregationLevel::Profile => &["day_type", "hour"],
+    };
+    let value_column = if report.level == AggregationLevel::Focus { "value" } else { "total_secs" };
+    let headers: Vec<String> = std::iter::once("period").chain(key_columns.iter().copied()).chain([value_column]).map(str::to_string).collect();
+
+    let mut rows: Vec<Vec<Value>> = Vec::new();
+    for period in periods {
+        let period_name = period.to_possible_value().map_or(period.to_string(), |value| value.get_name().to_string());
+        let mut add = |keys: Vec<String>, value: Value| {
+            rows.push(std::iter::once(Value::from(period_name.clone())).chain(keys.into_iter().map(Value::from)).chain([value]).collect());
+        };
+        match storage.query_stats(period, report.level, &report.filter)? {
+            AggregatedResult::ByApp(totals) => aliases.apply_to_totals(totals).into_iter().for_each(|(app, secs)| add(vec![app], secs.into())),
+            AggregatedResult::Detailed(records) => records
+                .into_iter()
+                .for_each(|record| add(vec![aliases.display(&record.app_name).to_string(), record.detailed_title], record.total_duration_secs.into())),
+            AggregatedResult::ByDesktop(totals) | AggregatedResult::ByDevice(totals) | AggregatedResult::ByRepo(totals) | AggregatedResult::ByPath(totals) | AggregatedResult::ByMeeting(totals) | AggregatedResult::ByTicket(totals) | AggregatedResult::ByUser(totals) | AggregatedResult::ByDocument(totals) | AggregatedResult::ByRemote(totals) => totals.into_iter().for_each(|(key, secs)| add(vec![key], secs.into())),
+            AggregatedResult::Focus(metrics) => {
+                let longest_secs = metrics.longest_session.as_ref().map_or(0, |(_, start, end)| end - start);
+                add(vec!["tracked_secs".to_string()], metrics.tracked_secs.into());
+                add(vec!["switches".to_string()], metrics.switches.into());
+                add(vec!["switches_per_hour".to_string()], ((metrics.switches_per_hour() * 10.0).round() / 10.0).into());
+                add(vec!["sessions".to_string()], metrics.sessions.into());
+                add(vec!["average_session_secs".to_string()], metrics.average_session_secs().into());
+                add(vec!["longest_session_secs".to_string()], longest_secs.into());
+            }
+            AggregatedResult::Profile(profile) => {
+                for (day_type, hours) in [("weekday", profile.weekday_secs), ("weekend", profile.weekend_secs)] {
+                    hours.iter().enumerate().for_each(|(hour, secs)| add(vec![day_type.to_string(), format!("{:02}", hour)], (*secs).into()));
+                }
+            }
+        }
+    }
+    Ok((headers, rows))
+}
@@ -0,0 +1,41 @@
+// src/commands/schedule.rs
+// `schedule list` / `schedule next`: the jobs of the `schedule` section and when the tracker runs them.
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use crate::schedule;
+use crate::types::ScheduleCommand;
+use crate::utils::format_duration_secs;
+
+pub fn execute(app_config: &AppConfig, command: ScheduleCommand) -> AppResult<()> {
+    if app_config.schedule.is_empty() {
+        println!("No scheduled jobs. Add them under \"schedule\" in {:?}.", app_config.config_file_path);
+        return Ok(());
+    }
+    match command {
+        ScheduleCommand::List => {
+            for job in &app_config.schedule {
+                println!("{:<24} {:<24} {}", job.name, job.kind.to_string(), job.timing);
+            }
+        }
+        ScheduleCommand::Next => {
+            let now = chrono::Utc::now().timestamp();
+            let mut runs: Vec<_> = app_config.schedule.iter().map(|job| (job.timing.next_after(now, &app_config.timezone), job)).collect();
+            runs.sort_by_key(|(next, _)| *next);
+            for (next, job) in runs {
+                // Interval jobs count from tracker start, so only their period is known here
+                let when = match job.timing {
+                    schedule::Timing::Every(_) => format!("{} after the tracker starts, then repeating", job.timing),
+                    schedule::Timing::Daily { .. } => format!(
+                        "{} (in {})",
+                        schedule::format_run_time(&app_config.timezone, next),
+                        format_duration_secs(next - now)
+                    ),
+                };
+                println!("{:<24} {:<24} {}", job.name, job.kind.to_string(), when);
+            }
+        }
+    }
+    println!("Jobs only run while the tracker is running; runs missed while it was stopped are skipped.");
+    Ok(())
+}
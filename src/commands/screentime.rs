@@ -0,0 +1,134 @@
+// src/commands/screentime.rs
+// `screentime`: today at a glance, phone style. Total and per-category time with bars and the
+// `screentime` limits, then pickups (app switches) and the longest single-app streak, both counted
+// over raw intervals so hours already aggregated without `archive_raw_intervals` are not included.
+
+use crate::app_metadata::AppAliases;
+use crate::clock::SystemClock;
+use crate::config::{AppConfig, ScreenTimeCategory};
+use crate::detection::{BREAK_APP_NAME, LOCKED_APP_NAME};
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::table::{Align, Cell, Column, Table};
+use crate::theme::{duration_bar, Palette};
+use crate::types::{AggregatedResult, AggregationLevel, DurationFormat, StatsFilter, TimePeriod};
+use crate::utils::{format_duration, glob_match};
+
+/// Apps no category matches are added up under this name.
+const OTHER_CATEGORY: &str = "Other";
+/// Intervals of the same app at most this far apart belong to one streak (short idle, detection hiccups)
+const STREAK_MAX_GAP_SECS: i64 = 120;
+
+pub fn execute(app_config: &AppConfig, color: bool) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let palette = Palette::new(&app_config.theme, color);
+    let format = app_config.duration_format;
+    let timezone = &app_config.timezone;
+    let (start, end) = persistence::calculate_timestamps(TimePeriod::Today, timezone, &SystemClock);
+    let now = chrono::Utc::now().timestamp();
+
+    let AggregatedResult::ByApp(rows) = storage.query_stats(TimePeriod::Today, AggregationLevel::ByApplication, &StatsFilter::default())? else {
+        return Err(AppError::Unexpected("Unexpected stats result shape".to_string()));
+    };
+    // Locked time and breaks marked with `audit` are not screen time
+    let rows = rows.into_iter().filter(|(app, _)| app != LOCKED_APP_NAME && app != BREAK_APP_NAME).collect();
+    let mut apps = AppAliases::load(storage.as_mut())?.apply_to_totals(rows);
+    apps.retain(|(_, secs)| *secs > 0);
+    apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let total: i64 = apps.iter().map(|(_, secs)| secs).sum();
+
+    let heading = format!("Screen time {} ({})", timezone.date_string(now), timezone.weekday_name(now));
+    println!("{}: {}{}", palette.paint(palette.header(), &heading), format_duration(total, format), limit_note(total, app_config.screentime.daily_limit_minutes, format));
+    if apps.is_empty() {
+        println!("Nothing tracked today.");
+        return Ok(());
+    }
+
+    let categories = categorize(&app_config.screentime.categories, &apps);
+    let max_secs = categories.first().map_or(0, |(_, secs, _)| *secs);
+    let mut table = Table::new(vec![
+        Column::new("Category").max_width(Some(30)),
+        Column::new("Time").align(Align::Right),
+        Column::new(""),
+        Column::new("Limit"),
+    ])
+    .header_style(palette.header());
+    for (name, secs, limit) in &categories {
+        table.add_row([
+            Cell::styled(name.as_str(), palette.app(name, false)),
+            Cell::from(format_duration(*secs, format)),
+            Cell::styled(duration_bar(*secs, max_secs), palette.bar()),
+            Cell::from(limit.map_or(String::new(), |minutes| limit_status(*secs, minutes, format))),
+        ]);
+    }
+    print!("{}", table.render());
+
+    let (top_app, top_secs) = &apps[0];
+    println!("Most used: {} ({})", top_app, format_duration(*top_secs, format));
+    let switches = storage.query_switch_stats(start, end, now, STREAK_MAX_GAP_SECS)?;
+    println!("Pickups: {} app switches", switches.switches);
+    if let Some((app, streak_start, streak_end)) = &switches.longest_streak {
+        println!(
+            "Longest streak: {} for {} ({}-{})",
+            app,
+            format_duration(streak_end - streak_start, format),
+            timezone.time_string(*streak_start),
+            timezone.time_string(*streak_end)
+        );
+    }
+
+    let limited: Vec<&(String, i64, Option<u64>)> = categories.iter().filter(|(_, _, limit)| limit.is_some()).collect();
+    let mut exceeded: Vec<&str> = limited
+        .iter()
+        .filter(|(_, secs, limit)| limit.is_some_and(|minutes| *secs > minutes as i64 * 60))
+        .map(|(name, _, _)| name.as_str())
+        .collect();
+    if app_config.screentime.daily_limit_minutes.is_some_and(|minutes| total > minutes as i64 * 60) {
+        exceeded.insert(0, "daily total");
+    }
+    let limit_count = limited.len() + usize::from(app_config.screentime.daily_limit_minutes.is_some());
+    if limit_count > 0 {
+        if exceeded.is_empty() {
+            println!("Limits: all {} within limit", limit_count);
+        } else {
+            println!("Limits: {} of {} exceeded ({})", exceeded.len(), limit_count, exceeded.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Totals per category (largest first) with the category's limit; every category with a limit is listed,
+/// even at zero, and apps matching none are added up under "Other".
+fn categorize(categories: &[ScreenTimeCategory], apps: &[(String, i64)]) -> Vec<(String, i64, Option<u64>)> {
+    let mut totals: Vec<(String, i64, Option<u64>)> = categories
+        .iter()
+        .filter(|category| category.daily_limit_minutes.is_some())
+        .map(|category| (category.name.clone(), 0, category.daily_limit_minutes))
+        .collect();
+    for (app, secs) in apps {
+        // Time `audit` assigned to a category is recorded under the category's name
+        let category = categories.iter().find(|category| category.name == *app || category.apps.iter().any(|glob| glob_match(glob, app)));
+        let name = category.map_or(OTHER_CATEGORY, |category| category.name.as_str());
+        match totals.iter_mut().find(|(existing, _, _)| existing == name) {
+            Some((_, total, _)) => *total += secs,
+            None => totals.push((name.to_string(), *secs, category.and_then(|category| category.daily_limit_minutes))),
+        }
+    }
+    totals.sort_by_key(|(_, secs, _)| std::cmp::Reverse(*secs));
+    totals
+}
+
+/// "12m left" or "over by 15m", against a limit in minutes.
+fn limit_status(secs: i64, limit_minutes: u64, format: DurationFormat) -> String {
+    let limit_secs = limit_minutes as i64 * 60;
+    if secs > limit_secs {
+        format!("over by {} (limit {})", format_duration(secs - limit_secs, format), format_duration(limit_secs, format))
+    } else {
+        format!("{} left (limit {})", format_duration(limit_secs - secs, format), format_duration(limit_secs, format))
+    }
+}
+
+fn limit_note(secs: i64, limit_minutes: Option<u64>, format: DurationFormat) -> String {
+    limit_minutes.map_or(String::new(), |minutes| format!(", {}", limit_status(secs, minutes, format)))
+}
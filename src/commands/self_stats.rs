@@ -0,0 +1,89 @@
+// src/commands/self_stats.rs
+// `self-stats`: health figures about the tracker itself, read from the local database only
+// (handy to paste into a bug report; nothing is sent anywhere).
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::types::TrackerDailyStats;
+use crate::utils::{format_bytes, format_duration};
+use chrono::Utc;
+
+pub fn execute(app_config: &AppConfig) -> AppResult<()> {
+    if app_config.database_url.is_some() {
+        return Err(AppError::Config("self-stats reads the local SQLite database (database_url is set)".to_string()));
+    }
+    let db_path = &app_config.database_path;
+    if !db_path.exists() {
+        return Err(AppError::Config(format!("No database at {:?} yet", db_path)));
+    }
+    let passphrase = persistence::database_passphrase(app_config)?;
+    let conn = persistence::open_database(app_config, passphrase.as_deref())?;
+    let timezone = &app_config.timezone;
+    let now = Utc::now().timestamp();
+
+    // Aggregated days plus the raw intervals not aggregated yet
+    let mut days = persistence::query_tracker_daily_stats(&conn)?;
+    let raw_intervals = persistence::query_completed_intervals_until(&conn, now)?;
+    for raw_day in persistence::plan_tracker_days(&raw_intervals, timezone) {
+        match days.iter_mut().find(|day| day.day_timestamp == raw_day.day_timestamp) {
+            Some(day) => {
+                day.interval_count += raw_day.interval_count;
+                day.longest_session_secs = day.longest_session_secs.max(raw_day.longest_session_secs);
+            }
+            None => days.push(TrackerDailyStats {
+                day_timestamp: raw_day.day_timestamp,
+                interval_count: raw_day.interval_count,
+                longest_session_secs: raw_day.longest_session_secs,
+                ..TrackerDailyStats::default()
+            }),
+        }
+    }
+    let tracked_days = persistence::query_tracked_days(&conn, timezone.day_offset_secs(now))?;
+    let format = app_config.duration_format;
+
+    println!("mouse_tracking {} ({}/{}), database {:?}", app_config.app_version, std::env::consts::OS, std::env::consts::ARCH, db_path);
+    println!("{:<22} {}", "Database size:", format_bytes(persistence::database_file_size(db_path)));
+    println!("{:<22} {} (this build: {})", "Schema version:", persistence::schema_version(&conn)?, persistence::SCHEMA_VERSION);
+    match (tracked_days.first(), tracked_days.last()) {
+        (Some(first), Some(last)) => println!(
+            "{:<22} {} ({} to {})",
+            "Days tracked:",
+            tracked_days.len(),
+            timezone.date_string(*first),
+            timezone.date_string(*last)
+        ),
+        _ => println!("{:<22} 0", "Days tracked:"),
+    }
+
+    let counted: Vec<&TrackerDailyStats> = days.iter().filter(|day| day.interval_count > 0).collect();
+    if counted.is_empty() {
+        println!("{:<22} no data yet", "Intervals per day:");
+    } else {
+        let total: i64 = counted.iter().map(|day| day.interval_count).sum();
+        println!("{:<22} {:.1} (over {} day(s))", "Intervals per day:", total as f64 / counted.len() as f64, counted.len());
+    }
+    match days.iter().max_by_key(|day| day.longest_session_secs).filter(|day| day.longest_session_secs > 0) {
+        Some(day) => println!(
+            "{:<22} {} (started {})",
+            "Longest session:",
+            format_duration(day.longest_session_secs, format),
+            timezone.date_string(day.day_timestamp)
+        ),
+        None => println!("{:<22} no data yet", "Longest session:"),
+    }
+
+    let error_days: Vec<&TrackerDailyStats> = days.iter().filter(|day| day.detection_errors > 0).collect();
+    let total_errors: i64 = error_days.iter().map(|day| day.detection_errors).sum();
+    println!("{:<22} {} on {} day(s)", "Detection errors:", total_errors, error_days.len());
+    if let Some(day) = error_days.iter().max_by_key(|day| day.day_timestamp) {
+        println!("{:<22} {} on {}", "Last error:", day.last_error.as_deref().unwrap_or("?"), timezone.date_string(day.day_timestamp));
+    }
+
+    println!("Rows per table:");
+    for (table, count) in persistence::table_row_counts(&conn)? {
+        println!("  {:<26} {:>10}", table, count);
+    }
+    println!("\nInterval counts and sessions cover days since this version started recording them.");
+    Ok(())
+}
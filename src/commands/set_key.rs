@@ -10,13 +10,24 @@ use clap::ValueEnum; // <--- Added based on Problem 2
 
 // --- Main Execution Function ---
 
-/// Execute configuration-related commands (currently only set-key)
+/// Execute configuration-related commands (keys, encryption)
 pub fn execute_config_command(app_config: &AppConfig, command: ConfigCommand) -> AppResult<()> { // Renamed function example
     match command {
         ConfigCommand::SetKey { key_type } => {
             log::info!("Executing set-key command for type: {:?}", key_type);
             set_api_key(app_config, key_type)?;
         }
+        ConfigCommand::GenEncryptionKey => generate_encryption_key(app_config)?,
+        ConfigCommand::Decrypt { input } => decrypt_file(app_config, &input)?,
+        ConfigCommand::ListKeys => list_keys(app_config)?,
+        ConfigCommand::DeleteKey { key_type, force } => delete_key(app_config, key_type, force)?,
+        ConfigCommand::TestKey { key_type } => {
+            test_key(app_config, key_type)?;
+            println!("✅ {} key is valid.", key_type);
+        }
+        ConfigCommand::Lock => crate::config_lock::lock(app_config)?,
+        ConfigCommand::Unlock => crate::config_lock::unlock(app_config)?,
+        ConfigCommand::LockStatus => crate::config_lock::print_status(app_config)?,
     }
     Ok(())
 }
@@ -60,6 +71,149 @@ fn set_api_key(app_config: &AppConfig, key_type: ApiKeyType) -> AppResult<()> {
     Ok(())
 }
 
+/// Prints every key type and whether the keyring holds a value for it.
+fn list_keys(app_config: &AppConfig) -> AppResult<()> {
+    println!("Keys in keyring service '{}':", app_config.keyring_service_name);
+    for key_type in ApiKeyType::value_variants() {
+        let entry = Entry::new(&app_config.keyring_service_name, key_type.keyring_username())?;
+        let state = match entry.get_password() {
+            Ok(_) => "set".to_string(),
+            Err(keyring::Error::NoEntry) => "not set".to_string(),
+            Err(e) => format!("unreadable ({})", e),
+        };
+        println!("  {:<12} {}", cli_name(*key_type), state);
+    }
+    Ok(())
+}
+
+/// Removes a stored key. The encryption key, database passphrase and pseudonymization salt need
+/// `force`: without the first two, encrypted exports and an encrypted database cannot be read
+/// anymore; without the salt, new hashes no longer match the stored ones.
+fn delete_key(app_config: &AppConfig, key_type: ApiKeyType, force: bool) -> AppResult<()> {
+    if key_type == ApiKeyType::Pseudonym && !force {
+        return Err(AppError::Config(
+            "A new salt gives different hashes, so new data would not add up with the stored data; add --force to delete it anyway.".to_string(),
+        ));
+    }
+    if matches!(key_type, ApiKeyType::Encryption | ApiKeyType::Database) && !force {
+        return Err(AppError::Config(format!(
+            "Data encrypted with the {} key cannot be read without it; add --force to delete it anyway.",
+            key_type
+        )));
+    }
+    let entry = Entry::new(&app_config.keyring_service_name, key_type.keyring_username())?;
+    match entry.delete_credential() {
+        Ok(()) => {
+            log::info!("{} key deleted from keyring.", key_type);
+            println!("✅ {} key deleted.", key_type);
+            Ok(())
+        }
+        Err(keyring::Error::NoEntry) => Err(AppError::ApiKeyNotFound(key_type, cli_name(key_type))),
+        Err(e) => Err(AppError::Keyring(e)),
+    }
+}
+
+/// Checks a stored key: one request to the service it is for, or a local check for the
+/// encryption key and database passphrase.
+fn test_key(app_config: &AppConfig, key_type: ApiKeyType) -> AppResult<()> {
+    match key_type {
+        ApiKeyType::OpenAI => {
+            let key = load_api_key(app_config, key_type)?;
+            let request = http_client()?.get("https://api.openai.com/v1/models").bearer_auth(key);
+            check_response(key_type, request)
+        }
+        ApiKeyType::Google => {
+            let key = load_api_key(app_config, key_type)?;
+            let request = http_client()?.get("https://generativelanguage.googleapis.com/v1beta/models").header("x-goog-api-key", key);
+            check_response(key_type, request)
+        }
+        ApiKeyType::Anthropic => {
+            let key = load_api_key(app_config, key_type)?;
+            let request = http_client()?
+                .get("https://api.anthropic.com/v1/models")
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01");
+            check_response(key_type, request)
+        }
+        ApiKeyType::Sync => crate::sync::test_token(app_config),
+        ApiKeyType::Toggl => crate::commands::toggl::test_token(app_config),
+        ApiKeyType::Mqtt => crate::mqtt::test_credentials(app_config),
+        ApiKeyType::Caldav => crate::calendar::test_credentials(app_config),
+        ApiKeyType::Encryption => crate::crypto::load_key(app_config).map(|_| ()),
+        ApiKeyType::Pseudonym => crate::pseudonym::Pseudonymizer::load(app_config).map(|_| ()),
+        ApiKeyType::Database => {
+            let passphrase = crate::persistence::database_passphrase(app_config)?.ok_or_else(|| {
+                AppError::Config("database_encryption is off, so the database passphrase is not used.".to_string())
+            })?;
+            if !app_config.database_path.exists() {
+                return Err(AppError::Config(format!("No database at {:?} to test the passphrase on.", app_config.database_path)));
+            }
+            // A wrong passphrase fails reading the first page (SQLITE_NOTADB)
+            let conn = crate::persistence::open_database(app_config, Some(&passphrase))?;
+            conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))?;
+            Ok(())
+        }
+    }
+}
+
+fn http_client() -> AppResult<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| AppError::Integration(e.to_string()))
+}
+
+fn check_response(key_type: ApiKeyType, request: reqwest::blocking::RequestBuilder) -> AppResult<()> {
+    let response = request.send().map_err(|e| AppError::Integration(format!("{} request failed: {}", key_type, e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Integration(format!("{} rejected the key: HTTP {}", key_type, response.status())));
+    }
+    Ok(())
+}
+
+/// Name of the key type on the command line ("openai", "sync", ...).
+fn cli_name(key_type: ApiKeyType) -> String {
+    key_type.to_possible_value().map_or_else(|| "unknown".to_string(), |value| value.get_name().to_string())
+}
+
+/// Creates a random encryption key and stores it, refusing to overwrite an existing one
+/// (data encrypted with it could no longer be read).
+fn generate_encryption_key(app_config: &AppConfig) -> AppResult<()> {
+    let entry = Entry::new(&app_config.keyring_service_name, ApiKeyType::Encryption.keyring_username())?;
+    match entry.get_password() {
+        Ok(_) => {
+            return Err(AppError::Config(
+                "An encryption key already exists; use 'config set-key encryption' to replace it explicitly.".to_string(),
+            ));
+        }
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(AppError::Keyring(e)),
+    }
+    let key = crate::crypto::generate_key();
+    entry.set_password(&key)?;
+    println!("✅ Encryption key generated and saved.");
+    println!("Copy it to every device/reader that must decrypt your data (set it there with 'config set-key encryption'):");
+    println!("{}", key);
+    Ok(())
+}
+
+/// Prints the plaintext of an encrypted payload (saved sync body or export file).
+fn decrypt_file(app_config: &AppConfig, input: &std::path::Path) -> AppResult<()> {
+    let contents = std::fs::read_to_string(input).map_err(|e| AppError::Io { path: input.to_path_buf(), source: e })?;
+    let mut value: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| AppError::Crypto(format!("{:?} is not JSON: {}", input, e)))?;
+    // Sync bodies wrap the payload in an "encrypted" field next to the plaintext id range
+    if let Some(inner) = value.get_mut("encrypted") {
+        value = inner.take();
+    }
+    let payload: crate::crypto::EncryptedPayload = serde_json::from_value(value)
+        .map_err(|e| AppError::Crypto(format!("{:?} is not an encrypted payload: {}", input, e)))?;
+    let key = crate::crypto::load_key(app_config)?;
+    let plaintext = crate::crypto::decrypt(&key, &payload)?;
+    println!("{}", String::from_utf8_lossy(&plaintext));
+    Ok(())
+}
+
 /// Loads the API key of the specified type from the secure credential store.
 pub fn load_api_key(app_config: &AppConfig, key_type: ApiKeyType) -> AppResult<String> {
     log::debug!("Attempting to load API key for type: {}", key_type);
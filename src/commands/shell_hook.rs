@@ -0,0 +1,81 @@
+// src/commands/shell_hook.rs
+// `shell-hook <SHELL>` prints the snippet that reports the shell's command and directory to a running
+// tracker; the snippet calls the hidden `shell-report` command, which sends them (see `detection::shell_hook`).
+// Reports run in the foreground (a few ms) so they arrive in order.
+
+use crate::config::AppConfig;
+use crate::detection::shell_hook::{self, ShellReport};
+use crate::errors::{AppError, AppResult};
+use crate::types::HookShell;
+
+const BASH_SNIPPET: &str = r#"# mouse_tracking shell integration (bash)
+__mouse_tracking_report() { {EXE} shell-report --pid="$$" --cwd="$PWD" "$@" >/dev/null 2>&1; }
+__mouse_tracking_at_prompt=0
+__mouse_tracking_preexec() {
+    [ -n "$COMP_LINE" ] && return
+    [ "$__mouse_tracking_at_prompt" = 1 ] || return
+    __mouse_tracking_at_prompt=0
+    __mouse_tracking_report --command="$BASH_COMMAND"
+}
+__mouse_tracking_precmd() { __mouse_tracking_at_prompt=1; __mouse_tracking_report; }
+trap '__mouse_tracking_preexec' DEBUG
+PROMPT_COMMAND="${PROMPT_COMMAND:+$PROMPT_COMMAND;}__mouse_tracking_precmd"
+__mouse_tracking_exit() { {EXE} shell-report --pid="$$" --exit >/dev/null 2>&1; }
+[ -z "$(trap -p EXIT)" ] && trap '__mouse_tracking_exit' EXIT
+"#;
+
+const ZSH_SNIPPET: &str = r#"# mouse_tracking shell integration (zsh)
+__mouse_tracking_report() { {EXE} shell-report --pid="$$" --cwd="$PWD" "$@" >/dev/null 2>&1; }
+__mouse_tracking_preexec() { __mouse_tracking_report --command="$1" }
+__mouse_tracking_precmd() { __mouse_tracking_report }
+__mouse_tracking_exit() { {EXE} shell-report --pid="$$" --exit >/dev/null 2>&1 }
+autoload -Uz add-zsh-hook
+add-zsh-hook preexec __mouse_tracking_preexec
+add-zsh-hook precmd __mouse_tracking_precmd
+add-zsh-hook zshexit __mouse_tracking_exit
+"#;
+
+const POWERSHELL_SNIPPET: &str = r#"# mouse_tracking shell integration (PowerShell)
+$global:MouseTrackingExe = {EXE}
+$global:MouseTrackingPrompt = $function:prompt
+function global:prompt {
+    & $global:MouseTrackingExe shell-report --pid=$PID --cwd="$((Get-Location).ProviderPath)" 2>$null | Out-Null
+    & $global:MouseTrackingPrompt
+}
+if (Get-Module PSReadLine) {
+    Set-PSReadLineOption -AddToHistoryHandler {
+        param([string]$line)
+        & $global:MouseTrackingExe shell-report --pid=$PID --cwd="$((Get-Location).ProviderPath)" --command="$line" 2>$null | Out-Null
+        return $true
+    }
+}
+Register-EngineEvent -SourceIdentifier PowerShell.Exiting -Action { & $global:MouseTrackingExe shell-report --pid=$PID --exit 2>$null | Out-Null } | Out-Null
+"#;
+
+/// Prints the snippet for `shell`, calling this executable by its full path.
+pub fn execute(app_config: &AppConfig, shell: HookShell) -> AppResult<()> {
+    if app_config.shell_hook.is_none() {
+        // stderr, so `eval "$(mouse_tracking shell-hook bash)"` still works
+        eprintln!("Note: add \"shell_hook\": {{}} to {:?} so the tracker listens for these reports.", app_config.config_file_path);
+    }
+    let exe = std::env::current_exe().map_err(|e| AppError::Unexpected(format!("Cannot locate this executable: {}", e)))?;
+    let exe = exe.to_string_lossy();
+    let snippet = match shell {
+        HookShell::Bash => BASH_SNIPPET.replace("{EXE}", &posix_quote(&exe)),
+        HookShell::Zsh => ZSH_SNIPPET.replace("{EXE}", &posix_quote(&exe)),
+        HookShell::Powershell => POWERSHELL_SNIPPET.replace("{EXE}", &format!("'{}'", exe.replace('\'', "''"))),
+    };
+    print!("{}", snippet);
+    Ok(())
+}
+
+/// `shell-report`: sends one report; silently does nothing when `shell_hook` is not configured.
+pub fn report(app_config: &AppConfig, pid: u32, cwd: String, command: Option<String>, exit: bool) -> AppResult<()> {
+    let Some(config) = app_config.shell_hook.as_ref() else { return Ok(()) };
+    let report = ShellReport { pid, cwd, command, exit }.redacted(config);
+    shell_hook::send_report(config, &report)
+}
+
+fn posix_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
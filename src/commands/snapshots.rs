@@ -0,0 +1,37 @@
+// src/commands/snapshots.rs
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::table::{Column, Table};
+use crate::types::SnapshotsCommand;
+use crate::utils::{format_local_timestamp, parse_user_time};
+
+/// Execute context-snapshot commands
+pub fn execute(app_config: &AppConfig, command: SnapshotsCommand) -> AppResult<()> {
+    match command {
+        SnapshotsCommand::Show { at } => show_snapshot(app_config, &at),
+    }
+}
+
+fn show_snapshot(app_config: &AppConfig, at: &str) -> AppResult<()> {
+    let at_time = parse_user_time(at).map_err(AppError::Config)?;
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+
+    let Some((snapshot_time, windows)) = storage.query_window_snapshot_at(at_time.timestamp())? else {
+        println!("No context snapshot found at or before {}.", format_local_timestamp(at_time.timestamp()));
+        if app_config.snapshot_interval.is_none() {
+            println!("Context snapshots are disabled; set \"snapshot_interval_mins\" in {:?} to enable them.", app_config.config_file_path);
+        }
+        return Ok(());
+    };
+
+    println!("Windows open at {} (snapshot taken {}):", format_local_timestamp(at_time.timestamp()), format_local_timestamp(snapshot_time));
+    let mut table = Table::new(vec![Column::new("Application").min_width(20), Column::new("Window Title")]);
+    for window in windows {
+        table.add_row(vec![window.app_name, window.window_title]);
+    }
+    table.print();
+    Ok(())
+}
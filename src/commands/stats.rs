@@ -1,42 +1,157 @@
 // src/commands/stats.rs
 
+use crate::app_metadata::AppAliases;
+use crate::habits;
+use crate::errors::AppError;
 use crate::persistence;
-use crate::types::{AggregationLevel, AggregatedResult, DetailedUsageRecord, TimePeriod, AppResult}; // Make sure AppResult is imported
-use crate::errors::AppError; // Import AppError if used in map_err
-use crate::utils::format_duration_secs;
-use std::path::Path;
-use log::{error, info}; // Keep needed log macros
+use crate::pseudonym::{Pseudonymizer, RevealMap};
+use crate::config::AppConfig;
+use crate::table::{Align, Cell, Column, Table};
+use crate::theme::{self, duration_bar, Palette};
+use crate::timezone::ReportTimeZone;
+use crate::types::{AggregationLevel, AggregatedResult, DetailedUsageRecord, DisplayOptions, FocusMetrics, IntervalStream, StatsFilter, TimeOfDayProfile, TimePeriod, AppResult}; // Make sure AppResult is imported
+use crate::utils::format_duration;
 
 // --- Display helper functions (print_aggregated_by_app, print_detailed_view) ---
 // (Keep the definitions for these functions here as provided before)
 
-fn print_aggregated_by_app(results: &mut Vec<(String, i64)>) {
+/// `categorized`: names are apps, so theme category colors apply (not for desktops/devices).
+fn print_aggregated_by_app(results: &mut [(String, i64)], label: &str, display: &DisplayOptions, palette: &Palette, categorized: bool) {
     if results.is_empty() { println!("  No activity recorded for this period."); return; }
-    results.sort_by(|a, b| b.1.cmp(&a.1));
-    let max_len = results.iter().map(|(name, _)| name.len()).max().unwrap_or(20).max(20);
-    println!("  {:<width$} : {}", "Application", "Duration", width = max_len);
-    println!("  {:-<width$} :----------", "", width = max_len);
-    for (app, secs) in results { println!("  {:<width$} : {}", app, format_duration_secs(*secs), width = max_len); }
+    results.sort_by_key(|r| std::cmp::Reverse(r.1));
+    let mut columns = vec![Column::new(label).min_width(20), Column::new("Duration").align(Align::Right)];
+    if palette.enabled() { columns.push(Column::new("")); }
+    let mut table = Table::new(columns).separator(" : ").header_style(palette.header());
+    let max_secs = results.first().map_or(0, |r| r.1);
+    for (index, (app, secs)) in results.iter().enumerate() {
+        let style = if categorized || index == 0 { palette.app(app, index == 0) } else { Default::default() };
+        let mut row = vec![Cell::styled(app.as_str(), style), Cell::from(format_duration(*secs, display.duration_format))];
+        if palette.enabled() { row.push(Cell::styled(duration_bar(*secs, max_secs), palette.bar())); }
+        table.add_row(row);
+    }
+    table.print();
 }
 
-fn print_detailed_view(records: &mut Vec<DetailedUsageRecord>) {
+fn print_detailed_view(records: &mut [DetailedUsageRecord], display: &DisplayOptions, palette: &Palette) {
      if records.is_empty() { println!("  No activity recorded for this period."); return; }
-    records.sort_by(|a, b| b.total_duration_secs.cmp(&a.total_duration_secs));
-    let max_app_len = records.iter().map(|r| r.app_name.len()).max().unwrap_or(20).max(15);
-    let max_title_len = records.iter().map(|r| r.detailed_title.len()).max().unwrap_or(40).max(20);
-    println!( "  {:<app_width$} | {:<title_width$} | {}", "Application", "Window Title", "Duration", app_width = max_app_len, title_width = max_title_len );
-    println!( "  {:-<app_width$}-+-{:-<title_width$}-+----------", "", "", app_width = max_app_len, title_width = max_title_len );
-    for record in records { println!( "  {:<app_width$} | {:<title_width$} | {}", record.app_name, record.detailed_title, format_duration_secs(record.total_duration_secs), app_width = max_app_len, title_width = max_title_len ); }
+    records.sort_by_key(|r| std::cmp::Reverse(r.total_duration_secs));
+    let mut columns = vec![
+        Column::new("Application").min_width(15),
+        Column::new("Window Title").min_width(20).max_width(display.max_title_width),
+        Column::new("Duration").align(Align::Right),
+    ];
+    if palette.enabled() { columns.push(Column::new("")); }
+    let mut table = Table::new(columns).header_style(palette.header());
+    let max_secs = records.first().map_or(0, |r| r.total_duration_secs);
+    for (index, record) in records.iter().enumerate() {
+        let mut row = vec![
+            Cell::styled(record.app_name.as_str(), palette.app(&record.app_name, index == 0)),
+            Cell::from(record.detailed_title.as_str()),
+            Cell::from(format_duration(record.total_duration_secs, display.duration_format)),
+        ];
+        if palette.enabled() { row.push(Cell::styled(duration_bar(record.total_duration_secs, max_secs), palette.bar())); }
+        table.add_row(row);
+    }
+    table.print();
+}
+
+/// Switch rate, sessions and switches per local hour (`--level focus`).
+fn print_focus_view(metrics: &FocusMetrics, aliases: &AppAliases, timezone: &ReportTimeZone, display: &DisplayOptions, palette: &Palette) {
+    let format = display.duration_format;
+    println!("  Tracked:         {}", format_duration(metrics.tracked_secs, format));
+    println!("  App switches:    {} ({:.1} per hour)", metrics.switches, metrics.switches_per_hour());
+    println!("  Focus sessions:  {}, average {}", metrics.sessions, format_duration(metrics.average_session_secs(), format));
+    if let Some((app, start, end)) = &metrics.longest_session {
+        println!(
+            "  Longest session: {} for {} ({}-{})",
+            palette.paint(palette.app(aliases.display(app), true), aliases.display(app)),
+            format_duration(end - start, format),
+            timezone.time_string(*start),
+            timezone.time_string(*end)
+        );
+    }
+    let mut columns = vec![Column::new("Hour"), Column::new("Switches").align(Align::Right)];
+    if palette.enabled() { columns.push(Column::new("")); }
+    let mut table = Table::new(columns).separator(" : ").header_style(palette.header());
+    let max_switches = metrics.switches_by_hour.iter().map(|(_, switches)| *switches).max().unwrap_or(0);
+    for (hour, switches) in &metrics.switches_by_hour {
+        let mut row = vec![Cell::from(timezone.time_string(*hour)), Cell::from(switches.to_string())];
+        if palette.enabled() { row.push(Cell::styled(duration_bar(*switches, max_switches), palette.bar())); }
+        table.add_row(row);
+    }
+    table.print();
+}
+
+/// 24-slot histogram of `--level profile`: total and per-day average of each local hour, or with
+/// `split_weekend` the per-day averages of weekdays and weekends side by side. Bars are always drawn.
+fn print_profile_view(profile: &TimeOfDayProfile, display: &DisplayOptions, palette: &Palette) {
+    let format = display.duration_format;
+    let per_day = |secs: i64, days: i64| if days == 0 { 0 } else { secs / days };
+    if display.split_weekend {
+        let weekday: Vec<i64> = profile.weekday_secs.iter().map(|secs| per_day(*secs, profile.weekdays)).collect();
+        let weekend: Vec<i64> = profile.weekend_secs.iter().map(|secs| per_day(*secs, profile.weekend_days)).collect();
+        let max_secs = weekday.iter().chain(&weekend).copied().max().unwrap_or(0);
+        println!("  Average per day over {} weekday(s) and {} weekend day(s)", profile.weekdays, profile.weekend_days);
+        let columns = vec![
+            Column::new("Hour"),
+            Column::new("Weekdays").align(Align::Right),
+            Column::new("").min_width(theme::BAR_WIDTH),
+            Column::new("Weekend").align(Align::Right),
+            Column::new(""),
+        ];
+        let mut table = Table::new(columns).separator(" : ").header_style(palette.header());
+        for hour in 0..24 {
+            table.add_row([
+                Cell::from(format!("{:02}:00", hour)),
+                Cell::from(format_duration(weekday[hour], format)),
+                Cell::styled(duration_bar(weekday[hour], max_secs), palette.bar()),
+                Cell::from(format_duration(weekend[hour], format)),
+                Cell::styled(duration_bar(weekend[hour], max_secs), palette.bar()),
+            ]);
+        }
+        table.print();
+        return;
+    }
+    let days = profile.weekdays + profile.weekend_days;
+    let totals: Vec<i64> = (0..24).map(|hour| profile.weekday_secs[hour] + profile.weekend_secs[hour]).collect();
+    let max_secs = totals.iter().copied().max().unwrap_or(0);
+    let columns = vec![Column::new("Hour"), Column::new("Total").align(Align::Right), Column::new("Per day").align(Align::Right), Column::new("")];
+    let mut table = Table::new(columns).separator(" : ").header_style(palette.header());
+    for (hour, secs) in totals.iter().enumerate() {
+        table.add_row([
+            Cell::from(format!("{:02}:00", hour)),
+            Cell::from(format_duration(*secs, format)),
+            Cell::from(format_duration(per_day(*secs, days), format)),
+            Cell::styled(duration_bar(*secs, max_secs), palette.bar()),
+        ]);
+    }
+    table.print();
 }
 
+/// Prints the active stats filters so the numbers below are not mistaken for full totals.
+fn print_filter_summary(filter: &StatsFilter, display: &DisplayOptions) {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(top) = filter.top { parts.push(format!("top {}", top)); }
+    if let Some(glob) = &filter.app_glob { parts.push(format!("app matches '{}'", glob)); }
+    if let Some(text) = &filter.title_contains { parts.push(format!("title contains '{}'", text)); }
+    if let Some(min) = filter.min_duration_secs { parts.push(format!("min duration {}", format_duration(min, display.duration_format))); }
+    if filter.exclude_passive { parts.push("excluding passive time".to_string()); }
+    if let Some(user) = &filter.user { parts.push(format!("user '{}'", user)); }
+    if filter.stream == IntervalStream::Audio { parts.push("audio stream".to_string()); }
+    println!("Filters: {}", parts.join(", "));
+}
 
 /// Helper function to display a section of stats based on the query result.
 fn display_stats_section(
     title: &str,
-    result: Result<AggregatedResult, rusqlite::Error>, // Receive SqlResult
+    result: AppResult<AggregatedResult>,
     level: AggregationLevel,
+    aliases: &AppAliases,
+    timezone: &ReportTimeZone,
+    display: &DisplayOptions,
+    palette: &Palette,
 ) {
-    println!("\n--- {} ({}) ---", title, level);
+    println!("\n{}", palette.paint(palette.header(), &format!("--- {} ({}) ---", title, level)));
 
     match result {
         Ok(mut agg_result) => { // Make mutable for sorting
@@ -45,8 +160,27 @@ fn display_stats_section(
                  return;
              }
             match &mut agg_result{ // Match on mutable ref
-                 AggregatedResult::ByApp(summary) => print_aggregated_by_app(summary),
-                 AggregatedResult::Detailed(records) => print_detailed_view(records),
+                 AggregatedResult::ByApp(summary) => {
+                     let mut summary = aliases.apply_to_totals(std::mem::take(summary));
+                     print_aggregated_by_app(&mut summary, "Application", display, palette, true)
+                 }
+                 AggregatedResult::ByDesktop(summary) => print_aggregated_by_app(summary, "Virtual Desktop", display, palette, false),
+                 AggregatedResult::ByDevice(summary) => print_aggregated_by_app(summary, "Device", display, palette, false),
+                 AggregatedResult::ByRepo(summary) => print_aggregated_by_app(summary, "Repository", display, palette, false),
+                 AggregatedResult::ByMeeting(summary) => print_aggregated_by_app(summary, "Meeting", display, palette, false),
+                 AggregatedResult::ByTicket(summary) => print_aggregated_by_app(summary, "Ticket", display, palette, false),
+                 AggregatedResult::ByUser(summary) => print_aggregated_by_app(summary, "User", display, palette, false),
+                 AggregatedResult::ByDocument(summary) => print_aggregated_by_app(summary, "Document", display, palette, false),
+                 AggregatedResult::ByRemote(summary) => print_aggregated_by_app(summary, "Remote Session", display, palette, false),
+                 AggregatedResult::ByPath(summary) => print_aggregated_by_app(summary, "Executable Path", display, palette, false),
+                 AggregatedResult::Detailed(records) => {
+                     for record in records.iter_mut() {
+                         record.app_name = aliases.display(&record.app_name).to_string();
+                     }
+                     print_detailed_view(records, display, palette)
+                 }
+                 AggregatedResult::Focus(metrics) => print_focus_view(metrics, aliases, timezone, display, palette),
+                 AggregatedResult::Profile(profile) => print_profile_view(profile, display, palette),
              }
         }
         Err(e) => {
@@ -59,25 +193,57 @@ fn display_stats_section(
 
 // --- The Command Execution Function ---
 // *** ENSURE 'pub' IS PRESENT HERE ***
-pub fn execute(data_path: &Path, level: AggregationLevel) -> AppResult<()> {
+pub fn execute(app_config: &AppConfig, level: AggregationLevel, period: Option<TimePeriod>, filter: &StatsFilter, display: &DisplayOptions) -> AppResult<()> {
     // Use log::info, not just info!
-    log::info!("Showing statistics with level: {:?}", level);
+    log::info!("Showing statistics with level: {:?}, filter: {:?}", level, filter);
+    if filter.stream == IntervalStream::Audio {
+        // Audio intervals have no titles, passive time or other dimensions
+        if level != AggregationLevel::ByApplication {
+            return Err(AppError::Config("--stream audio only works with --level app".to_string()));
+        }
+        if filter.title_contains.is_some() || filter.exclude_passive {
+            return Err(AppError::Config("--title-contains and --exclude-passive do not apply to --stream audio".to_string()));
+        }
+    }
     println!("Statistics Level: {}", level);
-    println!("Database path: {:?}", data_path);
+    println!("Database: {}", persistence::describe_location(app_config));
+    if !filter.is_empty() {
+        print_filter_summary(filter, display);
+        if level == AggregationLevel::Focus {
+            println!("(Filters do not apply to the focus level.)");
+        }
+    }
 
-    // Use the AppError type defined in errors.rs for mapping
-    let conn = persistence::open_connection_ensure_path(data_path)
-        .map_err(|e| AppError::Database(e))?; // Use #[from] implicitly via ? or map specifically
+    let mut storage = persistence::open_storage(app_config)?;
+    // Brings older databases up to date (e.g. tables added since they were created)
+    storage.initialize()?;
+    let aliases = AppAliases::load(storage.as_mut())?;
+    let palette = Palette::new(&app_config.theme, display.color);
+    let reveal = match (&app_config.pseudonymize, display.reveal) {
+        (Some(pseudonymize), true) => Pseudonymizer::load(app_config)?.reveal_map(&pseudonymize.reveal),
+        (None, true) => return Err(AppError::Config("--reveal needs \"pseudonymize\" in config.json".to_string())),
+        (_, false) => RevealMap::default(),
+    };
 
-    let periods_to_display = [
-        TimePeriod::Today,
-        TimePeriod::LastCompletedHour,
-        TimePeriod::CurrentHour,
-    ];
+    let periods_to_display = match period {
+        Some(period) => vec![period],
+        None => vec![TimePeriod::Today, TimePeriod::LastCompletedHour, TimePeriod::CurrentHour],
+    };
 
     for period in periods_to_display {
-        let result = persistence::query_stats(&conn, period, level);
-        display_stats_section(&period.to_string(), result, level);
+        let result = storage.query_stats(period, level, filter).map(|mut result| {
+            reveal.apply(&mut result);
+            result
+        });
+        display_stats_section(&period.to_string(), result, level, &aliases, &app_config.timezone, display, &palette);
+    }
+
+    let habits = habits::load_status(storage.as_mut(), &app_config.timezone, chrono::Utc::now().timestamp())?;
+    if !habits.is_empty() {
+        println!("\n--- Habits ---");
+        for status in &habits {
+            println!("{}", habits::describe(status, display.duration_format));
+        }
     }
 
     println!("\n---------------------------------------------");
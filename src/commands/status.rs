@@ -0,0 +1,90 @@
+// src/commands/status.rs
+// `status`: whether a tracker runs on this profile's database and, through its gRPC API, its state
+// and how detection failures were handled (`detection_retries`, `detection_grace_secs`). Outside
+// `tracking_hours` it says so, which needs no gRPC: the schedule is read from this config. Restarts by
+// the watchdog (`track --supervise`) are listed last, also when no tracker is running. `--verbose` adds
+// the health of the tracking loop (`loop_health`): latencies, missed polls and errors of the last polls.
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use crate::grpc;
+use crate::instance;
+use crate::loop_health::{LatencySummary, LoopHealthReport};
+use crate::utils::format_local_timestamp;
+use crate::watchdog;
+use chrono::Utc;
+
+pub fn execute(app_config: &AppConfig, verbose: bool) -> AppResult<()> {
+    tracker_status(app_config, verbose)?;
+    if let Some(state) = watchdog::load_state(app_config) {
+        println!("Watchdog:");
+        println!("  {:<24} {:>8}", "Tracker restarts", state.restarts);
+        if let Some(last_restart) = state.last_restart {
+            println!("  {:<24} {} ({})", "Last restart", format_local_timestamp(last_restart), state.last_reason.as_deref().unwrap_or("unknown"));
+        }
+        if let Some(report) = &state.last_report {
+            println!("  {:<24} {}", "Crash report", report.display());
+        }
+    }
+    Ok(())
+}
+
+fn tracker_status(app_config: &AppConfig, verbose: bool) -> AppResult<()> {
+    let Some(tracker) = instance::running_tracker(app_config) else {
+        println!("No tracker is running on {}.", crate::persistence::describe_location(app_config));
+        return Ok(());
+    };
+    println!("Tracker running ({}).", tracker.describe());
+    let tracking_hours = &app_config.tracking_hours;
+    let off_hours = (!tracking_hours.is_recording(Utc::now().timestamp(), &app_config.timezone))
+        .then(|| format!("not recording: outside tracking_hours ({})", tracking_hours.describe()));
+    let status = match tracker.grpc_listen {
+        Some(addr) => grpc::tracker_status(addr)?,
+        None => None,
+    };
+    let Some(status) = status else {
+        if let Some(off_hours) = &off_hours {
+            println!("  {:<24} {}", "State", off_hours);
+        }
+        println!("Set \"grpc_listen\" (in a build with --features grpc) to see its state, detection counters and loop health.");
+        return Ok(());
+    };
+    let counters = status.counters;
+    let state = match off_hours {
+        _ if status.paused => "paused".to_string(),
+        Some(off_hours) => off_hours,
+        None => "recording".to_string(),
+    };
+    println!("  {:<24} {}", "State", state);
+    println!("  {:<24} {:>8}", "Detection retries", counters.retries);
+    println!("  {:<24} {:>8}", "Bridged failed polls", counters.bridged_polls);
+    println!("  {:<24} {:>8}", "Unrecorded failed polls", counters.failed_polls);
+    if verbose {
+        match status.health {
+            Some(health) => print_loop_health(&health),
+            None => println!("This tracker does not report its loop health; restart it with this version."),
+        }
+    }
+    Ok(())
+}
+
+fn print_loop_health(health: &LoopHealthReport) {
+    println!("Loop health (last {} polls, last {} writes; totals since start):", health.polls, health.writes);
+    println!("  {:<24} {:>8}   total {}", "Polls", health.polls, health.totals.polls);
+    println!("  {:<24} {:>8}   total {}", "Missed polls", health.missed_polls, health.totals.missed_polls);
+    println!("  {:<24} {:>8}   total {}", "Detection errors", health.detection_errors, health.totals.detection_errors);
+    println!("  {:<24} {}", "Detection latency", format_latency(&health.detection));
+    println!("  {:<24} {:>8}   total {}", "Database writes", health.writes, health.totals.writes);
+    println!("  {:<24} {}", "Write latency", format_latency(&health.write));
+    if health.missed_polls > 0 {
+        println!("Missed polls are time the loop woke up late (system sleep, a stopped or starved process); nothing is detected then.");
+    }
+    if health.detection_errors * 10 > health.polls {
+        println!("More than one poll in ten failed; `mouse_tracking doctor` checks the detector and platform APIs.");
+    }
+}
+
+fn format_latency(summary: &LatencySummary) -> String {
+    let ms = |duration: std::time::Duration| duration.as_secs_f64() * 1000.0;
+    format!("avg {:.2} ms, p95 {:.2} ms, max {:.2} ms", ms(summary.avg), ms(summary.p95), ms(summary.max))
+}
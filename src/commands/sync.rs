@@ -0,0 +1,39 @@
+// src/commands/sync.rs
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use crate::persistence;
+use crate::sync;
+use crate::types::SyncCommand;
+use crate::utils::format_local_timestamp;
+
+/// Execute sync commands
+pub fn execute(app_config: &AppConfig, command: SyncCommand) -> AppResult<()> {
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    match command {
+        SyncCommand::Push => {
+            let pushed = sync::push(app_config, storage.as_mut())?;
+            println!("Uploaded {} interval(s).", pushed);
+        }
+        SyncCommand::Status => {
+            let state = storage.query_sync_state()?;
+            match &app_config.sync_url {
+                Some(url) => println!("Endpoint:        {}", url),
+                None => println!("Endpoint:        <not configured> (set \"sync_url\" in {:?})", app_config.config_file_path),
+            }
+            match app_config.sync_interval {
+                Some(interval) => println!("Auto-sync:       every {} min while tracking", interval.as_secs() / 60),
+                None => println!("Auto-sync:       off"),
+            }
+            match state.last_sync_time {
+                Some(time) => println!("Last sync:       {} ({} interval(s))", format_local_timestamp(time), state.last_sync_count),
+                None => println!("Last sync:       never"),
+            }
+            println!("Watermark:       interval id {}", state.last_interval_id);
+            let pending = storage.count_unsynced_intervals(state.last_interval_id)?;
+            println!("Waiting to sync: {} completed interval(s)", pending);
+        }
+    }
+    Ok(())
+}
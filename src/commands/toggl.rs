@@ -0,0 +1,149 @@
+// src/commands/toggl.rs
+
+use crate::clock::SystemClock;
+use crate::commands::set_key::load_api_key;
+use crate::config::{AppConfig, TogglConfig};
+use crate::errors::{AppError, AppResult};
+use crate::persistence;
+use crate::types::{AggregatedResult, AggregationLevel, ApiKeyType, StatsFilter, TimePeriod, TogglCommand};
+use crate::utils::{format_duration, glob_match};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+const TOGGL_API_BASE: &str = "https://api.track.toggl.com/api/v9";
+
+/// Body of `POST /workspaces/{id}/time_entries`
+#[derive(Debug, Serialize)]
+struct TimeEntry<'a> {
+    created_with: &'a str,
+    description: String,
+    workspace_id: u64,
+    project_id: Option<u64>,
+    start: String,
+    duration: i64,
+}
+
+/// Tracked time of one mapping category within the period
+#[derive(Debug)]
+struct CategoryTotal {
+    category: String,
+    project_id: Option<u64>,
+    secs: i64,
+}
+
+/// Execute Toggl commands
+pub fn execute(app_config: &AppConfig, command: TogglCommand) -> AppResult<()> {
+    match command {
+        TogglCommand::Push { period, dry_run } => push(app_config, period, dry_run),
+    }
+}
+
+/// Checks the stored token with `GET /me` (`config test-key toggl`).
+pub fn test_token(app_config: &AppConfig) -> AppResult<()> {
+    let token = load_api_key(app_config, ApiKeyType::Toggl)?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| AppError::Integration(e.to_string()))?;
+    let response = client
+        .get(format!("{}/me", TOGGL_API_BASE))
+        .basic_auth(&token, Some("api_token"))
+        .send()
+        .map_err(|e| AppError::Integration(format!("Toggl request failed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Integration(format!("Toggl rejected the token: HTTP {}", response.status())));
+    }
+    Ok(())
+}
+
+/// Sums per-app time into the first matching mapping category (or the default project).
+fn categorize(toggl: &TogglConfig, by_app: &[(String, i64)]) -> Vec<CategoryTotal> {
+    let mut totals: Vec<CategoryTotal> = Vec::new();
+    for (app, secs) in by_app {
+        let (category, project_id) = match toggl.mappings.iter().find(|m| m.apps.iter().any(|glob| glob_match(glob, app))) {
+            Some(mapping) => (mapping.category.clone(), mapping.project_id),
+            None if toggl.default_project_id.is_some() => (app.clone(), toggl.default_project_id),
+            None => {
+                log::debug!("No Toggl mapping for app '{}'; skipped.", app);
+                continue;
+            }
+        };
+        match totals.iter_mut().find(|t| t.category == category) {
+            Some(total) => total.secs += secs,
+            None => totals.push(CategoryTotal { category, project_id, secs: *secs }),
+        }
+    }
+    totals.retain(|t| t.secs > 0);
+    totals
+}
+
+/// Creates one time entry per category. Aggregated time has no real start times, so entries
+/// are laid out back to back from the start of the period.
+fn push(app_config: &AppConfig, period: TimePeriod, dry_run: bool) -> AppResult<()> {
+    let toggl = app_config
+        .toggl
+        .as_ref()
+        .ok_or_else(|| AppError::Config("No \"toggl\" section in config.json (workspace_id and mappings).".to_string()))?;
+
+    let mut storage = persistence::open_storage(app_config)?;
+    storage.initialize()?;
+    let AggregatedResult::ByApp(by_app) = storage.query_stats(period, AggregationLevel::ByApplication, &StatsFilter::default())? else {
+        return Err(AppError::Unexpected("Unexpected stats result shape".to_string()));
+    };
+    let totals = categorize(toggl, &by_app);
+    if totals.is_empty() {
+        println!("Nothing to push for {} (no mapped activity).", period);
+        return Ok(());
+    }
+
+    let (period_start, _) = persistence::calculate_timestamps(period, &app_config.timezone, &SystemClock);
+    let mut entry_start = period_start;
+    let entries: Vec<TimeEntry> = totals
+        .iter()
+        .map(|total| {
+            let start = DateTime::<Utc>::from_timestamp(entry_start, 0).unwrap_or_default().to_rfc3339();
+            entry_start += total.secs;
+            TimeEntry {
+                created_with: &app_config.app_name,
+                description: total.category.clone(),
+                workspace_id: toggl.workspace_id,
+                project_id: total.project_id,
+                start,
+                duration: total.secs,
+            }
+        })
+        .collect();
+
+    println!("Toggl time entries for {}:", period);
+    for entry in &entries {
+        println!("  {:<30} {} (project {})", entry.description, format_duration(entry.duration, app_config.duration_format),
+            entry.project_id.map_or("-".to_string(), |id| id.to_string()));
+    }
+    if dry_run {
+        println!("Dry run: nothing was sent.");
+        return Ok(());
+    }
+
+    let token = load_api_key(app_config, ApiKeyType::Toggl)?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| AppError::Integration(e.to_string()))?;
+    let url = format!("{}/workspaces/{}/time_entries", TOGGL_API_BASE, toggl.workspace_id);
+    for entry in &entries {
+        let response = client
+            .post(&url)
+            .basic_auth(&token, Some("api_token"))
+            .json(entry)
+            .send()
+            .map_err(|e| AppError::Integration(format!("Toggl request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(AppError::Integration(format!("Toggl rejected entry '{}': HTTP {} {}", entry.description, status, body)));
+        }
+    }
+    println!("✅ Created {} Toggl time entr{}.", entries.len(), if entries.len() == 1 { "y" } else { "ies" });
+    Ok(())
+}
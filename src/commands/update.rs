@@ -1,39 +1,127 @@
 // src/commands/update.rs
+// `update` replaces the running executable with a release archive, either the latest one on GitHub
+// Releases (downloaded through `update_proxy`/`update_ca_file`) or one given with `--from-file` on
+// machines without network access. Both end in `install_archive`: signature check, extraction, replacement.
 
 use crate::config::AppConfig;
-use crate::errors::AppResult;
+use crate::errors::{AppError, AppResult};
 use self_update;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::Duration;
 
-// Change return type to AppResult<()>
-pub fn execute(app_config:&AppConfig) -> AppResult<()> {
-    println!("Checking for updates...");
+/// zipsign (ed25519) public key release archives are signed with. Archives without a valid signature
+/// from it are refused before the executable is replaced.
+const RELEASE_PUBLIC_KEY: &[u8; 32] = include_bytes!("../../release-signing.pub");
+// Covers downloading a release archive on a slow link
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    assets: Vec<GithubAsset>,
+}
 
-    // Getting version with env! is fine, no error expected here
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+pub fn execute(app_config: &AppConfig, from_file: Option<&Path>) -> AppResult<()> {
     let current_version = env!("CARGO_PKG_VERSION");
     println!("Current version: {}", current_version);
+    if let Some(archive) = from_file {
+        println!("Installing {}...", archive.display());
+        install_archive(archive)?;
+        println!("Update installed from {}.", archive.display());
+        println!("Please restart the application if it was running.");
+        return Ok(());
+    }
 
-    // Use '?' - self_update::errors::Error will be automatically converted
-    // to AppError::Update by the #[from] attribute in errors.rs
-    let status = self_update::backends::github::Update::configure()
-        .repo_owner(&app_config.repo_owner)
-        .repo_name(&app_config.repo_name)
-        .target(self_update::get_target())
-        .bin_name(env!("CARGO_PKG_NAME")) 
-        .show_download_progress(true)
-        .current_version(current_version)
-        .build()? 
-        .update()?; 
-
-    match status {
-        self_update::Status::UpToDate(v) => {
-            println!("Already running the latest version: {}", v);
-        }
-        self_update::Status::Updated(v) => {
-            println!("Successfully updated to version: {}", v);
-            println!("Please restart the application if it was running.");
+    println!("Checking for updates...");
+    let client = http_client(app_config)?;
+    let url = format!("https://api.github.com/repos/{}/{}/releases/latest", app_config.repo_owner, app_config.repo_name);
+    let release: GithubRelease = client
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .map_err(|e| AppError::Integration(format!("Cannot read the latest release from {}: {}", url, e)))?;
+    let latest_version = release.tag_name.trim_start_matches('v');
+    if !self_update::version::bump_is_greater(current_version, latest_version)? {
+        println!("Already running the latest version: {}", latest_version);
+        return Ok(());
+    }
+
+    let target = self_update::get_target();
+    let asset = release
+        .assets
+        .iter()
+        .find(|asset| asset.name.contains(target) && asset.name.ends_with(".zip"))
+        .ok_or_else(|| AppError::Integration(format!("Release {} has no archive for {}", release.tag_name, target)))?;
+    println!("Downloading {}...", asset.name);
+    // The signature covers the file name, so the archive keeps the asset's name
+    let download_dir = self_update::TempDir::new().map_err(|e| AppError::Io { path: std::env::temp_dir(), source: e })?;
+    let archive = download_dir.path().join(&asset.name);
+    let mut file = File::create(&archive).map_err(|e| AppError::Io { path: archive.clone(), source: e })?;
+    client
+        .get(&asset.browser_download_url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|mut response| response.copy_to(&mut file))
+        .map_err(|e| AppError::Integration(format!("Cannot download {}: {}", asset.browser_download_url, e)))?;
+    drop(file);
+
+    install_archive(&archive)?;
+    println!("Successfully updated to version: {}", latest_version);
+    println!("Please restart the application if it was running.");
+    Ok(())
+}
+
+/// HTTP client for GitHub: `update_proxy` and `update_ca_file` when set, else the environment's proxy.
+fn http_client(app_config: &AppConfig) -> AppResult<reqwest::blocking::Client> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT);
+    if let Some(proxy) = &app_config.update_proxy {
+        let proxy = reqwest::Proxy::all(proxy).map_err(|e| AppError::Config(format!("Invalid update_proxy {:?}: {}", proxy, e)))?;
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &app_config.update_ca_file {
+        let pem = fs::read(path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| AppError::Config(format!("update_ca_file {:?} is not a PEM certificate: {}", path, e)))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
         }
     }
+    builder.build().map_err(|e| AppError::Integration(e.to_string()))
+}
+
+/// Checks the archive's signature, then replaces the running executable with the one inside.
+fn install_archive(archive: &Path) -> AppResult<()> {
+    verify_signature(archive)?;
+    let bin_name = format!("{}{}", env!("CARGO_PKG_NAME"), std::env::consts::EXE_SUFFIX);
+    let extract_dir = self_update::TempDir::new().map_err(|e| AppError::Io { path: std::env::temp_dir(), source: e })?;
+    self_update::Extract::from_source(archive).extract_file(extract_dir.path(), &bin_name)?;
+    let new_exe = extract_dir.path().join(&bin_name);
+    let current_exe = std::env::current_exe().unwrap_or_default();
+    self_update::self_replace::self_replace(&new_exe).map_err(|e| AppError::Io { path: current_exe, source: e })
+}
 
-    // Return Ok(()) on successful completion (either up-to-date or updated)
+/// Refuses archives that are unsigned, modified, or signed with another key than `RELEASE_PUBLIC_KEY`.
+fn verify_signature(archive: &Path) -> AppResult<()> {
+    let untrusted = |message: String| AppError::UntrustedRelease(format!("{}: {}", archive.display(), message));
+    // Releases are signed with their file name as context, so a renamed archive does not verify
+    let context = archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| untrusted("the file name is not valid UTF-8".to_string()))?;
+    let keys = zipsign_api::verify::collect_keys([Ok(*RELEASE_PUBLIC_KEY)]).map_err(|e| untrusted(e.to_string()))?;
+    let mut file = File::open(archive).map_err(|e| AppError::Io { path: archive.to_path_buf(), source: e })?;
+    zipsign_api::verify::verify_zip(&mut file, &keys, Some(context.as_bytes())).map_err(|e| untrusted(e.to_string()))?;
     Ok(())
-}
\ No newline at end of file
+}
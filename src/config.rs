@@ -1,90 +1,830 @@
 // src/config.rs
 
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use serde::Deserialize;
 use crate::errors::{AppError, AppResult}; // Use AppResult for loading errors
+use crate::detection::MergeMode;
+use crate::detection::document::{DocumentDetectionConfig, DocumentExtractor};
+use crate::detection::title_heuristics::{TitleHeuristicConfig, TitleHeuristics};
+use crate::tracker::ShortSwitchMode;
+use crate::timezone::{ReportTimeZone, WeekStart};
+use crate::types::{DurationFormat, LlmProvider};
+use crate::persistence::WriteMode;
+use crate::profile_rules::{ProfileRuleConfig, ProfileRules};
+use crate::tracking_hours::{TrackingHours, TrackingWindowConfig};
+use crate::title_rules::{TitleRuleConfig, TitleRules};
+use crate::saved_reports::{self, SavedReport, SavedReportConfig};
+use crate::schedule::{self, ScheduleEntryConfig, ScheduledJob};
 
 pub const KEYRING_SERVICE_NAME_PREFIX: &str = "llm-cli-"; // Or your preferred prefix
+pub const CONFIG_FILE_NAME: &str = "config.json";
+pub const DATABASE_FILE_NAME: &str = "app_usage.sqlite";
+/// Environment variable replacing the whole data directory (config.json, database, profiles).
+pub const DATA_DIR_ENV: &str = "MOUSE_TRACKING_DATA_DIR";
+/// Environment variable replacing the database file, like `database_path` in config.json.
+pub const DATABASE_PATH_ENV: &str = "MOUSE_TRACKING_DATABASE_PATH";
+/// Named profiles live in this subdirectory of the data directory, one directory each.
+pub const PROFILES_DIR_NAME: &str = "profiles";
+/// The profile stored directly in the data directory (what every install used before profiles).
+pub const DEFAULT_PROFILE: &str = "default";
+/// Where `LlmProvider::Ollama` finds its server unless `ollama_url` says otherwise.
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Optional user overrides read from `config.json` in the data directory.
+/// Every key is optional; anything missing keeps its built-in default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FileConfig {
+    database_url: Option<String>,
+    database_path: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    database_safe_write: Option<bool>,
+    shared_database_path: Option<PathBuf>,
+    sync_url: Option<String>,
+    sync_interval_mins: Option<u64>,
+    update_proxy: Option<String>,
+    update_ca_file: Option<PathBuf>,
+    llm_provider: Option<LlmProvider>,
+    ollama_url: Option<String>,
+    end_to_end_encryption: Option<bool>,
+    database_encryption: Option<bool>,
+    device_id: Option<String>,
+    user_name: Option<String>,
+    toggl: Option<TogglConfig>,
+    theme: ThemeConfig,
+    webhook: Option<WebhookConfig>,
+    mqtt: Option<MqttConfig>,
+    calendar: Option<CalendarConfig>,
+    shell_hook: Option<ShellHookConfig>,
+    repo_detection: Option<RepoDetectionConfig>,
+    ticket_detection: Option<TicketDetectionConfig>,
+    document_detection: Option<DocumentDetectionConfig>,
+    break_reminder: Option<BreakReminderConfig>,
+    pseudonymize: Option<PseudonymizeConfig>,
+    record_executable_path: Option<bool>,
+    record_command_line: Option<bool>,
+    audio_tracking: Option<bool>,
+    web_app_detection: Option<bool>,
+    grpc_listen: Option<String>,
+    metrics_listen: Option<String>,
+    check_interval_secs: Option<u64>,
+    min_interval_secs: Option<u64>,
+    short_switch_mode: Option<ShortSwitchMode>,
+    adaptive_polling: Option<bool>,
+    idle_poll_interval_secs: Option<u64>,
+    idle_threshold_secs: Option<u64>,
+    snapshot_interval_mins: Option<u64>,
+    detection_retries: Option<u32>,
+    detection_grace_secs: Option<u64>,
+    merge_gap_secs: Option<u64>,
+    vacuum_threshold_percent: Option<u64>,
+    archive_raw_intervals: Option<bool>,
+    hourly_summary_days: Option<u64>,
+    duration_format: Option<DurationFormat>,
+    timezone: Option<String>,
+    week_start: Option<WeekStart>,
+    day_start_hour: Option<u8>,
+    passive_apps: Option<Vec<String>>,
+    detector_plugins: Vec<DetectorPluginConfig>,
+    detector_order: Option<Vec<String>>,
+    profile_rules: Vec<ProfileRuleConfig>,
+    tracking_hours: Vec<TrackingWindowConfig>,
+    title_rules: Vec<TitleRuleConfig>,
+    title_heuristics: Vec<TitleHeuristicConfig>,
+    daily_recap_time: Option<String>,
+    notify: NotifyConfig,
+    screentime: ScreenTimeConfig,
+    reports: BTreeMap<String, SavedReportConfig>,
+    schedule: BTreeMap<String, ScheduleEntryConfig>,
+}
+
+/// `toggl` section of `config.json`: which workspace to write to and how apps map to projects.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TogglConfig {
+    pub workspace_id: u64,
+    /// Checked in order; an app goes to the first category with a matching glob
+    #[serde(default)]
+    pub mappings: Vec<TogglMapping>,
+    /// Project for time of apps no mapping matches (None = not pushed)
+    pub default_project_id: Option<u64>,
+}
+
+/// A category of apps pushed as one Toggl time entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TogglMapping {
+    /// Used as the time entry description
+    pub category: String,
+    /// Case-insensitive globs matched against the app name (e.g. "code*")
+    pub apps: Vec<String>,
+    pub project_id: Option<u64>,
+}
+
+/// `theme` section of `config.json`: styles such as "bold green" or "bright-red" for stats output.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Section headers (default "bold cyan")
+    pub header: Option<String>,
+    /// The entry with the most time (default "bold green")
+    pub top: Option<String>,
+    /// Duration bars (default "blue")
+    pub bar: Option<String>,
+    /// Colors for groups of apps; an app gets the first category with a matching glob
+    pub categories: Vec<ThemeCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeCategory {
+    /// Case-insensitive globs matched against the app name as shown (friendly name if set)
+    pub apps: Vec<String>,
+    pub color: String,
+}
+
+/// `webhook` section of `config.json`: where app-switch and threshold events are POSTed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// An app switch is only sent once the new app stayed focused this long (default 5)
+    pub debounce_secs: Option<u64>,
+    #[serde(default)]
+    pub thresholds: Vec<WebhookThreshold>,
+}
+
+/// Sends a `threshold_reached` event once per day when matching apps reach `daily_minutes`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookThreshold {
+    /// Case-insensitive glob matched against the app name (e.g. "steam*")
+    pub app: String,
+    pub daily_minutes: u64,
+}
+
+/// `notify` section of `config.json`: Slack/Discord incoming webhooks receiving the daily summary (`report --post`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    pub slack_webhook_url: Option<String>,
+    pub discord_webhook_url: Option<String>,
+    /// Local "HH:MM" at which a running tracker posts today's summary to every configured webhook
+    pub post_time: Option<String>,
+    /// Totals shown in the summary; an app counts in the first category with a matching glob
+    pub categories: Vec<NotifyCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifyCategory {
+    pub name: String,
+    /// Case-insensitive globs matched against the app name as shown (friendly name if set)
+    pub apps: Vec<String>,
+}
+
+/// `screentime` section of `config.json`: categories and daily limits of the `screentime` view.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScreenTimeConfig {
+    /// Limit on the day's total
+    pub daily_limit_minutes: Option<u64>,
+    /// An app counts in the first category with a matching glob (empty = `notify.categories`, without limits)
+    pub categories: Vec<ScreenTimeCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScreenTimeCategory {
+    pub name: String,
+    /// Case-insensitive globs matched against the app name as shown (friendly name if set)
+    pub apps: Vec<String>,
+    pub daily_limit_minutes: Option<u64>,
+}
+
+/// `calendar` section of `config.json`: events whose overlapping time is counted as meetings
+/// (`stats --level meetings`). Exactly one of `ics_file` and `caldav_url` is set.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalendarConfig {
+    /// Local iCalendar file, e.g. one kept up to date by a calendar client
+    pub ics_file: Option<PathBuf>,
+    /// CalDAV calendar collection; the password is stored with `config set-key caldav`
+    pub caldav_url: Option<String>,
+    pub username: Option<String>,
+    /// How often a running tracker reads the calendar again (default 15)
+    pub refresh_minutes: Option<u64>,
+}
+
+/// `mqtt` section of `config.json`: broker receiving the current activity (needs the `mqtt` feature).
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttConfig {
+    /// `mqtt://host[:port]` or `mqtts://host[:port]` (TLS)
+    pub broker_url: String,
+    /// Topics are `<prefix>/activity`, `<prefix>/idle` and `<prefix>/status` (default "mouse_tracking/<device_id>")
+    pub topic_prefix: Option<String>,
+    /// Force TLS on/off regardless of the URL scheme
+    pub tls: Option<bool>,
+    /// Password is the `mqtt` key in the keyring
+    pub username: Option<String>,
+    pub client_id: Option<String>,
+}
+
+/// `shell_hook` section of `config.json`: shells set up with `shell-hook` report their command and
+/// directory to the tracker, which puts them in the titles of terminal windows.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShellHookConfig {
+    /// UDP port on 127.0.0.1 the tracker listens on (default 47813)
+    pub port: u16,
+    /// Globs on the app name of terminal windows (default: common terminal emulators)
+    pub terminal_apps: Vec<String>,
+    /// Record whole command lines instead of only the program name (arguments may hold secrets)
+    pub record_arguments: bool,
+}
+
+impl Default for ShellHookConfig {
+    fn default() -> Self {
+        ShellHookConfig {
+            port: 47813,
+            terminal_apps: [
+                "WindowsTerminal.exe", "OpenConsole.exe", "conhost.exe", "mintty.exe", "*terminal*", "*term",
+                "alacritty*", "kitty", "wezterm*", "konsole", "tilix", "terminator", "foot", "urxvt",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+            record_arguments: false,
+        }
+    }
+}
+
+/// `repo_detection` section of `config.json`: the Git repository of paths in window titles (and of
+/// the directory shell integration reports) is recorded with each interval, for `stats --level repo`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RepoDetectionConfig {
+    /// Globs on the app names whose titles are searched (empty = every app)
+    pub apps: Vec<String>,
+}
+
+/// `ticket_detection` section of `config.json`: the issue key in window titles (Jira "PROJ-123",
+/// GitHub "owner/repo#456") is recorded with each interval, for `stats --level ticket` and worklogs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TicketDetectionConfig {
+    /// Jira project keys to recognize, e.g. ["PROJ", "OPS"] (empty = any KEY-123 shaped word)
+    pub jira_projects: Vec<String>,
+    /// Also recognize GitHub issue and pull request numbers (default true)
+    pub github: bool,
+    /// Globs on the app names whose titles are searched (empty = every app)
+    pub apps: Vec<String>,
+}
+
+impl Default for TicketDetectionConfig {
+    fn default() -> Self {
+        TicketDetectionConfig { jira_projects: Vec::new(), github: true, apps: Vec::new() }
+    }
+}
+
+/// `break_reminder` section of `config.json`: a desktop notification after a long stretch of
+/// continuous activity, with whether a break followed recorded for `report`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BreakReminderConfig {
+    /// Minutes of continuous activity (not idle, locked or paused) before the reminder (default 55)
+    pub after_minutes: u64,
+    /// Minutes without activity that count as a break and restart the count (default 5)
+    pub break_minutes: u64,
+    /// A break starting within this many minutes of the reminder counts as taken (default 10)
+    pub grace_minutes: u64,
+}
+
+impl Default for BreakReminderConfig {
+    fn default() -> Self {
+        BreakReminderConfig { after_minutes: 55, break_minutes: 5, grace_minutes: 10 }
+    }
+}
+
+/// `pseudonymize` section of `config.json`: store app names and titles as salted hashes (see `pseudonym`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PseudonymizeConfig {
+    /// Plain names and titles `stats --reveal` shows instead of their hashes (kept only in this file)
+    pub reveal: Vec<String>,
+}
+
+/// An external detector declared in `config.json` (see `detection::CommandDetector`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct DetectorPluginConfig {
+    /// Label used in logs
+    pub name: String,
+    /// Program run on every poll; prints an `ActivityInfo` JSON object (or `null`) on stdout
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Kill the command if it runs longer than this (default 2000 ms)
+    pub timeout_ms: Option<u64>,
+    /// Whether the plugin replaces the activity or only refines titles of the same app
+    #[serde(default)]
+    pub merge: MergeMode,
+}
+
+fn read_config_file(path: &Path) -> AppResult<FileConfig> {
+    // A locked profile uses the settings signed with `config lock`, whatever config.json says now
+    if let Some(contents) = crate::config_lock::locked_contents(path)? {
+        return serde_json::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid locked config in {:?}: {}", crate::config_lock::lock_path(path), e)));
+    }
+    if !path.exists() {
+        log::debug!("No config file at {:?}, using defaults.", path);
+        return Ok(FileConfig::default());
+    }
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Io { path: path.to_path_buf(), source: e })?;
+    serde_json::from_str(&contents)
+        .map_err(|e| AppError::Config(format!("Invalid config file {:?}: {}", path, e)))
+}
+
+/// Whether `contents` parses as a config file (checked before `config lock` signs it).
+pub fn check_config_contents(contents: &str) -> Result<(), String> {
+    serde_json::from_str::<FileConfig>(contents).map(|_| ()).map_err(|e| format!("Invalid config file: {}", e))
+}
 
 // Define the struct to hold application configuration
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     // Persistence
     pub database_path: PathBuf,
+    // What chose database_path, for `db path` ("default", "database_path in config.json", ...)
+    pub database_path_origin: &'static str,
+    // Journaling of the SQLite file: `Safe` in synced folders and on removable drives (`database_safe_write`)
+    pub database_write_mode: WriteMode,
+    // Open the SQLite database through SQLCipher with the keyring `database` passphrase
+    pub database_encryption: bool,
+    // Central PostgreSQL database ("postgres://..."); None = local SQLite at database_path
+    pub database_url: Option<String>,
+    pub config_file_path: PathBuf,
     pub dangling_threshold_secs: i64,
+    pub write_flush_interval: Duration,
+    // Aggregation merges same-target intervals separated by less than this (None = disabled)
+    pub merge_gap_secs: Option<i64>,
+    // Startup aggregation runs `db maintain` when more than this % of the SQLite file is free pages (None = never)
+    pub vacuum_threshold_percent: Option<u64>,
+    // Aggregation moves raw intervals to `app_intervals_archive` instead of deleting them
+    pub archive_raw_intervals: bool,
+    // Days of per-hour summaries kept by aggregation, for `stats --level profile` (at least 1)
+    pub hourly_summary_days: u64,
+    // Recorded with every interval so data from several machines can be told apart
+    pub device_id: String,
+    // Recorded with every interval so the users of a shared database can be told apart (default: login name)
+    pub user_name: String,
+    // database_path is the `shared_database_path` several OS users write to; `stats` shows only this user
+    pub shared_database: bool,
+
+    // Sync: endpoint receiving `sync push` uploads; token is the `sync` key in the keyring
+    pub sync_url: Option<String>,
+    // Auto-sync while tracking (None = only manual `sync push`)
+    pub sync_interval: Option<Duration>,
+    // Encrypt sync uploads and exports with the keyring encryption key
+    pub end_to_end_encryption: bool,
 
     // Update
     pub repo_owner: String,
     pub repo_name: String,
+    // Proxy for `update` (None = the HTTPS_PROXY/HTTP_PROXY/NO_PROXY environment variables)
+    pub update_proxy: Option<String>,
+    // Extra PEM root certificate(s) `update` trusts, e.g. a TLS-inspecting corporate proxy's
+    pub update_ca_file: Option<PathBuf>,
+
+    // Language model service used unless a command names another, and the local Ollama server's base URL
+    pub llm_provider: LlmProvider,
+    pub ollama_url: String,
 
     // Tracking
     pub check_interval: Duration,
+    pub heartbeat_interval: Duration,
+    // Switches shorter than this are not recorded on their own (zero = off)
+    pub min_interval: Duration,
+    pub short_switch_mode: ShortSwitchMode,
+    // Adaptive polling: slow down while the user is idle on the same window
+    pub adaptive_polling: bool,
+    pub idle_poll_interval: Duration,
+    pub idle_threshold: Duration,
+    // Context snapshots of all open windows (None = disabled)
+    pub snapshot_interval: Option<Duration>,
+    // Extra detection attempts within a poll after a failed one (with backoff)
+    pub detection_retries: u32,
+    // Failed polls keep the last detected activity for this long before nothing is recorded (zero = off)
+    pub detection_grace: Duration,
+    // Apps whose full-screen foreground time is tagged as passive
+    pub passive_apps: Vec<String>,
+    // External detectors merged with the OS detector
+    pub detector_plugins: Vec<DetectorPluginConfig>,
+    // Detector names ("os" = built-in), highest priority first; None = plugins then "os"
+    pub detector_order: Option<Vec<String>>,
+
+    // Toggl Track export (None = not configured)
+    pub toggl: Option<TogglConfig>,
+    // Colors of the stats output
+    pub theme: ThemeConfig,
+    // Hour, day and week boundaries of stats and summaries (default: system local, Monday weeks)
+    pub timezone: ReportTimeZone,
+    // Durations in stats, reports and exports (overridden by `--duration-format`)
+    pub duration_format: DurationFormat,
+    // HTTP notifications on app switches / usage thresholds (None = disabled)
+    pub webhook: Option<WebhookConfig>,
+    // Publish the current activity and idle state to an MQTT broker (None = disabled)
+    pub mqtt: Option<MqttConfig>,
+    // Calendar whose events tag overlapping time as meetings (None = disabled)
+    pub calendar: Option<CalendarConfig>,
+    // Command and directory reported by shells for terminal windows (None = disabled)
+    pub shell_hook: Option<ShellHookConfig>,
+    // Record the Git repository of paths in titles with each interval (None = disabled)
+    pub repo_detection: Option<RepoDetectionConfig>,
+    // Record the issue key found in titles with each interval (None = disabled)
+    pub ticket_detection: Option<TicketDetectionConfig>,
+    // Record the file open in editors and office apps, read from their titles (None = disabled)
+    pub document_detection: Option<DocumentExtractor>,
+    // Notify after `after_minutes` of continuous activity (None = disabled)
+    pub break_reminder: Option<BreakReminderConfig>,
+    // Store app names, titles, repositories, tickets and documents as salted hashes (None = plain text)
+    pub pseudonymize: Option<PseudonymizeConfig>,
+    // Record the full executable path of the tracked app with each interval
+    pub record_executable_path: bool,
+    // Record the command line of the tracked app's process with each interval (may contain secrets)
+    pub record_command_line: bool,
+    // Record which apps play audio as a parallel interval stream (`stats --stream audio`)
+    pub audio_tracking: bool,
+    // Report installed web apps (PWAs) under their own name rather than their browser's (Windows)
+    pub web_app_detection: bool,
+    // Rewrites of window titles before they are recorded (`normalize`)
+    pub title_rules: TitleRules,
+    // Per-app choice of the detailed title among the app's windows (Windows detector)
+    pub title_heuristics: TitleHeuristics,
+    // Address of the gRPC control/query API served while tracking (None = disabled)
+    pub grpc_listen: Option<SocketAddr>,
+    // Address of the Prometheus `/metrics` endpoint served while tracking (None = disabled)
+    pub metrics_listen: Option<SocketAddr>,
+    // Local minute of the day (in `timezone`) the tracker shows the daily recap notification (None = disabled)
+    pub daily_recap_minute: Option<u32>,
+    // Chat webhooks the daily summary is posted to (`report --post`; empty URLs removed)
+    pub notify: NotifyConfig,
+    // Local minute of the day the tracker posts the summary to every `notify` webhook (None = disabled)
+    pub notify_post_minute: Option<u32>,
+    // Categories and daily limits of `screentime` (categories fall back to `notify.categories`)
+    pub screentime: ScreenTimeConfig,
+    // Named stats queries run with `report run <name>`, by name
+    pub reports: BTreeMap<String, SavedReport>,
+    // Jobs the tracker runs at local times or intervals (`schedule list`), in name order
+    pub schedule: Vec<ScheduledJob>,
 
     // General App Info (can still be derived or stored here)
     pub app_name: String,
     pub app_version: String,
 
+    // Selected with `--profile` (None = the default profile in the data directory itself)
+    pub profile: Option<String>,
+    // Record intervals in other profiles by schedule/app while tracking (empty = always this profile)
+    pub profile_rules: ProfileRules,
+    // When the tracker records (empty = always); outside these windows it idles without writing
+    pub tracking_hours: TrackingHours,
+
     //Api keys
     pub keyring_service_name: String, 
 
 }
 
+/// Profile names become directory names and keyring suffixes, so only letters, digits, `-` and `_` are allowed.
+pub fn validate_profile_name(name: &str) -> AppResult<()> {
+    if name.is_empty() || name.len() > 64 {
+        return Err(AppError::Config(format!("Invalid profile name {:?}: use 1 to 64 characters", name)));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(AppError::Config(format!("Invalid profile name {:?}: use only letters, digits, '-' and '_'", name)));
+    }
+    Ok(())
+}
+
+/// `mouse_tracking[-dev]` in the user data directory (or `MOUSE_TRACKING_DATA_DIR`), created if
+/// missing. Holds the default profile's files and the `profiles` directory.
+pub fn data_dir_path() -> AppResult<PathBuf> {
+    let db_dir_path = match env_path(DATA_DIR_ENV) {
+        Some(dir) => dir,
+        None => {
+            // Database Path (using build profile for dev/release differentiation for now)
+            let mut dir_name = env!("CARGO_PKG_NAME").to_string();
+            if cfg!(debug_assertions) {
+                dir_name.push_str("-dev"); // Append suffix for debug builds
+            }
+            user_data_dir()?.join(dir_name)
+        }
+    };
+
+    // Ensure the directory exists before adding filename
+    if !db_dir_path.exists() {
+        std::fs::create_dir_all(&db_dir_path)
+            // Map IO error to our custom error type, including context
+            .map_err(|e| AppError::Io { path: db_dir_path.clone(), source: e })?;
+    }
+    Ok(db_dir_path)
+}
+
+/// The user data directory. On Linux `$XDG_DATA_HOME`, or `~/.local/share` when it is unset or not an
+/// absolute path (which the XDG Base Directory spec says to ignore); elsewhere the platform's own.
+fn user_data_dir() -> AppResult<PathBuf> {
+    if cfg!(target_os = "linux") {
+        let xdg_data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute());
+        if let Some(dir) = xdg_data_home.or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share"))) {
+            return Ok(dir);
+        }
+    }
+    dirs::data_dir().ok_or_else(|| AppError::DataDir("Could not find user data directory.".to_string()))
+}
+
+/// A non-empty path from the environment variable `name`, made absolute.
+fn env_path(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(std::env::var_os(name).filter(|value| !value.is_empty())?);
+    Some(std::path::absolute(&path).unwrap_or(path))
+}
+
+/// Directory holding a profile's config.json and database (None or "default" = the data directory itself).
+/// Does not check that a named profile exists.
+pub fn profile_dir_path(profile: Option<&str>) -> AppResult<PathBuf> {
+    match profile.filter(|name| *name != DEFAULT_PROFILE) {
+        Some(name) => {
+            validate_profile_name(name)?;
+            Ok(data_dir_path()?.join(PROFILES_DIR_NAME).join(name))
+        }
+        None => data_dir_path(),
+    }
+}
+
+/// Named profiles (directories under `profiles`), sorted; the default profile is not included.
+pub fn list_profiles() -> AppResult<Vec<String>> {
+    let profiles_dir = data_dir_path()?.join(PROFILES_DIR_NAME);
+    if !profiles_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(&profiles_dir).map_err(|e| AppError::Io { path: profiles_dir.clone(), source: e })?;
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| AppError::Io { path: profiles_dir.clone(), source: e })?;
+        if !entry.path().is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str().filter(|name| validate_profile_name(name).is_ok()) {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Keyring service of a profile: named profiles keep their own keys (database passphrase, tokens).
+pub fn keyring_service_name(profile: Option<&str>) -> String {
+    let mut service = format!("{}{}", KEYRING_SERVICE_NAME_PREFIX, env!("CARGO_PKG_NAME"));
+    if cfg!(debug_assertions) {
+        service.push_str("-dev");
+    }
+    if let Some(name) = profile.filter(|name| *name != DEFAULT_PROFILE) {
+        service.push_str("-profile-");
+        service.push_str(name);
+    }
+    service
+}
+
 // Function to determine and load the application configuration
 // This is where we'll centralize logic for finding paths,
 // reading files (later), parsing args (later), etc.
 pub fn load_configuration() -> AppResult<AppConfig> { // Return AppResult
+    load_profile_configuration(None)
+}
+
+/// Like `load_configuration`, for the profile selected with `--profile` (which must have been
+/// created with `profile create`).
+pub fn load_profile_configuration(profile: Option<&str>) -> AppResult<AppConfig> {
 
     // --- Determine Base Values (Compile time) ---
     let base_app_name = env!("CARGO_PKG_NAME").to_string();
     let app_version = env!("CARGO_PKG_VERSION").to_string();
 
     // --- Determine Runtime Values ---
-
-    // Database Path (using build profile for dev/release differentiation for now)
-    let mut dir_name = base_app_name.clone(); // Clone base name
-    let is_dev_build = cfg!(debug_assertions);
-    let mut unique_name_part = base_app_name.clone(); 
-
-    if is_dev_build {
-        dir_name.push_str("-dev"); // Append suffix for debug builds
+    if cfg!(debug_assertions) {
         println!("[Debug Build Detected] Using data directory suffix: -dev");
-        unique_name_part.push_str("-dev"); // Append suffix for debug builds
-
     }
-
-    let mut db_dir_path = dirs::data_dir()
-        // Map Option error to our custom error type
-        .ok_or_else(|| AppError::DataDir("Could not find user data directory.".to_string()))?;
-
-    db_dir_path.push(&dir_name); // Use determined directory name
-
-    // Ensure the directory exists before adding filename
-    if !db_dir_path.exists() {
-        std::fs::create_dir_all(&db_dir_path)
-            // Map IO error to our custom error type, including context
-            .map_err(|e| AppError::Io { path: db_dir_path.clone(), source: e })?;
+    let profile = profile.filter(|name| *name != DEFAULT_PROFILE).map(str::to_string);
+    let db_dir_path = profile_dir_path(profile.as_deref())?;
+    if let Some(name) = &profile
+        && !db_dir_path.is_dir()
+    {
+        return Err(AppError::Config(format!("Profile '{}' does not exist; create it with `profile create {}`", name, name)));
     }
 
-    let database_path = db_dir_path.join("app_usage.sqlite"); // Use a filename constant?
+    let config_file_path = db_dir_path.join(CONFIG_FILE_NAME);
+    let file_config = read_config_file(&config_file_path)?;
+    // Each OS user has their own data directory; `shared_database_path` puts everyone in one file
+    let shared_database_path = file_config.shared_database_path.clone().filter(|path| !path.as_os_str().is_empty());
+    let shared_database = shared_database_path.is_some();
+    // Relative paths in config.json are relative to its directory
+    let config_path = |path: &Option<PathBuf>| path.clone().filter(|path| !path.as_os_str().is_empty()).map(|path| db_dir_path.join(path));
+    let custom_database_path = [
+        (env_path(DATABASE_PATH_ENV), "the MOUSE_TRACKING_DATABASE_PATH environment variable"),
+        (config_path(&file_config.database_path), "database_path in config.json"),
+        (config_path(&file_config.data_dir).map(|dir| dir.join(DATABASE_FILE_NAME)), "data_dir in config.json"),
+    ]
+    .into_iter()
+    .find_map(|(path, origin)| Some((path?, origin)));
+    if let Some((_, origin)) = custom_database_path
+        && shared_database
+    {
+        return Err(AppError::Config(format!("shared_database_path cannot be combined with {}", origin)));
+    }
+    let (database_path, database_path_origin) = match (shared_database_path, custom_database_path) {
+        (Some(path), _) => (path, "shared_database_path in config.json"),
+        (None, Some(custom)) => custom,
+        (None, None) => (db_dir_path.join(DATABASE_FILE_NAME), "default"),
+    };
+    let database_write_mode = match file_config.database_safe_write {
+        Some(true) => WriteMode::Safe,
+        Some(false) => WriteMode::Wal,
+        None => WriteMode::for_path(&database_path),
+    };
  
-    // Other Config Values (currently hardcoded, could load from file/env later)
+    // Other Config Values (hardcoded defaults, some overridable from the config file)
     let repo_owner = "Netajam".to_string(); // Replace with your owner
     let repo_name = base_app_name.clone(); // Use base name for repo too
-    let check_interval_secs = 1;
+    let check_interval_secs = file_config.check_interval_secs.unwrap_or(1).max(1);
     let check_interval = Duration::from_secs(check_interval_secs);
+    let adaptive_polling = file_config.adaptive_polling.unwrap_or(false);
+    let idle_poll_interval = Duration::from_secs(file_config.idle_poll_interval_secs.unwrap_or(10).max(check_interval_secs));
+    let idle_threshold = Duration::from_secs(file_config.idle_threshold_secs.unwrap_or(60));
+    let snapshot_interval = file_config.snapshot_interval_mins
+        .filter(|mins| *mins > 0)
+        .map(|mins| Duration::from_secs(mins * 60));
+    let passive_apps = file_config.passive_apps.unwrap_or_else(|| {
+        crate::detection::passive::DEFAULT_PASSIVE_APPS.iter().map(|app| app.to_string()).collect()
+    });
+    let sync_url = file_config.sync_url.filter(|url| !url.trim().is_empty());
+    let sync_interval = file_config.sync_interval_mins
+        .filter(|mins| *mins > 0 && sync_url.is_some())
+        .map(|mins| Duration::from_secs(mins * 60));
+    let grpc_listen = file_config.grpc_listen
+        .filter(|addr| !addr.trim().is_empty())
+        .map(|addr| addr.trim().parse::<SocketAddr>()
+            .map_err(|e| AppError::Config(format!("Invalid grpc_listen {:?} (expected e.g. \"127.0.0.1:50051\"): {}", addr, e))))
+        .transpose()?;
+    let metrics_listen = file_config.metrics_listen
+        .filter(|addr| !addr.trim().is_empty())
+        .map(|addr| addr.trim().parse::<SocketAddr>()
+            .map_err(|e| AppError::Config(format!("Invalid metrics_listen {:?} (expected e.g. \"127.0.0.1:9187\"): {}", addr, e))))
+        .transpose()?;
+    let timezone = file_config.timezone.as_deref().map_or_else(|| Ok(ReportTimeZone::default()), ReportTimeZone::parse)
+        .map_err(AppError::Config)?
+        .with_week_start(file_config.week_start.unwrap_or_default());
+    let timezone = match file_config.day_start_hour {
+        Some(hour) if hour > 23 => return Err(AppError::Config(format!("day_start_hour must be an hour from 0 to 23, not {}", hour))),
+        hour => timezone.with_day_start_hour(hour.unwrap_or(0)),
+    };
+    let daily_recap_minute = file_config.daily_recap_time
+        .filter(|time| !time.trim().is_empty())
+        .map(|time| crate::utils::parse_time_of_day(&time).map_err(|e| AppError::Config(format!("daily_recap_time: {}", e))))
+        .transpose()?;
+    let mut notify = file_config.notify;
+    notify.slack_webhook_url = notify.slack_webhook_url.filter(|url| !url.trim().is_empty());
+    notify.discord_webhook_url = notify.discord_webhook_url.filter(|url| !url.trim().is_empty());
+    let notify_post_minute = notify.post_time
+        .as_deref()
+        .filter(|time| !time.trim().is_empty())
+        .map(|time| crate::utils::parse_time_of_day(time).map_err(|e| AppError::Config(format!("notify.post_time: {}", e))))
+        .transpose()?;
+    let mut screentime = file_config.screentime;
+    if screentime.categories.is_empty() {
+        screentime.categories = notify.categories
+            .iter()
+            .map(|category| ScreenTimeCategory { name: category.name.clone(), apps: category.apps.clone(), daily_limit_minutes: None })
+            .collect();
+    }
+    let calendar = file_config.calendar
+        .map(|mut calendar| {
+            calendar.caldav_url = calendar.caldav_url.filter(|url| !url.trim().is_empty());
+            match (&calendar.ics_file, &calendar.caldav_url) {
+                (Some(_), None) | (None, Some(_)) => Ok(calendar),
+                _ => Err(AppError::Config("calendar needs exactly one of ics_file and caldav_url".to_string())),
+            }
+        })
+        .transpose()?;
+    let ticket_detection = file_config.ticket_detection
+        .map(|mut tickets| {
+            for project in &mut tickets.jira_projects {
+                *project = project.trim().to_ascii_uppercase();
+                let mut chars = project.chars();
+                if !chars.next().is_some_and(|c| c.is_ascii_uppercase()) || !chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+                    return Err(AppError::Config(format!("ticket_detection.jira_projects: {:?} is not a Jira project key", project)));
+                }
+            }
+            Ok(tickets)
+        })
+        .transpose()?;
+    let break_reminder = file_config.break_reminder
+        .map(|reminder| {
+            if reminder.after_minutes == 0 || reminder.break_minutes == 0 {
+                Err(AppError::Config("break_reminder.after_minutes and break_minutes must be at least 1".to_string()))
+            } else {
+                Ok(reminder)
+            }
+        })
+        .transpose()?;
+    let shell_hook = file_config.shell_hook
+        .map(|hook| if hook.port == 0 { Err(AppError::Config("shell_hook.port must be a fixed port (1-65535)".to_string())) } else { Ok(hook) })
+        .transpose()?;
+    let reports = saved_reports::parse(&file_config.reports).map_err(AppError::Config)?;
+    let schedule = schedule::parse(&file_config.schedule, &reports, sync_url.is_some()).map_err(AppError::Config)?;
+    let profile_rules = ProfileRules::parse(&file_config.profile_rules, profile.as_deref()).map_err(AppError::Config)?;
+    let tracking_hours = TrackingHours::parse(&file_config.tracking_hours).map_err(AppError::Config)?;
+    let title_rules = TitleRules::parse(&file_config.title_rules).map_err(AppError::Config)?;
+    let title_heuristics = TitleHeuristics::parse(&file_config.title_heuristics).map_err(AppError::Config)?;
+    let document_detection = file_config.document_detection
+        .map(|documents| DocumentExtractor::parse(&documents).map_err(AppError::Config))
+        .transpose()?;
+    let heartbeat_interval = Duration::from_secs(30); // Max activity lost on a crash
     let dangling_threshold_secs = 24 * 60 * 60; // 1 day
+    let write_flush_interval = Duration::from_secs(10); // Buffered interval writes are committed at this pace
     
-    let keyring_service_name = format!("{}{}", KEYRING_SERVICE_NAME_PREFIX, unique_name_part);
+    let keyring_service_name = keyring_service_name(profile.as_deref());
     log::debug!("Derived keyring service name: {}", keyring_service_name); // Log derived name
     // --- Construct the AppConfig struct ---
     Ok(AppConfig {
         database_path,
+        database_path_origin,
+        database_write_mode,
+        database_encryption: file_config.database_encryption.unwrap_or(false),
+        database_url: file_config.database_url.filter(|url| !url.trim().is_empty()),
+        config_file_path,
         dangling_threshold_secs,
+        write_flush_interval,
+        merge_gap_secs: file_config.merge_gap_secs.filter(|secs| *secs > 0).map(|secs| secs as i64),
+        vacuum_threshold_percent: file_config.vacuum_threshold_percent.filter(|percent| *percent > 0),
+        archive_raw_intervals: file_config.archive_raw_intervals.unwrap_or(false),
+        hourly_summary_days: file_config.hourly_summary_days.unwrap_or(crate::persistence::DEFAULT_HOURLY_SUMMARY_DAYS).max(1),
+        device_id: file_config.device_id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(crate::utils::hostname),
+        user_name: file_config.user_name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(crate::utils::login_name),
+        shared_database,
+        sync_url,
+        sync_interval,
+        end_to_end_encryption: file_config.end_to_end_encryption.unwrap_or(false),
         repo_owner,
         repo_name,
+        update_proxy: file_config.update_proxy.filter(|url| !url.trim().is_empty()),
+        update_ca_file: file_config.update_ca_file.filter(|path| !path.as_os_str().is_empty()),
+        llm_provider: file_config.llm_provider.unwrap_or_default(),
+        ollama_url: file_config.ollama_url
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_OLLAMA_URL.to_string()),
         check_interval,
+        heartbeat_interval,
+        min_interval: Duration::from_secs(file_config.min_interval_secs.unwrap_or(0)),
+        short_switch_mode: file_config.short_switch_mode.unwrap_or_default(),
+        adaptive_polling,
+        idle_poll_interval,
+        idle_threshold,
+        snapshot_interval,
+        detection_retries: file_config.detection_retries.unwrap_or(2).min(5),
+        detection_grace: Duration::from_secs(file_config.detection_grace_secs.unwrap_or(5)),
+        passive_apps,
+        detector_plugins: file_config.detector_plugins,
+        detector_order: file_config.detector_order,
+        toggl: file_config.toggl,
+        theme: file_config.theme,
+        timezone,
+        duration_format: file_config.duration_format.unwrap_or_default(),
+        webhook: file_config.webhook.filter(|webhook| !webhook.url.trim().is_empty()),
+        mqtt: file_config.mqtt.filter(|mqtt| !mqtt.broker_url.trim().is_empty()),
+        calendar,
+        shell_hook,
+        repo_detection: file_config.repo_detection,
+        ticket_detection,
+        document_detection,
+        break_reminder,
+        pseudonymize: file_config.pseudonymize,
+        record_executable_path: file_config.record_executable_path.unwrap_or(false),
+        record_command_line: file_config.record_command_line.unwrap_or(false),
+        audio_tracking: file_config.audio_tracking.unwrap_or(false),
+        web_app_detection: file_config.web_app_detection.unwrap_or(true),
+        title_rules,
+        title_heuristics,
+        grpc_listen,
+        metrics_listen,
+        daily_recap_minute,
+        notify,
+        notify_post_minute,
+        screentime,
+        reports,
+        schedule,
         app_name: base_app_name, // Store derived app name
         app_version,             // Store derived version
+        profile,
+        profile_rules,
+        tracking_hours,
         keyring_service_name, 
     })
 }
@@ -0,0 +1,250 @@
+// src/config_lock.rs
+// Administrator lock (`config lock`): a signed copy of config.json is kept in `config.lock` next to it.
+// While the lock exists every command reads the settings from that copy, so edits to config.json (limits,
+// exclusions, retention, ...) have no effect until an administrator signs them, and commands that change
+// settings or data ask for the administrator password. The signing key is derived from that password with
+// Argon2id; only its public key is stored, in `admin_key.json` in a directory only administrators can write,
+// so a lock signed with any other key (e.g. a hand-made one with its own password) is rejected.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const LOCK_FILE_NAME: &str = "config.lock";
+pub const ADMIN_KEY_FILE_NAME: &str = "admin_key.json";
+/// Machine-wide, outside every user's data directory (fixed, not taken from the environment)
+#[cfg(windows)]
+const ADMIN_KEY_DIR: &str = r"C:\ProgramData\mouse_tracking";
+#[cfg(not(windows))]
+const ADMIN_KEY_DIR: &str = "/etc/mouse_tracking";
+/// Argon2id cost (memory in KiB, passes, lanes), fixed so keys stay valid across crate updates
+const KEY_DERIVATION_PARAMS: (u32, u32, u32) = (19 * 1024, 2, 1);
+
+/// Contents of `admin_key.json`: what checks the password and the signature of every lock on the machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminKey {
+    /// Base64 random salt of the key derivation
+    pub salt: String,
+    /// Base64 Ed25519 public key of the administrator
+    pub public_key: String,
+}
+
+/// Contents of `config.lock`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigLock {
+    /// Base64 public key the lock was signed with; must be the one in `admin_key.json`
+    pub public_key: String,
+    /// The locked config.json, verbatim
+    pub config: String,
+    /// Base64 signature of `config`
+    pub signature: String,
+    pub locked_at: i64,
+}
+
+impl AdminKey {
+    fn verifying_key(&self) -> AppResult<VerifyingKey> {
+        let bytes: [u8; 32] = decode(&self.public_key)?
+            .try_into()
+            .map_err(|_| damaged("the administrator key has the wrong length"))?;
+        VerifyingKey::from_bytes(&bytes).map_err(|_| damaged("the administrator key is invalid"))
+    }
+
+    /// The signing key when `password` is the administrator's.
+    fn signing_key(&self, password: &str) -> AppResult<Option<SigningKey>> {
+        let key = derive_key(password, &decode(&self.salt)?)?;
+        Ok((key.verifying_key() == self.verifying_key()?).then_some(key))
+    }
+}
+
+impl ConfigLock {
+    fn verify(&self, admin_key: &AdminKey) -> AppResult<()> {
+        if self.public_key.trim() != admin_key.public_key.trim() {
+            return Err(damaged("it was not signed with the administrator key"));
+        }
+        let signature = Signature::from_slice(&decode(&self.signature)?).map_err(|_| damaged("the signature is invalid"))?;
+        admin_key
+            .verifying_key()?
+            .verify(self.config.as_bytes(), &signature)
+            .map_err(|_| damaged("the signature does not match the locked settings"))
+    }
+}
+
+pub fn admin_key_path() -> PathBuf {
+    Path::new(ADMIN_KEY_DIR).join(ADMIN_KEY_FILE_NAME)
+}
+
+fn read_admin_key() -> AppResult<Option<AdminKey>> {
+    let path = admin_key_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    serde_json::from_str(&json).map(Some).map_err(|e| damaged(&format!("{:?}: {}", path, e)))
+}
+
+pub fn lock_path(config_file_path: &Path) -> PathBuf {
+    config_file_path.with_file_name(LOCK_FILE_NAME)
+}
+
+/// The lock next to `config_file_path`, if any, with the administrator key its signature was checked against.
+fn read(config_file_path: &Path) -> AppResult<Option<(ConfigLock, AdminKey)>> {
+    let path = lock_path(config_file_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    let lock: ConfigLock = serde_json::from_str(&json).map_err(|e| damaged(&e.to_string()))?;
+    let admin_key = read_admin_key()?.ok_or_else(|| damaged(&format!("the administrator key {:?} is missing", admin_key_path())))?;
+    lock.verify(&admin_key)?;
+    Ok(Some((lock, admin_key)))
+}
+
+/// The settings to use instead of config.json when it is locked (used by `config::load_profile_configuration`).
+pub fn locked_contents(config_file_path: &Path) -> AppResult<Option<String>> {
+    let Some((lock, _)) = read(config_file_path)? else { return Ok(None) };
+    if fs::read_to_string(config_file_path).ok().as_deref() != Some(lock.config.as_str()) {
+        log::warn!("{:?} was changed after it was locked; using the locked settings.", config_file_path);
+    }
+    Ok(Some(lock.config))
+}
+
+/// Asks for the administrator password when the config is locked; `action` completes
+/// "Administrator password to ...". Does nothing when it is not locked.
+pub fn require_admin(app_config: &AppConfig, action: &str) -> AppResult<()> {
+    require_admin_at(&app_config.config_file_path, action).map(|_| ())
+}
+
+/// `require_admin` for the default profile. Other profiles have config.json files of their own, so
+/// `--profile` and the `profile` commands would step around its lock without this. Returns whether the
+/// password was checked; one administrator key signs every lock, so that also covers the other profile's.
+pub fn require_default_profile_admin(action: &str) -> AppResult<bool> {
+    require_admin_at(&crate::config::profile_dir_path(None)?.join(crate::config::CONFIG_FILE_NAME), action)
+}
+
+fn require_admin_at(config_file_path: &Path, action: &str) -> AppResult<bool> {
+    let Some((_, admin_key)) = read(config_file_path)? else { return Ok(false) };
+    let password = rpassword::prompt_password(format!("The configuration is locked. Administrator password to {}: ", action))?;
+    if admin_key.signing_key(&password)?.is_none() {
+        return Err(AppError::Locked("wrong administrator password".to_string()));
+    }
+    Ok(true)
+}
+
+/// `config lock`: signs the current config.json. The first lock on the machine sets the password and
+/// saves `admin_key.json` (which needs administrator rights); later ones, of any profile, ask for it.
+pub fn lock(app_config: &AppConfig) -> AppResult<()> {
+    let config_path = &app_config.config_file_path;
+    let config = match fs::read_to_string(config_path) {
+        Ok(config) => config,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "{}\n".to_string(),
+        Err(e) => return Err(AppError::Io { path: config_path.clone(), source: e }),
+    };
+    // Parsed like any config, so a typo cannot lock the tracker into a broken configuration
+    crate::config::check_config_contents(&config).map_err(AppError::Config)?;
+    let (admin_key, key) = match read_admin_key()? {
+        Some(admin_key) => {
+            let password = rpassword::prompt_password("Administrator password: ")?;
+            let key = admin_key.signing_key(&password)?.ok_or_else(|| AppError::Locked("wrong administrator password".to_string()))?;
+            // A damaged lock is simply replaced; the password was checked against the administrator key
+            if let Ok(Some((existing, _))) = read(config_path)
+                && existing.config != config
+            {
+                println!("config.json differs from the locked settings; its current contents are locked now.");
+            }
+            (admin_key, key)
+        }
+        None => {
+            let password = rpassword::prompt_password("New administrator password: ")?;
+            if password.is_empty() {
+                return Err(AppError::Config("The administrator password cannot be empty".to_string()));
+            }
+            if rpassword::prompt_password("Repeat it: ")? != password {
+                return Err(AppError::Config("The passwords do not match".to_string()));
+            }
+            let salt = decode(&crate::crypto::generate_key())?;
+            let key = derive_key(&password, &salt)?;
+            let admin_key = AdminKey { salt: BASE64.encode(&salt), public_key: BASE64.encode(key.verifying_key().as_bytes()) };
+            write_admin_key(&admin_key)?;
+            (admin_key, key)
+        }
+    };
+    let lock = ConfigLock {
+        public_key: admin_key.public_key,
+        signature: BASE64.encode(key.sign(config.as_bytes()).to_bytes()),
+        config,
+        locked_at: chrono::Utc::now().timestamp(),
+    };
+    let path = lock_path(config_path);
+    let json = serde_json::to_string_pretty(&lock).map_err(|e| AppError::Unexpected(e.to_string()))?;
+    fs::write(&path, json).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    println!("Locked the settings in {:?} ({:?}).", config_path, path);
+    println!("Make {:?} read-only for the tracked account so it cannot be removed.", path);
+    Ok(())
+}
+
+/// Saves the administrator key; without the rights to, prints it for an administrator to save by hand.
+fn write_admin_key(admin_key: &AdminKey) -> AppResult<()> {
+    let path = admin_key_path();
+    let json = serde_json::to_string(admin_key).map_err(|e| AppError::Unexpected(e.to_string()))?;
+    if let Err(e) = fs::create_dir_all(ADMIN_KEY_DIR).and_then(|()| fs::write(&path, &json)) {
+        return Err(AppError::Locked(format!(
+            "cannot save the administrator key to {:?} ({}). As an administrator, save this line there, then run `config lock` again:\n{}",
+            path, e, json
+        )));
+    }
+    println!("Saved the administrator key to {:?}; only administrators should be able to change it.", path);
+    Ok(())
+}
+
+/// `config unlock`: removes the lock after checking the password; config.json applies again.
+pub fn unlock(app_config: &AppConfig) -> AppResult<()> {
+    let path = lock_path(&app_config.config_file_path);
+    if read(&app_config.config_file_path)?.is_none() {
+        println!("The configuration is not locked.");
+        return Ok(());
+    }
+    require_admin(app_config, "unlock it")?;
+    fs::remove_file(&path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    println!("Unlocked; {:?} applies again.", app_config.config_file_path);
+    Ok(())
+}
+
+/// `config lock-status`
+pub fn print_status(app_config: &AppConfig) -> AppResult<()> {
+    match read(&app_config.config_file_path)? {
+        None => println!("The configuration is not locked."),
+        Some((lock, _)) => {
+            println!("Locked since {}.", crate::utils::format_local_timestamp(lock.locked_at));
+            let current = fs::read_to_string(&app_config.config_file_path).ok();
+            if current.as_deref() == Some(lock.config.as_str()) {
+                println!("config.json matches the locked settings.");
+            } else {
+                println!("config.json was changed after locking; the locked settings are used until `config lock` signs the changes.");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> AppResult<SigningKey> {
+    let (memory_kib, passes, lanes) = KEY_DERIVATION_PARAMS;
+    let params = Params::new(memory_kib, passes, lanes, Some(32)).map_err(|e| AppError::Unexpected(e.to_string()))?;
+    let mut seed = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut seed)
+        .map_err(|e| AppError::Unexpected(format!("Key derivation failed: {}", e)))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+fn decode(value: &str) -> AppResult<Vec<u8>> {
+    BASE64.decode(value.trim()).map_err(|e| damaged(&e.to_string()))
+}
+
+fn damaged(reason: &str) -> AppError {
+    AppError::Locked(format!("{} is damaged or was tampered with ({}); ask the administrator to lock the settings again", LOCK_FILE_NAME, reason))
+}
@@ -0,0 +1,64 @@
+// src/crypto.rs
+// Client-side encryption for data leaving the machine (sync uploads, exports).
+// XChaCha20-Poly1305 with a random 256-bit key kept in the keyring (`ApiKeyType::Encryption`).
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::types::ApiKeyType;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+/// Value of `EncryptedPayload::algorithm`
+pub const ALGORITHM: &str = "xchacha20poly1305";
+
+/// Self-describing envelope replacing a plaintext JSON body/file when encryption is on.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedPayload {
+    pub algorithm: String,
+    /// Base64 24-byte nonce, fresh for every payload
+    pub nonce: String,
+    /// Base64 ciphertext including the Poly1305 tag
+    pub ciphertext: String,
+}
+
+/// Creates a new random key, base64-encoded for storage in the keyring.
+pub fn generate_key() -> String {
+    BASE64.encode(XChaCha20Poly1305::generate_key(&mut OsRng))
+}
+
+/// Loads the key saved by `config gen-encryption-key`.
+pub fn load_key(app_config: &AppConfig) -> AppResult<Key> {
+    let encoded = crate::commands::set_key::load_api_key(app_config, ApiKeyType::Encryption)?;
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .map_err(|e| AppError::Crypto(format!("Stored encryption key is not valid base64: {}", e)))?;
+    if bytes.len() != 32 {
+        return Err(AppError::Crypto(format!("Stored encryption key has {} bytes, expected 32", bytes.len())));
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> AppResult<EncryptedPayload> {
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::Crypto("Encryption failed".to_string()))?;
+    Ok(EncryptedPayload { algorithm: ALGORITHM.to_string(), nonce: BASE64.encode(nonce), ciphertext: BASE64.encode(ciphertext) })
+}
+
+pub fn decrypt(key: &Key, payload: &EncryptedPayload) -> AppResult<Vec<u8>> {
+    if payload.algorithm != ALGORITHM {
+        return Err(AppError::Crypto(format!("Unsupported algorithm '{}'", payload.algorithm)));
+    }
+    let nonce = BASE64.decode(&payload.nonce).map_err(|e| AppError::Crypto(format!("Invalid nonce: {}", e)))?;
+    if nonce.len() != 24 {
+        return Err(AppError::Crypto("Invalid nonce length".to_string()));
+    }
+    let ciphertext = BASE64.decode(&payload.ciphertext).map_err(|e| AppError::Crypto(format!("Invalid ciphertext: {}", e)))?;
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| AppError::Crypto("Decryption failed (wrong key or corrupted data)".to_string()))
+}
@@ -0,0 +1,89 @@
+// src/detection/command_detector.rs
+
+use super::{ActivityDetector, ActivityInfo};
+use crate::config::DetectorPluginConfig;
+use crate::errors::{AppError, AppResult, DetectionError};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// Detector plugin backed by an external command. Each poll runs the command and parses
+/// its stdout as an `ActivityInfo` JSON object, e.g.
+/// `{"app_name": "tmux", "main_title": "work", "detailed_title": "vim src/main.rs"}`.
+/// Empty output or `null` means the plugin has nothing to report.
+#[derive(Debug, Clone)]
+pub struct CommandDetector {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandDetector {
+    pub fn new(plugin: &DetectorPluginConfig) -> Self {
+        CommandDetector {
+            name: plugin.name.clone(),
+            command: plugin.command.clone(),
+            args: plugin.args.clone(),
+            timeout: plugin.timeout_ms.map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Runs the command, killing it if it exceeds the timeout, and returns its stdout.
+    fn run(&self) -> AppResult<String> {
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|source| DetectionError::PluginStart { plugin: self.name.clone(), source })?;
+
+        // Read stdout on a helper thread so a chatty plugin cannot block on a full pipe
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = thread::spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_| output)
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(DetectionError::PluginTimeout { plugin: self.name.clone(), timeout: self.timeout }.into());
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                Err(source) => return Err(DetectionError::PluginIo { plugin: self.name.clone(), source }.into()),
+            }
+        };
+        if !status.success() {
+            return Err(DetectionError::PluginExit { plugin: self.name.clone(), status }.into());
+        }
+        reader
+            .join()
+            .map_err(|_| AppError::Unexpected(format!("Detector plugin '{}' reader panicked", self.name)))?
+            .map_err(|source| DetectionError::PluginIo { plugin: self.name.clone(), source }.into())
+    }
+}
+
+impl ActivityDetector for CommandDetector {
+    fn get_current_activity(&self) -> AppResult<Option<ActivityInfo>> {
+        let output = self.run()?;
+        let output = output.trim();
+        if output.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str::<Option<ActivityInfo>>(output)
+            .map_err(|source| DetectionError::PluginOutput { plugin: self.name.clone(), source }.into())
+    }
+}
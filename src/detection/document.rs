@@ -0,0 +1,114 @@
+// src/detection/document.rs
+// `document_detection` in config.json: the file open in an editor or office app, read from the window
+// title ("report.docx - Word", "main.rs - mouse_tracking - Visual Studio Code") and recorded with each
+// interval for `stats --level document`. Built-in patterns cover common editors; own patterns come first.
+
+use super::ActivityInfo;
+use crate::utils::glob_match;
+use regex::Regex;
+use serde::Deserialize;
+
+/// `document_detection` section of `config.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DocumentDetectionConfig {
+    /// Use the built-in patterns (`BUILTIN_PATTERNS`) after the own ones (default true)
+    pub builtin: bool,
+    /// Own patterns, tried in order before the built-in ones
+    pub patterns: Vec<DocumentPatternConfig>,
+}
+
+impl Default for DocumentDetectionConfig {
+    fn default() -> Self {
+        DocumentDetectionConfig { builtin: true, patterns: Vec::new() }
+    }
+}
+
+/// One entry of `document_detection.patterns`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DocumentPatternConfig {
+    /// Glob on the app name (case-insensitive, e.g. "myeditor*")
+    pub app: String,
+    /// Regular expression on the window title; the group named `document` (else the first group) is the file
+    pub pattern: String,
+}
+
+/// App-name globs and title pattern of the editors and office apps known out of the box.
+const BUILTIN_PATTERNS: &[(&[&str], &str)] = &[
+    // "● main.rs - mouse_tracking - Visual Studio Code"
+    (&["code*", "vscodium*", "cursor*"], r"^(?:● )?(?P<document>.+?) - (?:.+ - )?(?:Visual Studio Code|VSCodium|Cursor)"),
+    // "*notes.txt - Notepad"
+    (&["notepad.exe"], r"^\*?(?P<document>.+) - Notepad$"),
+    // "*C:\src\notes.txt - Notepad++"
+    (&["notepad++*"], r"^\*?(?P<document>.+) - Notepad\+\+"),
+    // "main.rs (mouse_tracking) - Sublime Text", "~/src/main.rs • - Sublime Text"
+    (&["sublime_text*"], r"^(?P<document>.+?)(?: \(.+\))?(?: •)? - Sublime Text"),
+    // "Report.docx - Word", "Budget.xlsx [Compatibility Mode] - Excel"
+    (&["winword*", "excel*", "powerpnt*"], r"^(?P<document>.+?)(?: \[.+\])? - (?:Word|Excel|PowerPoint)$"),
+    // "report.odt - LibreOffice Writer"
+    (&["soffice*", "libreoffice*"], r"^(?P<document>.+?) - LibreOffice \w+$"),
+    // "manual.pdf - Adobe Acrobat Reader (64-bit)"
+    (&["acrobat*", "acrord32*"], r"^(?P<document>.+?) - Adobe Acrobat"),
+    // "mouse_tracking – src/main.rs", "mouse_tracking – main.rs [mouse_tracking]"
+    (
+        &["idea*", "pycharm*", "clion*", "rider*", "webstorm*", "goland*", "phpstorm*", "rustrover*", "datagrip*"],
+        r"^[^–]+ – (?P<document>.+?)(?: \[.+\])?$",
+    ),
+    // "notes.txt (~/Documents) - gedit", "notes.txt — Kate"
+    (&["gedit*", "gnome-text-editor*", "kate*", "mousepad*"], r"^\*?(?P<document>.+?)(?: \(.+\))? [-—] (?:gedit|Text Editor|Kate|Mousepad)$"),
+];
+
+/// Tab and page names editors show in the same place as file names.
+const NOT_DOCUMENTS: &[&str] = &["Welcome", "Settings", "Keyboard Shortcuts", "Release Notes", "Get Started", "Extensions", "Start Page"];
+
+#[derive(Debug, Clone)]
+struct DocumentPattern {
+    apps: Vec<String>,
+    pattern: Regex,
+}
+
+/// Sets `activity.document` from the first pattern whose app matches and whose regex matches a title.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentExtractor {
+    patterns: Vec<DocumentPattern>,
+}
+
+impl DocumentExtractor {
+    pub fn parse(config: &DocumentDetectionConfig) -> Result<Self, String> {
+        let mut patterns = Vec::with_capacity(config.patterns.len() + BUILTIN_PATTERNS.len());
+        for (index, entry) in config.patterns.iter().enumerate() {
+            let context = |message: String| format!("document_detection.patterns[{}]: {}", index, message);
+            if entry.app.trim().is_empty() || entry.pattern.is_empty() {
+                return Err(context("\"app\" and \"pattern\" are required".to_string()));
+            }
+            let pattern = Regex::new(&entry.pattern).map_err(|e| context(format!("invalid pattern: {}", e)))?;
+            patterns.push(DocumentPattern { apps: vec![entry.app.clone()], pattern });
+        }
+        if config.builtin {
+            patterns.extend(BUILTIN_PATTERNS.iter().map(|(apps, pattern)| DocumentPattern {
+                apps: apps.iter().map(|app| app.to_string()).collect(),
+                pattern: Regex::new(pattern).expect("valid built-in document pattern"),
+            }));
+        }
+        Ok(DocumentExtractor { patterns })
+    }
+
+    /// Leaves a document reported by the detector (e.g. a plugin) untouched.
+    pub fn tag(&self, activity: &mut ActivityInfo) {
+        if activity.document.is_some() {
+            return;
+        }
+        activity.document = self
+            .patterns
+            .iter()
+            .filter(|entry| entry.apps.iter().any(|glob| glob_match(glob, &activity.app_name)))
+            .find_map(|entry| find(&entry.pattern, &activity.main_title).or_else(|| find(&entry.pattern, &activity.detailed_title)));
+    }
+}
+
+fn find(pattern: &Regex, title: &str) -> Option<String> {
+    let captures = pattern.captures(title)?;
+    let document = captures.name("document").or_else(|| captures.get(1)).or_else(|| captures.get(0))?.as_str().trim();
+    (!document.is_empty() && !NOT_DOCUMENTS.iter().any(|name| name.eq_ignore_ascii_case(document))).then(|| document.to_string())
+}
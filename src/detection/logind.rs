@@ -0,0 +1,77 @@
+// src/detection/logind.rs
+#![cfg(target_os = "linux")]
+
+use super::SuspendState;
+use crate::errors::{AppResult, DetectionError};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Once;
+
+static SYSTEM_SUSPENDED: AtomicBool = AtomicBool::new(false);
+// Unix time of the last PrepareForSleep(true) (0 = none yet)
+static LAST_SUSPEND: AtomicI64 = AtomicI64::new(0);
+static SLEEP_MONITOR: Once = Once::new();
+
+/// Reads logind's `LockedHint` for the current session via `loginctl`.
+/// Desktop environments set the hint when the screen locker engages.
+pub fn is_session_locked() -> AppResult<bool> {
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "self".to_string());
+    let output = Command::new("loginctl")
+        .args(["show-session", &session, "--property=LockedHint", "--value"])
+        .output()
+        .map_err(|source| DetectionError::Command { program: "loginctl", source })?;
+    if !output.status.success() {
+        let detail = format!("show-session: {}", String::from_utf8_lossy(&output.stderr).trim());
+        return Err(DetectionError::CommandFailed { program: "loginctl", detail }.into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "yes")
+}
+
+/// Suspend/resume from logind's `PrepareForSleep` signal. The first call starts listening
+/// in a background thread (`gdbus monitor` on the system bus).
+pub fn suspend_state() -> SuspendState {
+    SLEEP_MONITOR.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("sleep-monitor".to_string())
+            .spawn(|| {
+                if let Err(e) = run_sleep_monitor() {
+                    log::warn!("Suspend/resume detection unavailable: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to spawn sleep monitor thread: {}", e);
+        }
+    });
+    let last_suspend = LAST_SUSPEND.load(Ordering::SeqCst);
+    SuspendState {
+        last_suspend: (last_suspend > 0).then_some(last_suspend),
+        suspended: SYSTEM_SUSPENDED.load(Ordering::SeqCst),
+    }
+}
+
+fn run_sleep_monitor() -> AppResult<()> {
+    let mut child = Command::new("gdbus")
+        .args(["monitor", "--system", "--dest", "org.freedesktop.login1", "--object-path", "/org/freedesktop/login1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|source| DetectionError::Command { program: "gdbus", source })?;
+    let stdout = child.stdout.take().ok_or_else(|| DetectionError::CommandFailed { program: "gdbus", detail: "monitor has no stdout".to_string() })?;
+    log::debug!("Listening for logind PrepareForSleep signals.");
+    for line in BufReader::new(stdout).lines() {
+        let line = line.map_err(|source| DetectionError::Command { program: "gdbus", source })?;
+        // e.g. "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)"
+        let Some((_, args)) = line.split_once(".PrepareForSleep (") else { continue };
+        if args.starts_with("true") {
+            log::debug!("System is suspending.");
+            LAST_SUSPEND.store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+            SYSTEM_SUSPENDED.store(true, Ordering::SeqCst);
+        } else {
+            log::debug!("System resumed.");
+            SYSTEM_SUSPENDED.store(false, Ordering::SeqCst);
+        }
+    }
+    let status = child.wait().map_err(|source| DetectionError::Command { program: "gdbus", source })?;
+    Err(DetectionError::CommandFailed { program: "gdbus", detail: format!("monitor exited ({})", status) }.into())
+}
@@ -0,0 +1,129 @@
+// src/detection/mock_detector.rs
+#![cfg(feature = "mock-detector")]
+
+use super::{ActivityDetector, ActivityInfo, SuspendState};
+use crate::errors::{AppError, AppResult};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// One step of a replay script: what the "user" does for `duration_secs`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptStep {
+    pub duration_secs: u64,
+    /// None = nothing detected (desktop, screen saver)
+    #[serde(default)]
+    pub activity: Option<ActivityInfo>,
+    /// Reported as the time since the last input during the step
+    #[serde(default)]
+    pub idle_secs: Option<u64>,
+    #[serde(default)]
+    pub locked: bool,
+    /// Apps playing audio during the step (`audio_tracking`)
+    #[serde(default)]
+    pub audio: Vec<String>,
+}
+
+/// Replays a scripted sequence of activities in real time instead of asking the OS
+/// (`track --detector mock --script FILE`, feature `mock-detector`). Runs the same on every
+/// platform, so the whole tracking -> aggregation -> stats pipeline can be exercised in CI.
+/// After the last step nothing is detected any more.
+#[derive(Debug, Clone)]
+pub struct MockDetector {
+    steps: Vec<ScriptStep>,
+    started: Instant,
+}
+
+impl MockDetector {
+    pub fn new(steps: Vec<ScriptStep>) -> Self {
+        MockDetector { steps, started: Instant::now() }
+    }
+
+    /// Loads a script: a JSON array of `ScriptStep`s, or CSV (`.csv`) with the columns
+    /// `duration_secs,app_name,main_title,detailed_title` (empty app = nothing detected).
+    pub fn from_file(path: &Path) -> AppResult<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| AppError::Io { path: path.to_path_buf(), source: e })?;
+        let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let steps = if is_csv {
+            parse_csv(&contents)
+        } else {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        }
+        .map_err(|e| AppError::Config(format!("Invalid mock detector script {:?}: {}", path, e)))?;
+        if steps.is_empty() {
+            return Err(AppError::Config(format!("Mock detector script {:?} has no steps", path)));
+        }
+        log::info!("Replaying {} scripted step(s) from {:?}.", steps.len(), path);
+        Ok(Self::new(steps))
+    }
+
+    /// The step the script is at now (None once it has ended).
+    fn current_step(&self) -> Option<&ScriptStep> {
+        let mut elapsed = self.started.elapsed();
+        for step in &self.steps {
+            let duration = Duration::from_secs(step.duration_secs);
+            if elapsed < duration {
+                return Some(step);
+            }
+            elapsed -= duration;
+        }
+        None
+    }
+}
+
+/// CSV rows are split on the first three commas, so the detailed title may contain commas.
+fn parse_csv(contents: &str) -> Result<Vec<ScriptStep>, String> {
+    let mut steps = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (index == 0 && line.starts_with("duration_secs")) {
+            continue;
+        }
+        let mut fields = line.splitn(4, ',').map(str::trim);
+        let duration_secs = fields
+            .next()
+            .unwrap_or_default()
+            .parse()
+            .map_err(|e| format!("line {}: duration_secs: {}", index + 1, e))?;
+        let app_name = fields.next().unwrap_or_default();
+        let activity = (!app_name.is_empty()).then(|| ActivityInfo {
+            app_name: app_name.to_string(),
+            main_title: fields.next().unwrap_or_default().to_string(),
+            detailed_title: fields.next().unwrap_or_default().to_string(),
+            virtual_desktop: None,
+            repo: None,
+            executable_path: None,
+            command_line: None,
+            ticket: None,
+            session_type: None,
+            remote_host: None,
+            document: None,
+            passive: false,
+        });
+        steps.push(ScriptStep { duration_secs, activity, idle_secs: None, locked: false, audio: Vec::new() });
+    }
+    Ok(steps)
+}
+
+impl ActivityDetector for MockDetector {
+    fn get_current_activity(&self) -> AppResult<Option<ActivityInfo>> {
+        Ok(self.current_step().and_then(|step| step.activity.clone()))
+    }
+
+    fn idle_time(&self) -> AppResult<Option<Duration>> {
+        Ok(self.current_step().and_then(|step| step.idle_secs).map(Duration::from_secs))
+    }
+
+    fn is_session_locked(&self) -> AppResult<bool> {
+        Ok(self.current_step().is_some_and(|step| step.locked))
+    }
+
+    // Scripts are independent of the machine running them
+    fn suspend_state(&self) -> AppResult<SuspendState> {
+        Ok(SuspendState::default())
+    }
+
+    fn audio_apps(&self) -> AppResult<Option<Vec<String>>> {
+        Ok(Some(self.current_step().map(|step| step.audio.clone()).unwrap_or_default()))
+    }
+}
@@ -1,41 +1,386 @@
 // src/detection/mod.rs
-use crate::errors::AppResult; // Or define a more specific DetectionError
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult}; // Or define a more specific DetectionError
+use crate::types::WindowInfo;
+use serde::Deserialize;
+use std::time::Duration;
 #[cfg(target_os = "windows")] // Optional: Only compile the file if targeting windows
 mod windows_detector;
+#[cfg(target_os = "linux")]
+mod logind;
+#[cfg(target_os = "linux")]
+mod pulse;
+pub mod document;
+pub mod passive;
+pub mod remote;
+pub mod repo;
+pub mod ticket;
+pub mod title_heuristics;
+mod command_detector;
+mod mock_detector;
+pub mod shell_hook;
+
+pub use command_detector::CommandDetector;
+pub use shell_hook::ShellHookDetector;
+#[cfg(feature = "mock-detector")]
+pub use mock_detector::{MockDetector, ScriptStep};
+
+/// Pseudo-app name recorded while the session/screen is locked
+pub const LOCKED_APP_NAME: &str = "[Locked]";
+/// Pseudo-app name of time marked as a break with `audit`
+pub const BREAK_APP_NAME: &str = "[Break]";
+
+/// Sleep as reported by the OS (see `ActivityDetector::suspend_state`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuspendState {
+    /// Unix time the system last started to suspend (None = not since the tracker started)
+    pub last_suspend: Option<i64>,
+    /// Between a suspend notification and the matching resume
+    pub suspended: bool,
+}
 // Define the data structure the detector should return
-#[derive(Debug, Clone, PartialEq, Eq)]
+// Deserialize: detector plugins print it as JSON (only app_name is required)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct ActivityInfo {
    pub app_name: String,
+   #[serde(default)]
    pub main_title: String,
+   #[serde(default)]
    pub detailed_title: String,
+   // Virtual desktop the window belongs to, if the platform has them
+   #[serde(default)]
+   pub virtual_desktop: Option<String>,
+   // Git repository the activity is in (plugins may report it; otherwise see `repo::RepoResolver`)
+   #[serde(default)]
+   pub repo: Option<String>,
+   // Full path of the app's executable; kept only with `record_executable_path`
+   #[serde(default)]
+   pub executable_path: Option<String>,
+   // Command line of the app's process; kept only with `record_command_line`
+   #[serde(default)]
+   pub command_line: Option<String>,
+   // Issue key in the window titles, e.g. PROJ-123 (plugins may report it; otherwise see `ticket::TicketExtractor`)
+   #[serde(default)]
+   pub ticket: Option<String>,
+   // Remote desktop involved, e.g. "rdp" or "rdp-client" (plugins may report it; otherwise see `remote::RemoteSessionTagger`)
+   #[serde(default)]
+   pub session_type: Option<String>,
+   // Machine a remote desktop client window is connected to
+   #[serde(default)]
+   pub remote_host: Option<String>,
+   // File open in an editor or office app (plugins may report it; otherwise see `document::DocumentExtractor`)
+   #[serde(default)]
+   pub document: Option<String>,
+   // Full-screen media/meeting time (see `passive::PassiveHeuristic`)
+   #[serde(skip)]
+   pub passive: bool,
 }
 
 // Define the trait
-pub trait ActivityDetector {
+// Send: detectors are polled from the dedicated detection thread
+pub trait ActivityDetector: Send {
     // Returns Ok(None) if no relevant activity detected (e.g., desktop, screen saver)
     // Returns Ok(Some(ActivityInfo)) if an app/window is detected
     // Returns Err on platform API errors
     fn get_current_activity(&self) -> AppResult<Option<ActivityInfo>>;
+
+    // Time since the last keyboard/mouse input, if the platform can report it
+    fn idle_time(&self) -> AppResult<Option<Duration>> {
+        Ok(None)
+    }
+
+    // Whether the user session is locked; locked time is recorded as LOCKED_APP_NAME.
+    // On Linux the default asks logind, so every detector gets it.
+    fn is_session_locked(&self) -> AppResult<bool> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                logind::is_session_locked()
+            } else {
+                Ok(false)
+            }
+        }
+    }
+
+    // Suspend/resume notifications; the tracker ends the open interval when the system sleeps.
+    // On Linux the default listens to logind, so every detector gets it.
+    fn suspend_state(&self) -> AppResult<SuspendState> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                Ok(logind::suspend_state())
+            } else {
+                Ok(SuspendState::default())
+            }
+        }
+    }
+
+    // Executable name of the foreground window when it is full-screen
+    fn fullscreen_app(&self) -> AppResult<Option<String>> {
+        Ok(None)
+    }
+
+    // All visible top-level windows (for context snapshots); empty if unsupported
+    fn list_windows(&self) -> AppResult<Vec<WindowInfo>> {
+        Ok(Vec::new())
+    }
+
+    // Executable names of the apps playing audio (`audio_tracking`); None if the platform can't tell.
+    // On Linux the default asks the PulseAudio/PipeWire server, so every detector gets it.
+    fn audio_apps(&self) -> AppResult<Option<Vec<String>>> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                pulse::playing_apps()
+            } else {
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl ActivityInfo {
+    /// The pseudo-activity recorded while the session is locked
+    pub fn locked() -> Self {
+        ActivityInfo {
+            app_name: LOCKED_APP_NAME.to_string(),
+            main_title: String::new(),
+            detailed_title: String::new(),
+            virtual_desktop: None,
+            repo: None,
+            executable_path: None,
+            command_line: None,
+            ticket: None,
+            session_type: None,
+            remote_host: None,
+            document: None,
+            passive: false,
+        }
+    }
+}
+
+/// Name of the built-in platform detector in `detector_order`
+pub const OS_DETECTOR_NAME: &str = "os";
+
+/// How a source's activity is combined with the others in a `CompositeDetector`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMode {
+    /// Replaces the whole activity (the highest-priority reporting source wins)
+    #[default]
+    Override,
+    /// Only refines titles/desktop of an activity from the same app; never changes the app name
+    Refine,
+}
+
+struct DetectorSource {
+    name: String,
+    detector: Box<dyn ActivityDetector>,
+    mode: MergeMode,
+}
+
+/// Merges several detectors (OS, plugins, ...) in priority order. The highest-priority
+/// `Override` source reporting an activity provides it; `Refine` sources reporting the
+/// same app then overwrite its titles, higher priority last so it wins.
+/// Platform queries (idle time, lock state, ...) go to the platform source.
+pub struct CompositeDetector {
+    // Highest priority first
+    sources: Vec<DetectorSource>,
+    platform_index: Option<usize>,
+}
+
+impl CompositeDetector {
+    pub fn new() -> Self {
+        CompositeDetector { sources: Vec::new(), platform_index: None }
+    }
+
+    /// Adds a source below all previously added ones.
+    pub fn add_source(&mut self, name: &str, detector: Box<dyn ActivityDetector>, mode: MergeMode) {
+        self.sources.push(DetectorSource { name: name.to_string(), detector, mode });
+    }
+
+    /// Adds the source that answers platform queries (added with `Override` mode).
+    pub fn add_platform_source(&mut self, name: &str, detector: Box<dyn ActivityDetector>) {
+        self.platform_index = Some(self.sources.len());
+        self.add_source(name, detector, MergeMode::Override);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn source_names(&self) -> Vec<&str> {
+        self.sources.iter().map(|source| source.name.as_str()).collect()
+    }
+
+    fn platform(&self) -> Option<&dyn ActivityDetector> {
+        self.platform_index
+            .or((!self.sources.is_empty()).then_some(0))
+            .map(|index| self.sources[index].detector.as_ref())
+    }
+}
+
+impl Default for CompositeDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn refine_activity(base: &mut ActivityInfo, refinement: ActivityInfo) {
+    if !refinement.main_title.is_empty() {
+        base.main_title = refinement.main_title;
+    }
+    if !refinement.detailed_title.is_empty() {
+        base.detailed_title = refinement.detailed_title;
+    }
+    if refinement.virtual_desktop.is_some() {
+        base.virtual_desktop = refinement.virtual_desktop;
+    }
+    if refinement.repo.is_some() {
+        base.repo = refinement.repo;
+    }
+    if refinement.executable_path.is_some() {
+        base.executable_path = refinement.executable_path;
+    }
+    if refinement.command_line.is_some() {
+        base.command_line = refinement.command_line;
+    }
+    if refinement.ticket.is_some() {
+        base.ticket = refinement.ticket;
+    }
+    if refinement.session_type.is_some() {
+        base.session_type = refinement.session_type;
+        base.remote_host = refinement.remote_host;
+    }
+    if refinement.document.is_some() {
+        base.document = refinement.document;
+    }
+}
+
+impl ActivityDetector for CompositeDetector {
+    fn get_current_activity(&self) -> AppResult<Option<ActivityInfo>> {
+        let mut base: Option<ActivityInfo> = None;
+        let mut refinements: Vec<ActivityInfo> = Vec::new();
+        let mut last_error = None;
+        let mut any_succeeded = false;
+        for source in &self.sources {
+            // Lower-priority overrides are not needed once one has reported
+            if base.is_some() && source.mode == MergeMode::Override {
+                continue;
+            }
+            match source.detector.get_current_activity() {
+                Ok(Some(info)) => {
+                    any_succeeded = true;
+                    match source.mode {
+                        MergeMode::Override => base = Some(info),
+                        MergeMode::Refine => refinements.push(info),
+                    }
+                }
+                Ok(None) => any_succeeded = true,
+                // One broken source should not stop the others
+                Err(e) => {
+                    log::warn!("Detector '{}' failed: {}", source.name, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        let Some(mut activity) = base else {
+            // Only surface an error when every source failed
+            return match last_error {
+                Some(e) if !any_succeeded => Err(e),
+                _ => Ok(None),
+            };
+        };
+        // Apply lowest priority first so higher-priority refinements win
+        for refinement in refinements.into_iter().rev() {
+            if refinement.app_name.eq_ignore_ascii_case(&activity.app_name) {
+                refine_activity(&mut activity, refinement);
+            }
+        }
+        Ok(Some(activity))
+    }
+
+    fn idle_time(&self) -> AppResult<Option<Duration>> {
+        self.platform().map_or(Ok(None), |detector| detector.idle_time())
+    }
+
+    fn is_session_locked(&self) -> AppResult<bool> {
+        self.platform().map_or(Ok(false), |detector| detector.is_session_locked())
+    }
+
+    fn suspend_state(&self) -> AppResult<SuspendState> {
+        self.platform().map_or(Ok(SuspendState::default()), |detector| detector.suspend_state())
+    }
+
+    fn fullscreen_app(&self) -> AppResult<Option<String>> {
+        self.platform().map_or(Ok(None), |detector| detector.fullscreen_app())
+    }
+
+    fn list_windows(&self) -> AppResult<Vec<WindowInfo>> {
+        self.platform().map_or(Ok(Vec::new()), |detector| detector.list_windows())
+    }
+
+    fn audio_apps(&self) -> AppResult<Option<Vec<String>>> {
+        self.platform().map_or(Ok(None), |detector| detector.audio_apps())
+    }
+}
+
+/// Creates the detector used by the tracker: the OS detector, combined with any
+/// detector plugins declared in the config file according to `detector_order`.
+pub fn create_detector(config: &AppConfig) -> AppResult<Box<dyn ActivityDetector>> {
+    if config.detector_plugins.is_empty() {
+        return create_os_detector(config);
+    }
+    let mut os_detector = match create_os_detector(config) {
+        Ok(detector) => Some(detector),
+        Err(e) => {
+            // Plugins alone are enough on platforms without a built-in detector
+            log::warn!("No OS detector, using plugins only: {}", e);
+            None
+        }
+    };
+
+    // Default priority: plugins in declared order, then the OS detector
+    let order: Vec<String> = config.detector_order.clone().unwrap_or_else(|| {
+        config.detector_plugins.iter().map(|p| p.name.clone())
+            .chain(std::iter::once(OS_DETECTOR_NAME.to_string()))
+            .collect()
+    });
+    let mut composite = CompositeDetector::new();
+    for name in &order {
+        if name == OS_DETECTOR_NAME {
+            if let Some(detector) = os_detector.take() {
+                composite.add_platform_source(OS_DETECTOR_NAME, detector);
+            }
+        } else if let Some(plugin) = config.detector_plugins.iter().find(|p| &p.name == name) {
+            composite.add_source(&plugin.name, Box::new(CommandDetector::new(plugin)), plugin.merge);
+        } else {
+            log::warn!("detector_order names unknown detector '{}'; ignoring it.", name);
+        }
+    }
+    if composite.is_empty() {
+        return Err(AppError::Config("No usable detectors: check detector_order and detector_plugins.".to_string()));
+    }
+    log::info!("Using detectors (highest priority first): {}", composite.source_names().join(", "));
+    Ok(Box::new(composite))
 }
 
-// Factory function to create the appropriate detector
-pub fn create_detector() -> AppResult<Box<dyn ActivityDetector>> {
-    cfg_if::cfg_if! {
+// Factory function to create the appropriate platform detector
+// The config only matters to the Windows detector so far
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+fn create_os_detector(config: &AppConfig) -> AppResult<Box<dyn ActivityDetector>> {    cfg_if::cfg_if! {
         if #[cfg(target_os = "windows")] {
             // Conditionally compile the windows module import
-            Ok(Box::new(windows_detector::WindowsDetector::new()?))
+            Ok(Box::new(windows_detector::WindowsDetector::new(config)?))
         } else if #[cfg(target_os = "macos")] {
              // Placeholder for macOS
              // mod macos_detector;
              // Ok(Box::new(macos_detector::MacosDetector::new()?))
-             Err(crate::errors::AppError::Platform("macOS detection not yet implemented".to_string()))
+             Err(crate::errors::DetectionError::Unsupported("macOS detection not yet implemented").into())
         } else if #[cfg(target_os = "linux")] {
              // Placeholder for Linux
              // mod linux_detector;
              // Ok(Box::new(linux_detector::LinuxDetector::new()?))
-             Err(crate::errors::AppError::Platform("Linux detection not yet implemented".to_string()))
+             Err(crate::errors::DetectionError::Unsupported("Linux detection not yet implemented").into())
         } else {
-            Err(crate::errors::AppError::Platform("Unsupported platform for activity detection".to_string()))
+            Err(crate::errors::DetectionError::Unsupported("Unsupported platform for activity detection").into())
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+// src/detection/passive.rs
+
+use super::{ActivityDetector, ActivityInfo};
+
+/// Media players and meeting apps whose full-screen time counts as passive by default.
+pub const DEFAULT_PASSIVE_APPS: &[&str] = &[
+    "vlc.exe",
+    "mpv.exe",
+    "mpc-hc64.exe",
+    "mpc-hc.exe",
+    "wmplayer.exe",
+    "Video.UI.exe",
+    "PotPlayerMini64.exe",
+    "Zoom.exe",
+    "Teams.exe",
+    "ms-teams.exe",
+    "webexmta.exe",
+    "CiscoCollabHost.exe",
+];
+
+/// Flags activity as passive when the tracked app is a known media/meeting app
+/// running full-screen in the foreground (watching, not working).
+#[derive(Debug, Clone)]
+pub struct PassiveHeuristic {
+    apps: Vec<String>,
+}
+
+impl PassiveHeuristic {
+    pub fn new(apps: &[String]) -> Self {
+        PassiveHeuristic { apps: apps.iter().map(|app| app.to_lowercase()).collect() }
+    }
+
+    fn is_passive_app(&self, app_name: &str) -> bool {
+        let app_name = app_name.to_lowercase();
+        self.apps.contains(&app_name)
+    }
+
+    /// Sets `activity.passive`. Only the app under the cursor can be passive, so moving
+    /// the cursor to another monitor to work ends the passive interval.
+    pub fn tag(&self, detector: &dyn ActivityDetector, activity: &mut ActivityInfo) {
+        if !self.is_passive_app(&activity.app_name) {
+            activity.passive = false;
+            return;
+        }
+        activity.passive = match detector.fullscreen_app() {
+            Ok(Some(fullscreen_app)) => fullscreen_app.eq_ignore_ascii_case(&activity.app_name),
+            Ok(None) => false,
+            Err(e) => {
+                log::debug!("Full-screen check failed: {}", e);
+                false
+            }
+        };
+    }
+}
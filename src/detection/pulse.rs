@@ -0,0 +1,46 @@
+// src/detection/pulse.rs
+#![cfg(target_os = "linux")]
+
+use crate::errors::{AppResult, DetectionError};
+use std::io::ErrorKind;
+use std::process::Command;
+
+/// Apps with a playing (not corked or muted) stream on the PulseAudio/PipeWire server, from
+/// `pactl list sink-inputs`. None when `pactl` is not installed.
+pub fn playing_apps() -> AppResult<Option<Vec<String>>> {
+    // The field names are translated in other locales
+    let output = match Command::new("pactl").args(["list", "sink-inputs"]).env("LC_ALL", "C").output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(DetectionError::Command { program: "pactl", source }.into()),
+    };
+    if !output.status.success() {
+        let detail = format!("list sink-inputs: {}", String::from_utf8_lossy(&output.stderr).trim());
+        return Err(DetectionError::CommandFailed { program: "pactl", detail }.into());
+    }
+    Ok(Some(parse_sink_inputs(&String::from_utf8_lossy(&output.stdout))))
+}
+
+/// One name per app: the process binary ("spotify"), else the name the client gave ("Firefox").
+fn parse_sink_inputs(text: &str) -> Vec<String> {
+    let mut apps: Vec<String> = Vec::new();
+    for block in text.split("Sink Input #").skip(1) {
+        let mut silent = false;
+        let (mut binary, mut name) = (None, None);
+        for line in block.lines().map(str::trim) {
+            if line == "Corked: yes" || line == "Mute: yes" {
+                silent = true;
+            } else if let Some(value) = line.strip_prefix("application.process.binary = ") {
+                binary = Some(value.trim_matches('"'));
+            } else if let Some(value) = line.strip_prefix("application.name = ") {
+                name = Some(value.trim_matches('"'));
+            }
+        }
+        if let Some(app) = binary.or(name).filter(|app| !silent && !app.is_empty())
+            && !apps.iter().any(|known| known == app)
+        {
+            apps.push(app.to_string());
+        }
+    }
+    apps
+}
@@ -0,0 +1,98 @@
+// src/detection/remote.rs
+// Remote desktop time. Two cases are told apart in `session_type`: the tracker itself runs inside a
+// remote session ("rdp", "citrix"), or the foreground window is a remote desktop client on this machine
+// ("rdp-client", "citrix-client", "vnc-client"), in which case the machine it shows is read from the
+// window title into `remote_host`. `stats --level remote` totals the time per session type and machine.
+
+use super::ActivityInfo;
+
+/// Client executables (compared case-insensitively, ".exe" optional) and the session type they record.
+const CLIENTS: &[(&str, &str)] = &[
+    ("mstsc", "rdp-client"),
+    ("msrdc", "rdp-client"),
+    ("Microsoft Remote Desktop", "rdp-client"),
+    ("Windows App", "rdp-client"),
+    ("xfreerdp", "rdp-client"),
+    ("wlfreerdp", "rdp-client"),
+    ("sdl-freerdp", "rdp-client"),
+    ("remmina", "rdp-client"),
+    ("CDViewer", "citrix-client"),
+    ("wfica32", "citrix-client"),
+    ("vncviewer", "vnc-client"),
+    ("tvnviewer", "vnc-client"),
+];
+
+/// Title parts that name the client, not the machine ("Remote Desktop Connection" alone, before connecting).
+const PRODUCT_NAMES: &[&str] = &[
+    "remote desktop connection",
+    "remote desktop",
+    "microsoft remote desktop",
+    "windows app",
+    "remmina",
+    "citrix workspace",
+    "citrix viewer",
+    "tightvnc viewer",
+    "vnc viewer",
+    "freerdp",
+];
+
+/// Sets `session_type` and `remote_host` of each activity.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteSessionTagger;
+
+impl RemoteSessionTagger {
+    pub fn new() -> Self {
+        RemoteSessionTagger
+    }
+
+    /// Leaves a session type reported by the detector (e.g. a plugin) untouched. A client window
+    /// wins over the tracker's own remote session: RDP inside RDP is recorded as the inner client.
+    pub fn tag(&self, activity: &mut ActivityInfo) {
+        if activity.session_type.is_some() {
+            return;
+        }
+        if let Some(session_type) = client_session_type(&activity.app_name) {
+            activity.session_type = Some(session_type.to_string());
+            activity.remote_host = remote_host(&activity.main_title).or_else(|| remote_host(&activity.detailed_title));
+        } else {
+            activity.session_type = own_session_type().map(str::to_string);
+        }
+    }
+}
+
+fn client_session_type(app_name: &str) -> Option<&'static str> {
+    let app = app_name.strip_suffix(".exe").or_else(|| app_name.strip_suffix(".EXE")).unwrap_or(app_name);
+    CLIENTS.iter().find(|(client, _)| client.eq_ignore_ascii_case(app)).map(|(_, session_type)| *session_type)
+}
+
+/// The machine in a client title: "SERVER01 - Remote Desktop Connection", "FreeRDP: 10.0.0.5",
+/// "build01.corp.local (1) - Remote Desktop Connection", "Desktop - Citrix Workspace".
+fn remote_host(title: &str) -> Option<String> {
+    let title = title.trim();
+    let title = title.strip_prefix("FreeRDP:").unwrap_or(title).trim();
+    let host = title.rsplit_once(" - ").map_or(title, |(host, _)| host).trim();
+    // Instance counters and status ("(1)", "(Disconnected)")
+    let host = match host.rfind(" (") {
+        Some(paren) if host.ends_with(')') => host[..paren].trim(),
+        _ => host,
+    };
+    let is_product = PRODUCT_NAMES.iter().any(|name| name.eq_ignore_ascii_case(host));
+    (!host.is_empty() && !is_product).then(|| host.to_string())
+}
+
+/// "rdp" or "citrix" while the tracker's session is shown through a remote desktop client.
+#[cfg(target_os = "windows")]
+fn own_session_type() -> Option<&'static str> {
+    if !crate::windows_api::is_remote_session() {
+        return None;
+    }
+    // Citrix names its sessions "ICA-TCP#0", RDP "RDP-Tcp#0"
+    let citrix = std::env::var("SESSIONNAME").is_ok_and(|name| name.to_ascii_uppercase().starts_with("ICA"));
+    Some(if citrix { "citrix" } else { "rdp" })
+}
+
+/// "rdp" inside an xrdp session.
+#[cfg(not(target_os = "windows"))]
+fn own_session_type() -> Option<&'static str> {
+    std::env::var_os("XRDP_SESSION").map(|_| "rdp")
+}
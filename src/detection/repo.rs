@@ -0,0 +1,98 @@
+// src/detection/repo.rs
+
+use super::ActivityInfo;
+use crate::config::RepoDetectionConfig;
+use crate::utils::glob_match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Titles remembered with their repository; the cache is cleared when it grows past this.
+const CACHE_LIMIT: usize = 2048;
+
+/// Sets `activity.repo` to the Git repository containing a path found in the window titles
+/// (an editor's file path, or the directory in a shell-integration title). Each title is
+/// resolved once, so the filesystem is only checked when a title changes.
+#[derive(Debug, Clone)]
+pub struct RepoResolver {
+    apps: Vec<String>,
+    cache: HashMap<String, Option<String>>,
+}
+
+impl RepoResolver {
+    pub fn new(config: &RepoDetectionConfig) -> Self {
+        RepoResolver { apps: config.apps.clone(), cache: HashMap::new() }
+    }
+
+    /// Leaves a repository reported by the detector (e.g. a plugin) untouched.
+    pub fn tag(&mut self, activity: &mut ActivityInfo) {
+        if activity.repo.is_some() {
+            return;
+        }
+        if !self.apps.is_empty() && !self.apps.iter().any(|glob| glob_match(glob, &activity.app_name)) {
+            return;
+        }
+        activity.repo = self.resolve(&activity.detailed_title).or_else(|| self.resolve(&activity.main_title));
+    }
+
+    fn resolve(&mut self, title: &str) -> Option<String> {
+        if title.is_empty() {
+            return None;
+        }
+        if let Some(repo) = self.cache.get(title) {
+            return repo.clone();
+        }
+        if self.cache.len() >= CACHE_LIMIT {
+            self.cache.clear();
+        }
+        let repo = repo_in_title(title);
+        self.cache.insert(title.to_string(), repo.clone());
+        repo
+    }
+}
+
+/// The repository of the first path in `title` that exists. Paths may contain spaces, so each
+/// start is tried with the longest candidate first ("~/my project/main.rs - Editor").
+fn repo_in_title(title: &str) -> Option<String> {
+    for start in path_starts(title) {
+        let rest = &title[start..];
+        let ends = rest.char_indices().filter(|(_, c)| c.is_whitespace()).map(|(index, _)| index).chain([rest.len()]);
+        let mut ends: Vec<usize> = ends.collect();
+        ends.reverse();
+        for end in ends {
+            let candidate = rest[..end].trim_end_matches([':', ',', ';', ')', ']', '"', '\'', '*']);
+            if let Some(repo) = expand_home(candidate).and_then(|path| repo_of(&path)) {
+                return Some(repo);
+            }
+        }
+    }
+    None
+}
+
+/// Byte offsets where an absolute path may begin: `/`, `~/` or `C:\` at the start of a word.
+fn path_starts(title: &str) -> Vec<usize> {
+    let bytes = title.as_bytes();
+    (0..bytes.len())
+        .filter(|&index| index == 0 || matches!(bytes[index - 1], b' ' | b'\t' | b'(' | b'[' | b'"' | b'\''))
+        .filter(|&index| {
+            let rest = &bytes[index..];
+            rest.starts_with(b"/")
+                || rest.starts_with(b"~/")
+                || (rest.len() >= 3 && rest[0].is_ascii_alphabetic() && rest[1] == b':' && matches!(rest[2], b'\\' | b'/'))
+        })
+        .collect()
+}
+
+fn expand_home(candidate: &str) -> Option<PathBuf> {
+    match candidate.strip_prefix("~/") {
+        Some(relative) => dirs::home_dir().map(|home| home.join(relative)),
+        None => Some(PathBuf::from(candidate)),
+    }
+}
+
+/// Name of the directory holding `.git` above `path` (a file, possibly unsaved, or a directory).
+fn repo_of(path: &Path) -> Option<String> {
+    let parent = path.parent()?;
+    let dir = if path.is_dir() { path } else { Some(parent).filter(|parent| parent.is_dir())? };
+    let root = dir.ancestors().find(|dir| dir.join(".git").exists())?;
+    root.file_name().map(|name| name.to_string_lossy().into_owned())
+}
@@ -0,0 +1,181 @@
+// src/detection/shell_hook.rs
+// Shell integration: shells set up with `shell-hook` send their command and directory to the tracker
+// (`shell-report`, one UDP datagram on 127.0.0.1), and terminal windows are titled with them.
+
+use super::{ActivityDetector, ActivityInfo, SuspendState};
+use crate::config::ShellHookConfig;
+use crate::errors::{AppError, AppResult};
+use crate::types::WindowInfo;
+use crate::utils::glob_match;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::path::Path;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+/// How often the listener checks whether the tracker has stopped.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Longest command kept from a report.
+const MAX_COMMAND_CHARS: usize = 200;
+
+/// One report of a shell: at its prompt (`command` None), running a command, or exiting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellReport {
+    pub pid: u32,
+    #[serde(default)]
+    pub cwd: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub exit: bool,
+}
+
+impl ShellReport {
+    /// Keeps only the program name unless `record_arguments` is on (`FOO=1 cargo test` -> `cargo`).
+    pub fn redacted(mut self, config: &ShellHookConfig) -> Self {
+        self.command = self.command.map(|command| command.trim().to_string()).filter(|command| !command.is_empty()).map(|command| {
+            if config.record_arguments {
+                command.chars().take(MAX_COMMAND_CHARS).collect()
+            } else {
+                let program = command.split_whitespace().find(|word| !word.contains('=')).unwrap_or_default();
+                Path::new(program).file_name().map_or(program.to_string(), |name| name.to_string_lossy().into_owned())
+            }
+        });
+        self
+    }
+}
+
+/// Sends a report to the tracker's listener; nothing happens when no tracker is running.
+pub fn send_report(config: &ShellHookConfig, report: &ShellReport) -> AppResult<()> {
+    let payload = serde_json::to_vec(report).map_err(|e| AppError::Unexpected(e.to_string()))?;
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).map_err(|e| AppError::Unexpected(format!("Cannot open a UDP socket: {}", e)))?;
+    socket
+        .send_to(&payload, (Ipv4Addr::LOCALHOST, config.port))
+        .map_err(|e| AppError::Unexpected(format!("Cannot send the shell report: {}", e)))?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct ShellSession {
+    cwd: String,
+    // Git repository or directory name of `cwd`
+    project: String,
+    command: Option<String>,
+    // Order of the last report; the most recent shell is the one the user is typing in
+    sequence: u64,
+}
+
+#[derive(Debug, Default)]
+struct ShellSessions {
+    by_pid: HashMap<u32, ShellSession>,
+    next_sequence: u64,
+}
+
+impl ShellSessions {
+    fn apply(&mut self, report: ShellReport) {
+        if report.exit {
+            self.by_pid.remove(&report.pid);
+            return;
+        }
+        self.next_sequence += 1;
+        let project = project_name(&report.cwd);
+        self.by_pid.insert(report.pid, ShellSession { cwd: report.cwd, project, command: report.command, sequence: self.next_sequence });
+    }
+
+    fn latest(&self) -> Option<&ShellSession> {
+        self.by_pid.values().max_by_key(|session| session.sequence)
+    }
+}
+
+/// Wraps the tracker's detector: while a terminal window is detected, its titles become the project
+/// (the git repository or directory name) and the command of the shell that reported last.
+pub struct ShellHookDetector {
+    inner: Box<dyn ActivityDetector>,
+    terminal_apps: Vec<String>,
+    sessions: Arc<Mutex<ShellSessions>>,
+}
+
+impl ShellHookDetector {
+    /// Starts the listener on `127.0.0.1:<port>`. When the port is taken, terminals keep their window titles.
+    pub fn wrap(inner: Box<dyn ActivityDetector>, config: &ShellHookConfig) -> Self {
+        let sessions = Arc::new(Mutex::new(ShellSessions::default()));
+        if let Err(e) = spawn_listener(config.port, Arc::downgrade(&sessions)) {
+            log::warn!("Shell hook listener not started: {}", e);
+        }
+        ShellHookDetector { inner, terminal_apps: config.terminal_apps.clone(), sessions }
+    }
+}
+
+/// Receives reports until the detector is dropped.
+fn spawn_listener(port: u16, sessions: Weak<Mutex<ShellSessions>>) -> Result<(), String> {
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, port)).map_err(|e| format!("cannot listen on 127.0.0.1:{}: {}", port, e))?;
+    socket.set_read_timeout(Some(RECEIVE_TIMEOUT)).map_err(|e| e.to_string())?;
+    thread::Builder::new()
+        .name("shell-hook".to_string())
+        .spawn(move || {
+            let mut buffer = [0u8; 8192];
+            loop {
+                let received = socket.recv(&mut buffer);
+                let Some(sessions) = sessions.upgrade() else { break };
+                let Ok(length) = received else { continue };
+                match serde_json::from_slice::<ShellReport>(&buffer[..length]) {
+                    Ok(report) => sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).apply(report),
+                    Err(e) => log::debug!("Ignoring malformed shell report: {}", e),
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    log::info!("Listening for shell reports on 127.0.0.1:{}.", port);
+    Ok(())
+}
+
+/// Name of the git repository containing `cwd`, else the directory's own name.
+fn project_name(cwd: &str) -> String {
+    let path = Path::new(cwd);
+    let root = path.ancestors().find(|dir| dir.join(".git").exists()).unwrap_or(path);
+    root.file_name().map_or(cwd.to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+impl ActivityDetector for ShellHookDetector {
+    fn get_current_activity(&self) -> AppResult<Option<ActivityInfo>> {
+        let Some(mut activity) = self.inner.get_current_activity()? else { return Ok(None) };
+        if !self.terminal_apps.iter().any(|glob| glob_match(glob, &activity.app_name)) {
+            return Ok(Some(activity));
+        }
+        let sessions = self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(session) = sessions.latest().filter(|session| !session.cwd.is_empty()) {
+            activity.main_title = session.project.clone();
+            activity.detailed_title = match &session.command {
+                Some(command) => format!("{} — {}", command, session.cwd),
+                None => session.cwd.clone(),
+            };
+        }
+        Ok(Some(activity))
+    }
+
+    fn idle_time(&self) -> AppResult<Option<Duration>> {
+        self.inner.idle_time()
+    }
+
+    fn is_session_locked(&self) -> AppResult<bool> {
+        self.inner.is_session_locked()
+    }
+
+    fn suspend_state(&self) -> AppResult<SuspendState> {
+        self.inner.suspend_state()
+    }
+
+    fn fullscreen_app(&self) -> AppResult<Option<String>> {
+        self.inner.fullscreen_app()
+    }
+
+    fn list_windows(&self) -> AppResult<Vec<WindowInfo>> {
+        self.inner.list_windows()
+    }
+
+    fn audio_apps(&self) -> AppResult<Option<Vec<String>>> {
+        self.inner.audio_apps()
+    }
+}
@@ -0,0 +1,66 @@
+// src/detection/ticket.rs
+
+use super::ActivityInfo;
+use crate::config::TicketDetectionConfig;
+use crate::utils::glob_match;
+use regex::Regex;
+
+/// Upper-case words shaped like a Jira key that are not one ("UTF-8", "SHA-256", "ISO-8601", ...);
+/// only used when `jira_projects` is empty.
+const NOT_PROJECTS: &[&str] = &["UTF", "SHA", "ISO", "RFC", "CVE", "COVID", "MD", "AES", "RSA", "HTTP", "TLS", "SSL", "IPV", "WIN", "GPT", "TOP"];
+
+/// Sets `activity.ticket` to the first issue key in the window titles: a Jira key ("PROJ-123",
+/// also inside branch names like "feature/PROJ-123-login") or a GitHub issue/pull request
+/// ("owner/repo#456", or the "Issue #456 · owner/repo" titles of github.com pages).
+#[derive(Debug, Clone)]
+pub struct TicketExtractor {
+    apps: Vec<String>,
+    jira: Regex,
+    /// Any key when `jira_projects` is empty (checked against `NOT_PROJECTS`)
+    any_project: bool,
+    github: Option<[Regex; 2]>,
+}
+
+impl TicketExtractor {
+    pub fn new(config: &TicketDetectionConfig) -> Self {
+        let projects = if config.jira_projects.is_empty() {
+            "[A-Z][A-Z0-9]{1,9}".to_string()
+        } else {
+            config.jira_projects.iter().map(|project| regex::escape(project)).collect::<Vec<_>>().join("|")
+        };
+        let jira = Regex::new(&format!(r"\b(?:{})-[1-9][0-9]{{0,6}}\b", projects)).expect("valid Jira key pattern");
+        let github = config.github.then(|| {
+            [
+                Regex::new(r"\b([\w.-]+/[\w.-]+)#([1-9][0-9]{0,6})\b").expect("valid GitHub reference pattern"),
+                Regex::new(r"\b(?:Issue|Pull Request|Discussion) #([1-9][0-9]{0,6}) · ([\w.-]+/[\w.-]+)").expect("valid GitHub title pattern"),
+            ]
+        });
+        TicketExtractor { apps: config.apps.clone(), jira, any_project: config.jira_projects.is_empty(), github }
+    }
+
+    /// Leaves a ticket reported by the detector (e.g. a plugin) untouched.
+    pub fn tag(&self, activity: &mut ActivityInfo) {
+        if activity.ticket.is_some() {
+            return;
+        }
+        if !self.apps.is_empty() && !self.apps.iter().any(|glob| glob_match(glob, &activity.app_name)) {
+            return;
+        }
+        activity.ticket = self.find(&activity.detailed_title).or_else(|| self.find(&activity.main_title));
+    }
+
+    /// The earliest issue key in `title`, as "PROJ-123" or "owner/repo#456".
+    fn find(&self, title: &str) -> Option<String> {
+        let jira = self
+            .jira
+            .find_iter(title)
+            .find(|key| !self.any_project || !key.as_str().split('-').next().is_some_and(|project| NOT_PROJECTS.contains(&project)))
+            .map(|key| (key.start(), key.as_str().to_string()));
+        let github = self.github.as_ref().and_then(|[reference, page]| {
+            let reference = reference.captures(title).map(|c| (c.get(0).map_or(0, |m| m.start()), format!("{}#{}", &c[1], &c[2])));
+            let page = page.captures(title).map(|c| (c.get(0).map_or(0, |m| m.start()), format!("{}#{}", &c[2], &c[1])));
+            reference.into_iter().chain(page).min_by_key(|(start, _)| *start)
+        });
+        jira.into_iter().chain(github).min_by_key(|(start, _)| *start).map(|(_, ticket)| ticket)
+    }
+}
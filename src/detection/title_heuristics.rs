@@ -0,0 +1,92 @@
+// src/detection/title_heuristics.rs
+// `title_heuristics` in config.json: how the detailed title is picked among the visible windows of the app
+// under the cursor. By default the longest title wins, which goes wrong for apps with many windows of their
+// own (Electron, JetBrains IDEs); a heuristic can prefer the foreground window or titles matching a pattern.
+
+use crate::utils::glob_match;
+use regex::Regex;
+use serde::Deserialize;
+
+/// One entry of `title_heuristics`; the first entry whose `app` matches is used.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TitleHeuristicConfig {
+    /// Glob on the app name (case-insensitive, e.g. "idea64.exe")
+    pub app: String,
+    /// Take the title of the app's foreground window when it has one
+    pub prefer_foreground: bool,
+    /// Regular expression; titles matching it are preferred over the others
+    pub prefer_pattern: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct TitleHeuristic {
+    app: String,
+    prefer_foreground: bool,
+    prefer_pattern: Option<Regex>,
+}
+
+/// The parsed heuristics.
+#[derive(Debug, Clone, Default)]
+pub struct TitleHeuristics {
+    heuristics: Vec<TitleHeuristic>,
+}
+
+/// Titles the detailed title is picked from, all from the process under the cursor.
+#[derive(Debug, Clone, Copy)]
+pub struct TitleCandidates<'a> {
+    /// Titles of its visible top-level windows, generic ones left out
+    pub windows: &'a [String],
+    /// Title of its window in the foreground, if the foreground window is one of them
+    pub foreground: Option<&'a str>,
+}
+
+impl TitleHeuristics {
+    pub fn parse(heuristics: &[TitleHeuristicConfig]) -> Result<Self, String> {
+        let mut parsed = Vec::with_capacity(heuristics.len());
+        for (index, heuristic) in heuristics.iter().enumerate() {
+            let context = |message: String| format!("title_heuristics[{}]: {}", index, message);
+            if heuristic.app.trim().is_empty() {
+                return Err(context("\"app\" is required".to_string()));
+            }
+            let prefer_pattern = heuristic
+                .prefer_pattern
+                .as_deref()
+                .filter(|pattern| !pattern.is_empty())
+                .map(|pattern| Regex::new(pattern).map_err(|e| context(format!("invalid prefer_pattern: {}", e))))
+                .transpose()?;
+            if !heuristic.prefer_foreground && prefer_pattern.is_none() {
+                return Err(context("set \"prefer_foreground\" and/or \"prefer_pattern\"".to_string()));
+            }
+            parsed.push(TitleHeuristic { app: heuristic.app.clone(), prefer_foreground: heuristic.prefer_foreground, prefer_pattern });
+        }
+        Ok(TitleHeuristics { heuristics: parsed })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heuristics.is_empty()
+    }
+
+    /// The window title to use as detailed title of `app_name`, None if there is no candidate.
+    /// Without a matching heuristic this is the longest title.
+    pub fn select(&self, app_name: &str, candidates: TitleCandidates<'_>) -> Option<String> {
+        let Some(heuristic) = self.heuristics.iter().find(|heuristic| glob_match(&heuristic.app, app_name)) else {
+            return longest(candidates.windows.iter());
+        };
+        let preferred = |title: &str| heuristic.prefer_pattern.as_ref().is_none_or(|pattern| pattern.is_match(title));
+        let foreground = candidates.foreground.filter(|_| heuristic.prefer_foreground);
+        // Pattern first, then the foreground window, then length; titles not matching the pattern only
+        // when none does
+        if let Some(title) = foreground.filter(|title| preferred(title)) {
+            return Some(title.to_string());
+        }
+        if let Some(title) = longest(candidates.windows.iter().filter(|title| preferred(title))) {
+            return Some(title);
+        }
+        foreground.map(str::to_string).or_else(|| longest(candidates.windows.iter()))
+    }
+}
+
+fn longest<'a>(titles: impl Iterator<Item = &'a String>) -> Option<String> {
+    titles.max_by_key(|title| title.len()).cloned()
+}
@@ -1,29 +1,86 @@
 // src/detection/windows_detector.rs
 #![cfg(target_os = "windows")] // Only compile this file on Windows
 
-use super::{ActivityDetector, ActivityInfo}; // Use trait/struct from parent mod
+use super::title_heuristics::TitleHeuristics;
+use super::{ActivityDetector, ActivityInfo, SuspendState}; // Use trait/struct from parent mod
+use crate::config::AppConfig;
 use crate::errors::AppResult;
+use crate::types::WindowInfo;
 use crate::windows_api; // Use the existing windows_api module
+use std::time::Duration;
 
-pub struct WindowsDetector; // Simple struct, might hold state later if needed
+pub struct WindowsDetector {
+    // Picks the detailed title among the windows of the app under the cursor
+    title_heuristics: TitleHeuristics,
+    // `record_command_line`: read through WMI, so only queried when it is kept
+    command_line: bool,
+    // `web_app_detection`: installed web apps reported under their own name
+    web_apps: bool,
+}
 
 impl WindowsDetector {
-    pub fn new() -> AppResult<Self> {
-        // Add any Windows-specific initialization if required
-        Ok(Self)
+    pub fn new(config: &AppConfig) -> AppResult<Self> {
+        // Lock/unlock and suspend/resume notifications arrive on a background message loop
+        windows_api::start_session_lock_monitor();
+        Ok(Self {
+            title_heuristics: config.title_heuristics.clone(),
+            command_line: config.record_command_line,
+            web_apps: config.web_app_detection,
+        })
     }
 }
 
 impl ActivityDetector for WindowsDetector {
     fn get_current_activity(&self) -> AppResult<Option<ActivityInfo>> {
-        // Call the existing windows_api function
-        let detection_result = windows_api::get_detailed_window_info()?; // Propagate errors
+        let Some(window) = windows_api::get_detailed_window_info(&self.title_heuristics, self.web_apps)? else {
+            return Ok(None);
+        };
+        let command_line = if self.command_line { windows_api::process_command_line(window.process_id, &window.app_name) } else { None };
 
         // Map the result to the common ActivityInfo struct
-        Ok(detection_result.map(|(app, main, detailed)| ActivityInfo {
-            app_name: app,
-            main_title: main,
-            detailed_title: detailed,
+        Ok(Some(ActivityInfo {
+            app_name: window.app_name,
+            main_title: window.main_title,
+            detailed_title: window.detailed_title,
+            virtual_desktop: window.virtual_desktop,
+            repo: None, // Resolved from the titles by the tracker (`repo_detection`)
+            // Dropped by the tracker unless `record_executable_path` is on
+            executable_path: window.executable_path.map(|path| path.to_string_lossy().into_owned()),
+            command_line,
+            ticket: None, // Read from the titles by the tracker (`ticket_detection`)
+            // Tagged by the tracker (`detection::remote`)
+            session_type: None,
+            remote_host: None,
+            document: None, // Read from the titles by the tracker (`document_detection`)
+            passive: false, // Tagged by the tracker's passive heuristic
         }))
     }
+
+    fn idle_time(&self) -> AppResult<Option<Duration>> {
+        windows_api::get_idle_time().map(Some)
+    }
+
+    fn is_session_locked(&self) -> AppResult<bool> {
+        Ok(windows_api::is_session_locked())
+    }
+
+    fn suspend_state(&self) -> AppResult<SuspendState> {
+        Ok(windows_api::suspend_state())
+    }
+
+    fn fullscreen_app(&self) -> AppResult<Option<String>> {
+        windows_api::get_fullscreen_foreground_app()
+    }
+
+    fn list_windows(&self) -> AppResult<Vec<WindowInfo>> {
+        let windows = windows_api::list_visible_windows()?;
+        Ok(windows
+            .into_iter()
+            .map(|(app_name, window_title)| WindowInfo { app_name, window_title })
+            .collect())
+    }
+
+    fn audio_apps(&self) -> AppResult<Option<Vec<String>>> {
+        windows_api::audio_playing_apps().map(Some)
+    }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 // src/errors.rs
 use thiserror::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
 use crate::types::ApiKeyType;
 
 #[derive(Error, Debug)]
@@ -11,6 +13,13 @@ pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[error(transparent)]
+    Persistence(#[from] PersistenceError),
+
+    #[cfg(feature = "postgres")]
+    #[error("PostgreSQL error: {0}")]
+    Postgres(#[from] postgres::Error),
+
     #[error("I/O error accessing path '{path}': {source}")]
     Io { path: PathBuf, #[source] source: std::io::Error },
 
@@ -28,14 +37,33 @@ pub enum AppError {
     #[error("Update check/download error: {0}")]
     Update(#[from] self_update::errors::Error),
 
-    #[error("Platform API error (e.g., getting cursor/window info): {0}")]
+    #[error("Release signature check failed, the update was not installed: {0}")]
+    UntrustedRelease(String),
+
+    #[error("Platform API error: {0}")]
     Platform(String),
+
+    #[error("Detection error: {0}")]
+    Detection(#[from] DetectionError),
+
     #[error("Argument parsing error: {0}")]
     CliArgs(#[from] clap::Error),
 
     #[error("Failed to set Ctrl-C handler: {0}")]
     CtrlC(#[from] ctrlc::Error),
 
+    #[error("Sync error: {0}")]
+    Sync(String),
+
+    #[error("Integration error: {0}")]
+    Integration(String),
+
+    #[error("Locked configuration: {0}")]
+    Locked(String),
+
+    #[error("Encryption error: {0}")]
+    Crypto(String),
+
     #[error("An unexpected error occurred: {0}")]
     Unexpected(String),
 
@@ -43,4 +71,82 @@ pub enum AppError {
     ApiKeyNotFound(ApiKeyType, String),
 }
 
-pub type AppResult<T> = Result<T, AppError>;
\ No newline at end of file
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Failures opening an SQLite database, naming the file (see `persistence::open_connection_with_key`).
+#[derive(Error, Debug)]
+pub enum PersistenceError {
+    #[error("Cannot create the data directory '{path}': {source}")]
+    CreateDataDir { path: PathBuf, #[source] source: std::io::Error },
+
+    #[error("Cannot open the database '{path}': {source}")]
+    Open { path: PathBuf, #[source] source: rusqlite::Error },
+
+    /// A setup statement failed; with SQLCipher a wrong passphrase shows up here (`SQLITE_NOTADB`)
+    #[error("Cannot set up the database '{path}' (PRAGMA {pragma}): {source}")]
+    Pragma { path: PathBuf, pragma: &'static str, #[source] source: rusqlite::Error },
+}
+
+impl PersistenceError {
+    pub fn path(&self) -> &Path {
+        match self {
+            PersistenceError::CreateDataDir { path, .. } | PersistenceError::Open { path, .. } | PersistenceError::Pragma { path, .. } => path,
+        }
+    }
+
+    /// The SQLite error behind the failure, if it came from SQLite.
+    pub fn sqlite_error(&self) -> Option<&rusqlite::Error> {
+        match self {
+            PersistenceError::CreateDataDir { .. } => None,
+            PersistenceError::Open { source, .. } | PersistenceError::Pragma { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Failures of activity detection, by the call, program or plugin that failed.
+#[derive(Error, Debug)]
+pub enum DetectionError {
+    /// A Win32 call failed; `code` is its HRESULT
+    #[error("{call} failed (HRESULT {code:#010x}): {message}")]
+    WindowsApi { call: &'static str, code: i32, message: String },
+
+    /// A helper program (`loginctl`, `gdbus`) could not be run or read; the errno is in `source`
+    #[error("Failed to run {program}: {source}")]
+    Command { program: &'static str, #[source] source: std::io::Error },
+
+    #[error("{program} failed: {detail}")]
+    CommandFailed { program: &'static str, detail: String },
+
+    #[error("Detector plugin '{plugin}' failed to start: {source}")]
+    PluginStart { plugin: String, #[source] source: std::io::Error },
+
+    #[error("Detector plugin '{plugin}' timed out after {timeout:?}")]
+    PluginTimeout { plugin: String, timeout: Duration },
+
+    #[error("Detector plugin '{plugin}' exited with {status}")]
+    PluginExit { plugin: String, status: ExitStatus },
+
+    /// Waiting for the plugin or reading its output failed
+    #[error("Detector plugin '{plugin}' I/O failed: {source}")]
+    PluginIo { plugin: String, #[source] source: std::io::Error },
+
+    #[error("Detector plugin '{plugin}' printed invalid JSON: {source}")]
+    PluginOutput { plugin: String, #[source] source: serde_json::Error },
+
+    #[error("{0}")]
+    Unsupported(&'static str),
+}
+
+impl DetectionError {
+    /// Whether trying again shortly may succeed (an API or program hiccup rather than a setup problem).
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, DetectionError::PluginStart { .. } | DetectionError::PluginOutput { .. } | DetectionError::Unsupported(_))
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl DetectionError {
+    pub fn windows_api(call: &'static str, error: &windows::core::Error) -> Self {
+        DetectionError::WindowsApi { call, code: error.code().0, message: error.message() }
+    }
+}
\ No newline at end of file
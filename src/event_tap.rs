@@ -0,0 +1,105 @@
+// src/event_tap.rs
+// `track --emit-events <FILE|->`: one JSON line per interval start and end, written as the persistence
+// thread records them, so scripts can follow activity changes live (e.g. `track --emit-events - | jq`).
+
+use crate::errors::{AppError, AppResult};
+use serde::Serialize;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// One line of the stream, tagged by `event`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IntervalEvent<'a> {
+    /// An interval was opened (after `min_interval_secs`, from when the switch happened)
+    IntervalStart {
+        timestamp: i64,
+        device_id: &'a str,
+        /// Profile chosen by `profile_rules` (None = the tracker's own profile)
+        profile: Option<&'a str>,
+        app: &'a str,
+        main_title: &'a str,
+        detailed_title: &'a str,
+        virtual_desktop: Option<&'a str>,
+        /// Git repository from `repo_detection`
+        repo: Option<&'a str>,
+        /// With `record_executable_path` / `record_command_line`
+        executable_path: Option<&'a str>,
+        command_line: Option<&'a str>,
+        /// Issue key from `ticket_detection`
+        ticket: Option<&'a str>,
+        /// Remote desktop session or client ("rdp", "rdp-client", ...) and the machine of a client window
+        session_type: Option<&'a str>,
+        remote_host: Option<&'a str>,
+        /// File from `document_detection`
+        document: Option<&'a str>,
+        passive: bool,
+    },
+    /// The open interval ended (switch, suspend, clock jump or shutdown)
+    IntervalEnd {
+        timestamp: i64,
+        start: Option<i64>,
+        device_id: &'a str,
+        profile: Option<&'a str>,
+        app: &'a str,
+    },
+}
+
+/// Where the events go. Each line is flushed right away; after a write error the tap stops.
+pub struct EventTap {
+    out: Option<Box<dyn Write + Send>>,
+    // Start of the open interval, repeated in its end event
+    open_start: Option<i64>,
+}
+
+impl fmt::Debug for EventTap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventTap").field("active", &self.out.is_some()).field("open_start", &self.open_start).finish()
+    }
+}
+
+impl EventTap {
+    /// `-` writes to stdout; a file is created or appended to.
+    pub fn open(target: &Path) -> AppResult<Self> {
+        let out: Box<dyn Write + Send> = if target.as_os_str() == "-" {
+            Box::new(io::stdout())
+        } else {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(target)
+                .map_err(|e| AppError::Io { path: target.to_path_buf(), source: e })?;
+            Box::new(file)
+        };
+        Ok(EventTap::from_writer(out))
+    }
+
+    /// Streams into any writer (library users, tests of scripts).
+    pub fn from_writer(out: Box<dyn Write + Send>) -> Self {
+        EventTap { out: Some(out), open_start: None }
+    }
+
+    pub fn emit(&mut self, event: &IntervalEvent) {
+        match event {
+            IntervalEvent::IntervalStart { timestamp, .. } => self.open_start = Some(*timestamp),
+            IntervalEvent::IntervalEnd { .. } => self.open_start = None,
+        }
+        let Some(out) = self.out.as_mut() else { return };
+        let written = serde_json::to_string(event)
+            .map_err(io::Error::other)
+            .and_then(|line| writeln!(out, "{}", line))
+            .and_then(|_| out.flush());
+        if let Err(e) = written {
+            // A closed pipe (`| head`) must not stop tracking; events are just no longer written
+            log::warn!("Event stream closed ({}); no more events are written.", e);
+            self.out = None;
+        }
+    }
+
+    /// Start of the interval an end event closes.
+    pub fn open_start(&self) -> Option<i64> {
+        self.open_start
+    }
+}
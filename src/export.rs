@@ -0,0 +1,285 @@
+// src/export.rs
+// Renders tracked intervals into file formats for other tools (calendar apps, issue trackers, ...).
+
+use crate::app_metadata::AppAliases;
+use crate::errors::AppResult;
+use crate::focus;
+use crate::persistence::Storage;
+use crate::timezone::ReportTimeZone;
+use crate::types::{DurationFormat, FocusMetrics, IntervalRecord};
+use crate::utils::format_duration;
+use chrono::{DateTime, Local, TimeZone, Utc};
+
+/// Same-target intervals separated by at most this many seconds form one block
+/// (e.g. across a pause or tracker restart).
+pub const MERGE_GAP_SECS: i64 = 60;
+
+/// A contiguous stretch of time on one app/title, built from one or more intervals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityBlock {
+    pub app_name: String,
+    pub title: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Id of the first interval of the block (stable event UID)
+    pub first_id: i64,
+}
+
+/// Merges consecutive intervals (sorted by start time) with the same app and detailed title.
+pub fn merge_blocks(intervals: &[IntervalRecord]) -> Vec<ActivityBlock> {
+    let mut blocks: Vec<ActivityBlock> = Vec::new();
+    for interval in intervals {
+        let title = interval.detailed_title.clone().unwrap_or_default();
+        if let Some(last) = blocks.last_mut()
+            && last.app_name == interval.app_name
+            && last.title == title
+            && interval.start_time - last.end_time <= MERGE_GAP_SECS
+        {
+            last.end_time = last.end_time.max(interval.end_time);
+            continue;
+        }
+        blocks.push(ActivityBlock {
+            app_name: interval.app_name.clone(),
+            title,
+            start_time: interval.start_time,
+            end_time: interval.end_time,
+            first_id: interval.id,
+        });
+    }
+    blocks.retain(|block| block.end_time > block.start_time);
+    blocks
+}
+
+/// Blocks of the intervals between `from` and `to` (open ones end at `now`), under their friendly app names.
+/// Also returns how many intervals they were built from.
+pub fn load_blocks(storage: &mut dyn Storage, from: i64, to: i64, now: i64) -> AppResult<(Vec<ActivityBlock>, usize)> {
+    let intervals = storage.query_intervals_between(from, to, now)?;
+    let aliases = AppAliases::load(storage)?;
+    let mut blocks = merge_blocks(&intervals);
+    for block in &mut blocks {
+        block.app_name = aliases.display(&block.app_name).to_string();
+    }
+    Ok((blocks, intervals.len()))
+}
+
+/// Time logged on one issue key (`ticket_detection`) in a day, for `export --format worklog`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorklogEntry {
+    pub ticket: String,
+    /// Start of the first interval on the ticket
+    pub started: i64,
+    pub secs: i64,
+    /// Friendly names of the apps the time was spent in, most used first
+    pub apps: Vec<String>,
+}
+
+/// Time per ticket of the live and archived intervals between `from` and `to` (clipped to the
+/// period; open ones end at `now`), first started first. Intervals without a ticket are left out.
+pub fn load_worklog(storage: &mut dyn Storage, from: i64, to: i64, now: i64) -> AppResult<Vec<WorklogEntry>> {
+    let mut intervals = storage.query_intervals_between(from, to, now)?;
+    intervals.extend(storage.query_archived_intervals(from, to, None)?);
+    let aliases = AppAliases::load(storage)?;
+    // (entry, seconds per app)
+    let mut entries: Vec<(WorklogEntry, Vec<(String, i64)>)> = Vec::new();
+    for interval in &intervals {
+        let Some(ticket) = &interval.ticket else { continue };
+        let (start, end) = (interval.start_time.max(from), interval.end_time.min(to));
+        if end <= start {
+            continue;
+        }
+        let index = match entries.iter().position(|(entry, _)| entry.ticket == *ticket) {
+            Some(index) => index,
+            None => {
+                entries.push((WorklogEntry { ticket: ticket.clone(), started: start, secs: 0, apps: Vec::new() }, Vec::new()));
+                entries.len() - 1
+            }
+        };
+        let (entry, apps) = &mut entries[index];
+        entry.started = entry.started.min(start);
+        entry.secs += end - start;
+        let app = aliases.display(&interval.app_name).to_string();
+        match apps.iter_mut().find(|(name, _)| *name == app) {
+            Some((_, secs)) => *secs += end - start,
+            None => apps.push((app, end - start)),
+        }
+    }
+    let mut entries: Vec<WorklogEntry> = entries
+        .into_iter()
+        .map(|(mut entry, mut apps)| {
+            apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            entry.apps = apps.into_iter().map(|(app, _)| app).collect();
+            entry
+        })
+        .collect();
+    entries.sort_by(|a, b| a.started.cmp(&b.started).then_with(|| a.ticket.cmp(&b.ticket)));
+    Ok(entries)
+}
+
+/// Focus metrics of the intervals between `from` and `to`, with the longest session under its friendly app name.
+pub fn load_focus(storage: &mut dyn Storage, from: i64, to: i64, now: i64, timezone: &ReportTimeZone) -> AppResult<FocusMetrics> {
+    let mut metrics = focus::load(storage, from, to, now, timezone)?;
+    if let Some((app, _, _)) = &mut metrics.longest_session {
+        *app = AppAliases::load(storage)?.display(app).to_string();
+    }
+    Ok(metrics)
+}
+
+/// Renders blocks as an iCalendar (RFC 5545) file with one VEVENT per block.
+/// The event description holds the block duration in `duration_format`.
+pub fn render_ics(blocks: &[ActivityBlock], product: &str, device_id: &str, duration_format: DurationFormat) -> String {
+    let now = ics_timestamp(Utc::now().timestamp());
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//{}//Activity Timeline//EN", product),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for block in blocks {
+        let summary = if block.title.is_empty() {
+            block.app_name.clone()
+        } else {
+            format!("{} — {}", block.app_name, block.title)
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{}@{}.{}", block.start_time, block.first_id, escape_text(device_id), product));
+        lines.push(format!("DTSTAMP:{}", now));
+        lines.push(format!("DTSTART:{}", ics_timestamp(block.start_time)));
+        lines.push(format!("DTEND:{}", ics_timestamp(block.end_time)));
+        lines.push(format!("SUMMARY:{}", escape_text(&summary)));
+        lines.push(format!("DESCRIPTION:{}", escape_text(&format_duration(block.end_time - block.start_time, duration_format))));
+        lines.push(format!("CATEGORIES:{}", escape_text(&block.app_name)));
+        lines.push("TRANSP:TRANSPARENT".to_string()); // Do not show as busy
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// Renders blocks as a standalone HTML page: total, focus metrics, time per app (with bars) and the timeline.
+/// `heading` names the day, e.g. "2024-05-06".
+pub fn render_html(blocks: &[ActivityBlock], focus: &FocusMetrics, heading: &str, product: &str, duration_format: DurationFormat) -> String {
+    let mut totals: Vec<(&str, i64)> = Vec::new();
+    for block in blocks {
+        let secs = block.end_time - block.start_time;
+        match totals.iter_mut().find(|(app, _)| *app == block.app_name) {
+            Some((_, total)) => *total += secs,
+            None => totals.push((&block.app_name, secs)),
+        }
+    }
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let total: i64 = totals.iter().map(|(_, secs)| secs).sum();
+    let top = totals.first().map_or(1, |(_, secs)| (*secs).max(1));
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{} — {}</title>\n", escape_html(product), escape_html(heading)));
+    html.push_str(concat!(
+        "<style>body{font-family:system-ui,sans-serif;margin:2em auto;max-width:60em;color:#222}",
+        "table{border-collapse:collapse;width:100%;margin-bottom:2em}td,th{padding:.3em .6em;text-align:left;border-bottom:1px solid #ddd}",
+        ".num{text-align:right;white-space:nowrap}.bar{background:#4a90d9;height:.8em;border-radius:.2em}</style>\n",
+        "</head>\n<body>\n"
+    ));
+    html.push_str(&format!("<h1>{}</h1>\n<p>Total tracked: <strong>{}</strong></p>\n", escape_html(heading), format_duration(total, duration_format)));
+
+    html.push_str("<h2>Focus</h2>\n<table>\n");
+    html.push_str(&format!(
+        "<tr><td>App switches</td><td class=\"num\">{} ({:.1} per hour)</td></tr>\n",
+        focus.switches,
+        focus.switches_per_hour()
+    ));
+    html.push_str(&format!(
+        "<tr><td>Focus sessions</td><td class=\"num\">{}, average {}</td></tr>\n",
+        focus.sessions,
+        format_duration(focus.average_session_secs(), duration_format)
+    ));
+    if let Some((app, start, end)) = &focus.longest_session {
+        html.push_str(&format!(
+            "<tr><td>Longest session</td><td class=\"num\">{} for {} ({}–{})</td></tr>\n",
+            escape_html(app),
+            format_duration(end - start, duration_format),
+            local_clock_time(*start),
+            local_clock_time(*end)
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Applications</h2>\n<table>\n<tr><th>Application</th><th class=\"num\">Time</th><th style=\"width:40%\"></th></tr>\n");
+    for (app, secs) in &totals {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td><div class=\"bar\" style=\"width:{:.1}%\"></div></td></tr>\n",
+            escape_html(app),
+            format_duration(*secs, duration_format),
+            *secs as f64 * 100.0 / top as f64
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Timeline</h2>\n<table>\n<tr><th>From</th><th>To</th><th>Application</th><th>Window</th><th class=\"num\">Time</th></tr>\n");
+    for block in blocks {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>\n",
+            local_clock_time(block.start_time),
+            local_clock_time(block.end_time),
+            escape_html(&block.app_name),
+            escape_html(&block.title),
+            format_duration(block.end_time - block.start_time, duration_format)
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Renders a worklog as tab-separated lines (ticket, local start, time spent, apps) that paste into
+/// Jira's "Log work" fields or a spreadsheet. Time spent is in Jira's notation ("1h 30m"), rounded to
+/// the minute; entries under half a minute are left out.
+pub fn render_worklog(entries: &[WorklogEntry]) -> String {
+    let mut text = String::from("Ticket\tStarted\tTime Spent\tComment\n");
+    for entry in entries {
+        let minutes = (entry.secs + 30) / 60;
+        if minutes == 0 {
+            continue;
+        }
+        let spent = match (minutes / 60, minutes % 60) {
+            (0, minutes) => format!("{}m", minutes),
+            (hours, 0) => format!("{}h", hours),
+            (hours, minutes) => format!("{}h {}m", hours, minutes),
+        };
+        let started = Local
+            .timestamp_opt(entry.started, 0)
+            .single()
+            .map_or_else(|| format!("@{}", entry.started), |time| time.format("%Y-%m-%d %H:%M").to_string());
+        text.push_str(&format!("{}\t{}\t{}\t{}\n", entry.ticket, started, spent, entry.apps.join(", ").replace(['\t', '\n'], " ")));
+    }
+    text
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn local_clock_time(timestamp: i64) -> String {
+    Local.timestamp_opt(timestamp, 0).single().map_or_else(|| format!("@{}", timestamp), |time| time.format("%H:%M").to_string())
+}
+
+fn ics_timestamp(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0).unwrap_or_default().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n").replace('\r', "")
+}
+
+/// Folds a content line at 75 octets (continuation lines start with a space), keeping UTF-8 intact.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,71 @@
+// src/focus.rs
+// Context-switching metrics over raw intervals: how often the foreground app changed, per hour and
+// overall, and the focus sessions (uninterrupted runs on one app) with their average and longest length.
+// Used by `stats --level focus` and the HTML day report; durations alone do not show fragmentation.
+
+use crate::detection::{BREAK_APP_NAME, LOCKED_APP_NAME};
+use crate::errors::AppResult;
+use crate::persistence::Storage;
+use crate::timezone::ReportTimeZone;
+use crate::types::{FocusMetrics, IntervalRecord};
+
+/// Intervals of the same app at most this many seconds apart belong to one session (short idle, restarts).
+pub const SESSION_GAP_SECS: i64 = 120;
+
+/// Metrics of the intervals overlapping [from, to), clipped to it; locked time and breaks are left out.
+/// Live and archived intervals may be passed together, in any order.
+pub fn compute(intervals: &[IntervalRecord], from: i64, to: i64, timezone: &ReportTimeZone) -> FocusMetrics {
+    let mut clipped: Vec<(&str, i64, i64, i64)> = intervals
+        .iter()
+        .filter(|interval| interval.app_name != LOCKED_APP_NAME && interval.app_name != BREAK_APP_NAME)
+        .map(|interval| (interval.app_name.as_str(), interval.start_time.max(from), interval.end_time.min(to), interval.id))
+        .filter(|(_, start, end, _)| end > start)
+        .collect();
+    clipped.sort_by_key(|(_, start, _, id)| (*start, *id));
+
+    let mut metrics = FocusMetrics::default();
+    let mut session: Option<(&str, i64, i64)> = None;
+    for (app, start, end, _) in clipped {
+        metrics.tracked_secs += end - start;
+        let hour = timezone.hour_start(start);
+        if metrics.switches_by_hour.last().is_none_or(|(last_hour, _)| *last_hour != hour) {
+            metrics.switches_by_hour.push((hour, 0));
+        }
+        session = match session {
+            Some((current, session_start, session_end)) if current == app && start - session_end <= SESSION_GAP_SECS => {
+                Some((current, session_start, session_end.max(end)))
+            }
+            previous => {
+                if let Some((previous_app, session_start, session_end)) = previous {
+                    if previous_app != app {
+                        metrics.switches += 1;
+                        if let Some((_, switches)) = metrics.switches_by_hour.last_mut() {
+                            *switches += 1;
+                        }
+                    }
+                    close_session(&mut metrics, previous_app, session_start, session_end);
+                }
+                Some((app, start, end))
+            }
+        };
+    }
+    if let Some((app, session_start, session_end)) = session {
+        close_session(&mut metrics, app, session_start, session_end);
+    }
+    metrics
+}
+
+/// Metrics of [from, to) from the live and archived intervals of `storage` (the open interval ends at `now`).
+pub fn load(storage: &mut dyn Storage, from: i64, to: i64, now: i64, timezone: &ReportTimeZone) -> AppResult<FocusMetrics> {
+    let mut intervals = storage.query_intervals_between(from, to, now)?;
+    intervals.extend(storage.query_archived_intervals(from, to, None)?);
+    Ok(compute(&intervals, from, to, timezone))
+}
+
+fn close_session(metrics: &mut FocusMetrics, app: &str, start: i64, end: i64) {
+    metrics.sessions += 1;
+    metrics.session_secs += end - start;
+    if metrics.longest_session.as_ref().is_none_or(|(_, longest_start, longest_end)| end - start > longest_end - longest_start) {
+        metrics.longest_session = Some((app.to_string(), start, end));
+    }
+}
@@ -0,0 +1,203 @@
+// src/gaps.rs
+// Periods with no interval at all (`gaps`), each with its likely cause taken from the tracker's own
+// run log (`tracker_runs`, `tracker_run_events`): the tracker was not running or crashed, the system
+// slept, recording was paused or outside `tracking_hours`, or the tracker ran and detected nothing.
+// Locked time is recorded as `[Locked]` intervals, so it counts as tracked here (shown separately).
+
+use crate::detection::LOCKED_APP_NAME;
+use crate::types::{IntervalRecord, RunEventKind, TrackerHistory, TrackerRun};
+
+/// A sleep or clock jump this many seconds before a gap still explains it (the last poll before the sleep)
+const SLEEP_SLACK_SECS: i64 = 120;
+/// Pieces of a gap shorter than this take the cause of a neighbour (events and intervals are a poll or two apart)
+const MIN_PIECE_SECS: i64 = 60;
+/// An unfinished latest run heartbeating this recently is still running (heartbeats are 30s apart)
+const ALIVE_SLACK_SECS: i64 = 120;
+
+/// Why nothing was recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GapReason {
+    /// The tracker was stopped
+    NotRunning,
+    /// The tracker stopped without shutting down (crash, kill, power loss)
+    Crashed,
+    /// The system slept (or the clock jumped forward, usually an unnotified sleep)
+    Sleep,
+    Paused,
+    /// Outside `tracking_hours`
+    OffHours,
+    /// The tracker ran but recorded nothing (no focused window, detection failing)
+    NothingDetected,
+    /// The hour's raw intervals were rolled into the summaries, so where its gaps were is unknown
+    Aggregated,
+    /// Before the first recorded run (runs are recorded from schema version 14 on)
+    Unknown,
+}
+
+impl GapReason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            GapReason::NotRunning => "tracker not running",
+            GapReason::Crashed => "tracker crashed or was killed",
+            GapReason::Sleep => "system asleep",
+            GapReason::Paused => "recording paused",
+            GapReason::OffHours => "outside tracking_hours",
+            GapReason::NothingDetected => "tracker running, nothing detected",
+            GapReason::Aggregated => "hour already aggregated (raw intervals removed)",
+            GapReason::Unknown => "unknown (before runs were recorded)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    pub start: i64,
+    pub end: i64,
+    pub reason: GapReason,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GapReport {
+    pub gaps: Vec<Gap>,
+    /// Seconds covered by intervals, locked time included
+    pub tracked_secs: i64,
+    pub locked_secs: i64,
+    /// Seconds in the listed gaps
+    pub untracked_secs: i64,
+}
+
+/// Gaps of at least `min_gap_secs` in [from, to) between `intervals` (live and archived, in any order).
+/// `aggregated_hours` are the starts of hours whose raw intervals are gone; `now` tells the running
+/// tracker's run from one that crashed.
+pub fn find(
+    intervals: &[IntervalRecord],
+    history: &TrackerHistory,
+    aggregated_hours: &[i64],
+    range: (i64, i64),
+    now: i64,
+    min_gap_secs: i64,
+) -> GapReport {
+    let (from, to) = range;
+    let mut clipped: Vec<(i64, i64, bool)> = intervals
+        .iter()
+        .map(|interval| (interval.start_time.max(from), interval.end_time.min(to), interval.app_name == LOCKED_APP_NAME))
+        .filter(|(start, end, _)| end > start)
+        .collect();
+    clipped.sort_unstable();
+
+    let mut report = GapReport::default();
+    let mut covered_until = from;
+    let mut holes = Vec::new();
+    for (start, end, locked) in clipped {
+        if start > covered_until {
+            holes.push((covered_until, start));
+        }
+        // Overlapping intervals count once
+        let added = end - start.max(covered_until);
+        if added > 0 {
+            report.tracked_secs += added;
+            if locked {
+                report.locked_secs += added;
+            }
+        }
+        covered_until = covered_until.max(end);
+    }
+    if to > covered_until {
+        holes.push((covered_until, to));
+    }
+
+    for (start, end) in holes.into_iter().filter(|(start, end)| end - start >= min_gap_secs.max(1)) {
+        report.gaps.extend(classify_hole(start, end, history, aggregated_hours, now));
+    }
+    report.untracked_secs = report.gaps.iter().map(|gap| gap.end - gap.start).sum();
+    report
+}
+
+/// Splits a hole wherever the run state changes and names the cause of each piece.
+fn classify_hole(start: i64, end: i64, history: &TrackerHistory, aggregated_hours: &[i64], now: i64) -> Vec<Gap> {
+    // Only the latest run can still be running
+    let latest_start = history.runs.iter().map(|run| run.start_time).max();
+    let run_end = |run: &TrackerRun| match run.end_time {
+        None if run.last_seen >= now - ALIVE_SLACK_SECS && latest_start == Some(run.start_time) => now,
+        _ => run.effective_end(),
+    };
+    let mut cuts = vec![start, end];
+    cuts.extend(history.runs.iter().flat_map(|run| [run.start_time, run_end(run)]));
+    cuts.extend(history.events.iter().map(|event| event.at));
+    cuts.extend(aggregated_hours.iter().flat_map(|hour| [*hour, hour + 3600]));
+    cuts.retain(|cut| (start..=end).contains(cut));
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut pieces: Vec<Gap> = Vec::new();
+    for window in cuts.windows(2) {
+        let (piece_start, piece_end) = (window[0], window[1]);
+        let middle = piece_start + (piece_end - piece_start) / 2;
+        let reason = if aggregated_hours.iter().any(|hour| (*hour..hour + 3600).contains(&middle)) {
+            GapReason::Aggregated
+        } else if let Some(run) = history.runs.iter().find(|run| (run.start_time..run_end(run)).contains(&middle)) {
+            run_reason(run.id, history, start, piece_end, middle)
+        } else if history.first_run_start.is_none_or(|first| middle < first) {
+            GapReason::Unknown
+        } else {
+            // The last run before the piece; one before the range was not loaded and counts as stopped
+            match history.runs.iter().filter(|run| run_end(run) <= middle).max_by_key(|run| run_end(run)) {
+                Some(run) if run.end_time.is_none() => GapReason::Crashed,
+                _ => GapReason::NotRunning,
+            }
+        };
+        push_piece(&mut pieces, Gap { start: piece_start, end: piece_end, reason });
+    }
+
+    // Slivers between an event and the interval it ended belong to the longer neighbour
+    let mut merged: Vec<Gap> = Vec::new();
+    for index in 0..pieces.len() {
+        let mut piece = pieces[index];
+        if piece.end - piece.start < MIN_PIECE_SECS && pieces.len() > 1 {
+            let previous = index.checked_sub(1).map(|i| pieces[i]);
+            let next = pieces.get(index + 1).copied();
+            let longer = match (previous, next) {
+                (Some(previous), Some(next)) if next.end - next.start > previous.end - previous.start => next,
+                (Some(previous), _) => previous,
+                (None, Some(next)) => next,
+                (None, None) => piece,
+            };
+            piece.reason = longer.reason;
+        }
+        push_piece(&mut merged, piece);
+    }
+    merged
+}
+
+/// The cause of a piece at `middle` inside run `run_id`, for a hole starting at `hole_start`.
+fn run_reason(run_id: i64, history: &TrackerHistory, hole_start: i64, piece_end: i64, middle: i64) -> GapReason {
+    let (mut paused, mut off_hours, mut slept) = (false, false, false);
+    for event in history.events.iter().filter(|event| event.run_id == run_id) {
+        match event.kind {
+            RunEventKind::Pause if event.at <= middle => paused = true,
+            RunEventKind::Resume if event.at <= middle => paused = false,
+            RunEventKind::OffHoursStart if event.at <= middle => off_hours = true,
+            RunEventKind::OffHoursEnd if event.at <= middle => off_hours = false,
+            RunEventKind::Suspend | RunEventKind::ClockJump => {
+                slept |= (hole_start - SLEEP_SLACK_SECS..piece_end).contains(&event.at);
+            }
+            _ => {}
+        }
+    }
+    if paused {
+        GapReason::Paused
+    } else if off_hours {
+        GapReason::OffHours
+    } else if slept {
+        GapReason::Sleep
+    } else {
+        GapReason::NothingDetected
+    }
+}
+
+fn push_piece(pieces: &mut Vec<Gap>, piece: Gap) {
+    match pieces.last_mut() {
+        Some(last) if last.reason == piece.reason && last.end == piece.start => last.end = piece.end,
+        _ => pieces.push(piece),
+    }
+}
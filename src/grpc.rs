@@ -0,0 +1,355 @@
+// src/grpc.rs
+// gRPC control-and-query API (proto/tracker.proto) served by `track` when `grpc_listen` is set,
+// so GUIs and scripts can drive the tracker without opening the database themselves.
+// Needs the `grpc` cargo feature.
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use crate::network::NetworkHandle;
+use crate::tracker::TrackerHandle;
+
+#[cfg(feature = "grpc")]
+pub use enabled::{GrpcServer, aggregate_in_tracker, tracker_status};
+
+/// Generated messages, server and client (`tracker_server`, `tracker_client`).
+#[cfg(feature = "grpc")]
+pub mod proto {
+    tonic::include_proto!("mouse_tracking.v1");
+}
+
+#[cfg(not(feature = "grpc"))]
+/// Stand-in when the `grpc` feature is off: warns if `grpc_listen` is configured and serves nothing.
+#[derive(Debug)]
+pub struct GrpcServer;
+
+#[cfg(not(feature = "grpc"))]
+impl GrpcServer {
+    pub fn start(app_config: &AppConfig, _handle: TrackerHandle, _network: &NetworkHandle) -> AppResult<Option<Self>> {
+        if app_config.grpc_listen.is_some() {
+            eprintln!("Warning: \"grpc_listen\" is configured but this build has no gRPC support (rebuild with --features grpc).");
+        }
+        Ok(None)
+    }
+
+    pub fn stop(self) {}
+}
+
+#[cfg(not(feature = "grpc"))]
+/// Without the `grpc` feature the running tracker cannot be reached; Ok(false) = not delegated.
+pub fn aggregate_in_tracker(_addr: std::net::SocketAddr) -> AppResult<bool> {
+    Ok(false)
+}
+
+/// State, detection counters and loop health of a running tracker, as reported by its gRPC API.
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteStatus {
+    pub paused: bool,
+    pub counters: crate::tracker::DetectionCounters,
+    /// None from trackers older than the loop health figures
+    pub health: Option<crate::loop_health::LoopHealthReport>,
+}
+
+#[cfg(not(feature = "grpc"))]
+/// Without the `grpc` feature the running tracker cannot be asked; Ok(None).
+pub fn tracker_status(_addr: std::net::SocketAddr) -> AppResult<Option<RemoteStatus>> {
+    Ok(None)
+}
+
+#[cfg(feature = "grpc")]
+mod enabled {
+    use super::proto::tracker_client::TrackerClient;
+    use super::proto::tracker_server::{Tracker, TrackerServer};
+    use super::proto::{
+        Activity, AggregateRequest, AggregateResponse, CurrentActivityRequest, CurrentActivityResponse, Latency, Level, LoopHealth,
+        PauseRequest, Period, QueryStatsRequest, QueryStatsResponse, ResumeRequest, StatusRequest, TrackerStatus, UsageEntry,
+    };
+    use super::{AppConfig, AppResult, NetworkHandle, RemoteStatus, TrackerHandle};
+    use crate::loop_health::{HealthTotals, LatencySummary, LoopHealthReport};
+    use crate::tracker::DetectionCounters;
+    use std::time::Duration;
+    use crate::errors::AppError;
+    use crate::persistence;
+    use crate::types::{AggregatedResult, AggregationLevel, IntervalStream, StatsFilter, TimePeriod};
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+    use tokio::sync::oneshot;
+    use tokio::task::JoinHandle;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::{Request, Response, Status};
+
+    /// The gRPC server task on the tracker's network runtime; `stop` (or dropping it) shuts it down.
+    pub struct GrpcServer {
+        shutdown: Option<oneshot::Sender<()>>,
+        task: Option<JoinHandle<()>>,
+        runtime: tokio::runtime::Handle,
+    }
+
+    impl GrpcServer {
+        /// Binds `grpc_listen` and starts serving on the network runtime; Ok(None) if it is not set.
+        pub fn start(app_config: &AppConfig, handle: TrackerHandle, network: &NetworkHandle) -> AppResult<Option<Self>> {
+            let Some(addr) = app_config.grpc_listen else { return Ok(None) };
+            let runtime = network.runtime().clone();
+            // Bind here so a taken port is reported before tracking carries on
+            let incoming = {
+                let _context = runtime.enter();
+                TcpIncoming::new(addr, true, None)
+                    .map_err(|e| AppError::Config(format!("Cannot listen on grpc_listen {}: {}", addr, e)))?
+            };
+            let service = TrackerService { config: app_config.clone(), storage: persistence::StoragePool::new(app_config, crate::clock::system_clock()), handle };
+            let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+
+            let task = runtime.spawn(async move {
+                let served = tonic::transport::Server::builder()
+                    .add_service(TrackerServer::new(service))
+                    .serve_with_incoming_shutdown(incoming, async {
+                        let _ = shutdown_signal.await;
+                    })
+                    .await;
+                if let Err(e) = served {
+                    eprintln!("[gRPC] Server stopped with an error: {}", e);
+                }
+            });
+            log::info!("gRPC API listening on {}", addr);
+            Ok(Some(GrpcServer { shutdown: Some(shutdown), task: Some(task), runtime }))
+        }
+
+        /// Stops accepting calls and waits for the server to finish.
+        pub fn stop(mut self) {
+            self.shutdown_and_join();
+        }
+
+        fn shutdown_and_join(&mut self) {
+            if let Some(shutdown) = self.shutdown.take() {
+                let _ = shutdown.send(());
+            }
+            if let Some(task) = self.task.take()
+                && let Err(e) = self.runtime.block_on(task)
+                && e.is_panic()
+            {
+                log::error!("gRPC server panicked");
+            }
+        }
+    }
+
+    impl Drop for GrpcServer {
+        fn drop(&mut self) {
+            self.shutdown_and_join();
+        }
+    }
+
+    /// Asks the tracker serving `addr` to run the aggregation on its own connection (`aggregate` while
+    /// `track` is live); Ok(true) once it is done.
+    pub fn aggregate_in_tracker(addr: SocketAddr) -> AppResult<bool> {
+        call_tracker(addr, |mut client| async move {
+            client
+                .aggregate(AggregateRequest {})
+                .await
+                .map_err(|e| AppError::Integration(format!("The tracker failed to aggregate: {}", e.message())))?;
+            Ok(true)
+        })
+    }
+
+    /// Asks the tracker serving `addr` for its state and detection counters (`status`).
+    pub fn tracker_status(addr: SocketAddr) -> AppResult<Option<RemoteStatus>> {
+        call_tracker(addr, |mut client| async move {
+            let status = client
+                .status(StatusRequest {})
+                .await
+                .map_err(|e| AppError::Integration(format!("The tracker did not report its status: {}", e.message())))?
+                .into_inner();
+            let counters = DetectionCounters {
+                retries: status.detection_retries,
+                bridged_polls: status.bridged_polls,
+                failed_polls: status.failed_polls,
+            };
+            Ok(Some(RemoteStatus { paused: status.paused, counters, health: status.health.map(health_report) }))
+        })
+    }
+
+    /// Connects to the tracker's API at `addr` and runs one call to completion.
+    fn call_tracker<T, F, Fut>(mut addr: SocketAddr, call: F) -> AppResult<T>
+    where
+        F: FnOnce(TrackerClient<tonic::transport::Channel>) -> Fut,
+        Fut: std::future::Future<Output = AppResult<T>>,
+    {
+        // A tracker listening on 0.0.0.0 / [::] is reached on loopback
+        if addr.ip().is_unspecified() {
+            addr.set_ip(if addr.is_ipv4() { Ipv4Addr::LOCALHOST.into() } else { Ipv6Addr::LOCALHOST.into() });
+        }
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| AppError::Unexpected(format!("Failed to create gRPC runtime: {}", e)))?;
+        runtime.block_on(async {
+            let client = TrackerClient::connect(format!("http://{}", addr))
+                .await
+                .map_err(|e| AppError::Integration(format!("Cannot reach the tracker's gRPC API at {}: {}", addr, e)))?;
+            call(client).await
+        })
+    }
+
+    fn health_message(report: &LoopHealthReport) -> LoopHealth {
+        let latency = |summary: &LatencySummary| Latency {
+            avg_us: summary.avg.as_micros() as u64,
+            p95_us: summary.p95.as_micros() as u64,
+            max_us: summary.max.as_micros() as u64,
+        };
+        LoopHealth {
+            polls: report.polls,
+            detection: Some(latency(&report.detection)),
+            missed_polls: report.missed_polls,
+            detection_errors: report.detection_errors,
+            writes: report.writes,
+            write: Some(latency(&report.write)),
+            total_polls: report.totals.polls,
+            total_missed_polls: report.totals.missed_polls,
+            total_detection_errors: report.totals.detection_errors,
+            total_writes: report.totals.writes,
+        }
+    }
+
+    fn health_report(message: LoopHealth) -> LoopHealthReport {
+        let latency = |latency: Option<Latency>| {
+            let latency = latency.unwrap_or_default();
+            LatencySummary {
+                avg: Duration::from_micros(latency.avg_us),
+                p95: Duration::from_micros(latency.p95_us),
+                max: Duration::from_micros(latency.max_us),
+            }
+        };
+        LoopHealthReport {
+            polls: message.polls,
+            detection: latency(message.detection),
+            missed_polls: message.missed_polls,
+            detection_errors: message.detection_errors,
+            writes: message.writes,
+            write: latency(message.write),
+            totals: HealthTotals {
+                polls: message.total_polls,
+                missed_polls: message.total_missed_polls,
+                detection_errors: message.total_detection_errors,
+                writes: message.total_writes,
+            },
+        }
+    }
+
+    struct TrackerService {
+        config: AppConfig,
+        // Calls share these connections instead of opening the database each time
+        storage: persistence::StoragePool,
+        handle: TrackerHandle,
+    }
+
+    impl TrackerService {
+        fn status(&self) -> TrackerStatus {
+            let counters = self.handle.detection_counters();
+            TrackerStatus {
+                running: self.handle.is_running(),
+                paused: self.handle.is_paused(),
+                detection_retries: counters.retries,
+                bridged_polls: counters.bridged_polls,
+                failed_polls: counters.failed_polls,
+                health: Some(health_message(&self.handle.loop_health())),
+            }
+        }
+
+        /// Runs a database call on the blocking pool (storage backends are synchronous) with a pooled storage.
+        async fn with_storage<T, F>(&self, operation: F) -> Result<T, Status>
+        where
+            T: Send + 'static,
+            F: FnOnce(&mut dyn persistence::Storage) -> AppResult<T> + Send + 'static,
+        {
+            let pool = self.storage.clone();
+            tokio::task::spawn_blocking(move || operation(pool.get()?.as_mut()))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| Status::internal(e.to_string()))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Tracker for TrackerService {
+        async fn current_activity(&self, _request: Request<CurrentActivityRequest>) -> Result<Response<CurrentActivityResponse>, Status> {
+            let activity = self.handle.current_activity().map(|info| Activity {
+                app_name: info.app_name,
+                main_title: info.main_title,
+                detailed_title: info.detailed_title,
+                virtual_desktop: info.virtual_desktop,
+                passive: info.passive,
+            });
+            Ok(Response::new(CurrentActivityResponse { activity, paused: self.handle.is_paused() }))
+        }
+
+        async fn query_stats(&self, request: Request<QueryStatsRequest>) -> Result<Response<QueryStatsResponse>, Status> {
+            let request = request.into_inner();
+            let period = match request.period() {
+                Period::Today => TimePeriod::Today,
+                Period::LastHour => TimePeriod::LastCompletedHour,
+                Period::CurrentHour => TimePeriod::CurrentHour,
+            };
+            let level = match request.level() {
+                Level::App => AggregationLevel::ByApplication,
+                Level::Detailed => AggregationLevel::Detailed,
+                Level::Desktop => AggregationLevel::ByDesktop,
+                Level::Device => AggregationLevel::ByDevice,
+                Level::Repo => AggregationLevel::ByRepo,
+                Level::Path => AggregationLevel::ByPath,
+            };
+            let filter = StatsFilter {
+                top: request.top.map(|top| top as usize),
+                app_glob: request.app_glob,
+                title_contains: request.title_contains,
+                min_duration_secs: request.min_duration_secs,
+                exclude_passive: request.exclude_passive,
+                user: None,
+                stream: IntervalStream::Foreground,
+            };
+            let result = self.with_storage(move |storage| storage.query_stats(period, level, &filter)).await?;
+            let entries = match result {
+                AggregatedResult::ByApp(totals)
+                | AggregatedResult::ByDesktop(totals)
+                | AggregatedResult::ByDevice(totals)
+                | AggregatedResult::ByRepo(totals)
+                | AggregatedResult::ByPath(totals)
+                | AggregatedResult::ByMeeting(totals)
+                | AggregatedResult::ByTicket(totals)
+                | AggregatedResult::ByUser(totals)
+                | AggregatedResult::ByDocument(totals)
+                | AggregatedResult::ByRemote(totals) => totals
+                    .into_iter()
+                    .map(|(name, total_secs)| UsageEntry { name, title: None, total_secs })
+                    .collect(),
+                AggregatedResult::Detailed(records) => records
+                    .into_iter()
+                    .map(|record| UsageEntry {
+                        name: record.app_name,
+                        title: Some(record.detailed_title),
+                        total_secs: record.total_duration_secs,
+                    })
+                    .collect(),
+                // No gRPC level maps to `Focus` or `Profile`
+                AggregatedResult::Focus(_) | AggregatedResult::Profile(_) => Vec::new(),
+            };
+            Ok(Response::new(QueryStatsResponse { entries }))
+        }
+
+        async fn pause(&self, _request: Request<PauseRequest>) -> Result<Response<TrackerStatus>, Status> {
+            self.handle.pause();
+            Ok(Response::new(self.status()))
+        }
+
+        async fn resume(&self, _request: Request<ResumeRequest>) -> Result<Response<TrackerStatus>, Status> {
+            self.handle.resume();
+            Ok(Response::new(self.status()))
+        }
+
+        async fn status(&self, _request: Request<StatusRequest>) -> Result<Response<TrackerStatus>, Status> {
+            Ok(Response::new(self.status()))
+        }
+
+        async fn aggregate(&self, _request: Request<AggregateRequest>) -> Result<Response<AggregateResponse>, Status> {
+            let merge_gap_secs = self.config.merge_gap_secs;
+            self.with_storage(move |storage| storage.aggregate_and_cleanup(merge_gap_secs)).await?;
+            Ok(Response::new(AggregateResponse {}))
+        }
+    }
+}
@@ -0,0 +1,418 @@
+// src/gui.rs
+// `gui`: a window with live activity, usage charts and config.json settings for users who never
+// open a terminal. It runs the same `Tracker` as `track` and reads stats through `Storage`.
+// Needs the `gui` cargo feature.
+
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+
+#[cfg(feature = "gui")]
+pub use enabled::run;
+
+#[cfg(not(feature = "gui"))]
+/// Stand-in when the `gui` feature is off.
+pub fn run(_app_config: &AppConfig) -> AppResult<()> {
+    Err(crate::errors::AppError::Config("This build has no GUI (rebuild with --features gui)".to_string()))
+}
+
+#[cfg(feature = "gui")]
+mod enabled {
+    use super::{AppConfig, AppResult};
+    use crate::clock;
+    use crate::config;
+    use crate::errors::AppError;
+    use crate::instance::InstanceLock;
+    use crate::persistence;
+    use crate::tracker::Tracker;
+    use crate::types::{AggregatedResult, AggregationLevel, StatsFilter, TimePeriod};
+    use crate::utils::format_duration;
+    use clap::ValueEnum;
+    use eframe::egui;
+    use egui_plot::{uniform_grid_spacer, Bar, BarChart, Plot};
+    use serde_json::{Map, Value};
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    /// How often usage figures are re-read from the database.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+    /// Apps shown in the chart; the rest are left out.
+    const CHART_APPS: usize = 15;
+
+    /// Opens the window and blocks until it is closed; a tracker started from it stops with it.
+    pub fn run(app_config: &AppConfig) -> AppResult<()> {
+        let app = GuiApp::new(app_config.clone());
+        let title = match &app_config.profile {
+            Some(profile) => format!("{} ({})", app_config.app_name, profile),
+            None => app_config.app_name.clone(),
+        };
+        let options = eframe::NativeOptions {
+            viewport: egui::ViewportBuilder::default().with_title(title).with_inner_size([760.0, 560.0]),
+            ..Default::default()
+        };
+        eframe::run_native(&app_config.app_name, options, Box::new(|_cc| Ok(Box::new(app))))
+            .map_err(|e| AppError::Platform(format!("Cannot open the GUI window: {}", e)))
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tab {
+        Live,
+        Charts,
+        Settings,
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum FieldKind {
+        Flag,
+        Number,
+        Text,
+    }
+
+    /// config.json keys editable in the Settings tab: (key, label, kind). Other keys are kept as they are.
+    const SETTINGS: &[(&str, &str, FieldKind)] = &[
+        ("check_interval_secs", "Check the active window every (seconds)", FieldKind::Number),
+        ("adaptive_polling", "Check less often while idle", FieldKind::Flag),
+        ("idle_threshold_secs", "Idle after (seconds)", FieldKind::Number),
+        ("min_interval_secs", "Ignore switches shorter than (seconds)", FieldKind::Number),
+        ("snapshot_interval_mins", "Snapshot all windows every (minutes)", FieldKind::Number),
+        ("audio_tracking", "Record apps playing audio", FieldKind::Flag),
+        ("merge_gap_secs", "Merge gaps shorter than (seconds)", FieldKind::Number),
+        ("archive_raw_intervals", "Archive raw intervals after aggregation", FieldKind::Flag),
+        ("timezone", "Time zone (\"local\" or e.g. \"Europe/Paris\")", FieldKind::Text),
+        ("week_start", "Week starts on (monday or sunday)", FieldKind::Text),
+        ("duration_format", "Durations (clock, human, hours or seconds)", FieldKind::Text),
+        ("device_id", "Device id", FieldKind::Text),
+        ("user_name", "User name (shared databases)", FieldKind::Text),
+    ];
+
+    #[derive(Debug, Clone)]
+    enum FieldValue {
+        Flag(bool),
+        // Numbers are edited as text and parsed on save
+        Text(String),
+    }
+
+    struct GuiApp {
+        config: AppConfig,
+        tab: Tab,
+        tracker: Option<Tracker>,
+        // Held while `tracker` runs, like `track` does
+        instance_lock: Option<InstanceLock>,
+        // Connections for the usage refreshes, kept between them
+        readers: persistence::StoragePool,
+        period: TimePeriod,
+        today: Vec<(String, i64)>,
+        usage: Vec<(String, i64)>,
+        last_refresh: Option<Instant>,
+        // Last error or confirmation, shown at the bottom
+        status: Option<String>,
+        settings: Vec<FieldValue>,
+    }
+
+    impl GuiApp {
+        fn new(config: AppConfig) -> Self {
+            let mut app = GuiApp {
+                readers: persistence::StoragePool::new(&config, clock::system_clock()),
+                config,
+                tab: Tab::Live,
+                tracker: None,
+                instance_lock: None,
+                period: TimePeriod::Today,
+                today: Vec::new(),
+                usage: Vec::new(),
+                last_refresh: None,
+                status: None,
+                settings: Vec::new(),
+            };
+            app.reload_settings();
+            app
+        }
+
+        fn refresh_if_due(&mut self) {
+            if self.last_refresh.is_some_and(|last| last.elapsed() < REFRESH_INTERVAL) {
+                return;
+            }
+            self.last_refresh = Some(Instant::now());
+            let result = query_by_app(&self.readers, TimePeriod::Today).and_then(|today| {
+                let usage = if self.period == TimePeriod::Today { today.clone() } else { query_by_app(&self.readers, self.period)? };
+                Ok((today, usage))
+            });
+            match result {
+                Ok((today, usage)) => {
+                    self.today = today;
+                    self.usage = usage;
+                }
+                Err(e) => self.status = Some(format!("Cannot read usage: {}", e)),
+            }
+        }
+
+        fn start_tracking(&mut self) {
+            // No gRPC API is served from the GUI
+            let instance_lock = match InstanceLock::acquire(&self.config, None) {
+                Ok(lock) => lock,
+                Err(e) => {
+                    self.status = Some(format!("Cannot start tracking: {}", e));
+                    return;
+                }
+            };
+            let mut tracker = Tracker::new(self.config.clone());
+            tracker.set_live_output(false);
+            match tracker.start() {
+                Ok(()) => {
+                    self.tracker = Some(tracker);
+                    self.instance_lock = Some(instance_lock);
+                    self.status = None;
+                }
+                Err(e) => self.status = Some(format!("Cannot start tracking: {}", e)),
+            }
+        }
+
+        fn stop_tracking(&mut self) {
+            if let Some(mut tracker) = self.tracker.take()
+                && let Err(e) = tracker.stop()
+            {
+                self.status = Some(format!("Error stopping the tracker: {}", e));
+            }
+            self.instance_lock = None;
+            self.last_refresh = None;
+        }
+
+        fn live_tab(&mut self, ui: &mut egui::Ui) {
+            let format = self.config.duration_format;
+            match self.tracker.as_ref().map(Tracker::handle) {
+                Some(handle) => {
+                    ui.label(format!("Tracking into {}", persistence::describe_location(&self.config)));
+                    ui.horizontal(|ui| {
+                        if handle.is_paused() {
+                            if ui.button("Resume").clicked() {
+                                handle.resume();
+                            }
+                        } else if ui.button("Pause").clicked() {
+                            handle.pause();
+                        }
+                        if ui.button("Stop tracking").clicked() {
+                            self.stop_tracking();
+                        }
+                    });
+                    ui.separator();
+                    let current = match handle.current_activity() {
+                        _ if handle.is_paused() => "Paused".to_string(),
+                        Some(info) if info.detailed_title.is_empty() => info.app_name,
+                        Some(info) => format!("{} ({})", info.app_name, info.detailed_title),
+                        None => "Nothing detected".to_string(),
+                    };
+                    ui.heading(current);
+                }
+                None => {
+                    ui.label("Not tracking.");
+                    if ui.button("Start tracking").clicked() {
+                        self.start_tracking();
+                    }
+                }
+            }
+            ui.separator();
+            let total: i64 = self.today.iter().map(|(_, secs)| secs).sum();
+            ui.strong(format!("Today: {}", format_duration(total, format)));
+            let top = self.today.first().map_or(0, |(_, secs)| *secs).max(1);
+            egui::Grid::new("today_top_apps").num_columns(3).spacing([12.0, 4.0]).show(ui, |ui| {
+                for (app, secs) in self.today.iter().take(10) {
+                    ui.label(app);
+                    ui.add(egui::ProgressBar::new(*secs as f32 / top as f32).desired_width(220.0));
+                    ui.label(format_duration(*secs, format));
+                    ui.end_row();
+                }
+            });
+        }
+
+        fn charts_tab(&mut self, ui: &mut egui::Ui) {
+            ui.horizontal(|ui| {
+                ui.label("Period:");
+                let before = self.period;
+                egui::ComboBox::from_id_salt("chart_period").selected_text(self.period.to_string()).show_ui(ui, |ui| {
+                    for period in TimePeriod::value_variants() {
+                        ui.selectable_value(&mut self.period, *period, period.to_string());
+                    }
+                });
+                if self.period != before {
+                    self.last_refresh = None;
+                }
+            });
+            if self.usage.is_empty() {
+                ui.label("No usage recorded in this period.");
+                return;
+            }
+            // Top app at the top: bars are placed from the bottom up
+            let apps: Vec<(String, i64)> = self.usage.iter().take(CHART_APPS).cloned().collect();
+            let count = apps.len();
+            let bars: Vec<Bar> = apps
+                .iter()
+                .enumerate()
+                .map(|(index, (app, secs))| Bar::new((count - 1 - index) as f64, *secs as f64 / 3600.0).name(app).width(0.7))
+                .collect();
+            let labels: Vec<String> = apps.iter().rev().map(|(app, _)| app.clone()).collect();
+            let format = self.config.duration_format;
+            let chart = BarChart::new("Usage", bars)
+                .horizontal()
+                .element_formatter(Box::new(move |bar, _| format!("{}: {}", bar.name, format_duration((bar.value * 3600.0).round() as i64, format))));
+            Plot::new("usage_chart")
+                .allow_zoom(false)
+                .allow_drag(false)
+                .allow_scroll(false)
+                .allow_boxed_zoom(false)
+                .x_axis_label("hours")
+                .y_grid_spacer(uniform_grid_spacer(|_| [1.0, 5.0, 10.0]))
+                .y_axis_formatter(move |mark, _| {
+                    let index = mark.value.round();
+                    if (mark.value - index).abs() > f64::EPSILON || index < 0.0 {
+                        return String::new();
+                    }
+                    labels.get(index as usize).cloned().unwrap_or_default()
+                })
+                .show(ui, |plot_ui| plot_ui.bar_chart(chart));
+        }
+
+        fn settings_tab(&mut self, ui: &mut egui::Ui) {
+            ui.label(format!("Saved to {:?}. Changes apply the next time tracking starts.", self.config.config_file_path));
+            ui.separator();
+            egui::Grid::new("settings").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+                for ((_, label, _), value) in SETTINGS.iter().zip(self.settings.iter_mut()) {
+                    ui.label(*label);
+                    match value {
+                        FieldValue::Flag(flag) => ui.checkbox(flag, ""),
+                        FieldValue::Text(text) => ui.text_edit_singleline(text),
+                    };
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    self.save_settings();
+                }
+                if ui.button("Revert").clicked() {
+                    self.reload_settings();
+                }
+            });
+        }
+
+        fn reload_settings(&mut self) {
+            let object = match read_config_object(&self.config.config_file_path) {
+                Ok(object) => object,
+                Err(e) => {
+                    self.status = Some(e);
+                    Map::new()
+                }
+            };
+            self.settings = SETTINGS
+                .iter()
+                .map(|(key, _, kind)| match (kind, object.get(*key)) {
+                    (FieldKind::Flag, value) => FieldValue::Flag(value.and_then(Value::as_bool).unwrap_or(false)),
+                    (_, Some(Value::String(text))) => FieldValue::Text(text.clone()),
+                    (_, Some(Value::Null) | None) => FieldValue::Text(String::new()),
+                    (_, Some(value)) => FieldValue::Text(value.to_string()),
+                })
+                .collect();
+        }
+
+        /// Writes the form into config.json and checks it loads; an invalid file is put back as it was.
+        fn save_settings(&mut self) {
+            let path = self.config.config_file_path.clone();
+            let result = read_config_object(&path).and_then(|mut object| {
+                for ((key, label, kind), value) in SETTINGS.iter().zip(&self.settings) {
+                    match (kind, value) {
+                        (_, FieldValue::Flag(flag)) => {
+                            object.insert(key.to_string(), Value::Bool(*flag));
+                        }
+                        (_, FieldValue::Text(text)) if text.trim().is_empty() => {
+                            object.remove(*key);
+                        }
+                        (FieldKind::Number, FieldValue::Text(text)) => {
+                            let number: u64 = text.trim().parse().map_err(|_| format!("{}: expected a whole number", label))?;
+                            object.insert(key.to_string(), Value::from(number));
+                        }
+                        (_, FieldValue::Text(text)) => {
+                            object.insert(key.to_string(), Value::String(text.trim().to_string()));
+                        }
+                    }
+                }
+                let previous = std::fs::read_to_string(&path).ok();
+                let contents = serde_json::to_string_pretty(&Value::Object(object)).map_err(|e| e.to_string())?;
+                std::fs::write(&path, contents).map_err(|e| format!("Cannot write {:?}: {}", path, e))?;
+                config::load_profile_configuration(self.config.profile.as_deref()).map_err(|e| {
+                    let restored = match &previous {
+                        Some(previous) => std::fs::write(&path, previous),
+                        None => std::fs::remove_file(&path),
+                    };
+                    if let Err(restore_error) = restored {
+                        log::warn!("Could not restore {:?}: {}", path, restore_error);
+                    }
+                    format!("Not saved: {}", e)
+                })
+            });
+            match result {
+                Ok(config) => {
+                    self.readers = persistence::StoragePool::new(&config, clock::system_clock());
+                    self.config = config;
+                    self.last_refresh = None;
+                    self.status = Some("Settings saved.".to_string());
+                }
+                Err(e) => self.status = Some(e),
+            }
+        }
+    }
+
+    impl eframe::App for GuiApp {
+        fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+            self.refresh_if_due();
+            egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, Tab::Live, "Live");
+                    ui.selectable_value(&mut self.tab, Tab::Charts, "Charts");
+                    ui.selectable_value(&mut self.tab, Tab::Settings, "Settings");
+                });
+            });
+            if let Some(status) = self.status.clone() {
+                egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(status);
+                        if ui.small_button("✕").clicked() {
+                            self.status = None;
+                        }
+                    });
+                });
+            }
+            egui::CentralPanel::default().show(ctx, |ui| match self.tab {
+                Tab::Live => self.live_tab(ui),
+                Tab::Charts => self.charts_tab(ui),
+                Tab::Settings => self.settings_tab(ui),
+            });
+            // Keeps the current activity and figures moving without input
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+
+        fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+            self.stop_tracking();
+        }
+    }
+
+    fn query_by_app(readers: &persistence::StoragePool, period: TimePeriod) -> AppResult<Vec<(String, i64)>> {
+        let mut storage = readers.get()?;
+        storage.initialize()?;
+        match storage.query_stats(period, AggregationLevel::ByApplication, &StatsFilter::default())? {
+            AggregatedResult::ByApp(rows) => Ok(rows),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// The top-level object of config.json (empty when the file does not exist yet).
+    fn read_config_object(path: &Path) -> Result<Map<String, Value>, String> {
+        if !path.exists() {
+            return Ok(Map::new());
+        }
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {:?}: {}", path, e))?;
+        match serde_json::from_str(&contents) {
+            Ok(Value::Object(object)) => Ok(object),
+            Ok(_) => Err(format!("{:?} is not a JSON object", path)),
+            Err(e) => Err(format!("Invalid config file {:?}: {}", path, e)),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// src/habits.rs
+// Habits are daily goals on app time ("at least 60 minutes in code* per day", `habit add`). A day
+// meets the goal when its matching apps add up to `daily_minutes`; streaks are runs of such days.
+// Past days come from the day summaries (`days_summary_by_app`, `daily_summary`), today from the
+// raw intervals as well. Shown by `habit list`, `stats` and the daily recap notification.
+
+use crate::errors::AppResult;
+use crate::persistence::Storage;
+use crate::timezone::ReportTimeZone;
+use crate::types::{DurationFormat, Habit, HabitStatus};
+use crate::utils::{format_duration, glob_match};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Status of every habit at `now`.
+pub fn load_status(storage: &mut dyn Storage, timezone: &ReportTimeZone, now: i64) -> AppResult<Vec<HabitStatus>> {
+    let habits = storage.query_habits()?;
+    if habits.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut day_totals = storage.query_daily_app_totals(0)?;
+    // Raw intervals not aggregated yet, split at local midnight
+    for interval in storage.query_intervals_between(0, now + 1, now)? {
+        let mut piece_start = interval.start_time;
+        while piece_start < interval.end_time {
+            let day = timezone.day_start(piece_start);
+            let piece_end = timezone.next_day_start(piece_start).min(interval.end_time);
+            day_totals.push((day, interval.app_name.clone(), piece_end - piece_start));
+            piece_start = piece_end;
+        }
+    }
+    Ok(habits.into_iter().map(|habit| evaluate(habit, &day_totals, timezone, now)).collect())
+}
+
+/// Streaks of one habit from (local day start, app, seconds) rows in any order.
+pub fn evaluate(habit: Habit, day_totals: &[(i64, String, i64)], timezone: &ReportTimeZone, now: i64) -> HabitStatus {
+    let mut per_day: BTreeMap<i64, i64> = BTreeMap::new();
+    for (day, app, secs) in day_totals {
+        if glob_match(&habit.app_glob, app) {
+            // Summaries store the day start of the offset in effect then; normalize to today's zone rules
+            *per_day.entry(timezone.day_start(*day)).or_default() += secs;
+        }
+    }
+    let goal_secs = habit.daily_minutes * 60;
+    let met: BTreeSet<i64> = per_day.iter().filter(|(_, secs)| **secs >= goal_secs).map(|(day, _)| *day).collect();
+
+    let today = timezone.day_start(now);
+    let today_secs = per_day.get(&today).copied().unwrap_or(0);
+    // An unmet today does not break the streak yet
+    let mut day = if met.contains(&today) { today } else { timezone.previous_day_start(today) };
+    let mut current_streak = 0;
+    while met.contains(&day) {
+        current_streak += 1;
+        day = timezone.previous_day_start(day);
+    }
+
+    let (mut longest_streak, mut run, mut previous) = (0, 0, None);
+    for day in &met {
+        run = if previous.is_some_and(|previous| timezone.next_day_start(previous) == *day) { run + 1 } else { 1 };
+        longest_streak = longest_streak.max(run);
+        previous = Some(*day);
+    }
+    HabitStatus { habit, today_secs, current_streak, longest_streak }
+}
+
+/// "coding (code*, 60 min/day): 4-day streak, best 12; today 00:35:00 of 01:00:00"
+pub fn describe(status: &HabitStatus, format: DurationFormat) -> String {
+    let habit = &status.habit;
+    let today = if status.met_today() {
+        format!("today done ({})", format_duration(status.today_secs, format))
+    } else {
+        format!("today {} of {}", format_duration(status.today_secs, format), format_duration(habit.daily_minutes * 60, format))
+    };
+    format!(
+        "{} ({}, {} min/day): {}-day streak, best {}; {}",
+        habit.name, habit.app_glob, habit.daily_minutes, status.current_streak, status.longest_streak, today
+    )
+}
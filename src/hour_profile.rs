@@ -0,0 +1,58 @@
+// src/hour_profile.rs
+// The time-of-day profile of `stats --level profile`: tracked time per local hour (0-23), kept apart
+// for weekdays and weekends. Completed hours come from `hourly_summary` (kept for `hourly_summary_days`),
+// raw intervals not aggregated yet are split at hour boundaries here.
+
+use crate::timezone::ReportTimeZone;
+use crate::types::{IntervalRecord, StatsFilter, TimeOfDayProfile};
+use crate::utils::glob_match;
+
+/// Adds up `hour_totals` (hour start, seconds) and the parts of `intervals` within [start, end)
+/// into local hours. The filters were applied to `hour_totals` in SQL and are applied to `intervals` here.
+pub fn build(hour_totals: &[(i64, i64)], intervals: &[IntervalRecord], filter: &StatsFilter, start: i64, end: i64, timezone: &ReportTimeZone) -> TimeOfDayProfile {
+    let mut profile = TimeOfDayProfile::default();
+    for (hour, secs) in hour_totals {
+        add(&mut profile, timezone, *hour, *secs);
+    }
+    let title_contains = filter.title_contains.as_deref().map(str::to_lowercase);
+    let matching = intervals.iter().filter(|interval| {
+        filter.app_glob.as_deref().is_none_or(|glob| glob_match(glob, &interval.app_name))
+            && title_contains
+                .as_deref()
+                .is_none_or(|text| interval.detailed_title.as_deref().is_some_and(|title| title.to_lowercase().contains(text)))
+            && !(filter.exclude_passive && interval.passive)
+    });
+    for interval in matching {
+        let mut piece_start = interval.start_time.max(start);
+        let interval_end = interval.end_time.min(end);
+        while piece_start < interval_end {
+            let piece_end = (timezone.hour_start(piece_start) + 3600).min(interval_end);
+            add(&mut profile, timezone, piece_start, piece_end - piece_start);
+            piece_start = piece_end;
+        }
+    }
+
+    let mut day = timezone.day_start(start);
+    while day < end {
+        if is_weekend(timezone, day) {
+            profile.weekend_days += 1;
+        } else {
+            profile.weekdays += 1;
+        }
+        day = timezone.next_day_start(day);
+    }
+    profile
+}
+
+fn add(profile: &mut TimeOfDayProfile, timezone: &ReportTimeZone, at: i64, secs: i64) {
+    let hour = (timezone.weekday_and_minute(at).1 / 60) as usize % 24;
+    if is_weekend(timezone, at) {
+        profile.weekend_secs[hour] += secs;
+    } else {
+        profile.weekday_secs[hour] += secs;
+    }
+}
+
+fn is_weekend(timezone: &ReportTimeZone, at: i64) -> bool {
+    timezone.weekday_and_minute(at).0 >= 5
+}
@@ -0,0 +1,103 @@
+// src/instance.rs
+// Instance lock held by `track`: `tracker.lock` in the data directory is locked for as long as the tracker
+// runs, and `tracker.json` says which process holds it and where its gRPC API listens. A second `track` is
+// refused; `aggregate` and `init-db` see the running tracker and delegate to it or wait for its writes.
+// The lock is per OS user, so with `shared_database_path` every user's tracker can run at once.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+const LOCK_FILE_NAME: &str = "tracker.lock";
+// Separate from the lock file: Windows locks are mandatory, so a locked file cannot be read by others
+const INFO_FILE_NAME: &str = "tracker.json";
+
+/// What a running tracker publishes about itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunningTracker {
+    /// None if `tracker.json` could not be read
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// `grpc_listen` of the tracker, when its API is served
+    #[serde(default)]
+    pub grpc_listen: Option<SocketAddr>,
+}
+
+impl RunningTracker {
+    /// "pid 1234", for messages
+    pub fn describe(&self) -> String {
+        self.pid.map_or("unknown pid".to_string(), |pid| format!("pid {}", pid))
+    }
+}
+
+/// Held by `track`; dropping it releases the lock and removes `tracker.json`.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+    info_path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Takes the lock, failing when another tracker already runs on the same database.
+    /// `grpc_listen` is where this tracker serves its API (None when it does not).
+    pub fn acquire(app_config: &AppConfig, grpc_listen: Option<SocketAddr>) -> AppResult<Self> {
+        let dir = lock_dir(app_config);
+        fs::create_dir_all(dir).map_err(|e| AppError::Io { path: dir.to_path_buf(), source: e })?;
+        let path = dir.join(LOCK_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = read_info(dir).unwrap_or_default().describe();
+                return Err(AppError::Config(format!("A tracker is already running on this database ({})", holder)));
+            }
+            Err(TryLockError::Error(e)) => return Err(AppError::Io { path, source: e }),
+        }
+
+        let info = RunningTracker { pid: Some(std::process::id()), grpc_listen };
+        let info_path = dir.join(INFO_FILE_NAME);
+        let json = serde_json::to_string(&info).map_err(|e| AppError::Unexpected(e.to_string()))?;
+        fs::write(&info_path, json).map_err(|e| AppError::Io { path: info_path.clone(), source: e })?;
+        log::debug!("Instance lock {:?} taken by {}.", path, info.describe());
+        Ok(InstanceLock { _file: file, info_path })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The lock itself goes with the file handle
+        let _ = fs::remove_file(&self.info_path);
+    }
+}
+
+/// The tracker currently holding the lock, if any.
+pub fn running_tracker(app_config: &AppConfig) -> Option<RunningTracker> {
+    let dir = lock_dir(app_config);
+    let file = File::open(dir.join(LOCK_FILE_NAME)).ok()?;
+    match file.try_lock_shared() {
+        // Nobody holds it; released when `file` is dropped
+        Ok(()) => None,
+        Err(TryLockError::WouldBlock) => Some(read_info(dir).unwrap_or_default()),
+        Err(TryLockError::Error(e)) => {
+            log::debug!("Cannot check the instance lock: {}", e);
+            None
+        }
+    }
+}
+
+fn lock_dir(app_config: &AppConfig) -> &Path {
+    app_config.config_file_path.parent().unwrap_or(Path::new("."))
+}
+
+fn read_info(dir: &Path) -> Option<RunningTracker> {
+    let json = fs::read_to_string(dir.join(INFO_FILE_NAME)).ok()?;
+    serde_json::from_str(&json).ok()
+}
@@ -1,5 +1,51 @@
-// In src/lib.rs or src/main.rs
+// src/lib.rs
+// Tracker core as a library; the CLI in main.rs is a thin wrapper around it.
+pub mod cli;
+pub mod clock;
+pub mod commands;
+pub mod config;
+pub mod config_lock;
+pub mod errors;
+pub mod persistence;
+pub mod types;
+pub mod utils;
+pub mod detection;
+pub mod tracker;
+pub mod sync;
+pub mod crypto;
+pub mod pseudonym;
+pub mod export;
+pub mod app_metadata;
+pub mod table;
+pub mod theme;
+pub mod timezone;
+pub mod profile_rules;
+pub mod tracking_hours;
+pub mod title_rules;
+pub mod saved_reports;
+pub mod schedule;
+pub mod webhook;
+pub mod mqtt;
+pub mod network;
+pub mod grpc;
+pub mod instance;
+pub mod gui;
+pub mod recap;
+pub mod event_tap;
+pub mod notify;
+pub mod llm;
+pub mod focus;
+pub mod hour_profile;
+pub mod habits;
+pub mod calendar;
+pub mod break_reminder;
+pub mod watchdog;
+pub mod loop_health;
+pub mod metrics;
+pub mod gaps;
+#[cfg(target_os = "windows")]
+mod windows_api;
+#[cfg(target_os = "windows")]
+pub mod tray;
 
-
-
-// Other pub use statements if needed
\ No newline at end of file
+pub use tracker::{DetectionCounters, Tracker, TrackerHandle};
@@ -0,0 +1,91 @@
+// src/llm.rs
+// One-shot text completions from a language model, used by `ask`: hosted (OpenAI or Anthropic, keys
+// from the keyring via `config set-key openai|anthropic`) or a local Ollama server at `ollama_url`,
+// which needs no key. Nothing is sent anywhere else.
+
+use crate::commands::set_key::load_api_key;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::types::{ApiKeyType, LlmProvider};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_TOKENS: u32 = 1024;
+/// How much of a non-JSON error body is quoted
+const ERROR_BODY_CHARS: usize = 200;
+
+/// The model's reply to `prompt` under the `system` instructions, at temperature 0.
+pub fn complete(app_config: &AppConfig, provider: LlmProvider, model: Option<&str>, system: &str, prompt: &str) -> AppResult<String> {
+    let model = model.unwrap_or(provider.default_model());
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Integration(e.to_string()))?;
+    let request = match provider {
+        LlmProvider::OpenAI => client
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth(load_api_key(app_config, ApiKeyType::OpenAI)?)
+            .json(&json!({
+                "model": model,
+                "temperature": 0,
+                "max_tokens": MAX_TOKENS,
+                "messages": [
+                    { "role": "system", "content": system },
+                    { "role": "user", "content": prompt },
+                ],
+            })),
+        LlmProvider::Anthropic => client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", load_api_key(app_config, ApiKeyType::Anthropic)?)
+            .header("anthropic-version", "2023-06-01")
+            .json(&json!({
+                "model": model,
+                "temperature": 0,
+                "max_tokens": MAX_TOKENS,
+                "system": system,
+                "messages": [{ "role": "user", "content": prompt }],
+            })),
+        LlmProvider::Ollama => client.post(format!("{}/api/chat", app_config.ollama_url)).json(&json!({
+            "model": model,
+            "stream": false,
+            "options": { "temperature": 0, "num_predict": MAX_TOKENS },
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": prompt },
+            ],
+        })),
+    };
+    let response = request.send().map_err(|e| AppError::Integration(format!("{} request failed: {}", provider_name(provider), e)))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .map_err(|e| AppError::Integration(format!("{} response could not be read: {}", provider_name(provider), e)))?;
+    if !status.is_success() {
+        return Err(AppError::Integration(format!("{} returned HTTP {} {}", provider_name(provider), status, error_message(&body))));
+    }
+    let body: Value = serde_json::from_str(&body)
+        .map_err(|e| AppError::Integration(format!("{} sent an unreadable response: {}", provider_name(provider), e)))?;
+    let text = match provider {
+        LlmProvider::OpenAI => body.pointer("/choices/0/message/content"),
+        LlmProvider::Anthropic => body.pointer("/content/0/text"),
+        LlmProvider::Ollama => body.pointer("/message/content"),
+    };
+    text.and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Integration(format!("{} returned no text", provider_name(provider))))
+}
+
+/// The message of an error response: its JSON `error` (Ollama's is a plain string), or the start of
+/// the body when a proxy or gateway answered with HTML or plain text.
+fn error_message(body: &str) -> String {
+    let json = serde_json::from_str::<Value>(body).unwrap_or_default();
+    match json.pointer("/error/message").or(json.get("error")).and_then(Value::as_str) {
+        Some(message) => message.to_string(),
+        None => body.trim().chars().take(ERROR_BODY_CHARS).collect(),
+    }
+}
+
+fn provider_name(provider: LlmProvider) -> String {
+    provider.api_key_type().map_or_else(|| "Ollama".to_string(), |key_type| key_type.to_string())
+}
@@ -0,0 +1,115 @@
+// src/loop_health.rs
+// Health of the tracking loop itself, for "why is my data full of holes": how long each detection
+// took, how long database writes took, polls the loop missed because it woke up late (a starved or
+// stopped process, a system sleep) and failed detections. The last `WINDOW` polls and writes are kept
+// in ring buffers; `status --verbose` (through the gRPC API) and `metrics_listen` report them.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Polls and writes kept for the latency figures (about 12 minutes of polls at the default 1s interval)
+pub const WINDOW: usize = 720;
+
+#[derive(Debug, Clone, Copy)]
+struct Poll {
+    // None when nothing was detected (paused, locked, outside tracking_hours)
+    detection: Option<Duration>,
+    missed: u64,
+    failed: bool,
+}
+
+/// Ring buffers of the detection and persistence threads, shared through the tracker's state.
+#[derive(Debug, Default)]
+pub struct LoopHealth {
+    polls: VecDeque<Poll>,
+    writes: VecDeque<Duration>,
+    totals: HealthTotals,
+}
+
+/// Counts since the tracker started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HealthTotals {
+    pub polls: u64,
+    pub missed_polls: u64,
+    pub detection_errors: u64,
+    pub writes: u64,
+}
+
+/// Average, 95th percentile and maximum of a latency window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub avg: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    fn of(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return LatencySummary::default();
+        }
+        samples.sort_unstable();
+        let total: Duration = samples.iter().sum();
+        LatencySummary {
+            avg: total / samples.len() as u32,
+            p95: samples[(samples.len() * 95).div_ceil(100) - 1],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// The last `WINDOW` polls and writes, summarized, and the totals since start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoopHealthReport {
+    /// Polls in the window
+    pub polls: u64,
+    /// Of the polls in the window that ran the detector
+    pub detection: LatencySummary,
+    /// Polls skipped because the loop woke up late, in the window
+    pub missed_polls: u64,
+    pub detection_errors: u64,
+    /// Interval writes (buffered flushes) in the window
+    pub writes: u64,
+    pub write: LatencySummary,
+    pub totals: HealthTotals,
+}
+
+impl LoopHealth {
+    /// One pass of the detection loop: `detection` is how long the detector took (with retries),
+    /// `missed` how many polls fit into the time it started late.
+    pub fn record_poll(&mut self, detection: Option<Duration>, missed: u64, failed: bool) {
+        if self.polls.len() == WINDOW {
+            self.polls.pop_front();
+        }
+        self.polls.push_back(Poll { detection, missed, failed });
+        self.totals.polls += 1;
+        self.totals.missed_polls += missed;
+        self.totals.detection_errors += u64::from(failed);
+    }
+
+    /// One flush of buffered intervals to the database, failed or not.
+    pub fn record_write(&mut self, latency: Duration) {
+        if self.writes.len() == WINDOW {
+            self.writes.pop_front();
+        }
+        self.writes.push_back(latency);
+        self.totals.writes += 1;
+    }
+
+    pub fn report(&self) -> LoopHealthReport {
+        LoopHealthReport {
+            polls: self.polls.len() as u64,
+            detection: LatencySummary::of(self.polls.iter().filter_map(|poll| poll.detection).collect()),
+            missed_polls: self.polls.iter().map(|poll| poll.missed).sum(),
+            detection_errors: self.polls.iter().filter(|poll| poll.failed).count() as u64,
+            writes: self.writes.len() as u64,
+            write: LatencySummary::of(self.writes.iter().copied().collect()),
+            totals: self.totals,
+        }
+    }
+}
+
+/// Polls missed by a loop pass that started `late` after it was due, polling every `interval`.
+pub fn missed_polls(late: Duration, interval: Duration) -> u64 {
+    if interval.is_zero() { 0 } else { (late.as_millis() / interval.as_millis().max(1)) as u64 }
+}
@@ -1,60 +1,16 @@
-// Declare the modules at the top level of the binary crate root
-pub mod commands;
-pub mod config;
-pub mod errors;
-// ACTION REQUIRED: Ensure only src/persistence.rs OR src/persistence/mod.rs exists, not both!
-pub mod persistence;
-pub mod types;
-pub mod utils;
-pub mod detection; // Assuming you have this
-#[cfg(target_os = "windows")]
-mod windows_api;
+// src/main.rs
+// CLI entry point; all functionality lives in the mouse_tracking library (src/lib.rs)
 // Now import items needed specifically in main.rs
 use clap::Parser;
 // use std::path::PathBuf; // REMOVED - Unused in main.rs scope
-use crate::{
+use mouse_tracking::{
+    cli::{Cli, Commands},
+    clock, commands, config, instance, persistence, theme, types,
     // We only import specific items needed for convenience or type annotations in main.rs itself.
-    errors::AppResult, // Keep AppResult as it's used for the return type
-    // errors::AppError, // REMOVED - Not used directly by name, only implicitly by `?` and AppResult
-    types::AggregationLevel, // Keep as it's used in Commands enum definition
-    // config::AppConfig, // REMOVED - Not used directly by name in this scope
+    errors::{AppError, AppResult}, // Keep AppResult as it's used for the return type
 };
-// ACTION REQUIRED: Add 'simple_logger = "..."' to your Cargo.toml dependencies
-use simple_logger;
 use log::LevelFilter; // Keep LevelFilter as it's used in setup_logging
 
-#[derive(Parser, Debug)]
-#[command(author, version, about = "Tracks application and window usage time.", long_about = None)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-
-    /// Increase logging verbosity (-v, -vv, -vvv)
-    #[arg(short, long, action = clap::ArgAction::Count)]
-    verbose: u8,
-}
-
-
-#[derive(clap::Subcommand, Debug)]
-enum Commands {
-    /// Start tracking application usage
-    Track,
-    /// Show usage statistics
-    Stats {
-        #[arg(short, long, value_enum, default_value_t = AggregationLevel::ByApplication)]
-        level: AggregationLevel,
-    },
-    /// Aggregate old data and cleanup database (usually run automatically)
-    Aggregate,
-    /// Initialize or update the database schema
-    InitDb,
-    /// Manage configuration (like API keys)
-    Config {
-        #[command(subcommand)]
-        config_command: types::ConfigCommand,
-    },
-}
-
 fn setup_logging(verbosity: u8) {
     let level = match verbosity {
         0 => LevelFilter::Warn,
@@ -71,8 +27,32 @@ fn setup_logging(verbosity: u8) {
 fn main() -> AppResult<()> {
     let cli = Cli::parse();
     setup_logging(cli.verbose);
-    let app_config = config::load_configuration()?;
+    // Generated from the CLI definitions alone; no config or database needed
+    match &cli.command {
+        Commands::Completions { shell } => return commands::completions::execute_completions(*shell),
+        Commands::Manpage { out_dir } => return commands::completions::execute_manpage(out_dir.as_deref()),
+        _ => {}
+    }
+    // Other profiles have their own config.json, so a locked default profile also locks switching to them
+    let other_profile = cli.profile.as_deref().is_some_and(|name| name != config::DEFAULT_PROFILE);
+    let profile_changes = matches!(cli.command, Commands::Profile { ref profile_command } if !matches!(profile_command, types::ProfileCommand::List));
+    let admin_checked = (other_profile || profile_changes) && mouse_tracking::config_lock::require_default_profile_admin("use or manage other profiles")?;
+    // Profiles are managed without loading one (the selected profile may not exist yet)
+    if let Commands::Profile { profile_command } = cli.command {
+        log::info!("Executing profile command: {:?}", profile_command);
+        return commands::profile::execute(cli.profile.as_deref(), profile_command);
+    }
+    let mut app_config = config::load_profile_configuration(cli.profile.as_deref())?;
+    if let Some(format) = cli.duration_format {
+        app_config.duration_format = format;
+    }
     log::debug!("Using configuration: {:?}", app_config);
+    // A locked configuration leaves the tracked user tracking, per-app stats and screen time
+    if let Some(action) = cli.command.admin_action()
+        && !admin_checked
+    {
+        mouse_tracking::config_lock::require_admin(&app_config, action)?;
+    }
 
     // Note: We remove the database initialization from *here* because
     // the track::execute function (formerly run::execute) handles its
@@ -80,30 +60,172 @@ fn main() -> AppResult<()> {
     // let data_path = app_config.database_path.clone(); // No longer needed here
 
     match cli.command {
-        Commands::Track => {
+        Commands::Track { tray, daemon, detector, script, emit_events, supervise, watchdog_heartbeat } => {
             // This now correctly calls the implementation in src/commands/track.rs
-            log::info!("Starting tracking mode...");
-            commands::track::execute(&app_config)?;
+            log::info!("Starting tracking mode (tray: {}, daemon: {}, detector: {:?}, supervise: {})...", tray, daemon, detector, supervise);
+            if supervise {
+                // Re-runs this command without --supervise as a child process
+                mouse_tracking::watchdog::supervise(&app_config, daemon)?;
+            } else {
+                commands::track::execute(&app_config, tray, daemon, detector, script.as_deref(), emit_events.as_deref(), watchdog_heartbeat.as_deref())?;
+            }
         }
-        Commands::Stats { level } => {
+        Commands::Stats { level, top, app_glob, title_contains, min_duration_secs, exclude_passive, user, all_users, interactive, max_title_width, period, split_weekend, reveal, stream } => {
             log::info!("Executing stats command with level: {:?}", level);
-             // Need data_path for stats
-             commands::stats::execute(&app_config.database_path, level)?;
+             // A shared database holds everyone's time; show your own unless asked otherwise
+             let user = match user {
+                 Some(user) => Some(user),
+                 None if app_config.shared_database && !all_users => Some(app_config.user_name.clone()),
+                 None => None,
+             };
+             let filter = types::StatsFilter { top, app_glob, title_contains, min_duration_secs, exclude_passive, user, stream };
+             if interactive && stream == types::IntervalStream::Audio {
+                 return Err(AppError::Config("--stream audio cannot be explored with --interactive".to_string()));
+             }
+             if interactive {
+                 commands::explore::execute(&app_config, &filter, period.unwrap_or(types::TimePeriod::Today))?;
+             } else {
+                 let display = types::DisplayOptions {
+                     max_title_width,
+                     color: theme::color_enabled(cli.no_color),
+                     duration_format: app_config.duration_format,
+                     split_weekend,
+                     reveal,
+                 };
+                 commands::stats::execute(&app_config, level, period, &filter, &display)?;
+             }
         }
-         Commands::Aggregate => {
+         Commands::Gui => {
+             log::info!("Opening the GUI window...");
+             mouse_tracking::gui::run(&app_config)?;
+         }
+         Commands::Aggregate { dry_run } => {
              log::info!("Executing aggregation and cleanup command...");
-             // Need data_path for aggregate
-             let mut conn = persistence::open_connection_ensure_path(&app_config.database_path)?;
-             persistence::aggregate_and_cleanup(&mut conn)?;
+             commands::aggregate::execute(&app_config, dry_run)?;
              log::info!("Aggregation finished.");
          }
+         Commands::Normalize { apply_history } => {
+             log::info!("Executing normalize command (apply history: {})", apply_history);
+             commands::normalize::execute(&app_config, apply_history)?;
+         }
          Commands::InitDb => {
              log::info!("Executing database initialization command...");
-             // Need data_path for InitDb
-             let mut conn = persistence::open_connection_ensure_path(&app_config.database_path)?;
-             persistence::initialize_db(&mut conn)?;
+             if let Some(tracker) = instance::running_tracker(&app_config) {
+                 eprintln!("Note: a tracker is running ({}); waiting for its writes when the database is busy.", tracker.describe());
+             }
+             persistence::open_storage(&app_config)?.initialize()?;
              log::info!("Database initialization check complete.");
          }
+         Commands::Doctor => {
+            log::info!("Executing doctor command...");
+            commands::doctor::execute(&app_config)?;
+         }
+         Commands::SelfStats => {
+            log::info!("Executing self-stats command...");
+            commands::self_stats::execute(&app_config)?;
+         }
+         Commands::Status { verbose } => {
+            log::info!("Executing status command...");
+            commands::status::execute(&app_config, verbose)?;
+         }
+         Commands::Autostart { autostart_command } => {
+            log::info!("Executing autostart command: {:?}", autostart_command);
+            commands::autostart::execute(&app_config, autostart_command)?;
+        }
+         Commands::Snapshots { snapshots_command } => {
+            log::info!("Executing snapshots command: {:?}", snapshots_command);
+            commands::snapshots::execute(&app_config, snapshots_command)?;
+        }
+         Commands::AddEntry { app, from, to, title, force } => {
+            log::info!("Executing add-entry command for '{}' from {} to {}", app, from, to);
+            commands::add_entry::execute(&app_config, commands::add_entry::ManualEntry { app, title, from, to, force })?;
+        }
+         Commands::Audit { date, min_minutes } => {
+            log::info!("Executing audit command for {}", date);
+            commands::audit::execute(&app_config, &date, min_minutes)?;
+        }
+         Commands::Gaps { date, min_minutes } => {
+            log::info!("Executing gaps command for {}", date);
+            commands::gaps::execute(&app_config, &date, min_minutes)?;
+        }
+         Commands::Purge { all, app, from, to, yes } => {
+            log::info!("Executing purge command");
+            commands::purge::execute(&app_config, all, app, from, to, yes)?;
+        }
+         Commands::Intervals { intervals_command } => {
+            log::info!("Executing intervals command: {:?}", intervals_command);
+            commands::intervals::execute(&app_config, intervals_command)?;
+        }
+         Commands::Archive { archive_command } => {
+            log::info!("Executing archive command: {:?}", archive_command);
+            commands::archive::execute(&app_config, archive_command)?;
+        }
+         Commands::App { app_command } => {
+            log::info!("Executing app command: {:?}", app_command);
+            commands::app::execute(&app_config, app_command)?;
+        }
+         Commands::Habit { habit_command } => {
+            log::info!("Executing habit command: {:?}", habit_command);
+            commands::habit::execute(&app_config, habit_command)?;
+        }
+         Commands::Calendar { calendar_command } => {
+            log::info!("Executing calendar command: {:?}", calendar_command);
+            commands::calendar::execute(&app_config, calendar_command)?;
+        }
+         Commands::Db { db_command } => {
+            log::info!("Executing db command: {:?}", db_command);
+            commands::db::execute(&app_config, db_command)?;
+        }
+         Commands::Sync { sync_command } => {
+            log::info!("Executing sync command: {:?}", sync_command);
+            commands::sync::execute(&app_config, sync_command)?;
+        }
+         Commands::Export { format, date, output } => {
+            log::info!("Executing export command: {:?} for {:?}", format, date);
+            commands::export::execute(&app_config, format, date, output)?;
+        }
+         Commands::Report { report_command, period, post } => {
+            log::info!("Executing report command: {:?} for {:?} (post: {:?})", report_command, period, post);
+            match report_command {
+                Some(report_command) => commands::report::execute_saved(&app_config, report_command, theme::color_enabled(cli.no_color))?,
+                None => commands::report::execute(&app_config, period, &post)?,
+            }
+        }
+         Commands::Schedule { schedule_command } => {
+            log::info!("Executing schedule command: {:?}", schedule_command);
+            commands::schedule::execute(&app_config, schedule_command)?;
+        }
+         Commands::ShellHook { shell } => {
+            log::info!("Executing shell-hook command for {:?}", shell);
+            commands::shell_hook::execute(&app_config, shell)?;
+        }
+         Commands::ShellReport { pid, cwd, command, exit } => {
+            commands::shell_hook::report(&app_config, pid, cwd, command, exit)?;
+        }
+         Commands::Query { sql, format, schema } => {
+            log::info!("Executing query command (format: {:?}, schema: {})", format, schema);
+            commands::query::execute(&app_config, sql.as_deref(), format, schema)?;
+        }
+         Commands::Screentime => {
+            log::info!("Executing screentime command");
+            commands::screentime::execute(&app_config, theme::color_enabled(cli.no_color))?;
+        }
+         Commands::Ask { question, provider, model } => {
+            let provider = provider.unwrap_or(app_config.llm_provider);
+            log::info!("Executing ask command (provider: {:?})", provider);
+            commands::ask::execute(&app_config, &question, provider, model.as_deref(), &clock::SystemClock)?;
+        }
+         Commands::Toggl { toggl_command } => {
+            log::info!("Executing toggl command: {:?}", toggl_command);
+            commands::toggl::execute(&app_config, toggl_command)?;
+        }
+         Commands::Completions { .. } | Commands::Manpage { .. } | Commands::Profile { .. } => {
+            unreachable!("handled before loading the configuration")
+        }
+         Commands::Update { from_file } => {
+            log::info!("Executing update command (from file: {:?})", from_file);
+            commands::update::execute(&app_config, from_file.as_deref())?;
+        }
          Commands::Config { config_command } => {
             log::info!("Executing config command: {:?}", config_command);
             commands::set_key::execute_config_command(&app_config, config_command)?;
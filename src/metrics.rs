@@ -0,0 +1,147 @@
+// src/metrics.rs
+// Prometheus text endpoint (`GET /metrics`) served by `track` when `metrics_listen` is set: detection
+// counters and the health of the tracking loop (`loop_health`) for dashboards and alerts. A plain
+// HTTP/1.1 responder on a thread of its own, one request per connection; needs no cargo feature.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::loop_health::LatencySummary;
+use crate::tracker::TrackerHandle;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A scraper that sends nothing is dropped after this long
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+/// Request heads longer than this are refused
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// The endpoint's thread; `stop` (or dropping it) shuts it down.
+pub struct MetricsServer {
+    addr: SocketAddr,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// Binds `metrics_listen` and starts answering; Ok(None) if it is not set.
+    pub fn start(app_config: &AppConfig, handle: TrackerHandle) -> AppResult<Option<Self>> {
+        let Some(addr) = app_config.metrics_listen else { return Ok(None) };
+        let listener = TcpListener::bind(addr).map_err(|e| AppError::Config(format!("Cannot listen on metrics_listen {}: {}", addr, e)))?;
+        let addr = listener.local_addr().map_err(|e| AppError::Config(format!("Cannot listen on metrics_listen {}: {}", addr, e)))?;
+        let stopping = Arc::new(AtomicBool::new(false));
+        let flag = stopping.clone();
+        let thread = thread::Builder::new()
+            .name("metrics".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if flag.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(e) = respond(stream, &handle) {
+                                log::debug!("Metrics request failed: {}", e);
+                            }
+                        }
+                        Err(e) => log::warn!("Accepting a metrics connection failed: {}", e),
+                    }
+                }
+            })
+            .map_err(|e| AppError::Unexpected(format!("Failed to spawn metrics thread: {}", e)))?;
+        log::info!("Metrics endpoint listening on http://{}/metrics", addr);
+        Ok(Some(MetricsServer { addr, stopping, thread: Some(thread) }))
+    }
+
+    /// Stops accepting requests and waits for the thread to finish.
+    pub fn stop(mut self) {
+        self.shutdown_and_join();
+    }
+
+    fn shutdown_and_join(&mut self) {
+        let Some(thread) = self.thread.take() else { return };
+        self.stopping.store(true, Ordering::SeqCst);
+        // The thread is blocked in accept; a connection of our own wakes it up
+        let mut wake = self.addr;
+        if wake.ip().is_unspecified() {
+            wake.set_ip(if wake.is_ipv4() { Ipv4Addr::LOCALHOST.into() } else { Ipv6Addr::LOCALHOST.into() });
+        }
+        match TcpStream::connect_timeout(&wake, REQUEST_TIMEOUT) {
+            Ok(_) => {
+                if thread.join().is_err() {
+                    log::error!("Metrics thread panicked");
+                }
+            }
+            Err(e) => log::warn!("Could not stop the metrics endpoint cleanly: {}", e),
+        }
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.shutdown_and_join();
+    }
+}
+
+fn respond(mut stream: TcpStream, handle: &TrackerHandle) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+    let (status, content_type, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", "text/plain; version=0.0.4; charset=utf-8", render(handle)),
+        (Some("GET"), _) => ("404 Not Found", "text/plain; charset=utf-8", "Not found; metrics are at /metrics\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", "Only GET is supported\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+/// The tracker's counters and loop health in the Prometheus text format.
+pub fn render(handle: &TrackerHandle) -> String {
+    let counters = handle.detection_counters();
+    let health = handle.loop_health();
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: u64| {
+        let _ = writeln!(text, "# HELP mouse_tracking_{} {}\n# TYPE mouse_tracking_{} {}\nmouse_tracking_{} {}", name, help, name, kind, name, value);
+    };
+    metric("running", "gauge", "1 while the tracker runs.", u64::from(handle.is_running()));
+    metric("paused", "gauge", "1 while recording is paused.", u64::from(handle.is_paused()));
+    metric("polls_total", "counter", "Passes of the detection loop.", health.totals.polls);
+    metric("missed_polls_total", "counter", "Polls skipped because the loop woke up late (sleep, starved or stopped process).", health.totals.missed_polls);
+    metric("detection_errors_total", "counter", "Polls whose detection failed.", health.totals.detection_errors);
+    metric("detection_retries_total", "counter", "Detection attempts repeated after a failure within a poll.", counters.retries);
+    metric("bridged_polls_total", "counter", "Failed polls that kept the last detected activity.", counters.bridged_polls);
+    metric("unrecorded_polls_total", "counter", "Failed polls that recorded nothing.", counters.failed_polls);
+    metric("db_writes_total", "counter", "Flushes of buffered intervals to the database.", health.totals.writes);
+    latency(&mut text, "detection_latency_seconds", "Detection time per poll over the last polls.", &health.detection);
+    latency(&mut text, "db_write_latency_seconds", "Database write time over the last writes.", &health.write);
+    text
+}
+
+fn latency(text: &mut String, name: &str, help: &str, summary: &LatencySummary) {
+    let _ = writeln!(text, "# HELP mouse_tracking_{} {}\n# TYPE mouse_tracking_{} gauge", name, help, name);
+    for (stat, value) in [("avg", summary.avg), ("p95", summary.p95), ("max", summary.max)] {
+        let _ = writeln!(text, "mouse_tracking_{}{{stat=\"{}\"}} {}", name, stat, value.as_secs_f64());
+    }
+}
@@ -0,0 +1,207 @@
+// src/mqtt.rs
+// Publishes the current activity and idle state to an MQTT broker (`mqtt` in config.json),
+// e.g. for Home Assistant automations. Messages are retained so new subscribers get the
+// latest state. Needs the `mqtt` cargo feature.
+
+use crate::config::AppConfig;
+use crate::detection::ActivityInfo;
+
+#[cfg(feature = "mqtt")]
+pub use enabled::{MqttPublisher, test_credentials};
+
+#[cfg(not(feature = "mqtt"))]
+/// Stand-in when the `mqtt` feature is off: warns if MQTT is configured and publishes nothing.
+#[derive(Debug)]
+pub struct MqttPublisher;
+
+#[cfg(not(feature = "mqtt"))]
+impl MqttPublisher {
+    pub fn start(app_config: &AppConfig) -> Option<Self> {
+        if app_config.mqtt.is_some() {
+            eprintln!("Warning: \"mqtt\" is configured but this build has no MQTT support (rebuild with --features mqtt).");
+        }
+        None
+    }
+
+    pub fn publish(&mut self, _activity: Option<&ActivityInfo>, _idle: bool) {}
+}
+
+#[cfg(not(feature = "mqtt"))]
+pub fn test_credentials(_app_config: &AppConfig) -> crate::errors::AppResult<()> {
+    Err(crate::errors::AppError::Config("This build has no MQTT support (rebuild with --features mqtt)".to_string()))
+}
+
+#[cfg(feature = "mqtt")]
+mod enabled {
+    use super::{ActivityInfo, AppConfig};
+    use crate::commands::set_key::load_api_key;
+    use crate::config::MqttConfig;
+    use crate::errors::{AppError, AppResult};
+    use crate::types::ApiKeyType;
+    use rumqttc::{Client, LastWill, MqttOptions, QoS, Transport};
+    use serde::Serialize;
+    use std::thread;
+    use std::time::Duration;
+
+    const KEEP_ALIVE: Duration = Duration::from_secs(30);
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+    /// Requests queued while the broker is unreachable; newer states are dropped beyond this
+    const QUEUE_CAPACITY: usize = 16;
+    /// How long `test_credentials` waits for the broker's answer
+    const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Body of `<prefix>/activity` (`null` while nothing is tracked, e.g. paused)
+    #[derive(Debug, Serialize)]
+    struct ActivityMessage<'a> {
+        app_name: &'a str,
+        main_title: &'a str,
+        detailed_title: &'a str,
+        virtual_desktop: Option<&'a str>,
+        passive: bool,
+    }
+
+    /// Publishing side used by the detection thread. The network runs on its own thread and
+    /// publishes never wait for it, so a slow or missing broker does not delay detection.
+    pub struct MqttPublisher {
+        client: Client,
+        topic_prefix: String,
+        last_activity: Option<String>,
+        last_idle: Option<bool>,
+    }
+
+    impl MqttPublisher {
+        /// Connects in the background when MQTT is configured; None if it is not or the URL is invalid.
+        pub fn start(app_config: &AppConfig) -> Option<Self> {
+            let mqtt = app_config.mqtt.as_ref()?;
+            let Some(mut options) = broker_options(app_config, mqtt) else {
+                eprintln!("Warning: invalid mqtt.broker_url {:?} (expected mqtt://host[:port] or mqtts://host[:port]).", mqtt.broker_url);
+                return None;
+            };
+            let topic_prefix = mqtt
+                .topic_prefix
+                .clone()
+                .unwrap_or_else(|| format!("{}/{}", app_config.app_name, app_config.device_id))
+                .trim_end_matches('/')
+                .to_string();
+            // The broker marks us offline if the tracker dies without saying goodbye
+            options.set_last_will(LastWill::new(format!("{}/status", topic_prefix), "offline", QoS::AtLeastOnce, true));
+            if let Some(username) = &mqtt.username {
+                match load_api_key(app_config, ApiKeyType::Mqtt) {
+                    Ok(password) => {
+                        options.set_credentials(username, password);
+                    }
+                    Err(e) => eprintln!("Warning: MQTT password unavailable ({}); connecting without it.", e),
+                }
+            }
+
+            let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+            let spawned = thread::Builder::new().name("mqtt".to_string()).spawn(move || {
+                // Iterating drives the connection (and reconnects); it ends once the client is dropped
+                for notification in connection.iter() {
+                    if let Err(e) = notification {
+                        log::warn!("MQTT connection error: {}", e);
+                        thread::sleep(RECONNECT_DELAY);
+                    }
+                }
+                log::debug!("MQTT thread stopped.");
+            });
+            if let Err(e) = spawned {
+                eprintln!("Warning: failed to start MQTT thread: {}", e);
+                return None;
+            }
+
+            let publisher = MqttPublisher { client, topic_prefix, last_activity: None, last_idle: None };
+            publisher.send("status", "online".to_string());
+            Some(publisher)
+        }
+
+        /// Publishes the activity and idle state when they differ from the last published ones.
+        pub fn publish(&mut self, activity: Option<&ActivityInfo>, idle: bool) {
+            let message = activity.map(|info| ActivityMessage {
+                app_name: &info.app_name,
+                main_title: &info.main_title,
+                detailed_title: &info.detailed_title,
+                virtual_desktop: info.virtual_desktop.as_deref(),
+                passive: info.passive,
+            });
+            let payload = serde_json::to_string(&message).unwrap_or_else(|_| "null".to_string());
+            if self.last_activity.as_deref() != Some(payload.as_str()) {
+                self.send("activity", payload.clone());
+                self.last_activity = Some(payload);
+            }
+            if self.last_idle != Some(idle) {
+                // Home Assistant binary_sensor default payloads
+                self.send("idle", if idle { "ON" } else { "OFF" }.to_string());
+                self.last_idle = Some(idle);
+            }
+        }
+
+        fn send(&self, topic: &str, payload: String) {
+            let topic = format!("{}/{}", self.topic_prefix, topic);
+            if let Err(e) = self.client.try_publish(&topic, QoS::AtLeastOnce, true, payload) {
+                log::debug!("MQTT publish to {} dropped: {}", topic, e);
+            }
+        }
+    }
+
+    impl Drop for MqttPublisher {
+        fn drop(&mut self) {
+            self.send("status", "offline".to_string());
+            let _ = self.client.try_disconnect();
+        }
+    }
+
+    /// Connects once with the stored password and waits for the broker to accept it (`config test-key mqtt`).
+    pub fn test_credentials(app_config: &AppConfig) -> AppResult<()> {
+        let mqtt = app_config
+            .mqtt
+            .as_ref()
+            .ok_or_else(|| AppError::Config("No MQTT broker configured; set \"mqtt\" in config.json.".to_string()))?;
+        let username = mqtt
+            .username
+            .as_ref()
+            .ok_or_else(|| AppError::Config("mqtt.username is not set, so the MQTT password is not used.".to_string()))?;
+        let mut options = broker_options(app_config, mqtt)
+            .ok_or_else(|| AppError::Config(format!("Invalid mqtt.broker_url {:?}", mqtt.broker_url)))?;
+        options.set_credentials(username, load_api_key(app_config, ApiKeyType::Mqtt)?);
+        let (_client, mut connection) = Client::new(options, 1);
+        // The first event is the broker's ConnAck; a refused login comes back as an error
+        match connection.recv_timeout(TEST_TIMEOUT) {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(AppError::Integration(format!("MQTT broker refused the connection: {}", e))),
+            Err(_) => Err(AppError::Integration(format!("No answer from the MQTT broker within {}s", TEST_TIMEOUT.as_secs()))),
+        }
+    }
+
+    /// Options for `mqtt.broker_url` without credentials or last will; None if the URL is invalid.
+    fn broker_options(app_config: &AppConfig, mqtt: &MqttConfig) -> Option<MqttOptions> {
+        let (tls_scheme, host, port) = parse_broker_url(&mqtt.broker_url)?;
+        let client_id = mqtt.client_id.clone().unwrap_or_else(|| format!("{}-{}", app_config.app_name, app_config.device_id));
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(KEEP_ALIVE);
+        if mqtt.tls.unwrap_or(tls_scheme) {
+            options.set_transport(Transport::tls_with_default_config());
+        }
+        Some(options)
+    }
+
+    /// `mqtts://broker:8883` -> (true, "broker", 8883); the port defaults to 1883 / 8883.
+    fn parse_broker_url(url: &str) -> Option<(bool, String, u16)> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("mqtts://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("mqtt://") {
+            (false, rest)
+        } else {
+            (false, url)
+        };
+        let authority = rest.split('/').next()?.trim();
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, if tls { 8883 } else { 1883 }),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some((tls, host.to_string(), port))
+    }
+}
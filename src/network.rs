@@ -0,0 +1,90 @@
+// src/network.rs
+// The tracker's async core: one tokio runtime for everything that talks to the network (auto-sync,
+// calendar refresh, chat summary posts, scheduled sync jobs, the gRPC API). Detection and persistence
+// stay on their own threads, so a slow or unreachable server never delays writing intervals.
+// Network jobs are blocking calls (reqwest's blocking client, database connections of their own) and
+// run on the runtime's blocking pool; at most one run per job name is in flight.
+
+use crate::errors::{AppError, AppResult};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long shutting down waits for network jobs still running (requests have their own timeouts).
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Owns the runtime; dropping it shuts the runtime down, abandoning jobs still running after `SHUTDOWN_GRACE`.
+pub struct NetworkRuntime {
+    runtime: Option<tokio::runtime::Runtime>,
+    handle: NetworkHandle,
+}
+
+impl NetworkRuntime {
+    pub fn start() -> AppResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("network")
+            .enable_all()
+            .build()
+            .map_err(|e| AppError::Unexpected(format!("Failed to create the network runtime: {}", e)))?;
+        let handle = NetworkHandle { runtime: runtime.handle().clone(), running: Arc::new(Mutex::new(HashSet::new())) };
+        Ok(NetworkRuntime { runtime: Some(runtime), handle })
+    }
+
+    pub fn handle(&self) -> NetworkHandle {
+        self.handle.clone()
+    }
+}
+
+impl Drop for NetworkRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_timeout(SHUTDOWN_GRACE);
+        }
+    }
+}
+
+/// Cheap to clone; hands work to the network runtime from any thread.
+#[derive(Clone)]
+pub struct NetworkHandle {
+    runtime: tokio::runtime::Handle,
+    // Names of the jobs running now
+    running: Arc<Mutex<HashSet<&'static str>>>,
+}
+
+impl NetworkHandle {
+    /// Runs `job` on the runtime's blocking pool and returns at once. False (and `job` is dropped)
+    /// while the previous run of `name` has not finished, so a hanging server cannot pile up runs.
+    pub fn run_job<F>(&self, name: &'static str, job: F) -> bool
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if !self.running.lock().unwrap_or_else(|e| e.into_inner()).insert(name) {
+            log::warn!("Network job '{}' is still running from last time; skipping this run.", name);
+            return false;
+        }
+        let running = RunningJob { name, running: self.running.clone() };
+        self.runtime.spawn_blocking(move || {
+            let _running = running;
+            job();
+        });
+        true
+    }
+
+    /// The tokio runtime, for async servers and clients (`grpc::GrpcServer`).
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+}
+
+/// Clears the job's name when it ends, even by panicking.
+struct RunningJob {
+    name: &'static str,
+    running: Arc<Mutex<HashSet<&'static str>>>,
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(self.name);
+    }
+}
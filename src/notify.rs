@@ -0,0 +1,195 @@
+// src/notify.rs
+// Posts the summary of a period (total, top apps, `notify.categories`) to Slack or Discord incoming
+// webhooks: on demand with `report --post`, or daily at `notify.post_time` from a running tracker.
+
+use crate::app_metadata::AppAliases;
+use crate::config::{AppConfig, NotifyConfig};
+use crate::errors::{AppError, AppResult};
+use crate::clock::SystemClock;
+use crate::network::NetworkHandle;
+use crate::persistence::{self, Storage};
+use crate::timezone::DailySchedule;
+use crate::types::{AggregatedResult, AggregationLevel, PostTarget, StatsFilter, TimePeriod};
+use crate::utils::{format_duration, glob_match};
+use serde_json::json;
+use std::time::Duration;
+
+/// Apps listed in the summary.
+const TOP_APPS: usize = 5;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Apps no category matches are added up under this name.
+const OTHER_CATEGORY: &str = "Other";
+
+/// Tracked time of a period, per app (friendly names, largest first) and per category.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub heading: String,
+    pub total_secs: i64,
+    pub apps: Vec<(String, i64)>,
+    /// Empty when no categories are configured
+    pub categories: Vec<(String, i64)>,
+    /// (break reminders shown, breaks taken after them); None when no reminder was shown
+    pub breaks: Option<(i64, i64)>,
+}
+
+/// Reads the totals of `period` ("Today" is the local day of `now`).
+pub fn summarize(config: &AppConfig, storage: &mut dyn Storage, period: TimePeriod, now: i64) -> AppResult<Summary> {
+    let AggregatedResult::ByApp(rows) = storage.query_stats(period, AggregationLevel::ByApplication, &StatsFilter::default())? else {
+        return Err(AppError::Unexpected("Unexpected stats result shape".to_string()));
+    };
+    let mut apps = AppAliases::load(storage)?.apply_to_totals(rows);
+    apps.retain(|(_, secs)| *secs > 0);
+    apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let heading = match period {
+        TimePeriod::Today => format!("{} ({}) on {}", period, config.timezone.date_string(now), config.device_id),
+        _ => format!("{} on {}", period, config.device_id),
+    };
+    let (start, end) = persistence::calculate_timestamps(period, &config.timezone, &SystemClock);
+    let breaks = storage.query_break_compliance(start, end)?;
+    Ok(Summary {
+        heading,
+        total_secs: apps.iter().map(|(_, secs)| secs).sum(),
+        categories: categorize(&config.notify, &apps),
+        apps,
+        breaks: (breaks.0 > 0).then_some(breaks),
+    })
+}
+
+fn categorize(notify: &NotifyConfig, apps: &[(String, i64)]) -> Vec<(String, i64)> {
+    if notify.categories.is_empty() {
+        return Vec::new();
+    }
+    let mut totals: Vec<(String, i64)> = Vec::new();
+    for (app, secs) in apps {
+        let name = notify
+            .categories
+            .iter()
+            .find(|category| category.name == *app || category.apps.iter().any(|glob| glob_match(glob, app)))
+            .map_or(OTHER_CATEGORY, |category| category.name.as_str());
+        match totals.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, total)) => *total += secs,
+            None => totals.push((name.to_string(), *secs)),
+        }
+    }
+    totals.sort_by_key(|(_, secs)| std::cmp::Reverse(*secs));
+    totals
+}
+
+/// The summary as chat markup: Slack mrkdwn or Discord markdown (None = plain text for the terminal).
+pub fn render(summary: &Summary, target: Option<PostTarget>, config: &AppConfig) -> String {
+    let format = config.duration_format;
+    let (bold, escape): (&str, fn(&str) -> String) = match target {
+        Some(PostTarget::Slack) => ("*", |text| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")),
+        Some(PostTarget::Discord) => ("**", |text| text.replace('*', "\\*").replace('_', "\\_").replace('`', "\\`")),
+        None => ("", str::to_string),
+    };
+    let mut lines = vec![format!(
+        "{}{}{}: {} tracked",
+        bold,
+        escape(&summary.heading),
+        bold,
+        format_duration(summary.total_secs, format)
+    )];
+    if summary.apps.is_empty() {
+        lines.push("Nothing tracked.".to_string());
+        return lines.join("\n");
+    }
+    lines.push("Top apps:".to_string());
+    for (index, (app, secs)) in summary.apps.iter().take(TOP_APPS).enumerate() {
+        lines.push(format!("{}. {} — {}", index + 1, escape(app), format_duration(*secs, format)));
+    }
+    if !summary.categories.is_empty() {
+        lines.push("Categories:".to_string());
+        for (category, secs) in &summary.categories {
+            lines.push(format!("• {} — {}", escape(category), format_duration(*secs, format)));
+        }
+    }
+    if let Some((reminders, taken)) = summary.breaks {
+        lines.push(format!("Breaks: {} of {} reminder(s) followed by a break ({}%)", taken, reminders, taken * 100 / reminders));
+    }
+    lines.join("\n")
+}
+
+/// Targets with a webhook URL in the `notify` section.
+pub fn configured_targets(notify: &NotifyConfig) -> Vec<PostTarget> {
+    [PostTarget::Slack, PostTarget::Discord].into_iter().filter(|target| webhook_url(notify, *target).is_some()).collect()
+}
+
+fn webhook_url(notify: &NotifyConfig, target: PostTarget) -> Option<&str> {
+    match target {
+        PostTarget::Slack => notify.slack_webhook_url.as_deref(),
+        PostTarget::Discord => notify.discord_webhook_url.as_deref(),
+    }
+}
+
+/// Sends the summary to one incoming webhook.
+pub fn post(config: &AppConfig, summary: &Summary, target: PostTarget) -> AppResult<()> {
+    let url = webhook_url(&config.notify, target).ok_or_else(|| {
+        AppError::Config(format!("No {} webhook URL: set \"notify\": {{\"{}_webhook_url\": ...}} in config.json", target, target.to_string().to_lowercase()))
+    })?;
+    let text = render(summary, Some(target), config);
+    let body = match target {
+        PostTarget::Slack => json!({ "text": text }),
+        PostTarget::Discord => json!({ "content": text }),
+    };
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Integration(e.to_string()))?;
+    let response = client
+        .post(url)
+        .json(&body)
+        .send()
+        .map_err(|e| AppError::Integration(format!("{} webhook request failed: {}", target, e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        return Err(AppError::Integration(format!("{} webhook answered HTTP {} {}", target, status, body)));
+    }
+    Ok(())
+}
+
+/// Posts today's summary at `notify.post_time`; scheduled from the persistence thread like the daily recap.
+#[derive(Debug)]
+pub struct DailyPost {
+    config: AppConfig,
+    schedule: DailySchedule,
+}
+
+impl DailyPost {
+    /// None without `notify.post_time` or without any webhook URL.
+    pub fn from_config(config: &AppConfig, now: i64) -> Option<Self> {
+        let minute = config.notify_post_minute?;
+        if configured_targets(&config.notify).is_empty() {
+            log::warn!("notify.post_time is set but no Slack or Discord webhook URL is; nothing will be posted.");
+            return None;
+        }
+        Some(DailyPost { config: config.clone(), schedule: DailySchedule::new(&config.timezone, minute, now) })
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        self.schedule.is_due(now)
+    }
+
+    /// Reads today's summary, then posts it on the network runtime so slow webhooks never delay writes.
+    pub fn run(&mut self, storage: &mut dyn Storage, network: &NetworkHandle, now: i64) {
+        self.schedule.mark_done(now);
+        let summary = match summarize(&self.config, storage, TimePeriod::Today, now) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("[Persist] Daily summary failed: {}", e);
+                return;
+            }
+        };
+        let config = self.config.clone();
+        network.run_job("notify", move || {
+            for target in configured_targets(&config.notify) {
+                match post(&config, &summary, target) {
+                    Ok(()) => log::info!("Posted the daily summary to {}.", target),
+                    Err(e) => log::warn!("Posting the daily summary to {} failed: {}", target, e),
+                }
+            }
+        });
+    }
+}
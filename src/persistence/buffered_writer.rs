@@ -0,0 +1,183 @@
+// src/persistence/buffered_writer.rs
+
+use super::Storage;
+use crate::types::NewInterval;
+use crate::errors::AppResult;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use log::{debug, warn};
+
+/// Opaque reference to an interval handed out by the writer.
+/// The database row id is only known once the interval has been flushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntervalHandle(u64);
+
+#[derive(Debug)]
+enum PendingOp {
+    // Boxed: an interval is much larger than the other operations
+    Insert { handle: IntervalHandle, interval: Box<QueuedInterval>, end_time: Option<i64> },
+    Finalize { row_id: i64, end_time: i64 },
+    Heartbeat { row_id: i64, heartbeat_time: i64 },
+}
+
+/// Owned copy of a `NewInterval` waiting in the queue.
+#[derive(Debug)]
+struct QueuedInterval {
+    app_name: String,
+    main_title: String,
+    detailed_title: String,
+    virtual_desktop: Option<String>,
+    repo: Option<String>,
+    executable_path: Option<String>,
+    command_line: Option<String>,
+    ticket: Option<String>,
+    session_type: Option<String>,
+    remote_host: Option<String>,
+    document: Option<String>,
+    passive: bool,
+    start_time: i64,
+    device_id: String,
+    user_name: String,
+}
+
+/// Queues interval writes in memory and flushes them in a single transaction
+/// every `flush_interval` (or on shutdown), instead of hitting SQLite on every switch.
+#[derive(Debug)]
+pub struct BufferedIntervalWriter {
+    flush_interval: Duration,
+    last_flush: Instant,
+    next_handle: u64,
+    pending: Vec<PendingOp>,
+    // Open intervals that were already written, so later updates can target their row
+    flushed_rows: HashMap<IntervalHandle, i64>,
+}
+
+impl BufferedIntervalWriter {
+    pub fn new(flush_interval: Duration) -> Self {
+        BufferedIntervalWriter {
+            flush_interval,
+            last_flush: Instant::now(),
+            next_handle: 0,
+            pending: Vec::new(),
+            flushed_rows: HashMap::new(),
+        }
+    }
+
+    /// Queues a new open interval and returns a handle to finalize it later.
+    pub fn start_interval(&mut self, interval: &NewInterval) -> IntervalHandle {
+        let handle = IntervalHandle(self.next_handle);
+        self.next_handle += 1;
+        let interval = QueuedInterval {
+            app_name: interval.app_name.to_string(),
+            main_title: interval.main_title.to_string(),
+            detailed_title: interval.detailed_title.to_string(),
+            virtual_desktop: interval.virtual_desktop.map(str::to_string),
+            repo: interval.repo.map(str::to_string),
+            executable_path: interval.executable_path.map(str::to_string),
+            command_line: interval.command_line.map(str::to_string),
+            ticket: interval.ticket.map(str::to_string),
+            session_type: interval.session_type.map(str::to_string),
+            remote_host: interval.remote_host.map(str::to_string),
+            document: interval.document.map(str::to_string),
+            passive: interval.passive,
+            start_time: interval.start_time,
+            device_id: interval.device_id.to_string(),
+            user_name: interval.user_name.to_string(),
+        };
+        self.pending.push(PendingOp::Insert { handle, interval: Box::new(interval), end_time: None });
+        handle
+    }
+
+    /// Sets the end time of an interval, merging into its queued insert when it was not flushed yet.
+    pub fn finalize_interval(&mut self, handle: IntervalHandle, end: i64) {
+        if let Some(end_time) = self.pending_insert_end(handle) {
+            *end_time = Some(end);
+        } else if let Some(row_id) = self.flushed_rows.remove(&handle) {
+            self.pending.push(PendingOp::Finalize { row_id, end_time: end });
+        } else {
+            warn!("Finalize requested for unknown interval handle {:?}", handle);
+        }
+    }
+
+    /// Queues a heartbeat for an open interval. Intervals still waiting in the queue need none.
+    pub fn heartbeat(&mut self, handle: IntervalHandle, heartbeat_time: i64) {
+        if let Some(&row_id) = self.flushed_rows.get(&handle) {
+            self.pending.push(PendingOp::Heartbeat { row_id, heartbeat_time });
+        }
+    }
+
+    pub fn is_flush_due(&self, now: Instant) -> bool {
+        !self.pending.is_empty() && now.duration_since(self.last_flush) >= self.flush_interval
+    }
+
+    /// Flushes when the flush interval has elapsed. Errors keep the queue intact for the next attempt.
+    pub fn flush_if_due(&mut self, storage: &mut dyn Storage, now: Instant) -> AppResult<usize> {
+        if self.is_flush_due(now) { self.flush(storage) } else { Ok(0) }
+    }
+
+    /// Writes all queued operations in one transaction and returns how many were applied.
+    pub fn flush(&mut self, storage: &mut dyn Storage) -> AppResult<usize> {
+        self.last_flush = Instant::now();
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        storage.begin_batch()?;
+        let new_rows = match self.apply_pending(storage) {
+            Ok(new_rows) => new_rows,
+            Err(e) => {
+                if let Err(rollback_err) = storage.rollback_batch() {
+                    warn!("Rollback of failed interval flush failed: {}", rollback_err);
+                }
+                return Err(e);
+            }
+        };
+        storage.commit_batch()?;
+        let written = self.pending.len();
+        self.pending.clear();
+        self.flushed_rows.extend(new_rows);
+        debug!("Flushed {} buffered interval operation(s).", written);
+        Ok(written)
+    }
+
+    /// Applies the queue inside the open batch; returns row ids of intervals left open.
+    fn apply_pending(&self, storage: &mut dyn Storage) -> AppResult<Vec<(IntervalHandle, i64)>> {
+        let mut new_rows: Vec<(IntervalHandle, i64)> = Vec::new();
+        for op in &self.pending {
+            match op {
+                PendingOp::Insert { handle, interval, end_time } => {
+                    let row_id = storage.insert_interval(&NewInterval {
+                        app_name: &interval.app_name,
+                        main_title: &interval.main_title,
+                        detailed_title: &interval.detailed_title,
+                        virtual_desktop: interval.virtual_desktop.as_deref(),
+                        repo: interval.repo.as_deref(),
+                        executable_path: interval.executable_path.as_deref(),
+                        command_line: interval.command_line.as_deref(),
+                        ticket: interval.ticket.as_deref(),
+                        session_type: interval.session_type.as_deref(),
+                        remote_host: interval.remote_host.as_deref(),
+                        document: interval.document.as_deref(),
+                        passive: interval.passive,
+                        start_time: interval.start_time,
+                        device_id: &interval.device_id,
+                        user_name: &interval.user_name,
+                    })?;
+                    match end_time {
+                        Some(end) => storage.finalize_interval(row_id, *end)?,
+                        None => new_rows.push((*handle, row_id)),
+                    }
+                }
+                PendingOp::Finalize { row_id, end_time } => storage.finalize_interval(*row_id, *end_time)?,
+                PendingOp::Heartbeat { row_id, heartbeat_time } => storage.heartbeat_interval(*row_id, *heartbeat_time)?,
+            }
+        }
+        Ok(new_rows)
+    }
+
+    fn pending_insert_end(&mut self, handle: IntervalHandle) -> Option<&mut Option<i64>> {
+        self.pending.iter_mut().find_map(|op| match op {
+            PendingOp::Insert { handle: h, end_time, .. } if *h == handle => Some(end_time),
+            _ => None,
+        })
+    }
+}
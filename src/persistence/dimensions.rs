@@ -0,0 +1,498 @@
+// src/persistence/dimensions.rs
+// The summary tables aggregation fills from the raw intervals: time per local hour or day, app and one
+// more property of the intervals (title, virtual desktop, device, repo, ...). They share one shape, so
+// their schema, their aggregation statement and (for the `stats` levels that read them) their stats
+// query are generated for both backends from `SUMMARIES` instead of a hand-copied file per table.
+
+use crate::timezone::ReportTimeZone;
+use crate::types::{AggregatedResult, AggregationLevel, AggregationReport};
+
+/// The buckets a summary splits time into; the bucket column holds the bucket's start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bucket {
+    /// Local hours (`hour_timestamp`)
+    Hour,
+    /// Local days starting at `day_start_hour` (`day_timestamp`)
+    Day,
+}
+
+impl Bucket {
+    pub const ALL: [Bucket; 2] = [Bucket::Hour, Bucket::Day];
+
+    pub fn column(self) -> &'static str {
+        match self {
+            Bucket::Hour => "hour_timestamp",
+            Bucket::Day => "day_timestamp",
+        }
+    }
+
+    /// The SQLite temp table aggregation reads this bucket's `spans` from
+    pub fn table(self) -> &'static str {
+        match self {
+            Bucket::Hour => "aggregation_hours",
+            Bucket::Day => "aggregation_days",
+        }
+    }
+
+    /// Start and end of each bucket overlapping [from, until), taken from the zone rather than a fixed
+    /// length: days last 23 or 25 hours around DST changes, and half-hour zones shift their hours.
+    pub fn spans(self, timezone: &ReportTimeZone, from: i64, until: i64) -> Vec<(i64, i64)> {
+        let mut start = match self {
+            Bucket::Hour => timezone.hour_start(from),
+            Bucket::Day => timezone.day_start(from),
+        };
+        let mut spans = Vec::new();
+        while start < until {
+            let end = match self {
+                Bucket::Hour => timezone.hour_start(start + 3600),
+                Bucket::Day => timezone.next_day_start(start),
+            };
+            spans.push((start, end));
+            start = end;
+        }
+        spans
+    }
+}
+
+/// The raw rows a summary is built from, each the raw interval `i`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Source {
+    /// `app_intervals`
+    Intervals,
+    /// The part of each of `app_intervals` overlapping a `calendar_events` row `e` (overlapping two
+    /// events at once counts for both)
+    Meetings,
+    /// `audio_intervals`, which have no passive time
+    Audio,
+}
+
+/// A `stats` level answered from a summary plus the raw, not yet aggregated intervals.
+pub(crate) struct StatsLevel {
+    pub level: AggregationLevel,
+    /// Names the level in warnings about unreadable rows ("by desktop")
+    pub label: &'static str,
+    /// The row label over the summary's columns, and what rows are grouped by
+    pub name: &'static str,
+    pub group_by: &'static str,
+    pub into_result: fn(Vec<(String, i64)>) -> AggregatedResult,
+}
+
+pub(crate) struct SummaryTable {
+    pub table: &'static str,
+    pub bucket: Bucket,
+    /// Key columns in primary key order (the bucket column follows), each with its value for a raw row
+    pub keys: &'static [(&'static str, &'static str)],
+    pub source: Source,
+    /// Raw rows left out of the summary entirely (`i.repo IS NOT NULL`: only time with a known repository)
+    pub condition: Option<&'static str>,
+    /// Where aggregation reports the rows it wrote
+    pub report_rows: fn(&mut AggregationReport) -> &mut usize,
+    pub stats: Option<StatsLevel>,
+}
+
+const APP: (&str, &str) = ("app_name", "i.app_name");
+
+pub(crate) const SUMMARIES: &[SummaryTable] = &[
+    SummaryTable {
+        table: "hourly_summary",
+        bucket: Bucket::Hour,
+        keys: &[APP, ("detailed_window_title", "COALESCE(i.detailed_window_title, '[No Detailed Title]')")],
+        source: Source::Intervals,
+        condition: None,
+        report_rows: |report| &mut report.hourly_rows,
+        stats: None,
+    },
+    SummaryTable {
+        table: "daily_summary",
+        bucket: Bucket::Day,
+        keys: &[APP, ("detailed_window_title", "COALESCE(i.detailed_window_title, '[No Detailed Title]')")],
+        source: Source::Intervals,
+        condition: None,
+        report_rows: |report| &mut report.daily_rows,
+        stats: None,
+    },
+    SummaryTable {
+        table: "daily_desktop_summary",
+        bucket: Bucket::Day,
+        keys: &[("virtual_desktop", "COALESCE(i.virtual_desktop, '[No Desktop]')"), APP],
+        source: Source::Intervals,
+        condition: None,
+        report_rows: |report| &mut report.desktop_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByDesktop,
+            label: "by desktop",
+            name: "virtual_desktop",
+            group_by: "virtual_desktop",
+            into_result: AggregatedResult::ByDesktop,
+        }),
+    },
+    SummaryTable {
+        table: "daily_device_summary",
+        bucket: Bucket::Day,
+        keys: &[("device_id", "COALESCE(i.device_id, '[Unknown Device]')"), APP],
+        source: Source::Intervals,
+        condition: None,
+        report_rows: |report| &mut report.device_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByDevice,
+            label: "by device",
+            name: "device_id",
+            group_by: "device_id",
+            into_result: AggregatedResult::ByDevice,
+        }),
+    },
+    SummaryTable {
+        table: "daily_repo_summary",
+        bucket: Bucket::Day,
+        keys: &[("repo", "i.repo"), APP],
+        source: Source::Intervals,
+        condition: Some("i.repo IS NOT NULL"),
+        report_rows: |report| &mut report.repo_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByRepo,
+            label: "by repo",
+            name: "repo",
+            group_by: "repo",
+            into_result: AggregatedResult::ByRepo,
+        }),
+    },
+    SummaryTable {
+        table: "daily_path_summary",
+        bucket: Bucket::Day,
+        keys: &[("executable_path", "i.executable_path"), APP],
+        source: Source::Intervals,
+        condition: Some("i.executable_path IS NOT NULL"),
+        report_rows: |report| &mut report.path_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByPath,
+            label: "by path",
+            name: "executable_path",
+            group_by: "executable_path",
+            into_result: AggregatedResult::ByPath,
+        }),
+    },
+    SummaryTable {
+        table: "daily_meeting_summary",
+        bucket: Bucket::Day,
+        keys: &[("meeting", "e.title"), APP],
+        source: Source::Meetings,
+        condition: None,
+        report_rows: |report| &mut report.meeting_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByMeeting,
+            label: "by meeting",
+            name: "meeting",
+            group_by: "meeting",
+            into_result: AggregatedResult::ByMeeting,
+        }),
+    },
+    SummaryTable {
+        table: "daily_ticket_summary",
+        bucket: Bucket::Day,
+        keys: &[("ticket", "i.ticket"), APP],
+        source: Source::Intervals,
+        condition: Some("i.ticket IS NOT NULL"),
+        report_rows: |report| &mut report.ticket_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByTicket,
+            label: "by ticket",
+            name: "ticket",
+            group_by: "ticket",
+            into_result: AggregatedResult::ByTicket,
+        }),
+    },
+    SummaryTable {
+        table: "daily_user_summary",
+        bucket: Bucket::Day,
+        keys: &[("user_name", "COALESCE(i.user_name, '[Unknown User]')"), APP],
+        source: Source::Intervals,
+        condition: None,
+        report_rows: |report| &mut report.user_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByUser,
+            label: "by user",
+            name: "user_name",
+            group_by: "user_name",
+            into_result: AggregatedResult::ByUser,
+        }),
+    },
+    SummaryTable {
+        table: "daily_remote_summary",
+        bucket: Bucket::Day,
+        // remote_host is '' when the title named no machine
+        keys: &[("session_type", "i.session_type"), ("remote_host", "COALESCE(i.remote_host, '')"), APP],
+        source: Source::Intervals,
+        condition: Some("i.session_type IS NOT NULL"), // Only remote desktop time
+        report_rows: |report| &mut report.remote_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByRemote,
+            label: "by remote session",
+            // "rdp-client: SERVER01", or just "rdp" when no machine is known
+            name: "session_type || CASE WHEN remote_host = '' THEN '' ELSE ': ' || remote_host END",
+            group_by: "session_type, remote_host",
+            into_result: AggregatedResult::ByRemote,
+        }),
+    },
+    SummaryTable {
+        table: "daily_document_summary",
+        bucket: Bucket::Day,
+        keys: &[("document", "i.document"), APP],
+        source: Source::Intervals,
+        condition: Some("i.document IS NOT NULL"),
+        report_rows: |report| &mut report.document_rows,
+        stats: Some(StatsLevel {
+            level: AggregationLevel::ByDocument,
+            label: "by document",
+            name: "document",
+            group_by: "document",
+            into_result: AggregatedResult::ByDocument,
+        }),
+    },
+];
+
+/// Apps playing audio (`audio_tracking`). Aggregated separately: whole local days only, so hour
+/// periods of today still see every audio interval.
+pub(crate) const AUDIO_SUMMARY: SummaryTable = SummaryTable {
+    table: "daily_audio_summary",
+    bucket: Bucket::Day,
+    keys: &[APP, ("user_name", "i.user_name")],
+    source: Source::Audio,
+    condition: None,
+    report_rows: |report| &mut report.audio_rows,
+    stats: None,
+};
+
+/// The summary `stats` reads for `level`, `None` for the levels with queries of their own.
+pub(crate) fn for_level(level: AggregationLevel) -> Option<(&'static SummaryTable, &'static StatsLevel)> {
+    SUMMARIES.iter().find_map(|summary| summary.stats.as_ref().filter(|stats| stats.level == level).map(|stats| (summary, stats)))
+}
+
+impl SummaryTable {
+    fn columns(&self) -> String {
+        self.keys.iter().map(|(column, _)| *column).collect::<Vec<_>>().join(", ")
+    }
+
+    /// The key values of a raw row, named after their columns
+    fn values(&self) -> String {
+        self.keys.iter().map(|(column, value)| format!("{} AS {}", value, column)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// FROM clause, start and end of the raw rows, where an interval `i` ends at `interval_end`;
+    /// `least`/`greatest` are the backend's two-argument MIN and MAX.
+    fn rows(&self, interval_end: &str, least: &str, greatest: &str) -> (String, String, String) {
+        match self.source {
+            Source::Intervals => ("app_intervals i".to_string(), "i.start_time".to_string(), interval_end.to_string()),
+            Source::Audio => ("audio_intervals i".to_string(), "i.start_time".to_string(), interval_end.to_string()),
+            Source::Meetings => (
+                format!("app_intervals i\n    JOIN calendar_events e ON e.start_time < {} AND e.end_time > i.start_time", interval_end),
+                format!("{}(i.start_time, e.start_time)", greatest),
+                format!("{}({}, e.end_time)", least, interval_end),
+            ),
+        }
+    }
+
+    fn has_passive(&self) -> bool {
+        self.source != Source::Audio
+    }
+
+    /// `text` when the summary keeps passive time, otherwise nothing
+    fn if_passive(&self, text: &str) -> String {
+        if self.has_passive() { text.to_string() } else { String::new() }
+    }
+
+    fn condition(&self) -> String {
+        self.condition.map(|condition| format!("\n      AND {}", condition)).unwrap_or_default()
+    }
+
+    fn schema(&self, integer: &str) -> String {
+        let keys: String = self.keys.iter().map(|(column, _)| format!("    {} TEXT NOT NULL,\n", column)).collect();
+        format!(
+            "CREATE TABLE IF NOT EXISTS {table} (\n{keys}    {bucket} {integer} NOT NULL,\n    \
+             total_duration_secs {integer} NOT NULL DEFAULT 0,\n{passive}    \
+             PRIMARY KEY ({columns}, {bucket})\n);",
+            table = self.table,
+            passive = self.if_passive(&format!("    passive_duration_secs {} NOT NULL DEFAULT 0, -- Part of total_duration_secs tagged passive\n", integer)),
+            bucket = self.bucket.column(),
+            columns = self.columns(),
+        )
+    }
+
+    pub fn create_table(&self) -> String {
+        self.schema("INTEGER")
+    }
+
+    /// Adds the raw intervals that ended by ?1 (aggregate_until; the start of today for audio) to the
+    /// summary. Each is split at the local bucket boundaries in the bucket's temp table (see
+    /// `Bucket::spans`) so time is counted in the hour or day it was spent in, not the one it started in.
+    pub fn aggregate(&self) -> String {
+        let (from, start, end) = self.rows("i.end_time", "MIN", "MAX");
+        let columns = self.columns();
+        format!(
+            "WITH raw_rows AS (
+    SELECT {values}, {passive} AS passive, {start} AS start_time, {end} AS end_time
+    FROM {from}
+    WHERE i.end_time IS NOT NULL AND i.end_time <= ?1
+      AND i.end_time > i.start_time -- Zero or negative (clock changed) durations add nothing{condition}
+)
+INSERT INTO {table} ({columns}, {bucket_column}, total_duration_secs{passive_column})
+SELECT
+    {columns}, b.bucket,
+    SUM(MIN(end_time, b.bucket_end) - MAX(start_time, b.bucket)){passive_sum}
+FROM raw_rows
+JOIN {buckets} b ON b.bucket < end_time AND b.bucket_end > start_time
+GROUP BY {columns}, b.bucket
+ON CONFLICT({columns}, {bucket_column}) DO UPDATE SET
+    total_duration_secs = total_duration_secs + excluded.total_duration_secs{passive_update};",
+            passive = if self.has_passive() { "i.passive" } else { "0" },
+            passive_column = self.if_passive(", passive_duration_secs"),
+            passive_sum = self.if_passive(",\n    SUM(CASE WHEN passive = 1 THEN MIN(end_time, b.bucket_end) - MAX(start_time, b.bucket) ELSE 0 END)"),
+            passive_update = self.if_passive(",\n    passive_duration_secs = passive_duration_secs + excluded.passive_duration_secs"),
+            values = self.values(),
+            condition = self.condition(),
+            table = self.table,
+            bucket_column = self.bucket.column(),
+            buckets = self.bucket.table(),
+        )
+    }
+
+    /// `stats` rows of `level` (see `for_level`): the summary's days in the period plus the raw, not yet
+    /// aggregated intervals clipped to it, filtered, sorted and limited in SQL.
+    /// Params: see query_stats_by_app.sql. These summaries are daily, so hour periods only count raw intervals.
+    pub fn stats_query(&self, stats: &StatsLevel) -> String {
+        let (from, start, end) = self.rows("COALESCE(i.end_time, :now)", "MIN", "MAX");
+        format!(
+            "SELECT
+    {name},
+    SUM(duration) AS total_duration
+FROM (
+    SELECT {columns}, total_duration_secs
+        - CASE WHEN :exclude_passive THEN passive_duration_secs ELSE 0 END AS duration
+    FROM {table}
+    WHERE :whole_days AND day_timestamp >= :start AND day_timestamp < :end
+      AND :title_contains IS NULL -- Summary rows carry no titles
+      AND {user_filter}
+
+    UNION ALL
+
+    SELECT
+        {values},
+        MAX(0, MIN({end}, :effective_end) - MAX({start}, :start)) AS duration
+    FROM {from}
+    WHERE i.start_time < :effective_end
+      AND COALESCE(i.end_time, :now) > :start{condition}
+      AND NOT (:exclude_passive AND i.passive = 1)
+      AND (:user IS NULL OR COALESCE(i.user_name, '[Unknown User]') = :user)
+      AND (:title_contains IS NULL
+           OR instr(lower(i.detailed_window_title), lower(:title_contains)) > 0)
+)
+WHERE :app_glob IS NULL OR lower(app_name) GLOB lower(:app_glob)
+GROUP BY {group_by}
+HAVING total_duration >= :min_duration
+ORDER BY total_duration DESC
+LIMIT :limit;",
+            name = stats.name,
+            columns = self.columns(),
+            table = self.table,
+            user_filter = self.user_filter(":user"),
+            values = self.values(),
+            condition = self.condition(),
+            group_by = stats.group_by,
+        )
+    }
+
+    /// Whether rows are kept per user, so they can satisfy a user filter
+    pub fn keeps_users(&self) -> bool {
+        self.keys.iter().any(|(column, _)| *column == "user_name")
+    }
+
+    /// Summary rows satisfy a user filter only when they are kept per user
+    fn user_filter(&self, user: &str) -> String {
+        if self.keeps_users() {
+            format!("({user} IS NULL OR user_name = {user})")
+        } else {
+            format!("{} IS NULL", user)
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    pub fn postgres_create_table(&self) -> String {
+        self.schema("BIGINT")
+    }
+
+    /// PostgreSQL `aggregate`: $1 = aggregate_until, $2 and $3 = starts and ends of the bucket's `spans`.
+    #[cfg(feature = "postgres")]
+    pub fn postgres_aggregate(&self) -> String {
+        let (from, start, end) = self.rows("i.end_time", "LEAST", "GREATEST");
+        let columns = self.columns();
+        format!(
+            "INSERT INTO {table} AS s ({columns}, {bucket_column}, total_duration_secs{passive_column})
+SELECT
+    {columns}, b.bucket,
+    SUM(LEAST(end_time, b.bucket_end) - GREATEST(start_time, b.bucket))::BIGINT{passive_sum}
+FROM (
+    SELECT {values}, {passive} AS passive, {start} AS start_time, {end} AS end_time
+    FROM {from}
+    WHERE i.end_time IS NOT NULL AND i.end_time <= $1
+      AND i.end_time > i.start_time{condition}
+) AS raw_rows
+JOIN unnest($2::BIGINT[], $3::BIGINT[]) AS b (bucket, bucket_end) ON b.bucket < end_time AND b.bucket_end > start_time
+GROUP BY {columns}, b.bucket
+ON CONFLICT ({columns}, {bucket_column}) DO UPDATE SET
+    total_duration_secs = s.total_duration_secs + excluded.total_duration_secs{passive_update};",
+            passive = if self.has_passive() { "i.passive" } else { "FALSE" },
+            passive_column = self.if_passive(", passive_duration_secs"),
+            passive_sum = self.if_passive(
+                ",\n    SUM(CASE WHEN passive THEN LEAST(end_time, b.bucket_end) - GREATEST(start_time, b.bucket) ELSE 0 END)::BIGINT"
+            ),
+            passive_update = self.if_passive(",\n    passive_duration_secs = s.passive_duration_secs + excluded.passive_duration_secs"),
+            table = self.table,
+            bucket_column = self.bucket.column(),
+            values = self.values(),
+            condition = self.condition(),
+        )
+    }
+
+    /// PostgreSQL `stats_query`; params as in sql/postgres/query_stats_by_app.sql.
+    #[cfg(feature = "postgres")]
+    pub fn postgres_stats_query(&self, stats: &StatsLevel) -> String {
+        let (from, start, end) = self.rows("COALESCE(i.end_time, $4)", "LEAST", "GREATEST");
+        format!(
+            "SELECT
+    {name},
+    SUM(duration)::BIGINT AS total_duration
+FROM (
+    SELECT {columns}, total_duration_secs
+        - CASE WHEN $9 THEN passive_duration_secs ELSE 0 END AS duration
+    FROM {table}
+    WHERE $10 AND day_timestamp >= $1 AND day_timestamp < $2
+      AND $6::TEXT IS NULL
+      AND {user_filter}
+
+    UNION ALL
+
+    SELECT
+        {values},
+        GREATEST(0, LEAST({end}, $3) - GREATEST({start}, $1)) AS duration
+    FROM {from}
+    WHERE i.start_time < $3
+      AND COALESCE(i.end_time, $4) > $1{condition}
+      AND NOT ($9 AND i.passive)
+      AND ($11::TEXT IS NULL OR COALESCE(i.user_name, '[Unknown User]') = $11)
+      AND ($6::TEXT IS NULL
+           OR strpos(lower(i.detailed_window_title), lower($6)) > 0)
+) AS combined
+WHERE $5::TEXT IS NULL OR app_name ILIKE $5
+GROUP BY {group_by}
+HAVING SUM(duration) >= $7::BIGINT -- SUM of BIGINT is NUMERIC
+ORDER BY total_duration DESC
+LIMIT $8;",
+            name = stats.name,
+            columns = self.columns(),
+            table = self.table,
+            user_filter = self.user_filter("$11::TEXT"),
+            values = self.values(),
+            condition = self.condition(),
+            group_by = stats.group_by,
+        )
+    }
+}
@@ -0,0 +1,299 @@
+// src/persistence/fixtures.rs
+// Builders for populated in-memory databases, so the aggregation and stats SQL can be exercised
+// (intervals spanning hour/day boundaries, open and dangling intervals, pre-aggregated summaries)
+// without running the tracker. Pair with `clock::ManualClock` to control "now".
+
+use super::{finalize_interval, initialize_db, insert_new_interval, SqliteStorage};
+use crate::types::NewInterval;
+use rusqlite::{params, Connection, Result as SqlResult};
+
+/// A raw interval to insert; `end_time` None leaves it open (still tracking, or dangling after a crash).
+#[derive(Debug, Clone)]
+pub struct FixtureInterval {
+    pub app_name: String,
+    pub main_title: String,
+    pub detailed_title: String,
+    pub virtual_desktop: Option<String>,
+    pub passive: bool,
+    pub device_id: String,
+    pub user_name: String,
+    pub repo: Option<String>,
+    pub executable_path: Option<String>,
+    pub start_time: i64,
+    pub end_time: Option<i64>,
+}
+
+impl FixtureInterval {
+    pub fn new(app_name: &str, start_time: i64, end_time: Option<i64>) -> Self {
+        FixtureInterval {
+            app_name: app_name.to_string(),
+            main_title: String::new(),
+            detailed_title: String::new(),
+            virtual_desktop: None,
+            passive: false,
+            device_id: "fixture".to_string(),
+            user_name: "fixture".to_string(),
+            repo: None,
+            executable_path: None,
+            start_time,
+            end_time,
+        }
+    }
+}
+
+/// Which summary table `summary_rows` reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryTable {
+    Hourly,
+    Daily,
+    DaysByApp,
+}
+
+/// Collects rows, then writes them into a fresh in-memory database with the current schema.
+#[derive(Debug, Default)]
+pub struct DatabaseBuilder {
+    intervals: Vec<FixtureInterval>,
+    // (table, app, detailed title, bucket start, seconds)
+    summaries: Vec<(SummaryTable, String, String, i64, i64)>,
+}
+
+impl DatabaseBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A completed interval of `app_name` without titles.
+    pub fn interval(self, app_name: &str, start_time: i64, end_time: i64) -> Self {
+        self.push_interval(FixtureInterval::new(app_name, start_time, Some(end_time)))
+    }
+
+    /// A completed interval with a detailed window title.
+    pub fn titled_interval(self, app_name: &str, detailed_title: &str, start_time: i64, end_time: i64) -> Self {
+        self.push_interval(FixtureInterval { detailed_title: detailed_title.to_string(), ..FixtureInterval::new(app_name, start_time, Some(end_time)) })
+    }
+
+    /// An interval that was never finalized.
+    pub fn open_interval(self, app_name: &str, start_time: i64) -> Self {
+        self.push_interval(FixtureInterval::new(app_name, start_time, None))
+    }
+
+    /// `count` back-to-back completed intervals filling [start, end), cycling through 25 apps with 8
+    /// titles each (`benches/persistence.rs` builds its 10k to 1M row databases with it).
+    pub fn synthetic_intervals(mut self, count: usize, start: i64, end: i64) -> Self {
+        let step = ((end - start) / count.max(1) as i64).max(1);
+        self.intervals.reserve(count);
+        for index in 0..count {
+            let interval_start = start + index as i64 * step;
+            let app_name = format!("app-{:02}.exe", index % 25);
+            let detailed_title = format!("Document {} - {}", index % 8, app_name);
+            self.intervals.push(FixtureInterval {
+                detailed_title,
+                passive: index % 10 == 0,
+                ..FixtureInterval::new(&app_name, interval_start, Some(interval_start + step))
+            });
+        }
+        self
+    }
+
+    pub fn push_interval(mut self, interval: FixtureInterval) -> Self {
+        self.intervals.push(interval);
+        self
+    }
+
+    /// A row already rolled into `table` (`detailed_title` is ignored for `DaysByApp`).
+    pub fn summary(mut self, table: SummaryTable, app_name: &str, detailed_title: &str, bucket_start: i64, secs: i64) -> Self {
+        self.summaries.push((table, app_name.to_string(), detailed_title.to_string(), bucket_start, secs));
+        self
+    }
+
+    pub fn build(self) -> SqlResult<Connection> {
+        let mut conn = Connection::open_in_memory()?;
+        initialize_db(&mut conn)?;
+        let tx = conn.transaction()?;
+        for interval in &self.intervals {
+            let id = insert_new_interval(
+                &tx,
+                &NewInterval {
+                    app_name: &interval.app_name,
+                    main_title: &interval.main_title,
+                    detailed_title: &interval.detailed_title,
+                    virtual_desktop: interval.virtual_desktop.as_deref(),
+                    repo: interval.repo.as_deref(),
+                    executable_path: interval.executable_path.as_deref(),
+                    command_line: None,
+                    ticket: None,
+                    session_type: None,
+                    remote_host: None,
+                    document: None,
+                    passive: interval.passive,
+                    start_time: interval.start_time,
+                    device_id: &interval.device_id,
+                    user_name: &interval.user_name,
+                },
+            )?;
+            if let Some(end_time) = interval.end_time {
+                finalize_interval(&tx, id, end_time)?;
+            }
+        }
+        for (table, app_name, detailed_title, bucket_start, secs) in &self.summaries {
+            match table {
+                SummaryTable::Hourly => tx.execute(
+                    "INSERT INTO hourly_summary (app_name, detailed_window_title, hour_timestamp, total_duration_secs) VALUES (?1, ?2, ?3, ?4)",
+                    params![app_name, detailed_title, bucket_start, secs],
+                )?,
+                SummaryTable::Daily => tx.execute(
+                    "INSERT INTO daily_summary (app_name, detailed_window_title, day_timestamp, total_duration_secs) VALUES (?1, ?2, ?3, ?4)",
+                    params![app_name, detailed_title, bucket_start, secs],
+                )?,
+                SummaryTable::DaysByApp => tx.execute(
+                    "INSERT INTO days_summary_by_app (app_name, day_timestamp, total_duration_secs) VALUES (?1, ?2, ?3)",
+                    params![app_name, bucket_start, secs],
+                )?,
+            };
+        }
+        tx.commit()?;
+        Ok(conn)
+    }
+
+    /// `build` wrapped in the `Storage` implementation (chain `with_clock`/`with_timezone` as needed).
+    pub fn build_storage(self) -> SqlResult<SqliteStorage> {
+        Ok(SqliteStorage::from_connection(self.build()?))
+    }
+}
+
+/// (app, bucket start, total seconds) of every row in `table`, summed over titles and ordered by bucket then app.
+pub fn summary_rows(conn: &Connection, table: SummaryTable) -> SqlResult<Vec<(String, i64, i64)>> {
+    let sql = match table {
+        SummaryTable::Hourly => "SELECT app_name, hour_timestamp, SUM(total_duration_secs) FROM hourly_summary GROUP BY 1, 2 ORDER BY 2, 1",
+        SummaryTable::Daily => "SELECT app_name, day_timestamp, SUM(total_duration_secs) FROM daily_summary GROUP BY 1, 2 ORDER BY 2, 1",
+        SummaryTable::DaysByApp => "SELECT app_name, day_timestamp, SUM(total_duration_secs) FROM days_summary_by_app GROUP BY 1, 2 ORDER BY 2, 1",
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, ManualClock};
+    use crate::persistence::{compact_intervals, finalize_dangling_intervals, run_aggregation};
+    use crate::timezone::ReportTimeZone;
+
+    const HOUR: i64 = 3600;
+    const DAY: i64 = 86_400;
+    /// 2026-03-02 00:00 UTC
+    const MONDAY: i64 = 1_772_409_600;
+
+    fn aggregate(conn: &mut Connection, timezone: &ReportTimeZone, clock: &ManualClock) {
+        run_aggregation(conn, None, timezone, clock, false, 7, true).unwrap();
+    }
+
+    fn raw_interval_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM app_intervals", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn interval_crossing_hours_is_split_per_hour() {
+        let utc = ReportTimeZone::parse("UTC").unwrap();
+        let mut conn = DatabaseBuilder::new().interval("a.exe", MONDAY + 10 * HOUR + 1800, MONDAY + 12 * HOUR + 900).build().unwrap();
+        aggregate(&mut conn, &utc, &ManualClock::new(MONDAY + 14 * HOUR));
+
+        assert_eq!(
+            summary_rows(&conn, SummaryTable::Hourly).unwrap(),
+            vec![
+                ("a.exe".to_string(), MONDAY + 10 * HOUR, 1800),
+                ("a.exe".to_string(), MONDAY + 11 * HOUR, 3600),
+                ("a.exe".to_string(), MONDAY + 12 * HOUR, 900),
+            ]
+        );
+        assert_eq!(summary_rows(&conn, SummaryTable::Daily).unwrap(), vec![("a.exe".to_string(), MONDAY, 6300)]);
+        assert_eq!(raw_interval_count(&conn), 0);
+    }
+
+    #[test]
+    fn interval_crossing_midnight_is_split_per_day() {
+        let utc = ReportTimeZone::parse("UTC").unwrap();
+        let mut conn = DatabaseBuilder::new().interval("a.exe", MONDAY + 23 * HOUR, MONDAY + DAY + 5400).build().unwrap();
+        aggregate(&mut conn, &utc, &ManualClock::new(MONDAY + DAY + 3 * HOUR));
+
+        assert_eq!(
+            summary_rows(&conn, SummaryTable::Daily).unwrap(),
+            vec![("a.exe".to_string(), MONDAY, 3600), ("a.exe".to_string(), MONDAY + DAY, 5400)]
+        );
+        assert_eq!(summary_rows(&conn, SummaryTable::Hourly).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn open_interval_and_current_hour_stay_raw() {
+        let utc = ReportTimeZone::parse("UTC").unwrap();
+        let mut conn = DatabaseBuilder::new()
+            .interval("a.exe", MONDAY + 9 * HOUR, MONDAY + 10 * HOUR)
+            .interval("b.exe", MONDAY + 12 * HOUR, MONDAY + 12 * HOUR + 600)
+            .open_interval("c.exe", MONDAY + 10 * HOUR)
+            .build()
+            .unwrap();
+        aggregate(&mut conn, &utc, &ManualClock::new(MONDAY + 12 * HOUR + 1200));
+
+        assert_eq!(summary_rows(&conn, SummaryTable::Hourly).unwrap(), vec![("a.exe".to_string(), MONDAY + 9 * HOUR, 3600)]);
+        // The open interval and the one ending in the current hour wait for a later run
+        assert_eq!(raw_interval_count(&conn), 2);
+    }
+
+    #[test]
+    fn dangling_intervals_are_aggregated_once_finalized() {
+        let utc = ReportTimeZone::parse("UTC").unwrap();
+        let mut conn = DatabaseBuilder::new()
+            .open_interval("old.exe", MONDAY + 10 * HOUR)
+            .open_interval("recent.exe", MONDAY + 12 * HOUR)
+            .build()
+            .unwrap();
+        let clock = ManualClock::new(MONDAY + 12 * HOUR + 1800);
+        // Without heartbeats, an old dangling interval ends where it started and a recent one at shutdown
+        assert_eq!(finalize_dangling_intervals(&conn, clock.now(), HOUR, "fixture").unwrap(), 2);
+        clock.advance(2 * HOUR);
+        aggregate(&mut conn, &utc, &clock);
+
+        assert_eq!(summary_rows(&conn, SummaryTable::Hourly).unwrap(), vec![("recent.exe".to_string(), MONDAY + 12 * HOUR, 1800)]);
+        assert_eq!(raw_interval_count(&conn), 0);
+    }
+
+    #[test]
+    fn days_follow_daylight_saving_changes() {
+        let berlin = ReportTimeZone::parse("Europe/Berlin").unwrap();
+        // 2026-03-29 00:00 CET; clocks move from 02:00 to 03:00, so the day lasts 23 hours
+        let dst_day = 1_774_738_800;
+        let next_day = dst_day + 23 * HOUR;
+        let mut conn = DatabaseBuilder::new().interval("a.exe", dst_day - HOUR, next_day + 5400).build().unwrap();
+        aggregate(&mut conn, &berlin, &ManualClock::new(next_day + 3 * HOUR));
+
+        // The day before now's day was rolled into days_summary_by_app
+        assert_eq!(summary_rows(&conn, SummaryTable::DaysByApp).unwrap(), vec![("a.exe".to_string(), dst_day - DAY, 3600)]);
+        assert_eq!(
+            summary_rows(&conn, SummaryTable::Daily).unwrap(),
+            vec![("a.exe".to_string(), dst_day, 23 * HOUR), ("a.exe".to_string(), next_day, 5400)]
+        );
+        let hours = summary_rows(&conn, SummaryTable::Hourly).unwrap();
+        assert_eq!(hours.iter().filter(|(_, hour, _)| (dst_day..next_day).contains(hour)).count(), 23);
+    }
+
+    #[test]
+    fn compaction_keeps_users_of_one_device_apart() {
+        let interval = |user_name: &str, start_time: i64, end_time: i64| FixtureInterval {
+            user_name: user_name.to_string(),
+            ..FixtureInterval::new("a.exe", start_time, Some(end_time))
+        };
+        let conn = DatabaseBuilder::new()
+            .push_interval(interval("alice", MONDAY, MONDAY + 100))
+            .push_interval(interval("bob", MONDAY + 105, MONDAY + 190))
+            .push_interval(interval("alice", MONDAY + 110, MONDAY + 200))
+            .build()
+            .unwrap();
+
+        // Only alice's two intervals merge; bob's, lying between them, is left alone
+        assert_eq!(compact_intervals(&conn, 30).unwrap(), 1);
+        let mut stmt = conn.prepare("SELECT user_name, start_time, end_time FROM app_intervals ORDER BY user_name").unwrap();
+        let rows: Vec<(String, i64, i64)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap().collect::<SqlResult<_>>().unwrap();
+        assert_eq!(rows, vec![("alice".to_string(), MONDAY, MONDAY + 200), ("bob".to_string(), MONDAY + 105, MONDAY + 190)]);
+    }
+}
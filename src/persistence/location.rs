@@ -0,0 +1,134 @@
+// src/persistence/location.rs
+// Where the SQLite file lives. Sync clients (Dropbox, OneDrive, Google Drive, iCloud, ...) upload and
+// replace the file and its -wal/-shm companions while the tracker writes them, and removable or network
+// drives can disappear mid-write; both end in "database is locked" errors or a corrupt database. Such
+// locations are recognized from the path, so `track` and `doctor` can warn and the database is opened in
+// safe-write mode there (`database_safe_write` in config.json overrides the detection).
+
+use crate::config::AppConfig;
+use std::path::{Component, Path, PathBuf};
+
+/// How the SQLite file is journaled and synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// WAL with `synchronous = NORMAL`: fast, and readers such as `stats` never block the tracker
+    #[default]
+    Wal,
+    /// Rollback journal with `synchronous = FULL`: a single file that is complete after every commit.
+    /// WAL's shared-memory index does not work through sync clients and network file systems.
+    Safe,
+}
+
+impl WriteMode {
+    /// Safe for risky locations (`risky_location`), WAL elsewhere.
+    pub fn for_path(path: &Path) -> Self {
+        if risky_location(path).is_some() { WriteMode::Safe } else { WriteMode::Wal }
+    }
+}
+
+/// Folder names of sync clients, compared case-insensitively with each path component
+/// ("Dropbox (Team)" and "OneDrive - Contoso" match by prefix).
+const SYNCED_FOLDERS: &[(&str, &str)] = &[
+    ("Dropbox", "Dropbox"),
+    ("OneDrive", "OneDrive"),
+    ("Google Drive", "Google Drive"),
+    ("GoogleDrive", "Google Drive"),
+    ("My Drive", "Google Drive"),
+    ("iCloud Drive", "iCloud Drive"),
+    ("iCloudDrive", "iCloud Drive"),
+    ("Mobile Documents", "iCloud Drive"),
+    ("CloudStorage", "a cloud storage provider"),
+    ("Box", "Box"),
+    ("Box Sync", "Box"),
+    ("pCloud Drive", "pCloud"),
+    ("Nextcloud", "Nextcloud"),
+    ("ownCloud", "ownCloud"),
+    ("MEGA", "MEGA"),
+    ("MEGAsync", "MEGA"),
+    ("SynologyDrive", "Synology Drive"),
+];
+
+/// Environment variables Windows sets to the OneDrive folders (which users can rename).
+const ONEDRIVE_VARIABLES: &[&str] = &["OneDrive", "OneDriveCommercial", "OneDriveConsumer"];
+
+/// Why `path` is a risky place for the database ("a folder synced by Dropbox", "a removable drive", ...), if it is.
+pub fn risky_location(path: &Path) -> Option<String> {
+    if path == Path::new(super::IN_MEMORY_PATH) {
+        return None;
+    }
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Some(service) = synced_folder(&path) {
+        return Some(format!("a folder synced by {}", service));
+    }
+    removable_or_network_drive(&path)
+}
+
+/// Warning shown by `track` when the configured SQLite database is in a risky location.
+pub fn warning(config: &AppConfig) -> Option<String> {
+    if config.database_url.is_some() {
+        return None;
+    }
+    let location = risky_location(&config.database_path)?;
+    let mode = match config.database_write_mode {
+        WriteMode::Safe => "Safe-write mode is on (no WAL, full sync), which is slower but keeps the file whole.",
+        WriteMode::Wal => "database_safe_write is false, so the database uses WAL there; its -wal and -shm files must stay together.",
+    };
+    Some(format!(
+        "The database is in {}, where the file can be locked, replaced or cut off while it is written. {} Set database_path to a local folder to avoid this.",
+        location, mode
+    ))
+}
+
+fn synced_folder(path: &Path) -> Option<&'static str> {
+    let in_onedrive = ONEDRIVE_VARIABLES
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .any(|folder| !folder.as_os_str().is_empty() && path.starts_with(folder));
+    if in_onedrive {
+        return Some("OneDrive");
+    }
+    path.components().find_map(|component| {
+        let Component::Normal(name) = component else { return None };
+        let name = name.to_string_lossy().to_lowercase();
+        SYNCED_FOLDERS.iter().find_map(|(folder, service)| {
+            let folder = folder.to_lowercase();
+            let matches = name == folder || name.starts_with(&format!("{} (", folder)) || name.starts_with(&format!("{} - ", folder));
+            matches.then_some(*service)
+        })
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn removable_or_network_drive(path: &Path) -> Option<String> {
+    use std::path::Prefix;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+    // GetDriveTypeW results
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_REMOTE: u32 = 4;
+
+    let Some(Component::Prefix(prefix)) = path.components().next() else { return None };
+    let letter = match prefix.kind() {
+        Prefix::UNC(..) | Prefix::VerbatimUNC(..) => return Some("a network share".to_string()),
+        Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => letter as char,
+        _ => return None,
+    };
+    let root: Vec<u16> = format!("{}:\\", letter).encode_utf16().chain(Some(0)).collect();
+    match unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) } {
+        DRIVE_REMOVABLE => Some(format!("a removable drive ({}:)", letter)),
+        DRIVE_REMOTE => Some(format!("a network drive ({}:)", letter)),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn removable_or_network_drive(path: &Path) -> Option<String> {
+    // Where desktops mount USB sticks and external disks
+    let removable: &[&str] = if cfg!(target_os = "macos") { &["/Volumes"] } else { &["/media", "/run/media"] };
+    if removable.iter().any(|mount| path.starts_with(mount)) {
+        return Some("a removable drive".to_string());
+    }
+    // autofs network mounts
+    ["/net", "/nfs"].iter().any(|mount| path.starts_with(mount)).then(|| "a network drive".to_string())
+}
@@ -0,0 +1,1631 @@
+// src/persistence/mod.rs
+
+// Keep necessary use statements
+use crate::config::AppConfig;
+use crate::clock::{Clock, ClockJump};
+use crate::errors::{AppResult, PersistenceError};
+use crate::timezone::ReportTimeZone;
+use crate::types::{AppMetadata, AppMetadataSource, AudioSample, AggregationLevel, AggregatedResult, AggregationReport, CalendarEvent, DetailedUsageRecord, Habit, IntervalRecord, IntervalStream, NewInterval, StatsFilter, PurgeScope, RunEvent, RunEventKind, SwitchStats, SyncState, TimePeriod, TitleRename, TrackerDailyStats, TrackerHistory, TrackerRun, WindowInfo};
+use rusqlite::{named_params, params, Connection, OpenFlags, OptionalExtension, Result as SqlResult}; // Keep only needed rusqlite items
+use std::path::Path; // Keep Path
+use std::sync::Arc;
+use std::time::Duration;
+use std::collections::BTreeMap;
+use std::fs;
+use chrono::{Utc, TimeZone}; // Keep needed chrono items
+use log::{debug, info, warn}; // Keep needed log items
+
+mod buffered_writer;
+mod dimensions;
+use dimensions::Bucket;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod fixtures;
+pub mod location;
+mod pool;
+mod storage;
+#[cfg(feature = "postgres")]
+mod postgres;
+pub use buffered_writer::{BufferedIntervalWriter, IntervalHandle};
+pub use location::WriteMode;
+pub use pool::{PooledConnection, StoragePool, DEFAULT_POOL_SIZE};
+pub use storage::{SqliteStorage, Storage};
+#[cfg(feature = "postgres")]
+pub use self::postgres::PostgresStorage;
+
+/// Opens the configured storage backend: PostgreSQL when `database_url` is set, otherwise
+/// the SQLite database at `database_path`.
+pub fn open_storage(config: &AppConfig) -> AppResult<Box<dyn Storage>> {
+    open_storage_with_clock(config, crate::clock::system_clock())
+}
+
+/// `open_storage` with "now" taken from `clock` (simulations and tests).
+pub fn open_storage_with_clock(config: &AppConfig, clock: Arc<dyn Clock>) -> AppResult<Box<dyn Storage>> {
+    match &config.database_url {
+        #[cfg(feature = "postgres")]
+        Some(url) => Ok(Box::new(
+            PostgresStorage::connect(url)?
+                .with_timezone(config.timezone.clone())
+                .with_archive(config.archive_raw_intervals)
+                .with_hourly_summary_days(config.hourly_summary_days)
+                .with_clock(clock),
+        )),
+        #[cfg(not(feature = "postgres"))]
+        Some(_) => Err(crate::errors::AppError::Config(
+            "database_url is set but this build has no PostgreSQL support (rebuild with --features postgres)".to_string(),
+        )),
+        None => {
+            let passphrase = database_passphrase(config)?;
+            let storage = SqliteStorage::from_connection(open_database(config, passphrase.as_deref())?);
+            Ok(Box::new(configure_sqlite(storage, config, clock)))
+        }
+    }
+}
+
+/// Applies the configured SQLite settings (also used for pooled storages).
+fn configure_sqlite(storage: SqliteStorage, config: &AppConfig, clock: Arc<dyn Clock>) -> SqliteStorage {
+    storage
+        .with_timezone(config.timezone.clone())
+        .with_vacuum_threshold(config.vacuum_threshold_percent)
+        .with_archive(config.archive_raw_intervals)
+        .with_hourly_summary_days(config.hourly_summary_days)
+        .with_clock(clock)
+}
+
+/// The SQLCipher passphrase from the keyring when `database_encryption` is on.
+pub fn database_passphrase(config: &AppConfig) -> AppResult<Option<String>> {
+    if !config.database_encryption {
+        return Ok(None);
+    }
+    if !cfg!(feature = "sqlcipher") {
+        return Err(crate::errors::AppError::Config(
+            "database_encryption is set but this build has no SQLCipher support (rebuild with --features sqlcipher)".to_string(),
+        ));
+    }
+    Ok(Some(crate::commands::set_key::load_api_key(config, crate::types::ApiKeyType::Database)?))
+}
+
+/// Opens the configured SQLite database in its configured `WriteMode` (`database_safe_write`).
+pub fn open_database(config: &AppConfig, passphrase: Option<&str>) -> Result<Connection, PersistenceError> {
+    open_connection_with_mode(&config.database_path, passphrase, config.database_write_mode)
+}
+
+/// Human-readable location of the configured database (for status output; hides any password in the URL).
+pub fn describe_location(config: &AppConfig) -> String {
+    match &config.database_url {
+        Some(url) => redact_url_password(url),
+        None => format!("{:?}", config.database_path),
+    }
+}
+
+fn redact_url_password(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else { return url.to_string() };
+    let Some((credentials, host)) = rest.split_once('@') else { return url.to_string() };
+    match credentials.split_once(':') {
+        Some((user, _password)) => format!("{}://{}:***@{}", scheme, user, host),
+        None => url.to_string(),
+    }
+}
+
+// --- Connection & Initialization ---
+/// Path that opens a private in-memory database instead of a file (gone when the connection closes).
+pub const IN_MEMORY_PATH: &str = ":memory:";
+
+pub fn open_connection_ensure_path(path: &Path) -> Result<Connection, PersistenceError> {
+    open_connection_with_key(path, None)
+}
+
+/// Like `open_connection_ensure_path`, but unlocks an SQLCipher database with `passphrase` first.
+/// Files in synced folders and on removable drives are opened in `WriteMode::Safe`.
+pub fn open_connection_with_key(path: &Path, passphrase: Option<&str>) -> Result<Connection, PersistenceError> {
+    open_connection_with_mode(path, passphrase, WriteMode::for_path(path))
+}
+
+/// Opens (and creates) the database at `path` journaled as `mode` says.
+pub fn open_connection_with_mode(path: &Path, passphrase: Option<&str>, mode: WriteMode) -> Result<Connection, PersistenceError> {
+    let open_error = |source| PersistenceError::Open { path: path.to_path_buf(), source };
+    let pragma_error = |pragma| move |source| PersistenceError::Pragma { path: path.to_path_buf(), pragma, source };
+    if path == Path::new(IN_MEMORY_PATH) {
+        debug!("Opening in-memory database.");
+        let conn = Connection::open_in_memory().map_err(open_error)?;
+        if let Some(passphrase) = passphrase {
+            conn.pragma_update(None, "key", passphrase).map_err(pragma_error("key"))?;
+        }
+        return Ok(conn); // No WAL: there is no file to make durable
+    }
+    if let Some(parent_dir) = path.parent() {
+        if !parent_dir.exists() {
+            info!("Data directory not found. Creating: {:?}", parent_dir);
+            fs::create_dir_all(parent_dir)
+                .map_err(|source| PersistenceError::CreateDataDir { path: parent_dir.to_path_buf(), source })?;
+            info!("Successfully created data directory.");
+        } else {
+            debug!("Data directory already exists: {:?}", parent_dir);
+        }
+    } else {
+        warn!(
+            "Could not determine parent directory for database path: {:?}",
+            path
+        );
+    }
+    debug!("Opening database connection at: {:?}", path);
+    let conn = Connection::open(path).map_err(open_error)?; // Creates file if not exists
+    if let Some(passphrase) = passphrase {
+        // Must be the first statement on the connection
+        conn.pragma_update(None, "key", passphrase).map_err(pragma_error("key"))?;
+    }
+    // WAL keeps committed intervals durable across crashes without blocking readers (e.g. `stats`);
+    // safe-write mode keeps everything in the one file a sync client copies, fsynced on every commit
+    let (wanted, synchronous) = match mode {
+        WriteMode::Wal => ("wal", "NORMAL"),
+        WriteMode::Safe => ("delete", "FULL"),
+    };
+    let journal_mode: String = conn
+        .query_row(&format!("PRAGMA journal_mode = {}", wanted), [], |row| row.get(0))
+        .map_err(pragma_error("journal_mode"))?;
+    debug!("SQLite journal mode: {}", journal_mode);
+    if !journal_mode.eq_ignore_ascii_case(wanted) {
+        // Leaving WAL needs the only connection to the file; the next start switches
+        warn!("{:?} stays in journal mode {} while another connection is open (wanted {}).", path, journal_mode, wanted);
+    }
+    conn.pragma_update(None, "synchronous", synchronous).map_err(pragma_error("synchronous"))?;
+    // Another process (the tracker, or `aggregate` next to it) may hold the write lock for a moment
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(pragma_error("busy_timeout"))?;
+    Ok(conn)
+}
+
+/// How long a statement waits for another connection's write lock before failing with "database is locked".
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Attempts of `retry_when_busy`, waiting 0.5s, 1s, 2s... in between (on top of `BUSY_TIMEOUT` each).
+const BUSY_RETRIES: u32 = 4;
+
+/// Runs `operation` again while SQLite reports the database busy or locked, with exponential backoff.
+/// Long administrative runs (`aggregate`, `init-db`) use it so a tracker writing meanwhile only delays them.
+pub fn retry_when_busy<T>(what: &str, mut operation: impl FnMut() -> SqlResult<T>) -> SqlResult<T> {
+    let mut delay = Duration::from_millis(500);
+    for attempt in 1.. {
+        match operation() {
+            Err(rusqlite::Error::SqliteFailure(error, _))
+                if attempt <= BUSY_RETRIES && matches!(error.code, rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) =>
+            {
+                warn!("{}: database is locked by another process; retrying in {:.1}s.", what, delay.as_secs_f64());
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    unreachable!("the loop only ends by returning")
+}
+
+/// Opens an existing database with `SQLITE_OPEN_READONLY` and `query_only` (`query`): no statement on
+/// this connection can change the file, and a missing file is an error instead of being created.
+pub fn open_connection_read_only(path: &Path, passphrase: Option<&str>) -> Result<Connection, PersistenceError> {
+    let pragma_error = |pragma| move |source| PersistenceError::Pragma { path: path.to_path_buf(), pragma, source };
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|source| PersistenceError::Open { path: path.to_path_buf(), source })?;
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase).map_err(pragma_error("key"))?;
+    }
+    conn.pragma_update(None, "query_only", true).map_err(pragma_error("query_only"))?;
+    Ok(conn)
+}
+
+/// Adds a column to an existing table if it is missing (schema upgrades for older databases).
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> SqlResult<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+    if !exists {
+        info!("Adding missing column '{}' to table '{}'.", column, table);
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+/// Stored in `PRAGMA user_version` by `initialize_db`; bump it whenever the schema changes.
+pub const SCHEMA_VERSION: i64 = 14;
+
+/// The schema version of an SQLite database (0 = created before versioning or not initialized).
+pub fn schema_version(conn: &Connection) -> SqlResult<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// `PRAGMA integrity_check`: a single "ok" row for a healthy database, otherwise one row per problem.
+pub fn integrity_check(conn: &Connection) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Percentage of the database's pages that are free: space `VACUUM` would give back.
+pub fn free_page_percent(conn: &Connection) -> SqlResult<f64> {
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let freelist_count: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    Ok(if page_count == 0 { 0.0 } else { freelist_count as f64 * 100.0 / page_count as f64 })
+}
+
+/// Bytes on disk of an SQLite database and its WAL file.
+pub fn database_file_size(path: &Path) -> u64 {
+    let mut wal_path = path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    [path.to_path_buf(), wal_path.into()]
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Row count of every table, by table name.
+pub fn table_row_counts(conn: &Connection) -> SqlResult<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+    let tables = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<SqlResult<Vec<_>>>()?;
+    tables
+        .into_iter()
+        .map(|table| {
+            let count = conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| row.get(0))?;
+            Ok((table, count))
+        })
+        .collect()
+}
+
+/// Local days with any recorded time, oldest first (raw intervals are bucketed with `day_offset`,
+/// see `ReportTimeZone::day_offset_secs`).
+pub fn query_tracked_days(conn: &Connection, day_offset: i64) -> SqlResult<Vec<i64>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_tracked_days.sql"))?;
+    let rows = stmt.query_map(params![day_offset], |row| row.get(0))?;
+    rows.collect()
+}
+
+pub fn query_tracker_daily_stats(conn: &Connection) -> SqlResult<Vec<TrackerDailyStats>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_tracker_daily_stats.sql"))?;
+    let rows = stmt.query_map([], |row| {
+        Ok(TrackerDailyStats {
+            day_timestamp: row.get(0)?,
+            interval_count: row.get(1)?,
+            longest_session_secs: row.get(2)?,
+            detection_errors: row.get(3)?,
+            last_error: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// `db maintain`: integrity check, then `ANALYZE` and `VACUUM` (and a WAL checkpoint so the file
+/// shrinks). A damaged database is left alone; returns the integrity problems (empty = maintained).
+pub fn maintain_db(conn: &Connection) -> SqlResult<Vec<String>> {
+    let problems: Vec<String> = integrity_check(conn)?.into_iter().filter(|row| row != "ok").collect();
+    if !problems.is_empty() {
+        warn!("Integrity check failed; skipping ANALYZE and VACUUM.");
+        return Ok(problems);
+    }
+    conn.execute_batch("ANALYZE; VACUUM;")?;
+    checkpoint_wal(conn)?;
+    Ok(problems)
+}
+
+pub fn initialize_db(conn: &mut Connection) -> SqlResult<()> {
+    info!("Initializing database schema if needed...");
+    let tx = conn.transaction()?;
+    // Assumes sql/ is in the project root, two levels up from src/persistence/
+    tx.execute(include_str!("../../sql/initialize_db_app_intervals.sql"), [])?;
+    for summary in dimensions::SUMMARIES {
+        tx.execute(&summary.create_table(), [])?;
+    }
+    tx.execute(include_str!("../../sql/initialize_db_days_summary_by_app.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_window_snapshots.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_sync_state.sql"), [])?;
+    ensure_column(&tx, "app_intervals", "heartbeat_time", "INTEGER")?;
+    ensure_column(&tx, "app_intervals", "virtual_desktop", "TEXT")?;
+    ensure_column(&tx, "app_intervals", "passive", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(&tx, "app_intervals", "device_id", "TEXT")?;
+    tx.execute(include_str!("../../sql/initialize_db_audit_log.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_app_metadata.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_weeks_summary_by_app.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_tracker_daily_stats.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_app_intervals_archive.sql"), [])?;
+    ensure_column(&tx, "app_intervals", "repo", "TEXT")?;
+    ensure_column(&tx, "app_intervals_archive", "repo", "TEXT")?;
+    for table in ["app_intervals", "app_intervals_archive"] {
+        ensure_column(&tx, table, "executable_path", "TEXT")?;
+        ensure_column(&tx, table, "command_line", "TEXT")?;
+    }
+    tx.execute(include_str!("../../sql/initialize_db_habits.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_calendar_events.sql"), [])?;
+    ensure_column(&tx, "app_intervals", "ticket", "TEXT")?;
+    ensure_column(&tx, "app_intervals_archive", "ticket", "TEXT")?;
+    tx.execute(include_str!("../../sql/initialize_db_break_reminders.sql"), [])?;
+    ensure_column(&tx, "app_intervals", "user_name", "TEXT")?;
+    ensure_column(&tx, "app_intervals_archive", "user_name", "TEXT")?;
+    for table in ["app_intervals", "app_intervals_archive"] {
+        ensure_column(&tx, table, "session_type", "TEXT")?;
+        ensure_column(&tx, table, "remote_host", "TEXT")?;
+    }
+    ensure_column(&tx, "app_intervals", "document", "TEXT")?;
+    ensure_column(&tx, "app_intervals_archive", "document", "TEXT")?;
+    tx.execute(include_str!("../../sql/initialize_db_audio_intervals.sql"), [])?;
+    tx.execute(&dimensions::AUDIO_SUMMARY.create_table(), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_tracker_runs.sql"), [])?;
+    tx.execute(include_str!("../../sql/initialize_db_tracker_run_events.sql"), [])?;
+    for summary_table in ["hourly_summary", "daily_summary", "days_summary_by_app", "daily_desktop_summary", "daily_device_summary"] {
+        ensure_column(&tx, summary_table, "passive_duration_secs", "INTEGER NOT NULL DEFAULT 0")?;
+    }
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_app_intervals_app_name ON app_intervals (app_name);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_app_intervals_main_title ON app_intervals (main_window_title);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_app_intervals_detailed_title ON app_intervals (detailed_window_title);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_app_intervals_start_time ON app_intervals (start_time);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_app_intervals_end_time ON app_intervals (end_time);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_window_snapshots_time ON window_snapshots (snapshot_time);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_app_intervals_archive_start_time ON app_intervals_archive (start_time);",
+        [],
+    )?;
+    tx.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tracker_run_events_run_id ON tracker_run_events (run_id);",
+        [],
+    )?;
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()
+}
+
+// --- Interval Management ---
+// The tracker runs these on every app switch or heartbeat, so their statements come from the
+// connection's statement cache (`prepare_cached`) instead of being compiled again each time.
+pub fn insert_new_interval(conn: &Connection, interval: &NewInterval) -> SqlResult<i64> {
+    conn.prepare_cached(include_str!("../../sql/insert_interval.sql"))?.execute(
+        params![
+            interval.app_name,
+            interval.main_title,
+            interval.detailed_title,
+            interval.start_time,
+            interval.virtual_desktop,
+            interval.passive,
+            interval.device_id,
+            interval.repo,
+            interval.executable_path,
+            interval.command_line,
+            interval.ticket,
+            interval.user_name,
+            interval.session_type,
+            interval.remote_host,
+            interval.document,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn finalize_interval(conn: &Connection, row_id: i64, end_time: i64) -> SqlResult<usize> {
+    conn.prepare_cached(include_str!("../../sql/finalize_interval.sql"))?.execute(params![end_time, row_id])
+}
+
+/// Records that the interval is still active, so a crash loses at most one heartbeat period.
+pub fn heartbeat_interval(conn: &Connection, row_id: i64, heartbeat_time: i64) -> SqlResult<usize> {
+    conn.prepare_cached(include_str!("../../sql/heartbeat_interval.sql"))?.execute(params![heartbeat_time, row_id])
+}
+
+/// Flushes the WAL into the main database file (used on clean shutdown).
+pub fn checkpoint_wal(conn: &Connection) -> SqlResult<()> {
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+}
+
+/// Copies the database into `path` with `VACUUM INTO` (compacted, safe while other connections write).
+/// SQLCipher encrypts the copy with the same passphrase.
+pub fn backup_database(conn: &Connection, path: &Path) -> SqlResult<()> {
+    conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+    Ok(())
+}
+
+/// Closes the intervals a previous session of `user_name` left open (rows of other users sharing
+/// the database belong to their own trackers; rows from before `user_name` existed count as ours).
+pub fn finalize_dangling_intervals(
+    conn: &Connection,
+    shutdown_time: i64,
+    threshold_secs: i64,
+    user_name: &str,
+) -> SqlResult<usize> {
+    info!(
+        "Checking for dangling intervals from previous sessions (threshold: {} seconds)...",
+        threshold_secs
+    );
+    let cutoff_time = shutdown_time - threshold_secs;
+    debug!(
+        "Dangling interval cutoff time (before this = old): {}",
+        cutoff_time
+    );
+    let updated_old = conn.execute(
+        include_str!("../../sql/finalize_dangling_old.sql"),
+        params![cutoff_time, user_name],
+    )?;
+    if updated_old > 0 {
+        debug!(
+            "-> Finalized {} old dangling interval(s) at their last heartbeat (or start_time).",
+            updated_old
+        );
+    }
+    let updated_recent = conn.execute(
+        include_str!("../../sql/finalize_dangling_recent.sql"),
+        params![shutdown_time, cutoff_time, user_name],
+    )?;
+    if updated_recent > 0 {
+        debug!(
+            "-> Finalized {} recent dangling interval(s) at their last heartbeat (or now).",
+            updated_recent
+        );
+    }
+    let total_updated = updated_old + updated_recent;
+    if total_updated > 0 {
+        info!(
+            "Finalized a total of {} dangling interval(s).",
+            total_updated
+        );
+    } else {
+        debug!("No dangling intervals found to finalize.");
+    }
+    Ok(total_updated)
+}
+
+// --- Context Snapshots ---
+/// Stores one snapshot (all rows share `snapshot_time`) and returns the number of windows written.
+pub fn insert_window_snapshot(conn: &mut Connection, snapshot_time: i64, windows: &[WindowInfo]) -> SqlResult<usize> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare_cached(include_str!("../../sql/insert_window_snapshot.sql"))?;
+        for window in windows {
+            stmt.execute(params![snapshot_time, window.app_name, window.window_title])?;
+        }
+    }
+    tx.commit()?;
+    Ok(windows.len())
+}
+
+/// Returns the most recent snapshot taken at or before `at` as (snapshot_time, windows).
+pub fn query_window_snapshot_at(conn: &Connection, at: i64) -> SqlResult<Option<(i64, Vec<WindowInfo>)>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_window_snapshot_at.sql"))?;
+    let rows = stmt.query_map(params![at], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            WindowInfo {
+                app_name: row.get(1)?,
+                window_title: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            },
+        ))
+    })?;
+    let mut snapshot_time = None;
+    let mut windows = Vec::new();
+    for row in rows {
+        let (time, window) = row?;
+        snapshot_time = Some(time);
+        windows.push(window);
+    }
+    Ok(snapshot_time.map(|time| (time, windows)))
+}
+
+// --- Raw Interval Queries ---
+/// Intervals overlapping [from, to), oldest first; an open interval is reported as ending at `now`.
+pub fn query_intervals_between(conn: &Connection, from: i64, to: i64, now: i64) -> SqlResult<Vec<IntervalRecord>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_intervals_between.sql"))?;
+    let rows = stmt.query_map(params![from, to, now], interval_record_from_row)?;
+    rows.collect()
+}
+
+/// Intervals moved to `app_intervals_archive` by aggregation overlapping [from, to), oldest first.
+pub fn query_archived_intervals(conn: &Connection, from: i64, to: i64, app_glob: Option<&str>) -> SqlResult<Vec<IntervalRecord>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_archived_intervals.sql"))?;
+    let rows = stmt.query_map(params![from, to, app_glob], interval_record_from_row)?;
+    rows.collect()
+}
+
+/// Foreground app changes between intervals starting in [from, to) and the longest run of one app,
+/// where intervals of that app at most `max_gap_secs` apart count as one run.
+pub fn query_switch_stats(conn: &Connection, from: i64, to: i64, now: i64, max_gap_secs: i64) -> SqlResult<SwitchStats> {
+    let locked = crate::detection::LOCKED_APP_NAME;
+    let switches = conn.query_row(include_str!("../../sql/query_app_switches.sql"), params![from, to, locked], |row| row.get(0))?;
+    let longest_streak = conn
+        .query_row(include_str!("../../sql/query_longest_streak.sql"), params![from, to, now, locked, max_gap_secs], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .optional()?;
+    Ok(SwitchStats { switches, longest_streak })
+}
+
+/// Maps the column list shared by the interval queries (id, app, titles, times, desktop, passive, device, repo,
+/// executable path, command line, ticket, user, remote session type and host, document).
+fn interval_record_from_row(row: &rusqlite::Row) -> SqlResult<IntervalRecord> {
+    Ok(IntervalRecord {
+        id: row.get(0)?,
+        app_name: row.get(1)?,
+        main_title: row.get(2)?,
+        detailed_title: row.get(3)?,
+        start_time: row.get(4)?,
+        end_time: row.get(5)?,
+        virtual_desktop: row.get(6)?,
+        passive: row.get(7)?,
+        device_id: row.get(8)?,
+        repo: row.get(9)?,
+        executable_path: row.get(10)?,
+        command_line: row.get(11)?,
+        ticket: row.get(12)?,
+        user_name: row.get(13)?,
+        session_type: row.get(14)?,
+        remote_host: row.get(15)?,
+        document: row.get(16)?,
+    })
+}
+
+// --- Interval editing (intervals delete/split, add-entry) ---
+/// A completed raw interval by id; None if it does not exist, is still open or was aggregated away.
+pub fn query_completed_interval(conn: &Connection, id: i64) -> SqlResult<Option<IntervalRecord>> {
+    conn.query_row(include_str!("../../sql/query_completed_interval.sql"), params![id], interval_record_from_row)
+        .optional()
+}
+
+/// Deletes a completed interval and records it in `audit_log`; false if there was no such interval.
+pub fn delete_interval(conn: &mut Connection, id: i64, now: i64) -> SqlResult<bool> {
+    let tx = conn.transaction()?;
+    let Some(before) = query_completed_interval(&tx, id)? else { return Ok(false) };
+    tx.execute(include_str!("../../sql/delete_interval.sql"), params![id])?;
+    insert_audit_log(&tx, now, "delete", id, &serde_json::json!({ "before": before }))?;
+    tx.commit()?;
+    Ok(true)
+}
+
+/// Splits a completed interval at `at` (strictly inside it) and records it in `audit_log`.
+/// Returns the id of the new second half, or None if there is no such interval or `at` is outside it.
+pub fn split_interval(conn: &mut Connection, id: i64, at: i64, now: i64) -> SqlResult<Option<i64>> {
+    let tx = conn.transaction()?;
+    let Some(before) = query_completed_interval(&tx, id)? else { return Ok(None) };
+    if tx.execute(include_str!("../../sql/split_interval_insert.sql"), params![id, at])? == 0 {
+        return Ok(None);
+    }
+    let new_id = tx.last_insert_rowid();
+    tx.execute(include_str!("../../sql/split_interval_truncate.sql"), params![id, at])?;
+    insert_audit_log(&tx, now, "split", id, &serde_json::json!({ "before": before, "at": at, "new_interval_id": new_id }))?;
+    tx.commit()?;
+    Ok(Some(new_id))
+}
+
+/// Inserts an already finished interval (manual entry) and records it in `audit_log`; returns its id.
+pub fn add_manual_interval(conn: &mut Connection, interval: &NewInterval, end_time: i64, now: i64) -> SqlResult<i64> {
+    let tx = conn.transaction()?;
+    let id = insert_new_interval(&tx, interval)?;
+    finalize_interval(&tx, id, end_time)?;
+    insert_audit_log(&tx, now, "add", id, &manual_entry_details(interval, end_time))?;
+    tx.commit()?;
+    Ok(id)
+}
+
+/// `audit_log.details` of a manual entry (shared with the PostgreSQL backend)
+pub(crate) fn manual_entry_details(interval: &NewInterval, end_time: i64) -> serde_json::Value {
+    serde_json::json!({
+        "added": {
+            "app_name": interval.app_name,
+            "detailed_title": interval.detailed_title,
+            "start_time": interval.start_time,
+            "end_time": end_time,
+            "device_id": interval.device_id,
+            "user_name": interval.user_name,
+        }
+    })
+}
+
+/// Records a wall-clock jump in `audit_log`; it concerns no single interval, so `interval_id` is 0.
+pub fn record_clock_adjustment(conn: &Connection, jump: &ClockJump) -> SqlResult<()> {
+    insert_audit_log(conn, jump.after, "clock_adjustment", 0, &serde_json::json!({ "jump": jump }))
+}
+
+/// Brings the open audio intervals of the sample's device and user in line with the apps playing now:
+/// stale ones end at their last heartbeat, stopped ones at `timestamp`, new apps start an interval.
+pub fn record_audio(conn: &mut Connection, sample: &AudioSample) -> SqlResult<()> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        include_str!("../../sql/close_stale_audio_intervals.sql"),
+        params![sample.device_id, sample.user_name, sample.stale_before],
+    )?;
+    let open: Vec<(i64, String)> = {
+        let mut stmt = tx.prepare(include_str!("../../sql/query_open_audio_intervals.sql"))?;
+        let rows = stmt.query_map(params![sample.device_id, sample.user_name], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<SqlResult<_>>()?
+    };
+    for (id, app_name) in &open {
+        let sql = if sample.apps.contains(app_name) {
+            include_str!("../../sql/heartbeat_audio_interval.sql")
+        } else {
+            include_str!("../../sql/finalize_audio_interval.sql")
+        };
+        tx.execute(sql, params![sample.timestamp, id])?;
+    }
+    let mut started: Vec<&String> = Vec::new();
+    for app_name in sample.apps {
+        if open.iter().any(|(_, open_app)| open_app == app_name) || started.contains(&app_name) {
+            continue;
+        }
+        tx.execute(
+            include_str!("../../sql/insert_audio_interval.sql"),
+            params![app_name, sample.timestamp, sample.device_id, sample.user_name],
+        )?;
+        started.push(app_name);
+    }
+    tx.commit()
+}
+
+/// Adds `count` failed detector polls to the day of `day_timestamp` in `tracker_daily_stats`.
+pub fn add_detection_errors(conn: &Connection, day_timestamp: i64, count: i64, last_error: &str) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/add_detection_errors.sql"), params![day_timestamp, count, last_error])?;
+    Ok(())
+}
+
+fn insert_audit_log(conn: &Connection, now: i64, action: &str, interval_id: i64, details: &serde_json::Value) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/insert_audit_log.sql"), params![now, action, interval_id, details.to_string()])?;
+    Ok(())
+}
+
+// --- Title normalization ---
+pub fn query_distinct_titles(conn: &Connection) -> SqlResult<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_distinct_titles.sql"))?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Renames stored titles (`normalize --apply-history`); summary rows of both titles are added together.
+pub fn rename_titles(conn: &mut Connection, renames: &[TitleRename], now: i64) -> SqlResult<usize> {
+    let tx = conn.transaction()?;
+    let mut changed = 0;
+    for rename in renames.iter().filter(|rename| rename.from != rename.to) {
+        let rename_params = params![rename.app_name, rename.from, rename.to];
+        changed += tx.execute(include_str!("../../sql/rename_title_intervals.sql"), rename_params)?;
+        changed += tx.execute(include_str!("../../sql/rename_title_intervals_archive.sql"), rename_params)?;
+        changed += tx.execute(include_str!("../../sql/rename_title_hourly.sql"), rename_params)?;
+        tx.execute(include_str!("../../sql/delete_title_hourly.sql"), params![rename.app_name, rename.from])?;
+        changed += tx.execute(include_str!("../../sql/rename_title_daily.sql"), rename_params)?;
+        tx.execute(include_str!("../../sql/delete_title_daily.sql"), params![rename.app_name, rename.from])?;
+        insert_audit_log(&tx, now, "normalize_title", 0, &title_rename_details(rename))?;
+    }
+    tx.commit()?;
+    Ok(changed)
+}
+
+/// `audit_log.details` of a title rename (shared with the PostgreSQL backend)
+pub(crate) fn title_rename_details(rename: &TitleRename) -> serde_json::Value {
+    serde_json::json!({ "app_name": rename.app_name, "from": rename.from, "to": rename.to })
+}
+
+// --- Purge ---
+/// Tables `purge --all` empties (every table of the schema).
+pub(crate) const PURGE_TABLES: &[&str] = &[
+    "app_intervals",
+    "app_intervals_archive",
+    "hourly_summary",
+    "daily_summary",
+    "daily_desktop_summary",
+    "daily_device_summary",
+    "daily_repo_summary",
+    "daily_path_summary",
+    "daily_ticket_summary",
+    "daily_user_summary",
+    "daily_remote_summary",
+    "daily_document_summary",
+    "daily_meeting_summary",
+    "audio_intervals",
+    "daily_audio_summary",
+    "days_summary_by_app",
+    "weeks_summary_by_app",
+    "tracker_daily_stats",
+    "window_snapshots",
+    "app_metadata",
+    "audit_log",
+    "break_reminders",
+    "calendar_events",
+    "habits",
+    "tracker_runs",
+    "tracker_run_events",
+    "sync_state",
+];
+
+/// Tables with per-app rows `purge --app` deletes from, and the column its range applies to
+/// (`weeks_summary_by_app` is rebuilt separately).
+pub(crate) const PURGE_APP_TABLES: &[(&str, &str)] = &[
+    ("app_intervals", "start_time"),
+    ("app_intervals_archive", "start_time"),
+    ("hourly_summary", "hour_timestamp"),
+    ("daily_summary", "day_timestamp"),
+    ("daily_desktop_summary", "day_timestamp"),
+    ("daily_device_summary", "day_timestamp"),
+    ("daily_repo_summary", "day_timestamp"),
+    ("daily_path_summary", "day_timestamp"),
+    ("daily_ticket_summary", "day_timestamp"),
+    ("daily_user_summary", "day_timestamp"),
+    ("daily_remote_summary", "day_timestamp"),
+    ("daily_document_summary", "day_timestamp"),
+    ("daily_meeting_summary", "day_timestamp"),
+    ("audio_intervals", "start_time"),
+    ("daily_audio_summary", "day_timestamp"),
+    ("days_summary_by_app", "day_timestamp"),
+    ("window_snapshots", "snapshot_time"),
+];
+
+/// Deletes what `scope` covers in one transaction; returns the rows removed per table.
+pub fn purge(conn: &mut Connection, scope: &PurgeScope, timezone: &ReportTimeZone) -> SqlResult<Vec<(String, usize)>> {
+    let tx = conn.transaction()?;
+    let mut removed = Vec::new();
+    match scope {
+        PurgeScope::All => {
+            for table in PURGE_TABLES {
+                removed.push((table.to_string(), tx.execute(&format!("DELETE FROM {}", table), [])?));
+            }
+        }
+        PurgeScope::App { app_name, from, to } => {
+            let (start, end) = (from.unwrap_or(i64::MIN), to.unwrap_or(i64::MAX));
+            for (table, time_column) in PURGE_APP_TABLES {
+                let sql = format!("DELETE FROM {} WHERE app_name = ?1 AND {} >= ?2 AND {} < ?3", table, time_column, time_column);
+                removed.push((table.to_string(), tx.execute(&sql, params![app_name, start, end])?));
+            }
+            // Weeks partly in the range are rebuilt from the days left
+            let count_weeks = || tx.query_row(include_str!("../../sql/count_app_weeks.sql"), params![app_name], |row| row.get::<_, i64>(0));
+            let weeks_before = count_weeks()?;
+            let from_week = from.map_or(i64::MIN, |from| timezone.week_start(from));
+            tx.execute(include_str!("../../sql/purge_app_weeks.sql"), params![app_name, from_week, end])?;
+            if let Some(rebuild_from) = from.or(*to) {
+                rebuild_weeks_summary(&tx, timezone.week_start(rebuild_from), timezone)?;
+            }
+            removed.push(("weeks_summary_by_app".to_string(), (weeks_before - count_weeks()?).max(0) as usize));
+            if from.is_none() && to.is_none() {
+                removed.push(("app_metadata".to_string(), tx.execute(include_str!("../../sql/delete_app_metadata.sql"), params![app_name])?));
+                removed.push(("audit_log".to_string(), tx.execute(include_str!("../../sql/purge_audit_log_app.sql"), params![app_name])?));
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(removed)
+}
+
+// --- App metadata (friendly names) ---
+pub fn query_app_metadata(conn: &Connection) -> SqlResult<Vec<AppMetadata>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_app_metadata.sql"))?;
+    let rows = stmt.query_map([], |row| {
+        let source: String = row.get(3)?;
+        Ok(AppMetadata {
+            app_name: row.get(0)?,
+            display_name: row.get(1)?,
+            icon_path: row.get(2)?,
+            source: AppMetadataSource::from_db(&source),
+        })
+    })?;
+    rows.collect()
+}
+
+/// Inserts or replaces an app's metadata; names set by the user are kept over ones read from executables.
+pub fn upsert_app_metadata(conn: &Connection, metadata: &AppMetadata, now: i64) -> SqlResult<()> {
+    conn.execute(
+        include_str!("../../sql/upsert_app_metadata.sql"),
+        params![metadata.app_name, metadata.display_name, metadata.icon_path, metadata.source.as_str(), now],
+    )?;
+    Ok(())
+}
+
+pub fn delete_app_metadata(conn: &Connection, app_name: &str) -> SqlResult<bool> {
+    Ok(conn.execute(include_str!("../../sql/delete_app_metadata.sql"), params![app_name])? > 0)
+}
+
+// --- Habits ---
+pub fn query_habits(conn: &Connection) -> SqlResult<Vec<Habit>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_habits.sql"))?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Habit { name: row.get(0)?, app_glob: row.get(1)?, daily_minutes: row.get(2)?, created_at: row.get(3)? })
+    })?;
+    rows.collect()
+}
+
+/// Adds a habit or redefines the one with the same name.
+pub fn upsert_habit(conn: &Connection, habit: &Habit) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/upsert_habit.sql"), params![habit.name, habit.app_glob, habit.daily_minutes, habit.created_at])?;
+    Ok(())
+}
+
+pub fn delete_habit(conn: &Connection, name: &str) -> SqlResult<bool> {
+    Ok(conn.execute(include_str!("../../sql/delete_habit.sql"), params![name])? > 0)
+}
+
+/// (local day start, app, seconds) from the day and days summaries since `from`, oldest first.
+pub fn query_daily_app_totals(conn: &Connection, from: i64) -> SqlResult<Vec<(i64, String, i64)>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_daily_app_totals.sql"))?;
+    let rows = stmt.query_map(params![from], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    rows.collect()
+}
+
+/// Replaces the stored events starting in [from, to) with `events` in one transaction.
+pub fn replace_calendar_events(conn: &mut Connection, from: i64, to: i64, events: &[CalendarEvent]) -> SqlResult<()> {
+    let tx = conn.transaction()?;
+    tx.execute(include_str!("../../sql/delete_calendar_events.sql"), params![from, to])?;
+    for event in events {
+        tx.execute(include_str!("../../sql/insert_calendar_event.sql"), params![event.start_time, event.uid, event.end_time, event.title])?;
+    }
+    tx.commit()
+}
+
+/// Stored events overlapping [from, to), in start order.
+pub fn query_calendar_events(conn: &Connection, from: i64, to: i64) -> SqlResult<Vec<CalendarEvent>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_calendar_events.sql"))?;
+    let rows = stmt.query_map(params![from, to], |row| {
+        Ok(CalendarEvent { uid: row.get(0)?, title: row.get(1)?, start_time: row.get(2)?, end_time: row.get(3)? })
+    })?;
+    rows.collect()
+}
+
+// --- Break reminders ---
+pub fn record_break_reminder(conn: &Connection, reminded_at: i64, active_secs: i64) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/insert_break_reminder.sql"), params![reminded_at, active_secs])?;
+    Ok(())
+}
+
+pub fn record_break_taken(conn: &Connection, reminded_at: i64, break_start: i64) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/update_break_taken.sql"), params![reminded_at, break_start])?;
+    Ok(())
+}
+
+/// (reminders shown, breaks taken after them) for reminders in [from, to).
+pub fn query_break_compliance(conn: &Connection, from: i64, to: i64) -> SqlResult<(i64, i64)> {
+    conn.query_row(include_str!("../../sql/query_break_compliance.sql"), params![from, to], |row| Ok((row.get(0)?, row.get(1)?)))
+}
+
+// --- Tracker runs (`gaps`) ---
+pub fn start_tracker_run(conn: &Connection, start_time: i64, device_id: &str, user_name: &str) -> SqlResult<i64> {
+    conn.execute(include_str!("../../sql/insert_tracker_run.sql"), params![start_time, device_id, user_name])?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn heartbeat_tracker_run(conn: &Connection, run_id: i64, at: i64) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/heartbeat_tracker_run.sql"), params![run_id, at])?;
+    Ok(())
+}
+
+pub fn end_tracker_run(conn: &Connection, run_id: i64, at: i64) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/end_tracker_run.sql"), params![run_id, at])?;
+    Ok(())
+}
+
+pub fn record_run_event(conn: &Connection, run_id: i64, at: i64, kind: RunEventKind) -> SqlResult<()> {
+    conn.execute(include_str!("../../sql/insert_tracker_run_event.sql"), params![run_id, at, kind.as_str()])?;
+    Ok(())
+}
+
+/// The runs of `user_name` overlapping [from, to) with their events; unknown event kinds are skipped.
+pub fn query_tracker_history(conn: &Connection, from: i64, to: i64, user_name: &str) -> SqlResult<TrackerHistory> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_tracker_runs.sql"))?;
+    let runs = stmt
+        .query_map(params![from, to, user_name], |row| {
+            Ok(TrackerRun { id: row.get(0)?, start_time: row.get(1)?, last_seen: row.get(2)?, end_time: row.get(3)? })
+        })?
+        .collect::<SqlResult<Vec<_>>>()?;
+    let mut stmt = conn.prepare(include_str!("../../sql/query_tracker_run_events.sql"))?;
+    let events = stmt
+        .query_map(params![from, to, user_name], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))?
+        .collect::<SqlResult<Vec<_>>>()?
+        .into_iter()
+        .filter_map(|(run_id, at, kind)| RunEventKind::parse(&kind).map(|kind| RunEvent { run_id, at, kind }))
+        .collect();
+    let first_run_start = conn.query_row(include_str!("../../sql/query_first_tracker_run.sql"), params![user_name], |row| row.get(0))?;
+    Ok(TrackerHistory { runs, events, first_run_start })
+}
+
+/// Hours in [from, to) already rolled into `hourly_summary`.
+pub fn query_aggregated_hours(conn: &Connection, from: i64, to: i64) -> SqlResult<Vec<i64>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_aggregated_hours.sql"))?;
+    let rows = stmt.query_map(params![from, to], |row| row.get(0))?;
+    rows.collect()
+}
+
+// --- Sync ---
+/// Returns up to `limit` completed intervals with an id above `after_id`, oldest first.
+pub fn query_unsynced_intervals(conn: &Connection, after_id: i64, limit: usize) -> SqlResult<Vec<IntervalRecord>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_unsynced_intervals.sql"))?;
+    let rows = stmt.query_map(params![after_id, limit as i64], interval_record_from_row)?;
+    rows.collect()
+}
+
+pub fn count_unsynced_intervals(conn: &Connection, after_id: i64) -> SqlResult<usize> {
+    conn.query_row(include_str!("../../sql/count_unsynced_intervals.sql"), params![after_id], |row| row.get(0))
+}
+
+pub fn query_sync_state(conn: &Connection) -> SqlResult<SyncState> {
+    let state = conn
+        .query_row(include_str!("../../sql/query_sync_state.sql"), [], |row| {
+            Ok(SyncState { last_interval_id: row.get(0)?, last_sync_time: row.get(1)?, last_sync_count: row.get(2)? })
+        })
+        .optional()?;
+    Ok(state.unwrap_or_default())
+}
+
+pub fn update_sync_state(conn: &Connection, state: &SyncState) -> SqlResult<()> {
+    conn.execute(
+        include_str!("../../sql/update_sync_state.sql"),
+        params![state.last_interval_id, state.last_sync_time, state.last_sync_count],
+    )?;
+    Ok(())
+}
+
+// --- Compaction ---
+/// Intervals scanned per compaction pass; the rest are merged on the next aggregation
+pub(crate) const COMPACTION_SCAN_LIMIT: usize = 50_000;
+/// Days of `hourly_summary` kept when `hourly_summary_days` is not set
+pub const DEFAULT_HOURLY_SUMMARY_DAYS: u64 = 90;
+
+/// A run of consecutive intervals on the same target, merged into its first interval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IntervalMerge {
+    pub keep_id: i64,
+    pub end_time: i64,
+    pub merged_ids: Vec<i64>,
+}
+
+/// Finds runs of consecutive intervals (per device and user) on the same app, titles, desktop, repository, process, ticket, remote session, document and passive flag
+/// separated by less than `merge_gap_secs`. The gaps between them are counted as time on that target.
+pub(crate) fn plan_interval_merges(intervals: &[IntervalRecord], merge_gap_secs: i64) -> Vec<IntervalMerge> {
+    let mut sorted: Vec<&IntervalRecord> = intervals.iter().collect();
+    sorted.sort_by(|a, b| (&a.device_id, &a.user_name, a.start_time, a.id).cmp(&(&b.device_id, &b.user_name, b.start_time, b.id)));
+    let same_target = |a: &IntervalRecord, b: &IntervalRecord| {
+        a.device_id == b.device_id
+            && a.user_name == b.user_name
+            && a.app_name == b.app_name
+            && a.main_title == b.main_title
+            && a.detailed_title == b.detailed_title
+            && a.virtual_desktop == b.virtual_desktop
+            && a.repo == b.repo
+            && a.executable_path == b.executable_path
+            && a.command_line == b.command_line
+            && a.ticket == b.ticket
+            && a.session_type == b.session_type
+            && a.remote_host == b.remote_host
+            && a.document == b.document
+            && a.passive == b.passive
+    };
+
+    let mut merges = Vec::new();
+    let mut runs = sorted.into_iter();
+    let Some(mut first) = runs.next() else { return merges };
+    let mut current = IntervalMerge { keep_id: first.id, end_time: first.end_time, merged_ids: Vec::new() };
+    for interval in runs {
+        if same_target(first, interval) && interval.start_time - current.end_time < merge_gap_secs {
+            current.end_time = current.end_time.max(interval.end_time);
+            current.merged_ids.push(interval.id);
+            continue;
+        }
+        let next = IntervalMerge { keep_id: interval.id, end_time: interval.end_time, merged_ids: Vec::new() };
+        let finished = std::mem::replace(&mut current, next);
+        if !finished.merged_ids.is_empty() {
+            merges.push(finished);
+        }
+        first = interval;
+    }
+    if !current.merged_ids.is_empty() {
+        merges.push(current);
+    }
+    merges
+}
+
+/// Merges flapping intervals (see `plan_interval_merges`); returns how many rows were removed.
+/// Only completed intervals above the sync watermark are touched, so already uploaded rows never change.
+fn compact_intervals(conn: &Connection, merge_gap_secs: i64) -> SqlResult<usize> {
+    let watermark = query_sync_state(conn)?.last_interval_id;
+    let intervals = query_unsynced_intervals(conn, watermark, COMPACTION_SCAN_LIMIT)?;
+    let mut removed = 0;
+    for merge in plan_interval_merges(&intervals, merge_gap_secs) {
+        conn.execute(include_str!("../../sql/merge_interval_extend.sql"), params![merge.keep_id, merge.end_time])?;
+        for id in &merge.merged_ids {
+            removed += conn.execute(include_str!("../../sql/delete_interval.sql"), params![id])?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Per-app totals of one week in `weeks_summary_by_app`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WeekTotal {
+    pub app_name: String,
+    pub week_timestamp: i64,
+    pub total_duration_secs: i64,
+    pub passive_duration_secs: i64,
+}
+
+/// Sums `days_summary_by_app` rows (app, day, total, passive) into weeks of `timezone`.
+/// Weeks are grouped here rather than in SQL so DST changes inside a week do not split it.
+pub(crate) fn plan_week_totals(days: &[(String, i64, i64, i64)], timezone: &ReportTimeZone) -> Vec<WeekTotal> {
+    let mut weeks: BTreeMap<(String, i64), (i64, i64)> = BTreeMap::new();
+    for (app_name, day_timestamp, total, passive) in days {
+        let week = weeks.entry((app_name.clone(), timezone.week_start(*day_timestamp))).or_default();
+        week.0 += total;
+        week.1 += passive;
+    }
+    weeks
+        .into_iter()
+        .map(|((app_name, week_timestamp), (total_duration_secs, passive_duration_secs))| WeekTotal {
+            app_name,
+            week_timestamp,
+            total_duration_secs,
+            passive_duration_secs,
+        })
+        .collect()
+}
+
+/// A break at least this long (or a locked screen) ends a continuous session
+const SESSION_BREAK_SECS: i64 = 300;
+
+/// Interval count and longest continuous session of one day, added to `tracker_daily_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct TrackerDay {
+    pub day_timestamp: i64,
+    pub interval_count: i64,
+    pub longest_session_secs: i64,
+}
+
+/// Counts completed raw intervals (app, start, end; sorted by start) per local day and finds the
+/// longest session of each day: intervals chained by gaps shorter than `SESSION_BREAK_SECS`,
+/// credited to the day the session started. Sessions are cut at the end of the batch.
+pub(crate) fn plan_tracker_days(intervals: &[(String, i64, i64)], timezone: &ReportTimeZone) -> Vec<TrackerDay> {
+    let mut days: BTreeMap<i64, TrackerDay> = BTreeMap::new();
+    let mut session: Option<(i64, i64)> = None;
+    let end_session = |session: Option<(i64, i64)>, days: &mut BTreeMap<i64, TrackerDay>| {
+        if let Some((start, end)) = session {
+            let day_timestamp = timezone.day_start(start);
+            let day = days.entry(day_timestamp).or_insert(TrackerDay { day_timestamp, ..TrackerDay::default() });
+            day.longest_session_secs = day.longest_session_secs.max(end - start);
+        }
+    };
+    for (app_name, start, end) in intervals {
+        let day_timestamp = timezone.day_start(*start);
+        days.entry(day_timestamp).or_insert(TrackerDay { day_timestamp, ..TrackerDay::default() }).interval_count += 1;
+        if app_name == crate::detection::LOCKED_APP_NAME {
+            end_session(session.take(), &mut days);
+            continue;
+        }
+        session = match session {
+            Some((session_start, session_end)) if *start - session_end < SESSION_BREAK_SECS => Some((session_start, session_end.max(*end))),
+            previous => {
+                end_session(previous, &mut days);
+                Some((*start, *end))
+            }
+        };
+    }
+    end_session(session, &mut days);
+    days.into_values().collect()
+}
+
+/// Adds the raw intervals about to be aggregated (ending by `until`) to `tracker_daily_stats`.
+fn record_tracker_days(conn: &Connection, until: i64, timezone: &ReportTimeZone) -> SqlResult<usize> {
+    let intervals = query_completed_intervals_until(conn, until)?;
+    let mut written = 0;
+    for day in plan_tracker_days(&intervals, timezone) {
+        written += conn.execute(
+            include_str!("../../sql/upsert_tracker_daily_intervals.sql"),
+            params![day.day_timestamp, day.interval_count, day.longest_session_secs],
+        )?;
+    }
+    Ok(written)
+}
+
+/// App and times of completed raw intervals ending by `until`, oldest first.
+pub fn query_completed_intervals_until(conn: &Connection, until: i64) -> SqlResult<Vec<(String, i64, i64)>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_completed_intervals_until.sql"))?;
+    let rows = stmt.query_map(params![until], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    rows.collect()
+}
+
+/// Recomputes every week from `from_week` on out of `days_summary_by_app`; returns the rows written.
+fn rebuild_weeks_summary(conn: &Connection, from_week: i64, timezone: &ReportTimeZone) -> SqlResult<usize> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_days_summary_since.sql"))?;
+    let days = stmt
+        .query_map(params![from_week], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<SqlResult<Vec<_>>>()?;
+    let mut written = 0;
+    for week in plan_week_totals(&days, timezone) {
+        written += conn.execute(
+            include_str!("../../sql/upsert_weeks_summary.sql"),
+            params![week.app_name, week.week_timestamp, week.total_duration_secs, week.passive_duration_secs],
+        )?;
+    }
+    Ok(written)
+}
+
+// --- Aggregation and Cleanup ---
+/// Returns (start of the current hour, start of yesterday): raw intervals ending before the first
+/// are rolled into summaries, detailed summaries older than the second into `days_summary_by_app`.
+/// Both are local to `timezone`.
+/// Oldest hour aggregation keeps in `hourly_summary`: 1 day keeps yesterday and today (the day
+/// cutoff), each further day one more (a DST change moves it by an hour at most).
+fn hourly_summary_cutoff(cutoff_day_ts: i64, days: u64) -> i64 {
+    cutoff_day_ts - days.saturating_sub(1) as i64 * 86_400
+}
+
+fn aggregation_cutoffs(timezone: &ReportTimeZone, clock: &dyn Clock) -> (i64, i64) {
+    let now = clock.now();
+    let current_hour_start = timezone.hour_start(now);
+    let cutoff_day_ts = timezone.previous_day_start(now);
+    (current_hour_start, cutoff_day_ts)
+}
+
+/// Rolls completed raw intervals into the summary tables. With `merge_gap_secs`, flapping
+/// intervals of the same target are first merged (see `plan_interval_merges`).
+/// Hour and day buckets are the local hours and days of `timezone` (see `Bucket::spans`), so a batch
+/// spanning a DST change is split where the zone's hours and days actually start.
+/// Weeks that receive rolled-up days are rebuilt in `weeks_summary_by_app`.
+/// "Now" (which hour is still open) comes from `clock`. With `archive_raw`, aggregated raw
+/// intervals are moved to `app_intervals_archive` instead of being dropped.
+pub fn aggregate_and_cleanup(
+    conn: &mut Connection,
+    merge_gap_secs: Option<i64>,
+    timezone: &ReportTimeZone,
+    clock: &dyn Clock,
+    archive_raw: bool,
+    hourly_summary_days: u64,
+) -> SqlResult<AggregationReport> {
+    run_aggregation(conn, merge_gap_secs, timezone, clock, archive_raw, hourly_summary_days, true)
+}
+
+/// Fills each bucket's temp table (`Bucket::table`) with its `spans` from the earliest raw interval
+/// ending by `until` on, for the aggregation statements to split intervals at.
+fn write_buckets(conn: &Connection, timezone: &ReportTimeZone, until: i64) -> SqlResult<()> {
+    let from: Option<i64> = conn.query_row(include_str!("../../sql/query_min_start_time.sql"), params![until], |row| row.get(0))?;
+    for bucket in Bucket::ALL {
+        conn.execute_batch(&format!(
+            "CREATE TEMP TABLE IF NOT EXISTS {table} (bucket INTEGER PRIMARY KEY, bucket_end INTEGER NOT NULL);
+             DELETE FROM {table};",
+            table = bucket.table()
+        ))?;
+        let mut stmt = conn.prepare(&format!("INSERT INTO {} (bucket, bucket_end) VALUES (?1, ?2)", bucket.table()))?;
+        for (start, end) in from.map(|from| bucket.spans(timezone, from, until)).unwrap_or_default() {
+            stmt.execute(params![start, end])?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `aggregate_and_cleanup` inside a transaction that is rolled back, reporting what it would change.
+pub fn preview_aggregation(
+    conn: &mut Connection,
+    merge_gap_secs: Option<i64>,
+    timezone: &ReportTimeZone,
+    clock: &dyn Clock,
+    archive_raw: bool,
+    hourly_summary_days: u64,
+) -> SqlResult<AggregationReport> {
+    run_aggregation(conn, merge_gap_secs, timezone, clock, archive_raw, hourly_summary_days, false)
+}
+
+fn run_aggregation(
+    conn: &mut Connection,
+    merge_gap_secs: Option<i64>,
+    timezone: &ReportTimeZone,
+    clock: &dyn Clock,
+    archive_raw: bool,
+    hourly_summary_days: u64,
+    commit: bool,
+) -> SqlResult<AggregationReport> {
+    info!("Starting aggregation and cleanup{}...", if commit { "" } else { " (dry run)" });
+    let mut report = AggregationReport::default();
+    let tx = conn.transaction()?;
+    if let Some(gap) = merge_gap_secs {
+        report.merged_intervals = compact_intervals(&tx, gap)?;
+        if report.merged_intervals > 0 {
+            debug!("-> Merged {} raw intervals into their neighbours.", report.merged_intervals);
+        }
+    }
+    let (current_hour_start, cutoff_day_ts) = aggregation_cutoffs(timezone, clock);
+    let today_start = timezone.day_start(clock.now());
+    write_buckets(&tx, timezone, current_hour_start.max(today_start))?;
+    let max_end_time_to_process: Option<i64> = tx.query_row(
+        include_str!("../../sql/query_max_end_time.sql"),
+        params![current_hour_start],
+        |row| row.get(0),
+    )?;
+
+    if let Some(aggregate_until) = max_end_time_to_process {
+        if aggregate_until < current_hour_start {
+            debug!(
+                "Aggregating raw intervals completed before: {}",
+                Utc.timestamp_opt(aggregate_until, 0).unwrap() // Consider handling error
+            );
+            for summary in dimensions::SUMMARIES {
+                let rows = tx.execute(&summary.aggregate(), params![aggregate_until])?;
+                *(summary.report_rows)(&mut report) = rows;
+                if rows > 0 {
+                    debug!("-> Aggregated {} rows into {}.", rows, summary.table);
+                }
+            }
+            report.tracker_day_rows = record_tracker_days(&tx, aggregate_until, timezone)?;
+            if report.tracker_day_rows > 0 {
+                debug!("-> Updated {} rows of tracker_daily_stats.", report.tracker_day_rows);
+            }
+            if archive_raw {
+                report.archived_raw = tx.execute(
+                    include_str!("../../sql/archive_aggregated.sql"),
+                    params![aggregate_until, clock.now()],
+                )?;
+                debug!("-> Archived {} raw interval rows.", report.archived_raw);
+            }
+            report.deleted_raw = tx.execute(
+                include_str!("../../sql/delete_aggregated.sql"),
+                params![aggregate_until],
+            )?;
+            if report.deleted_raw > 0 {
+                debug!("-> Deleted {} processed raw interval rows.", report.deleted_raw);
+            }
+        } else {
+            debug!("No full hours completed since last aggregation to process.");
+        }
+    } else {
+        debug!("No completed raw intervals found to aggregate.");
+    }
+
+    // Audio has no hourly summary: whole local days only, so hour periods of today still see every interval
+    report.audio_rows = tx.execute(&dimensions::AUDIO_SUMMARY.aggregate(), params![today_start])?;
+    report.deleted_audio = tx.execute(include_str!("../../sql/delete_aggregated_audio.sql"), params![today_start])?;
+    if report.audio_rows > 0 {
+        debug!("-> Aggregated {} rows into daily audio summary, deleted {} audio intervals.", report.audio_rows, report.deleted_audio);
+    }
+
+    debug!(
+        "Aggregating detailed summaries older than timestamp: {}",
+        cutoff_day_ts
+    );
+    let rebuild_from_day: Option<i64> = tx.query_row(
+        include_str!("../../sql/query_weeks_rebuild_from.sql"),
+        params![cutoff_day_ts],
+        |row| row.get(0),
+    )?;
+    report.days_summary_rows = tx.execute(
+        include_str!("../../sql/aggregate_days_summary.sql"),
+        params![cutoff_day_ts],
+    )?;
+    if report.days_summary_rows > 0 {
+        debug!("-> Aggregated older daily data into days_summary_by_app.");
+    }
+    if let Some(first_day) = rebuild_from_day {
+        report.weeks_summary_rows = rebuild_weeks_summary(&tx, timezone.week_start(first_day), timezone)?;
+        debug!("-> Rebuilt {} rows of weeks_summary_by_app.", report.weeks_summary_rows);
+    }
+    report.deleted_daily = tx.execute(
+        include_str!("../../sql/delete_aggregated_daily.sql"),
+        params![cutoff_day_ts],
+    )?;
+    if report.deleted_daily > 0 {
+        debug!("-> Deleted {} old daily summary rows.", report.deleted_daily);
+    }
+    report.deleted_hourly = tx.execute(
+        include_str!("../../sql/delete_aggregated_hourly.sql"),
+        params![hourly_summary_cutoff(cutoff_day_ts, hourly_summary_days)],
+    )?;
+    if report.deleted_hourly > 0 {
+        debug!("-> Deleted {} old hourly summary rows.", report.deleted_hourly);
+    }
+    if commit {
+        tx.commit()?;
+        info!("Aggregation and cleanup finished.");
+    } else {
+        tx.rollback()?;
+        info!("Aggregation dry run finished; changes rolled back.");
+    }
+    Ok(report)
+}
+
+// --- Statistics Querying ---
+
+/// Helper to calculate start (inclusive) and end (exclusive) timestamps for a period
+/// (day and hour boundaries are local to `timezone`, "now" comes from `clock`)
+pub fn calculate_timestamps(period: TimePeriod, timezone: &ReportTimeZone, clock: &dyn Clock) -> (i64, i64) {
+    let now = clock.now();
+
+    match period {
+        TimePeriod::Today => {
+            let start = timezone.day_start(now);
+            let end = timezone.next_day_start(now);
+            (start, end)
+        }
+        TimePeriod::LastCompletedHour => {
+            let end = timezone.hour_start(now);
+            let start = end - 3600;
+            (start, end)
+        }
+        TimePeriod::CurrentHour => {
+            let start = timezone.hour_start(now);
+            let end = now + 1;
+            (start, end)
+        }
+        TimePeriod::ThisWeek => (timezone.week_start(now), timezone.add_weeks(now, 1)),
+        TimePeriod::LastWeek => (timezone.add_weeks(now, -1), timezone.week_start(now)),
+        TimePeriod::Last12Weeks => (timezone.add_weeks(now, -11), timezone.add_weeks(now, 1)),
+    }
+}
+
+/// End of the part of `period` read from `weeks_summary_by_app` (whole weeks before the current one);
+/// equal to the period start when the period has no such weeks.
+pub fn weeks_summary_until(period: TimePeriod, timezone: &ReportTimeZone, clock: &dyn Clock) -> i64 {
+    let (start, end) = calculate_timestamps(period, timezone, clock);
+    match period {
+        TimePeriod::LastWeek | TimePeriod::Last12Weeks => end.min(timezone.week_start(clock.now())),
+        _ => start,
+    }
+}
+
+/// Refuses a `filter.user` the summaries behind `level` over `period` cannot honor. Only the raw intervals,
+/// `daily_user_summary` (whole days, no titles) and the audio summary know whose time they hold; leaving the
+/// other summaries out would report too little. The title drill-down (`query_app_titles`) checks as `Detailed`.
+pub(crate) fn check_user_filter(period: TimePeriod, level: AggregationLevel, filter: &StatsFilter) -> AppResult<()> {
+    let Some(user) = &filter.user else { return Ok(()) };
+    // Aggregation stops at the current hour, so it is only ever read from raw intervals
+    let per_user = filter.stream == IntervalStream::Audio
+        || period == TimePeriod::CurrentHour
+        || match level {
+            AggregationLevel::ByApplication => period.spans_whole_days() && filter.title_contains.is_none(),
+            // Computed from raw intervals; the stats filters do not apply
+            AggregationLevel::Focus => true,
+            // Daily summaries, which hour periods do not read
+            _ => dimensions::for_level(level).is_some_and(|(summary, _)| summary.keeps_users() || !period.spans_whole_days()),
+        };
+    if per_user {
+        return Ok(());
+    }
+    Err(crate::errors::AppError::Config(format!(
+        "{} stats for {} cannot be limited to user '{}': their summaries add up every user's time (pass --all-users)",
+        level, period, user
+    )))
+}
+
+pub fn query_stats(
+    conn: &Connection,
+    period: TimePeriod,
+    level: AggregationLevel,
+    filter: &StatsFilter,
+    timezone: &ReportTimeZone,
+    clock: &dyn Clock,
+) -> SqlResult<AggregatedResult> {
+    let (period_start_ts, period_end_ts) = calculate_timestamps(period, timezone, clock);
+    let now_ts = clock.now(); // Needed for active intervals
+
+    // Use period_end_ts unless it's in the future (can happen for 'Today' end calc)
+    // We want the effective 'now' for COALESCE, but the period boundary for MIN.
+    let effective_end_ts = now_ts.min(period_end_ts);
+
+    debug!(
+        "Querying stats for period: {:?}, level: {:?}, period_start: {}, period_end: {}, now: {}, filter: {:?}",
+        period, level, period_start_ts, period_end_ts, now_ts, filter
+    );
+
+    // SQLite treats a negative LIMIT as "no limit"
+    let limit = filter.top.map_or(-1, |n| n as i64);
+    let min_duration = filter.min_duration_secs.unwrap_or(0);
+    let params = named_params! {
+        ":start": period_start_ts,
+        ":end": period_end_ts,
+        ":whole_days": period.spans_whole_days(),
+        ":effective_end": effective_end_ts,
+        ":now": now_ts,
+        ":app_glob": filter.app_glob,
+        ":title_contains": filter.title_contains,
+        ":min_duration": min_duration,
+        ":limit": limit,
+        ":exclude_passive": filter.exclude_passive,
+        ":user": filter.user,
+    };
+
+    if filter.stream == IntervalStream::Audio {
+        // Only per-app totals exist for audio (`commands::stats` rejects other levels)
+        let audio_params = named_params! {
+            ":start": period_start_ts,
+            ":end": period_end_ts,
+            ":whole_days": period.spans_whole_days(),
+            ":effective_end": effective_end_ts,
+            ":app_glob": filter.app_glob,
+            ":min_duration": min_duration,
+            ":limit": limit,
+            ":user": filter.user,
+        };
+        let mut stmt = conn.prepare(include_str!("../../sql/query_stats_audio.sql"))?;
+        let rows = stmt.query_map(audio_params, |row| Ok((row.get(0)?, row.get(1)?)))?;
+        return Ok(AggregatedResult::ByApp(rows.collect::<SqlResult<_>>()?));
+    }
+
+    match level {
+        AggregationLevel::ByApplication => {
+            // Summary and raw rows are merged, filtered, sorted and limited in SQL
+            let weeks_until = weeks_summary_until(period, timezone, clock);
+            let mut by_app_params = params.to_vec();
+            by_app_params.push((":weeks_until", &weeks_until as &dyn rusqlite::ToSql));
+            let mut stmt = conn.prepare(include_str!("../../sql/query_stats_by_app.sql"))?;
+            let rows = stmt.query_map(by_app_params.as_slice(), |row| {
+                let app: String = row.get(0)?;
+                let secs: i64 = row.get(1).unwrap_or(0); // SUM might be NULL if no rows
+                Ok((app, secs))
+            })?;
+            let mut results: Vec<(String, i64)> = Vec::new();
+            for result in rows {
+                match result {
+                    Ok(row) => results.push(row),
+                    Err(e) => warn!("Error processing stats row (by app): {}", e),
+                }
+            }
+            Ok(AggregatedResult::ByApp(results))
+        }
+
+        AggregationLevel::Detailed => {
+            let mut stmt = conn.prepare(include_str!("../../sql/query_stats_detailed.sql"))?;
+            let rows = stmt.query_map(params, |row| {
+                Ok(DetailedUsageRecord {
+                    app_name: row.get(0)?,
+                    detailed_title: row.get(1)?,
+                    total_duration_secs: row.get(2).unwrap_or(0), // SUM might be NULL if no rows
+                })
+            })?;
+            let mut results: Vec<DetailedUsageRecord> = Vec::new();
+            for result in rows {
+                match result {
+                    Ok(record) => results.push(record),
+                    Err(e) => warn!("Error processing detailed stats row: {}", e),
+                }
+            }
+            Ok(AggregatedResult::Detailed(results))
+        }
+
+        AggregationLevel::ByDesktop
+        | AggregationLevel::ByDevice
+        | AggregationLevel::ByRepo
+        | AggregationLevel::ByPath
+        | AggregationLevel::ByMeeting
+        | AggregationLevel::ByTicket
+        | AggregationLevel::ByUser
+        | AggregationLevel::ByDocument
+        | AggregationLevel::ByRemote => {
+            let (summary, stats) = dimensions::for_level(level).expect("every dimension level has a summary in SUMMARIES");
+            let results = query_dimension_totals(conn, &summary.stats_query(stats), params, stats.label)?;
+            Ok((stats.into_result)(results))
+        }
+
+        AggregationLevel::Focus => {
+            // Computed from raw intervals; the stats filters do not apply
+            let mut intervals = query_intervals_between(conn, period_start_ts, period_end_ts, now_ts)?;
+            intervals.extend(query_archived_intervals(conn, period_start_ts, period_end_ts, None)?);
+            Ok(AggregatedResult::Focus(crate::focus::compute(&intervals, period_start_ts, period_end_ts, timezone)))
+        }
+
+        AggregationLevel::Profile => {
+            let mut stmt = conn.prepare(include_str!("../../sql/query_hourly_totals.sql"))?;
+            let hour_totals = stmt
+                .query_map(
+                    named_params! {
+                        ":start": period_start_ts,
+                        ":end": period_end_ts,
+                        ":app_glob": filter.app_glob,
+                        ":title_contains": filter.title_contains,
+                        ":exclude_passive": filter.exclude_passive,
+                        ":user": filter.user,
+                    },
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?
+                .collect::<SqlResult<Vec<(i64, i64)>>>()?;
+            let intervals = query_intervals_between(conn, period_start_ts, effective_end_ts, now_ts)?;
+            Ok(AggregatedResult::Profile(Box::new(crate::hour_profile::build(&hour_totals, &intervals, filter, period_start_ts, effective_end_ts, timezone))))
+        }
+    }
+}
+
+/// Runs the `stats` statement of one dimension (see `dimensions::SUMMARIES`); unreadable rows are
+/// logged and skipped like those of the other levels.
+fn query_dimension_totals(conn: &Connection, sql: &str, params: &[(&str, &dyn rusqlite::ToSql)], label: &str) -> SqlResult<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, |row| {
+        let key: String = row.get(0)?;
+        let secs: i64 = row.get(1).unwrap_or(0); // SUM might be NULL if no rows
+        Ok((key, secs))
+    })?;
+    let mut results: Vec<(String, i64)> = Vec::new();
+    for result in rows {
+        match result {
+            Ok(row) => results.push(row),
+            Err(e) => warn!("Error processing stats row ({}): {}", label, e),
+        }
+    }
+    Ok(results)
+}
+
+/// Time per app between two local day boundaries (`ask`), through the same query as `stats`.
+pub fn query_app_totals_between(conn: &Connection, start: i64, end: i64, filter: &StatsFilter, now: i64) -> SqlResult<Vec<(String, i64)>> {
+    let limit = filter.top.map_or(-1, |n| n as i64);
+    let mut stmt = conn.prepare(include_str!("../../sql/query_stats_by_app.sql"))?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":start": start,
+            ":end": end,
+            ":whole_days": true,
+            // Older days are all in days_summary_by_app; weeks_summary_by_app is not needed
+            ":weeks_until": start,
+            ":effective_end": now.min(end),
+            ":now": now,
+            ":app_glob": filter.app_glob,
+            ":title_contains": filter.title_contains,
+            ":min_duration": filter.min_duration_secs.unwrap_or(0),
+            ":limit": limit,
+            ":exclude_passive": filter.exclude_passive,
+            ":user": filter.user,
+        },
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?.unwrap_or(0))),
+    )?;
+    rows.collect()
+}
+
+/// App names with the most tracked time, most used first (`ask` passes them to the model).
+pub fn query_top_app_names(conn: &Connection, limit: usize) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare(include_str!("../../sql/query_top_app_names.sql"))?;
+    let rows = stmt.query_map([limit as i64], |row| row.get(0))?;
+    rows.collect()
+}
+
+// --- Drill-down (stats --interactive) ---
+/// Usage of one application per detailed window title within `period` (exact app name match).
+pub fn query_app_titles(
+    conn: &Connection,
+    period: TimePeriod,
+    app_name: &str,
+    filter: &StatsFilter,
+    timezone: &ReportTimeZone,
+    clock: &dyn Clock,
+) -> SqlResult<Vec<DetailedUsageRecord>> {
+    let (period_start_ts, period_end_ts) = calculate_timestamps(period, timezone, clock);
+    let now_ts = clock.now();
+    let mut stmt = conn.prepare(include_str!("../../sql/query_app_titles.sql"))?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":app_name": app_name,
+            ":start": period_start_ts,
+            ":end": period_end_ts,
+            ":whole_days": period.spans_whole_days(),
+            ":effective_end": now_ts.min(period_end_ts),
+            ":now": now_ts,
+            ":title_contains": filter.title_contains,
+            ":min_duration": filter.min_duration_secs.unwrap_or(0),
+            ":limit": filter.top.map_or(-1, |n| n as i64),
+            ":exclude_passive": filter.exclude_passive,
+            ":user": filter.user,
+        },
+        |row| {
+            Ok(DetailedUsageRecord {
+                app_name: row.get(0)?,
+                detailed_title: row.get(1)?,
+                total_duration_secs: row.get(2).unwrap_or(0),
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+/// Raw intervals of one application within `period`, oldest first; `title` narrows to one detailed title.
+pub fn query_target_intervals(
+    conn: &Connection,
+    period: TimePeriod,
+    app_name: &str,
+    title: Option<&str>,
+    timezone: &ReportTimeZone,
+    clock: &dyn Clock,
+) -> SqlResult<Vec<IntervalRecord>> {
+    let (period_start_ts, period_end_ts) = calculate_timestamps(period, timezone, clock);
+    let now_ts = clock.now();
+    let mut stmt = conn.prepare(include_str!("../../sql/query_target_intervals.sql"))?;
+    let rows = stmt.query_map(params![app_name, period_start_ts, period_end_ts, now_ts, title], interval_record_from_row)?;
+    rows.collect()
+}
@@ -0,0 +1,188 @@
+// src/persistence/pool.rs
+// Connection sharing within one process. Ownership model:
+// - the tracker's persistence thread owns the only long-lived writer (its own `SqliteStorage`), never shared;
+// - everything else that touches the database while the process runs (gRPC calls, GUI refreshes,
+//   `Tracker::query`) borrows a storage from a `StoragePool` and gives its connection back on drop.
+// SQLite runs in WAL mode, so pooled readers never block the writer nor wait for it; the occasional
+// pooled write (gRPC `Aggregate`) waits on SQLite's lock (`BUSY_TIMEOUT`). Pooled connections are
+// blocking: async callers take them inside `spawn_blocking`. One-shot commands keep `open_storage`.
+
+use super::{SqliteStorage, Storage};
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::errors::AppResult;
+use rusqlite::Connection;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+
+/// Connections a `StoragePool` keeps open at most; further callers wait for one to be returned.
+pub const DEFAULT_POOL_SIZE: usize = 4;
+
+/// Hands out storages on shared connections; cheap to clone (all clones share the connections).
+/// Nothing is opened until the first `get`.
+#[derive(Clone)]
+pub struct StoragePool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    config: AppConfig,
+    clock: Arc<dyn Clock>,
+    max_size: usize,
+    // Read from the keyring once, on the first connection
+    passphrase: OnceLock<Option<String>>,
+    state: Mutex<PoolState>,
+    returned: Condvar,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: Vec<Connection>,
+    // Idle plus handed out
+    open: usize,
+}
+
+impl StoragePool {
+    pub fn new(config: &AppConfig, clock: Arc<dyn Clock>) -> Self {
+        Self::with_size(config, clock, DEFAULT_POOL_SIZE)
+    }
+
+    pub fn with_size(config: &AppConfig, clock: Arc<dyn Clock>, max_size: usize) -> Self {
+        // Every in-memory connection is a database of its own, so share a single one
+        let max_size = if config.database_path == Path::new(super::IN_MEMORY_PATH) { 1 } else { max_size.max(1) };
+        StoragePool {
+            inner: Arc::new(PoolInner {
+                config: config.clone(),
+                clock,
+                max_size,
+                passphrase: OnceLock::new(),
+                state: Mutex::new(PoolState::default()),
+                returned: Condvar::new(),
+            }),
+        }
+    }
+
+    /// A storage on a pooled connection, waiting while all `max_size` are in use.
+    /// With `database_url` set each call connects to PostgreSQL as `open_storage` does.
+    pub fn get(&self) -> AppResult<Box<dyn Storage>> {
+        let config = &self.inner.config;
+        if config.database_url.is_some() {
+            return super::open_storage_with_clock(config, self.inner.clock.clone());
+        }
+        let storage = SqliteStorage::from_pooled(self.connection()?);
+        Ok(Box::new(super::configure_sqlite(storage, config, self.inner.clock.clone())))
+    }
+
+    /// A pooled SQLite connection (opened now if none is idle and the pool is not full).
+    pub fn connection(&self) -> AppResult<PooledConnection> {
+        let mut state = self.lock_state();
+        loop {
+            if let Some(conn) = state.idle.pop() {
+                return Ok(PooledConnection { conn: Some(conn), pool: Some(self.inner.clone()) });
+            }
+            if state.open < self.inner.max_size {
+                state.open += 1;
+                // Opening (and SQLCipher key derivation) happens outside the lock
+                drop(state);
+                return match self.open_connection() {
+                    Ok(conn) => Ok(PooledConnection { conn: Some(conn), pool: Some(self.inner.clone()) }),
+                    Err(e) => {
+                        self.inner.forget_one();
+                        Err(e)
+                    }
+                };
+            }
+            state = self.inner.returned.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+
+    fn open_connection(&self) -> AppResult<Connection> {
+        let config = &self.inner.config;
+        let passphrase = match self.inner.passphrase.get() {
+            Some(passphrase) => passphrase,
+            None => {
+                let passphrase = super::database_passphrase(config)?;
+                self.inner.passphrase.get_or_init(|| passphrase)
+            }
+        };
+        log::debug!("Opening pooled connection to {:?}.", config.database_path);
+        Ok(super::open_database(config, passphrase.as_deref())?)
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, PoolState> {
+        self.inner.lock_state()
+    }
+}
+
+impl fmt::Debug for StoragePool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock_state();
+        f.debug_struct("StoragePool")
+            .field("database", &super::describe_location(&self.inner.config))
+            .field("max_size", &self.inner.max_size)
+            .field("open", &state.open)
+            .field("idle", &state.idle.len())
+            .finish()
+    }
+}
+
+impl PoolInner {
+    fn lock_state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// A connection was closed instead of returned; lets a waiting caller open a new one.
+    fn forget_one(&self) {
+        self.lock_state().open -= 1;
+        self.returned.notify_one();
+    }
+
+    fn give_back(&self, conn: Connection) {
+        self.lock_state().idle.push(conn);
+        self.returned.notify_one();
+    }
+}
+
+/// A connection that goes back to its pool when dropped; one made with `From<Connection>` belongs
+/// to no pool and is simply closed.
+pub struct PooledConnection {
+    // Some until dropped
+    conn: Option<Connection>,
+    pool: Option<Arc<PoolInner>>,
+}
+
+impl From<Connection> for PooledConnection {
+    fn from(conn: Connection) -> Self {
+        PooledConnection { conn: Some(conn), pool: None }
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection is present until dropped")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection is present until dropped")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let (Some(conn), Some(pool)) = (self.conn.take(), self.pool.take()) else { return };
+        if conn.is_autocommit() {
+            pool.give_back(conn);
+        } else {
+            // Left inside a transaction (a panic mid-batch): not safe to hand out again
+            log::warn!("Closing a pooled connection left inside a transaction.");
+            drop(conn);
+            pool.forget_one();
+        }
+    }
+}
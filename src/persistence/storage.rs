@@ -0,0 +1,430 @@
+// src/persistence/storage.rs
+
+use super::PooledConnection;
+use crate::clock::{self, Clock, ClockJump};
+use crate::errors::{AppError, AppResult};
+use crate::timezone::ReportTimeZone;
+use crate::types::{AppMetadata, AggregatedResult, AudioSample, AggregationReport, AggregationLevel, CalendarEvent, DetailedUsageRecord, Habit, IntervalRecord, NewInterval, PurgeScope, RunEventKind, StatsFilter, SwitchStats, SyncState, TimePeriod, TitleRename, TrackerHistory, WindowInfo};
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Backend-neutral persistence API used by the tracker and the commands.
+/// `SqliteStorage` is the built-in implementation; `PostgresStorage` (feature `postgres`) is the other.
+pub trait Storage: Send {
+    /// Creates missing tables/columns/indexes (idempotent).
+    fn initialize(&mut self) -> AppResult<()>;
+
+    // Batches group interval writes into one transaction (see `BufferedIntervalWriter::flush`)
+    fn begin_batch(&mut self) -> AppResult<()>;
+    fn commit_batch(&mut self) -> AppResult<()>;
+    fn rollback_batch(&mut self) -> AppResult<()>;
+
+    /// Inserts an open interval and returns its row id.
+    fn insert_interval(&mut self, interval: &NewInterval) -> AppResult<i64>;
+    fn finalize_interval(&mut self, row_id: i64, end_time: i64) -> AppResult<()>;
+    fn heartbeat_interval(&mut self, row_id: i64, heartbeat_time: i64) -> AppResult<()>;
+    /// Closes intervals a previous session of `user_name` left open; returns how many were closed.
+    fn finalize_dangling_intervals(&mut self, now: i64, threshold_secs: i64, user_name: &str) -> AppResult<usize>;
+    /// Merges flapping intervals closer than `merge_gap_secs` (None = off), then rolls raw intervals into summaries.
+    fn aggregate_and_cleanup(&mut self, merge_gap_secs: Option<i64>) -> AppResult<AggregationReport>;
+    /// Runs the aggregation in a transaction that is rolled back (`aggregate --dry-run`).
+    fn preview_aggregation(&mut self, merge_gap_secs: Option<i64>) -> AppResult<AggregationReport>;
+
+    fn query_stats(&mut self, period: TimePeriod, level: AggregationLevel, filter: &StatsFilter) -> AppResult<AggregatedResult>;
+    /// Drill-down: one application's time per detailed title (`title_contains`, `top`, `min_duration_secs`,
+    /// `exclude_passive` and `user` apply; `app_glob` is ignored).
+    fn query_app_titles(&mut self, period: TimePeriod, app_name: &str, filter: &StatsFilter) -> AppResult<Vec<DetailedUsageRecord>>;
+    /// Drill-down: raw intervals of one application (optionally one title) within `period`, oldest first.
+    fn query_target_intervals(&mut self, period: TimePeriod, app_name: &str, title: Option<&str>) -> AppResult<Vec<IntervalRecord>>;
+
+    fn insert_window_snapshot(&mut self, snapshot_time: i64, windows: &[WindowInfo]) -> AppResult<usize>;
+    fn query_window_snapshot_at(&mut self, at: i64) -> AppResult<Option<(i64, Vec<WindowInfo>)>>;
+
+    /// Raw intervals overlapping [from, to), oldest first; the open interval ends at `now`.
+    fn query_intervals_between(&mut self, from: i64, to: i64, now: i64) -> AppResult<Vec<IntervalRecord>>;
+    /// Aggregated intervals kept in `app_intervals_archive` overlapping [from, to), oldest first;
+    /// `app_glob` matches the app name case-insensitively.
+    fn query_archived_intervals(&mut self, from: i64, to: i64, app_glob: Option<&str>) -> AppResult<Vec<IntervalRecord>>;
+    /// App switches and the longest single-app run among raw intervals (live and archived) in [from, to);
+    /// runs continue across gaps of at most `max_gap_secs`.
+    fn query_switch_stats(&mut self, from: i64, to: i64, now: i64, max_gap_secs: i64) -> AppResult<SwitchStats>;
+
+    /// A completed raw interval by id (None if missing, still open or already aggregated).
+    fn query_completed_interval(&mut self, id: i64) -> AppResult<Option<IntervalRecord>>;
+    /// Deletes a completed interval, logging it to `audit_log`; false if there was none.
+    fn delete_interval(&mut self, id: i64, now: i64) -> AppResult<bool>;
+    /// Splits a completed interval at `at`, logging it to `audit_log`; returns the new second half's id.
+    fn split_interval(&mut self, id: i64, at: i64, now: i64) -> AppResult<Option<i64>>;
+    /// Inserts a finished interval entered by hand (`add-entry`), logging it to `audit_log`; returns its id.
+    fn add_manual_interval(&mut self, interval: &NewInterval, end_time: i64, now: i64) -> AppResult<i64>;
+    /// Logs a wall-clock jump seen by the tracker to `audit_log` (action `clock_adjustment`).
+    fn record_clock_adjustment(&mut self, jump: &ClockJump) -> AppResult<()>;
+    /// Ends, continues and starts audio intervals so the open ones of the sample's device and user
+    /// are exactly `sample.apps` (`audio_tracking`).
+    fn record_audio(&mut self, sample: &AudioSample) -> AppResult<()>;
+    /// Adds `count` failed detector polls to the local day of `at` in `tracker_daily_stats`.
+    fn record_detection_errors(&mut self, at: i64, count: i64, last_error: &str) -> AppResult<()>;
+
+    /// Every (app, title) stored in raw or archived intervals and the per-title summaries, sorted.
+    fn query_distinct_titles(&mut self) -> AppResult<Vec<(String, String)>>;
+    /// Applies `renames` in one transaction, merging summary rows that end up with the same title and
+    /// logging each rename to `audit_log`; returns the number of rows changed.
+    fn rename_titles(&mut self, renames: &[TitleRename], now: i64) -> AppResult<usize>;
+    /// Deletes the rows `scope` covers from every table in one transaction (`purge`); returns the
+    /// rows removed per table. Nothing is written to `audit_log`.
+    fn purge(&mut self, scope: &PurgeScope) -> AppResult<Vec<(String, usize)>>;
+
+    /// Friendly names/icons of executables, sorted by app name.
+    fn query_app_metadata(&mut self) -> AppResult<Vec<AppMetadata>>;
+    /// Inserts or replaces an app's metadata; a user-set name is never replaced by one read from the executable.
+    fn upsert_app_metadata(&mut self, metadata: &AppMetadata, now: i64) -> AppResult<()>;
+    /// Removes an app's metadata; false if there was none.
+    fn delete_app_metadata(&mut self, app_name: &str) -> AppResult<bool>;
+
+    /// Habits defined with `habit add`, by name.
+    fn query_habits(&mut self) -> AppResult<Vec<Habit>>;
+    /// Adds a habit or redefines the one with the same name (its `created_at` is kept).
+    fn upsert_habit(&mut self, habit: &Habit) -> AppResult<()>;
+    fn delete_habit(&mut self, name: &str) -> AppResult<bool>;
+    /// Seconds per (local day start, app) in the day summaries since `from`; raw intervals are not included.
+    fn query_daily_app_totals(&mut self, from: i64) -> AppResult<Vec<(i64, String, i64)>>;
+
+    /// Replaces the stored calendar events starting in [from, to) with `events` (`calendar::refresh`).
+    fn replace_calendar_events(&mut self, from: i64, to: i64, events: &[CalendarEvent]) -> AppResult<()>;
+    /// Stored calendar events overlapping [from, to), in start order.
+    fn query_calendar_events(&mut self, from: i64, to: i64) -> AppResult<Vec<CalendarEvent>>;
+
+    /// A break reminder shown at `reminded_at` after `active_secs` of continuous activity.
+    fn record_break_reminder(&mut self, reminded_at: i64, active_secs: i64) -> AppResult<()>;
+    /// The reminder shown at `reminded_at` was followed by a break starting at `break_start`.
+    fn record_break_taken(&mut self, reminded_at: i64, break_start: i64) -> AppResult<()>;
+    /// (reminders shown, breaks taken after them) for reminders in [from, to).
+    fn query_break_compliance(&mut self, from: i64, to: i64) -> AppResult<(i64, i64)>;
+
+    /// Records that `track` started recording at `start_time`; returns the run's id.
+    fn start_tracker_run(&mut self, start_time: i64, device_id: &str, user_name: &str) -> AppResult<i64>;
+    /// The run is still alive at `at`; a run that never ends (crash, power loss) ends at its last heartbeat.
+    fn heartbeat_tracker_run(&mut self, run_id: i64, at: i64) -> AppResult<()>;
+    /// The run stopped cleanly at `at`.
+    fn end_tracker_run(&mut self, run_id: i64, at: i64) -> AppResult<()>;
+    /// A sleep, clock jump, pause or `tracking_hours` boundary within the run.
+    fn record_run_event(&mut self, run_id: i64, at: i64, kind: RunEventKind) -> AppResult<()>;
+    /// The runs of `user_name` overlapping [from, to), their events and when the first run ever started (`gaps`).
+    fn query_tracker_history(&mut self, from: i64, to: i64, user_name: &str) -> AppResult<TrackerHistory>;
+    /// Starts of the hours in [from, to) whose raw intervals were rolled into `hourly_summary`.
+    fn query_aggregated_hours(&mut self, from: i64, to: i64) -> AppResult<Vec<i64>>;
+
+    /// Completed intervals above the sync watermark, oldest first (see `sync::push`).
+    fn query_unsynced_intervals(&mut self, after_id: i64, limit: usize) -> AppResult<Vec<IntervalRecord>>;
+    fn count_unsynced_intervals(&mut self, after_id: i64) -> AppResult<usize>;
+    fn query_sync_state(&mut self) -> AppResult<SyncState>;
+    fn update_sync_state(&mut self, state: &SyncState) -> AppResult<()>;
+
+    /// Backend housekeeping on clean shutdown (e.g. WAL checkpoint).
+    fn checkpoint(&mut self) -> AppResult<()> {
+        Ok(())
+    }
+
+    /// Writes a consistent copy of the whole database to the new file `path` (`db backup`, scheduled backups).
+    fn backup_to(&mut self, _path: &Path) -> AppResult<()> {
+        Err(AppError::Config("Backups only cover a local SQLite database; back up a server database with its own tools (e.g. pg_dump)".to_string()))
+    }
+}
+
+/// SQLite implementation of `Storage`, delegating to the functions in `persistence`.
+pub struct SqliteStorage {
+    // Owned outright, or borrowed from a `StoragePool`
+    conn: PooledConnection,
+    timezone: ReportTimeZone,
+    // Free-page percentage above which aggregation is followed by `maintain_db` (None = never)
+    vacuum_threshold_percent: Option<u64>,
+    // Keep aggregated raw intervals in `app_intervals_archive`
+    archive_raw_intervals: bool,
+    // Days of `hourly_summary` kept by aggregation (time-of-day profile)
+    hourly_summary_days: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> AppResult<Self> {
+        Self::open_with_key(path, None)
+    }
+
+    /// Opens an SQLCipher-encrypted database (plain SQLite when `passphrase` is None).
+    pub fn open_with_key(path: &Path, passphrase: Option<&str>) -> AppResult<Self> {
+        Ok(Self::from_connection(super::open_connection_with_key(path, passphrase)?))
+    }
+
+    pub fn from_connection(conn: Connection) -> Self {
+        Self::from_pooled(conn.into())
+    }
+
+    /// Uses a connection from a `StoragePool`; it goes back to the pool when the storage is dropped.
+    pub fn from_pooled(conn: PooledConnection) -> Self {
+        SqliteStorage { conn, timezone: ReportTimeZone::default(), vacuum_threshold_percent: None, archive_raw_intervals: false, hourly_summary_days: super::DEFAULT_HOURLY_SUMMARY_DAYS, clock: clock::system_clock() }
+    }
+
+    /// Zone of the day/hour boundaries used by stats and aggregation (default: system local).
+    pub fn with_timezone(mut self, timezone: ReportTimeZone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Vacuum after aggregation once more than `percent` of the file is free pages.
+    pub fn with_vacuum_threshold(mut self, percent: Option<u64>) -> Self {
+        self.vacuum_threshold_percent = percent;
+        self
+    }
+
+    /// Move aggregated raw intervals to `app_intervals_archive` instead of deleting them.
+    pub fn with_archive(mut self, enabled: bool) -> Self {
+        self.archive_raw_intervals = enabled;
+        self
+    }
+
+    /// Days of `hourly_summary` aggregation keeps (at least 1: yesterday and today).
+    pub fn with_hourly_summary_days(mut self, days: u64) -> Self {
+        self.hourly_summary_days = days;
+        self
+    }
+
+    /// Source of "now" for stats periods and aggregation cutoffs (default: the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn initialize(&mut self) -> AppResult<()> {
+        Ok(super::retry_when_busy("Schema update", || super::initialize_db(&mut self.conn))?)
+    }
+
+    fn begin_batch(&mut self) -> AppResult<()> {
+        Ok(self.conn.execute_batch("BEGIN")?)
+    }
+
+    fn commit_batch(&mut self) -> AppResult<()> {
+        Ok(self.conn.execute_batch("COMMIT")?)
+    }
+
+    fn rollback_batch(&mut self) -> AppResult<()> {
+        Ok(self.conn.execute_batch("ROLLBACK")?)
+    }
+
+    fn insert_interval(&mut self, interval: &NewInterval) -> AppResult<i64> {
+        Ok(super::insert_new_interval(&self.conn, interval)?)
+    }
+
+    fn finalize_interval(&mut self, row_id: i64, end_time: i64) -> AppResult<()> {
+        super::finalize_interval(&self.conn, row_id, end_time)?;
+        Ok(())
+    }
+
+    fn heartbeat_interval(&mut self, row_id: i64, heartbeat_time: i64) -> AppResult<()> {
+        super::heartbeat_interval(&self.conn, row_id, heartbeat_time)?;
+        Ok(())
+    }
+
+    fn finalize_dangling_intervals(&mut self, now: i64, threshold_secs: i64, user_name: &str) -> AppResult<usize> {
+        Ok(super::finalize_dangling_intervals(&self.conn, now, threshold_secs, user_name)?)
+    }
+
+    fn aggregate_and_cleanup(&mut self, merge_gap_secs: Option<i64>) -> AppResult<AggregationReport> {
+        let report = super::retry_when_busy("Aggregation", || {
+            super::aggregate_and_cleanup(&mut self.conn, merge_gap_secs, &self.timezone, self.clock.as_ref(), self.archive_raw_intervals, self.hourly_summary_days)
+        })?;
+        if let Some(threshold) = self.vacuum_threshold_percent {
+            let free = super::free_page_percent(&self.conn)?;
+            if free > threshold as f64 {
+                log::info!("{:.1}% of the database is free pages (threshold {}%); running maintenance.", free, threshold);
+                let problems = super::maintain_db(&self.conn)?;
+                if let Some(first) = problems.first() {
+                    log::warn!("Database integrity check failed ({} problem(s), first: {}); run `doctor`.", problems.len(), first);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    fn preview_aggregation(&mut self, merge_gap_secs: Option<i64>) -> AppResult<AggregationReport> {
+        Ok(super::retry_when_busy("Aggregation preview", || {
+            super::preview_aggregation(&mut self.conn, merge_gap_secs, &self.timezone, self.clock.as_ref(), self.archive_raw_intervals, self.hourly_summary_days)
+        })?)
+    }
+
+    fn query_stats(&mut self, period: TimePeriod, level: AggregationLevel, filter: &StatsFilter) -> AppResult<AggregatedResult> {
+        super::check_user_filter(period, level, filter)?;
+        Ok(super::query_stats(&self.conn, period, level, filter, &self.timezone, self.clock.as_ref())?)
+    }
+
+    fn query_app_titles(&mut self, period: TimePeriod, app_name: &str, filter: &StatsFilter) -> AppResult<Vec<DetailedUsageRecord>> {
+        super::check_user_filter(period, AggregationLevel::Detailed, filter)?;
+        Ok(super::query_app_titles(&self.conn, period, app_name, filter, &self.timezone, self.clock.as_ref())?)
+    }
+
+    fn query_target_intervals(&mut self, period: TimePeriod, app_name: &str, title: Option<&str>) -> AppResult<Vec<IntervalRecord>> {
+        Ok(super::query_target_intervals(&self.conn, period, app_name, title, &self.timezone, self.clock.as_ref())?)
+    }
+
+    fn insert_window_snapshot(&mut self, snapshot_time: i64, windows: &[WindowInfo]) -> AppResult<usize> {
+        Ok(super::insert_window_snapshot(&mut self.conn, snapshot_time, windows)?)
+    }
+
+    fn query_window_snapshot_at(&mut self, at: i64) -> AppResult<Option<(i64, Vec<WindowInfo>)>> {
+        Ok(super::query_window_snapshot_at(&self.conn, at)?)
+    }
+
+    fn query_intervals_between(&mut self, from: i64, to: i64, now: i64) -> AppResult<Vec<IntervalRecord>> {
+        Ok(super::query_intervals_between(&self.conn, from, to, now)?)
+    }
+
+    fn query_archived_intervals(&mut self, from: i64, to: i64, app_glob: Option<&str>) -> AppResult<Vec<IntervalRecord>> {
+        Ok(super::query_archived_intervals(&self.conn, from, to, app_glob)?)
+    }
+
+    fn query_switch_stats(&mut self, from: i64, to: i64, now: i64, max_gap_secs: i64) -> AppResult<SwitchStats> {
+        Ok(super::query_switch_stats(&self.conn, from, to, now, max_gap_secs)?)
+    }
+
+    fn query_completed_interval(&mut self, id: i64) -> AppResult<Option<IntervalRecord>> {
+        Ok(super::query_completed_interval(&self.conn, id)?)
+    }
+
+    fn delete_interval(&mut self, id: i64, now: i64) -> AppResult<bool> {
+        Ok(super::delete_interval(&mut self.conn, id, now)?)
+    }
+
+    fn split_interval(&mut self, id: i64, at: i64, now: i64) -> AppResult<Option<i64>> {
+        Ok(super::split_interval(&mut self.conn, id, at, now)?)
+    }
+
+    fn add_manual_interval(&mut self, interval: &NewInterval, end_time: i64, now: i64) -> AppResult<i64> {
+        Ok(super::add_manual_interval(&mut self.conn, interval, end_time, now)?)
+    }
+
+    fn record_clock_adjustment(&mut self, jump: &ClockJump) -> AppResult<()> {
+        Ok(super::record_clock_adjustment(&self.conn, jump)?)
+    }
+
+    fn record_audio(&mut self, sample: &AudioSample) -> AppResult<()> {
+        Ok(super::record_audio(&mut self.conn, sample)?)
+    }
+
+    fn record_detection_errors(&mut self, at: i64, count: i64, last_error: &str) -> AppResult<()> {
+        Ok(super::add_detection_errors(&self.conn, self.timezone.day_start(at), count, last_error)?)
+    }
+
+    fn query_distinct_titles(&mut self) -> AppResult<Vec<(String, String)>> {
+        Ok(super::query_distinct_titles(&self.conn)?)
+    }
+
+    fn rename_titles(&mut self, renames: &[TitleRename], now: i64) -> AppResult<usize> {
+        Ok(super::rename_titles(&mut self.conn, renames, now)?)
+    }
+
+    fn purge(&mut self, scope: &PurgeScope) -> AppResult<Vec<(String, usize)>> {
+        Ok(super::purge(&mut self.conn, scope, &self.timezone)?)
+    }
+
+    fn query_app_metadata(&mut self) -> AppResult<Vec<AppMetadata>> {
+        Ok(super::query_app_metadata(&self.conn)?)
+    }
+
+    fn upsert_app_metadata(&mut self, metadata: &AppMetadata, now: i64) -> AppResult<()> {
+        Ok(super::upsert_app_metadata(&self.conn, metadata, now)?)
+    }
+
+    fn delete_app_metadata(&mut self, app_name: &str) -> AppResult<bool> {
+        Ok(super::delete_app_metadata(&self.conn, app_name)?)
+    }
+
+    fn query_habits(&mut self) -> AppResult<Vec<Habit>> {
+        Ok(super::query_habits(&self.conn)?)
+    }
+
+    fn upsert_habit(&mut self, habit: &Habit) -> AppResult<()> {
+        Ok(super::upsert_habit(&self.conn, habit)?)
+    }
+
+    fn delete_habit(&mut self, name: &str) -> AppResult<bool> {
+        Ok(super::delete_habit(&self.conn, name)?)
+    }
+
+    fn query_daily_app_totals(&mut self, from: i64) -> AppResult<Vec<(i64, String, i64)>> {
+        Ok(super::query_daily_app_totals(&self.conn, from)?)
+    }
+
+    fn replace_calendar_events(&mut self, from: i64, to: i64, events: &[CalendarEvent]) -> AppResult<()> {
+        Ok(super::replace_calendar_events(&mut self.conn, from, to, events)?)
+    }
+
+    fn query_calendar_events(&mut self, from: i64, to: i64) -> AppResult<Vec<CalendarEvent>> {
+        Ok(super::query_calendar_events(&self.conn, from, to)?)
+    }
+
+    fn record_break_reminder(&mut self, reminded_at: i64, active_secs: i64) -> AppResult<()> {
+        Ok(super::record_break_reminder(&self.conn, reminded_at, active_secs)?)
+    }
+
+    fn record_break_taken(&mut self, reminded_at: i64, break_start: i64) -> AppResult<()> {
+        Ok(super::record_break_taken(&self.conn, reminded_at, break_start)?)
+    }
+
+    fn query_break_compliance(&mut self, from: i64, to: i64) -> AppResult<(i64, i64)> {
+        Ok(super::query_break_compliance(&self.conn, from, to)?)
+    }
+
+    fn start_tracker_run(&mut self, start_time: i64, device_id: &str, user_name: &str) -> AppResult<i64> {
+        Ok(super::start_tracker_run(&self.conn, start_time, device_id, user_name)?)
+    }
+
+    fn heartbeat_tracker_run(&mut self, run_id: i64, at: i64) -> AppResult<()> {
+        Ok(super::heartbeat_tracker_run(&self.conn, run_id, at)?)
+    }
+
+    fn end_tracker_run(&mut self, run_id: i64, at: i64) -> AppResult<()> {
+        Ok(super::end_tracker_run(&self.conn, run_id, at)?)
+    }
+
+    fn record_run_event(&mut self, run_id: i64, at: i64, kind: RunEventKind) -> AppResult<()> {
+        Ok(super::record_run_event(&self.conn, run_id, at, kind)?)
+    }
+
+    fn query_tracker_history(&mut self, from: i64, to: i64, user_name: &str) -> AppResult<TrackerHistory> {
+        Ok(super::query_tracker_history(&self.conn, from, to, user_name)?)
+    }
+
+    fn query_aggregated_hours(&mut self, from: i64, to: i64) -> AppResult<Vec<i64>> {
+        Ok(super::query_aggregated_hours(&self.conn, from, to)?)
+    }
+
+    fn query_unsynced_intervals(&mut self, after_id: i64, limit: usize) -> AppResult<Vec<IntervalRecord>> {
+        Ok(super::query_unsynced_intervals(&self.conn, after_id, limit)?)
+    }
+
+    fn count_unsynced_intervals(&mut self, after_id: i64) -> AppResult<usize> {
+        Ok(super::count_unsynced_intervals(&self.conn, after_id)?)
+    }
+
+    fn query_sync_state(&mut self) -> AppResult<SyncState> {
+        Ok(super::query_sync_state(&self.conn)?)
+    }
+
+    fn update_sync_state(&mut self, state: &SyncState) -> AppResult<()> {
+        Ok(super::update_sync_state(&self.conn, state)?)
+    }
+
+    fn checkpoint(&mut self) -> AppResult<()> {
+        Ok(super::checkpoint_wal(&self.conn)?)
+    }
+
+    fn backup_to(&mut self, path: &Path) -> AppResult<()> {
+        Ok(super::backup_database(&self.conn, path)?)
+    }
+}
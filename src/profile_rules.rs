@@ -0,0 +1,108 @@
+// src/profile_rules.rs
+// `profile_rules` in config.json: which profile an interval is recorded in, chosen by local
+// schedule and/or app while tracking (e.g. weekdays 9-17 -> "work", steam.exe -> "personal").
+
+use crate::config::{validate_profile_name, DEFAULT_PROFILE};
+use crate::timezone::ReportTimeZone;
+use crate::utils::{glob_match, parse_time_of_day, parse_weekdays};
+use serde::Deserialize;
+
+/// One entry of `profile_rules`. Every condition given must match; a rule without any matches everything.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileRuleConfig {
+    /// Profile the interval goes to ("default" = the data directory's own profile)
+    pub profile: String,
+    /// "mon".."sun", "weekdays" or "weekend" (empty = every day)
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Local "HH:MM" start of the time window (with `to`; a window past midnight wraps, e.g. 22:00-06:00)
+    pub from: Option<String>,
+    /// Local "HH:MM" end of the time window (exclusive)
+    pub to: Option<String>,
+    /// Glob on the app name (case-insensitive, e.g. "steam*")
+    pub app: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct ProfileRule {
+    // None = the profile the tracker was started with
+    profile: Option<String>,
+    // Indexed by weekday, Monday first
+    days: [bool; 7],
+    // Minutes of the day [from, to)
+    window: Option<(u32, u32)>,
+    app: Option<String>,
+}
+
+impl ProfileRule {
+    fn matches(&self, app_name: &str, weekday: u8, minute: u32) -> bool {
+        let in_window = match self.window {
+            None => true,
+            Some((from, to)) if from < to => minute >= from && minute < to,
+            Some((from, to)) => minute >= from || minute < to,
+        };
+        self.days[usize::from(weekday)] && in_window && self.app.as_deref().is_none_or(|pattern| glob_match(pattern, app_name))
+    }
+}
+
+/// The parsed rules, checked in order; the first match wins.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileRules {
+    rules: Vec<ProfileRule>,
+}
+
+impl ProfileRules {
+    /// Validates `rules`; `own_profile` is the profile being loaded (rules naming it record there as usual).
+    pub fn parse(rules: &[ProfileRuleConfig], own_profile: Option<&str>) -> Result<Self, String> {
+        let own_profile = own_profile.unwrap_or(DEFAULT_PROFILE);
+        let mut parsed = Vec::with_capacity(rules.len());
+        for (index, rule) in rules.iter().enumerate() {
+            let context = |message: String| format!("profile_rules[{}]: {}", index, message);
+            if rule.profile != DEFAULT_PROFILE {
+                validate_profile_name(&rule.profile).map_err(|e| context(e.to_string()))?;
+            }
+            let window = match (&rule.from, &rule.to) {
+                (None, None) => None,
+                (Some(from), Some(to)) => {
+                    let window = (parse_time_of_day(from).map_err(context)?, parse_time_of_day(to).map_err(context)?);
+                    if window.0 == window.1 {
+                        return Err(context("\"from\" and \"to\" are the same time".to_string()));
+                    }
+                    Some(window)
+                }
+                _ => return Err(context("set both \"from\" and \"to\", or neither".to_string())),
+            };
+            parsed.push(ProfileRule {
+                profile: (rule.profile != own_profile).then(|| rule.profile.clone()),
+                days: parse_weekdays(&rule.days).map_err(context)?,
+                window,
+                app: rule.app.clone().filter(|pattern| !pattern.trim().is_empty()),
+            });
+        }
+        Ok(ProfileRules { rules: parsed })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Other profiles the rules can record into, without duplicates.
+    pub fn target_profiles(&self) -> Vec<&str> {
+        let mut profiles: Vec<&str> = Vec::new();
+        for profile in self.rules.iter().filter_map(|rule| rule.profile.as_deref()) {
+            if !profiles.contains(&profile) {
+                profiles.push(profile);
+            }
+        }
+        profiles
+    }
+
+    /// Profile of an interval of `app_name` starting at `timestamp` (None = the tracker's own profile).
+    pub fn resolve(&self, app_name: &str, timestamp: i64, timezone: &ReportTimeZone) -> Option<&str> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let (weekday, minute) = timezone.weekday_and_minute(timestamp);
+        self.rules.iter().find(|rule| rule.matches(app_name, weekday, minute)).and_then(|rule| rule.profile.as_deref())
+    }
+}
@@ -0,0 +1,143 @@
+// src/pseudonym.rs
+// `pseudonymize` in config.json: for shared machines. App names, window titles, repositories and
+// issue keys are replaced by salted SHA-256 hashes on the detection thread, so the plain strings never
+// reach the database, snapshots, webhooks or MQTT. Equal strings give equal hashes, so stats still add
+// up and compare per app/title. The salt is kept in the keyring (`ApiKeyType::Pseudonym`);
+// `stats --reveal` shows the plain names listed in `pseudonymize.reveal` again.
+
+use crate::commands::set_key::load_api_key;
+use crate::config::AppConfig;
+use crate::detection::ActivityInfo;
+use crate::errors::{AppError, AppResult};
+use crate::types::{AggregatedResult, ApiKeyType, WindowInfo};
+use keyring::Entry;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Start of every stored hash, e.g. "h:3f2a9c0d51e7b846"
+pub const HASH_PREFIX: &str = "h:";
+/// Hex digits of the digest kept (64 bits)
+const HASH_HEX_DIGITS: usize = 16;
+
+#[derive(Clone)]
+pub struct Pseudonymizer {
+    salt: Vec<u8>,
+}
+
+// The salt stays out of debug output and logs
+impl fmt::Debug for Pseudonymizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pseudonymizer").finish_non_exhaustive()
+    }
+}
+
+impl Pseudonymizer {
+    /// Loads the salt saved in the keyring.
+    pub fn load(app_config: &AppConfig) -> AppResult<Self> {
+        let salt = load_api_key(app_config, ApiKeyType::Pseudonym)?;
+        Ok(Pseudonymizer { salt: salt.trim().as_bytes().to_vec() })
+    }
+
+    /// Loads the salt, generating and saving a random one the first time (`track`).
+    pub fn load_or_create(app_config: &AppConfig) -> AppResult<Self> {
+        let entry = Entry::new(&app_config.keyring_service_name, ApiKeyType::Pseudonym.keyring_username())?;
+        match entry.get_password() {
+            Ok(salt) => Ok(Pseudonymizer { salt: salt.trim().as_bytes().to_vec() }),
+            Err(keyring::Error::NoEntry) => {
+                let salt = crate::crypto::generate_key();
+                entry.set_password(&salt)?;
+                log::info!("Generated the pseudonymization salt and saved it in the keyring.");
+                Ok(Pseudonymizer { salt: salt.into_bytes() })
+            }
+            Err(e) => Err(AppError::Keyring(e)),
+        }
+    }
+
+    /// The stored form of `value`. Empty strings and bracketed pseudo-apps ("[Locked]", "[Break]", ...)
+    /// are kept: they hold nothing personal and other features look for them.
+    pub fn hash(&self, value: &str) -> String {
+        if value.is_empty() || (value.starts_with('[') && value.ends_with(']')) || value.starts_with(HASH_PREFIX) {
+            return value.to_string();
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(&self.salt);
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+        let digest = hasher.finalize();
+        let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}{}", HASH_PREFIX, &hex[..HASH_HEX_DIGITS])
+    }
+
+    /// Hashes what identifies the activity; the executable path and command line are dropped.
+    pub fn apply(&self, activity: &mut ActivityInfo) {
+        activity.app_name = self.hash(&activity.app_name);
+        activity.main_title = self.hash(&activity.main_title);
+        activity.detailed_title = self.hash(&activity.detailed_title);
+        activity.repo = activity.repo.as_deref().map(|repo| self.hash(repo));
+        activity.ticket = activity.ticket.as_deref().map(|ticket| self.hash(ticket));
+        activity.remote_host = activity.remote_host.as_deref().map(|host| self.hash(host));
+        activity.document = activity.document.as_deref().map(|document| self.hash(document));
+        activity.executable_path = None;
+        activity.command_line = None;
+    }
+
+    pub fn apply_to_windows(&self, windows: &mut [WindowInfo]) {
+        for window in windows {
+            window.app_name = self.hash(&window.app_name);
+            window.window_title = self.hash(&window.window_title);
+        }
+    }
+
+    /// Hash -> plain string for the names listed in `pseudonymize.reveal`.
+    pub fn reveal_map(&self, names: &[String]) -> RevealMap {
+        RevealMap(names.iter().map(|name| (self.hash(name), name.clone())).collect())
+    }
+}
+
+/// Turns the hashes of known names back into the names (`stats --reveal`); other hashes stay as they are.
+#[derive(Debug, Clone, Default)]
+pub struct RevealMap(HashMap<String, String>);
+
+impl RevealMap {
+    pub fn apply(&self, result: &mut AggregatedResult) {
+        let reveal = |value: &mut String| {
+            if let Some(name) = self.0.get(value.as_str()) {
+                value.clone_from(name);
+            }
+        };
+        match result {
+            AggregatedResult::ByApp(rows)
+            | AggregatedResult::ByRepo(rows)
+            | AggregatedResult::ByTicket(rows)
+            | AggregatedResult::ByDocument(rows) => rows.iter_mut().for_each(|(name, _)| reveal(name)),
+            // "rdp-client: <hash of the machine>"
+            AggregatedResult::ByRemote(rows) => {
+                for (label, _) in rows.iter_mut() {
+                    if let Some((session_type, host)) = label.split_once(": ")
+                        && let Some(name) = self.0.get(host)
+                    {
+                        *label = format!("{}: {}", session_type, name);
+                    }
+                }
+            }
+            AggregatedResult::Detailed(records) => {
+                for record in records {
+                    reveal(&mut record.app_name);
+                    reveal(&mut record.detailed_title);
+                }
+            }
+            AggregatedResult::Focus(metrics) => {
+                if let Some((app_name, _, _)) = metrics.longest_session.as_mut() {
+                    reveal(app_name);
+                }
+            }
+            AggregatedResult::ByDesktop(_)
+            | AggregatedResult::ByDevice(_)
+            | AggregatedResult::ByPath(_)
+            | AggregatedResult::ByMeeting(_)
+            | AggregatedResult::ByUser(_)
+            | AggregatedResult::Profile(_) => {}
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// src/recap.rs
+// Daily recap: at `daily_recap_time` the running tracker shows a desktop notification with today's
+// total, top three apps and habit streaks. Clicking it opens an HTML report of the day (`export::render_html`).
+
+use crate::app_metadata::AppAliases;
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::export;
+use crate::habits;
+use crate::persistence::Storage;
+use crate::timezone::DailySchedule;
+use crate::types::{AggregatedResult, AggregationLevel, StatsFilter, TimePeriod};
+use crate::utils::format_duration;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+/// Apps listed in the notification.
+const TOP_APPS: usize = 3;
+
+/// Scheduled from the persistence thread, like auto-sync.
+#[derive(Debug)]
+pub struct DailyRecap {
+    config: AppConfig,
+    schedule: DailySchedule,
+}
+
+/// What the notification says and the report a click opens.
+#[derive(Debug, Clone)]
+pub struct RecapNotification {
+    pub title: String,
+    pub body: String,
+    pub report: Option<PathBuf>,
+}
+
+impl DailyRecap {
+    /// None when `daily_recap_time` is not set. A tracker started after that time waits for the next day.
+    pub fn from_config(config: &AppConfig, now: i64) -> Option<Self> {
+        let minute = config.daily_recap_minute?;
+        Some(DailyRecap { config: config.clone(), schedule: DailySchedule::new(&config.timezone, minute, now) })
+    }
+
+    /// True once the configured time of a day without a recap yet has passed.
+    pub fn is_due(&self, now: i64) -> bool {
+        self.schedule.is_due(now)
+    }
+
+    /// Shows today's recap (once per day); the notification runs on its own thread.
+    pub fn run(&mut self, storage: &mut dyn Storage, now: i64) {
+        self.schedule.mark_done(now);
+        match prepare(&self.config, storage, now) {
+            Ok(notification) => {
+                let spawned = thread::Builder::new().name("recap".to_string()).spawn(move || {
+                    if let Err(e) = show_notification(&notification) {
+                        log::warn!("Daily recap notification failed: {}", e);
+                    }
+                });
+                if let Err(e) = spawned {
+                    log::warn!("Could not start the daily recap notification: {}", e);
+                }
+            }
+            Err(e) => eprintln!("[Persist] Daily recap failed: {}", e),
+        }
+    }
+}
+
+/// Today's figures up to `now`, and the HTML report written next to config.json (`reports/recap-DATE.html`).
+/// No report is written when the database or exports are encrypted, since it would hold the same data in plain text.
+pub fn prepare(config: &AppConfig, storage: &mut dyn Storage, now: i64) -> AppResult<RecapNotification> {
+    let totals = match storage.query_stats(TimePeriod::Today, AggregationLevel::ByApplication, &StatsFilter::default())? {
+        AggregatedResult::ByApp(rows) => AppAliases::load(storage)?.apply_to_totals(rows),
+        _ => Vec::new(),
+    };
+    let format = config.duration_format;
+    let total: i64 = totals.iter().map(|(_, secs)| secs).sum();
+    let title = format!("Today: {} tracked", format_duration(total, format));
+    let mut body = if totals.is_empty() {
+        "Nothing tracked today.".to_string()
+    } else {
+        totals
+            .iter()
+            .take(TOP_APPS)
+            .enumerate()
+            .map(|(index, (app, secs))| format!("{}. {} — {}", index + 1, app, format_duration(*secs, format)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    for status in habits::load_status(storage, &config.timezone, now)? {
+        body.push('\n');
+        body.push_str(&habits::describe(&status, format));
+    }
+
+    let report = if config.database_encryption || config.end_to_end_encryption {
+        None
+    } else {
+        let day_start = config.timezone.day_start(now);
+        let date = config.timezone.date_string(now);
+        let (blocks, _) = export::load_blocks(storage, day_start, now, now)?;
+        let path = report_path(config, &date);
+        if let Some(reports_dir) = path.parent() {
+            std::fs::create_dir_all(reports_dir).map_err(|e| AppError::Io { path: reports_dir.to_path_buf(), source: e })?;
+        }
+        let focus = export::load_focus(storage, day_start, now, now, &config.timezone)?;
+        let html = export::render_html(&blocks, &focus, &date, &config.app_name, format);
+        std::fs::write(&path, html).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+        Some(path)
+    };
+    Ok(RecapNotification { title, body, report })
+}
+
+/// Where `prepare` writes the report of `date` ("YYYY-MM-DD") for `config`.
+fn report_path(config: &AppConfig, date: &str) -> PathBuf {
+    config.config_file_path.parent().unwrap_or(Path::new(".")).join("reports").join(format!("recap-{}.html", date))
+}
+
+/// Windows: a toast shown through PowerShell; clicking it opens the report with the default browser.
+#[cfg(target_os = "windows")]
+pub fn show_notification(notification: &RecapNotification) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    // Toasts need a registered app id; PowerShell's is always present
+    const APP_ID: &str = r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+    // Protocol activation hands `launch` to its default handler without an activator of our own
+    let launch = notification
+        .report
+        .as_deref()
+        .map(|path| format!(" activationType=\"protocol\" launch=\"{}\"", escape_xml(&file_uri(path))))
+        .unwrap_or_default();
+    let xml = format!(
+        "<toast{}><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>",
+        launch,
+        escape_xml(&notification.title),
+        escape_xml(&notification.body)
+    );
+    let script = format!(
+        concat!(
+            "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null; ",
+            "[Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] | Out-Null; ",
+            "$xml = New-Object Windows.Data.Xml.Dom.XmlDocument; $xml.LoadXml('{}'); ",
+            "[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show([Windows.UI.Notifications.ToastNotification]::new($xml))"
+        ),
+        xml.replace('\'', "''"),
+        APP_ID
+    );
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| format!("Cannot run powershell: {}", e))?;
+    if status.success() { Ok(()) } else { Err(format!("powershell exited with {}", status)) }
+}
+
+/// Linux: `notify-send`, with an "Open report" action that runs `xdg-open` (blocks until the
+/// notification is clicked or dismissed).
+#[cfg(target_os = "linux")]
+pub fn show_notification(notification: &RecapNotification) -> Result<(), String> {
+    let run = |with_action: bool| {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=mouse_tracking");
+        if with_action {
+            command.arg("--action=open=Open report");
+        }
+        command.arg(&notification.title).arg(&notification.body).output().map_err(|e| format!("Cannot run notify-send: {}", e))
+    };
+    let with_action = notification.report.is_some();
+    let mut output = run(with_action)?;
+    if with_action && !output.status.success() {
+        // libnotify before 0.7.12 has no --action
+        output = run(false)?;
+    }
+    if !output.status.success() {
+        return Err(format!("notify-send exited with {}", output.status));
+    }
+    if let Some(report) = &notification.report
+        && String::from_utf8_lossy(&output.stdout).trim() == "open"
+    {
+        Command::new("xdg-open").arg(report).spawn().map_err(|e| format!("Cannot run xdg-open: {}", e))?;
+    }
+    Ok(())
+}
+
+/// macOS: a Notification Center banner (without a click action; the report is in `reports`).
+#[cfg(target_os = "macos")]
+pub fn show_notification(notification: &RecapNotification) -> Result<(), String> {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let script = format!("display notification {} with title {}", quote(&notification.body), quote(&notification.title));
+    let status = Command::new("osascript").args(["-e", &script]).status().map_err(|e| format!("Cannot run osascript: {}", e))?;
+    if status.success() { Ok(()) } else { Err(format!("osascript exited with {}", status)) }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+pub fn show_notification(_notification: &RecapNotification) -> Result<(), String> {
+    Err("Desktop notifications are not supported on this platform".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(target_os = "windows")]
+fn file_uri(path: &Path) -> String {
+    format!("file:///{}", path.display().to_string().replace('\\', "/").replace(' ', "%20"))
+}
@@ -0,0 +1,77 @@
+// src/saved_reports.rs
+// `reports` in config.json: named stats queries (period, level, filters, output format) run with
+// `report run <name>`, so recurring analyses don't need long flag strings.
+
+use crate::types::{AggregationLevel, IntervalStream, QueryFormat, StatsFilter, TimePeriod};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// One entry of `reports`, keyed by its name. Values use the names of the `stats` flags.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SavedReportConfig {
+    /// Shown by `report list`
+    pub description: Option<String>,
+    /// A `--period` value (default: today, the last completed hour and the current hour, like `stats`)
+    pub period: Option<String>,
+    /// "app" (default), "detailed", "desktop", "device", "repo" or "path"
+    pub level: Option<String>,
+    pub top: Option<usize>,
+    /// Glob on the app name (case-insensitive)
+    pub app: Option<String>,
+    pub title_contains: Option<String>,
+    pub min_duration_secs: Option<i64>,
+    pub exclude_passive: bool,
+    /// "table" (default, the `stats` layout), "csv" or "json"
+    pub format: Option<String>,
+}
+
+/// A validated saved report.
+#[derive(Debug, Clone)]
+pub struct SavedReport {
+    pub description: Option<String>,
+    /// None = the default `stats` periods
+    pub period: Option<TimePeriod>,
+    pub level: AggregationLevel,
+    pub filter: StatsFilter,
+    pub format: QueryFormat,
+}
+
+/// Validates every entry of `reports`; errors name the report and the bad value.
+pub fn parse(reports: &BTreeMap<String, SavedReportConfig>) -> Result<BTreeMap<String, SavedReport>, String> {
+    reports
+        .iter()
+        .map(|(name, report)| {
+            let context = |message: String| format!("reports.{}: {}", name, message);
+            if name.trim().is_empty() {
+                return Err("reports: a report needs a name".to_string());
+            }
+            let saved = SavedReport {
+                description: report.description.clone(),
+                period: report.period.as_deref().map(|period| parse_value(period, "period")).transpose().map_err(context)?,
+                level: report.level.as_deref().map_or(Ok(AggregationLevel::ByApplication), |level| parse_value(level, "level")).map_err(context)?,
+                filter: StatsFilter {
+                    top: report.top,
+                    app_glob: report.app.clone().filter(|glob| !glob.trim().is_empty()),
+                    title_contains: report.title_contains.clone().filter(|text| !text.is_empty()),
+                    min_duration_secs: report.min_duration_secs,
+                    exclude_passive: report.exclude_passive,
+                    user: None,
+                    stream: IntervalStream::Foreground,
+                },
+                format: report.format.as_deref().map_or(Ok(QueryFormat::Table), |format| parse_value(format, "format")).map_err(context)?,
+            };
+            Ok((name.clone(), saved))
+        })
+        .collect()
+}
+
+/// Parses a CLI value name (e.g. "last-week"), listing the accepted ones on error.
+fn parse_value<T: ValueEnum>(value: &str, key: &str) -> Result<T, String> {
+    T::from_str(value.trim(), true).map_err(|_| {
+        let accepted: Vec<String> =
+            T::value_variants().iter().filter_map(|variant| variant.to_possible_value()).map(|value| value.get_name().to_string()).collect();
+        format!("unknown {} {:?} (expected one of: {})", key, value, accepted.join(", "))
+    })
+}
@@ -0,0 +1,274 @@
+// src/schedule.rs
+// `schedule` in config.json: maintenance jobs the running tracker starts at local times or intervals
+// (e.g. aggregate nightly, back up weekly, write a saved report Sunday 18:00, sync hourly).
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::network::NetworkHandle;
+use crate::persistence::{self, Storage};
+use crate::saved_reports::SavedReport;
+use crate::timezone::ReportTimeZone;
+use crate::types::QueryFormat;
+use crate::utils::{parse_time_of_day, parse_weekdays};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// One entry of `schedule`, keyed by its name. Exactly one of `at` and `every_mins` is set.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScheduleEntryConfig {
+    /// "aggregate", "backup", "sync" or "report"
+    pub job: String,
+    /// Local "HH:MM" the job runs at
+    pub at: Option<String>,
+    /// With `at`: "mon".."sun", "weekdays" or "weekend" (empty = every day)
+    pub days: Vec<String>,
+    /// Runs every N minutes instead, counted from tracker start
+    pub every_mins: Option<u64>,
+    /// Saved report written by a `report` job
+    pub report: Option<String>,
+    /// Backups kept by a `backup` job (older ones are deleted; default: all)
+    pub keep: Option<usize>,
+}
+
+/// What a scheduled job does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobKind {
+    /// `aggregate`
+    Aggregate,
+    /// `db backup --keep`
+    Backup { keep: Option<usize> },
+    /// `sync push`
+    Sync,
+    /// `report run <name>`, written to `reports/<name>-DATE.<txt|csv|json>` next to config.json
+    Report { name: String },
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobKind::Aggregate => write!(f, "aggregate"),
+            JobKind::Backup { keep: Some(keep) } => write!(f, "backup (keep {})", keep),
+            JobKind::Backup { keep: None } => write!(f, "backup"),
+            JobKind::Sync => write!(f, "sync"),
+            JobKind::Report { name } => write!(f, "report {}", name),
+        }
+    }
+}
+
+/// When a job runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Timing {
+    /// Local minute of the day on the selected weekdays (indexed Monday first)
+    Daily { days: [bool; 7], minute: u32 },
+    /// Every N seconds
+    Every(i64),
+}
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+impl Timing {
+    /// First run strictly after `timestamp`.
+    pub fn next_after(&self, timestamp: i64, timezone: &ReportTimeZone) -> i64 {
+        match self {
+            Timing::Every(secs) => timestamp + secs,
+            Timing::Daily { days, minute } => {
+                let mut day = timezone.day_start(timestamp);
+                // A selected weekday comes up within 8 days (today's time may already be past)
+                for _ in 0..8 {
+                    let run = timezone.at_minute(day, *minute);
+                    if days[usize::from(timezone.weekday_and_minute(day).0)] && run > timestamp {
+                        return run;
+                    }
+                    day = timezone.next_day_start(day);
+                }
+                i64::MAX
+            }
+        }
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timing::Every(secs) => write!(f, "every {} min", secs / 60),
+            Timing::Daily { days, minute } => {
+                let time = format!("{:02}:{:02}", minute / 60, minute % 60);
+                match days {
+                    [true, true, true, true, true, true, true] => write!(f, "daily at {}", time),
+                    [true, true, true, true, true, false, false] => write!(f, "weekdays at {}", time),
+                    [false, false, false, false, false, true, true] => write!(f, "weekends at {}", time),
+                    _ => {
+                        let names: Vec<&str> = DAY_NAMES.iter().zip(days).filter(|(_, on)| **on).map(|(name, _)| *name).collect();
+                        write!(f, "{} at {}", names.join(", "), time)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A validated `schedule` entry.
+#[derive(Debug, Clone)]
+pub struct ScheduledJob {
+    pub name: String,
+    pub kind: JobKind,
+    pub timing: Timing,
+}
+
+/// Validates every entry of `schedule`; report jobs must name a saved report and sync jobs need `sync_url`.
+pub fn parse(
+    entries: &BTreeMap<String, ScheduleEntryConfig>,
+    reports: &BTreeMap<String, SavedReport>,
+    sync_configured: bool,
+) -> Result<Vec<ScheduledJob>, String> {
+    entries
+        .iter()
+        .map(|(name, entry)| {
+            let context = |message: String| format!("schedule.{}: {}", name, message);
+            if name.trim().is_empty() {
+                return Err("schedule: a job needs a name".to_string());
+            }
+            let at = entry.at.as_deref().filter(|time| !time.trim().is_empty());
+            let timing = match (at, entry.every_mins) {
+                (Some(_), Some(_)) => return Err(context("set either \"at\" or \"every_mins\", not both".to_string())),
+                (None, None) => return Err(context("set \"at\" (\"HH:MM\") or \"every_mins\"".to_string())),
+                (None, Some(_)) if !entry.days.is_empty() => return Err(context("\"days\" only applies with \"at\"".to_string())),
+                (None, Some(0)) => return Err(context("every_mins must be at least 1".to_string())),
+                (None, Some(mins)) => Timing::Every(mins.saturating_mul(60) as i64),
+                (Some(time), None) => Timing::Daily {
+                    days: parse_weekdays(&entry.days).map_err(context)?,
+                    minute: parse_time_of_day(time).map_err(context)?,
+                },
+            };
+            let kind = match entry.job.trim().to_lowercase().as_str() {
+                "aggregate" => JobKind::Aggregate,
+                "backup" => JobKind::Backup { keep: entry.keep },
+                "sync" if !sync_configured => return Err(context("sync jobs need \"sync_url\"".to_string())),
+                "sync" => JobKind::Sync,
+                "report" => {
+                    let report = entry.report.as_deref().map(str::trim).filter(|report| !report.is_empty());
+                    let report = report.ok_or_else(|| context("report jobs need \"report\": the name of a saved report".to_string()))?;
+                    if !reports.contains_key(report) {
+                        return Err(context(format!("no saved report {:?} under \"reports\"", report)));
+                    }
+                    JobKind::Report { name: report.to_string() }
+                }
+                other => return Err(context(format!("unknown job {:?} (expected aggregate, backup, sync or report)", other))),
+            };
+            Ok(ScheduledJob { name: name.clone(), kind, timing })
+        })
+        .collect()
+}
+
+/// Local "YYYY-MM-DD HH:MM" of a run.
+pub fn format_run_time(timezone: &ReportTimeZone, timestamp: i64) -> String {
+    let minute = timezone.weekday_and_minute(timestamp).1;
+    format!("{} {:02}:{:02}", timezone.date_string(timestamp), minute / 60, minute % 60)
+}
+
+/// Runs the `schedule` jobs from the persistence thread. Runs missed while the tracker was stopped are skipped.
+#[derive(Debug)]
+pub struct Scheduler {
+    config: AppConfig,
+    // Each job with its next run
+    jobs: Vec<(ScheduledJob, i64)>,
+}
+
+impl Scheduler {
+    /// None when no jobs are configured.
+    pub fn from_config(config: &AppConfig, now: i64) -> Option<Self> {
+        if config.schedule.is_empty() {
+            return None;
+        }
+        let jobs = config.schedule.iter().map(|job| (job.clone(), job.timing.next_after(now, &config.timezone))).collect();
+        Some(Scheduler { config: config.clone(), jobs })
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        self.jobs.iter().any(|(_, next)| *next <= now)
+    }
+
+    /// Runs every due job once, in config order, and schedules its next run after `now`. `sync` jobs
+    /// run on the network runtime with a connection of their own; the others run here.
+    pub fn run_due(&mut self, storage: &mut dyn Storage, network: &NetworkHandle, now: i64) {
+        for (job, next) in self.jobs.iter_mut().filter(|(_, next)| *next <= now) {
+            *next = job.timing.next_after(now, &self.config.timezone);
+            let next_run = format_run_time(&self.config.timezone, *next);
+            if let JobKind::Sync = job.kind {
+                let (config, job) = (self.config.clone(), job.clone());
+                network.run_job("sync", move || {
+                    let started = Instant::now();
+                    let result = persistence::open_storage(&config).and_then(|mut storage| run_job(&config, storage.as_mut(), &job.kind, now));
+                    log_outcome(&job, started, result, &next_run);
+                });
+                continue;
+            }
+            let started = Instant::now();
+            log_outcome(job, started, run_job(&self.config, storage, &job.kind, now), &next_run);
+        }
+    }
+}
+
+fn log_outcome(job: &ScheduledJob, started: Instant, result: AppResult<String>, next_run: &str) {
+    match result {
+        Ok(outcome) => log::info!(
+            "Scheduled job '{}' ({}) done in {:.1}s: {}; next run {}.",
+            job.name,
+            job.kind,
+            started.elapsed().as_secs_f64(),
+            outcome,
+            next_run
+        ),
+        Err(e) => eprintln!("[Schedule] Scheduled job '{}' ({}) failed: {}", job.name, job.kind, e),
+    }
+}
+
+/// Runs one job and describes what it did.
+pub fn run_job(config: &AppConfig, storage: &mut dyn Storage, kind: &JobKind, now: i64) -> AppResult<String> {
+    match kind {
+        JobKind::Aggregate => {
+            let report = storage.aggregate_and_cleanup(config.merge_gap_secs)?;
+            Ok(format!("{} raw intervals rolled up", report.deleted_raw + report.archived_raw))
+        }
+        JobKind::Backup { keep } => {
+            let path = crate::commands::db::backup(config, storage, *keep)?;
+            Ok(format!("wrote {:?}", path))
+        }
+        JobKind::Sync => Ok(format!("{} intervals pushed", crate::sync::push(config, storage)?)),
+        JobKind::Report { name } => {
+            let report = config.reports.get(name).ok_or_else(|| AppError::Config(format!("No saved report '{}'", name)))?;
+            let path = write_report(config, storage, name, report, now)?;
+            Ok(format!("wrote {:?}", path))
+        }
+    }
+}
+
+/// Writes a saved report next to config.json (`reports/<name>-DATE.<ext>`, replaced by later runs that day).
+/// Like the daily recap's HTML report, nothing is written in plain text when the database or exports are encrypted.
+fn write_report(config: &AppConfig, storage: &mut dyn Storage, name: &str, report: &SavedReport, now: i64) -> AppResult<PathBuf> {
+    if config.database_encryption || config.end_to_end_encryption {
+        return Err(AppError::Config("Report files are not written while database_encryption or end_to_end_encryption is on".to_string()));
+    }
+    let extension = match report.format {
+        QueryFormat::Table => "txt",
+        QueryFormat::Csv => "csv",
+        QueryFormat::Json => "json",
+    };
+    let dir = config.config_file_path.parent().unwrap_or(Path::new(".")).join("reports");
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::Io { path: dir.clone(), source: e })?;
+    let path = dir.join(format!("{}-{}.{}", name, config.timezone.date_string(now), extension));
+
+    let (headers, rows) = crate::commands::report::saved_report_rows(storage, report)?;
+    let file = File::create(&path).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    let mut out = BufWriter::new(file);
+    crate::commands::query::write_rows(&mut out, &headers, rows, report.format)
+        .and_then(|_| std::io::Write::flush(&mut out))
+        .map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    Ok(path)
+}
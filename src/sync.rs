@@ -0,0 +1,114 @@
+// src/sync.rs
+// Pushes completed intervals to a user-configured server (`sync_url` in config.json).
+
+use crate::config::AppConfig;
+use crate::crypto;
+use crate::errors::{AppError, AppResult};
+use crate::persistence::Storage;
+use crate::types::{ApiKeyType, IntervalRecord, SyncState};
+use chrono::Utc;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Intervals uploaded per HTTP request
+const BATCH_SIZE: usize = 500;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// JSON body of one upload. Intervals are immutable once completed, so the server can
+/// append them and ignore ids it has already stored (re-sending a batch is harmless).
+#[derive(Debug, Serialize)]
+struct SyncBatch<'a> {
+    client: &'a str,
+    client_version: &'a str,
+    intervals: &'a [IntervalRecord],
+}
+
+/// Body sent instead of `SyncBatch` with `end_to_end_encryption`: the server only sees the
+/// id range (for de-duplication) and the encrypted `SyncBatch` JSON.
+#[derive(Debug, Serialize)]
+struct EncryptedSyncBatch<'a> {
+    client: &'a str,
+    client_version: &'a str,
+    first_id: i64,
+    last_id: i64,
+    encrypted: crypto::EncryptedPayload,
+}
+
+/// Checks the stored token by sending an empty batch (`config test-key sync`); the server stores nothing.
+pub fn test_token(app_config: &AppConfig) -> AppResult<()> {
+    let url = app_config
+        .sync_url
+        .as_deref()
+        .ok_or_else(|| AppError::Config("No sync endpoint configured; set \"sync_url\" in config.json.".to_string()))?;
+    let token = crate::commands::set_key::load_api_key(app_config, ApiKeyType::Sync)?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Sync(e.to_string()))?;
+    let batch = SyncBatch { client: &app_config.app_name, client_version: &app_config.app_version, intervals: &[] };
+    let response = client
+        .post(url)
+        .bearer_auth(&token)
+        .json(&batch)
+        .send()
+        .map_err(|e| AppError::Sync(format!("Request to {} failed: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Sync(format!("Server rejected the token: HTTP {}", response.status())));
+    }
+    Ok(())
+}
+
+/// Uploads all completed intervals above the watermark in batches and returns how many were sent.
+/// The watermark is advanced after each accepted batch, so a failure resumes where it stopped.
+pub fn push(app_config: &AppConfig, storage: &mut dyn Storage) -> AppResult<usize> {
+    let url = app_config
+        .sync_url
+        .as_deref()
+        .ok_or_else(|| AppError::Config("No sync endpoint configured; set \"sync_url\" in config.json.".to_string()))?;
+    let token = crate::commands::set_key::load_api_key(app_config, ApiKeyType::Sync)?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Sync(e.to_string()))?;
+    let key = if app_config.end_to_end_encryption { Some(crypto::load_key(app_config)?) } else { None };
+
+    let mut state = storage.query_sync_state()?;
+    let mut pushed = 0;
+    loop {
+        let intervals = storage.query_unsynced_intervals(state.last_interval_id, BATCH_SIZE)?;
+        let Some(last) = intervals.last() else { break };
+        let last_id = last.id;
+        let batch = SyncBatch { client: &app_config.app_name, client_version: &app_config.app_version, intervals: &intervals };
+        let request = client.post(url).bearer_auth(&token);
+        let request = match &key {
+            Some(key) => {
+                let plaintext = serde_json::to_vec(&batch).map_err(|e| AppError::Sync(e.to_string()))?;
+                request.json(&EncryptedSyncBatch {
+                    client: batch.client,
+                    client_version: batch.client_version,
+                    first_id: intervals[0].id,
+                    last_id,
+                    encrypted: crypto::encrypt(key, &plaintext)?,
+                })
+            }
+            None => request.json(&batch),
+        };
+        let response = request
+            .send()
+            .map_err(|e| AppError::Sync(format!("Request to {} failed: {}", url, e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Sync(format!("Server rejected batch: HTTP {}", response.status())));
+        }
+        pushed += intervals.len();
+        state.last_interval_id = last_id;
+        storage.update_sync_state(&state)?;
+        log::debug!("Synced {} interval(s) up to id {}.", intervals.len(), last_id);
+        if intervals.len() < BATCH_SIZE {
+            break;
+        }
+    }
+    state = SyncState { last_sync_time: Some(Utc::now().timestamp()), last_sync_count: pushed as i64, ..state };
+    storage.update_sync_state(&state)?;
+    log::info!("Sync push finished: {} interval(s) uploaded.", pushed);
+    Ok(pushed)
+}
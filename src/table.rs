@@ -0,0 +1,203 @@
+// src/table.rs
+// Plain-text tables for terminal output, aligned by display width so CJK titles and emoji
+// (two terminal columns per character) do not push the following columns out of line.
+
+use anstyle::Style;
+use std::borrow::Cow;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+}
+
+/// A column header plus its layout rules.
+#[derive(Debug, Clone)]
+pub struct Column {
+    header: String,
+    align: Align,
+    min_width: usize,
+    /// Longer cells are cut and end with "…" (None = never truncated)
+    max_width: Option<usize>,
+}
+
+impl Column {
+    pub fn new(header: impl Into<String>) -> Self {
+        Column { header: header.into(), align: Align::Left, min_width: 0, max_width: None }
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn min_width(mut self, min_width: usize) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    pub fn max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width.map(|width| width.max(1));
+        self
+    }
+}
+
+/// A cell's text and the style it is printed in (applied after alignment, so escapes never count as width).
+#[derive(Debug, Clone, Default)]
+pub struct Cell {
+    text: String,
+    style: Style,
+}
+
+impl Cell {
+    pub fn styled(text: impl Into<String>, style: Style) -> Self {
+        Cell { text: text.into(), style }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell { text, style: Style::new() }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::from(text.to_string())
+    }
+}
+
+/// Rows of text cells rendered under a header and a rule line, indented by two spaces.
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+    separator: &'static str,
+    header_style: Style,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Table { columns, rows: Vec::new(), separator: " | ", header_style: Style::new() }
+    }
+
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// Text between columns (default " | "); the rule line puts '+' under '|'.
+    pub fn separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Adds a row; control characters (tabs, newlines in titles) are shown as spaces.
+    pub fn add_row<C: Into<Cell>>(&mut self, cells: impl IntoIterator<Item = C>) {
+        let cells = cells
+            .into_iter()
+            .map(|cell| {
+                let mut cell: Cell = cell.into();
+                if cell.text.chars().any(char::is_control) {
+                    cell.text = cell.text.replace(char::is_control, " ");
+                }
+                cell
+            })
+            .collect();
+        self.rows.push(cells);
+    }
+
+    pub fn render(&self) -> String {
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let content = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|cell| display_width(&cell.text))
+                    .chain(std::iter::once(display_width(&column.header)))
+                    .max()
+                    .unwrap_or(0);
+                // An explicit maximum beats the minimum
+                let width = content.max(column.min_width);
+                column.max_width.map_or(width, |max| width.min(max))
+            })
+            .collect();
+
+        let mut output = String::new();
+        let headers: Vec<Cell> = self.columns.iter().map(|column| Cell::styled(column.header.as_str(), self.header_style)).collect();
+        self.render_line(&mut output, &headers, &widths);
+        let rule_separator: String = self.separator.chars().map(|c| if c == '|' { '+' } else if c == ' ' { '-' } else { c }).collect();
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        output.push_str("  ");
+        output.push_str(&rule.join(&rule_separator));
+        output.push('\n');
+        for row in &self.rows {
+            self.render_line(&mut output, row, &widths);
+        }
+        output
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render());
+    }
+
+    fn render_line(&self, output: &mut String, cells: &[Cell], widths: &[usize]) {
+        output.push_str("  ");
+        for (index, (column, width)) in self.columns.iter().zip(widths).enumerate() {
+            if index > 0 {
+                output.push_str(self.separator);
+            }
+            let (text, style) = cells.get(index).map_or(("", Style::new()), |cell| (cell.text.as_str(), cell.style));
+            let text = truncate_to_width(text, *width);
+            let is_last = index + 1 == self.columns.len();
+            // No trailing spaces after a left-aligned last column
+            let text = if is_last && column.align == Align::Left { text.into_owned() } else { pad_to_width(&text, *width, column.align) };
+            output.push_str(&format!("{}{}{}", style.render(), text, style.render_reset()));
+        }
+        output.push('\n');
+    }
+}
+
+/// Number of terminal columns `text` occupies.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Cuts `text` to at most `max_width` terminal columns, ending with "…" when shortened.
+pub fn truncate_to_width(text: &str, max_width: usize) -> Cow<'_, str> {
+    if display_width(text) <= max_width {
+        return Cow::Borrowed(text);
+    }
+    let budget = max_width.saturating_sub(1); // Room for the ellipsis
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let width = c.width().unwrap_or(0);
+        if used + width > budget {
+            break;
+        }
+        truncated.push(c);
+        used += width;
+    }
+    if max_width > 0 {
+        truncated.push(ELLIPSIS);
+    }
+    Cow::Owned(truncated)
+}
+
+/// Pads `text` with spaces to `width` terminal columns.
+pub fn pad_to_width(text: &str, width: usize, align: Align) -> String {
+    let padding = " ".repeat(width.saturating_sub(display_width(text)));
+    match align {
+        Align::Left => format!("{}{}", text, padding),
+        Align::Right => format!("{}{}", padding, text),
+    }
+}
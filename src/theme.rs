@@ -0,0 +1,146 @@
+// src/theme.rs
+// Terminal colors for stats output, configured by the `theme` section of config.json.
+// Disabled by `--no-color`, a non-empty NO_COLOR variable, or when stdout is not a terminal.
+
+use crate::config::ThemeConfig;
+use crate::utils::glob_match;
+use anstyle::{AnsiColor, Style};
+use std::io::IsTerminal;
+
+/// Characters of a full duration bar
+pub const BAR_WIDTH: usize = 20;
+
+/// Whether colors should be used for stdout (see the module comment).
+pub fn color_enabled(no_color_flag: bool) -> bool {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color_flag && !no_color_env && std::io::stdout().is_terminal()
+}
+
+/// Resolved styles; every style is plain when colors are off.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    enabled: bool,
+    header: Style,
+    top: Style,
+    bar: Style,
+    /// Checked in order; an app gets the first category with a matching glob
+    categories: Vec<(Vec<String>, Style)>,
+}
+
+impl Palette {
+    pub fn new(theme: &ThemeConfig, enabled: bool) -> Self {
+        let style = |value: &Option<String>, default: Style| match value {
+            Some(value) => parse_style(value).unwrap_or_else(|| {
+                eprintln!("Warning: unknown theme style {:?}; using the default.", value);
+                default
+            }),
+            None => default,
+        };
+        let categories = theme
+            .categories
+            .iter()
+            .filter_map(|category| match parse_style(&category.color) {
+                Some(style) => Some((category.apps.clone(), style)),
+                None => {
+                    eprintln!("Warning: unknown theme color {:?} for {:?}; ignored.", category.color, category.apps);
+                    None
+                }
+            })
+            .collect();
+        Palette {
+            enabled,
+            header: style(&theme.header, Style::new().bold().fg_color(Some(AnsiColor::Cyan.into()))),
+            top: style(&theme.top, Style::new().bold().fg_color(Some(AnsiColor::Green.into()))),
+            bar: style(&theme.bar, Style::new().fg_color(Some(AnsiColor::Blue.into()))),
+            categories,
+        }
+    }
+
+    /// Colors and progress bars off.
+    pub fn plain() -> Self {
+        Palette { enabled: false, header: Style::new(), top: Style::new(), bar: Style::new(), categories: Vec::new() }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn header(&self) -> Style {
+        self.active(self.header)
+    }
+
+    pub fn bar(&self) -> Style {
+        self.active(self.bar)
+    }
+
+    /// Style of an app cell: the top entry is highlighted, others use their category color.
+    pub fn app(&self, app_name: &str, is_top: bool) -> Style {
+        if is_top {
+            return self.active(self.top);
+        }
+        let category = self
+            .categories
+            .iter()
+            .find(|(globs, _)| globs.iter().any(|glob| glob_match(glob, app_name)))
+            .map_or(Style::new(), |(_, style)| *style);
+        self.active(category)
+    }
+
+    /// `text` wrapped in `style` (unchanged when colors are off).
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        let style = self.active(style);
+        format!("{}{}{}", style.render(), text, style.render_reset())
+    }
+
+    fn active(&self, style: Style) -> Style {
+        if self.enabled { style } else { Style::new() }
+    }
+}
+
+/// A proportional bar of `secs` relative to `max_secs`, at most `BAR_WIDTH` characters.
+pub fn duration_bar(secs: i64, max_secs: i64) -> String {
+    if max_secs <= 0 || secs <= 0 {
+        return String::new();
+    }
+    let filled = ((secs as f64 / max_secs as f64) * BAR_WIDTH as f64).round().clamp(1.0, BAR_WIDTH as f64) as usize;
+    "█".repeat(filled)
+}
+
+/// Parses styles like "green", "bold bright-red" or "underline cyan".
+fn parse_style(value: &str) -> Option<Style> {
+    let mut style = Style::new();
+    for word in value.split_whitespace() {
+        style = match word.to_lowercase().as_str() {
+            "bold" => style.bold(),
+            "dim" | "dimmed" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            "plain" | "none" => style,
+            color => style.fg_color(Some(parse_color(color)?.into())),
+        };
+    }
+    Some(style)
+}
+
+fn parse_color(name: &str) -> Option<AnsiColor> {
+    let color = match name {
+        "black" => AnsiColor::Black,
+        "red" => AnsiColor::Red,
+        "green" => AnsiColor::Green,
+        "yellow" => AnsiColor::Yellow,
+        "blue" => AnsiColor::Blue,
+        "magenta" => AnsiColor::Magenta,
+        "cyan" => AnsiColor::Cyan,
+        "white" => AnsiColor::White,
+        "bright-black" | "gray" | "grey" => AnsiColor::BrightBlack,
+        "bright-red" => AnsiColor::BrightRed,
+        "bright-green" => AnsiColor::BrightGreen,
+        "bright-yellow" => AnsiColor::BrightYellow,
+        "bright-blue" => AnsiColor::BrightBlue,
+        "bright-magenta" => AnsiColor::BrightMagenta,
+        "bright-cyan" => AnsiColor::BrightCyan,
+        "bright-white" => AnsiColor::BrightWhite,
+        _ => return None,
+    };
+    Some(color)
+}
@@ -0,0 +1,221 @@
+// src/timezone.rs
+// Hour, day and week boundaries in the `timezone` of config.json (default: the system's local zone).
+// Timestamps stay UTC seconds everywhere; only period boundaries and summary buckets depend on the zone.
+// Days may start later than midnight (`day_start_hour`): 02:00 then still belongs to the day before.
+
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use jiff::{Span, Timestamp, Zoned};
+use serde::Deserialize;
+
+/// First day of the week for week periods and `weeks_summary_by_app` (`week_start` in config.json).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    /// ISO weeks
+    #[default]
+    Monday,
+    Sunday,
+}
+
+/// The zone, week start and day start hour that stats periods and the summary buckets are computed in.
+#[derive(Debug, Clone)]
+pub struct ReportTimeZone {
+    tz: TimeZone,
+    week_start: WeekStart,
+    // Local hour (0-23) days begin at
+    day_start_hour: u8,
+}
+
+impl Default for ReportTimeZone {
+    fn default() -> Self {
+        ReportTimeZone { tz: TimeZone::system(), week_start: WeekStart::default(), day_start_hour: 0 }
+    }
+}
+
+impl ReportTimeZone {
+    /// "local" (or empty) is the system zone; anything else must be an IANA name like "Europe/Paris" or "UTC".
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case("local") {
+            return Ok(Self::default());
+        }
+        TimeZone::get(name)
+            .map(|tz| ReportTimeZone { tz, ..Self::default() })
+            .map_err(|e| format!("Unknown timezone {:?} (expected \"local\" or an IANA name like \"Europe/Paris\"): {}", name, e))
+    }
+
+    pub fn with_week_start(mut self, week_start: WeekStart) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    /// Days (and the weeks made of them) begin at `hour` local time instead of midnight.
+    pub fn with_day_start_hour(mut self, hour: u8) -> Self {
+        self.day_start_hour = hour.min(23);
+        self
+    }
+
+    pub fn day_start_hour(&self) -> u8 {
+        self.day_start_hour
+    }
+
+    /// IANA name of the zone ("local" when the system zone has none).
+    pub fn name(&self) -> &str {
+        self.tz.iana_name().unwrap_or("local")
+    }
+
+    /// Offset from UTC in seconds at `timestamp` (e.g. 7200 for Paris in summer).
+    pub fn offset_secs(&self, timestamp: i64) -> i64 {
+        i64::from(self.tz.to_offset(to_timestamp(timestamp)).seconds())
+    }
+
+    /// `offset_secs` less `day_start_hour`: what the SQL day buckets (`ts - (ts + offset) % 86400`) add
+    /// so days start at `day_start_hour`.
+    pub fn day_offset_secs(&self, timestamp: i64) -> i64 {
+        self.offset_secs(timestamp) - i64::from(self.day_start_hour) * 3600
+    }
+
+    /// Start of the local hour containing `timestamp` (not a UTC hour in half-hour zones).
+    pub fn hour_start(&self, timestamp: i64) -> i64 {
+        timestamp - (timestamp + self.offset_secs(timestamp)).rem_euclid(3600)
+    }
+
+    /// Start of the local day containing `timestamp`.
+    pub fn day_start(&self, timestamp: i64) -> i64 {
+        self.start_of(self.date_of(timestamp)).unwrap_or(timestamp)
+    }
+
+    /// Start of the following local day (days last 23 or 25 hours around DST changes).
+    pub fn next_day_start(&self, timestamp: i64) -> i64 {
+        self.date_of(timestamp).tomorrow().and_then(|day| self.start_of(day)).unwrap_or(timestamp + 86_400)
+    }
+
+    /// Start of the preceding local day.
+    pub fn previous_day_start(&self, timestamp: i64) -> i64 {
+        self.date_of(timestamp).yesterday().and_then(|day| self.start_of(day)).unwrap_or(timestamp - 86_400)
+    }
+
+    /// Start of the local week containing `timestamp` (see `WeekStart`).
+    pub fn week_start(&self, timestamp: i64) -> i64 {
+        let date = self.date_of(timestamp);
+        let days_into_week = match self.week_start {
+            WeekStart::Monday => date.weekday().to_monday_zero_offset(),
+            WeekStart::Sunday => date.weekday().to_sunday_zero_offset(),
+        };
+        date.checked_sub(Span::new().days(i64::from(days_into_week)))
+            .and_then(|first_day| self.start_of(first_day))
+            .unwrap_or_else(|_| self.day_start(timestamp))
+    }
+
+    /// Start of the week `weeks` weeks after the one containing `timestamp` (negative = earlier).
+    pub fn add_weeks(&self, timestamp: i64, weeks: i64) -> i64 {
+        let week_start = self.week_start(timestamp);
+        self.date_of(week_start)
+            .checked_add(Span::new().weeks(weeks))
+            .and_then(|date| self.start_of(date))
+            .unwrap_or(week_start + weeks * 7 * 86_400)
+    }
+
+    /// Local date of the day `timestamp` belongs to as "YYYY-MM-DD".
+    pub fn date_string(&self, timestamp: i64) -> String {
+        self.date_of(timestamp).to_string()
+    }
+
+    /// Local time of `timestamp` as "HH:MM".
+    pub fn time_string(&self, timestamp: i64) -> String {
+        self.zoned(timestamp).strftime("%H:%M").to_string()
+    }
+
+    /// Start of the local day of a "YYYY-MM-DD" date.
+    pub fn date_start(&self, date: &str) -> Result<i64, String> {
+        let date: Date = date.trim().parse().map_err(|e| format!("invalid date {:?} (expected YYYY-MM-DD): {}", date, e))?;
+        self.start_of(date).map_err(|e| format!("date {} is out of range: {}", date, e))
+    }
+
+    /// Weekday name ("Tuesday") of the day `timestamp` belongs to.
+    pub fn weekday_name(&self, timestamp: i64) -> String {
+        self.date_of(timestamp).strftime("%A").to_string()
+    }
+
+    /// Local weekday (0 = Monday) and minute of the day (0..1440) at `timestamp`.
+    pub fn weekday_and_minute(&self, timestamp: i64) -> (u8, u32) {
+        let zoned = self.zoned(timestamp);
+        (zoned.weekday().to_monday_zero_offset() as u8, zoned.hour() as u32 * 60 + zoned.minute() as u32)
+    }
+
+    /// `minute` of the local date of `timestamp`, whatever `day_start_hour` is (a time skipped by DST moves forward).
+    pub fn at_minute(&self, timestamp: i64, minute: u32) -> i64 {
+        let (hour, minute) = ((minute / 60).min(23) as i8, (minute % 60) as i8);
+        let midnight = timestamp - (timestamp + self.offset_secs(timestamp)).rem_euclid(86_400);
+        self.zoned(timestamp)
+            .date()
+            .at(hour, minute, 0, 0)
+            .to_zoned(self.tz.clone())
+            .map_or(midnight + i64::from(hour) * 3600 + i64::from(minute) * 60, |zoned| zoned.timestamp().as_second())
+    }
+
+    /// The zone itself (calendar times without a zone of their own are read in it).
+    pub fn zone(&self) -> &TimeZone {
+        &self.tz
+    }
+
+    fn zoned(&self, timestamp: i64) -> Zoned {
+        to_timestamp(timestamp).to_zoned(self.tz.clone())
+    }
+
+    // The date of the day `timestamp` belongs to: before `day_start_hour`, the day before
+    fn date_of(&self, timestamp: i64) -> Date {
+        let zoned = self.zoned(timestamp);
+        if zoned.hour() < self.day_start_hour as i8 {
+            zoned.date().yesterday().unwrap_or(zoned.date())
+        } else {
+            zoned.date()
+        }
+    }
+
+    // When the day of `date` starts; an hour skipped by DST moves forward
+    fn start_of(&self, date: Date) -> Result<i64, jiff::Error> {
+        let day = if self.day_start_hour == 0 {
+            date.to_zoned(self.tz.clone())?.start_of_day()?
+        } else {
+            date.at(self.day_start_hour as i8, 0, 0, 0).to_zoned(self.tz.clone())?
+        };
+        Ok(day.timestamp().as_second())
+    }
+}
+
+/// Fires once per calendar day, at the first check after a minute of the day (daily recap and summary post).
+/// The minute is a wall-clock time, so `day_start_hour` does not move it to another date.
+#[derive(Debug, Clone)]
+pub struct DailySchedule {
+    timezone: ReportTimeZone,
+    minute: u32,
+    // The run it last fired (or was skipped) for
+    last_run: i64,
+}
+
+impl DailySchedule {
+    /// A schedule created after `minute` has passed today waits for the next day.
+    pub fn new(timezone: &ReportTimeZone, minute: u32, now: i64) -> Self {
+        let mut schedule = DailySchedule { timezone: timezone.clone(), minute, last_run: i64::MIN };
+        if schedule.is_due(now) {
+            schedule.mark_done(now);
+        }
+        schedule
+    }
+
+    /// True once today's `minute` has passed, if it has not fired for it yet.
+    pub fn is_due(&self, now: i64) -> bool {
+        let run = self.timezone.at_minute(now, self.minute);
+        now >= run && run != self.last_run
+    }
+
+    pub fn mark_done(&mut self, now: i64) {
+        self.last_run = self.timezone.at_minute(now, self.minute);
+    }
+}
+
+fn to_timestamp(timestamp: i64) -> Timestamp {
+    Timestamp::from_second(timestamp).unwrap_or(Timestamp::UNIX_EPOCH)
+}
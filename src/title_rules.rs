@@ -0,0 +1,96 @@
+// src/title_rules.rs
+// `title_rules` in config.json: rewrites window titles before they are recorded, so variants of one
+// title aggregate together (e.g. "(3) Inbox — Gmail" -> "Inbox — Gmail"). `normalize --apply-history`
+// applies them to titles already stored.
+
+use crate::utils::glob_match;
+use regex::Regex;
+use serde::Deserialize;
+
+/// One entry of `title_rules`. Each rule does exactly one of `strip_counter`, `trim_suffix` or `pattern`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TitleRuleConfig {
+    /// Glob on the app name (case-insensitive, e.g. "chrome*"; unset = every app)
+    pub app: Option<String>,
+    /// Removes a leading unread counter: "(3) ", "[12] ", "(99+) "
+    pub strip_counter: bool,
+    /// Removes this text from the end of the title (e.g. " - Google Chrome")
+    pub trim_suffix: Option<String>,
+    /// Regular expression whose matches are replaced with `replace`
+    pub pattern: Option<String>,
+    /// Replacement for `pattern` (`$1` refers to a capture group; default: empty)
+    pub replace: String,
+}
+
+#[derive(Debug, Clone)]
+enum TitleAction {
+    StripCounter,
+    TrimSuffix(String),
+    Replace(Regex, String),
+}
+
+#[derive(Debug, Clone)]
+struct TitleRule {
+    app: Option<String>,
+    action: TitleAction,
+}
+
+/// The parsed rules, applied in order to the main and the detailed title.
+#[derive(Debug, Clone, Default)]
+pub struct TitleRules {
+    rules: Vec<TitleRule>,
+    // Leading "(3) " style counter, shared by every `strip_counter` rule
+    counter: Option<Regex>,
+}
+
+impl TitleRules {
+    pub fn parse(rules: &[TitleRuleConfig]) -> Result<Self, String> {
+        let mut parsed = Vec::with_capacity(rules.len());
+        for (index, rule) in rules.iter().enumerate() {
+            let context = |message: String| format!("title_rules[{}]: {}", index, message);
+            let trim_suffix = rule.trim_suffix.clone().filter(|suffix| !suffix.is_empty());
+            let pattern = rule.pattern.as_deref().filter(|pattern| !pattern.is_empty());
+            let action = match (rule.strip_counter, trim_suffix, pattern) {
+                (true, None, None) => TitleAction::StripCounter,
+                (false, Some(suffix), None) => TitleAction::TrimSuffix(suffix),
+                (false, None, Some(pattern)) => {
+                    let regex = Regex::new(pattern).map_err(|e| context(format!("invalid pattern: {}", e)))?;
+                    TitleAction::Replace(regex, rule.replace.clone())
+                }
+                (false, None, None) => return Err(context("set \"strip_counter\", \"trim_suffix\" or \"pattern\"".to_string())),
+                _ => return Err(context("set only one of \"strip_counter\", \"trim_suffix\" and \"pattern\"".to_string())),
+            };
+            parsed.push(TitleRule { app: rule.app.clone().filter(|pattern| !pattern.trim().is_empty()), action });
+        }
+        let counter = parsed
+            .iter()
+            .any(|rule| matches!(rule.action, TitleAction::StripCounter))
+            .then(|| Regex::new(r"^\s*[(\[]\d+\+?[)\]]\s*").expect("valid counter pattern"));
+        Ok(TitleRules { rules: parsed, counter })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// `title` of an `app_name` window after every matching rule, trimmed (unchanged when no rule matches the app).
+    pub fn normalize(&self, app_name: &str, title: &str) -> String {
+        let mut rules = self.rules.iter().filter(|rule| rule.app.as_deref().is_none_or(|pattern| glob_match(pattern, app_name))).peekable();
+        if rules.peek().is_none() {
+            return title.to_string();
+        }
+        let mut title = title.to_string();
+        for rule in rules {
+            title = match &rule.action {
+                TitleAction::StripCounter => match &self.counter {
+                    Some(counter) => counter.replace(&title, "").into_owned(),
+                    None => title,
+                },
+                TitleAction::TrimSuffix(suffix) => title.strip_suffix(suffix.as_str()).map_or(title.clone(), str::to_string),
+                TitleAction::Replace(regex, replacement) => regex.replace_all(&title, replacement.as_str()).into_owned(),
+            };
+        }
+        title.trim().to_string()
+    }
+}
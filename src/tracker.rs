@@ -0,0 +1,1520 @@
+// src/tracker.rs
+
+use crate::{
+    persistence,
+    app_metadata::MetadataCollector,
+    clock::{self, Clock, ClockGuard, ClockJump},
+    config::{self, AppConfig},
+    profile_rules::ProfileRules,
+    tracking_hours::TrackingHours,
+    title_rules::TitleRules,
+    recap::DailyRecap,
+    break_reminder::{BreakEvent, BreakReminder},
+    loop_health::{self, LoopHealth, LoopHealthReport},
+    event_tap::{EventTap, IntervalEvent},
+    notify::DailyPost,
+    schedule::Scheduler,
+    timezone::ReportTimeZone,
+    pseudonym::Pseudonymizer,
+    errors::{AppError, AppResult},
+    detection::{self, passive::PassiveHeuristic, document::DocumentExtractor, remote::RemoteSessionTagger, repo::RepoResolver, ticket::TicketExtractor, ActivityDetector, ActivityInfo, SuspendState}, // Import detection trait/struct
+    types::{AggregatedResult, AggregationLevel, AudioSample, NewInterval, RunEventKind, StatsFilter, TimePeriod, WindowInfo},
+    mqtt::MqttPublisher,
+    network::{NetworkHandle, NetworkRuntime},
+    webhook::WebhookNotifier,
+};
+use std::cell::RefCell;
+use std::panic;
+use std::sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, Mutex, Once, OnceLock};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use persistence::{BufferedIntervalWriter, IntervalHandle, Storage, StoragePool};
+
+// --- Helper Structs (TrackedTarget can now use ActivityInfo) ---
+
+// Option 1: Keep TrackedTarget separate if it might diverge later
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrackedTarget {
+    app_name: String,
+    main_title: String,
+    detailed_title: String,
+    virtual_desktop: Option<String>,
+    repo: Option<String>,
+    executable_path: Option<String>,
+    command_line: Option<String>,
+    ticket: Option<String>,
+    session_type: Option<String>,
+    remote_host: Option<String>,
+    document: Option<String>,
+    passive: bool,
+    // Profile the interval is recorded in (None = the tracker's own); set from `profile_rules`
+    profile: Option<String>,
+}
+
+// Option 2: Use ActivityInfo directly (if identical)
+// type TrackedTarget = ActivityInfo; // Simpler if they are the same
+
+impl From<ActivityInfo> for TrackedTarget { // Helper conversion
+    fn from(info: ActivityInfo) -> Self {
+        TrackedTarget {
+            app_name: info.app_name,
+            main_title: info.main_title,
+            detailed_title: info.detailed_title,
+            virtual_desktop: info.virtual_desktop,
+            repo: info.repo,
+            executable_path: info.executable_path,
+            command_line: info.command_line,
+            ticket: info.ticket,
+            session_type: info.session_type,
+            remote_host: info.remote_host,
+            document: info.document,
+            passive: info.passive,
+            profile: None,
+        }
+    }
+}
+
+/// Interval writers of the tracker's own profile and of each profile `profile_rules` can send
+/// intervals to. Only intervals are routed; snapshots, errors and sync stay with the own profile.
+struct ProfileWriters {
+    rules: ProfileRules,
+    timezone: ReportTimeZone,
+    own: BufferedIntervalWriter,
+    // (profile, its storage, its writer)
+    others: Vec<(String, Box<dyn Storage>, BufferedIntervalWriter)>,
+    device_id: String,
+    user_name: String,
+    // `track --emit-events`
+    events: Option<EventTap>,
+}
+
+impl ProfileWriters {
+    fn new(settings: &PersistenceSettings, others: Vec<(String, Box<dyn Storage>)>, events: Option<EventTap>) -> Self {
+        ProfileWriters {
+            rules: settings.profile_rules.clone(),
+            timezone: settings.timezone.clone(),
+            own: BufferedIntervalWriter::new(settings.write_flush_interval),
+            others: others
+                .into_iter()
+                .map(|(profile, storage)| (profile, storage, BufferedIntervalWriter::new(settings.write_flush_interval)))
+                .collect(),
+            device_id: settings.device_id.clone(),
+            user_name: settings.user_name.clone(),
+            events,
+        }
+    }
+
+    /// Queues the start of an interval of `target` in its profile's writer.
+    fn start_interval(&mut self, target: &TrackedTarget, start_time: i64) -> IntervalHandle {
+        if let Some(events) = self.events.as_mut() {
+            events.emit(&IntervalEvent::IntervalStart {
+                timestamp: start_time,
+                device_id: &self.device_id,
+                profile: target.profile.as_deref(),
+                app: &target.app_name,
+                main_title: &target.main_title,
+                detailed_title: &target.detailed_title,
+                virtual_desktop: target.virtual_desktop.as_deref(),
+                repo: target.repo.as_deref(),
+                executable_path: target.executable_path.as_deref(),
+                command_line: target.command_line.as_deref(),
+                ticket: target.ticket.as_deref(),
+                session_type: target.session_type.as_deref(),
+                remote_host: target.remote_host.as_deref(),
+                document: target.document.as_deref(),
+                passive: target.passive,
+            });
+        }
+        let device_id = self.device_id.clone();
+        let user_name = self.user_name.clone();
+        let interval = NewInterval {
+            app_name: &target.app_name,
+            main_title: &target.main_title,
+            detailed_title: &target.detailed_title,
+            virtual_desktop: target.virtual_desktop.as_deref(),
+            repo: target.repo.as_deref(),
+            executable_path: target.executable_path.as_deref(),
+            command_line: target.command_line.as_deref(),
+            ticket: target.ticket.as_deref(),
+            session_type: target.session_type.as_deref(),
+            remote_host: target.remote_host.as_deref(),
+            document: target.document.as_deref(),
+            passive: target.passive,
+            start_time,
+            device_id: &device_id,
+            user_name: &user_name,
+        };
+        self.writer(target.profile.as_deref()).start_interval(&interval)
+    }
+
+    fn finalize_interval(&mut self, target: &TrackedTarget, handle: IntervalHandle, end_time: i64) {
+        if let Some(events) = self.events.as_mut() {
+            events.emit(&IntervalEvent::IntervalEnd {
+                timestamp: end_time,
+                start: events.open_start(),
+                device_id: &self.device_id,
+                profile: target.profile.as_deref(),
+                app: &target.app_name,
+            });
+        }
+        self.writer(target.profile.as_deref()).finalize_interval(handle, end_time);
+    }
+
+    /// Profile an interval of `app_name` starting at `timestamp` belongs in.
+    fn profile_for(&self, app_name: &str, timestamp: i64) -> Option<String> {
+        self.rules.resolve(app_name, timestamp, &self.timezone).map(str::to_string)
+    }
+
+    fn writer(&mut self, profile: Option<&str>) -> &mut BufferedIntervalWriter {
+        match profile.and_then(|profile| self.others.iter().position(|(name, _, _)| name == profile)) {
+            Some(index) => &mut self.others[index].2,
+            None => &mut self.own,
+        }
+    }
+
+    /// Flushes every writer that is due; errors of other profiles are logged, the own profile's returned.
+    fn flush_if_due(&mut self, storage: &mut dyn Storage, now: Instant) -> AppResult<usize> {
+        for (profile, other_storage, writer) in &mut self.others {
+            if let Err(e) = writer.flush_if_due(other_storage.as_mut(), now) {
+                eprintln!("[Persist] Error flushing buffered intervals of profile '{}': {}", profile, e);
+            }
+        }
+        self.own.flush_if_due(storage, now)
+    }
+
+    fn flush(&mut self, storage: &mut dyn Storage) -> AppResult<usize> {
+        for (profile, other_storage, writer) in &mut self.others {
+            if let Err(e) = writer.flush(other_storage.as_mut()) {
+                eprintln!("[Persist] Error flushing buffered intervals of profile '{}' on shutdown: {}", profile, e);
+            }
+            if let Err(e) = other_storage.checkpoint() {
+                eprintln!("[Persist] Warning: checkpoint of profile '{}' on shutdown failed: {}", profile, e);
+            }
+        }
+        self.own.flush(storage)
+    }
+}
+
+
+/// What happens to a switch that lasts less than `min_interval_secs` (e.g. alt-tabbing through windows).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortSwitchMode {
+    /// The time stays with the app that was tracked before the switch
+    #[default]
+    Previous,
+    /// The time is not recorded at all (the previous interval ends when the switch happened)
+    Discard,
+}
+
+/// A detected change that has not lasted `min_interval` yet.
+#[derive(Debug)]
+struct PendingSwitch {
+    target: Option<TrackedTarget>,
+    since: Instant,
+    since_timestamp: i64,
+    /// When the first of a row of unconfirmed switches happened (A -> B -> C: when B appeared)
+    chain_start: i64,
+}
+
+#[derive(Debug)]
+struct TrackerState {
+    // Store TrackedTarget or ActivityInfo depending on choice above
+    current_target: Option<(TrackedTarget, Instant, IntervalHandle)>,
+    // When the open interval was last confirmed in the DB (start or heartbeat)
+    last_heartbeat: Option<Instant>,
+    // Switches shorter than this are not recorded as intervals of their own (zero = off)
+    min_interval: Duration,
+    short_switches: ShortSwitchMode,
+    pending: Option<PendingSwitch>,
+    // Time of the final interval end at shutdown
+    clock: Arc<dyn Clock>,
+}
+
+impl TrackerState {
+    fn new(min_interval: Duration, short_switches: ShortSwitchMode, clock: Arc<dyn Clock>) -> Self {
+        TrackerState { current_target: None, last_heartbeat: None, min_interval, short_switches, pending: None, clock }
+    }
+
+    /// Periodically stamps the open interval so a crash only loses the time since the last heartbeat.
+    fn heartbeat(&mut self, writers: &mut ProfileWriters, heartbeat_interval: Duration, now_instant: Instant, now_timestamp: i64) {
+        let Some((target, _, handle)) = &self.current_target else { return };
+        let due = self.last_heartbeat.is_none_or(|last| now_instant.duration_since(last) >= heartbeat_interval);
+        if due {
+            writers.writer(target.profile.as_deref()).heartbeat(*handle, now_timestamp);
+            self.last_heartbeat = Some(now_instant);
+        }
+    }
+
+    // Update signature to take Option<ActivityInfo>
+    fn update(
+        &mut self,
+        writers: &mut ProfileWriters,
+        detection_result_option: Option<ActivityInfo>, // Changed type
+        now_instant: Instant,
+        now_timestamp: i64,
+    ) {
+        // Convert ActivityInfo to TrackedTarget if needed; a profile change (e.g. at the end of a
+        // scheduled window) is a target change like any other
+        let new_target_option: Option<TrackedTarget> = detection_result_option.map(|info| {
+            let profile = writers.profile_for(&info.app_name, now_timestamp);
+            TrackedTarget { profile, ..TrackedTarget::from(info) }
+        });
+
+        let target_changed = match &self.current_target {
+            Some((tracked_target, _, _)) => new_target_option.as_ref() != Some(tracked_target),
+            None => new_target_option.is_some(),
+        };
+
+        if self.min_interval.is_zero() {
+            if target_changed {
+                self.switch_to(writers, new_target_option, now_timestamp, now_timestamp, now_instant);
+            }
+            return;
+        }
+
+        if !target_changed {
+            // Back on the tracked target before the switch was confirmed
+            if let Some(pending) = self.pending.take()
+                && self.short_switches == ShortSwitchMode::Discard
+            {
+                self.switch_to(writers, new_target_option, pending.chain_start, now_timestamp, now_instant);
+            }
+            return;
+        }
+
+        match self.pending.take() {
+            Some(pending) if pending.target == new_target_option => {
+                if now_instant.duration_since(pending.since) >= self.min_interval {
+                    // Confirmed: record the switch from when it really happened
+                    let end_time = match self.short_switches {
+                        ShortSwitchMode::Previous => pending.since_timestamp,
+                        ShortSwitchMode::Discard => pending.chain_start,
+                    };
+                    self.switch_to(writers, new_target_option, end_time, pending.since_timestamp, pending.since);
+                } else {
+                    self.pending = Some(pending);
+                }
+            }
+            previous => {
+                self.pending = Some(PendingSwitch {
+                    target: new_target_option,
+                    since: now_instant,
+                    since_timestamp: now_timestamp,
+                    chain_start: previous.map_or(now_timestamp, |pending| pending.chain_start),
+                });
+            }
+        }
+    }
+
+    /// Ends the open interval at `end_time` and opens one for `new_target` (if any) at `start_time`.
+    fn switch_to(&mut self, writers: &mut ProfileWriters, new_target: Option<TrackedTarget>, end_time: i64, start_time: i64, start_instant: Instant) {
+        if let Some((target, _start_instant, handle)) = self.current_target.take() {
+            writers.finalize_interval(&target, handle, end_time);
+        }
+
+        if let Some(new_target) = new_target { // This is now TrackedTarget
+            // Writes are queued; the writer commits them in batches
+            let handle = writers.start_interval(&new_target, start_time);
+            self.current_target = Some((new_target, start_instant, handle));
+            self.last_heartbeat = Some(start_instant);
+        }
+    }
+
+    /// The system went to sleep at `since`: the open interval ends there, and the first sample
+    /// after resuming opens a new one.
+    fn suspend(&mut self, writers: &mut ProfileWriters, since: i64) {
+        self.pending = None;
+        if let Some((target, _start, handle)) = self.current_target.take() {
+            writers.finalize_interval(&target, handle, since);
+            log::info!("System suspended; ended interval for app '{}'.", target.app_name);
+        }
+    }
+
+    /// The wall clock jumped: the open interval ends at the last poll before the jump (or when the
+    /// system went to sleep) and the next sample opens a new one on the new clock. Timestamps of a
+    /// pending switch are from the old clock and are dropped with it.
+    fn clock_adjusted(&mut self, writers: &mut ProfileWriters, jump: ClockJump, suspended_since: Option<i64>) {
+        self.pending = None;
+        if let Some((target, _start, handle)) = self.current_target.take() {
+            writers.finalize_interval(&target, handle, suspended_since.unwrap_or(jump.before));
+            log::warn!(
+                "System clock jumped {:+}s ({} -> {}); ended interval for app '{}'.",
+                jump.offset_secs,
+                jump.before,
+                jump.after,
+                target.app_name
+            );
+        }
+    }
+
+    fn finalize(&mut self, writers: &mut ProfileWriters, live_output: bool) {
+         // An unconfirmed switch at shutdown is treated like any other short switch
+         let end_time = match self.pending.take() {
+             Some(pending) if self.short_switches == ShortSwitchMode::Discard => pending.chain_start,
+             _ => self.clock.now(),
+         };
+         if let Some((target, _start, handle)) = self.current_target.take() {
+             writers.finalize_interval(&target, handle, end_time);
+             log::info!("Finalized last active interval for app '{}'.", target.app_name);
+             if live_output {
+                 println!("Finalized last active interval for app '{}'.", target.app_name);
+             }
+         }
+    }
+}
+// --- End Helper Structs ---
+
+
+/// One detection result produced by the detection thread.
+#[derive(Debug)]
+struct DetectionSample {
+    activity: Option<ActivityInfo>,
+    instant: Instant,
+    timestamp: i64,
+}
+
+/// Messages sent from the detection thread to the persistence thread.
+#[derive(Debug)]
+enum TrackerEvent {
+    Sample(Box<DetectionSample>),
+    /// All visible top-level windows at `timestamp` (context snapshot)
+    Snapshot { timestamp: i64, windows: Vec<WindowInfo> },
+    /// The system slept starting at `since` (Unix time)
+    Suspended { since: i64 },
+    /// The wall clock jumped between two polls (NTP step, manual change or an unnotified sleep);
+    /// `suspended_since` is set when a suspend notification came with it
+    ClockAdjusted { jump: ClockJump, suspended_since: Option<i64> },
+    /// A detector poll failed (counted in `tracker_daily_stats` for `self-stats`)
+    DetectionError { timestamp: i64, message: String },
+    /// A break reminder was shown or followed by a break (`break_reminder`)
+    Break(BreakEvent),
+    /// Apps playing audio at `timestamp` (`audio_tracking`); open audio intervals last seen
+    /// before `stale_before` missed polls (sleep, crash) and end where they were last seen
+    Audio { timestamp: i64, apps: Vec<String>, stale_before: i64 },
+    /// Recording paused or resumed, or a `tracking_hours` boundary passed (for `gaps`)
+    RunState { at: i64, kind: RunEventKind },
+}
+
+/// This run of the tracker in `tracker_runs`, so `gaps` can tell time the tracker was not running
+/// (or crashed) from time it ran and recorded nothing. Failing to record it never stops tracking.
+struct RunLog {
+    // None when the run could not be recorded
+    id: Option<i64>,
+    last_heartbeat: Instant,
+}
+
+impl RunLog {
+    fn start(storage: &mut dyn Storage, settings: &PersistenceSettings, now: i64) -> Self {
+        let id = storage
+            .start_tracker_run(now, &settings.device_id, &settings.user_name)
+            .map_err(|e| eprintln!("[Persist] Error recording tracker run: {}", e))
+            .ok();
+        RunLog { id, last_heartbeat: Instant::now() }
+    }
+
+    fn event(&self, storage: &mut dyn Storage, at: i64, kind: RunEventKind) {
+        if let Some(id) = self.id
+            && let Err(e) = storage.record_run_event(id, at, kind)
+        {
+            eprintln!("[Persist] Error recording tracker run event: {}", e);
+        }
+    }
+
+    fn heartbeat_if_due(&mut self, storage: &mut dyn Storage, heartbeat_interval: Duration, now: i64) {
+        let Some(id) = self.id else { return };
+        if self.last_heartbeat.elapsed() < heartbeat_interval {
+            return;
+        }
+        self.last_heartbeat = Instant::now();
+        if let Err(e) = storage.heartbeat_tracker_run(id, now) {
+            eprintln!("[Persist] Error updating tracker run: {}", e);
+        }
+    }
+
+    fn end(&self, storage: &mut dyn Storage, now: i64) {
+        if let Some(id) = self.id
+            && let Err(e) = storage.end_tracker_run(id, now)
+        {
+            eprintln!("[Persist] Error recording end of tracker run: {}", e);
+        }
+    }
+}
+
+/// Detection errors counted in memory and written at most once per flush interval, so a
+/// detector failing on every poll does not turn into a write per poll.
+#[derive(Debug)]
+struct DetectionErrorLog {
+    count: i64,
+    last: Option<(i64, String)>,
+    last_write: Instant,
+}
+
+impl DetectionErrorLog {
+    fn new() -> Self {
+        DetectionErrorLog { count: 0, last: None, last_write: Instant::now() }
+    }
+
+    fn record(&mut self, timestamp: i64, message: String) {
+        self.count += 1;
+        self.last = Some((timestamp, message));
+    }
+
+    fn flush_if_due(&mut self, storage: &mut dyn Storage, flush_interval: Duration, now: Instant) {
+        if now.duration_since(self.last_write) >= flush_interval {
+            self.flush(storage);
+        }
+    }
+
+    fn flush(&mut self, storage: &mut dyn Storage) {
+        self.last_write = Instant::now();
+        let Some((timestamp, message)) = &self.last else { return };
+        match storage.record_detection_errors(*timestamp, self.count, message) {
+            Ok(()) => {
+                self.count = 0;
+                self.last = None;
+            }
+            // Kept for the next attempt
+            Err(e) => eprintln!("[Persist] Error recording detection errors: {}", e),
+        }
+    }
+}
+
+/// Settings the persistence thread needs, copied out of `AppConfig`.
+#[derive(Debug, Clone)]
+struct PersistenceSettings {
+    heartbeat_interval: Duration,
+    write_flush_interval: Duration,
+    device_id: String,
+    user_name: String,
+    // Switches shorter than this are buffered (zero = every change is recorded)
+    min_interval: Duration,
+    short_switches: ShortSwitchMode,
+    // Print "[Detected]" lines for every target change
+    live_output: bool,
+    profile_rules: ProfileRules,
+    // Zone the schedule of `profile_rules` is read in
+    timezone: ReportTimeZone,
+    // Look up friendly names of new apps (off with `pseudonymize`: hashed names match no executable)
+    app_metadata: bool,
+}
+
+/// Poll timing for the detection thread, copied out of `AppConfig`, and the clock samples are stamped with.
+#[derive(Debug, Clone)]
+struct PollingSettings {
+    check_interval: Duration,
+    adaptive_polling: bool,
+    idle_poll_interval: Duration,
+    idle_threshold: Duration,
+    // None disables context snapshots
+    snapshot_interval: Option<Duration>,
+    // None disables `audio_tracking`
+    audio_interval: Option<Duration>,
+    detection_retries: u32,
+    detection_grace: Duration,
+    // Outside these windows the loop idles as if paused; read in `timezone`
+    tracking_hours: TrackingHours,
+    timezone: ReportTimeZone,
+    clock: Arc<dyn Clock>,
+    // Print tracking_hours boundaries (off in daemon mode and when stdout carries `--emit-events -`)
+    live_output: bool,
+}
+
+/// Enrichment of each detected activity before it is sent: the passive flag, the Git repository,
+/// issue key and document (with `repo_detection` / `ticket_detection` / `document_detection`, read from the
+/// titles as detected), the remote desktop session and the `title_rules` rewrites.
+struct ActivityTagging {
+    passive: PassiveHeuristic,
+    repos: Option<RepoResolver>,
+    tickets: Option<TicketExtractor>,
+    documents: Option<DocumentExtractor>,
+    remote: RemoteSessionTagger,
+    titles: TitleRules,
+    // `record_executable_path` / `record_command_line`: what detectors report is dropped otherwise
+    executable_path: bool,
+    command_line: bool,
+    // `pseudonymize`: hashes everything above last, so rules and detection still see the plain strings
+    pseudonyms: Option<Pseudonymizer>,
+}
+
+impl ActivityTagging {
+    fn tag(&mut self, detector: &dyn ActivityDetector, activity: &mut ActivityInfo) {
+        if !self.executable_path {
+            activity.executable_path = None;
+        }
+        if !self.command_line {
+            activity.command_line = None;
+        }
+        self.passive.tag(detector, activity);
+        if let Some(repos) = self.repos.as_mut() {
+            repos.tag(activity);
+        }
+        if let Some(tickets) = &self.tickets {
+            tickets.tag(activity);
+        }
+        if let Some(documents) = &self.documents {
+            documents.tag(activity);
+        }
+        self.remote.tag(activity);
+        if !self.titles.is_empty() {
+            activity.main_title = self.titles.normalize(&activity.app_name, &activity.main_title);
+            activity.detailed_title = self.titles.normalize(&activity.app_name, &activity.detailed_title);
+        }
+        if let Some(pseudonyms) = &self.pseudonyms {
+            pseudonyms.apply(activity);
+        }
+    }
+}
+
+/// Periodic `sync push` (enabled by `sync_interval_mins`), timed by the persistence thread and run
+/// on the network runtime with a connection of its own.
+struct AutoSync {
+    config: AppConfig,
+    network: NetworkHandle,
+    interval: Duration,
+    next_due: Instant,
+}
+
+impl AutoSync {
+    fn from_config(config: &AppConfig, network: &NetworkHandle) -> Option<Self> {
+        let interval = config.sync_interval?;
+        Some(AutoSync { config: config.clone(), network: network.clone(), interval, next_due: Instant::now() + interval })
+    }
+
+    fn run_if_due(&mut self, now: Instant) {
+        if now < self.next_due {
+            return;
+        }
+        self.next_due = now + self.interval;
+        let config = self.config.clone();
+        self.network.run_job("sync", move || {
+            if let Err(e) = persistence::open_storage(&config).and_then(|mut storage| crate::sync::push(&config, storage.as_mut())) {
+                // The watermark only advances on success; the next run retries
+                eprintln!("[Network] Auto-sync failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Re-reads the `calendar` every `refresh_minutes` on the network runtime, so intervals are
+/// matched against current events when they are aggregated.
+struct CalendarRefresh {
+    config: AppConfig,
+    network: NetworkHandle,
+    interval_secs: i64,
+    next_due: i64,
+}
+
+impl CalendarRefresh {
+    fn from_config(config: &AppConfig, network: &NetworkHandle, now: i64) -> Option<Self> {
+        let minutes = config.calendar.as_ref()?.refresh_minutes.unwrap_or(crate::calendar::DEFAULT_REFRESH_MINUTES).max(1);
+        let interval_secs = minutes as i64 * 60;
+        Some(CalendarRefresh { config: config.clone(), network: network.clone(), interval_secs, next_due: now + interval_secs })
+    }
+
+    fn run_if_due(&mut self, now: i64) {
+        if now < self.next_due {
+            return;
+        }
+        self.next_due = now + self.interval_secs;
+        let config = self.config.clone();
+        self.network.run_job("calendar", move || {
+            // Yesterday too: its last hours may not be aggregated yet
+            if let Err(e) = persistence::open_storage(&config).and_then(|mut storage| crate::calendar::refresh(&config, storage.as_mut(), now, 1)) {
+                eprintln!("[Network] Calendar refresh failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Timed jobs of the persistence thread: the recap notification, the chat summary post, `schedule`
+/// and the calendar refresh. Their network parts run on the network runtime.
+struct ScheduledTasks {
+    recap: Option<DailyRecap>,
+    post: Option<DailyPost>,
+    jobs: Option<Scheduler>,
+    calendar: Option<CalendarRefresh>,
+    network: NetworkHandle,
+}
+
+impl ScheduledTasks {
+    fn from_config(config: &AppConfig, network: &NetworkHandle, now: i64) -> Self {
+        ScheduledTasks {
+            recap: DailyRecap::from_config(config, now),
+            post: DailyPost::from_config(config, now),
+            jobs: Scheduler::from_config(config, now),
+            calendar: CalendarRefresh::from_config(config, network, now),
+            network: network.clone(),
+        }
+    }
+
+    /// All read the database, so what is still buffered is written first.
+    fn run_if_due(&mut self, storage: &mut dyn Storage, writer: &mut BufferedIntervalWriter, now: i64) {
+        if let Some(calendar) = self.calendar.as_mut() {
+            calendar.run_if_due(now);
+        }
+        let recap_due = self.recap.as_ref().is_some_and(|recap| recap.is_due(now));
+        let post_due = self.post.as_ref().is_some_and(|post| post.is_due(now));
+        let jobs_due = self.jobs.as_ref().is_some_and(|jobs| jobs.is_due(now));
+        if !recap_due && !post_due && !jobs_due {
+            return;
+        }
+        if let Err(e) = writer.flush(storage) {
+            eprintln!("[Persist] Error flushing buffered intervals: {}", e);
+        }
+        if let Some(recap) = self.recap.as_mut().filter(|_| recap_due) {
+            recap.run(storage, now);
+        }
+        if let Some(post) = self.post.as_mut().filter(|_| post_due) {
+            post.run(storage, &self.network, now);
+        }
+        if let Some(jobs) = self.jobs.as_mut().filter(|_| jobs_due) {
+            jobs.run_due(storage, &self.network, now);
+        }
+    }
+}
+
+/// State shared between the tracker threads and any `TrackerHandle`.
+#[derive(Debug, Default)]
+struct SharedState {
+    running: AtomicBool,
+    paused: AtomicBool,
+    current_activity: Mutex<Option<ActivityInfo>>,
+    // Since the tracker started, see `DetectionCounters`
+    detection_retries: AtomicU64,
+    bridged_polls: AtomicU64,
+    failed_polls: AtomicU64,
+    // Detection loop iterations, the heartbeat read by the watchdog (`track --supervise`)
+    detection_polls: AtomicU64,
+    // Latencies and missed polls of the last polls and writes (`status --verbose`, `metrics_listen`)
+    health: Mutex<LoopHealth>,
+}
+
+/// How transient detection failures were handled since the tracker started (`status`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DetectionCounters {
+    /// Detection attempts repeated after a failure within a poll
+    pub retries: u64,
+    /// Failed polls that kept the last detected activity (`detection_grace_secs`)
+    pub bridged_polls: u64,
+    /// Failed polls that recorded nothing
+    pub failed_polls: u64,
+}
+
+/// First pause before a failed detection is retried; doubled for each further attempt.
+const DETECTION_RETRY_DELAY: Duration = Duration::from_millis(50);
+/// How often the apps playing audio are read with `audio_tracking` (at least `check_interval`)
+const AUDIO_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Retries failed detections and bridges short outages with the last activity detected, so
+/// a brief API hiccup neither splits the current interval nor goes unrecorded.
+struct DetectionRecovery {
+    retries: u32,
+    grace: Duration,
+    // Last successful detection (tagged) and when it was made
+    last_good: Option<(Option<ActivityInfo>, Instant)>,
+}
+
+/// One poll's result: the activity to record and the error it failed with, if it failed.
+struct DetectionOutcome {
+    activity: Option<ActivityInfo>,
+    error: Option<AppError>,
+    bridged: bool,
+}
+
+impl DetectionRecovery {
+    fn new(retries: u32, grace: Duration) -> Self {
+        DetectionRecovery { retries, grace, last_good: None }
+    }
+
+    fn poll(&mut self, detector: &dyn ActivityDetector, tagging: &mut ActivityTagging, shared: &SharedState) -> DetectionOutcome {
+        let mut delay = DETECTION_RETRY_DELAY;
+        let mut attempt = 0;
+        let error = loop {
+            match detector.get_current_activity() {
+                Ok(mut activity) => {
+                    if let Some(info) = activity.as_mut() {
+                        tagging.tag(detector, info);
+                    }
+                    self.last_good = Some((activity.clone(), Instant::now()));
+                    return DetectionOutcome { activity, error: None, bridged: false };
+                }
+                Err(e) if attempt < self.retries && is_transient(&e) && shared.running.load(Ordering::SeqCst) => {
+                    log::debug!("Detection failed ({}); retrying in {:?}.", e, delay);
+                    shared.detection_retries.fetch_add(1, Ordering::Relaxed);
+                    thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => break e,
+            }
+        };
+        match &self.last_good {
+            Some((activity, at)) if at.elapsed() < self.grace => {
+                shared.bridged_polls.fetch_add(1, Ordering::Relaxed);
+                DetectionOutcome { activity: activity.clone(), error: Some(error), bridged: true }
+            }
+            _ => {
+                shared.failed_polls.fetch_add(1, Ordering::Relaxed);
+                DetectionOutcome { activity: None, error: Some(error), bridged: false }
+            }
+        }
+    }
+}
+
+/// Failures worth another attempt; a misconfigured or unsupported detector fails the same way every time.
+fn is_transient(error: &AppError) -> bool {
+    match error {
+        AppError::Detection(error) => error.is_transient(),
+        _ => false,
+    }
+}
+
+/// What the detection thread feeds on every poll besides the persistence thread: the webhook,
+/// MQTT and break reminders (each None when not configured).
+struct PollObservers {
+    webhook: Option<WebhookNotifier>,
+    mqtt: Option<MqttPublisher>,
+    breaks: Option<BreakReminder>,
+}
+
+// --- Detection Thread (producer) ---
+/// Polls the detector every `check_interval` and sends each sample to the persistence thread.
+/// Returning drops the sender, which tells the consumer to shut down.
+fn run_detection_loop(
+    detector: Box<dyn ActivityDetector>,
+    polling: PollingSettings,
+    mut tagging: ActivityTagging,
+    shared: Arc<SharedState>,
+    sender: Sender<TrackerEvent>,
+    mut observers: PollObservers,
+) {
+    let clock = polling.clock.clone();
+    let mut last_activity: Option<ActivityInfo> = None;
+    let mut next_snapshot = Instant::now();
+    let mut audio = polling.audio_interval.map(AudioPoll::new);
+    let mut handled_suspend: Option<i64> = None;
+    let mut clock_guard = ClockGuard::default();
+    let mut recovery = DetectionRecovery::new(polling.detection_retries, polling.detection_grace);
+    let mut off_hours = false;
+    let mut paused = false;
+    // When this pass should have started; a later start missed polls
+    let mut due: Option<Instant> = None;
+    while shared.running.load(Ordering::SeqCst) {
+        let loop_start_time = Instant::now();
+        shared.detection_polls.fetch_add(1, Ordering::Relaxed);
+        let missed = due.map_or(0, |due| loop_health::missed_polls(loop_start_time.saturating_duration_since(due), polling.check_interval));
+        let (mut detection_time, mut detection_failed) = (None, false);
+
+        let suspend = suspend_state(detector.as_ref());
+        let notified = suspend.last_suspend.filter(|since| handled_suspend != Some(*since));
+        if notified.is_some() {
+            handled_suspend = notified;
+        }
+        let jump = clock_guard.check(clock.instant(), clock.now());
+        let event = match (notified, jump) {
+            // A notification read only after waking up must not count the sleep as activity
+            (Some(since), jump) => {
+                let since = clock_guard.previous_timestamp().map_or(since, |previous| since.min(previous));
+                Some(match jump {
+                    // Sleep explains a forward jump, not a clock set back during it
+                    Some(jump) if jump.is_backward() => TrackerEvent::ClockAdjusted { jump, suspended_since: Some(since) },
+                    _ => TrackerEvent::Suspended { since },
+                })
+            }
+            (None, Some(jump)) => Some(TrackerEvent::ClockAdjusted { jump, suspended_since: None }),
+            (None, None) => None,
+        };
+        if let Some(event) = event
+            && sender.send(event).is_err()
+        {
+            eprintln!("[Detect] Persistence thread stopped; ending detection loop.");
+            break;
+        }
+
+        let outside = !polling.tracking_hours.is_recording(clock.now(), &polling.timezone);
+        if outside != off_hours {
+            off_hours = outside;
+            if polling.live_output {
+                if off_hours {
+                    println!("[Detect] Outside tracking_hours ({}); not recording.", polling.tracking_hours.describe());
+                } else {
+                    println!("[Detect] Within tracking_hours; recording resumed.");
+                }
+            }
+            let kind = if off_hours { RunEventKind::OffHoursStart } else { RunEventKind::OffHoursEnd };
+            let _ = sender.send(TrackerEvent::RunState { at: clock.now(), kind });
+        }
+        if shared.paused.load(Ordering::SeqCst) != paused {
+            paused = !paused;
+            let kind = if paused { RunEventKind::Pause } else { RunEventKind::Resume };
+            let _ = sender.send(TrackerEvent::RunState { at: clock.now(), kind });
+        }
+        let locked = !off_hours && session_locked(detector.as_ref());
+        let activity = if shared.paused.load(Ordering::SeqCst) || suspend.suspended || off_hours {
+            None // Paused, outside tracking_hours or going to sleep: closes the open interval and records nothing
+        } else if locked {
+            Some(ActivityInfo::locked()) // Not attributed to the window left under the cursor
+        } else {
+            let detection_start = Instant::now();
+            let outcome = recovery.poll(detector.as_ref(), &mut tagging, &shared);
+            detection_time = Some(detection_start.elapsed());
+            detection_failed = outcome.error.is_some();
+            if let Some(e) = outcome.error {
+                if outcome.bridged {
+                    log::info!("Detection failed, keeping the last activity: {}", e);
+                } else {
+                    eprintln!("[Detect] Detection Error: {}", e);
+                }
+                let _ = sender.send(TrackerEvent::DetectionError { timestamp: clock.now(), message: e.to_string() });
+            }
+            outcome.activity // None after a failure outside the grace window
+        };
+        if let Ok(mut health) = shared.health.lock() {
+            health.record_poll(detection_time, missed, detection_failed);
+        }
+        let unchanged = activity == last_activity;
+        last_activity = activity.clone();
+        if let Ok(mut current) = shared.current_activity.lock() {
+            current.clone_from(&activity);
+        }
+
+        let timestamp = clock.now();
+        if let Some(webhook) = observers.webhook.as_mut() {
+            let info = activity.as_ref();
+            webhook.observe(info.map(|info| info.app_name.as_str()), info.map(|info| info.detailed_title.as_str()), timestamp);
+        }
+        if let Some(mqtt) = observers.mqtt.as_mut() {
+            mqtt.publish(activity.as_ref(), is_user_idle(detector.as_ref(), &polling));
+        }
+        if let Some(breaks) = observers.breaks.as_mut() {
+            let active = activity.is_some() && !locked && !is_user_idle(detector.as_ref(), &polling);
+            if let Some(event) = breaks.observe(active, timestamp) {
+                let _ = sender.send(TrackerEvent::Break(event));
+            }
+        }
+        let sample = DetectionSample { activity, instant: clock.instant(), timestamp };
+        clock_guard.record(sample.instant, timestamp);
+        if sender.send(TrackerEvent::Sample(Box::new(sample))).is_err() {
+            eprintln!("[Detect] Persistence thread stopped; ending detection loop.");
+            break;
+        }
+
+        if let Some(snapshot_interval) = polling.snapshot_interval
+            && loop_start_time >= next_snapshot
+            && !shared.paused.load(Ordering::SeqCst)
+            && !off_hours
+            && !locked
+        {
+            next_snapshot = loop_start_time + snapshot_interval;
+            match detector.list_windows() {
+                Ok(mut windows) => {
+                    if let Some(pseudonyms) = &tagging.pseudonyms {
+                        pseudonyms.apply_to_windows(&mut windows);
+                    }
+                    let _ = sender.send(TrackerEvent::Snapshot { timestamp: clock.now(), windows });
+                }
+                Err(e) => log::warn!("Context snapshot failed: {}", e),
+            }
+        }
+
+        if let Some(poll) = audio.as_mut()
+            && loop_start_time >= poll.next
+        {
+            let recording = !shared.paused.load(Ordering::SeqCst) && !suspend.suspended && !off_hours;
+            match poll.read(detector.as_ref(), recording, &tagging, loop_start_time) {
+                Ok(Some(apps)) => {
+                    let timestamp = clock.now();
+                    let stale_before = timestamp - poll.stale_after(&polling).as_secs() as i64;
+                    let _ = sender.send(TrackerEvent::Audio { timestamp, apps, stale_before });
+                }
+                Ok(None) => {}
+                Err(AudioUnsupported) => {
+                    eprintln!("[Detect] audio_tracking: this platform cannot tell which apps play audio; disabled.");
+                    audio = None;
+                }
+            }
+        }
+
+        if polling.adaptive_polling && unchanged && is_user_idle(detector.as_ref(), &polling) {
+            due = Some(loop_start_time + polling.idle_poll_interval);
+            wait_while_idle(detector.as_ref(), &polling, &shared.running, loop_start_time);
+        } else {
+            due = Some(loop_start_time + polling.check_interval);
+            let elapsed = loop_start_time.elapsed();
+            if elapsed < polling.check_interval {
+                thread::sleep(polling.check_interval - elapsed);
+            }
+        }
+    }
+    if audio.is_some_and(|poll| !poll.playing.is_empty()) {
+        let timestamp = clock.now();
+        let _ = sender.send(TrackerEvent::Audio { timestamp, apps: Vec::new(), stale_before: timestamp });
+    }
+}
+
+/// The detector reported that it cannot list the apps playing audio.
+#[derive(Debug)]
+struct AudioUnsupported;
+
+/// Audio polling state of the detection loop (`audio_tracking`).
+#[derive(Debug)]
+struct AudioPoll {
+    interval: Duration,
+    next: Instant,
+    // Apps sent last; nothing is sent while nothing plays
+    playing: Vec<String>,
+}
+
+impl AudioPoll {
+    fn new(interval: Duration) -> Self {
+        AudioPoll { interval, next: Instant::now(), playing: Vec::new() }
+    }
+
+    /// The apps to send (already pseudonymized), None when there is nothing new to write.
+    /// Paused, off-hours or sleeping (`recording` false) counts as silence; failed reads are logged and skipped.
+    fn read(&mut self, detector: &dyn ActivityDetector, recording: bool, tagging: &ActivityTagging, now: Instant) -> Result<Option<Vec<String>>, AudioUnsupported> {
+        self.next = now + self.interval;
+        let apps = if recording {
+            match detector.audio_apps() {
+                Ok(Some(apps)) => apps,
+                Ok(None) => return Err(AudioUnsupported),
+                Err(e) => {
+                    log::warn!("Reading the apps playing audio failed: {}", e);
+                    return Ok(None);
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let apps: Vec<String> = match &tagging.pseudonyms {
+            Some(pseudonyms) => apps.iter().map(|app| pseudonyms.hash(app)).collect(),
+            None => apps,
+        };
+        if apps.is_empty() && self.playing.is_empty() {
+            return Ok(None);
+        }
+        self.playing.clone_from(&apps);
+        Ok(Some(apps))
+    }
+
+    /// Polls can be this far apart while the loop waits out idle time; an open interval not seen
+    /// for longer belongs to a stopped tracker or a sleep.
+    fn stale_after(&self, polling: &PollingSettings) -> Duration {
+        2 * self.interval.max(polling.idle_poll_interval)
+    }
+}
+
+fn suspend_state(detector: &dyn ActivityDetector) -> SuspendState {
+    detector.suspend_state().unwrap_or_else(|e| {
+        log::debug!("Suspend state unavailable: {}", e);
+        SuspendState::default()
+    })
+}
+
+fn session_locked(detector: &dyn ActivityDetector) -> bool {
+    detector.is_session_locked().unwrap_or_else(|e| {
+        log::debug!("Session lock state unavailable: {}", e);
+        false
+    })
+}
+
+fn is_user_idle(detector: &dyn ActivityDetector, polling: &PollingSettings) -> bool {
+    match detector.idle_time() {
+        Ok(Some(idle)) => idle >= polling.idle_threshold,
+        Ok(None) => false, // Platform cannot tell; keep the base interval
+        Err(e) => {
+            log::debug!("Idle time unavailable: {}", e);
+            false
+        }
+    }
+}
+
+/// Sleeps up to `idle_poll_interval`, waking early on user input or shutdown
+/// so the base interval resumes as soon as something changes.
+fn wait_while_idle(detector: &dyn ActivityDetector, polling: &PollingSettings, running: &AtomicBool, loop_start_time: Instant) {
+    while loop_start_time.elapsed() < polling.idle_poll_interval {
+        thread::sleep(polling.check_interval.min(polling.idle_poll_interval.saturating_sub(loop_start_time.elapsed())));
+        if !running.load(Ordering::SeqCst) || !is_user_idle(detector, polling) {
+            break;
+        }
+    }
+}
+
+// --- Persistence Thread (consumer) ---
+/// What the persistence loop owns. Dropped while unwinding from a panic in the loop, it still ends the
+/// open interval now and writes the queued intervals, instead of leaving them to the next start's
+/// dangling-interval pass.
+struct PersistenceScope {
+    storage: Box<dyn Storage>,
+    writers: ProfileWriters,
+    tracker_state: TrackerState,
+}
+
+impl PersistenceScope {
+    fn new(storage: Box<dyn Storage>, writers: ProfileWriters, settings: &PersistenceSettings, clock: Arc<dyn Clock>) -> Self {
+        PersistenceScope { storage, writers, tracker_state: TrackerState::new(settings.min_interval, settings.short_switches, clock) }
+    }
+}
+
+impl Drop for PersistenceScope {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            return;
+        }
+        self.tracker_state.finalize(&mut self.writers, false);
+        match self.writers.flush(self.storage.as_mut()) {
+            Ok(_) => log::warn!("Persistence thread panicked; the open interval was finalized."),
+            Err(e) => log::error!("Persistence thread panicked; finalizing the open interval failed: {}", e),
+        }
+    }
+}
+
+thread_local! {
+    // The tracker a detection or persistence thread belongs to, stopped by the panic hook
+    static TRACKER_THREAD: RefCell<Option<Arc<SharedState>>> = const { RefCell::new(None) };
+}
+
+/// Installs (once per process, after the existing hook) a panic hook that stops the tracker of a
+/// panicking detection or persistence thread, so the other thread, the tray icon and `wait` wind down
+/// instead of running on with half a tracker.
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous(info);
+            let shared = TRACKER_THREAD.try_with(|tracker| tracker.try_borrow().ok().and_then(|tracker| tracker.clone())).ok().flatten();
+            if let Some(shared) = shared {
+                shared.running.store(false, Ordering::SeqCst);
+                log::error!("Tracker thread '{}' panicked; stopping the tracker.", thread::current().name().unwrap_or("unnamed"));
+            }
+        }));
+    });
+}
+
+/// Spawns a tracker thread the panic hook knows to stop the tracker for.
+fn spawn_tracker_thread(name: &str, shared: Arc<SharedState>, run: impl FnOnce() + Send + 'static) -> AppResult<JoinHandle<()>> {
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            TRACKER_THREAD.with(|tracker| *tracker.borrow_mut() = Some(shared));
+            run();
+        })
+        .map_err(|e| AppError::Unexpected(format!("Failed to spawn {} thread: {}", name, e)))
+}
+
+/// Owns the storage backend and tracker state; applies samples as they arrive and
+/// keeps flushing buffered writes even when no samples come in.
+fn run_persistence_loop(
+    mut scope: PersistenceScope,
+    receiver: Receiver<TrackerEvent>,
+    settings: PersistenceSettings,
+    mut auto_sync: Option<AutoSync>,
+    mut scheduled_tasks: ScheduledTasks,
+    clock: Arc<dyn Clock>,
+    shared: Arc<SharedState>,
+) {
+    let PersistenceScope { storage, writers, tracker_state } = &mut scope;
+    let mut metadata = if settings.app_metadata { MetadataCollector::start(storage.as_mut()) } else { None };
+    let mut detection_errors = DetectionErrorLog::new();
+    let mut run = RunLog::start(storage.as_mut(), &settings, clock.now());
+
+    loop {
+        match receiver.recv_timeout(settings.write_flush_interval) {
+            Ok(TrackerEvent::Snapshot { timestamp, windows }) => {
+                match storage.insert_window_snapshot(timestamp, &windows) {
+                    Ok(count) => log::debug!("Recorded context snapshot with {} window(s).", count),
+                    Err(e) => eprintln!("[Persist] Error recording context snapshot: {}", e),
+                }
+            }
+            Ok(TrackerEvent::Suspended { since }) => {
+                if settings.live_output {
+                    println!("[Detected] System suspended; tracking resumes after wake-up.");
+                }
+                tracker_state.suspend(writers, since);
+                run.event(storage.as_mut(), since, RunEventKind::Suspend);
+            }
+            Ok(TrackerEvent::ClockAdjusted { jump, suspended_since }) => {
+                if settings.live_output {
+                    println!("[Detected] System clock jumped {:+}s; interval restarted.", jump.offset_secs);
+                }
+                tracker_state.clock_adjusted(writers, jump, suspended_since);
+                if let Err(e) = storage.record_clock_adjustment(&jump) {
+                    eprintln!("[Persist] Error recording clock adjustment: {}", e);
+                }
+                // A forward jump without a notification is usually an unnotified sleep
+                match suspended_since {
+                    Some(since) => run.event(storage.as_mut(), since, RunEventKind::Suspend),
+                    None if !jump.is_backward() => run.event(storage.as_mut(), jump.before, RunEventKind::ClockJump),
+                    None => {}
+                }
+            }
+            Ok(TrackerEvent::DetectionError { timestamp, message }) => detection_errors.record(timestamp, message),
+            Ok(TrackerEvent::Audio { timestamp, apps, stale_before }) => {
+                let sample = AudioSample { apps: &apps, timestamp, stale_before, device_id: &settings.device_id, user_name: &settings.user_name };
+                if let Err(e) = storage.record_audio(&sample) {
+                    eprintln!("[Persist] Error recording audio intervals: {}", e);
+                }
+            }
+            Ok(TrackerEvent::Break(event)) => {
+                let recorded = match event {
+                    BreakEvent::Reminded { at, active_secs } => storage.record_break_reminder(at, active_secs),
+                    BreakEvent::Taken { reminded_at, break_start } => storage.record_break_taken(reminded_at, break_start),
+                };
+                if let Err(e) = recorded {
+                    eprintln!("[Persist] Error recording break reminder: {}", e);
+                }
+            }
+            Ok(TrackerEvent::RunState { at, kind }) => run.event(storage.as_mut(), at, kind),
+            Ok(TrackerEvent::Sample(sample)) => {
+                if settings.live_output {
+                    log_detection_change(tracker_state, &sample.activity);
+                }
+                if let (Some(collector), Some(info)) = (metadata.as_mut(), sample.activity.as_ref()) {
+                    collector.observe(storage.as_mut(), &info.app_name, sample.timestamp);
+                }
+                tracker_state.update(writers, sample.activity, sample.instant, sample.timestamp);
+                tracker_state.heartbeat(writers, settings.heartbeat_interval, sample.instant, sample.timestamp);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        let flush_start = Instant::now();
+        let flushed = writers.flush_if_due(storage.as_mut(), flush_start);
+        if !matches!(flushed, Ok(0))
+            && let Ok(mut health) = shared.health.lock()
+        {
+            health.record_write(flush_start.elapsed());
+        }
+        if let Err(e) = flushed {
+            // Queue is kept; the next flush retries it
+            eprintln!("[Persist] Error flushing buffered intervals: {}", e);
+        }
+        detection_errors.flush_if_due(storage.as_mut(), settings.write_flush_interval, Instant::now());
+        if let Some(auto_sync) = auto_sync.as_mut() {
+            auto_sync.run_if_due(Instant::now());
+        }
+        scheduled_tasks.run_if_due(storage.as_mut(), &mut writers.own, clock.now());
+        run.heartbeat_if_due(storage.as_mut(), settings.heartbeat_interval, clock.now());
+    }
+
+    // --- Shutdown ---
+    tracker_state.finalize(writers, settings.live_output);
+    if let Err(e) = writers.flush(storage.as_mut()) {
+        eprintln!("[Persist] Error flushing buffered intervals on shutdown: {}", e);
+    }
+    detection_errors.flush(storage.as_mut());
+    run.end(storage.as_mut(), clock.now());
+    if let Err(e) = storage.checkpoint() {
+        eprintln!("[Persist] Warning: checkpoint on shutdown failed: {}", e);
+    }
+}
+
+/// Live logging of target changes
+fn log_detection_change(tracker_state: &TrackerState, activity: &Option<ActivityInfo>) {
+    match activity {
+        Some(info) => {
+            let current_tracked = tracker_state.current_target.as_ref().map(|(t, _, _)| t);
+            // Compare ActivityInfo with TrackedTarget
+            if current_tracked.is_none_or(|t| t.app_name != info.app_name || t.main_title != info.main_title || t.detailed_title != info.detailed_title) {
+                println!("[Detected] App: '{}', MainTitle: '{}', DetailTitle: '{}'", info.app_name, info.main_title, info.detailed_title);
+            }
+        }
+        None => {
+            if tracker_state.current_target.is_some() { println!("[Detected] App: <None>, Titles: <None>"); }
+        }
+    }
+}
+
+// --- Public Tracker API ---
+
+/// Cloneable control handle for a running `Tracker` (Ctrl+C handler, tray menu, GUI...).
+#[derive(Debug, Clone)]
+pub struct TrackerHandle(Arc<SharedState>);
+
+impl TrackerHandle {
+    /// Asks the tracker to stop; `Tracker::wait` returns once the last interval is written.
+    pub fn stop(&self) {
+        self.0.running.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.0.running.load(Ordering::SeqCst)
+    }
+
+    /// Stops recording (closing the open interval) without stopping the tracker.
+    pub fn pause(&self) {
+        self.0.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.0.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.paused.load(Ordering::SeqCst)
+    }
+
+    /// The activity seen by the most recent detection, if any.
+    pub fn current_activity(&self) -> Option<ActivityInfo> {
+        self.0.current_activity.lock().ok().and_then(|current| current.clone())
+    }
+
+    /// Retried, bridged and failed detections since the tracker started.
+    pub fn detection_counters(&self) -> DetectionCounters {
+        DetectionCounters {
+            retries: self.0.detection_retries.load(Ordering::Relaxed),
+            bridged_polls: self.0.bridged_polls.load(Ordering::Relaxed),
+            failed_polls: self.0.failed_polls.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Iterations of the detection loop so far; stops growing when detection hangs.
+    pub fn detection_polls(&self) -> u64 {
+        self.0.detection_polls.load(Ordering::Relaxed)
+    }
+
+    /// Detection and write latencies, missed polls and detection errors of the last polls.
+    pub fn loop_health(&self) -> LoopHealthReport {
+        self.0.health.lock().map(|health| health.report()).unwrap_or_default()
+    }
+}
+
+/// The embeddable tracker core: owns the detection and persistence threads.
+/// `start` returns immediately; `stop` (or `TrackerHandle::stop` followed by `wait`) shuts it down
+/// and finalizes the last interval.
+pub struct Tracker {
+    config: AppConfig,
+    detector: Option<Box<dyn ActivityDetector>>,
+    live_output: bool,
+    event_tap: Option<EventTap>,
+    clock: Arc<dyn Clock>,
+    shared: Arc<SharedState>,
+    threads: Option<(JoinHandle<()>, JoinHandle<()>)>,
+    // Network jobs and servers; created by `start`, shut down when the tracker is dropped
+    network: Option<NetworkRuntime>,
+    // Connections for `query`, opened on first use (the persistence thread keeps its own writer)
+    readers: OnceLock<StoragePool>,
+}
+
+impl Tracker {
+    /// Creates a tracker using the detectors from `detection::create_detector` (OS detector plus plugins).
+    pub fn new(config: AppConfig) -> Self {
+        Tracker { config, detector: None, live_output: true, event_tap: None, clock: clock::system_clock(), shared: Arc::new(SharedState::default()), threads: None, network: None, readers: OnceLock::new() }
+    }
+
+    /// Creates a tracker driven by a caller-supplied detector.
+    pub fn with_detector(config: AppConfig, detector: Box<dyn ActivityDetector>) -> Self {
+        let mut tracker = Tracker::new(config);
+        tracker.detector = Some(detector);
+        tracker
+    }
+
+    /// Enables/disables printing detected target changes to stdout (on by default).
+    pub fn set_live_output(&mut self, enabled: bool) {
+        self.live_output = enabled;
+    }
+
+    /// Streams interval start/end events as JSON lines (`track --emit-events`). Takes effect on `start`.
+    pub fn set_event_tap(&mut self, tap: EventTap) {
+        self.event_tap = Some(tap);
+    }
+
+    /// Replaces the system clock for interval times, clock-jump checks, min_interval/heartbeat
+    /// durations and aggregation cutoffs (simulations and tests). Takes effect on `start`.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+        self.readers = OnceLock::new();
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.threads.is_some() && self.shared.running.load(Ordering::SeqCst)
+    }
+
+    pub fn handle(&self) -> TrackerHandle {
+        TrackerHandle(self.shared.clone())
+    }
+
+    /// The runtime network work of this tracker runs on (e.g. to serve the gRPC API); None before `start`.
+    pub fn network(&self) -> Option<NetworkHandle> {
+        self.network.as_ref().map(NetworkRuntime::handle)
+    }
+
+    /// Prepares the database (schema, dangling intervals, aggregation) and spawns the tracking threads.
+    pub fn start(&mut self) -> AppResult<()> {
+        if self.threads.is_some() {
+            return Err(AppError::Unexpected("Tracker is already running".to_string()));
+        }
+        let detector = match self.detector.take() {
+            Some(detector) => detector,
+            None => detection::create_detector(&self.config)?,
+        };
+        let detector: Box<dyn ActivityDetector> = match &self.config.shell_hook {
+            Some(shell_hook) => Box::new(detection::ShellHookDetector::wrap(detector, shell_hook)),
+            None => detector,
+        };
+
+        let app_config = &self.config;
+        let polling = PollingSettings {
+            check_interval: app_config.check_interval,
+            adaptive_polling: app_config.adaptive_polling,
+            idle_poll_interval: app_config.idle_poll_interval,
+            idle_threshold: app_config.idle_threshold,
+            snapshot_interval: app_config.snapshot_interval,
+            audio_interval: app_config.audio_tracking.then(|| AUDIO_POLL_INTERVAL.max(app_config.check_interval)),
+            detection_retries: app_config.detection_retries,
+            detection_grace: app_config.detection_grace,
+            tracking_hours: app_config.tracking_hours.clone(),
+            timezone: app_config.timezone.clone(),
+            clock: self.clock.clone(),
+            live_output: self.live_output,
+        };
+        let tagging = ActivityTagging {
+            passive: PassiveHeuristic::new(&app_config.passive_apps),
+            repos: app_config.repo_detection.as_ref().map(RepoResolver::new),
+            tickets: app_config.ticket_detection.as_ref().map(TicketExtractor::new),
+            documents: app_config.document_detection.clone(),
+            remote: RemoteSessionTagger::new(),
+            titles: app_config.title_rules.clone(),
+            executable_path: app_config.record_executable_path,
+            command_line: app_config.record_command_line,
+            pseudonyms: app_config.pseudonymize.as_ref().map(|_| Pseudonymizer::load_or_create(app_config)).transpose()?,
+        };
+        let settings = PersistenceSettings {
+            heartbeat_interval: app_config.heartbeat_interval,
+            write_flush_interval: app_config.write_flush_interval,
+            device_id: app_config.device_id.clone(),
+            user_name: app_config.user_name.clone(),
+            min_interval: app_config.min_interval,
+            short_switches: app_config.short_switch_mode,
+            live_output: self.live_output,
+            profile_rules: app_config.profile_rules.clone(),
+            timezone: app_config.timezone.clone(),
+            app_metadata: app_config.pseudonymize.is_none(),
+        };
+
+        let mut storage = persistence::open_storage_with_clock(app_config, self.clock.clone())?;
+        storage.initialize()?;
+
+        let startup_timestamp = self.clock.now();
+        storage.finalize_dangling_intervals(startup_timestamp, app_config.dangling_threshold_secs, &app_config.user_name)?;
+        // Audio intervals a previous run left open end at their last heartbeat
+        let leftover_audio = AudioSample {
+            apps: &[],
+            timestamp: startup_timestamp,
+            stale_before: startup_timestamp,
+            device_id: &app_config.device_id,
+            user_name: &app_config.user_name,
+        };
+        storage.record_audio(&leftover_audio)?;
+        let network = match self.network.take() {
+            Some(network) => network,
+            None => NetworkRuntime::start()?,
+        };
+        let auto_sync = AutoSync::from_config(app_config, &network.handle());
+        if auto_sync.is_some() {
+            // Aggregation deletes raw intervals, so upload them first
+            if let Err(e) = crate::sync::push(app_config, storage.as_mut()) {
+                eprintln!("Warning: sync before aggregation failed: {}", e);
+            }
+        }
+        if app_config.calendar.is_some() {
+            // Intervals since the last run are matched against the events during aggregation
+            match crate::calendar::refresh(app_config, storage.as_mut(), startup_timestamp, 1) {
+                Ok(count) => log::info!("Read {} calendar event(s).", count),
+                Err(e) => eprintln!("Warning: reading the calendar failed: {}", e),
+            }
+        }
+        storage.aggregate_and_cleanup(app_config.merge_gap_secs)?;
+        let writers = ProfileWriters::new(&settings, self.open_profile_storages(startup_timestamp)?, self.event_tap.take());
+        let scheduled_tasks = ScheduledTasks::from_config(app_config, &network.handle(), startup_timestamp);
+        self.network = Some(network);
+
+        // Notifications run on their own threads, which end when the detection thread drops the senders
+        let observers = PollObservers {
+            webhook: WebhookNotifier::spawn(app_config),
+            mqtt: MqttPublisher::start(app_config),
+            breaks: BreakReminder::from_config(app_config.break_reminder.as_ref()),
+        };
+
+        self.shared.running.store(true, Ordering::SeqCst);
+        let (sender, receiver) = mpsc::channel::<TrackerEvent>();
+
+        install_panic_hook();
+        let persistence_clock = self.clock.clone();
+        let persistence_shared = self.shared.clone();
+        let scope = PersistenceScope::new(storage, writers, &settings, self.clock.clone());
+        let persistence_handle = spawn_tracker_thread("persistence", self.shared.clone(), move || {
+            run_persistence_loop(scope, receiver, settings, auto_sync, scheduled_tasks, persistence_clock, persistence_shared)
+        })?;
+
+        let detection_shared = self.shared.clone();
+        let detection_handle = spawn_tracker_thread("detection", self.shared.clone(), move || {
+            run_detection_loop(detector, polling, tagging, detection_shared, sender, observers)
+        })?;
+
+        self.threads = Some((detection_handle, persistence_handle));
+        Ok(())
+    }
+
+    /// Opens the databases of the profiles `profile_rules` can record into, each with its own config.
+    fn open_profile_storages(&self, startup_timestamp: i64) -> AppResult<Vec<(String, Box<dyn Storage>)>> {
+        let mut storages = Vec::new();
+        for profile in self.config.profile_rules.target_profiles() {
+            let profile_config = config::load_profile_configuration(Some(profile))?;
+            let mut storage = persistence::open_storage_with_clock(&profile_config, self.clock.clone())?;
+            storage.initialize()?;
+            storage.finalize_dangling_intervals(startup_timestamp, profile_config.dangling_threshold_secs, &profile_config.user_name)?;
+            log::info!("profile_rules can record into profile '{}' ({}).", profile, persistence::describe_location(&profile_config));
+            storages.push((profile.to_string(), storage));
+        }
+        Ok(storages)
+    }
+
+    /// Signals the threads to stop and waits for the last interval to be written.
+    pub fn stop(&mut self) -> AppResult<()> {
+        self.handle().stop();
+        self.wait()
+    }
+
+    /// Blocks until the tracker has been stopped through a `TrackerHandle`.
+    pub fn wait(&mut self) -> AppResult<()> {
+        let Some((detection_handle, persistence_handle)) = self.threads.take() else {
+            return Ok(());
+        };
+        // The detection thread returns once `running` is cleared; dropping its sender
+        // lets the persistence thread finalize the last interval and exit.
+        let detection_result = detection_handle.join();
+        let persistence_result = persistence_handle.join();
+
+        if detection_result.is_err() {
+            return Err(AppError::Unexpected("Detection thread panicked".to_string()));
+        }
+        if persistence_result.is_err() {
+            return Err(AppError::Unexpected("Persistence thread panicked".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Queries usage statistics on a pooled read connection; safe while tracking is running.
+    pub fn query(&self, period: TimePeriod, level: AggregationLevel, filter: &StatsFilter) -> AppResult<AggregatedResult> {
+        let readers = self.readers.get_or_init(|| StoragePool::new(&self.config, self.clock.clone()));
+        readers.get()?.query_stats(period, level, filter)
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        if self.threads.is_some()
+            && let Err(e) = self.stop() {
+            log::error!("Error stopping tracker on drop: {}", e);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// src/tracking_hours.rs
+// `tracking_hours` in config.json: the local days and times the tracker records (e.g. weekdays
+// 08:00-18:00). Outside them the detection loop behaves as if paused: nothing is detected or written.
+
+use crate::timezone::ReportTimeZone;
+use crate::utils::{parse_time_of_day, parse_weekdays};
+use serde::Deserialize;
+
+/// One entry of `tracking_hours`; the tracker records while any entry matches.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrackingWindowConfig {
+    /// "mon".."sun", "weekdays" or "weekend" (empty = every day)
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Local "HH:MM" start of the time window (with `to`; a window past midnight wraps, e.g. 22:00-02:00)
+    pub from: Option<String>,
+    /// Local "HH:MM" end of the time window (exclusive)
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct TrackingWindow {
+    // Indexed by weekday, Monday first
+    days: [bool; 7],
+    // Minutes of the day [from, to)
+    window: Option<(u32, u32)>,
+    // The entry as written, for `status`
+    label: String,
+}
+
+impl TrackingWindow {
+    fn matches(&self, weekday: u8, minute: u32) -> bool {
+        let in_window = match self.window {
+            None => true,
+            Some((from, to)) if from < to => minute >= from && minute < to,
+            Some((from, to)) => minute >= from || minute < to,
+        };
+        self.days[usize::from(weekday)] && in_window
+    }
+}
+
+/// The parsed windows; empty records at any time.
+#[derive(Debug, Clone, Default)]
+pub struct TrackingHours {
+    windows: Vec<TrackingWindow>,
+}
+
+impl TrackingHours {
+    pub fn parse(windows: &[TrackingWindowConfig]) -> Result<Self, String> {
+        let mut parsed = Vec::with_capacity(windows.len());
+        for (index, entry) in windows.iter().enumerate() {
+            let context = |message: String| format!("tracking_hours[{}]: {}", index, message);
+            let window = match (&entry.from, &entry.to) {
+                (None, None) => None,
+                (Some(from), Some(to)) => {
+                    let window = (parse_time_of_day(from).map_err(context)?, parse_time_of_day(to).map_err(context)?);
+                    if window.0 == window.1 {
+                        return Err(context("\"from\" and \"to\" are the same time".to_string()));
+                    }
+                    Some(window)
+                }
+                _ => return Err(context("set both \"from\" and \"to\", or neither".to_string())),
+            };
+            let days = if entry.days.is_empty() { "every day".to_string() } else { entry.days.join(", ") };
+            let label = match (&entry.from, &entry.to) {
+                (Some(from), Some(to)) => format!("{} {}-{}", days, from.trim(), to.trim()),
+                _ => days,
+            };
+            parsed.push(TrackingWindow { days: parse_weekdays(&entry.days).map_err(context)?, window, label });
+        }
+        Ok(TrackingHours { windows: parsed })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Whether the tracker records at `timestamp`.
+    pub fn is_recording(&self, timestamp: i64, timezone: &ReportTimeZone) -> bool {
+        if self.windows.is_empty() {
+            return true;
+        }
+        let (weekday, minute) = timezone.weekday_and_minute(timestamp);
+        self.windows.iter().any(|window| window.matches(weekday, minute))
+    }
+
+    /// "weekdays 08:00-18:00; sat 10:00-12:00"
+    pub fn describe(&self) -> String {
+        self.windows.iter().map(|window| window.label.as_str()).collect::<Vec<_>>().join("; ")
+    }
+}
@@ -0,0 +1,251 @@
+// src/tray.rs
+#![cfg(target_os = "windows")] // Only compile this file on Windows
+
+use crate::errors::{AppError, AppResult};
+use crate::tracker::TrackerHandle;
+use std::cell::RefCell;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Shell::{
+    Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NIM_MODIFY, NOTIFYICONDATAW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    AppendMenuW, CreatePopupMenu, CreateWindowExW, DefWindowProcW, DestroyMenu, DestroyWindow,
+    DispatchMessageW, GetCursorPos, GetMessageW, KillTimer, LoadIconW, PostQuitMessage, RegisterClassW,
+    SetForegroundWindow, SetTimer, TrackPopupMenu, TranslateMessage, HWND_MESSAGE, IDI_APPLICATION,
+    MF_SEPARATOR, MF_STRING, MSG, TPM_BOTTOMALIGN, TPM_RIGHTBUTTON, WINDOW_EX_STYLE, WINDOW_STYLE,
+    WM_APP, WM_COMMAND, WM_DESTROY, WM_LBUTTONUP, WM_RBUTTONUP, WM_TIMER, WNDCLASSW,
+};
+use log::{debug, warn};
+
+const WM_TRAY_CALLBACK: u32 = WM_APP + 1;
+const TRAY_ICON_ID: u32 = 1;
+const REFRESH_TIMER_ID: usize = 1;
+const REFRESH_INTERVAL_MS: u32 = 1000;
+
+const MENU_PAUSE_RESUME: usize = 100;
+const MENU_OPEN_STATS: usize = 101;
+const MENU_QUIT: usize = 102;
+
+// The window procedure is a plain extern fn, so tray state lives in a thread-local
+// owned by the thread running the message loop.
+struct TrayState {
+    handle: TrackerHandle,
+    app_name: String,
+    last_tip: String,
+}
+
+thread_local! {
+    static TRAY_STATE: RefCell<Option<TrayState>> = const { RefCell::new(None) };
+}
+
+/// Shows the tray icon and runs the message loop on the current thread until the user
+/// picks Quit or the tracker is stopped elsewhere (e.g. Ctrl+C).
+pub fn run_tray(handle: TrackerHandle, app_name: &str) -> AppResult<()> {
+    unsafe {
+        let instance = GetModuleHandleW(None)
+            .map_err(|e| AppError::Platform(format!("GetModuleHandleW failed: {}", e)))?;
+        let class_name = w!("MouseTrackingTrayWindow");
+        let wnd_class = WNDCLASSW {
+            lpfnWndProc: Some(tray_window_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&wnd_class) == 0 {
+            return Err(AppError::Platform(format!("RegisterClassW failed: {:?}", windows::core::Error::from_win32())));
+        }
+
+        // Message-only window: never shown, only receives tray callbacks and timer ticks
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            w!("Mouse Tracking"),
+            WINDOW_STYLE::default(),
+            0, 0, 0, 0,
+            Some(HWND_MESSAGE),
+            None,
+            Some(instance.into()),
+            None,
+        )
+        .map_err(|e| AppError::Platform(format!("CreateWindowExW failed: {}", e)))?;
+
+        TRAY_STATE.with(|state| {
+            *state.borrow_mut() = Some(TrayState { handle, app_name: app_name.to_string(), last_tip: String::new() });
+        });
+
+        let mut icon_data = notify_icon_data(hwnd);
+        icon_data.uFlags = NIF_ICON | NIF_MESSAGE | NIF_TIP;
+        icon_data.uCallbackMessage = WM_TRAY_CALLBACK;
+        icon_data.hIcon = LoadIconW(None, IDI_APPLICATION)
+            .map_err(|e| AppError::Platform(format!("LoadIconW failed: {}", e)))?;
+        copy_to_wide_buf(&mut icon_data.szTip, app_name);
+        if !Shell_NotifyIconW(NIM_ADD, &icon_data).as_bool() {
+            let _ = DestroyWindow(hwnd);
+            return Err(AppError::Platform("Shell_NotifyIconW(NIM_ADD) failed".to_string()));
+        }
+        SetTimer(Some(hwnd), REFRESH_TIMER_ID, REFRESH_INTERVAL_MS, None);
+        debug!("Tray icon created.");
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        TRAY_STATE.with(|state| state.borrow_mut().take());
+        debug!("Tray message loop finished.");
+        Ok(())
+    }
+}
+
+extern "system" fn tray_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe {
+        match msg {
+            WM_TRAY_CALLBACK => {
+                let event = lparam.0 as u32;
+                if event == WM_RBUTTONUP || event == WM_LBUTTONUP {
+                    show_context_menu(hwnd);
+                }
+                LRESULT(0)
+            }
+            WM_COMMAND => {
+                handle_menu_command(hwnd, wparam.0 & 0xFFFF);
+                LRESULT(0)
+            }
+            WM_TIMER => {
+                refresh_tray(hwnd);
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                let _ = KillTimer(Some(hwnd), REFRESH_TIMER_ID);
+                let _ = Shell_NotifyIconW(NIM_DELETE, &notify_icon_data(hwnd));
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+}
+
+/// Updates the tooltip with the current activity and closes the tray once the tracker stops.
+unsafe fn refresh_tray(hwnd: HWND) {
+    let tip = TRAY_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let state = state.as_mut()?;
+        if !state.handle.is_running() {
+            return Some(None);
+        }
+        let status = if state.handle.is_paused() {
+            "Paused".to_string()
+        } else {
+            match state.handle.current_activity() {
+                Some(activity) => format!("Tracking: {}", activity.app_name),
+                None => "Tracking: <nothing>".to_string(),
+            }
+        };
+        let tip = format!("{}\n{}", state.app_name, status);
+        if tip == state.last_tip {
+            return None;
+        }
+        state.last_tip = tip.clone();
+        Some(Some(tip))
+    });
+
+    match tip {
+        Some(None) => {
+            // Stopped elsewhere (Ctrl+C): tear down the icon and end the message loop
+            let _ = unsafe { DestroyWindow(hwnd) };
+        }
+        Some(Some(tip)) => {
+            let mut icon_data = notify_icon_data(hwnd);
+            icon_data.uFlags = NIF_TIP;
+            copy_to_wide_buf(&mut icon_data.szTip, &tip);
+            let _ = unsafe { Shell_NotifyIconW(NIM_MODIFY, &icon_data) };
+        }
+        None => {}
+    }
+}
+
+unsafe fn show_context_menu(hwnd: HWND) {
+    let paused = TRAY_STATE.with(|state| state.borrow().as_ref().is_some_and(|s| s.handle.is_paused()));
+    unsafe {
+        let Ok(menu) = CreatePopupMenu() else {
+            warn!("CreatePopupMenu failed.");
+            return;
+        };
+        let pause_label = if paused { w!("Resume") } else { w!("Pause") };
+        let _ = AppendMenuW(menu, MF_STRING, MENU_PAUSE_RESUME, pause_label);
+        let _ = AppendMenuW(menu, MF_STRING, MENU_OPEN_STATS, w!("Open Stats"));
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null());
+        let _ = AppendMenuW(menu, MF_STRING, MENU_QUIT, w!("Quit"));
+
+        let mut point = POINT::default();
+        let _ = GetCursorPos(&mut point);
+        // Required so the menu closes when the user clicks elsewhere
+        let _ = SetForegroundWindow(hwnd);
+        let _ = TrackPopupMenu(menu, TPM_RIGHTBUTTON | TPM_BOTTOMALIGN, point.x, point.y, None, hwnd, None);
+        let _ = DestroyMenu(menu);
+    }
+}
+
+unsafe fn handle_menu_command(hwnd: HWND, command_id: usize) {
+    let Some(handle) = TRAY_STATE.with(|state| state.borrow().as_ref().map(|s| s.handle.clone())) else {
+        return;
+    };
+    match command_id {
+        MENU_PAUSE_RESUME => {
+            if handle.is_paused() {
+                println!("[Tray] Resuming tracking.");
+                handle.resume();
+            } else {
+                println!("[Tray] Pausing tracking.");
+                handle.pause();
+            }
+            unsafe { refresh_tray(hwnd) };
+        }
+        MENU_OPEN_STATS => open_stats_console(),
+        MENU_QUIT => {
+            println!("[Tray] Quit selected. Shutting down tracker...");
+            handle.stop();
+            let _ = unsafe { DestroyWindow(hwnd) };
+        }
+        _ => {}
+    }
+}
+
+/// Opens a new console window running `stats` with the current executable.
+fn open_stats_console() {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            warn!("Could not determine current executable: {}", e);
+            return;
+        }
+    };
+    let result = std::process::Command::new("cmd")
+        .args(["/C", "start", "", "cmd", "/K"])
+        .arg(exe)
+        .arg("stats")
+        .spawn();
+    if let Err(e) = result {
+        warn!("Failed to open stats console: {}", e);
+    }
+}
+
+fn notify_icon_data(hwnd: HWND) -> NOTIFYICONDATAW {
+    NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: hwnd,
+        uID: TRAY_ICON_ID,
+        ..Default::default()
+    }
+}
+
+/// Copies `text` into a fixed-size, NUL-terminated UTF-16 buffer, truncating if needed.
+fn copy_to_wide_buf(buf: &mut [u16], text: &str) {
+    let wide: Vec<u16> = text.encode_utf16().take(buf.len() - 1).collect();
+    buf[..wide.len()].copy_from_slice(&wide);
+    buf[wide.len()] = 0;
+}
@@ -1,5 +1,6 @@
 use crate::errors::AppError; // Assuming AppError is defined elsewhere
 use clap::ValueEnum; // Needed for CLI integration
+use serde::Deserialize;
 use std::fmt;
 
 // --- Enums for Control Flow ---
@@ -12,6 +13,39 @@ pub enum AggregationLevel {
     /// Show usage time for each application and window title combination
     #[value(name = "detailed")]
     Detailed,
+    /// Aggregate usage time by virtual desktop (Windows)
+    #[value(name = "desktop")]
+    ByDesktop,
+    /// Aggregate usage time by the device (computer) it was recorded on
+    #[value(name = "device")]
+    ByDevice,
+    /// Aggregate coding time by the Git repository it was spent in (`repo_detection`)
+    #[value(name = "repo")]
+    ByRepo,
+    /// Aggregate usage time by the full path of the executable (`record_executable_path`)
+    #[value(name = "path")]
+    ByPath,
+    /// Aggregate usage time by the calendar event it overlapped (`calendar`)
+    #[value(name = "meetings")]
+    ByMeeting,
+    /// Aggregate usage time by the issue key in the window titles (`ticket_detection`)
+    #[value(name = "ticket")]
+    ByTicket,
+    /// Aggregate usage time by the OS user it was recorded for (shared databases)
+    #[value(name = "user")]
+    ByUser,
+    /// Aggregate usage time by the file open in an editor or office app (`document_detection`)
+    #[value(name = "document")]
+    ByDocument,
+    /// Remote desktop time by session type and the remote machine of client windows (RDP, Citrix, VNC)
+    #[value(name = "remote")]
+    ByRemote,
+    /// App switches per hour and focus sessions (runs on one app) instead of durations
+    #[value(name = "focus")]
+    Focus,
+    /// Tracked time per local hour of the day (24-slot histogram), from `hourly_summary`
+    #[value(name = "profile")]
+    Profile,
 }
 
 // Implement Display for better printing in headers etc.
@@ -20,19 +54,50 @@ impl fmt::Display for AggregationLevel {
         match self {
             AggregationLevel::ByApplication => write!(f, "By Application"),
             AggregationLevel::Detailed => write!(f, "Detailed (App + Title)"),
+            AggregationLevel::ByDesktop => write!(f, "By Virtual Desktop"),
+            AggregationLevel::ByDevice => write!(f, "By Device"),
+            AggregationLevel::ByRepo => write!(f, "By Git Repository"),
+            AggregationLevel::ByPath => write!(f, "By Executable Path"),
+            AggregationLevel::ByMeeting => write!(f, "By Meeting"),
+            AggregationLevel::ByTicket => write!(f, "By Ticket"),
+            AggregationLevel::ByUser => write!(f, "By User"),
+            AggregationLevel::ByDocument => write!(f, "By Document"),
+            AggregationLevel::ByRemote => write!(f, "By Remote Session"),
+            AggregationLevel::Focus => write!(f, "Focus"),
+            AggregationLevel::Profile => write!(f, "Time of Day"),
         }
     }
 }
 
+/// Which interval stream `stats` reads
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntervalStream {
+    /// The foreground app and window (the main stream)
+    #[default]
+    #[value(name = "foreground")]
+    Foreground,
+    /// Apps playing audio, recorded in parallel with `audio_tracking` (time per app only)
+    #[value(name = "audio")]
+    Audio,
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimePeriod {
+    #[value(name = "today")]
     Today,
+    #[value(name = "last-hour")]
     LastCompletedHour,
+    #[value(name = "current-hour")]
     CurrentHour,
+    #[value(name = "this-week")]
+    ThisWeek,
+    #[value(name = "last-week")]
+    LastWeek,
+    /// The current week and the 11 before it
+    #[value(name = "last-12-weeks")]
+    Last12Weeks,
     // Future ideas:
     // Yesterday,
-    // ThisWeek,
     // Last7Days,
     // SpecificDate(chrono::NaiveDate),
     // DateRange(i64, i64), // Using timestamps
@@ -45,10 +110,21 @@ impl fmt::Display for TimePeriod {
              TimePeriod::Today => write!(f, "Today"),
              TimePeriod::LastCompletedHour => write!(f, "Last Completed Hour"),
              TimePeriod::CurrentHour => write!(f, "Current Hour (Approx)"),
+             TimePeriod::ThisWeek => write!(f, "This Week"),
+             TimePeriod::LastWeek => write!(f, "Last Week"),
+             TimePeriod::Last12Weeks => write!(f, "Last 12 Weeks"),
          }
      }
  }
 
+impl TimePeriod {
+    /// True when the period starts and ends on local day starts, so day (and week) summary rows
+    /// fall entirely inside or outside it; hour periods read hourly summaries instead.
+    pub fn spans_whole_days(self) -> bool {
+        !matches!(self, TimePeriod::LastCompletedHour | TimePeriod::CurrentHour)
+    }
+}
+
 
 // --- Structs for Data Representation ---
 
@@ -60,6 +136,352 @@ pub struct DetailedUsageRecord {
     pub total_duration_secs: i64,
 }
 
+/// A visible top-level window, as recorded in context snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub app_name: String,
+    pub window_title: String,
+}
+
+/// A stored title rewritten by `normalize --apply-history`, in every table that holds it for `app_name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleRename {
+    pub app_name: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// What `purge` deletes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeScope {
+    /// Every row of every table
+    All,
+    /// One app's rows whose interval, snapshot, hour, day or week starts in [from, to) (None = unbounded).
+    /// Without a range its metadata and the `audit_log` entries naming it go too.
+    App { app_name: String, from: Option<i64>, to: Option<i64> },
+}
+
+/// The apps playing audio at one poll of the parallel audio stream (`audio_tracking`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioSample<'a> {
+    /// Empty when nothing plays, and when tracking pauses or stops (ends every open audio interval)
+    pub apps: &'a [String],
+    pub timestamp: i64,
+    /// Open audio intervals last seen before this time belong to polls that stopped (crash, sleep);
+    /// they end when they were last seen instead of at `timestamp`
+    pub stale_before: i64,
+    pub device_id: &'a str,
+    pub user_name: &'a str,
+}
+
+/// One run of `track` on this database (`tracker_runs`), for `gaps`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackerRun {
+    pub id: i64,
+    pub start_time: i64,
+    /// Last heartbeat of the run; a run that crashed ends here
+    pub last_seen: i64,
+    /// None while it runs, and after a crash or power loss
+    pub end_time: Option<i64>,
+}
+
+impl TrackerRun {
+    /// When the run stopped (or was last seen alive)
+    pub fn effective_end(&self) -> i64 {
+        self.end_time.unwrap_or(self.last_seen)
+    }
+}
+
+/// Why a running tracker recorded nothing for a while (`tracker_run_events`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunEventKind {
+    /// The system went to sleep
+    Suspend,
+    /// The wall clock jumped forward without a sleep notification (usually an unnotified sleep)
+    ClockJump,
+    Pause,
+    Resume,
+    /// Left the windows of `tracking_hours`
+    OffHoursStart,
+    OffHoursEnd,
+}
+
+impl RunEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunEventKind::Suspend => "suspend",
+            RunEventKind::ClockJump => "clock_jump",
+            RunEventKind::Pause => "pause",
+            RunEventKind::Resume => "resume",
+            RunEventKind::OffHoursStart => "off_hours_start",
+            RunEventKind::OffHoursEnd => "off_hours_end",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        [RunEventKind::Suspend, RunEventKind::ClockJump, RunEventKind::Pause, RunEventKind::Resume, RunEventKind::OffHoursStart, RunEventKind::OffHoursEnd]
+            .into_iter()
+            .find(|candidate| candidate.as_str() == kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunEvent {
+    pub run_id: i64,
+    pub at: i64,
+    pub kind: RunEventKind,
+}
+
+/// The runs of one user overlapping a range and all their events, oldest first (`gaps`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackerHistory {
+    pub runs: Vec<TrackerRun>,
+    pub events: Vec<RunEvent>,
+    /// Start of the user's first recorded run (None = runs were never recorded, e.g. older versions)
+    pub first_run_start: Option<i64>,
+}
+
+/// A new open interval as written to `app_intervals`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewInterval<'a> {
+    pub app_name: &'a str,
+    pub main_title: &'a str,
+    pub detailed_title: &'a str,
+    pub virtual_desktop: Option<&'a str>,
+    /// Git repository the activity was in (`repo_detection`)
+    pub repo: Option<&'a str>,
+    /// Full path of the app's executable (`record_executable_path`)
+    pub executable_path: Option<&'a str>,
+    /// Command line of the app's process (`record_command_line`)
+    pub command_line: Option<&'a str>,
+    /// Issue key found in the window titles (`ticket_detection`)
+    pub ticket: Option<&'a str>,
+    pub passive: bool,
+    pub start_time: i64,
+    /// Machine the interval was recorded on (`device_id` in config.json, default: hostname)
+    pub device_id: &'a str,
+    /// OS user the tracker runs as (`user_name` in config.json, default: the login name)
+    pub user_name: &'a str,
+    /// Remote desktop involved ("rdp", "citrix", "rdp-client", ...; None = local), see `detection::remote`
+    pub session_type: Option<&'a str>,
+    /// Machine a remote desktop client window was connected to, read from its title
+    pub remote_host: Option<&'a str>,
+    /// File open in an editor or office app, read from the title (`document_detection`)
+    pub document: Option<&'a str>,
+}
+
+/// A raw interval row (sync uploads, exports, `intervals` commands)
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct IntervalRecord {
+    /// Local row id; unique per database and used by the server to ignore re-sent rows
+    pub id: i64,
+    pub app_name: String,
+    pub main_title: Option<String>,
+    pub detailed_title: Option<String>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub virtual_desktop: Option<String>,
+    pub passive: bool,
+    pub device_id: Option<String>,
+    pub repo: Option<String>,
+    pub executable_path: Option<String>,
+    pub command_line: Option<String>,
+    pub ticket: Option<String>,
+    pub user_name: Option<String>,
+    pub session_type: Option<String>,
+    pub remote_host: Option<String>,
+    pub document: Option<String>,
+}
+
+/// Where an app's friendly name came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppMetadataSource {
+    /// Set with `app alias set`
+    User,
+    /// Read from the executable's version resource (Windows)
+    VersionInfo,
+}
+
+impl AppMetadataSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AppMetadataSource::User => "user",
+            AppMetadataSource::VersionInfo => "version_info",
+        }
+    }
+
+    /// Unknown values are treated as user-set so they are never overwritten
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "version_info" => AppMetadataSource::VersionInfo,
+            _ => AppMetadataSource::User,
+        }
+    }
+}
+
+/// Friendly name and icon of an executable, stored in the `app_metadata` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppMetadata {
+    pub app_name: String,
+    pub display_name: Option<String>,
+    pub icon_path: Option<String>,
+    pub source: AppMetadataSource,
+}
+
+/// One day of the tracker's own statistics (`tracker_daily_stats`, shown by `self-stats`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackerDailyStats {
+    /// Start of the local day
+    pub day_timestamp: i64,
+    pub interval_count: i64,
+    /// Longest run of intervals without a break that started this day
+    pub longest_session_secs: i64,
+    pub detection_errors: i64,
+    pub last_error: Option<String>,
+}
+
+/// Progress of `sync push`, stored in the `sync_state` table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncState {
+    /// Highest interval id already accepted by the server (the watermark)
+    pub last_interval_id: i64,
+    /// When the last successful push finished
+    pub last_sync_time: Option<i64>,
+    /// Number of intervals uploaded by that push
+    pub last_sync_count: i64,
+}
+
+/// How fragmented the tracked time of a period was (`stats --level focus`, see `focus::compute`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusMetrics {
+    /// Time in raw intervals, locked time excluded
+    pub tracked_secs: i64,
+    /// Times the foreground app changed
+    pub switches: i64,
+    /// Runs on one app; a run survives gaps up to `focus::SESSION_GAP_SECS`
+    pub sessions: i64,
+    /// Summed length of the sessions (includes their short gaps)
+    pub session_secs: i64,
+    /// Longest session: (app, start, end)
+    pub longest_session: Option<(String, i64, i64)>,
+    /// (local hour start, switches in that hour) for every hour in which intervals start, oldest first
+    pub switches_by_hour: Vec<(i64, i64)>,
+}
+
+impl FocusMetrics {
+    /// Switches per hour of tracked time
+    pub fn switches_per_hour(&self) -> f64 {
+        if self.tracked_secs == 0 { 0.0 } else { self.switches as f64 * 3600.0 / self.tracked_secs as f64 }
+    }
+
+    pub fn average_session_secs(&self) -> i64 {
+        if self.sessions == 0 { 0 } else { self.session_secs / self.sessions }
+    }
+}
+
+/// Tracked time per local hour of the day over a period (`stats --level profile`, see `hour_profile::build`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeOfDayProfile {
+    /// Seconds per local hour (0-23) on Monday to Friday
+    pub weekday_secs: [i64; 24],
+    /// Seconds per local hour on Saturday and Sunday
+    pub weekend_secs: [i64; 24],
+    /// Days of each kind in the period up to now, for per-day averages
+    pub weekdays: i64,
+    pub weekend_days: i64,
+}
+
+impl TimeOfDayProfile {
+    pub fn total_secs(&self) -> i64 {
+        self.weekday_secs.iter().chain(&self.weekend_secs).sum()
+    }
+}
+
+/// A daily goal kept as a streak (`habit add`), stored in the `habits` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Habit {
+    pub name: String,
+    /// Case-insensitive glob matched against the app name (e.g. "code*")
+    pub app_glob: String,
+    /// A day counts once matching apps reach this many minutes
+    pub daily_minutes: i64,
+    pub created_at: i64,
+}
+
+/// Where a habit stands today (see `habits::evaluate`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HabitStatus {
+    pub habit: Habit,
+    /// Time on matching apps today so far
+    pub today_secs: i64,
+    /// Days in a row the goal was met, ending today or, while today is not met yet, yesterday
+    pub current_streak: i64,
+    pub longest_streak: i64,
+}
+
+impl HabitStatus {
+    pub fn met_today(&self) -> bool {
+        self.today_secs >= self.habit.daily_minutes * 60
+    }
+}
+
+/// One occurrence of a calendar event (`calendar`), stored in the `calendar_events` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub title: String,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// App switching within a period, from raw intervals (`screentime`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SwitchStats {
+    /// Times the foreground app changed ("pickups"); locked time is not an app
+    pub switches: i64,
+    /// Longest uninterrupted stretch on one app: (app, start, end), clipped to the period
+    pub longest_streak: Option<(String, i64, i64)>,
+}
+
+/// Optional filters applied inside the stats SQL (see `persistence::query_stats`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsFilter {
+    /// Keep only the N entries with the highest duration
+    pub top: Option<usize>,
+    /// Case-insensitive glob matched against the application name (e.g. "chrome*")
+    pub app_glob: Option<String>,
+    /// Case-insensitive substring the window title must contain
+    pub title_contains: Option<String>,
+    /// Drop entries whose total duration is below this many seconds
+    pub min_duration_secs: Option<i64>,
+    /// Leave out time tagged as passive (full-screen media/meetings)
+    pub exclude_passive: bool,
+    /// Only time recorded for this OS user (shared databases)
+    pub user: Option<String>,
+    /// The foreground intervals, or the parallel audio stream (`--stream audio`)
+    pub stream: IntervalStream,
+}
+
+impl StatsFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == StatsFilter::default()
+    }
+}
+
+/// How stats tables are rendered in the terminal
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Cut window titles to this many terminal columns (None = full titles)
+    pub max_title_width: Option<usize>,
+    /// Colors and duration bars (see `theme::color_enabled`)
+    pub color: bool,
+    pub duration_format: DurationFormat,
+    /// `--level profile`: weekdays and weekends side by side instead of one histogram
+    pub split_weekend: bool,
+    /// `--reveal`: show the names in `pseudonymize.reveal` instead of their hashes
+    pub reveal: bool,
+}
+
 /// Represents the possible results from querying statistics
 #[derive(Debug)]
 pub enum AggregatedResult {
@@ -67,6 +489,57 @@ pub enum AggregatedResult {
     ByApp(Vec<(String, i64)>), // Vec<(app_name, total_secs)>
     /// Results aggregated by application name and window title
     Detailed(Vec<DetailedUsageRecord>),
+    /// Results aggregated by virtual desktop
+    ByDesktop(Vec<(String, i64)>), // Vec<(desktop_name, total_secs)>
+    /// Results aggregated by device
+    ByDevice(Vec<(String, i64)>), // Vec<(device_id, total_secs)>
+    /// Results aggregated by Git repository (only time with a known repository)
+    ByRepo(Vec<(String, i64)>), // Vec<(repo, total_secs)>
+    /// Results aggregated by executable path (only time with a recorded path)
+    ByPath(Vec<(String, i64)>), // Vec<(executable_path, total_secs)>
+    /// Results aggregated by calendar event title (only time overlapping an event)
+    ByMeeting(Vec<(String, i64)>), // Vec<(meeting, total_secs)>
+    /// Results aggregated by issue key (only time with a detected ticket)
+    ByTicket(Vec<(String, i64)>), // Vec<(ticket, total_secs)>
+    /// Results aggregated by OS user
+    ByUser(Vec<(String, i64)>), // Vec<(user_name, total_secs)>
+    /// Results aggregated by document (only time with a known document)
+    ByDocument(Vec<(String, i64)>), // Vec<(document, total_secs)>
+    /// Results aggregated by remote session (only remote desktop time)
+    ByRemote(Vec<(String, i64)>), // Vec<("session_type[: remote_host]", total_secs)>
+    /// Context-switching metrics computed from the raw intervals
+    Focus(FocusMetrics),
+    /// Time per local hour of the day (boxed: two 24-slot arrays)
+    Profile(Box<TimeOfDayProfile>),
+}
+
+/// Rows written or deleted by one aggregation run (summary counts include updated rows)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AggregationReport {
+    /// Flapping raw intervals merged into a neighbour (`merge_gap_secs`)
+    pub merged_intervals: usize,
+    pub hourly_rows: usize,
+    pub daily_rows: usize,
+    pub desktop_rows: usize,
+    pub device_rows: usize,
+    pub repo_rows: usize,
+    pub path_rows: usize,
+    pub meeting_rows: usize,
+    pub ticket_rows: usize,
+    pub user_rows: usize,
+    pub remote_rows: usize,
+    pub document_rows: usize,
+    /// Rows of `daily_audio_summary` written from ended audio intervals, which are then deleted
+    pub audio_rows: usize,
+    pub deleted_audio: usize,
+    pub tracker_day_rows: usize,
+    /// Raw intervals copied to `app_intervals_archive` before deletion (`archive_raw_intervals`)
+    pub archived_raw: usize,
+    pub deleted_raw: usize,
+    pub days_summary_rows: usize,
+    pub weeks_summary_rows: usize,
+    pub deleted_daily: usize,
+    pub deleted_hourly: usize,
 }
 
 // Helper to check if the result contains any data
@@ -75,6 +548,17 @@ impl AggregatedResult {
         match self {
             AggregatedResult::ByApp(v) => v.is_empty(),
             AggregatedResult::Detailed(v) => v.is_empty(),
+            AggregatedResult::ByDesktop(v) => v.is_empty(),
+            AggregatedResult::ByDevice(v) => v.is_empty(),
+            AggregatedResult::ByRepo(v) => v.is_empty(),
+            AggregatedResult::ByPath(v) => v.is_empty(),
+            AggregatedResult::ByMeeting(v) => v.is_empty(),
+            AggregatedResult::ByTicket(v) => v.is_empty(),
+            AggregatedResult::ByUser(v) => v.is_empty(),
+            AggregatedResult::ByDocument(v) => v.is_empty(),
+            AggregatedResult::ByRemote(v) => v.is_empty(),
+            AggregatedResult::Focus(metrics) => metrics.tracked_secs == 0,
+            AggregatedResult::Profile(profile) => profile.total_secs() == 0,
         }
     }
 }
@@ -83,12 +567,106 @@ impl AggregatedResult {
 // If your AppError isn't directly usable with rusqlite, create mapping or a specific error enum
 pub type AppResult<T> = Result<T, AppError>; // Assuming AppError can wrap rusqlite::Error
 
+/// How durations are printed (`--duration-format` / `duration_format` in config.json)
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationFormat {
+    /// Largest units only, e.g. "2h 13m"
+    Human,
+    /// HH:MM:SS, e.g. "02:13:20"
+    #[default]
+    Clock,
+    /// Decimal hours, e.g. "2.22h"
+    Hours,
+    /// Total seconds, e.g. "8000s"
+    Seconds,
+}
+
+/// Where `track` gets the current activity from
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetectorKind {
+    /// The OS detector plus any detector plugins from config.json
+    #[default]
+    Os,
+    /// Replay `--script` (needs the `mock-detector` feature)
+    Mock,
+}
+
+/// File formats of the `export` command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// iCalendar file, one event per contiguous app/title block
+    #[value(name = "ics")]
+    Ics,
+    /// Standalone HTML report: time per app and the timeline
+    #[value(name = "html")]
+    Html,
+    /// Tab-separated time per issue key (`ticket_detection`) for Jira work logs
+    #[value(name = "worklog")]
+    Worklog,
+}
+
+/// Output of the `query` command and of saved reports
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFormat {
+    #[value(name = "table")]
+    Table,
+    /// RFC 4180 CSV with a header row
+    #[value(name = "csv")]
+    Csv,
+    /// An array with one object per row
+    #[value(name = "json")]
+    Json,
+}
+
+/// Chat services `report --post` sends the summary to (webhook URLs in the `notify` section)
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostTarget {
+    #[value(name = "slack")]
+    Slack,
+    #[value(name = "discord")]
+    Discord,
+}
+
+impl fmt::Display for PostTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PostTarget::Slack => write!(f, "Slack"),
+            PostTarget::Discord => write!(f, "Discord"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum ApiKeyType {
     #[value(name = "openai")] // CLI argument name
     OpenAI,
     #[value(name = "google")] // Example for future
     Google,
+    /// Anthropic API key (console.anthropic.com -> API Keys)
+    #[value(name = "anthropic")]
+    Anthropic,
+    /// Bearer token sent to the `sync_url` server
+    #[value(name = "sync")]
+    Sync,
+    /// Key for end-to-end encryption of synced/exported data (see `config gen-encryption-key`)
+    #[value(name = "encryption")]
+    Encryption,
+    /// SQLCipher passphrase for the local database (see `db encrypt`)
+    #[value(name = "database")]
+    Database,
+    /// Toggl Track API token (Profile settings -> API Token)
+    #[value(name = "toggl")]
+    Toggl,
+    /// Password for the MQTT broker (`mqtt.username` in config.json)
+    #[value(name = "mqtt")]
+    Mqtt,
+    /// Password (or app password) for the CalDAV server (`calendar.username` in config.json)
+    #[value(name = "caldav")]
+    Caldav,
+    /// Salt of the hashed app names and titles (`pseudonymize`; generated by the first `track`)
+    #[value(name = "pseudonym")]
+    Pseudonym,
     // Add other key types here as needed
 }
 
@@ -98,6 +676,14 @@ impl fmt::Display for ApiKeyType {
         match self {
             ApiKeyType::OpenAI => write!(f, "OpenAI"),
             ApiKeyType::Google => write!(f, "Google"),
+            ApiKeyType::Anthropic => write!(f, "Anthropic"),
+            ApiKeyType::Sync => write!(f, "Sync"),
+            ApiKeyType::Encryption => write!(f, "Encryption"),
+            ApiKeyType::Database => write!(f, "Database"),
+            ApiKeyType::Toggl => write!(f, "Toggl"),
+            ApiKeyType::Mqtt => write!(f, "MQTT"),
+            ApiKeyType::Caldav => write!(f, "CalDAV"),
+            ApiKeyType::Pseudonym => write!(f, "Pseudonymization salt"),
         }
     }
 }
@@ -109,6 +695,14 @@ impl ApiKeyType {
             // These MUST be unique within your app's keyring service
             ApiKeyType::OpenAI => "openai_api_key",
             ApiKeyType::Google => "google_api_key",
+            ApiKeyType::Anthropic => "anthropic_api_key",
+            ApiKeyType::Sync => "sync_token",
+            ApiKeyType::Encryption => "encryption_key",
+            ApiKeyType::Database => "database_passphrase",
+            ApiKeyType::Toggl => "toggl_api_token",
+            ApiKeyType::Mqtt => "mqtt_password",
+            ApiKeyType::Caldav => "caldav_password",
+            ApiKeyType::Pseudonym => "pseudonym_salt",
         }
     }
 }
@@ -120,5 +714,271 @@ pub enum ConfigCommand {
         #[arg(value_enum)] // Use the enum directly
         key_type: ApiKeyType,
     },
-    // No GetKey or DeleteKey based on your requirements
-}
\ No newline at end of file
+    /// Generate a random end-to-end encryption key and save it in the keyring
+    GenEncryptionKey,
+    /// Decrypt a file/body produced with end-to-end encryption and print it
+    Decrypt {
+        /// Path of the encrypted JSON payload
+        input: std::path::PathBuf,
+    },
+    /// Show which keys are stored in the keyring (values are never printed)
+    ListKeys,
+    /// Remove a stored key from the keyring
+    DeleteKey {
+        #[arg(value_enum)]
+        key_type: ApiKeyType,
+        /// Also delete the encryption key, database passphrase (data locked with them becomes unreadable) or pseudonymization salt
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check that a stored key is accepted, with one cheap call to its service
+    TestKey {
+        #[arg(value_enum)]
+        key_type: ApiKeyType,
+    },
+    /// Lock config.json with an administrator password: its current settings are signed and used until
+    /// unlocked, and changing settings or data asks for the password (run again to sign later edits)
+    Lock,
+    /// Remove the administrator lock (asks for the password)
+    Unlock,
+    /// Show whether the configuration is locked
+    LockStatus,
+}
+
+#[derive(clap::Subcommand, Debug, Clone, Copy)]
+pub enum AutostartCommand {
+    /// Launch the tracker (`track --daemon --supervise`) automatically at login
+    Enable,
+    /// Remove the login entry
+    Disable,
+    /// Show whether the login entry exists
+    Status,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum SnapshotsCommand {
+    /// Show the windows that were open at a given time
+    Show {
+        /// Local time: "HH:MM", "YYYY-MM-DD HH:MM" or RFC 3339
+        #[arg(long)]
+        at: String,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum IntervalsCommand {
+    /// List raw intervals overlapping a time range (default: today so far)
+    List {
+        /// Local time: "HH:MM", "YYYY-MM-DD [HH:MM]" or RFC 3339 (default: start of today)
+        #[arg(long)]
+        from: Option<String>,
+        /// Same formats as --from (default: now)
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Delete a completed interval (logged to the audit_log table)
+    Delete {
+        id: i64,
+    },
+    /// Split a completed interval in two at a point in time (logged to the audit_log table)
+    Split {
+        id: i64,
+        /// Local time inside the interval, same formats as `list --from`
+        #[arg(long)]
+        at: String,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ArchiveCommand {
+    /// List archived raw intervals (kept by `archive_raw_intervals` after aggregation)
+    Query {
+        /// Local time: "HH:MM", "YYYY-MM-DD [HH:MM]" or RFC 3339 (default: the oldest archived interval)
+        #[arg(long)]
+        from: Option<String>,
+        /// Same formats as --from (default: now)
+        #[arg(long)]
+        to: Option<String>,
+        /// Only include applications matching this glob (case-insensitive, e.g. "chrome*")
+        #[arg(long = "app", value_name = "GLOB")]
+        app_glob: Option<String>,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// List profiles with their database sizes (the selected one is marked)
+    List,
+    /// Create an empty profile with its own config.json and database
+    Create {
+        name: String,
+        /// Start from a copy of the selected profile's config.json instead of the defaults
+        #[arg(long)]
+        copy_config: bool,
+    },
+    /// Delete a profile's directory (config and database) and its keyring keys
+    Delete {
+        name: String,
+        /// Confirm the deletion; without it only what would be removed is printed
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ReportCommand {
+    /// Run a saved report (`stats` with the period, level, filters and format stored under NAME)
+    Run {
+        name: String,
+    },
+    /// List the saved reports of config.json
+    List,
+}
+
+/// Language model service `llm::complete` asks (key set with `config set-key <provider>`, none for
+/// Ollama); the default is `llm_provider` in config.json.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProvider {
+    #[default]
+    #[value(name = "openai")]
+    OpenAI,
+    Anthropic,
+    /// A local Ollama server at `ollama_url`; needs no key and nothing leaves the machine
+    Ollama,
+}
+
+impl LlmProvider {
+    /// The keyring key the service needs, None for a local server.
+    pub fn api_key_type(self) -> Option<ApiKeyType> {
+        match self {
+            LlmProvider::OpenAI => Some(ApiKeyType::OpenAI),
+            LlmProvider::Anthropic => Some(ApiKeyType::Anthropic),
+            LlmProvider::Ollama => None,
+        }
+    }
+
+    /// Model used when `--model` is not given: a small, cheap one (the task is short and structured).
+    pub fn default_model(self) -> &'static str {
+        match self {
+            LlmProvider::OpenAI => "gpt-4o-mini",
+            LlmProvider::Anthropic => "claude-3-5-haiku-latest",
+            LlmProvider::Ollama => "llama3.2",
+        }
+    }
+}
+
+/// Shells `shell-hook` prints an integration snippet for.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    #[value(alias = "pwsh")]
+    Powershell,
+}
+
+#[derive(clap::Subcommand, Debug, Clone, Copy)]
+pub enum ScheduleCommand {
+    /// List the jobs of the `schedule` section with their timing
+    List,
+    /// Show when each job runs next, soonest first
+    Next,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum AppCommand {
+    /// Friendly names shown instead of executable names in stats and exports
+    Alias {
+        #[command(subcommand)]
+        alias_command: AliasCommand,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum AliasCommand {
+    /// Show an app under a friendly name, e.g. `app alias set msedge.exe "Microsoft Edge"`
+    Set {
+        /// Executable name as shown by `stats` (case-insensitive)
+        app: String,
+        name: String,
+        /// Icon file for the app (e.g. for GUIs reading the app_metadata table)
+        #[arg(long)]
+        icon: Option<std::path::PathBuf>,
+    },
+    /// Go back to showing the executable name
+    Remove {
+        app: String,
+    },
+    /// List all friendly names (set by you or read from executables)
+    List,
+}
+
+#[derive(clap::Subcommand, Debug, Clone, Copy)]
+pub enum CalendarCommand {
+    /// Read the calendar now and store its events (a running tracker does this every `refresh_minutes`)
+    Sync {
+        /// Days back to read, so time tracked before the calendar was set up is tagged too
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Show today's stored events (time spent in them: `stats --level meetings`)
+    List,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum HabitCommand {
+    /// Define a daily goal, e.g. `habit add coding --app "code*" --min-minutes 60` (same name: redefine)
+    Add {
+        name: String,
+        /// Apps that count, as a case-insensitive glob on the app name
+        #[arg(long = "app", value_name = "GLOB")]
+        app_glob: String,
+        /// Minutes per day the matching apps must reach
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(i64).range(1..=1440))]
+        min_minutes: i64,
+    },
+    /// Stop tracking a habit
+    Remove {
+        name: String,
+    },
+    /// Show each habit with today's progress and its current and longest streak
+    List,
+}
+
+#[derive(clap::Subcommand, Debug, Clone, Copy)]
+pub enum SyncCommand {
+    /// Upload intervals completed since the last sync to `sync_url`
+    Push,
+    /// Show the sync endpoint, watermark and number of intervals waiting
+    Status,
+}
+
+#[derive(clap::Subcommand, Debug, Clone, Copy)]
+pub enum DbCommand {
+    /// Print where the database and config.json are, and which setting chose the database location
+    Path,
+    /// Convert the plaintext database to SQLCipher using the keyring `database` passphrase
+    Encrypt,
+    /// Convert an SQLCipher database back to plaintext SQLite
+    Decrypt,
+    /// Check integrity, then ANALYZE and VACUUM; prints the file size before and after
+    Maintain,
+    /// Write a compacted copy of the database to `backups/` next to it (safe while tracking)
+    Backup {
+        /// Then delete the oldest backups so only this many remain
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+    },
+}
+
+#[derive(clap::Subcommand, Debug, Clone, Copy)]
+pub enum TogglCommand {
+    /// Create Toggl time entries from the tracked time of a period
+    Push {
+        #[arg(long, value_enum, default_value_t = TimePeriod::Today)]
+        period: TimePeriod,
+        /// Show the entries that would be created without sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
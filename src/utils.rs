@@ -1,11 +1,157 @@
 // src/utils.rs
 
+use crate::types::DurationFormat;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
 
 // Formats total seconds (i64)
 pub fn format_duration_secs(total_seconds: i64) -> String {
+    format_duration(total_seconds, DurationFormat::Clock)
+}
+
+/// Formats total seconds as "2h 13m" (human), "02:13:20" (clock), "2.22h" (hours) or "8000s" (seconds).
+pub fn format_duration(total_seconds: i64, format: DurationFormat) -> String {
     if total_seconds < 0 { return "Invalid".to_string(); }
     let hours = total_seconds / 3600;
     let minutes = (total_seconds % 3600) / 60;
     let seconds = total_seconds % 60;
-    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
-}
\ No newline at end of file
+    match format {
+        DurationFormat::Human if hours > 0 => format!("{}h {}m", hours, minutes),
+        DurationFormat::Human if minutes > 0 => format!("{}m", minutes),
+        DurationFormat::Human => format!("{}s", seconds),
+        DurationFormat::Clock => format!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+        DurationFormat::Hours => format!("{:.2}h", total_seconds as f64 / 3600.0),
+        DurationFormat::Seconds => format!("{}s", total_seconds),
+    }
+}
+
+/// Formats a byte count as "512 B", "48.0 KiB", "12.3 MiB" or "1.2 GiB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
+/// Case-insensitive glob match supporting `*` (any run of characters) and `?` (one character).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last '*' and the text index it is currently matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Name of this computer, used as the default `device_id`.
+pub fn hostname() -> String {
+    let from_env = std::env::var("COMPUTERNAME").or_else(|_| std::env::var("HOSTNAME")).ok();
+    from_env
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown-device".to_string())
+}
+
+/// Name of the logged-in OS user, used as the default `user_name`.
+pub fn login_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown-user".to_string())
+}
+
+/// Parses a user-supplied point in time, interpreted in local time:
+/// "HH:MM" (today), "YYYY-MM-DD HH:MM[:SS]", "YYYY-MM-DD" (midnight) or RFC 3339.
+pub fn parse_user_time(input: &str) -> Result<DateTime<Utc>, String> {
+    let input = input.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let naive = if let Ok(time) = NaiveTime::parse_from_str(input, "%H:%M") {
+        Local::now().date_naive().and_time(time)
+    } else if let Ok(dt) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S") {
+        dt
+    } else if let Ok(dt) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M") {
+        dt
+    } else if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        date.and_time(NaiveTime::MIN)
+    } else {
+        return Err(format!(
+            "Could not parse time '{}'. Use HH:MM, YYYY-MM-DD HH:MM or RFC 3339.",
+            input
+        ));
+    };
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok_or_else(|| format!("Time '{}' does not exist in the local time zone.", input))
+}
+
+/// Minutes since midnight of a local "HH:MM" time.
+pub fn parse_time_of_day(time: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid time {:?} (expected \"HH:MM\")", time);
+    let (hours, minutes) = time.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+/// `days` of config.json ("mon".."sun", "weekdays", "weekend") as flags indexed Monday first; empty = every day.
+pub fn parse_weekdays(days: &[String]) -> Result<[bool; 7], String> {
+    if days.is_empty() {
+        return Ok([true; 7]);
+    }
+    let mut selected = [false; 7];
+    for day in days {
+        let indexes: &[usize] = match day.trim().to_lowercase().as_str() {
+            "weekdays" => &[0, 1, 2, 3, 4],
+            "weekend" => &[5, 6],
+            "mon" | "monday" => &[0],
+            "tue" | "tuesday" => &[1],
+            "wed" | "wednesday" => &[2],
+            "thu" | "thursday" => &[3],
+            "fri" | "friday" => &[4],
+            "sat" | "saturday" => &[5],
+            "sun" | "sunday" => &[6],
+            _ => return Err(format!("unknown day {:?} (expected e.g. \"mon\", \"weekdays\" or \"weekend\")", day)),
+        };
+        for index in indexes {
+            selected[*index] = true;
+        }
+    }
+    Ok(selected)
+}
+
+/// Formats a UTC timestamp as local "YYYY-MM-DD HH:MM:SS".
+pub fn format_local_timestamp(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => format!("@{}", timestamp),
+    }
+}
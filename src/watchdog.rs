@@ -0,0 +1,269 @@
+// src/watchdog.rs
+// Supervisor mode (`track --supervise`, registered by `autostart`): the started process stays small and
+// runs the real tracker as a child process, restarting it when it panics, dies or its detection thread
+// stops polling. The child writes its detection poll count to `watchdog.heartbeat` every few seconds; a
+// count that stops moving is a hang. Each restart leaves a crash report in `crash-reports/` next to
+// `config.json` and is counted in `watchdog.json`, which `status` shows.
+
+use crate::config::AppConfig;
+use crate::errors::{AppError, AppResult};
+use crate::instance;
+use crate::tracker::TrackerHandle;
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const STATE_FILE_NAME: &str = "watchdog.json";
+const HEARTBEAT_FILE_NAME: &str = "watchdog.heartbeat";
+const CRASH_REPORT_DIR: &str = "crash-reports";
+/// How often the child writes its heartbeat and the supervisor reads it
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// A heartbeat unchanged for this long (at least 3 × `idle_poll_interval`) is a hung detection thread
+const MIN_HANG_TIMEOUT: Duration = Duration::from_secs(120);
+/// A child failing sooner than this after its start never got going (bad config, database unavailable...)
+const STARTUP_GRACE: Duration = Duration::from_secs(10);
+/// Failures in a row within `STARTUP_GRACE` after which the supervisor gives up
+const MAX_STARTUP_FAILURES: u32 = 3;
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+/// Last lines of the child's stderr kept for the crash report (a panic message ends up there)
+const STDERR_TAIL_LINES: usize = 50;
+
+/// Restarts by the watchdog so far (`watchdog.json`), shown by `status`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchdogState {
+    pub restarts: u64,
+    /// Unix time of the last restart
+    #[serde(default)]
+    pub last_restart: Option<i64>,
+    /// Why the tracker was restarted then, e.g. "exited with exit status: 101"
+    #[serde(default)]
+    pub last_reason: Option<String>,
+    #[serde(default)]
+    pub last_report: Option<PathBuf>,
+}
+
+/// The restart counter of this data directory; None before the first restart.
+pub fn load_state(app_config: &AppConfig) -> Option<WatchdogState> {
+    let json = fs::read_to_string(data_dir(app_config).join(STATE_FILE_NAME)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Child side: writes the detection poll count of `handle` to `path` until the tracker stops.
+pub fn start_heartbeat(handle: TrackerHandle, path: PathBuf) {
+    let spawned = thread::Builder::new().name("watchdog-heartbeat".to_string()).spawn(move || {
+        while handle.is_running() {
+            if let Err(e) = fs::write(&path, handle.detection_polls().to_string()) {
+                log::warn!("Could not write the watchdog heartbeat {:?}: {}", path, e);
+            }
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn the watchdog heartbeat thread: {}", e);
+    }
+}
+
+/// How a supervised run ended.
+enum Outcome {
+    Exited(ExitStatus),
+    /// Killed after its heartbeat stood still this long
+    Hung(Duration),
+}
+
+/// Supervisor side: runs `track` (this process's arguments without `--supervise`) as a child until it
+/// exits cleanly or Ctrl+C is pressed, restarting it after crashes and hangs with a growing delay.
+pub fn supervise(app_config: &AppConfig, daemon: bool) -> AppResult<()> {
+    if let Some(tracker) = instance::running_tracker(app_config) {
+        return Err(AppError::Config(format!("A tracker is already running on this database ({})", tracker.describe())));
+    }
+    let exe = std::env::current_exe().map_err(|e| AppError::Unexpected(format!("Could not determine current executable: {}", e)))?;
+    let heartbeat_path = data_dir(app_config).join(HEARTBEAT_FILE_NAME);
+    let args: Vec<OsString> = std::env::args_os()
+        .skip(1)
+        .filter(|arg| arg != "--supervise")
+        .chain([OsString::from("--watchdog-heartbeat"), heartbeat_path.clone().into_os_string()])
+        .collect();
+    let hang_timeout = MIN_HANG_TIMEOUT.max(3 * app_config.idle_poll_interval);
+    // The child gets the same Ctrl+C (same console or process group) and stops on its own
+    let stopping = Arc::new(AtomicBool::new(false));
+    let flag = stopping.clone();
+    ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst))?;
+    if daemon {
+        #[cfg(target_os = "windows")]
+        crate::windows_api::detach_console();
+    }
+    log::info!("Watchdog supervising the tracker (hang timeout {}s).", hang_timeout.as_secs());
+
+    let mut startup_failures = 0;
+    let mut delay = Duration::from_secs(1);
+    loop {
+        let _ = fs::remove_file(&heartbeat_path);
+        let started = Instant::now();
+        let mut child = spawn_child(&exe, &args, daemon)?;
+        let stderr_tail = forward_stderr(&mut child);
+        let outcome = watch(&mut child, &heartbeat_path, hang_timeout, &stopping)?;
+        let uptime = started.elapsed();
+        let reason = match outcome {
+            Outcome::Exited(status) if status.success() || stopping.load(Ordering::SeqCst) => {
+                log::info!("Tracker stopped ({}); watchdog exiting.", status);
+                let _ = fs::remove_file(&heartbeat_path);
+                return Ok(());
+            }
+            Outcome::Exited(status) => format!("exited with {}", status),
+            Outcome::Hung(stalled) => format!("detection hung: no poll for {}s, killed", stalled.as_secs()),
+        };
+        // Give the reader thread a moment to collect the last lines the child printed
+        thread::sleep(Duration::from_millis(200));
+        let stderr_lines: Vec<String> = stderr_tail.lock().map(|lines| lines.iter().cloned().collect()).unwrap_or_default();
+
+        if uptime < STARTUP_GRACE {
+            startup_failures += 1;
+        } else {
+            startup_failures = 0;
+            delay = Duration::from_secs(1);
+        }
+        let mut state = load_state(app_config).unwrap_or_default();
+        let report = write_crash_report(app_config, &reason, uptime, state.restarts + 1, &stderr_lines);
+        if let Err(e) = &report {
+            log::warn!("Could not write the crash report: {}", e);
+        }
+        if startup_failures >= MAX_STARTUP_FAILURES {
+            return Err(AppError::Unexpected(format!(
+                "The tracker failed {} times right after starting ({}); not restarting it",
+                startup_failures, reason
+            )));
+        }
+        state.restarts += 1;
+        state.last_restart = Some(Utc::now().timestamp());
+        state.last_reason = Some(reason.clone());
+        state.last_report = report.ok();
+        if let Err(e) = save_state(app_config, &state) {
+            log::warn!("Could not update the watchdog counter: {}", e);
+        }
+        log::warn!("Tracker {}; restarting it in {}s (restart #{}).", reason, delay.as_secs(), state.restarts);
+        if !daemon {
+            eprintln!("[Watchdog] Tracker {}; restarting it in {}s.", reason, delay.as_secs());
+        }
+        thread::sleep(delay);
+        delay = (delay * 2).min(MAX_RESTART_DELAY);
+        if stopping.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+    }
+}
+
+fn spawn_child(exe: &Path, args: &[OsString], daemon: bool) -> AppResult<Child> {
+    let mut command = Command::new(exe);
+    command.args(args).stdin(Stdio::null()).stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    if daemon {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: a console child of a detached process would open a console window
+        command.creation_flags(0x0800_0000);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = daemon;
+    command.spawn().map_err(|e| AppError::Io { path: exe.to_path_buf(), source: e })
+}
+
+/// Copies the child's stderr to ours, keeping the last lines for a crash report.
+fn forward_stderr(child: &mut Child) -> Arc<Mutex<VecDeque<String>>> {
+    let tail = Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_TAIL_LINES)));
+    if let Some(stderr) = child.stderr.take() {
+        let lines = tail.clone();
+        let spawned = thread::Builder::new().name("watchdog-stderr".to_string()).spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                eprintln!("{}", line);
+                if let Ok(mut lines) = lines.lock() {
+                    if lines.len() == STDERR_TAIL_LINES {
+                        lines.pop_front();
+                    }
+                    lines.push_back(line);
+                }
+            }
+        });
+        if let Err(e) = spawned {
+            log::warn!("Failed to spawn the watchdog stderr thread: {}", e);
+        }
+    }
+    tail
+}
+
+/// Waits for the child to exit, killing it once its heartbeat has stood still for `hang_timeout`.
+/// Stall time only counts while the heartbeat has been seen at least once and this process itself
+/// kept running on time: after a system sleep both sides start over.
+fn watch(child: &mut Child, heartbeat_path: &Path, hang_timeout: Duration, stopping: &AtomicBool) -> AppResult<Outcome> {
+    let mut last_beat: Option<String> = None;
+    let mut stalled = Duration::ZERO;
+    loop {
+        let tick = Instant::now();
+        while tick.elapsed() < CHECK_INTERVAL {
+            if let Some(status) = child.try_wait().map_err(|e| AppError::Unexpected(format!("Waiting for the tracker failed: {}", e)))? {
+                return Ok(Outcome::Exited(status));
+            }
+            thread::sleep(Duration::from_millis(250));
+        }
+        let elapsed = tick.elapsed();
+        let beat = fs::read_to_string(heartbeat_path).ok();
+        if beat.is_none() || beat != last_beat || elapsed > 3 * CHECK_INTERVAL || stopping.load(Ordering::SeqCst) {
+            last_beat = beat;
+            stalled = Duration::ZERO;
+            continue;
+        }
+        stalled += elapsed;
+        if stalled >= hang_timeout {
+            if let Err(e) = child.kill() {
+                log::warn!("Could not kill the hung tracker: {}", e);
+            }
+            let _ = child.wait();
+            return Ok(Outcome::Hung(stalled));
+        }
+    }
+}
+
+/// `crash-reports/crash-YYYYMMDD-HHMMSS.txt` next to `config.json`.
+fn write_crash_report(app_config: &AppConfig, reason: &str, uptime: Duration, restart: u64, stderr_lines: &[String]) -> AppResult<PathBuf> {
+    let dir = data_dir(app_config).join(CRASH_REPORT_DIR);
+    fs::create_dir_all(&dir).map_err(|e| AppError::Io { path: dir.clone(), source: e })?;
+    let now = Local::now();
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    let mut report = format!(
+        "{} crash report\nTime:    {}\nVersion: {}\nOS:      {} {}\nReason:  {}\nUptime:  {}s\nRestart: #{}\n\nLast stderr output of the tracker:\n",
+        app_config.app_name,
+        now.format("%Y-%m-%d %H:%M:%S %:z"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        reason,
+        uptime.as_secs(),
+        restart,
+    );
+    if stderr_lines.is_empty() {
+        report.push_str("(none)\n");
+    }
+    for line in stderr_lines {
+        report.push_str(line);
+        report.push('\n');
+    }
+    fs::write(&path, report).map_err(|e| AppError::Io { path: path.clone(), source: e })?;
+    Ok(path)
+}
+
+fn save_state(app_config: &AppConfig, state: &WatchdogState) -> AppResult<()> {
+    let path = data_dir(app_config).join(STATE_FILE_NAME);
+    let json = serde_json::to_string_pretty(state).map_err(|e| AppError::Unexpected(e.to_string()))?;
+    fs::write(&path, json).map_err(|e| AppError::Io { path, source: e })
+}
+
+fn data_dir(app_config: &AppConfig) -> &Path {
+    app_config.config_file_path.parent().unwrap_or(Path::new("."))
+}
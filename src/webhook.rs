@@ -0,0 +1,221 @@
+// src/webhook.rs
+// POSTs app-switch and usage-threshold events to a user-configured URL (`webhook` in config.json).
+// Runs on its own thread fed by a channel, so slow or failing HTTP never delays detection.
+
+use crate::config::{AppConfig, WebhookConfig};
+use crate::persistence;
+use crate::types::{AggregatedResult, AggregationLevel, StatsFilter, TimePeriod};
+use crate::utils::glob_match;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_DEBOUNCE_SECS: u64 = 5;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often thresholds are re-checked while the focused app does not change
+const THRESHOLD_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// JSON body of one webhook call, tagged by `event`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// The focused app changed and stayed for at least `debounce_secs`
+    AppSwitch {
+        timestamp: i64,
+        device_id: String,
+        /// Previously announced app (None = nothing tracked, e.g. paused)
+        previous_app: Option<String>,
+        app: Option<String>,
+        title: Option<String>,
+    },
+    /// Today's time on apps matching `pattern` reached the configured limit
+    ThresholdReached {
+        timestamp: i64,
+        device_id: String,
+        pattern: String,
+        daily_minutes: u64,
+        tracked_secs: i64,
+    },
+}
+
+/// What the detection thread reports; sent only when the focused app changes.
+#[derive(Debug)]
+struct ActivityChange {
+    app: Option<String>,
+    title: Option<String>,
+    timestamp: i64,
+}
+
+/// Sending side handed to the detection thread. Dropping it stops the webhook thread.
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    sender: Sender<ActivityChange>,
+    last_app: Option<String>,
+}
+
+impl WebhookNotifier {
+    /// Spawns the webhook thread when a webhook is configured.
+    pub fn spawn(app_config: &AppConfig) -> Option<Self> {
+        let webhook = app_config.webhook.clone()?;
+        let (sender, receiver) = mpsc::channel();
+        let config = app_config.clone();
+        let spawned = thread::Builder::new()
+            .name("webhook".to_string())
+            .spawn(move || run_webhook_loop(&config, webhook, receiver));
+        match spawned {
+            Ok(_) => Some(WebhookNotifier { sender, last_app: None }),
+            Err(e) => {
+                eprintln!("Warning: failed to start webhook thread: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Records the current app; only app changes are forwarded to the webhook thread.
+    pub fn observe(&mut self, app: Option<&str>, title: Option<&str>, timestamp: i64) {
+        if self.last_app.as_deref() == app {
+            return;
+        }
+        self.last_app = app.map(str::to_string);
+        let change = ActivityChange { app: self.last_app.clone(), title: title.map(str::to_string), timestamp };
+        // A send error means the webhook thread is gone; tracking carries on regardless
+        let _ = self.sender.send(change);
+    }
+}
+
+/// Per-day usage of every app seen by the webhook thread, used for thresholds.
+struct UsageCounter {
+    day: i64,
+    secs_by_app: HashMap<String, i64>,
+    /// Indexes of thresholds already sent today
+    fired: Vec<usize>,
+}
+
+impl UsageCounter {
+    fn new(day: i64) -> Self {
+        UsageCounter { day, secs_by_app: HashMap::new(), fired: Vec::new() }
+    }
+
+    /// Counts `app` from `start` to `end`, splitting at UTC midnight like `stats today`.
+    fn add(&mut self, app: &str, start: i64, end: i64) {
+        let mut start = start;
+        while start < end {
+            let day = start.div_euclid(SECS_PER_DAY);
+            let segment_end = end.min((day + 1) * SECS_PER_DAY);
+            if day > self.day {
+                *self = UsageCounter::new(day);
+            }
+            if day == self.day {
+                *self.secs_by_app.entry(app.to_string()).or_default() += segment_end - start;
+            }
+            start = segment_end;
+        }
+    }
+
+    fn total_matching(&self, pattern: &str) -> i64 {
+        self.secs_by_app.iter().filter(|(app, _)| glob_match(pattern, app)).map(|(_, secs)| secs).sum()
+    }
+}
+
+fn run_webhook_loop(app_config: &AppConfig, webhook: WebhookConfig, receiver: Receiver<ActivityChange>) {
+    let client = match reqwest::blocking::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[Webhook] Failed to create HTTP client: {}", e);
+            return;
+        }
+    };
+    let debounce = Duration::from_secs(webhook.debounce_secs.unwrap_or(DEFAULT_DEBOUNCE_SECS));
+    let send = |event: WebhookEvent| {
+        match client.post(&webhook.url).json(&event).send() {
+            Ok(response) if !response.status().is_success() => {
+                log::warn!("Webhook {} answered HTTP {}", webhook.url, response.status());
+            }
+            Ok(_) => log::debug!("Webhook event sent: {:?}", event),
+            Err(e) => log::warn!("Webhook request to {} failed: {}", webhook.url, e),
+        }
+    };
+
+    let mut usage = seed_usage(app_config, &webhook);
+    let mut announced_app: Option<String> = None;
+    // Latest change not announced yet, with the time it may be sent
+    let mut pending: Option<(ActivityChange, Instant)> = None;
+    let mut current: Option<(String, i64)> = None;
+
+    loop {
+        let timeout = pending
+            .as_ref()
+            .map(|(_, due)| due.saturating_duration_since(Instant::now()))
+            .unwrap_or(THRESHOLD_CHECK_INTERVAL)
+            .min(THRESHOLD_CHECK_INTERVAL);
+        match receiver.recv_timeout(timeout) {
+            Ok(change) => {
+                if let Some((app, since)) = current.take() {
+                    usage.add(&app, since, change.timestamp);
+                }
+                current = change.app.clone().map(|app| (app, change.timestamp));
+                pending = Some((change, Instant::now() + debounce));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if let Some((_, due)) = &pending
+            && Instant::now() >= *due
+            && let Some((change, _)) = pending.take()
+            && change.app != announced_app
+        {
+            let previous_app = std::mem::replace(&mut announced_app, change.app.clone());
+            send(WebhookEvent::AppSwitch {
+                timestamp: change.timestamp,
+                device_id: app_config.device_id.clone(),
+                previous_app,
+                app: change.app,
+                title: change.title,
+            });
+        }
+
+        let now = Utc::now().timestamp();
+        if let Some((app, since)) = current.as_mut() {
+            usage.add(app, *since, now);
+            *since = now;
+        }
+        for (index, threshold) in webhook.thresholds.iter().enumerate() {
+            if usage.fired.contains(&index) {
+                continue;
+            }
+            let tracked_secs = usage.total_matching(&threshold.app);
+            if tracked_secs >= (threshold.daily_minutes * 60) as i64 {
+                usage.fired.push(index);
+                send(WebhookEvent::ThresholdReached {
+                    timestamp: now,
+                    device_id: app_config.device_id.clone(),
+                    pattern: threshold.app.clone(),
+                    daily_minutes: threshold.daily_minutes,
+                    tracked_secs,
+                });
+            }
+        }
+    }
+    log::debug!("Webhook thread stopped.");
+}
+
+/// Starts the day's counters from what is already stored, so a restart does not reset thresholds.
+fn seed_usage(app_config: &AppConfig, webhook: &WebhookConfig) -> UsageCounter {
+    let mut usage = UsageCounter::new(Utc::now().timestamp().div_euclid(SECS_PER_DAY));
+    if webhook.thresholds.is_empty() {
+        return usage;
+    }
+    let stored = persistence::open_storage(app_config)
+        .and_then(|mut storage| storage.query_stats(TimePeriod::Today, AggregationLevel::ByApplication, &StatsFilter::default()));
+    match stored {
+        Ok(AggregatedResult::ByApp(apps)) => usage.secs_by_app.extend(apps),
+        Ok(_) => {}
+        Err(e) => log::warn!("Webhook thresholds start from zero; reading today's usage failed: {}", e),
+    }
+    usage
+}
@@ -1,24 +1,73 @@
 // src/windows_api.rs
 
-use crate::errors::{AppError, AppResult};
+use crate::detection::SuspendState;
+use crate::detection::title_heuristics::{TitleCandidates, TitleHeuristics};
+use crate::errors::{AppError, AppResult, DetectionError};
+use std::cell::RefCell;
 use std::ffi::OsString; // Keep ptr and mem if used by EnumWindows callback data pointer
 use std::os::windows::ffi::OsStringExt;
-use std::path::Path;
-use windows::core::BOOL;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{LazyLock, Mutex, Once};
+use std::time::{Duration, Instant};
+use windows::core::{w, Interface, BOOL, BSTR, GUID, PCWSTR, PWSTR};
 use windows::Win32::Foundation::{
-    CloseHandle, MAX_PATH, HANDLE, HWND, LPARAM // Keep LPARAM/BOOL for EnumWindows
+    CloseHandle, ERROR_FILE_NOT_FOUND, ERROR_SUCCESS, MAX_PATH, HANDLE, HWND, LPARAM, LRESULT, PROPERTYKEY, RECT, S_OK, WPARAM // Keep LPARAM/BOOL for EnumWindows
+};
+use windows::Win32::Media::Audio::{
+    eMultimedia, eRender, AudioSessionStateActive, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator, MMDeviceEnumerator,
+};
+use windows::Win32::Media::Audio::Endpoints::IAudioMeterInformation;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Power::RegisterSuspendResumeNotification;
+use windows::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoSetProxyBlanket, CLSCTX_ALL, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, EOAC_NONE,
+    RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
+};
+use windows::Win32::System::Com::StructuredStorage::{PropVariantClear, PropVariantToBSTR};
+use windows::Win32::System::Console::FreeConsole;
+use windows::Win32::System::Registry::{
+    RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_BINARY,
+    RRF_RT_REG_SZ, REG_ROUTINE_FLAGS,
 };
 use windows::Win32::System::ProcessStatus::GetModuleFileNameExW;
-use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+use windows::Win32::Storage::EnhancedStorage::{PKEY_AppUserModel_ID, PKEY_AppUserModel_RelaunchDisplayNameResource};
+use windows::Win32::Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW};
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+use windows::Win32::UI::Shell::PropertiesSystem::{IPropertyStore, SHGetPropertyStoreForWindow};
+use windows::Win32::UI::Shell::{IVirtualDesktopManager, VirtualDesktopManager};
+use windows::Win32::System::Rpc::{RPC_C_AUTHN_WINNT, RPC_C_AUTHZ_NONE};
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION,
+    PROCESS_VM_READ,
+};
+use windows::Win32::System::Variant::{VariantClear, VARIANT, VT_BSTR};
+use windows::Win32::System::Wmi::{
+    IWbemClassObject, IWbemLocator, IWbemServices, WbemLocator, WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY,
+    WBEM_GENERIC_FLAG_TYPE,
+};
 use windows::Win32::UI::WindowsAndMessaging::{
     GetCursorPos, WindowFromPoint, GetWindowThreadProcessId,
     GetWindowTextW, GetAncestor, GA_ROOTOWNER,
-    EnumWindows, IsWindowVisible // Keep EnumWindows imports
+    EnumWindows, IsWindowVisible, // Keep EnumWindows imports
+    GetForegroundWindow, GetWindowRect,
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage,
+    HWND_MESSAGE, MSG, WINDOW_EX_STYLE, WINDOW_STYLE, WM_WTSSESSION_CHANGE, WNDCLASSW,
+    WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+    DEVICE_NOTIFY_WINDOW_HANDLE, PBT_APMRESUMEAUTOMATIC, PBT_APMRESUMESUSPEND, PBT_APMSUSPEND, WM_POWERBROADCAST,
+    GetSystemMetrics, SM_REMOTESESSION,
 };
 use log::{debug, warn}; // Import log macros
 
 const MAX_TITLE_LENGTH: usize = 512;
 
+/// Full paths of the executables seen by the detector, by file name (for `executable_path`)
+static EXECUTABLE_PATHS: LazyLock<Mutex<HashMap<String, PathBuf>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
 // --- EnumWindows Callback Setup ---
 // Keep this struct as it's needed for enumeration
 #[derive(Debug)] // Add Debug for logging if needed
@@ -51,14 +100,101 @@ extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
 fn is_generic_title(title: &str) -> bool {
     matches!(title, "Chrome Legacy Window" | "MSCTFIME UI" | "Default IME" | "") // Add more if needed
 }
+
+// Callback for context snapshots: every visible top-level window with a real title, any process
+extern "system" fn enum_all_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    unsafe {
+        let windows = &mut *(lparam.0 as *mut Vec<(u32, u32, String)>); // (pid, thread_id, title)
+        if IsWindowVisible(hwnd).as_bool() {
+            let title = get_hwnd_title(hwnd);
+            if !is_generic_title(&title) {
+                let mut window_pid: u32 = 0;
+                let thread_id = GetWindowThreadProcessId(hwnd, Some(&mut window_pid));
+                windows.push((window_pid, thread_id, title));
+            }
+        }
+        BOOL(1) // Continue enumeration
+    }
+}
 // --- End EnumWindows Callback Setup ---
 
+/// Lists all visible top-level windows as (app_name, title), in Z-order.
+pub fn list_visible_windows() -> AppResult<Vec<(String, String)>> {
+    let mut raw_windows: Vec<(u32, u32, String)> = Vec::new();
+    unsafe {
+        EnumWindows(Some(enum_all_windows_callback), LPARAM(&mut raw_windows as *mut _ as isize))
+            .map_err(|e| DetectionError::windows_api("EnumWindows", &e))?;
+    }
+    // Several windows usually share a process; resolve each PID once
+    let mut names: std::collections::HashMap<u32, String> = std::collections::HashMap::new();
+    let mut result = Vec::with_capacity(raw_windows.len());
+    for (pid, thread_id, title) in raw_windows {
+        let app_name = match names.get(&pid) {
+            Some(name) => name.clone(),
+            None => {
+                let name = unsafe { get_process_executable_name(pid, thread_id)? };
+                names.insert(pid, name.clone());
+                name
+            }
+        };
+        result.push((app_name, title));
+    }
+    debug!("Context snapshot found {} visible window(s).", result.len());
+    Ok(result)
+}
+
+
+/// The window under the cursor, as found by `get_detailed_window_info`.
+#[derive(Debug, Clone)]
+pub struct DetectedWindow {
+    pub app_name: String,
+    pub main_title: String,
+    pub detailed_title: String,
+    pub virtual_desktop: Option<String>,
+    /// Full path of the app's executable, when it could be read
+    pub executable_path: Option<PathBuf>,
+    pub process_id: u32,
+}
+
+/// What identifies the window under the cursor on a poll; all of it is cheap to read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WindowKey {
+    // HWNDs as integers (HWND itself is a pointer, not Send)
+    hwnd_under_cursor: isize,
+    ancestor_hwnd: isize,
+    foreground_hwnd: isize,
+    process_id: u32,
+    title_under_cursor: String,
+    ancestor_title: String,
+    web_apps: bool,
+}
+
+/// The result of the last full lookup, with what it was computed from.
+struct CachedWindow {
+    key: WindowKey,
+    looked_up_at: Instant,
+    // App name and path before the web app check (valid while `key.process_id` is the same)
+    process: (String, Option<PathBuf>),
+    window: DetectedWindow,
+}
+
+/// A window that has not changed is looked up in full again after this long anyway, so titles of
+/// its sibling windows (the enumeration) and moves to another virtual desktop are picked up.
+const WINDOW_CACHE_REFRESH: Duration = Duration::from_secs(30);
+
+thread_local! {
+    // Steady state (same window, same titles) skips the process lookup, the enumeration and COM calls
+    static LAST_WINDOW: RefCell<Option<CachedWindow>> = const { RefCell::new(None) };
+}
 
 // --- Main Public Function ---
-pub fn get_detailed_window_info() -> AppResult<Option<(String, String, String)>> { // (app, main_title, detailed_title)
+/// `web_apps`: report installed web apps (PWAs) under their own name instead of the browser's.
+/// When the window under the cursor, its root window, the foreground window and their titles are
+/// the same as on the previous poll, the previous result is reused (see `WINDOW_CACHE_REFRESH`).
+pub fn get_detailed_window_info(title_heuristics: &TitleHeuristics, web_apps: bool) -> AppResult<Option<DetectedWindow>> {
     unsafe {
         let mut point = Default::default();
-        GetCursorPos(&mut point).map_err(|e| AppError::Platform(format!("GetCursorPos failed: {}", e)))?;
+        GetCursorPos(&mut point).map_err(|e| DetectionError::windows_api("GetCursorPos", &e))?;
         debug!("Cursor position: ({}, {})", point.x, point.y);
 
         let hwnd_under_cursor = WindowFromPoint(point);
@@ -89,12 +225,45 @@ pub fn get_detailed_window_info() -> AppResult<Option<(String, String, String)>>
         let thread_id = GetWindowThreadProcessId(ancestor_hwnd, Some(&mut process_id));
         debug!("PID from ancestor HWND: {} (Thread ID: {})", process_id, thread_id);
 
-        // --- Get App Name ---
-        let app_name = get_process_executable_name(process_id, thread_id)?;
+        // --- Reuse the last result while nothing visible changed ---
+        let ancestor_title = get_hwnd_title(ancestor_hwnd);
+        let foreground_hwnd = GetForegroundWindow();
+        let key = WindowKey {
+            hwnd_under_cursor: hwnd_under_cursor.0 as isize,
+            ancestor_hwnd: ancestor_hwnd.0 as isize,
+            foreground_hwnd: foreground_hwnd.0 as isize,
+            process_id,
+            title_under_cursor: title_under_cursor.clone(),
+            ancestor_title: ancestor_title.clone(),
+            web_apps,
+        };
+        let (unchanged, cached_process) = LAST_WINDOW.with(|last| match last.borrow().as_ref() {
+            Some(cached) if cached.looked_up_at.elapsed() < WINDOW_CACHE_REFRESH => {
+                let unchanged = (cached.key == key).then(|| cached.window.clone());
+                let process = (cached.key.process_id == process_id).then(|| cached.process.clone());
+                (unchanged, process)
+            }
+            _ => (None, None),
+        });
+        if let Some(window) = unchanged {
+            debug!("Window under cursor unchanged; reusing the last lookup.");
+            return Ok(Some(window));
+        }
+
+        // --- Get App Name (the process lookup is skipped while the PID stays the same) ---
+        let process = match cached_process {
+            Some(process) => process,
+            None => get_process_executable(process_id, thread_id)?,
+        };
+        let (mut app_name, executable_path) = process.clone();
         debug!("App name from PID {}: '{}'", process_id, app_name);
+        // An installed web app runs in its browser's process; `executable_path` stays the browser's
+        if web_apps && let Some(web_app) = web_app_name(ancestor_hwnd) {
+            debug!("Window belongs to web app '{}' hosted by '{}'.", web_app, app_name);
+            app_name = web_app;
+        }
 
-        // --- Get Ancestor Window Title (Candidate for Main) ---
-        let ancestor_title = get_hwnd_title(ancestor_hwnd);
+        // --- Ancestor Window Title (Candidate for Main) ---
         debug!("Ancestor title: '{}'", ancestor_title);
         // Assign placeholder if empty
         let final_main_title = if ancestor_title.is_empty() {
@@ -114,13 +283,17 @@ pub fn get_detailed_window_info() -> AppResult<Option<(String, String, String)>>
             EnumWindows(Some(enum_windows_callback), LPARAM(&mut callback_data as *mut _ as isize));
             debug!("Enumeration found {} potential sibling windows.", callback_data.windows.len());
 
-            // Heuristic: Find the longest non-generic title among enumerated siblings
-             if let Some((hwnd, title)) = callback_data.windows.iter().max_by_key(|(_, t)| t.len()) {
-                 debug!("Longest sibling title found ('{}' on {:?}).", title, hwnd);
-                 enum_title = title.clone(); // Use this candidate
-             } else {
-                  debug!("No suitable sibling title found via enumeration.");
-             }
+            // Heuristic: the longest non-generic title among enumerated siblings, unless `title_heuristics`
+            // has a rule for this app (e.g. prefer its foreground window)
+            let foreground = callback_data.windows.iter().find(|(hwnd, _)| *hwnd == foreground_hwnd).map(|(_, title)| title.as_str());
+            let titles: Vec<String> = callback_data.windows.iter().map(|(_, title)| title.clone()).collect();
+            match title_heuristics.select(&app_name, TitleCandidates { windows: &titles, foreground }) {
+                Some(title) => {
+                    debug!("Sibling title selected: '{}'.", title);
+                    enum_title = title; // Use this candidate
+                }
+                None => debug!("No suitable sibling title found via enumeration."),
+            }
         }
         // --- End Enumeration ---
 
@@ -142,9 +315,441 @@ pub fn get_detailed_window_info() -> AppResult<Option<(String, String, String)>>
         // --- End Detailed Title ---
 
 
-        Ok(Some((app_name, final_main_title, final_detailed_title)))
+        let virtual_desktop = get_window_desktop_name(ancestor_hwnd);
+        debug!("Virtual desktop: {:?}", virtual_desktop);
+
+        let window = DetectedWindow {
+            app_name,
+            main_title: final_main_title,
+            detailed_title: final_detailed_title,
+            virtual_desktop,
+            executable_path,
+            process_id,
+        };
+        LAST_WINDOW.with(|last| {
+            *last.borrow_mut() = Some(CachedWindow { key, looked_up_at: Instant::now(), process, window: window.clone() });
+        });
+        Ok(Some(window))
+    }
+}
+// --- Idle Time (time since last keyboard/mouse input) ---
+pub fn get_idle_time() -> AppResult<Duration> {
+    unsafe {
+        let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return Err(DetectionError::windows_api("GetLastInputInfo", &windows::core::Error::from_win32()).into());
+        }
+        // Both values are tick counts that wrap every ~49 days; wrapping_sub keeps the difference correct
+        let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Ok(Duration::from_millis(idle_ms as u64))
+    }
+}
+
+// --- Full-screen Foreground Window ---
+/// Executable name of the foreground window if it covers its whole monitor, else None.
+pub fn get_fullscreen_foreground_app() -> AppResult<Option<String>> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return Ok(None);
+        }
+        let mut window_rect = RECT::default();
+        GetWindowRect(hwnd, &mut window_rect)
+            .map_err(|e| DetectionError::windows_api("GetWindowRect", &e))?;
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut monitor_info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+        if !GetMonitorInfoW(monitor, &mut monitor_info).as_bool() {
+            return Err(DetectionError::windows_api("GetMonitorInfoW", &windows::core::Error::from_win32()).into());
+        }
+        let screen = monitor_info.rcMonitor;
+        let covers_monitor = window_rect.left <= screen.left
+            && window_rect.top <= screen.top
+            && window_rect.right >= screen.right
+            && window_rect.bottom >= screen.bottom;
+        if !covers_monitor {
+            return Ok(None);
+        }
+        let mut process_id: u32 = 0;
+        let thread_id = GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        get_process_executable_name(process_id, thread_id).map(Some)
+    }
+}
+
+// --- Session Lock (WTS session notifications) and Suspend/Resume (power broadcasts) ---
+static SESSION_LOCKED: AtomicBool = AtomicBool::new(false);
+static SYSTEM_SUSPENDED: AtomicBool = AtomicBool::new(false);
+// Unix time of the last PBT_APMSUSPEND (0 = none yet)
+static LAST_SUSPEND: AtomicI64 = AtomicI64::new(0);
+static SESSION_MONITOR: Once = Once::new();
+
+/// Starts (once per process) a background thread that receives session lock/unlock and
+/// suspend/resume notifications through a message-only window.
+pub fn start_session_lock_monitor() {
+    SESSION_MONITOR.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("session-monitor".to_string())
+            .spawn(|| {
+                if let Err(e) = run_session_monitor() {
+                    warn!("Session lock monitoring unavailable: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to spawn session monitor thread: {}", e);
+        }
+    });
+}
+
+/// Whether this session is shown through Remote Desktop or Citrix instead of the local console.
+/// Checked on every poll: a session can be reconnected from the console or from a client at any time.
+pub fn is_remote_session() -> bool {
+    unsafe { GetSystemMetrics(SM_REMOTESESSION) != 0 }
+}
+
+/// Whether the session is currently locked, as last reported by the session monitor.
+pub fn is_session_locked() -> bool {
+    SESSION_LOCKED.load(Ordering::SeqCst)
+}
+
+/// Suspend/resume as last reported by the session monitor.
+pub fn suspend_state() -> SuspendState {
+    let last_suspend = LAST_SUSPEND.load(Ordering::SeqCst);
+    SuspendState {
+        last_suspend: (last_suspend > 0).then_some(last_suspend),
+        suspended: SYSTEM_SUSPENDED.load(Ordering::SeqCst),
+    }
+}
+
+fn run_session_monitor() -> AppResult<()> {
+    unsafe {
+        let instance = GetModuleHandleW(None)
+            .map_err(|e| DetectionError::windows_api("GetModuleHandleW", &e))?;
+        let class_name = w!("MouseTrackingSessionMonitor");
+        let wnd_class = WNDCLASSW {
+            lpfnWndProc: Some(session_window_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&wnd_class) == 0 {
+            return Err(DetectionError::windows_api("RegisterClassW", &windows::core::Error::from_win32()).into());
+        }
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            w!("Mouse Tracking Session Monitor"),
+            WINDOW_STYLE::default(),
+            0, 0, 0, 0,
+            Some(HWND_MESSAGE),
+            None,
+            Some(instance.into()),
+            None,
+        )
+        .map_err(|e| DetectionError::windows_api("CreateWindowExW", &e))?;
+        WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION)
+            .map_err(|e| DetectionError::windows_api("WTSRegisterSessionNotification", &e))?;
+        debug!("Session lock monitor registered.");
+        // Message-only windows get no broadcasts, so power events must be requested explicitly
+        match RegisterSuspendResumeNotification(hwnd.into(), DEVICE_NOTIFY_WINDOW_HANDLE) {
+            Ok(_) => debug!("Suspend/resume notifications registered."),
+            Err(e) => warn!("Suspend/resume detection unavailable: RegisterSuspendResumeNotification failed: {}", e),
+        }
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+        Ok(())
+    }
+}
+
+extern "system" fn session_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if msg == WM_WTSSESSION_CHANGE {
+        match wparam.0 as u32 {
+            WTS_SESSION_LOCK => {
+                debug!("Session locked.");
+                SESSION_LOCKED.store(true, Ordering::SeqCst);
+            }
+            WTS_SESSION_UNLOCK => {
+                debug!("Session unlocked.");
+                SESSION_LOCKED.store(false, Ordering::SeqCst);
+            }
+            _ => {}
+        }
+        return LRESULT(0);
+    }
+    if msg == WM_POWERBROADCAST {
+        match wparam.0 as u32 {
+            PBT_APMSUSPEND => {
+                debug!("System is suspending.");
+                LAST_SUSPEND.store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+                SYSTEM_SUSPENDED.store(true, Ordering::SeqCst);
+            }
+            PBT_APMRESUMEAUTOMATIC | PBT_APMRESUMESUSPEND => {
+                debug!("System resumed.");
+                SYSTEM_SUSPENDED.store(false, Ordering::SeqCst);
+            }
+            _ => {}
+        }
+        return LRESULT(1);
+    }
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
+
+// --- Executable metadata (app_metadata friendly names) ---
+/// Full path of an executable the detector has seen this session, by file name (case-insensitive).
+pub fn executable_path(app_name: &str) -> Option<PathBuf> {
+    EXECUTABLE_PATHS.lock().ok()?.get(&app_name.to_lowercase()).cloned()
+}
+
+/// `FileDescription` from an executable's version resource, e.g. "Microsoft Edge" for msedge.exe.
+pub fn executable_description(path: &Path) -> Option<String> {
+    let wide_path = to_wide(&path.to_string_lossy());
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(wide_path.as_ptr()), None);
+        if size == 0 {
+            return None;
+        }
+        let mut data = vec![0u8; size as usize];
+        GetFileVersionInfoW(PCWSTR(wide_path.as_ptr()), None, size, data.as_mut_ptr().cast()).ok()?;
+
+        // Strings are stored per language/code page; use the first one listed (default: US English, Unicode)
+        let mut buffer: *mut std::ffi::c_void = std::ptr::null_mut();
+        let mut len = 0u32;
+        let language = if VerQueryValueW(data.as_ptr().cast(), w!("\\VarFileInfo\\Translation"), &mut buffer, &mut len).as_bool()
+            && len >= 4
+        {
+            let pair = (buffer as *const [u16; 2]).read_unaligned();
+            format!("{:04x}{:04x}", pair[0], pair[1])
+        } else {
+            "040904b0".to_string()
+        };
+
+        let sub_block = to_wide(&format!("\\StringFileInfo\\{}\\FileDescription", language));
+        if !VerQueryValueW(data.as_ptr().cast(), PCWSTR(sub_block.as_ptr()), &mut buffer, &mut len).as_bool() || len == 0 {
+            return None;
+        }
+        let chars = std::slice::from_raw_parts(buffer as *const u16, len as usize);
+        let description = String::from_utf16_lossy(chars).trim_end_matches('\0').trim().to_string();
+        (!description.is_empty()).then_some(description)
+    }
+}
+
+// --- Autostart (HKCU Run key) ---
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+fn to_wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+pub fn set_run_entry(name: &str, command: &str) -> AppResult<()> {
+    let (key, value_name, data) = (to_wide(RUN_KEY), to_wide(name), to_wide(command));
+    let status = unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(value_name.as_ptr()),
+            REG_SZ.0,
+            Some(data.as_ptr() as *const _),
+            (data.len() * std::mem::size_of::<u16>()) as u32,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return Err(AppError::Platform(format!("RegSetKeyValueW failed: {:?}", status)));
     }
+    Ok(())
 }
+
+/// Returns false if there was no entry to delete.
+pub fn delete_run_entry(name: &str) -> AppResult<bool> {
+    let (key, value_name) = (to_wide(RUN_KEY), to_wide(name));
+    let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, PCWSTR(key.as_ptr()), PCWSTR(value_name.as_ptr())) };
+    match status {
+        ERROR_SUCCESS => Ok(true),
+        ERROR_FILE_NOT_FOUND => Ok(false),
+        other => Err(AppError::Platform(format!("RegDeleteKeyValueW failed: {:?}", other))),
+    }
+}
+
+pub fn get_run_entry(name: &str) -> AppResult<Option<String>> {
+    read_registry_value(RUN_KEY, name, RRF_RT_REG_SZ).map(|data| data.map(|bytes| wide_bytes_to_string(&bytes)))
+}
+
+/// Reads a HKCU registry value as raw bytes; Ok(None) if the key or value does not exist.
+fn read_registry_value(key: &str, value: &str, flags: REG_ROUTINE_FLAGS) -> AppResult<Option<Vec<u8>>> {
+    let (key, value_name) = (to_wide(key), to_wide(value));
+    let mut buf: Vec<u8> = vec![0; 4096];
+    let mut size_bytes = buf.len() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(value_name.as_ptr()),
+            flags,
+            None,
+            Some(buf.as_mut_ptr() as *mut _),
+            Some(&mut size_bytes),
+        )
+    };
+    match status {
+        ERROR_SUCCESS => {
+            buf.truncate(size_bytes as usize);
+            Ok(Some(buf))
+        }
+        ERROR_FILE_NOT_FOUND => Ok(None),
+        other => Err(AppError::Platform(format!("RegGetValueW failed: {:?}", other))),
+    }
+}
+
+/// Decodes a NUL-terminated REG_SZ payload.
+fn wide_bytes_to_string(bytes: &[u8]) -> String {
+    let wide: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len()); // Drop NUL
+    String::from_utf16_lossy(&wide[..len])
+}
+
+// --- Virtual Desktops ---
+const VIRTUAL_DESKTOPS_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Explorer\VirtualDesktops";
+
+thread_local! {
+    // COM objects are per-thread, so each polling thread lazily creates its own manager
+    static DESKTOP_MANAGER: RefCell<Option<Option<IVirtualDesktopManager>>> = const { RefCell::new(None) };
+}
+
+fn create_desktop_manager() -> Option<IVirtualDesktopManager> {
+    unsafe {
+        // S_FALSE (already initialized) is fine; a real failure surfaces from CoCreateInstance
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        match CoCreateInstance(&VirtualDesktopManager, None, CLSCTX_ALL) {
+            Ok(manager) => Some(manager),
+            Err(e) => {
+                warn!("Virtual desktop manager unavailable: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Name of the virtual desktop a top-level window belongs to: the user-assigned name,
+/// else "Desktop N" by position, else the desktop GUID. None if unknown.
+pub fn get_window_desktop_name(hwnd: HWND) -> Option<String> {
+    let desktop_id = DESKTOP_MANAGER.with(|cell| {
+        let mut manager = cell.borrow_mut();
+        let manager = manager.get_or_insert_with(create_desktop_manager).as_ref()?;
+        unsafe { manager.GetWindowDesktopId(hwnd) }.ok()
+    })?;
+    if desktop_id == GUID::zeroed() {
+        return None; // Windows not assigned to a desktop (e.g. shell windows)
+    }
+    Some(desktop_label(&desktop_id))
+}
+
+fn desktop_label(desktop_id: &GUID) -> String {
+    let id_text = format!("{{{:?}}}", desktop_id);
+    let name_key = format!(r"{}\Desktops\{}", VIRTUAL_DESKTOPS_KEY, id_text);
+    if let Ok(Some(bytes)) = read_registry_value(&name_key, "Name", RRF_RT_REG_SZ) {
+        let name = wide_bytes_to_string(&bytes);
+        if !name.is_empty() {
+            return name;
+        }
+    }
+    // VirtualDesktopIDs lists every desktop's GUID (16 raw bytes each) in display order
+    if let Ok(Some(ids)) = read_registry_value(VIRTUAL_DESKTOPS_KEY, "VirtualDesktopIDs", RRF_RT_REG_BINARY) {
+        let mut raw = Vec::with_capacity(16);
+        raw.extend_from_slice(&desktop_id.data1.to_le_bytes());
+        raw.extend_from_slice(&desktop_id.data2.to_le_bytes());
+        raw.extend_from_slice(&desktop_id.data3.to_le_bytes());
+        raw.extend_from_slice(&desktop_id.data4);
+        if let Some(index) = ids.chunks_exact(16).position(|chunk| chunk == raw.as_slice()) {
+            return format!("Desktop {}", index + 1);
+        }
+    }
+    id_text
+}
+
+/// Name of the installed web app (PWA) a browser window belongs to; None for ordinary windows.
+fn web_app_name(hwnd: HWND) -> Option<String> {
+    let store: IPropertyStore = unsafe { SHGetPropertyStoreForWindow(hwnd) }.ok()?;
+    let app_id = property_string(&store, &PKEY_AppUserModel_ID)?;
+    // Chromium browsers (Chrome, Edge, Brave, ...) give each installed app its own id: "<browser>._crx_<app id>"
+    if !app_id.contains("_crx_") {
+        return None;
+    }
+    // The name shown on the taskbar; the id itself when only a resource reference ("@...") is set
+    let name = property_string(&store, &PKEY_AppUserModel_RelaunchDisplayNameResource).filter(|name| !name.starts_with('@'));
+    Some(name.unwrap_or(app_id))
+}
+
+fn property_string(store: &IPropertyStore, key: &PROPERTYKEY) -> Option<String> {
+    unsafe {
+        let mut value = store.GetValue(key).ok()?;
+        let text = PropVariantToBSTR(&value).ok().map(|text| text.to_string()).filter(|text| !text.is_empty());
+        let _ = PropVariantClear(&mut value);
+        text
+    }
+}
+
+// --- Audio sessions ---
+/// Executable names of the apps whose audio sessions on the default output device are making sound
+/// (active with a non-zero peak meter; the system sounds session is left out). Empty without an output device.
+pub fn audio_playing_apps() -> AppResult<Vec<String>> {
+    unsafe {
+        // S_FALSE (already initialized) is fine; a real failure surfaces from CoCreateInstance
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+            .map_err(|e| DetectionError::windows_api("CoCreateInstance(MMDeviceEnumerator)", &e))?;
+        let device = match enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia) {
+            Ok(device) => device,
+            Err(e) => {
+                debug!("No default audio output device: {}", e);
+                return Ok(Vec::new());
+            }
+        };
+        let manager: IAudioSessionManager2 =
+            device.Activate(CLSCTX_ALL, None).map_err(|e| DetectionError::windows_api("IMMDevice::Activate", &e))?;
+        let sessions = manager.GetSessionEnumerator().map_err(|e| DetectionError::windows_api("GetSessionEnumerator", &e))?;
+        let count = sessions.GetCount().map_err(|e| DetectionError::windows_api("IAudioSessionEnumerator::GetCount", &e))?;
+        let mut apps: Vec<String> = Vec::new();
+        for index in 0..count {
+            let Ok(session) = sessions.GetSession(index) else { continue };
+            if !matches!(session.GetState(), Ok(state) if state == AudioSessionStateActive) {
+                continue;
+            }
+            let Ok(control) = session.cast::<IAudioSessionControl2>() else { continue };
+            if control.IsSystemSoundsSession() == S_OK {
+                continue;
+            }
+            // A paused player often keeps its session active, but silent
+            let audible = session.cast::<IAudioMeterInformation>().and_then(|meter| meter.GetPeakValue()).is_ok_and(|peak| peak > 0.0);
+            let Ok(process_id) = control.GetProcessId() else { continue };
+            if !audible || process_id == 0 {
+                continue;
+            }
+            if let Some(name) = process_executable_name(process_id)
+                && !apps.contains(&name)
+            {
+                apps.push(name);
+            }
+        }
+        Ok(apps)
+    }
+}
+
+/// Executable name of a process without a window (audio sessions only know the PID).
+unsafe fn process_executable_name(process_id: u32) -> Option<String> {
+    match unsafe { module_file_name(process_id) }.or_else(|_| unsafe { limited_image_name(process_id) }) {
+        Ok(path) => Some(record_executable(&path)),
+        Err(_) => wmi_process(process_id).map(|(name, _)| name),
+    }
+}
+
+/// Detaches from the console window so a login-started tracker runs without one.
+pub fn detach_console() {
+    if let Err(e) = unsafe { FreeConsole() } {
+        warn!("FreeConsole failed: {}", e);
+    }
+}
+
 // --- Helper Function to Get Title for a specific HWND ---
 unsafe fn get_hwnd_title(hwnd: HWND) -> String {
     let mut title_buf: Vec<u16> = vec![0; MAX_TITLE_LENGTH];
@@ -158,38 +763,169 @@ unsafe fn get_hwnd_title(hwnd: HWND) -> String {
 
 // --- Helper Function to Get Process Executable Name ---
 unsafe fn get_process_executable_name(process_id: u32, thread_id: u32) -> AppResult<String> {
+    unsafe { get_process_executable(process_id, thread_id) }.map(|(name, _)| name)
+}
+
+// Elevated processes refuse PROCESS_VM_READ to a non-elevated tracker, so the module path is tried first,
+// then the image name (PROCESS_QUERY_LIMITED_INFORMATION is granted for most of them), then WMI.
+// Returns the file name and, when it could be read, the full path.
+unsafe fn get_process_executable(process_id: u32, thread_id: u32) -> AppResult<(String, Option<PathBuf>)> {
     if thread_id == 0 || process_id == 0 {
         let win_err = windows::core::Error::from_win32();
         warn!("Could not get valid PID/ThreadID: {:?}", win_err);
-        Ok(format!("[System Process or No PID: {:?}]", win_err))
-    } else {
-        match OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, process_id) {
-            Ok(process_handle) => {
-                struct HandleGuard(HANDLE);
-                impl Drop for HandleGuard { fn drop(&mut self) { if !self.0.is_invalid() { let _ = unsafe { CloseHandle(self.0) }; } } }
-                let _handle_guard = HandleGuard(process_handle);
-
-                let mut exe_path_buf: Vec<u16> = vec![0; MAX_PATH as usize];
-                let path_len = GetModuleFileNameExW(Some(process_handle), None, &mut exe_path_buf);
-
-                if path_len == 0 {
-                    let win_err = windows::core::Error::from_win32();
-                    warn!("GetModuleFileNameExW failed for PID {}: {:?}", process_id, win_err);
-                    Ok(format!("[Unknown Path PID {} - Detail: {:?}]", process_id, win_err))
-                } else {
-                    let os_string = OsString::from_wide(&exe_path_buf[..path_len as usize]);
-                    if let Some(path_str) = os_string.to_str() {
-                        Ok(Path::new(path_str).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "[Invalid Path]".to_string()))
-                    } else {
-                         warn!("Executable path for PID {} is not valid UTF-8.", process_id);
-                        Ok("[Non-UTF8 Path]".to_string())
-                    }
+        return Ok((format!("[System Process or No PID: {:?}]", win_err), None));
+    }
+    let path = unsafe { module_file_name(process_id) }.or_else(|e| {
+        debug!("GetModuleFileNameExW failed for PID {}: {}", process_id, e);
+        unsafe { limited_image_name(process_id) }
+    });
+    match path {
+        Ok(path) => Ok((record_executable(&path), Some(path))),
+        Err(e) => {
+            debug!("QueryFullProcessImageNameW failed for PID {}: {}", process_id, e);
+            match wmi_process(process_id) {
+                Some((_, Some(path))) => Ok((record_executable(&path), Some(path))),
+                Some((name, None)) => Ok((name, None)),
+                None => {
+                    warn!("No executable name for PID {}: access denied to every query.", process_id);
+                    // One placeholder for all such processes rather than one app per PID
+                    Ok(("[Access Denied]".to_string(), None))
                 }
             }
-            Err(e) => {
-                warn!("OpenProcess failed for PID {}: {}", process_id, e);
-                 Ok(format!("[Access Denied/Error PID {} - Detail: {:?}]", process_id, e))
-            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Remembers the full path for `executable_path` and returns its file name.
+fn record_executable(path: &Path) -> String {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy().into_owned()) else {
+        return "[Invalid Path]".to_string();
+    };
+    if let Ok(mut paths) = EXECUTABLE_PATHS.lock() {
+        paths.entry(name.to_lowercase()).or_insert_with(|| path.to_path_buf());
+    }
+    name
+}
+
+/// Closes a process handle when dropped.
+struct HandleGuard(HANDLE);
+
+impl Drop for HandleGuard {
+    fn drop(&mut self) {
+        if !self.0.is_invalid() {
+            let _ = unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+unsafe fn module_file_name(process_id: u32) -> windows::core::Result<PathBuf> {
+    unsafe {
+        let process = HandleGuard(OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, process_id)?);
+        let mut path_buf: Vec<u16> = vec![0; MAX_PATH as usize];
+        let path_len = GetModuleFileNameExW(Some(process.0), None, &mut path_buf);
+        if path_len == 0 {
+            return Err(windows::core::Error::from_win32());
+        }
+        Ok(PathBuf::from(OsString::from_wide(&path_buf[..path_len as usize])))
+    }
+}
+
+unsafe fn limited_image_name(process_id: u32) -> windows::core::Result<PathBuf> {
+    unsafe {
+        let process = HandleGuard(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id)?);
+        // Unlike GetModuleFileNameExW this is not limited to MAX_PATH
+        let mut path_buf: Vec<u16> = vec![0; 1024];
+        let mut path_len = path_buf.len() as u32;
+        QueryFullProcessImageNameW(process.0, PROCESS_NAME_WIN32, PWSTR(path_buf.as_mut_ptr()), &mut path_len)?;
+        Ok(PathBuf::from(OsString::from_wide(&path_buf[..path_len as usize])))
+    }
+}
+
+/// How long a WMI process lookup may take before the placeholder is used.
+const WMI_TIMEOUT_MS: i32 = 2000;
+
+thread_local! {
+    // Like DESKTOP_MANAGER: connected on first use by each polling thread, None if WMI is unavailable
+    static WMI_SERVICES: RefCell<Option<Option<IWbemServices>>> = const { RefCell::new(None) };
+}
+
+fn connect_wmi() -> Option<IWbemServices> {
+    let connect = || unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let locator: IWbemLocator = CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER)?;
+        let services = locator.ConnectServer(&BSTR::from(r"ROOT\CIMV2"), &BSTR::new(), &BSTR::new(), &BSTR::new(), 0, &BSTR::new(), None)?;
+        CoSetProxyBlanket(&services, RPC_C_AUTHN_WINNT, RPC_C_AUTHZ_NONE, PCWSTR::null(), RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE, None, EOAC_NONE)?;
+        Ok::<_, windows::core::Error>(services)
+    };
+    match connect() {
+        Ok(services) => Some(services),
+        Err(e) => {
+            warn!("WMI unavailable, access-denied processes stay unnamed: {}", e);
+            None
+        }
+    }
+}
+
+/// Name and (when readable) executable path of a process from `Win32_Process`.
+fn wmi_process(process_id: u32) -> Option<(String, Option<PathBuf>)> {
+    let process = wmi_process_object(process_id, "Name, ExecutablePath")?;
+    let name = wmi_string(&process, w!("Name"))?;
+    let path = wmi_string(&process, w!("ExecutablePath")).map(PathBuf::from);
+    Some((name, path))
+}
+
+/// The `Win32_Process` row of a process with the given comma-separated properties.
+fn wmi_process_object(process_id: u32, properties: &str) -> Option<IWbemClassObject> {
+    let services = WMI_SERVICES.with(|cell| cell.borrow_mut().get_or_insert_with(connect_wmi).clone())?;
+    let query = BSTR::from(format!("SELECT {} FROM Win32_Process WHERE ProcessId = {}", properties, process_id));
+    let flags = WBEM_GENERIC_FLAG_TYPE(WBEM_FLAG_FORWARD_ONLY.0 | WBEM_FLAG_RETURN_IMMEDIATELY.0);
+    let results = match unsafe { services.ExecQuery(&BSTR::from("WQL"), &query, flags, None) } {
+        Ok(results) => results,
+        Err(e) => {
+            debug!("WMI query for PID {} failed: {}", process_id, e);
+            return None;
+        }
+    };
+    let mut objects = [None];
+    let mut returned = 0;
+    unsafe { results.Next(WMI_TIMEOUT_MS, &mut objects, &mut returned) }.ok().ok()?;
+    objects[0].take().filter(|_| returned == 1)
+}
+
+thread_local! {
+    // A process keeps its command line, so each is queried once: (pid, app name) -> command line
+    static COMMAND_LINES: RefCell<HashMap<(u32, String), Option<String>>> = RefCell::new(HashMap::new());
+}
+
+/// Processes whose command lines are remembered before the cache is cleared.
+const MAX_CACHED_COMMAND_LINES: usize = 256;
+
+/// Command line of a running process (for `record_command_line`), None if WMI cannot read it.
+/// `app_name` tells a reused PID apart from the process it was cached for.
+pub fn process_command_line(process_id: u32, app_name: &str) -> Option<String> {
+    let key = (process_id, app_name.to_string());
+    if let Some(cached) = COMMAND_LINES.with(|cache| cache.borrow().get(&key).cloned()) {
+        return cached;
+    }
+    let command_line = wmi_process_object(process_id, "CommandLine").and_then(|process| wmi_string(&process, w!("CommandLine")));
+    COMMAND_LINES.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= MAX_CACHED_COMMAND_LINES {
+            cache.clear();
+        }
+        cache.insert(key, command_line.clone());
+    });
+    command_line
+}
+
+fn wmi_string(object: &IWbemClassObject, property: PCWSTR) -> Option<String> {
+    unsafe {
+        let mut value = VARIANT::default();
+        object.Get(property, 0, &mut value, None, None).ok()?;
+        // ExecutablePath and CommandLine are NULL for processes WMI cannot open either
+        let inner = &value.Anonymous.Anonymous;
+        let text = (inner.vt == VT_BSTR).then(|| inner.Anonymous.bstrVal.to_string()).filter(|text| !text.is_empty());
+        let _ = VariantClear(&mut value);
+        text
+    }
+}